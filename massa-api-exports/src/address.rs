@@ -34,6 +34,11 @@ pub struct AddressInfo {
     /// deferred credits
     pub deferred_credits: Vec<SlotAmount>,
 
+    /// final balance split between spendable and locked coins
+    pub final_balance_breakdown: BalanceBreakdown,
+    /// candidate balance split between spendable and locked coins
+    pub candidate_balance_breakdown: BalanceBreakdown,
+
     /// next block draws
     pub next_block_draws: Vec<Slot>,
     /// next endorsement draws
@@ -58,6 +63,11 @@ impl std::fmt::Display for AddressInfo {
             "\tBalance: final={}, candidate={}",
             self.final_balance, self.candidate_balance
         )?;
+        writeln!(
+            f,
+            "\tTotal balance (including locked coins): final={}, candidate={}",
+            self.final_balance_breakdown.total, self.candidate_balance_breakdown.total
+        )?;
         writeln!(
             f,
             "\tRolls: final={}, candidate={}",
@@ -116,6 +126,45 @@ impl AddressInfo {
             candidate_rolls: self.candidate_roll_count,
             final_balance: self.final_balance,
             candidate_balance: self.candidate_balance,
+            final_total_balance: self.final_balance_breakdown.total,
+            candidate_total_balance: self.candidate_balance_breakdown.total,
+        }
+    }
+}
+
+/// Coins owned by an address, split between spendable and locked ones.
+///
+/// Buying rolls converts coins into rolls and selling them credits the coins back
+/// only after a few cycles: those coins do not show in the spendable balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BalanceBreakdown {
+    /// coins that can be spent right away (ledger balance)
+    pub spendable: Amount,
+    /// value of the owned rolls, at roll price
+    pub in_rolls: Amount,
+    /// coins of sold rolls that are not credited yet
+    pub deferred: Amount,
+    /// sum of the spendable and locked coins
+    pub total: Amount,
+}
+
+impl BalanceBreakdown {
+    /// Computes the breakdown of the coins of an address
+    pub fn new(
+        spendable: Amount,
+        roll_count: u64,
+        roll_price: Amount,
+        deferred_credits: &[SlotAmount],
+    ) -> Self {
+        let in_rolls = roll_price.saturating_mul_u64(roll_count);
+        let deferred = deferred_credits.iter().fold(Amount::zero(), |acc, credit| {
+            acc.saturating_add(credit.amount)
+        });
+        BalanceBreakdown {
+            spendable,
+            in_rolls,
+            deferred,
+            total: spendable.saturating_add(in_rolls).saturating_add(deferred),
         }
     }
 }
@@ -137,6 +186,10 @@ pub struct CompactAddressInfo {
     pub final_balance: Amount,
     /// candidate balance
     pub candidate_balance: Amount,
+    /// final balance including the locked coins
+    pub final_total_balance: Amount,
+    /// candidate balance including the locked coins
+    pub candidate_total_balance: Amount,
 }
 
 impl std::fmt::Display for CompactAddressInfo {
@@ -147,6 +200,11 @@ impl std::fmt::Display for CompactAddressInfo {
             "\tBalance: final={}, candidate={}",
             self.final_balance, self.candidate_balance
         )?;
+        writeln!(
+            f,
+            "\tTotal balance (including locked coins): final={}, candidate={}",
+            self.final_total_balance, self.candidate_total_balance
+        )?;
         writeln!(
             f,
             "\tRolls: active={}, final={}, candidate={}",
@@ -177,3 +235,12 @@ impl std::fmt::Display for AddressFilter {
         }
     }
 }
+
+/// request for the rewards earned by an address on a set of cycles
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct RewardsRequest {
+    /// Address
+    pub address: Address,
+    /// cycles to get the rewards of
+    pub cycles: Vec<u64>,
+}
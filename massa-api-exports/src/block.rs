@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_models::{address::Address, block::Block, block_id::BlockId, slot::Slot};
+use massa_models::{address::Address, amount::Amount, block::Block, block_id::BlockId, slot::Slot};
 
 use serde::{Deserialize, Serialize};
 
@@ -88,3 +88,43 @@ impl std::fmt::Display for BlockSummary {
         Ok(())
     }
 }
+
+/// Load of the network, aggregated over the blockclique blocks of the latest periods
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NetworkLoad {
+    /// first slot of the aggregated window
+    pub start_slot: Slot,
+    /// last slot of the aggregated window (included)
+    pub end_slot: Slot,
+    /// number of slots in the window
+    pub slot_count: u64,
+    /// number of blockclique blocks in the window (the other slots were missed)
+    pub block_count: u64,
+    /// number of operations included in these blocks
+    pub operation_count: u64,
+    /// average ratio of the operation bytes of a block to the maximum block size
+    pub size_usage: f64,
+    /// average ratio of the gas of a block to the maximum gas per block
+    pub gas_usage: f64,
+    /// average fee of the included operations
+    pub average_fee: Amount,
+    /// overall load, between 0 (empty blocks) and 1 (full blocks): the highest of `size_usage` and `gas_usage`
+    pub load: f64,
+}
+
+impl std::fmt::Display for NetworkLoad {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Slots: {} to {}", self.start_slot, self.end_slot)?;
+        writeln!(
+            f,
+            "Blocks: {} out of {} slots",
+            self.block_count, self.slot_count
+        )?;
+        writeln!(f, "Operations: {}", self.operation_count)?;
+        writeln!(f, "Size usage: {:.2}%", self.size_usage * 100.0)?;
+        writeln!(f, "Gas usage: {:.2}%", self.gas_usage * 100.0)?;
+        writeln!(f, "Average fee: {}", self.average_fee)?;
+        writeln!(f, "Load: {:.2}%", self.load * 100.0)?;
+        Ok(())
+    }
+}
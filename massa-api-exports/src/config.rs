@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::amount::Amount;
+use massa_models::config::ChainParametersSchedule;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use std::net::SocketAddr;
@@ -60,10 +61,6 @@ pub struct APIConfig {
     pub max_function_name_length: u16,
     /// max parameter size
     pub max_parameter_size: u32,
-    /// max gas in a block
-    pub max_gas_per_block: u64,
-    /// base gas used by any operation
-    pub base_operation_gas_cost: u64,
     /// Amount required for a SinglePass compilation (ExecuteSC operation)
     pub sp_compilation_cost: u64,
     /// thread count
@@ -84,4 +81,13 @@ pub struct APIConfig {
     pub deferred_credits_delta: MassaTime,
     /// minimal fees to include an operation in a block
     pub minimal_fees: Amount,
+    /// price of a roll, used to value the coins locked in rolls
+    pub roll_price: Amount,
+    /// chain parameters of each protocol version
+    #[serde(skip)]
+    pub chain_parameters: ChainParametersSchedule,
+    /// number of periods over which the network load is aggregated
+    pub network_load_periods: u64,
+    /// number of finalized slots whose ledger changes are kept to resume ledger change streams
+    pub final_history_length: usize,
 }
@@ -1,7 +1,10 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use std::collections::BTreeMap;
+
 use displaydoc::Display;
 use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
+use serde::{Deserialize, Serialize};
 
 use massa_hash::MassaHashError;
 use massa_models::error::ModelsError;
@@ -49,10 +52,40 @@ pub enum ApiError {
     FactoryError(#[from] FactoryError),
 }
 
-impl From<ApiError> for ErrorObjectOwned {
-    fn from(err: ApiError) -> Self {
-        // JSON-RPC Server errors codes must be between -32099 to -32000
-        let code = match err {
+/// Category of an API error, telling the client how to react to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorCategory {
+    /// the request is invalid and fails the same way if sent again
+    InvalidRequest,
+    /// the requested item is not known by the node
+    NotFound,
+    /// the node cannot serve the request for now
+    Unavailable,
+    /// the endpoint is disabled or not served by this API
+    Unsupported,
+    /// the node failed to process the request
+    Internal,
+}
+
+/// Machine-readable description of an API error, sent as the `data` of the JSON-RPC error object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiErrorData {
+    /// stable numeric code of the error, also used as the JSON-RPC error code
+    pub code: i32,
+    /// category of the error
+    pub category: ApiErrorCategory,
+    /// true if the same request may succeed when sent again later
+    pub retryable: bool,
+    /// additional information about the error
+    pub details: BTreeMap<String, String>,
+}
+
+impl ApiError {
+    /// Stable numeric code of the error.
+    /// JSON-RPC Server errors codes must be between -32099 to -32000
+    pub fn code(&self) -> i32 {
+        match self {
             ApiError::BadRequest(_) => -32000,
             ApiError::InternalServerError(_) => -32001,
             ApiError::NotFound => -32004,
@@ -70,8 +103,114 @@ impl From<ApiError> for ErrorObjectOwned {
             ApiError::MissingConfig(_) => -32018,
             ApiError::WrongAPI => -32019,
             ApiError::FactoryError(_) => -32020,
+        }
+    }
+
+    /// Category of the error
+    pub fn category(&self) -> ApiErrorCategory {
+        match self {
+            ApiError::BadRequest(_)
+            | ApiError::MassaHashError(_)
+            | ApiError::ModelsError(_)
+            | ApiError::WalletError(_) => ApiErrorCategory::InvalidRequest,
+            ApiError::NotFound => ApiErrorCategory::NotFound,
+            ApiError::SendChannelError(_)
+            | ApiError::ReceiveChannelError(_)
+            | ApiError::ConsensusError(_)
+            | ApiError::ProtocolError(_) => ApiErrorCategory::Unavailable,
+            ApiError::MissingCommandSender(_) | ApiError::MissingConfig(_) | ApiError::WrongAPI => {
+                ApiErrorCategory::Unsupported
+            }
+            ApiError::InternalServerError(_)
+            | ApiError::ExecutionError(_)
+            | ApiError::TimeError(_)
+            | ApiError::InconsistencyError(_)
+            | ApiError::FactoryError(_) => ApiErrorCategory::Internal,
+        }
+    }
+
+    /// Returns true if the same request may succeed when sent again later:
+    /// the node was busy or one of its components could not be reached
+    pub fn is_retryable(&self) -> bool {
+        match self.category() {
+            ApiErrorCategory::Unavailable => true,
+            ApiErrorCategory::Internal => matches!(self, ApiError::InternalServerError(_)),
+            ApiErrorCategory::InvalidRequest
+            | ApiErrorCategory::NotFound
+            | ApiErrorCategory::Unsupported => false,
+        }
+    }
+
+    /// Machine-readable description of the error
+    pub fn data(&self) -> ApiErrorData {
+        let mut details = BTreeMap::new();
+        let (component, reason) = match self {
+            ApiError::SendChannelError(reason) | ApiError::ReceiveChannelError(reason) => {
+                (None, Some(reason.clone()))
+            }
+            ApiError::MassaHashError(err) => (Some("hash"), Some(err.to_string())),
+            ApiError::ConsensusError(reason) => (Some("consensus"), Some(reason.clone())),
+            ApiError::ExecutionError(reason) => (Some("execution"), Some(reason.clone())),
+            ApiError::ProtocolError(reason) => (Some("protocol"), Some(reason.clone())),
+            ApiError::ModelsError(err) => (Some("models"), Some(err.to_string())),
+            ApiError::TimeError(err) => (Some("time"), Some(err.to_string())),
+            ApiError::WalletError(err) => (Some("wallet"), Some(err.to_string())),
+            ApiError::FactoryError(err) => (Some("versioning"), Some(err.to_string())),
+            ApiError::InconsistencyError(reason)
+            | ApiError::MissingCommandSender(reason)
+            | ApiError::MissingConfig(reason)
+            | ApiError::BadRequest(reason)
+            | ApiError::InternalServerError(reason) => (None, Some(reason.clone())),
+            ApiError::NotFound | ApiError::WrongAPI => (None, None),
         };
+        if let Some(component) = component {
+            details.insert("component".to_string(), component.to_string());
+        }
+        if let Some(reason) = reason {
+            details.insert("reason".to_string(), reason);
+        }
+        ApiErrorData {
+            code: self.code(),
+            category: self.category(),
+            retryable: self.is_retryable(),
+            details,
+        }
+    }
+}
+
+impl From<ApiError> for ErrorObjectOwned {
+    fn from(err: ApiError) -> Self {
+        ErrorObject::owned(err.code(), err.to_string(), Some(err.data()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiError, ApiErrorCategory};
+    use jsonrpsee::types::ErrorObjectOwned;
+
+    #[test]
+    fn test_api_error_data() {
+        let err: ErrorObjectOwned = ApiError::ProtocolError("channel closed".to_string()).into();
+        assert_eq!(err.code(), -32012);
+        assert_eq!(err.message(), "Protocol error: channel closed");
+        assert!(err.data().is_some());
+
+        let data = ApiError::ProtocolError("channel closed".to_string()).data();
+        assert_eq!(data.code, -32012);
+        assert_eq!(data.category, ApiErrorCategory::Unavailable);
+        assert!(data.retryable);
+        assert_eq!(data.details["component"], "protocol");
+        assert_eq!(data.details["reason"], "channel closed");
+
+        let data = ApiError::BadRequest("invalid fee".to_string()).data();
+        assert_eq!(data.category, ApiErrorCategory::InvalidRequest);
+        assert!(!data.retryable);
+        assert!(!data.details.contains_key("component"));
 
-        ErrorObject::owned(code, err.to_string(), None::<()>)
+        let data = ApiError::NotFound.data();
+        assert_eq!(data.category, ApiErrorCategory::NotFound);
+        assert!(!data.retryable);
+        assert!(data.details.is_empty());
     }
 }
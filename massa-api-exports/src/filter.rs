@@ -0,0 +1,211 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::ops::Bound;
+
+use massa_models::{
+    address::Address, amount::Amount, config::NAME_REGISTRATION_COST, operation::OperationType,
+    slot::Slot,
+};
+use serde::{Deserialize, Serialize};
+
+/// Role of an address in an operation or an event
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum AddressRole {
+    /// creator of an operation (who signs it and pays its fee), original caller of an event
+    Sender,
+    /// recipient of a transaction, target of a smart contract call or new owner of a name,
+    /// emitter of an event
+    Recipient,
+    /// creator of an operation, emitter of an event
+    Creator,
+}
+
+/// Kind of an operation
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    /// coin transfer
+    Transaction,
+    /// roll purchase
+    RollBuy,
+    /// roll sale
+    RollSell,
+    /// smart contract execution
+    ExecuteSC,
+    /// smart contract call
+    CallSC,
+    /// name registration or renewal
+    RegisterName,
+    /// change of the target of a name
+    SetNameTarget,
+    /// change of the owner of a name
+    TransferName,
+}
+
+impl From<&OperationType> for OperationKind {
+    fn from(op: &OperationType) -> Self {
+        match op {
+            OperationType::Transaction { .. } => OperationKind::Transaction,
+            OperationType::RollBuy { .. } => OperationKind::RollBuy,
+            OperationType::RollSell { .. } => OperationKind::RollSell,
+            OperationType::ExecuteSC { .. } => OperationKind::ExecuteSC,
+            OperationType::CallSC { .. } => OperationKind::CallSC,
+            OperationType::RegisterName { .. } => OperationKind::RegisterName,
+            OperationType::SetNameTarget { .. } => OperationKind::SetNameTarget,
+            OperationType::TransferName { .. } => OperationKind::TransferName,
+        }
+    }
+}
+
+/// Filter accepted by the operation and event queries: every set criterion must be met.
+///
+/// Events are matched on their execution context, and on the operation that emitted them
+/// for `operation_types` and `min_amount`.
+#[derive(Default, Debug, Deserialize, Serialize, Clone)]
+pub struct QueryFilter {
+    /// address involved in the operation or event
+    pub address: Option<Address>,
+    /// role of `address`, any role if none
+    pub role: Option<AddressRole>,
+    /// optional start slot (included)
+    pub start: Option<Slot>,
+    /// optional end slot (excluded)
+    pub end: Option<Slot>,
+    /// accepted operation kinds, any kind if none
+    pub operation_types: Option<Vec<OperationKind>>,
+    /// minimal amount of coins moved by the operation: transferred amount, coins sent to a call
+    /// maximal coins of an execution or cost of a name registration.
+    /// Roll operations and the other name operations never match.
+    pub min_amount: Option<Amount>,
+    /// optional finality: `Some(true)` for final only, `Some(false)` for candidate only
+    pub is_final: Option<bool>,
+}
+
+impl QueryFilter {
+    /// Slot range of the filter
+    pub fn slot_range(&self) -> (Bound<Slot>, Bound<Slot>) {
+        (
+            self.start.map_or(Bound::Unbounded, Bound::Included),
+            self.end.map_or(Bound::Unbounded, Bound::Excluded),
+        )
+    }
+
+    /// Returns true if the filter restricts the slot range
+    pub fn has_slot_range(&self) -> bool {
+        self.start.is_some() || self.end.is_some()
+    }
+
+    /// Returns true if `slot` is in the slot range of the filter
+    pub fn matches_slot(&self, slot: &Slot) -> bool {
+        self.start.map_or(true, |start| *slot >= start) && self.end.map_or(true, |end| *slot < end)
+    }
+
+    /// Checks the address criteria on an operation created by `creator`
+    pub fn matches_operation_address(&self, creator: &Address, op: &OperationType) -> bool {
+        let Some(address) = &self.address else {
+            return true;
+        };
+        let is_creator = creator == address;
+        let is_recipient = match op {
+            OperationType::Transaction {
+                recipient_address, ..
+            } => recipient_address == address,
+            OperationType::CallSC { target_addr, .. } => target_addr == address,
+            OperationType::TransferName { new_owner, .. } => new_owner == address,
+            _ => false,
+        };
+        match self.role {
+            None => is_creator || is_recipient,
+            Some(AddressRole::Sender) | Some(AddressRole::Creator) => is_creator,
+            Some(AddressRole::Recipient) => is_recipient,
+        }
+    }
+
+    /// Checks the operation kind and amount criteria on an operation
+    pub fn matches_operation_content(&self, op: &OperationType) -> bool {
+        if let Some(kinds) = &self.operation_types {
+            if !kinds.contains(&OperationKind::from(op)) {
+                return false;
+            }
+        }
+        if let Some(min_amount) = self.min_amount {
+            let amount = match op {
+                OperationType::Transaction { amount, .. } => *amount,
+                OperationType::CallSC { coins, .. } => *coins,
+                OperationType::ExecuteSC { max_coins, .. } => *max_coins,
+                OperationType::RegisterName { .. } => NAME_REGISTRATION_COST,
+                OperationType::RollBuy { .. }
+                | OperationType::RollSell { .. }
+                | OperationType::SetNameTarget { .. }
+                | OperationType::TransferName { .. } => return false,
+            };
+            if amount < min_amount {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns true if the filter has criteria on the content of the operations
+    pub fn filters_operation_content(&self) -> bool {
+        self.operation_types.is_some() || self.min_amount.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    fn address() -> Address {
+        Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_operation_filter() {
+        let sender = address();
+        let recipient = address();
+        let transaction = OperationType::Transaction {
+            recipient_address: recipient,
+            amount: Amount::from_str("10").unwrap(),
+        };
+        let roll_buy = OperationType::RollBuy { roll_count: 1 };
+
+        let filter = QueryFilter {
+            address: Some(recipient),
+            ..Default::default()
+        };
+        assert!(filter.matches_operation_address(&sender, &transaction));
+        assert!(!filter.matches_operation_address(&sender, &roll_buy));
+
+        let filter = QueryFilter {
+            address: Some(recipient),
+            role: Some(AddressRole::Sender),
+            ..Default::default()
+        };
+        assert!(!filter.matches_operation_address(&sender, &transaction));
+
+        let filter = QueryFilter {
+            operation_types: Some(vec![OperationKind::Transaction]),
+            min_amount: Some(Amount::from_str("5").unwrap()),
+            ..Default::default()
+        };
+        assert!(filter.matches_operation_content(&transaction));
+        assert!(!filter.matches_operation_content(&roll_buy));
+
+        let filter = QueryFilter {
+            min_amount: Some(Amount::from_str("20").unwrap()),
+            ..Default::default()
+        };
+        assert!(!filter.matches_operation_content(&transaction));
+
+        let filter = QueryFilter {
+            start: Some(Slot::new(2, 0)),
+            end: Some(Slot::new(4, 0)),
+            ..Default::default()
+        };
+        assert!(filter.matches_slot(&Slot::new(2, 0)));
+        assert!(filter.matches_slot(&Slot::new(3, 5)));
+        assert!(!filter.matches_slot(&Slot::new(4, 0)));
+    }
+}
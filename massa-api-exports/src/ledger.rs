@@ -1,7 +1,10 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_ledger_exports::LedgerChanges;
 use massa_models::amount::Amount;
+use massa_models::block_id::BlockId;
 use massa_models::ledger::LedgerData;
+use massa_models::slot::Slot;
 
 use serde::{Deserialize, Serialize};
 
@@ -28,3 +31,14 @@ impl std::fmt::Display for LedgerInfo {
         Ok(())
     }
 }
+
+/// Ledger changes of a finalized slot
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SlotLedgerChanges {
+    /// finalized slot
+    pub slot: Slot,
+    /// block of the slot, none for a miss
+    pub block_id: Option<BlockId>,
+    /// ledger entries created, updated or deleted at this slot
+    pub ledger_changes: LedgerChanges,
+}
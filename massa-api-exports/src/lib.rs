@@ -23,6 +23,8 @@ pub mod endorsement;
 pub mod error;
 /// execution
 pub mod execution;
+/// operation and event query filter
+pub mod filter;
 /// ledger structures
 pub mod ledger;
 /// node related structure
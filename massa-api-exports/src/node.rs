@@ -1,13 +1,16 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_models::address::Address;
 use massa_models::amount::Amount;
+use massa_models::block_id::BlockId;
+use massa_models::endorsement::EndorsementId;
 use massa_models::node::NodeId;
 use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats};
 use massa_models::{config::CompactConfig, slot::Slot, version::Version};
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 
 /// node status
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -28,10 +31,29 @@ pub struct NodeStatus {
     pub next_cycle_time: MassaTime,
     /// connected nodes (node id, ip address, true if the connection is outgoing, false if incoming)
     pub connected_nodes: BTreeMap<NodeId, (IpAddr, bool)>,
+    /// version and user agent announced by the connected nodes during the handshake
+    #[serde(default)]
+    pub connected_nodes_agents: BTreeMap<NodeId, NodeAgent>,
+    /// round trip latency to the connected nodes that answered the keepalive pings
+    #[serde(default)]
+    pub connected_nodes_latencies: BTreeMap<NodeId, NodeLatency>,
+    /// true if the node is in maintenance mode: it does not produce blocks nor endorsements
+    #[serde(default)]
+    pub maintenance_mode: bool,
+    /// warnings about wire features supported by a majority of the connected nodes but not used by this node
+    #[serde(default)]
+    pub protocol_feature_warnings: Vec<String>,
+    /// warning raised when the blocks finalized recently were first sent by a tiny subset of peers:
+    /// the node may be eclipsed or on the minority side of a network split
+    #[serde(default)]
+    pub net_split_warning: Option<String>,
     /// latest slot, none if now is before genesis timestamp
     pub last_slot: Option<Slot>,
     /// next slot
     pub next_slot: Slot,
+    /// version of the chain parameters in force at the next slot
+    #[serde(default)]
+    pub chain_parameters_version: u32,
     /// consensus stats
     pub consensus_stats: ConsensusStats,
     /// pool stats (operation count and endorsement count)
@@ -48,8 +70,229 @@ pub struct NodeStatus {
     pub minimal_fees: Amount,
 }
 
+/// what a ban kept across node restarts applies to
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeBanTarget {
+    /// a node, whatever its address
+    NodeId(NodeId),
+    /// all the addresses of a subnet written `address/prefix_length`, or a single address
+    Subnet(String),
+}
+
+/// ban kept across node restarts
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodeBan {
+    /// banned node or subnet
+    pub target: NodeBanTarget,
+    /// time at which the ban is lifted, none for a permanent ban
+    pub expires_at: Option<MassaTime>,
+}
+
+/// bans to add
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NodeBanRequest {
+    /// banned nodes and subnets
+    pub targets: Vec<NodeBanTarget>,
+    /// duration of the bans, none for permanent bans
+    pub duration: Option<MassaTime>,
+}
+
+/// what the node remembers of another node across restarts
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodePeerRecord {
+    /// node
+    pub node_id: NodeId,
+    /// listeners announced by the node, or set by the node operator
+    pub listeners: Vec<SocketAddr>,
+    /// time at which the node was first recorded
+    pub first_seen: MassaTime,
+    /// last time the node was seen connected, none if it never was
+    pub last_seen: Option<MassaTime>,
+    /// total time the node stayed connected
+    pub uptime: MassaTime,
+    /// number of successful handshakes with the node
+    pub connections: u64,
+    /// number of failed handshakes with the node
+    pub handshake_failures: u64,
+    /// times at which the node was banned, the latest last
+    pub bans: Vec<MassaTime>,
+    /// software announced by the node during the last handshake
+    pub agent: Option<NodeAgent>,
+    /// whether the node is dialed first and never forgotten
+    pub pinned: bool,
+    /// score ranking the node for the outbound connections, the higher the better
+    pub reputation: i64,
+}
+
+/// change of the record of a node
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodePeerRecordEdit {
+    /// node, recorded if it is not known yet
+    pub node_id: NodeId,
+    /// listeners replacing the known ones, if any
+    #[serde(default)]
+    pub listeners: Option<Vec<SocketAddr>>,
+    /// whether the node is pinned, unchanged if none
+    #[serde(default)]
+    pub pinned: Option<bool>,
+    /// forget the handshake failures and the bans of the node
+    #[serde(default)]
+    pub reset_reputation: bool,
+}
+
+/// version and user agent announced by a node
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodeAgent {
+    /// node version
+    pub version: Version,
+    /// user agent, none if the node did not send one
+    pub user_agent: Option<String>,
+}
+
+impl std::fmt::Display for NodeAgent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.user_agent {
+            Some(user_agent) => write!(f, "{} ({})", self.version, user_agent),
+            None => write!(f, "{}", self.version),
+        }
+    }
+}
+
+/// round trip latency to a node, measured with keepalive pings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodeLatency {
+    /// smoothed round trip time
+    pub rtt: MassaTime,
+    /// smoothed deviation of the round trip time
+    pub jitter: MassaTime,
+}
+
+impl std::fmt::Display for NodeLatency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ms (jitter {} ms)",
+            self.rtt.as_millis(),
+            self.jitter.as_millis()
+        )
+    }
+}
+
+/// link of a staking address to the node running it, signed by both
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodeAddressLinkInfo {
+    /// staking address
+    pub address: Address,
+    /// node running the staking address
+    pub node_id: NodeId,
+    /// time at which the link was signed
+    pub timestamp: MassaTime,
+    /// true if the node is currently connected to us
+    pub connected: bool,
+}
+
+impl std::fmt::Display for NodeAddressLinkInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        writeln!(f, "\tNode: {}", self.node_id)?;
+        writeln!(f, "\tSigned at: {}", self.timestamp.format_instant())?;
+        writeln!(f, "\tConnected: {}", self.connected)?;
+        Ok(())
+    }
+}
+
+/// number of blocks whose knowledge by a connected node is tracked
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodeKnownBlocks {
+    /// connected node
+    pub node_id: NodeId,
+    /// number of blocks the node is known to know or not to know,
+    /// at most `max_node_known_blocks_size`
+    pub known_blocks: usize,
+}
+
+/// what a connected node is believed to know, and the blocks being asked to it
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodeKnowledgeInfo {
+    /// connected node
+    pub node_id: NodeId,
+    /// blocks the node is believed to know
+    pub known_blocks: Vec<BlockId>,
+    /// blocks the node is believed not to know
+    pub unknown_blocks: Vec<BlockId>,
+    /// number of operations the node is believed to know
+    pub known_operations_count: usize,
+    /// endorsements the node is believed to know
+    pub known_endorsements: Vec<EndorsementId>,
+    /// blocks whose data is being asked to the node, with the time elapsed since the ask
+    pub asked_blocks: Vec<(BlockId, MassaTime)>,
+}
+
+/// rolling contribution of a node to the final blocks and endorsements,
+/// counting the ones it was the first to send us
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NodePeerContribution {
+    /// node
+    pub node_id: NodeId,
+    /// final blocks first sent by the node, decayed over time
+    pub blocks: f64,
+    /// final endorsements first sent by the node, decayed over time
+    pub endorsements: f64,
+    /// combined score: the endorsements of a full block weigh as much as the block
+    pub score: f64,
+    /// true if the node is currently connected to us
+    pub connected: bool,
+    /// true if the node is protected from eviction as one of the top contributors
+    pub protected: bool,
+}
+
+/// messages and bytes exchanged with the nodes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodeTraffic {
+    /// number of messages sent
+    pub messages_sent: u64,
+    /// size of the messages sent, in bytes
+    pub bytes_sent: u64,
+    /// number of messages received
+    pub messages_received: u64,
+    /// size of the messages received, in bytes
+    pub bytes_received: u64,
+}
+
+/// traffic exchanged with a connected node since it connected
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodePeerTraffic {
+    /// connected node
+    pub node_id: NodeId,
+    /// messages and bytes exchanged with the node
+    pub traffic: NodeTraffic,
+}
+
+/// traffic exchanged with the nodes since our node started
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NodeNetworkStats {
+    /// traffic of each message type, such as `Block/Header`
+    pub by_message_type: BTreeMap<String, NodeTraffic>,
+    /// traffic with each connected node
+    pub by_node: Vec<NodePeerTraffic>,
+}
+
+/// approximate memory used by the big in-memory structures of a subsystem
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MemoryUsageInfo {
+    /// subsystem owning the structures
+    pub subsystem: String,
+    /// approximate memory in bytes, as last reported by the subsystem
+    pub bytes: u64,
+}
+
 impl std::fmt::Display for NodeStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(warning) = &self.net_split_warning {
+            writeln!(f, "WARNING: {}", warning)?;
+            writeln!(f)?;
+        }
         writeln!(f, "Node's ID: {}", self.node_id)?;
         if self.node_ip.is_some() {
             writeln!(f, "Node's IP: {}", self.node_ip.unwrap())?;
@@ -59,6 +302,12 @@ impl std::fmt::Display for NodeStatus {
         writeln!(f)?;
 
         writeln!(f, "Version: {}", self.version)?;
+        if self.maintenance_mode {
+            writeln!(
+                f,
+                "Maintenance mode: enabled (not producing blocks nor endorsements)"
+            )?;
+        }
         writeln!(f, "Config:\n{}", self.config)?;
         writeln!(f)?;
 
@@ -68,6 +317,11 @@ impl std::fmt::Display for NodeStatus {
             writeln!(f, "Last slot: {}", self.last_slot.unwrap())?;
         }
         writeln!(f, "Next slot: {}", self.next_slot)?;
+        writeln!(
+            f,
+            "Chain parameters version: {}",
+            self.chain_parameters_version
+        )?;
         writeln!(f)?;
 
         writeln!(f, "{}", self.consensus_stats)?;
@@ -83,13 +337,27 @@ impl std::fmt::Display for NodeStatus {
 
         writeln!(f, "Connected nodes:")?;
         for (node_id, (ip_addr, is_outgoing)) in &self.connected_nodes {
-            writeln!(
+            write!(
                 f,
                 "Node's ID: {} / IP address: {} / {} connection",
                 node_id,
                 ip_addr,
                 if *is_outgoing { "Out" } else { "In" }
-            )?
+            )?;
+            if let Some(latency) = self.connected_nodes_latencies.get(node_id) {
+                write!(f, " / Latency: {}", latency)?;
+            }
+            match self.connected_nodes_agents.get(node_id) {
+                Some(agent) => writeln!(f, " / Version: {}", agent)?,
+                None => writeln!(f)?,
+            }
+        }
+        if !self.protocol_feature_warnings.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Protocol feature warnings:")?;
+            for warning in &self.protocol_feature_warnings {
+                writeln!(f, "\t{}", warning)?;
+            }
         }
         Ok(())
     }
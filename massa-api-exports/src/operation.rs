@@ -1,8 +1,12 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    block_header::SecuredHeader,
     block_id::BlockId,
-    operation::{OperationId, SecureShareOperation},
+    operation::{
+        compute_operations_hash, OperationId, OperationIdSerializer, SecureShareOperation,
+    },
+    slot::Slot,
 };
 
 use massa_signature::{PublicKey, Signature};
@@ -65,6 +69,132 @@ impl std::fmt::Display for OperationInfo {
     }
 }
 
+/// Proof that an operation was included in a block, verifiable with the header chain only
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OperationInclusionProof {
+    /// id of the proven operation
+    pub operation_id: OperationId,
+    /// signed header of the block including the operation, whose id is checked against the header chain
+    pub header: SecuredHeader,
+    /// position of the operation in the block
+    pub index: usize,
+    /// ids of all the operations of the block, in order:
+    /// the operation root of the header is computed from them
+    pub operation_ids: Vec<OperationId>,
+    /// true if the block is final
+    pub is_final: bool,
+}
+
+impl OperationInclusionProof {
+    /// Checks that the header is signed by its creator, that its operation root commits to
+    /// `operation_ids` and that the operation is at `index` in them
+    pub fn verify(&self) -> bool {
+        self.header.verify_signature().is_ok()
+            && self.operation_ids.get(self.index) == Some(&self.operation_id)
+            && compute_operations_hash(&self.operation_ids, &OperationIdSerializer::new())
+                == self.header.content.operation_merkle_root
+    }
+}
+
+impl std::fmt::Display for OperationInclusionProof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Operation {} is operation {} of {} in block {}{}",
+            self.operation_id,
+            self.index,
+            self.operation_ids.len(),
+            self.header.id,
+            display_if_true(self.is_final, "final")
+        )?;
+        writeln!(f, "{}", self.header)
+    }
+}
+
+/// Stage of an operation its sender would like to cancel.
+///
+/// An operation cannot be cancelled: it can only be outlived until it expires.
+/// It usually goes from `Pending` to `Included` then `Final`, or from `Pending` to `Expired`.
+/// A reorg can bring an `Included` operation back to `Pending`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationCancellationStatus {
+    /// unknown to the node: never received, or pruned from the node
+    Unknown,
+    /// waiting in the pool: it can still be included until it expires
+    Pending,
+    /// included in blocks that are not final yet: it will most likely be executed
+    Included,
+    /// executed in a final block: it is too late to do anything about it
+    Final,
+    /// its validity window is over without it being included: it will never be executed
+    Expired,
+}
+
+impl std::fmt::Display for OperationCancellationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationCancellationStatus::Unknown => write!(f, "unknown"),
+            OperationCancellationStatus::Pending => write!(f, "pending, can still be included"),
+            OperationCancellationStatus::Included => write!(f, "included, not final yet"),
+            OperationCancellationStatus::Final => write!(f, "final"),
+            OperationCancellationStatus::Expired => write!(f, "expired, will never be executed"),
+        }
+    }
+}
+
+/// Tracking of an operation its sender would like to cancel
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OperationCancellationInfo {
+    /// id of the operation
+    pub id: OperationId,
+    /// stage of the operation
+    pub status: OperationCancellationStatus,
+    /// last slot in which the operation can be included, None if the operation is unknown
+    pub expire_slot: Option<Slot>,
+    /// blocks including the operation
+    pub in_blocks: Vec<BlockId>,
+}
+
+impl std::fmt::Display for OperationCancellationInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Operation {}: {}", self.id, self.status)?;
+        if let Some(expire_slot) = self.expire_slot {
+            write!(f, " (expires after slot {})", expire_slot)?;
+        }
+        writeln!(f)?;
+        for block_id in &self.in_blocks {
+            writeln!(f, "\t- in block {}", block_id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Replacement of an operation broadcast while the operation can still be included
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OperationReplacement {
+    /// tracking of the replaced operation
+    pub replaced: OperationCancellationInfo,
+    /// id of the broadcast replacement
+    pub replacement_id: OperationId,
+    /// true if nothing prevents both operations from being executed:
+    /// the replaced operation is then only outlived by waiting for its expiration
+    pub advisory: bool,
+}
+
+impl std::fmt::Display for OperationReplacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Replacement {} sent", self.replacement_id)?;
+        if self.advisory {
+            writeln!(
+                f,
+                "Advisory replacement: the replaced operation may still be executed until it expires"
+            )?;
+        }
+        write!(f, "{}", self.replaced)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use jsonrpsee::core::__reexports::serde_json::{self, Value};
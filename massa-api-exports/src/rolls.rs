@@ -1,5 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_models::{address::Address, slot::Slot};
+use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 
 /// Roll counts
@@ -21,3 +23,85 @@ impl std::fmt::Display for RollsInfo {
         Ok(())
     }
 }
+
+/// Liveness of an active staker, as seen through the heartbeats gossiped by staking nodes
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StakerLiveness {
+    /// staker address
+    pub address: Address,
+    /// active rolls of the staker for the current cycle
+    pub active_rolls: u64,
+    /// timestamp of the latest fresh heartbeat received from the staker, if any
+    pub last_heartbeat: Option<MassaTime>,
+    /// last slot in which the staker claims to have produced a block
+    pub last_produced_slot: Option<Slot>,
+}
+
+impl std::fmt::Display for StakerLiveness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Staker: {}", self.address)?;
+        writeln!(f, "\tActive rolls: {}", self.active_rolls)?;
+        match self.last_heartbeat {
+            Some(timestamp) => writeln!(f, "\tLast heartbeat: {}", timestamp.format_instant())?,
+            None => writeln!(f, "\tLast heartbeat: none (possibly offline)")?,
+        }
+        if let Some(slot) = self.last_produced_slot {
+            writeln!(f, "\tLast produced slot: {}", slot)?;
+        }
+        Ok(())
+    }
+}
+
+/// Activation state of a staking address.
+///
+/// Rolls only take part in the draws 3 cycles after they became final,
+/// so a freshly registered staking address stays inactive for a while.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum StakingKeyActivation {
+    /// the state was not computed yet (address added since the last check)
+    Unknown,
+    /// the address owns no rolls: buy rolls to start staking
+    AwaitingRolls,
+    /// the address owns rolls that are not taken into account in the draws yet
+    AwaitingCycle {
+        /// cycle from which the address is expected to be drawn
+        activation_cycle: u64,
+    },
+    /// the address is drawn in the current cycle
+    Active {
+        /// active rolls of the address for the current cycle
+        active_rolls: u64,
+    },
+}
+
+impl std::fmt::Display for StakingKeyActivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StakingKeyActivation::Unknown => write!(f, "unknown (not checked yet)"),
+            StakingKeyActivation::AwaitingRolls => write!(f, "awaiting rolls (no roll owned)"),
+            StakingKeyActivation::AwaitingCycle { activation_cycle } => write!(
+                f,
+                "awaiting cycle (rolls taken into account from cycle {})",
+                activation_cycle
+            ),
+            StakingKeyActivation::Active { active_rolls } => {
+                write!(f, "active ({} active rolls)", active_rolls)
+            }
+        }
+    }
+}
+
+/// Activation state of a staking address of the node
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StakingAddressActivation {
+    /// staking address
+    pub address: Address,
+    /// activation state
+    pub activation: StakingKeyActivation,
+}
+
+impl std::fmt::Display for StakingAddressActivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.address, self.activation)
+    }
+}
@@ -1,6 +1,8 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 //! Json RPC API for a massa-node
+use std::collections::VecDeque;
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use crate::api_trait::MassaApiServer;
 use crate::{ApiServer, ApiV2, StopHandle, API};
@@ -11,10 +13,13 @@ use jsonrpsee::core::{Error as JsonRpseeError, RpcResult, SubscriptionResult};
 use jsonrpsee::{PendingSubscriptionSink, SubscriptionMessage};
 use massa_api_exports::config::APIConfig;
 use massa_api_exports::error::ApiError;
+use massa_api_exports::ledger::SlotLedgerChanges;
 use massa_api_exports::page::{PageRequest, PagedVec, PagedVecV2};
 use massa_api_exports::ApiRequest;
 use massa_consensus_exports::{ConsensusBroadcasts, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{
+    ExecutionChannels, ExecutionController, ExecutionOutput, SlotExecutionOutput,
+};
 use massa_models::address::Address;
 use massa_models::block_id::BlockId;
 use massa_models::slot::Slot;
@@ -22,7 +27,9 @@ use massa_models::timeslots::get_latest_block_slot_at_timestamp;
 use massa_models::version::Version;
 use massa_pool_exports::PoolBroadcasts;
 use massa_time::MassaTime;
+use parking_lot::RwLock;
 use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
 use tokio_stream::wrappers::BroadcastStream;
 
 impl API<ApiV2> {
@@ -31,6 +38,7 @@ impl API<ApiV2> {
         consensus_controller: Box<dyn ConsensusController>,
         consensus_broadcasts: ConsensusBroadcasts,
         execution_controller: Box<dyn ExecutionController>,
+        execution_channels: ExecutionChannels,
         pool_broadcasts: PoolBroadcasts,
         api_settings: APIConfig,
         version: Version,
@@ -39,6 +47,8 @@ impl API<ApiV2> {
             consensus_controller,
             consensus_broadcasts,
             execution_controller,
+            execution_channels,
+            ledger_changes_history: Default::default(),
             pool_broadcasts,
             api_settings,
             version,
@@ -53,6 +63,14 @@ impl ApiServer for API<ApiV2> {
         url: &SocketAddr,
         api_config: &APIConfig,
     ) -> Result<StopHandle, JsonRpseeError> {
+        tokio::spawn(record_final_ledger_changes(
+            self.0
+                .execution_channels
+                .slot_execution_output_sender
+                .subscribe(),
+            self.0.ledger_changes_history.clone(),
+            self.0.api_settings.final_history_length,
+        ));
         crate::serve(self.into_rpc(), url, api_config).await
     }
 }
@@ -149,6 +167,130 @@ impl MassaApiServer for API<ApiV2> {
     ) -> SubscriptionResult {
         broadcast_via_ws(self.0.pool_broadcasts.operation_sender.clone(), pending).await
     }
+
+    async fn subscribe_final_ledger_changes(
+        &self,
+        pending: PendingSubscriptionSink,
+        from_slot: Option<Slot>,
+    ) -> SubscriptionResult {
+        // subscribe before reading the history so that no slot is missed in between
+        let receiver = self
+            .0
+            .execution_channels
+            .slot_execution_output_sender
+            .subscribe();
+        let replayed: Vec<SlotLedgerChanges> = match from_slot {
+            Some(from_slot) => {
+                let history = self.0.ledger_changes_history.read();
+                match history.front() {
+                    Some(oldest) if oldest.slot <= from_slot => history
+                        .iter()
+                        .filter(|changes| changes.slot >= from_slot)
+                        .cloned()
+                        .collect(),
+                    _ => {
+                        drop(history);
+                        pending
+                            .reject(ApiError::BadRequest(format!(
+                                "slot {} is out of the history window",
+                                from_slot
+                            )))
+                            .await;
+                        return Ok(());
+                    }
+                }
+            }
+            None => Vec::new(),
+        };
+        stream_final_ledger_changes(pending, receiver, replayed, from_slot).await
+    }
+}
+
+fn slot_ledger_changes(output: ExecutionOutput) -> SlotLedgerChanges {
+    SlotLedgerChanges {
+        slot: output.slot,
+        block_id: output.block_info.map(|info| info.block_id),
+        ledger_changes: output.state_changes.ledger_changes,
+    }
+}
+
+/// Keep the ledger changes of the last `history_length` finalized slots
+async fn record_final_ledger_changes(
+    mut receiver: broadcast::Receiver<SlotExecutionOutput>,
+    history: Arc<RwLock<VecDeque<SlotLedgerChanges>>>,
+    history_length: usize,
+) {
+    loop {
+        match receiver.recv().await {
+            Ok(SlotExecutionOutput::FinalizedSlot(output)) => {
+                let mut history = history.write();
+                history.push_back(slot_ledger_changes(output));
+                while history.len() > history_length {
+                    history.pop_front();
+                }
+            }
+            Ok(SlotExecutionOutput::ExecutedSlot(_)) => {}
+            // slots were missed: the history cannot be resumed from before them
+            Err(RecvError::Lagged(_)) => history.write().clear(),
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Send the replayed ledger changes, then the ones of the slots finalized from now on
+async fn stream_final_ledger_changes(
+    pending: PendingSubscriptionSink,
+    mut receiver: broadcast::Receiver<SlotExecutionOutput>,
+    replayed: Vec<SlotLedgerChanges>,
+    from_slot: Option<Slot>,
+) -> SubscriptionResult {
+    let sink = pending.accept().await?;
+    // last slot sent, to skip the live slots already replayed
+    let mut last_sent: Option<Slot> = None;
+    for changes in replayed {
+        last_sent = Some(changes.slot);
+        if sink
+            .send(SubscriptionMessage::from_json(&changes)?)
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+    }
+
+    loop {
+        let output = tokio::select! {
+            _ = sink.closed() => break Ok(()),
+            output = receiver.recv() => output,
+        };
+        match output {
+            Ok(SlotExecutionOutput::FinalizedSlot(output)) => {
+                if last_sent.map_or(false, |slot| output.slot <= slot)
+                    || from_slot.map_or(false, |slot| output.slot < slot)
+                {
+                    continue;
+                }
+                last_sent = Some(output.slot);
+                let changes = slot_ledger_changes(output);
+                if sink
+                    .send(SubscriptionMessage::from_json(&changes)?)
+                    .await
+                    .is_err()
+                {
+                    break Ok(());
+                }
+            }
+            Ok(SlotExecutionOutput::ExecutedSlot(_)) => {}
+            // the subscriber would miss slots: end the stream so that it resumes from its last slot
+            Err(RecvError::Lagged(_)) => {
+                break Err(
+                    "ledger changes stream lagged behind, resume from the last received slot"
+                        .into(),
+                )
+            }
+            Err(RecvError::Closed) => break Ok(()),
+        }
+    }
 }
 
 // Brodcast the stream(sender) content via a WebSocket
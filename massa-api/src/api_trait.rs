@@ -6,6 +6,7 @@ use massa_api_exports::page::PagedVecV2;
 use massa_api_exports::ApiRequest;
 use massa_models::address::Address;
 use massa_models::block_id::BlockId;
+use massa_models::slot::Slot;
 use massa_models::version::Version;
 
 /// Exposed API methods
@@ -57,4 +58,13 @@ pub trait MassaApi {
 		item = Operation
 	)]
     async fn subscribe_new_operations(&self) -> SubscriptionResult;
+
+    /// Ledger changes of the finalized slots, starting from `from_slot` if it is in the history window,
+    /// or from the next finalized slot if no slot is given.
+    #[subscription(
+        name = "subscribe_final_ledger_changes" => "final_ledger_changes",
+        unsubscribe = "unsubscribe_final_ledger_changes",
+        item = SlotLedgerChanges
+    )]
+    async fn subscribe_final_ledger_changes(&self, from_slot: Option<Slot>) -> SubscriptionResult;
 }
@@ -13,39 +13,61 @@ use jsonrpsee::server::{BatchRequestConfig, ServerBuilder, ServerHandle};
 use jsonrpsee::RpcModule;
 use massa_api_exports::execution::Transfer;
 use massa_api_exports::{
-    address::{AddressFilter, AddressInfo},
-    block::{BlockInfo, BlockSummary},
+    address::{AddressFilter, AddressInfo, RewardsRequest},
+    block::{BlockInfo, BlockSummary, NetworkLoad},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError::WrongAPI,
     execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    filter::QueryFilter,
+    ledger::SlotLedgerChanges,
+    node::{
+        MemoryUsageInfo, NodeAddressLinkInfo, NodeBan, NodeBanRequest, NodeBanTarget,
+        NodeKnowledgeInfo, NodeKnownBlocks, NodeNetworkStats, NodePeerContribution, NodePeerRecord,
+        NodePeerRecordEdit, NodeStatus,
+    },
+    operation::{
+        OperationCancellationInfo, OperationInclusionProof, OperationInfo, OperationInput,
+        OperationReplacement,
+    },
     page::{PageRequest, PagedVec},
+    rolls::{StakerLiveness, StakingAddressActivation, StakingKeyActivation},
     TimeInterval,
 };
-use massa_consensus_exports::{ConsensusBroadcasts, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_consensus_exports::{
+    archive::BlockArchive, dependencies::BlockDependencies, equivocation::EquivocationEvidence,
+    parent_selection::ParentSelectionDiagnostics, ConsensusBroadcasts, ConsensusController,
+};
+use massa_execution_exports::{ExecutionChannels, ExecutionController};
+use massa_metrics::MassaMetrics;
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
+use massa_models::name_registry::NameRecord;
 use massa_models::node::NodeId;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
-use massa_models::prehash::PreHashSet;
+use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::{
-    address::Address, block::Block, block_id::BlockId, endorsement::EndorsementId,
-    execution::EventFilter, slot::Slot, version::Version,
+    address::{Address, AddressCycleRewards},
+    block::Block,
+    block_id::BlockId,
+    endorsement::EndorsementId,
+    execution::EventFilter,
+    slot::Slot,
+    version::Version,
 };
 use massa_pool_exports::{PoolBroadcasts, PoolController};
-use massa_pos_exports::SelectorController;
+use massa_pos_exports::{CycleDrawAudit, SelectorController};
 use massa_protocol_exports::{ProtocolConfig, ProtocolController};
 use massa_storage::Storage;
 use massa_versioning::keypair_factory::KeyPairFactory;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use serde_json::Value;
+use std::collections::VecDeque;
 use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Condvar, Mutex};
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, warn};
@@ -87,6 +109,10 @@ pub struct Public {
     pub node_id: NodeId,
     /// keypair factory
     pub keypair_factory: KeyPairFactory,
+    /// Maintenance mode flag, shared with the factory
+    pub maintenance_mode: Arc<AtomicBool>,
+    /// Archive of the final blocks, if the node is archival
+    pub block_archive: Option<BlockArchive>,
 }
 
 /// Private API content
@@ -102,6 +128,12 @@ pub struct Private {
     pub stop_cv: Arc<(Mutex<bool>, Condvar)>,
     /// User wallet
     pub node_wallet: Arc<RwLock<Wallet>>,
+    /// Maintenance mode flag, shared with the factory
+    pub maintenance_mode: Arc<AtomicBool>,
+    /// Activation states of the staking addresses, kept up to date by the node
+    pub staking_activations: Arc<RwLock<PreHashMap<Address, StakingKeyActivation>>>,
+    /// Node metrics, holding the memory usage reported by the subsystems
+    pub massa_metrics: MassaMetrics,
 }
 
 /// API v2 content
@@ -112,6 +144,10 @@ pub struct ApiV2 {
     pub consensus_broadcasts: ConsensusBroadcasts,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
+    /// channels with informations broadcasted by the execution
+    pub execution_channels: ExecutionChannels,
+    /// ledger changes of the last finalized slots, to resume ledger change streams
+    pub ledger_changes_history: Arc<RwLock<VecDeque<SlotLedgerChanges>>>,
     /// channels with informations broadcasted by the pool
     pub pool_broadcasts: PoolBroadcasts,
     /// API settings
@@ -262,6 +298,11 @@ pub trait MassaRpc {
     #[method(name = "get_staking_addresses")]
     async fn get_staking_addresses(&self) -> RpcResult<PreHashSet<Address>>;
 
+    /// Return the activation state of each staking address: rolls are only taken into account
+    /// in the draws some cycles after being bought.
+    #[method(name = "get_staking_addresses_activation")]
+    async fn get_staking_addresses_activation(&self) -> RpcResult<Vec<StakingAddressActivation>>;
+
     /// Bans given IP address(es).
     /// No confirmation to expect.
     #[method(name = "node_ban_by_ip")]
@@ -276,14 +317,13 @@ pub trait MassaRpc {
     #[method(name = "node_peers_whitelist")]
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>>;
 
-    /// Add IP address(es) to node peers whitelist.
+    /// Add IP address(es) to node peers whitelist: they are exempted from the banned subnets.
+    /// The whitelist is kept across restarts.
     /// No confirmation to expect.
-    /// Note: If the ip was unknown it adds it to the known peers, otherwise it updates the peer type
     #[method(name = "node_add_to_peers_whitelist")]
     async fn node_add_to_peers_whitelist(&self, arg: Vec<IpAddr>) -> RpcResult<()>;
 
     /// Remove from peers whitelist given IP address(es).
-    /// keep it as standard
     /// No confirmation to expect.
     #[method(name = "node_remove_from_peers_whitelist")]
     async fn node_remove_from_peers_whitelist(&self, arg: Vec<IpAddr>) -> RpcResult<()>;
@@ -327,6 +367,64 @@ pub trait MassaRpc {
     #[method(name = "node_unban_by_id")]
     async fn node_unban_by_id(&self, arg: Vec<NodeId>) -> RpcResult<()>;
 
+    /// Bans given node ids and subnets, permanently or for a duration.
+    /// Unlike the other bans, these are kept across restarts.
+    /// No confirmation to expect.
+    #[method(name = "node_ban")]
+    async fn node_ban(&self, arg: NodeBanRequest) -> RpcResult<()>;
+
+    /// Lifts the bans of given node ids and subnets.
+    /// No confirmation to expect.
+    #[method(name = "node_unban")]
+    async fn node_unban(&self, arg: Vec<NodeBanTarget>) -> RpcResult<()>;
+
+    /// Returns the bans kept across restarts, with their expiry.
+    #[method(name = "node_ban_list")]
+    async fn node_ban_list(&self) -> RpcResult<Vec<NodeBan>>;
+
+    /// Returns the records kept across restarts of given node ids, or of all the known nodes if none is given,
+    /// with their uptime, handshake failures, bans and reputation, the best ranked first.
+    #[method(name = "node_peer_records")]
+    async fn node_peer_records(&self, arg: Vec<NodeId>) -> RpcResult<Vec<NodePeerRecord>>;
+
+    /// Edits the records of nodes, recording the unknown ones: pinned nodes are dialed first and never forgotten.
+    /// No confirmation to expect.
+    #[method(name = "node_edit_peer_records")]
+    async fn node_edit_peer_records(&self, arg: Vec<NodePeerRecordEdit>) -> RpcResult<()>;
+
+    /// Forgets the records of given node ids.
+    /// No confirmation to expect.
+    #[method(name = "node_remove_peer_records")]
+    async fn node_remove_peer_records(&self, arg: Vec<NodeId>) -> RpcResult<()>;
+
+    /// Returns the number of blocks whose knowledge is tracked for each connected node.
+    #[method(name = "node_known_blocks")]
+    async fn node_known_blocks(&self) -> RpcResult<Vec<NodeKnownBlocks>>;
+
+    /// Returns the blocks, operations and endorsements each connected node is believed to know,
+    /// and the blocks being asked to it, to debug the propagation.
+    #[method(name = "node_knowledge")]
+    async fn node_knowledge(&self) -> RpcResult<Vec<NodeKnowledgeInfo>>;
+
+    /// Returns the leaderboard of the nodes by rolling contribution to the final blocks and endorsements,
+    /// the nodes that first sent us the most final items first.
+    #[method(name = "node_peer_contributions")]
+    async fn node_peer_contributions(&self) -> RpcResult<Vec<NodePeerContribution>>;
+
+    /// Returns the messages and bytes exchanged since the node started, per message type,
+    /// and with each connected node since it connected.
+    #[method(name = "node_network_stats")]
+    async fn node_network_stats(&self) -> RpcResult<NodeNetworkStats>;
+
+    /// Returns the approximate memory used by the big in-memory structures of each subsystem.
+    #[method(name = "node_memory_usage")]
+    async fn node_memory_usage(&self) -> RpcResult<Vec<MemoryUsageInfo>>;
+
+    /// Enable or disable the maintenance mode, in which the node keeps relaying, syncing
+    /// and serving the API but does not produce blocks nor endorsements.
+    #[method(name = "node_set_maintenance_mode")]
+    async fn node_set_maintenance_mode(&self, arg: bool) -> RpcResult<()>;
+
     /// Summary of the current state: time, last final blocks (hash, thread, slot, timestamp), clique count, connected nodes count.
     #[method(name = "get_status")]
     async fn get_status(&self) -> RpcResult<NodeStatus>;
@@ -342,10 +440,62 @@ pub trait MassaRpc {
         page_request: Option<PageRequest>,
     ) -> RpcResult<PagedVec<(Address, u64)>>;
 
+    /// Returns the active stakers of the current cycle along with their latest liveness heartbeat.
+    #[method(name = "get_stakers_liveness")]
+    async fn get_stakers_liveness(
+        &self,
+        page_request: Option<PageRequest>,
+    ) -> RpcResult<PagedVec<StakerLiveness>>;
+
+    /// Returns the links of staking addresses to the nodes running them, gossiped by the nodes whose operator opted in.
+    #[method(name = "get_node_address_links")]
+    async fn get_node_address_links(&self) -> RpcResult<Vec<NodeAddressLinkInfo>>;
+
+    /// Returns the equivocations (same creator signing two different headers for a slot) detected recently.
+    #[method(name = "get_equivocations")]
+    async fn get_equivocations(&self) -> RpcResult<Vec<EquivocationEvidence>>;
+
+    /// Debug endpoint: returns the blocks consensus is waiting for and the blocks blocked on them.
+    #[method(name = "get_block_dependencies")]
+    async fn get_block_dependencies(&self) -> RpcResult<BlockDependencies>;
+
+    /// Debug endpoint: returns the best parents a block produced now would use in each thread,
+    /// and why the more recent blocks were not selected.
+    #[method(name = "get_parent_selection")]
+    async fn get_parent_selection(&self) -> RpcResult<ParentSelectionDiagnostics>;
+
+    /// Returns the inputs and outputs of the selector draws of a cycle, allowing to re-verify them.
+    #[method(name = "get_cycle_draw_audit")]
+    async fn get_cycle_draw_audit(&self, cycle: u64) -> RpcResult<CycleDrawAudit>;
+
+    /// Returns a proof that an operation was included in a block, along with the block finality.
+    /// The proof can be verified knowing only the header chain.
+    #[method(name = "get_operation_inclusion_proof")]
+    async fn get_operation_inclusion_proof(
+        &self,
+        arg: OperationId,
+    ) -> RpcResult<OperationInclusionProof>;
+
+    /// Returns the candidate registration of a name of the name registry, if it is registered and not expired.
+    #[method(name = "resolve_name")]
+    async fn resolve_name(&self, arg: String) -> RpcResult<Option<NameRecord>>;
+
+    /// Returns the block rewards, endorsement rewards and fees earned by an address on the requested cycles.
+    /// Only final slots are accounted for, and cycles older than the node's rewards history are omitted.
+    #[method(name = "get_rewards")]
+    async fn get_rewards(&self, arg: RewardsRequest) -> RpcResult<Vec<AddressCycleRewards>>;
+
     /// Returns operation(s) information associated to a given list of operation(s) ID(s).
     #[method(name = "get_operations")]
     async fn get_operations(&self, arg: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>>;
 
+    /// Returns whether operations their sender regrets can still be included: pending, included, final or expired.
+    #[method(name = "get_operations_cancellation_status")]
+    async fn get_operations_cancellation_status(
+        &self,
+        arg: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationCancellationInfo>>;
+
     /// Returns endorsement(s) information associated to a given list of endorsement(s) ID(s)
     #[method(name = "get_endorsements")]
     async fn get_endorsements(&self, arg: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>>;
@@ -364,6 +514,11 @@ pub trait MassaRpc {
     #[method(name = "get_graph_interval")]
     async fn get_graph_interval(&self, arg: TimeInterval) -> RpcResult<Vec<BlockSummary>>;
 
+    /// Get the load of the network (fullness of the blocks and average fee) over the latest periods,
+    /// to adapt fees and batch sizes to congestion.
+    #[method(name = "get_network_load")]
+    async fn get_network_load(&self) -> RpcResult<NetworkLoad>;
+
     /// Get multiple datastore entries.
     #[method(name = "get_datastore_entries")]
     async fn get_datastore_entries(
@@ -387,6 +542,15 @@ pub trait MassaRpc {
     #[method(name = "send_operations")]
     async fn send_operations(&self, arg: Vec<OperationInput>) -> RpcResult<Vec<OperationId>>;
 
+    /// Broadcasts a pre-built replacement of an operation that is not final yet, from the same sender.
+    /// The replaced operation is not invalidated: the replacement is advisory and both may be executed.
+    #[method(name = "send_replacement_operation")]
+    async fn send_replacement_operation(
+        &self,
+        replaced: OperationId,
+        replacement: OperationInput,
+    ) -> RpcResult<OperationReplacement>;
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -397,6 +561,15 @@ pub trait MassaRpc {
     async fn get_filtered_sc_output_event(&self, arg: EventFilter)
         -> RpcResult<Vec<SCOutputEvent>>;
 
+    /// Get the operations matching a filter.
+    /// The filter must either target the sender or creator of the operations, or restrict the slot range.
+    #[method(name = "get_filtered_operations")]
+    async fn get_filtered_operations(&self, arg: QueryFilter) -> RpcResult<Vec<OperationInfo>>;
+
+    /// Get the events matching a filter.
+    #[method(name = "get_filtered_events")]
+    async fn get_filtered_events(&self, arg: QueryFilter) -> RpcResult<Vec<SCOutputEvent>>;
+
     /// Get OpenRPC specification.
     #[method(name = "rpc.discover")]
     async fn get_openrpc_spec(&self) -> RpcResult<Value>;
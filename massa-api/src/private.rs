@@ -5,38 +5,68 @@ use crate::{MassaRpcServer, Private, RpcServer, StopHandle, Value, API};
 use async_trait::async_trait;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
-    address::{AddressFilter, AddressInfo},
-    block::{BlockInfo, BlockSummary},
+    address::{AddressFilter, AddressInfo, RewardsRequest},
+    block::{BlockInfo, BlockSummary, NetworkLoad},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError,
     execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall, Transfer},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    filter::QueryFilter,
+    node::{
+        MemoryUsageInfo, NodeAddressLinkInfo, NodeAgent, NodeBan, NodeBanRequest, NodeBanTarget,
+        NodeKnowledgeInfo, NodeKnownBlocks, NodeNetworkStats, NodePeerContribution, NodePeerRecord,
+        NodePeerRecordEdit, NodePeerTraffic, NodeStatus, NodeTraffic,
+    },
+    operation::{
+        OperationCancellationInfo, OperationInclusionProof, OperationInfo, OperationInput,
+        OperationReplacement,
+    },
     page::{PageRequest, PagedVec},
+    rolls::{StakerLiveness, StakingAddressActivation, StakingKeyActivation},
     ListType, ScrudOperation, TimeInterval,
 };
+use massa_consensus_exports::{
+    dependencies::BlockDependencies, equivocation::EquivocationEvidence,
+    parent_selection::ParentSelectionDiagnostics,
+};
 use massa_execution_exports::ExecutionController;
 use massa_hash::Hash;
+use massa_metrics::MassaMetrics;
 use massa_models::{
-    address::Address, block::Block, block_id::BlockId, clique::Clique, composite::PubkeySig,
-    endorsement::EndorsementId, execution::EventFilter, node::NodeId, operation::OperationId,
-    output_event::SCOutputEvent, prehash::PreHashSet, slot::Slot,
+    address::{Address, AddressCycleRewards},
+    block::Block,
+    block_id::BlockId,
+    clique::Clique,
+    composite::PubkeySig,
+    endorsement::EndorsementId,
+    execution::EventFilter,
+    name_registry::NameRecord,
+    node::NodeId,
+    operation::OperationId,
+    output_event::SCOutputEvent,
+    prehash::{PreHashMap, PreHashSet},
+    slot::Slot,
+};
+use massa_pos_exports::CycleDrawAudit;
+use massa_protocol_exports::{
+    BanEntry, BanTarget, IpSubnet, PeerId, PeerRecordEdit, ProtocolController, TrafficCounters,
 };
-use massa_protocol_exports::{PeerId, ProtocolController};
 use massa_signature::KeyPair;
+use massa_time::MassaTime;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{collections::BTreeSet, sync::Mutex};
 use std::{
     fs::{remove_file, OpenOptions},
     sync::Condvar,
 };
+use tracing::info;
 
 impl API<Private> {
     /// generate a new private API
@@ -46,6 +76,9 @@ impl API<Private> {
         api_settings: APIConfig,
         stop_cv: Arc<(Mutex<bool>, Condvar)>,
         node_wallet: Arc<RwLock<Wallet>>,
+        maintenance_mode: Arc<AtomicBool>,
+        staking_activations: Arc<RwLock<PreHashMap<Address, StakingKeyActivation>>>,
+        massa_metrics: MassaMetrics,
     ) -> Self {
         API(Private {
             protocol_controller,
@@ -53,6 +86,9 @@ impl API<Private> {
             api_settings,
             stop_cv,
             node_wallet,
+            maintenance_mode,
+            staking_activations,
+            massa_metrics,
         })
     }
 }
@@ -147,16 +183,40 @@ impl MassaRpcServer for API<Private> {
         Ok(w_wallet.get_wallet_address_list())
     }
 
-    async fn node_ban_by_ip(&self, _ips: Vec<IpAddr>) -> RpcResult<()> {
-        //TODO: Reinvoke
-        // let network_command_sender = self.0.network_command_sender.clone();
-        // network_command_sender
-        //    .node_ban_by_ips(ips)
-        //    .await
-        //    .map_err(|e| ApiError::NetworkError(e).into())
-        return Err(
-            ApiError::BadRequest("This request is currently not available".to_string()).into(),
-        );
+    async fn get_staking_addresses_activation(&self) -> RpcResult<Vec<StakingAddressActivation>> {
+        let mut addresses: Vec<Address> = self
+            .0
+            .node_wallet
+            .read()
+            .get_wallet_address_list()
+            .into_iter()
+            .collect();
+        addresses.sort_unstable();
+        let activations = self.0.staking_activations.read();
+        Ok(addresses
+            .into_iter()
+            .map(|address| StakingAddressActivation {
+                address,
+                activation: activations
+                    .get(&address)
+                    .copied()
+                    .unwrap_or(StakingKeyActivation::Unknown),
+            })
+            .collect())
+    }
+
+    async fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
+        let bans = ips
+            .into_iter()
+            .map(|ip| BanEntry {
+                target: BanTarget::Subnet(IpSubnet::from_address(ip)),
+                expires_at: None,
+            })
+            .collect();
+        self.0
+            .protocol_controller
+            .add_bans(bans)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
     }
 
     async fn node_ban_by_id(&self, ids: Vec<NodeId>) -> RpcResult<()> {
@@ -174,25 +234,270 @@ impl MassaRpcServer for API<Private> {
     async fn node_unban_by_id(&self, ids: Vec<NodeId>) -> RpcResult<()> {
         let protocol_controller = self.0.protocol_controller.clone();
         //TODO: Change when unify node id and peer id
+        // also lifts the bans kept across restarts
+        let targets = ids
+            .into_iter()
+            .map(|id| BanTarget::Peer(PeerId::from_public_key(id.get_public_key())))
+            .collect();
+        protocol_controller
+            .remove_bans(targets)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
+    }
+
+    async fn node_ban(&self, request: NodeBanRequest) -> RpcResult<()> {
+        if request.targets.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let expires_at = match request.duration {
+            Some(duration) => Some(
+                MassaTime::now()
+                    .checked_add(duration)
+                    .map_err(ApiError::TimeError)?,
+            ),
+            None => None,
+        };
+        let bans = request
+            .targets
+            .into_iter()
+            .map(|target| {
+                Ok(BanEntry {
+                    target: to_ban_target(target)?,
+                    expires_at,
+                })
+            })
+            .collect::<Result<Vec<_>, ApiError>>()?;
+        self.0
+            .protocol_controller
+            .add_bans(bans)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
+    }
+
+    async fn node_unban(&self, targets: Vec<NodeBanTarget>) -> RpcResult<()> {
+        if targets.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let targets = targets
+            .into_iter()
+            .map(to_ban_target)
+            .collect::<Result<Vec<_>, ApiError>>()?;
+        self.0
+            .protocol_controller
+            .remove_bans(targets)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
+    }
+
+    async fn node_ban_list(&self) -> RpcResult<Vec<NodeBan>> {
+        let bans = self
+            .0
+            .protocol_controller
+            .get_bans()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?;
+        Ok(bans
+            .into_iter()
+            .map(|ban| NodeBan {
+                target: match ban.target {
+                    BanTarget::Peer(peer_id) => {
+                        NodeBanTarget::NodeId(NodeId::new(peer_id.get_public_key()))
+                    }
+                    BanTarget::Subnet(subnet) => NodeBanTarget::Subnet(subnet.to_string()),
+                },
+                expires_at: ban.expires_at,
+            })
+            .collect())
+    }
+
+    async fn node_peer_records(&self, ids: Vec<NodeId>) -> RpcResult<Vec<NodePeerRecord>> {
+        if ids.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
         let peer_ids = ids
             .into_iter()
             .map(|id| PeerId::from_public_key(id.get_public_key()))
             .collect();
-        protocol_controller
-            .unban_peers(peer_ids)
+        let records = self
+            .0
+            .protocol_controller
+            .get_peer_records(peer_ids)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?;
+        let now = MassaTime::now();
+        Ok(records
+            .into_iter()
+            .map(|record| NodePeerRecord {
+                node_id: NodeId::new(record.peer_id.get_public_key()),
+                reputation: record.reputation(now),
+                listeners: record.listeners,
+                first_seen: record.first_seen,
+                last_seen: record.last_seen,
+                uptime: record.uptime,
+                connections: record.connections,
+                handshake_failures: record.handshake_failures,
+                bans: record.bans,
+                agent: record.agent.map(|agent| NodeAgent {
+                    version: agent.version,
+                    user_agent: agent.user_agent,
+                }),
+                pinned: record.pinned,
+            })
+            .collect())
+    }
+
+    async fn node_edit_peer_records(&self, edits: Vec<NodePeerRecordEdit>) -> RpcResult<()> {
+        if edits.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let edits = edits
+            .into_iter()
+            .map(|edit| PeerRecordEdit {
+                peer_id: PeerId::from_public_key(edit.node_id.get_public_key()),
+                listeners: edit.listeners,
+                pinned: edit.pinned,
+                reset_reputation: edit.reset_reputation,
+            })
+            .collect();
+        self.0
+            .protocol_controller
+            .edit_peer_records(edits)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
+    }
+
+    async fn node_remove_peer_records(&self, ids: Vec<NodeId>) -> RpcResult<()> {
+        if ids.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let peer_ids = ids
+            .into_iter()
+            .map(|id| PeerId::from_public_key(id.get_public_key()))
+            .collect();
+        self.0
+            .protocol_controller
+            .remove_peer_records(peer_ids)
             .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
     }
 
-    async fn node_unban_by_ip(&self, _ips: Vec<IpAddr>) -> RpcResult<()> {
-        //TODO: Reinvoke
-        // let network_command_sender = self.0.network_command_sender.clone();
-        // network_command_sender
-        //     .node_unban_ips(ips)
-        //     .await
-        //     .map_err(|e| ApiError::NetworkError(e).into())
-        return Err(
-            ApiError::BadRequest("This request is currently not available".to_string()).into(),
-        );
+    async fn node_known_blocks(&self) -> RpcResult<Vec<NodeKnownBlocks>> {
+        let sizes = self
+            .0
+            .protocol_controller
+            .get_known_blocks_sizes()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?;
+        let mut known_blocks: Vec<NodeKnownBlocks> = sizes
+            .into_iter()
+            .map(|(peer_id, known_blocks)| NodeKnownBlocks {
+                node_id: NodeId::new(peer_id.get_public_key()),
+                known_blocks,
+            })
+            .collect();
+        known_blocks.sort_unstable_by_key(|node| node.node_id);
+        Ok(known_blocks)
+    }
+
+    async fn node_knowledge(&self) -> RpcResult<Vec<NodeKnowledgeInfo>> {
+        let knowledge = self
+            .0
+            .protocol_controller
+            .get_node_knowledge()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?;
+        let mut knowledge: Vec<NodeKnowledgeInfo> = knowledge
+            .into_iter()
+            .map(|(peer_id, node_knowledge)| NodeKnowledgeInfo {
+                node_id: NodeId::new(peer_id.get_public_key()),
+                known_blocks: node_knowledge.known_blocks,
+                unknown_blocks: node_knowledge.unknown_blocks,
+                known_operations_count: node_knowledge.known_operations_count,
+                known_endorsements: node_knowledge.known_endorsements,
+                asked_blocks: node_knowledge.asked_blocks,
+            })
+            .collect();
+        knowledge.sort_unstable_by_key(|node| node.node_id);
+        Ok(knowledge)
+    }
+
+    async fn node_peer_contributions(&self) -> RpcResult<Vec<NodePeerContribution>> {
+        let contributions = self
+            .0
+            .protocol_controller
+            .get_peer_contributions()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?;
+        let mut leaderboard: Vec<NodePeerContribution> = contributions
+            .into_iter()
+            .map(|(peer_id, contribution)| NodePeerContribution {
+                node_id: NodeId::new(peer_id.get_public_key()),
+                blocks: contribution.blocks,
+                endorsements: contribution.endorsements,
+                score: contribution.score,
+                connected: contribution.connected,
+                protected: contribution.protected,
+            })
+            .collect();
+        leaderboard.sort_unstable_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.node_id.cmp(&b.node_id))
+        });
+        Ok(leaderboard)
+    }
+
+    async fn node_network_stats(&self) -> RpcResult<NodeNetworkStats> {
+        let stats = self
+            .0
+            .protocol_controller
+            .get_network_stats()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?;
+        let to_api = |counters: TrafficCounters| NodeTraffic {
+            messages_sent: counters.messages_sent,
+            bytes_sent: counters.bytes_sent,
+            messages_received: counters.messages_received,
+            bytes_received: counters.bytes_received,
+        };
+        let mut by_node: Vec<NodePeerTraffic> = stats
+            .by_peer
+            .into_iter()
+            .map(|(peer_id, counters)| NodePeerTraffic {
+                node_id: NodeId::new(peer_id.get_public_key()),
+                traffic: to_api(counters),
+            })
+            .collect();
+        by_node.sort_unstable_by_key(|node| node.node_id);
+        Ok(NodeNetworkStats {
+            by_message_type: stats
+                .by_message_type
+                .into_iter()
+                .map(|(message_type, counters)| (message_type, to_api(counters)))
+                .collect(),
+            by_node,
+        })
+    }
+
+    async fn node_memory_usage(&self) -> RpcResult<Vec<MemoryUsageInfo>> {
+        Ok(self
+            .0
+            .massa_metrics
+            .get_memory_usage()
+            .into_iter()
+            .map(|(subsystem, bytes)| MemoryUsageInfo { subsystem, bytes })
+            .collect())
+    }
+
+    async fn node_set_maintenance_mode(&self, enabled: bool) -> RpcResult<()> {
+        let previous = self.0.maintenance_mode.swap(enabled, Ordering::Relaxed);
+        if previous != enabled {
+            info!(
+                "maintenance mode {}",
+                if enabled { "enabled" } else { "disabled" }
+            );
+        }
+        Ok(())
+    }
+
+    async fn node_unban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
+        let targets = ips
+            .into_iter()
+            .map(|ip| BanTarget::Subnet(IpSubnet::from_address(ip)))
+            .collect();
+        self.0
+            .protocol_controller
+            .remove_bans(targets)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
     }
 
     async fn get_slots_transfers(&self, _: Vec<Slot>) -> RpcResult<Vec<Vec<Transfer>>> {
@@ -211,10 +516,59 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<PagedVec<(Address, u64)>>()
     }
 
+    async fn get_stakers_liveness(
+        &self,
+        _: Option<PageRequest>,
+    ) -> RpcResult<PagedVec<StakerLiveness>> {
+        crate::wrong_api::<PagedVec<StakerLiveness>>()
+    }
+
+    async fn get_node_address_links(&self) -> RpcResult<Vec<NodeAddressLinkInfo>> {
+        crate::wrong_api::<Vec<NodeAddressLinkInfo>>()
+    }
+
+    async fn get_equivocations(&self) -> RpcResult<Vec<EquivocationEvidence>> {
+        crate::wrong_api::<Vec<EquivocationEvidence>>()
+    }
+
+    async fn get_block_dependencies(&self) -> RpcResult<BlockDependencies> {
+        crate::wrong_api::<BlockDependencies>()
+    }
+
+    async fn get_parent_selection(&self) -> RpcResult<ParentSelectionDiagnostics> {
+        crate::wrong_api::<ParentSelectionDiagnostics>()
+    }
+
+    async fn get_cycle_draw_audit(&self, _: u64) -> RpcResult<CycleDrawAudit> {
+        crate::wrong_api::<CycleDrawAudit>()
+    }
+
+    async fn get_operation_inclusion_proof(
+        &self,
+        _: OperationId,
+    ) -> RpcResult<OperationInclusionProof> {
+        crate::wrong_api::<OperationInclusionProof>()
+    }
+
+    async fn resolve_name(&self, _: String) -> RpcResult<Option<NameRecord>> {
+        crate::wrong_api::<Option<NameRecord>>()
+    }
+
+    async fn get_rewards(&self, _: RewardsRequest) -> RpcResult<Vec<AddressCycleRewards>> {
+        crate::wrong_api::<Vec<AddressCycleRewards>>()
+    }
+
     async fn get_operations(&self, _: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>> {
         crate::wrong_api::<Vec<OperationInfo>>()
     }
 
+    async fn get_operations_cancellation_status(
+        &self,
+        _: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationCancellationInfo>> {
+        crate::wrong_api::<Vec<OperationCancellationInfo>>()
+    }
+
     async fn get_endorsements(&self, _: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>> {
         crate::wrong_api::<Vec<EndorsementInfo>>()
     }
@@ -231,6 +585,10 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<BlockSummary>>()
     }
 
+    async fn get_network_load(&self) -> RpcResult<NetworkLoad> {
+        crate::wrong_api::<NetworkLoad>()
+    }
+
     async fn get_datastore_entries(
         &self,
         _: Vec<DatastoreEntryInput>,
@@ -250,45 +608,45 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<OperationId>>()
     }
 
+    async fn send_replacement_operation(
+        &self,
+        _: OperationId,
+        _: OperationInput,
+    ) -> RpcResult<OperationReplacement> {
+        crate::wrong_api::<OperationReplacement>()
+    }
+
     async fn get_filtered_sc_output_event(&self, _: EventFilter) -> RpcResult<Vec<SCOutputEvent>> {
         crate::wrong_api::<Vec<SCOutputEvent>>()
     }
 
+    async fn get_filtered_operations(&self, _: QueryFilter) -> RpcResult<Vec<OperationInfo>> {
+        crate::wrong_api::<Vec<OperationInfo>>()
+    }
+
+    async fn get_filtered_events(&self, _: QueryFilter) -> RpcResult<Vec<SCOutputEvent>> {
+        crate::wrong_api::<Vec<SCOutputEvent>>()
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
-        //TODO: Reinvoke
-        // let network_command_sender = self.0.network_command_sender.clone();
-        // match network_command_sender.get_peers().await {
-        //     Ok(peers) => Ok(peers.peers.into_keys().sorted().collect::<Vec<IpAddr>>()),
-        //     Err(e) => Err(ApiError::NetworkError(e).into()),
-        // }
-        return Err(
-            ApiError::BadRequest("This request is currently not available".to_string()).into(),
-        );
-    }
-
-    async fn node_add_to_peers_whitelist(&self, _ips: Vec<IpAddr>) -> RpcResult<()> {
-        //TODO: Readd in network refactoring
-        // let network_command_sender = self.0.network_command_sender.clone();
-        // network_command_sender
-        //     .add_to_whitelist(ips)
-        //     .await
-        //     .map_err(|e| ApiError::NetworkError(e).into())
-        return Err(
-            ApiError::BadRequest("This request is currently not available".to_string()).into(),
-        );
-    }
-
-    async fn node_remove_from_peers_whitelist(&self, _ips: Vec<IpAddr>) -> RpcResult<()> {
-        //TODO: Reinvoke
-        //TODO: Readd in network refactoring
-        // let network_command_sender = self.0.network_command_sender.clone();
-        // network_command_sender
-        //     .remove_from_whitelist(ips)
-        //     .await
-        //     .map_err(|e| ApiError::NetworkError(e).into())
-        return Err(
-            ApiError::BadRequest("This request is currently not available".to_string()).into(),
-        );
+        self.0
+            .protocol_controller
+            .get_peers_whitelist()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
+    }
+
+    async fn node_add_to_peers_whitelist(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
+        self.0
+            .protocol_controller
+            .add_to_peers_whitelist(ips)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
+    }
+
+    async fn node_remove_from_peers_whitelist(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
+        self.0
+            .protocol_controller
+            .remove_from_peers_whitelist(ips)
+            .map_err(|e| ApiError::ProtocolError(e.to_string()).into())
     }
 
     async fn node_bootstrap_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
@@ -356,6 +714,18 @@ impl MassaRpcServer for API<Private> {
     }
 }
 
+/// Converts a ban target of the API to a ban target of protocol
+fn to_ban_target(target: NodeBanTarget) -> Result<BanTarget, ApiError> {
+    match target {
+        NodeBanTarget::NodeId(node_id) => Ok(BanTarget::Peer(PeerId::from_public_key(
+            node_id.get_public_key(),
+        ))),
+        NodeBanTarget::Subnet(subnet) => IpSubnet::from_str(&subnet)
+            .map(BanTarget::Subnet)
+            .map_err(|e| ApiError::BadRequest(e.to_string())),
+    }
+}
+
 /// Run Search, Create, Read, Update, Delete operation on bootstrap list of IP(s)
 fn run_scrud_operation(
     bootstrap_list_file: PathBuf,
@@ -6,8 +6,8 @@ use async_trait::async_trait;
 use itertools::{izip, Itertools};
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
-    address::{AddressFilter, AddressInfo},
-    block::{BlockInfo, BlockInfoContent, BlockSummary},
+    address::{AddressFilter, AddressInfo, BalanceBreakdown, RewardsRequest},
+    block::{BlockInfo, BlockInfoContent, BlockSummary, NetworkLoad},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
@@ -15,21 +15,35 @@ use massa_api_exports::{
     execution::{
         ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyResult, Transfer,
     },
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    filter::{AddressRole, QueryFilter},
+    node::{
+        MemoryUsageInfo, NodeAddressLinkInfo, NodeAgent, NodeBan, NodeBanRequest, NodeBanTarget,
+        NodeKnowledgeInfo, NodeKnownBlocks, NodeLatency, NodeNetworkStats, NodePeerContribution,
+        NodePeerRecord, NodePeerRecordEdit, NodeStatus,
+    },
+    operation::{
+        OperationCancellationInfo, OperationCancellationStatus, OperationInclusionProof,
+        OperationInfo, OperationInput, OperationReplacement,
+    },
     page::{PageRequest, PagedVec},
+    rolls::{StakerLiveness, StakingAddressActivation},
     slot::SlotAmount,
     TimeInterval,
 };
+use massa_consensus_exports::archive::BlockArchive;
 use massa_consensus_exports::block_status::DiscardReason;
 use massa_consensus_exports::ConsensusController;
+use massa_consensus_exports::{
+    dependencies::BlockDependencies, equivocation::EquivocationEvidence,
+    parent_selection::ParentSelectionDiagnostics,
+};
 use massa_execution_exports::{
     ExecutionController, ExecutionQueryRequest, ExecutionQueryRequestItem,
     ExecutionQueryResponseItem, ExecutionStackElement, ReadOnlyExecutionRequest,
     ReadOnlyExecutionTarget,
 };
 use massa_models::{
-    address::Address,
+    address::{Address, AddressCycleRewards},
     amount::Amount,
     block::{Block, BlockGraphStatus},
     block_id::BlockId,
@@ -41,6 +55,8 @@ use massa_models::{
     endorsement::SecureShareEndorsement,
     error::ModelsError,
     execution::EventFilter,
+    heartbeat::Heartbeat,
+    name_registry::{check_name, name_registry_address, NameRecord, NameRecordDeserializer},
     node::NodeId,
     operation::OperationDeserializer,
     operation::OperationId,
@@ -54,8 +70,10 @@ use massa_models::{
     version::Version,
 };
 use massa_pool_exports::PoolController;
-use massa_pos_exports::SelectorController;
-use massa_protocol_exports::{PeerConnectionType, ProtocolConfig, ProtocolController};
+use massa_pos_exports::{CycleDrawAudit, PosError, SelectorController};
+use massa_protocol_exports::{
+    feature_adoption_warnings, PeerConnectionType, ProtocolConfig, ProtocolController,
+};
 use massa_serialization::{DeserializeError, Deserializer};
 use massa_storage::Storage;
 use massa_time::MassaTime;
@@ -63,8 +81,10 @@ use massa_versioning::versioning_factory::FactoryStrategy;
 use massa_versioning::{
     keypair_factory::KeyPairFactory, versioning::MipStore, versioning_factory::VersioningFactory,
 };
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 impl API<Public> {
     /// generate a new public API
@@ -80,6 +100,8 @@ impl API<Public> {
         node_id: NodeId,
         storage: Storage,
         mip_store: MipStore,
+        maintenance_mode: Arc<AtomicBool>,
+        block_archive: Option<BlockArchive>,
     ) -> Self {
         API(Public {
             consensus_controller,
@@ -93,8 +115,105 @@ impl API<Public> {
             protocol_config,
             storage,
             keypair_factory: KeyPairFactory { mip_store },
+            maintenance_mode,
+            block_archive,
         })
     }
+
+    /// get the current cycle
+    fn get_current_cycle(&self) -> Result<u64, ApiError> {
+        let cfg = &self.0.api_settings;
+        match get_latest_block_slot_at_timestamp(
+            cfg.thread_count,
+            cfg.t0,
+            cfg.genesis_timestamp,
+            MassaTime::now(),
+        ) {
+            Ok(Some(cur_slot)) if cur_slot.period <= cfg.last_start_period => {
+                Ok(Slot::new(cfg.last_start_period, 0).get_cycle(cfg.periods_per_cycle))
+            }
+            Ok(Some(cur_slot)) => Ok(cur_slot.get_cycle(cfg.periods_per_cycle)),
+            Ok(None) => Ok(0),
+            Err(e) => Err(ApiError::ModelsError(e)),
+        }
+    }
+
+    /// Get the creator and the cancellation status of operations, from the node storage or the block archive
+    fn get_operations_cancellation_info(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Result<Vec<(Option<Address>, OperationCancellationInfo)>, ApiError> {
+        let cfg = &self.0.api_settings;
+        let last_slot = get_latest_block_slot_at_timestamp(
+            cfg.thread_count,
+            cfg.t0,
+            cfg.genesis_timestamp,
+            MassaTime::now(),
+        )
+        .map_err(ApiError::ModelsError)?;
+        let in_pool = self
+            .0
+            .pool_command_sender
+            .contains_operations(operation_ids);
+        let exec_statuses = self
+            .0
+            .execution_controller
+            .get_ops_exec_status(operation_ids);
+
+        let read_ops = self.0.storage.read_operations();
+        let read_blocks = self.0.storage.read_blocks();
+        let mut res = Vec::with_capacity(operation_ids.len());
+        for (id, in_pool, (candidate_exec, final_exec)) in
+            izip!(operation_ids, in_pool, exec_statuses)
+        {
+            let mut operation = read_ops.get(id).cloned();
+            let mut in_blocks: Vec<BlockId> = read_blocks
+                .get_blocks_by_operation(id)
+                .map(|ids| ids.iter().copied().collect())
+                .unwrap_or_default();
+            // the operations pruned from storage may still be in the archive, in final blocks
+            let mut archived = false;
+            if operation.is_none() {
+                if let Some(block_archive) = &self.0.block_archive {
+                    operation = block_archive.get_operation(id);
+                    in_blocks = block_archive
+                        .get_blocks_by_operation(id)
+                        .into_iter()
+                        .collect();
+                    archived = operation.is_some();
+                }
+            }
+            in_blocks.sort_unstable();
+
+            let expire_slot = operation
+                .as_ref()
+                .map(|op| op.get_expire_slot(cfg.thread_count));
+            let status = if final_exec.is_some() || (archived && !in_blocks.is_empty()) {
+                OperationCancellationStatus::Final
+            } else if candidate_exec.is_some() || !in_blocks.is_empty() {
+                OperationCancellationStatus::Included
+            } else if expire_slot
+                .zip(last_slot)
+                .is_some_and(|(expire_slot, last_slot)| expire_slot <= last_slot)
+            {
+                OperationCancellationStatus::Expired
+            } else if in_pool || operation.is_some() {
+                OperationCancellationStatus::Pending
+            } else {
+                OperationCancellationStatus::Unknown
+            };
+            res.push((
+                operation.map(|op| op.content_creator_address),
+                OperationCancellationInfo {
+                    id: *id,
+                    status,
+                    expire_slot,
+                    in_blocks,
+                },
+            ));
+        }
+        Ok(res)
+    }
 }
 
 #[async_trait]
@@ -434,6 +553,10 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<PreHashSet<Address>>()
     }
 
+    async fn get_staking_addresses_activation(&self) -> RpcResult<Vec<StakingAddressActivation>> {
+        crate::wrong_api::<Vec<StakingAddressActivation>>()
+    }
+
     async fn node_ban_by_ip(&self, _: Vec<IpAddr>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -450,13 +573,61 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    async fn node_ban(&self, _: NodeBanRequest) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_unban(&self, _: Vec<NodeBanTarget>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_ban_list(&self) -> RpcResult<Vec<NodeBan>> {
+        crate::wrong_api::<Vec<NodeBan>>()
+    }
+
+    async fn node_peer_records(&self, _: Vec<NodeId>) -> RpcResult<Vec<NodePeerRecord>> {
+        crate::wrong_api::<Vec<NodePeerRecord>>()
+    }
+
+    async fn node_edit_peer_records(&self, _: Vec<NodePeerRecordEdit>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_remove_peer_records(&self, _: Vec<NodeId>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_known_blocks(&self) -> RpcResult<Vec<NodeKnownBlocks>> {
+        crate::wrong_api::<Vec<NodeKnownBlocks>>()
+    }
+
+    async fn node_knowledge(&self) -> RpcResult<Vec<NodeKnowledgeInfo>> {
+        crate::wrong_api::<Vec<NodeKnowledgeInfo>>()
+    }
+
+    async fn node_peer_contributions(&self) -> RpcResult<Vec<NodePeerContribution>> {
+        crate::wrong_api::<Vec<NodePeerContribution>>()
+    }
+
+    async fn node_network_stats(&self) -> RpcResult<NodeNetworkStats> {
+        crate::wrong_api::<NodeNetworkStats>()
+    }
+
+    async fn node_memory_usage(&self) -> RpcResult<Vec<MemoryUsageInfo>> {
+        crate::wrong_api::<Vec<MemoryUsageInfo>>()
+    }
+
+    async fn node_set_maintenance_mode(&self, _: bool) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
     /// get status
     async fn get_status(&self) -> RpcResult<NodeStatus> {
         let version = self.0.version;
         let api_settings = self.0.api_settings.clone();
         let protocol_config = self.0.protocol_config.clone();
         let node_id = self.0.node_id;
-        let config = CompactConfig::default();
+        let mut config = CompactConfig::default();
         let now = MassaTime::now();
 
         let last_slot_result = get_latest_block_slot_at_timestamp(
@@ -495,6 +666,8 @@ impl MassaRpcServer for API<Public> {
             Ok(next_slot) => next_slot,
             Err(e) => return Err(ApiError::ModelsError(e).into()),
         };
+        let chain_parameters = api_settings.chain_parameters.active_at(next_slot);
+        config.max_block_size = chain_parameters.parameters.max_block_size;
 
         let connected_nodes = peers
             .iter()
@@ -507,6 +680,49 @@ impl MassaRpcServer for API<Public> {
             })
             .collect::<BTreeMap<_, _>>();
 
+        // informative only: the status is still returned if the agents are unavailable
+        let peer_agents = self
+            .0
+            .protocol_controller
+            .get_peer_agents()
+            .unwrap_or_default();
+        let protocol_feature_warnings =
+            feature_adoption_warnings(peer_agents.values(), &protocol_config);
+        let net_split_warning = self
+            .0
+            .protocol_controller
+            .get_net_split_alert()
+            .unwrap_or_default()
+            .map(|alert| alert.to_string());
+        let connected_nodes_agents = peer_agents
+            .into_iter()
+            .map(|(id, agent)| {
+                (
+                    NodeId::new(id.get_public_key()),
+                    NodeAgent {
+                        version: agent.version,
+                        user_agent: agent.user_agent,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let connected_nodes_latencies = self
+            .0
+            .protocol_controller
+            .get_peer_latencies()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, latency)| {
+                (
+                    NodeId::new(id.get_public_key()),
+                    NodeLatency {
+                        rtt: latency.rtt,
+                        jitter: latency.jitter,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+
         let current_cycle = last_slot
             .unwrap_or_else(|| Slot::new(0, 0))
             .get_cycle(api_settings.periods_per_cycle);
@@ -546,8 +762,14 @@ impl MassaRpcServer for API<Public> {
             current_cycle_time,
             next_cycle_time,
             connected_nodes,
+            connected_nodes_agents,
+            connected_nodes_latencies,
+            maintenance_mode: self.0.maintenance_mode.load(Ordering::Relaxed),
+            protocol_feature_warnings,
+            net_split_warning,
             last_slot,
             next_slot,
+            chain_parameters_version: chain_parameters.version,
             execution_stats,
             consensus_stats,
             network_stats,
@@ -569,25 +791,7 @@ impl MassaRpcServer for API<Public> {
         &self,
         page_request: Option<PageRequest>,
     ) -> RpcResult<PagedVec<(Address, u64)>> {
-        let cfg = self.0.api_settings.clone();
-
-        let now = MassaTime::now();
-
-        let latest_block_slot_at_timestamp_result = get_latest_block_slot_at_timestamp(
-            cfg.thread_count,
-            cfg.t0,
-            cfg.genesis_timestamp,
-            now,
-        );
-
-        let curr_cycle = match latest_block_slot_at_timestamp_result {
-            Ok(Some(cur_slot)) if cur_slot.period <= self.0.api_settings.last_start_period => {
-                Slot::new(self.0.api_settings.last_start_period, 0).get_cycle(cfg.periods_per_cycle)
-            }
-            Ok(Some(cur_slot)) => cur_slot.get_cycle(cfg.periods_per_cycle),
-            Ok(None) => 0,
-            Err(e) => return Err(ApiError::ModelsError(e).into()),
-        };
+        let curr_cycle = self.get_current_cycle()?;
 
         let mut staker_vec = self
             .0
@@ -604,6 +808,205 @@ impl MassaRpcServer for API<Public> {
         Ok(paged_vec)
     }
 
+    async fn get_stakers_liveness(
+        &self,
+        page_request: Option<PageRequest>,
+    ) -> RpcResult<PagedVec<StakerLiveness>> {
+        let curr_cycle = self.get_current_cycle()?;
+
+        let heartbeats: PreHashMap<Address, Heartbeat> = self
+            .0
+            .protocol_controller
+            .get_staker_heartbeats()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?
+            .into_iter()
+            .map(|heartbeat| (heartbeat.content_creator_address, heartbeat.content))
+            .collect();
+
+        let mut stakers: Vec<StakerLiveness> = self
+            .0
+            .execution_controller
+            .get_cycle_active_rolls(curr_cycle)
+            .into_iter()
+            .map(|(address, active_rolls)| {
+                let heartbeat = heartbeats.get(&address);
+                StakerLiveness {
+                    address,
+                    active_rolls,
+                    last_heartbeat: heartbeat.map(|h| h.timestamp),
+                    last_produced_slot: heartbeat.and_then(|h| h.last_produced_slot),
+                }
+            })
+            .collect();
+
+        stakers.sort_by(|a, b| b.active_rolls.cmp(&a.active_rolls));
+
+        Ok(PagedVec::new(stakers, page_request))
+    }
+
+    async fn get_node_address_links(&self) -> RpcResult<Vec<NodeAddressLinkInfo>> {
+        let connected_nodes: HashSet<NodeId> = self
+            .0
+            .protocol_controller
+            .get_stats()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?
+            .1
+            .keys()
+            .map(|peer_id| NodeId::new(peer_id.get_public_key()))
+            .collect();
+
+        let mut links: Vec<NodeAddressLinkInfo> = self
+            .0
+            .protocol_controller
+            .get_node_address_links()
+            .map_err(|e| ApiError::ProtocolError(e.to_string()))?
+            .into_iter()
+            .map(|proof| NodeAddressLinkInfo {
+                address: proof.link.content_creator_address,
+                node_id: proof.link.content.node_id,
+                timestamp: proof.link.content.timestamp,
+                connected: connected_nodes.contains(&proof.link.content.node_id),
+            })
+            .collect();
+
+        links.sort_by(|a, b| a.address.cmp(&b.address));
+
+        Ok(links)
+    }
+
+    /// get equivocations
+    async fn get_equivocations(&self) -> RpcResult<Vec<EquivocationEvidence>> {
+        Ok(self.0.consensus_controller.get_equivocations())
+    }
+
+    /// get the blocks waiting for dependencies
+    async fn get_block_dependencies(&self) -> RpcResult<BlockDependencies> {
+        Ok(self.0.consensus_controller.get_block_dependencies())
+    }
+
+    /// get the diagnostics of the parent selection
+    async fn get_parent_selection(&self) -> RpcResult<ParentSelectionDiagnostics> {
+        self.0
+            .consensus_controller
+            .get_parent_selection()
+            .map_err(|e| ApiError::ConsensusError(e.to_string()).into())
+    }
+
+    /// get the draw audit of a cycle
+    async fn get_cycle_draw_audit(&self, cycle: u64) -> RpcResult<CycleDrawAudit> {
+        match self.0.selector_controller.get_cycle_draw_audit(cycle) {
+            Ok(audit) => Ok(audit),
+            Err(PosError::CycleUnavailable(_)) => Err(ApiError::NotFound.into()),
+            Err(err) => Err(ApiError::InconsistencyError(err.to_string()).into()),
+        }
+    }
+
+    /// get a proof of inclusion of an operation, preferably in a final block
+    async fn get_operation_inclusion_proof(
+        &self,
+        operation_id: OperationId,
+    ) -> RpcResult<OperationInclusionProof> {
+        let block_ids: Vec<BlockId> = self
+            .0
+            .storage
+            .read_blocks()
+            .get_blocks_by_operation(&operation_id)
+            .map(|ids| ids.iter().copied().collect())
+            .unwrap_or_default();
+        let block_statuses = self.0.consensus_controller.get_block_statuses(&block_ids);
+        let Some((block_id, status)) = block_ids
+            .into_iter()
+            .zip(block_statuses)
+            .filter(|(_, status)| *status != BlockGraphStatus::Discarded)
+            .max_by_key(|(id, status)| {
+                (
+                    *status == BlockGraphStatus::Final,
+                    *status == BlockGraphStatus::ActiveInBlockclique,
+                    *id,
+                )
+            })
+        else {
+            return Err(ApiError::NotFound.into());
+        };
+
+        let read_blocks = self.0.storage.read_blocks();
+        let Some(block) = read_blocks.get(&block_id) else {
+            return Err(ApiError::NotFound.into());
+        };
+        let Some(index) = block
+            .content
+            .operations
+            .iter()
+            .position(|id| *id == operation_id)
+        else {
+            return Err(ApiError::InconsistencyError(format!(
+                "operation {} is indexed in block {} but not part of it",
+                operation_id, block_id
+            ))
+            .into());
+        };
+        Ok(OperationInclusionProof {
+            operation_id,
+            header: block.content.header.clone(),
+            index,
+            operation_ids: block.content.operations.clone(),
+            is_final: status == BlockGraphStatus::Final,
+        })
+    }
+
+    /// resolve a name of the name registry
+    async fn resolve_name(&self, name: String) -> RpcResult<Option<NameRecord>> {
+        check_name(&name).map_err(|err| ApiError::BadRequest(err.to_string()))?;
+        let response = self
+            .0
+            .execution_controller
+            .query_state(ExecutionQueryRequest {
+                requests: vec![ExecutionQueryRequestItem::AddressDatastoreValue {
+                    addr: name_registry_address(),
+                    key: name.into_bytes(),
+                }],
+            })
+            .responses
+            .pop();
+        let bytes = match response {
+            Some(Ok(ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(values))) => {
+                values.candidate_value
+            }
+            Some(Err(err)) => return Err(ApiError::InternalServerError(err.to_string()).into()),
+            _ => {
+                return Err(
+                    ApiError::InternalServerError("unexpected response type".to_string()).into(),
+                )
+            }
+        };
+        let Some(bytes) = bytes else {
+            return Ok(None);
+        };
+        let (_, record) = NameRecordDeserializer::new()
+            .deserialize::<DeserializeError>(&bytes)
+            .map_err(|err| ApiError::InconsistencyError(err.to_string()))?;
+        let api_cfg = &self.0.api_settings;
+        let period = get_latest_block_slot_at_timestamp(
+            api_cfg.thread_count,
+            api_cfg.t0,
+            api_cfg.genesis_timestamp,
+            MassaTime::now(),
+        )
+        .map_err(ApiError::ModelsError)?
+        .map_or(0, |slot| slot.period);
+        Ok((!record.is_expired(period)).then_some(record))
+    }
+
+    async fn get_rewards(&self, req: RewardsRequest) -> RpcResult<Vec<AddressCycleRewards>> {
+        if req.cycles.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        Ok(self
+            .0
+            .execution_controller
+            .get_address_rewards(&req.address, &req.cycles))
+    }
+
     /// get operations
     async fn get_operations(
         &self,
@@ -618,7 +1021,7 @@ impl MassaRpcServer for API<Public> {
                 .collect()
         };
 
-        let storage_info: Vec<(SecureShareOperation, PreHashSet<BlockId>)> = {
+        let mut storage_info: Vec<(SecureShareOperation, PreHashSet<BlockId>)> = {
             let read_blocks = self.0.storage.read_blocks();
             secure_share_operations
                 .into_iter()
@@ -635,6 +1038,17 @@ impl MassaRpcServer for API<Public> {
                 .collect()
         };
 
+        // the operations pruned from storage may still be in the archive
+        if let Some(block_archive) = &self.0.block_archive {
+            let found: PreHashSet<OperationId> = storage_info.iter().map(|(op, _)| op.id).collect();
+            for op_id in operations_ids.iter().filter(|id| !found.contains(id)) {
+                if let Some(operation) = block_archive.get_operation(op_id) {
+                    let in_blocks = block_archive.get_blocks_by_operation(op_id);
+                    storage_info.push((operation, in_blocks));
+                }
+            }
+        }
+
         // keep only the ops id (found in storage)
         let ops: Vec<OperationId> = storage_info.iter().map(|(op, _)| op.id).collect();
 
@@ -715,6 +1129,21 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    /// get whether operations can still be included
+    async fn get_operations_cancellation_status(
+        &self,
+        operation_ids: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationCancellationInfo>> {
+        if operation_ids.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        Ok(self
+            .get_operations_cancellation_info(&operation_ids)?
+            .into_iter()
+            .map(|(_, info)| info)
+            .collect())
+    }
+
     /// get endorsements
     async fn get_endorsements(
         &self,
@@ -813,6 +1242,7 @@ impl MassaRpcServer for API<Public> {
     /// Returns only active blocks are returned
     async fn get_blocks(&self, mut ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
         let mut blocks: Vec<Block> = Vec::with_capacity(ids.len());
+        let mut missing_ids: Vec<BlockId> = Vec::new();
         {
             let block_storage_lock = self.0.storage.read_blocks();
             ids.retain(|id| {
@@ -820,11 +1250,12 @@ impl MassaRpcServer for API<Public> {
                     blocks.push(wrapped_block.content.clone());
                     return true;
                 };
+                missing_ids.push(*id);
                 false
             });
         }
         let block_statuses = self.0.consensus_controller.get_block_statuses(&ids);
-        let res = ids
+        let mut res: Vec<BlockInfo> = ids
             .into_iter()
             .zip(blocks)
             .zip(block_statuses)
@@ -840,6 +1271,22 @@ impl MassaRpcServer for API<Public> {
                 }),
             })
             .collect();
+
+        // the blocks pruned from storage may still be in the archive, and archived blocks are final
+        if let Some(block_archive) = &self.0.block_archive {
+            res.extend(missing_ids.into_iter().filter_map(|id| {
+                block_archive.get_block(&id).map(|block| BlockInfo {
+                    id,
+                    content: Some(BlockInfoContent {
+                        is_final: true,
+                        is_in_blockclique: false,
+                        is_candidate: false,
+                        is_discarded: false,
+                        block: block.content,
+                    }),
+                })
+            }));
+        }
         Ok(res)
     }
 
@@ -924,32 +1371,141 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    /// get the load of the network over the blockclique blocks of the latest periods
+    async fn get_network_load(&self) -> RpcResult<NetworkLoad> {
+        let api_settings = &self.0.api_settings;
+
+        let end_slot = get_latest_block_slot_at_timestamp(
+            api_settings.thread_count,
+            api_settings.t0,
+            api_settings.genesis_timestamp,
+            MassaTime::now(),
+        )
+        .map_err(ApiError::ModelsError)?
+        .ok_or(ApiError::NotFound)?;
+
+        // walk back the latest periods, without going before genesis
+        let max_slot_count = api_settings
+            .network_load_periods
+            .saturating_mul(api_settings.thread_count as u64)
+            .max(1);
+        let mut slots = vec![end_slot];
+        while (slots.len() as u64) < max_slot_count {
+            match slots[slots.len() - 1].get_prev_slot(api_settings.thread_count) {
+                Ok(slot) => slots.push(slot),
+                Err(_) => break,
+            }
+        }
+        let start_slot = slots[slots.len() - 1];
+
+        let block_ids: Vec<BlockId> = slots
+            .iter()
+            .filter_map(|slot| {
+                self.0
+                    .consensus_controller
+                    .get_blockclique_block_at_slot(*slot)
+            })
+            .collect();
+
+        let mut block_count: u64 = 0;
+        let mut operation_count: u64 = 0;
+        let mut size_usage = 0.0;
+        let mut gas_usage = 0.0;
+        let mut total_fee = Amount::zero();
+        {
+            let read_blocks = self.0.storage.read_blocks();
+            let read_ops = self.0.storage.read_operations();
+            for block_id in &block_ids {
+                let Some(block) = read_blocks.get(block_id) else {
+                    continue;
+                };
+                block_count += 1;
+                let parameters = api_settings
+                    .chain_parameters
+                    .parameters_at(block.content.header.content.slot);
+                let mut block_size: usize = 0;
+                let mut block_gas: u64 = 0;
+                for op in block
+                    .content
+                    .operations
+                    .iter()
+                    .filter_map(|op_id| read_ops.get(op_id))
+                {
+                    operation_count += 1;
+                    block_size = block_size.saturating_add(op.serialized_size());
+                    block_gas = block_gas.saturating_add(op.get_gas_usage(
+                        parameters.base_operation_gas_cost,
+                        api_settings.sp_compilation_cost,
+                    ));
+                    total_fee = total_fee.saturating_add(op.content.fee);
+                }
+                size_usage += block_size as f64 / parameters.max_block_size as f64;
+                gas_usage += block_gas as f64 / parameters.max_gas_per_block as f64;
+            }
+        }
+        if block_count > 0 {
+            size_usage /= block_count as f64;
+            gas_usage /= block_count as f64;
+        }
+        let average_fee = total_fee
+            .checked_div_u64(operation_count)
+            .unwrap_or_default();
+
+        Ok(NetworkLoad {
+            start_slot,
+            end_slot,
+            slot_count: slots.len() as u64,
+            block_count,
+            operation_count,
+            size_usage,
+            gas_usage,
+            average_fee,
+            load: size_usage.max(gas_usage),
+        })
+    }
+
     /// get datastore entries
     async fn get_datastore_entries(
         &self,
         entries: Vec<DatastoreEntryInput>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>> {
-        Ok(self
+        let queries = entries
+            .into_iter()
+            .map(|input| ExecutionQueryRequestItem::AddressDatastoreValue {
+                addr: input.address,
+                key: input.key,
+            })
+            .collect::<Vec<_>>();
+
+        let responses = self
             .0
             .execution_controller
-            .get_final_and_active_data_entry(
-                entries
-                    .into_iter()
-                    .map(|input| (input.address, input.key))
-                    .collect::<Vec<_>>(),
-            )
+            .query_state(ExecutionQueryRequest { requests: queries })
+            .responses;
+
+        let res: Result<Vec<DatastoreEntryOutput>, ApiError> = responses
             .into_iter()
-            .map(|output| DatastoreEntryOutput {
-                final_value: output.0,
-                candidate_value: output.1,
+            .map(|value| match value {
+                Ok(ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(values)) => {
+                    Ok(DatastoreEntryOutput {
+                        final_value: values.final_value,
+                        candidate_value: values.candidate_value,
+                    })
+                }
+                Ok(_) => Err(ApiError::InternalServerError(
+                    "unexpected response type".to_string(),
+                )),
+                Err(err) => Err(ApiError::InternalServerError(err.to_string())),
             })
-            .collect())
+            .collect();
+
+        Ok(res?)
     }
 
     /// get addresses
     async fn get_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         // get info from storage about which blocks the addresses have created
-        let created_blocks: Vec<PreHashSet<BlockId>> = {
+        let mut created_blocks: Vec<PreHashSet<BlockId>> = {
             let lck = self.0.storage.read_blocks();
             addresses
                 .iter()
@@ -962,7 +1518,7 @@ impl MassaRpcServer for API<Public> {
         };
 
         // get info from storage about which operations the addresses have created
-        let created_operations: Vec<PreHashSet<OperationId>> = {
+        let mut created_operations: Vec<PreHashSet<OperationId>> = {
             let lck = self.0.storage.read_operations();
             addresses
                 .iter()
@@ -974,6 +1530,18 @@ impl MassaRpcServer for API<Public> {
                 .collect()
         };
 
+        // add the blocks and operations pruned from storage that are in the archive
+        if let Some(block_archive) = &self.0.block_archive {
+            for (address, blocks, operations) in izip!(
+                addresses.iter(),
+                created_blocks.iter_mut(),
+                created_operations.iter_mut()
+            ) {
+                blocks.extend(block_archive.get_blocks_created_by(address));
+                operations.extend(block_archive.get_operations_created_by(address));
+            }
+        }
+
         // get info from storage about which endorsements the addresses have created
         let created_endorsements: Vec<PreHashSet<EndorsementId>> = {
             let lck = self.0.storage.read_endorsements();
@@ -1069,6 +1637,28 @@ impl MassaRpcServer for API<Public> {
             (next_block_draws, next_endorsement_draws),
         ) in iterator
         {
+            let deferred_credits = execution_infos
+                .future_deferred_credits
+                .into_iter()
+                .map(|(slot, amount)| SlotAmount { slot, amount })
+                .collect::<Vec<_>>();
+            let final_deferred_credits = execution_infos
+                .final_future_deferred_credits
+                .into_iter()
+                .map(|(slot, amount)| SlotAmount { slot, amount })
+                .collect::<Vec<_>>();
+            let final_balance_breakdown = BalanceBreakdown::new(
+                execution_infos.final_balance,
+                execution_infos.final_roll_count,
+                self.0.api_settings.roll_price,
+                &final_deferred_credits,
+            );
+            let candidate_balance_breakdown = BalanceBreakdown::new(
+                execution_infos.candidate_balance,
+                execution_infos.candidate_roll_count,
+                self.0.api_settings.roll_price,
+                &deferred_credits,
+            );
             res.push(AddressInfo {
                 // general address info
                 address,
@@ -1090,12 +1680,10 @@ impl MassaRpcServer for API<Public> {
                     .into_iter()
                     .collect::<Vec<_>>(),
 
-                // deferred credits
-                deferred_credits: execution_infos
-                    .future_deferred_credits
-                    .into_iter()
-                    .map(|(slot, amount)| SlotAmount { slot, amount })
-                    .collect::<Vec<_>>(),
+                // deferred credits and balance breakdowns
+                deferred_credits,
+                final_balance_breakdown,
+                candidate_balance_breakdown,
 
                 // selector info
                 next_block_draws,
@@ -1117,14 +1705,8 @@ impl MassaRpcServer for API<Public> {
     /// get addresses bytecode
     async fn get_addresses_bytecode(&self, args: Vec<AddressFilter>) -> RpcResult<Vec<Vec<u8>>> {
         let queries = args
-            .into_iter()
-            .map(|arg| {
-                if arg.is_final {
-                    ExecutionQueryRequestItem::AddressBytecodeFinal(arg.address)
-                } else {
-                    ExecutionQueryRequestItem::AddressBytecodeCandidate(arg.address)
-                }
-            })
+            .iter()
+            .map(|arg| ExecutionQueryRequestItem::AddressBytecode(arg.address))
             .collect::<Vec<_>>();
 
         if queries.is_empty() {
@@ -1143,13 +1725,21 @@ impl MassaRpcServer for API<Public> {
 
         let res: Result<Vec<Vec<u8>>, ApiError> = responses
             .into_iter()
-            .map(|value| match value {
-                Ok(item) => match item {
-                    ExecutionQueryResponseItem::Bytecode(bytecode) => Ok(bytecode.0),
-                    _ => Err(ApiError::InternalServerError(
-                        "unexpected response type".to_string(),
-                    )),
-                },
+            .zip(args)
+            .map(|(value, arg)| match value {
+                Ok(ExecutionQueryResponseItem::BytecodeFinalAndCandidate(bytecodes)) => {
+                    let bytecode = if arg.is_final {
+                        bytecodes.final_value
+                    } else {
+                        bytecodes.candidate_value
+                    };
+                    bytecode
+                        .map(|bytecode| bytecode.0)
+                        .ok_or(ApiError::NotFound)
+                }
+                Ok(_) => Err(ApiError::InternalServerError(
+                    "unexpected response type".to_string(),
+                )),
                 Err(err) => Err(ApiError::InternalServerError(err.to_string())),
             })
             .collect();
@@ -1198,6 +1788,23 @@ impl MassaRpcServer for API<Public> {
                         Ok(()) => (),
                         Err(e) => return Err(ApiError::ModelsError(e).into()),
                     };
+                    if let Err(e) =
+                        operation.verify_fee_payer(api_cfg.chain_id, api_cfg.thread_count)
+                    {
+                        return Err(ApiError::ModelsError(e).into());
+                    }
+                    if !self
+                        .0
+                        .keypair_factory
+                        .mip_store
+                        .is_operation_format_active_at(&operation.content, now)
+                    {
+                        return Err(ApiError::BadRequest(format!(
+                            "version {} of the operation format is not active yet",
+                            operation.content.get_format_version()
+                        ))
+                        .into());
+                    }
                     Ok(operation)
                 }
                 Err(e) => Err(e),
@@ -1217,6 +1824,47 @@ impl MassaRpcServer for API<Public> {
         Ok(ids)
     }
 
+    /// send a replacement of an operation that is not final yet
+    async fn send_replacement_operation(
+        &self,
+        replaced: OperationId,
+        replacement: OperationInput,
+    ) -> RpcResult<OperationReplacement> {
+        let Some((creator, replaced_info)) =
+            self.get_operations_cancellation_info(&[replaced])?.pop()
+        else {
+            return Err(ApiError::NotFound.into());
+        };
+        let Some(creator) = creator else {
+            return Err(ApiError::NotFound.into());
+        };
+        if replaced_info.status == OperationCancellationStatus::Final {
+            return Err(ApiError::BadRequest(format!(
+                "operation {} is already final and cannot be replaced",
+                replaced
+            ))
+            .into());
+        }
+        if Address::from_public_key(&replacement.creator_public_key) != creator {
+            return Err(ApiError::BadRequest(format!(
+                "the replacement is not sent by the sender of operation {}",
+                replaced
+            ))
+            .into());
+        }
+
+        let Some(replacement_id) = self.send_operations(vec![replacement]).await?.pop() else {
+            return Err(ApiError::InternalServerError("replacement not sent".to_string()).into());
+        };
+        // without a nonce shared by both operations, nothing prevents the replaced one from being executed too
+        let advisory = replaced_info.status != OperationCancellationStatus::Expired;
+        Ok(OperationReplacement {
+            replaced: replaced_info,
+            replacement_id,
+            advisory,
+        })
+    }
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -1236,6 +1884,129 @@ impl MassaRpcServer for API<Public> {
         Ok(events)
     }
 
+    async fn get_filtered_operations(&self, filter: QueryFilter) -> RpcResult<Vec<OperationInfo>> {
+        let operation_ids: Vec<OperationId> = {
+            let read_ops = self.0.storage.read_operations();
+            let read_blocks = self.0.storage.read_blocks();
+
+            // use the creator index or the slot index to get the candidate operations
+            let candidates: PreHashSet<OperationId> = match (filter.address, filter.role) {
+                (Some(address), Some(AddressRole::Sender | AddressRole::Creator)) => read_ops
+                    .get_operations_created_by(&address)
+                    .cloned()
+                    .unwrap_or_default(),
+                _ if filter.has_slot_range() => read_blocks
+                    .aggregate_blocks_by_slot_range(filter.slot_range())
+                    .iter()
+                    .filter_map(|block_id| read_blocks.get(block_id))
+                    .flat_map(|block| block.content.operations.iter().copied())
+                    .collect(),
+                _ => {
+                    return Err(ApiError::BadRequest(
+                        "the filter must either target the sender or creator of the operations, or restrict the slot range".into(),
+                    )
+                    .into())
+                }
+            };
+
+            // evaluate the rest of the filter
+            candidates
+                .into_iter()
+                .filter(|id| {
+                    let Some(operation) = read_ops.get(id) else {
+                        return false;
+                    };
+                    if !filter.matches_operation_address(
+                        &operation.content_creator_address,
+                        &operation.content.op,
+                    ) || !filter.matches_operation_content(&operation.content.op)
+                    {
+                        return false;
+                    }
+                    !filter.has_slot_range()
+                        || read_blocks
+                            .get_blocks_by_operation(id)
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|block_id| read_blocks.get(block_id))
+                            .any(|block| filter.matches_slot(&block.content.header.content.slot))
+                })
+                .collect()
+        };
+
+        if operation_ids.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest(
+                "too many operations match the filter, please narrow it".into(),
+            )
+            .into());
+        }
+
+        let mut operations = self.get_operations(operation_ids).await?;
+        if let Some(is_final) = filter.is_final {
+            operations.retain(|op| op.is_operation_final == Some(is_final));
+        }
+        Ok(operations)
+    }
+
+    async fn get_filtered_events(&self, filter: QueryFilter) -> RpcResult<Vec<SCOutputEvent>> {
+        let base_filter = EventFilter {
+            start: filter.start,
+            end: filter.end,
+            is_final: filter.is_final,
+            ..Default::default()
+        };
+        let caller_filter = |address| EventFilter {
+            original_caller_address: Some(address),
+            ..base_filter.clone()
+        };
+        let emitter_filter = |address| EventFilter {
+            emitter_address: Some(address),
+            ..base_filter.clone()
+        };
+        let event_filters = match (filter.address, filter.role) {
+            (None, _) => vec![base_filter.clone()],
+            (Some(address), Some(AddressRole::Sender)) => vec![caller_filter(address)],
+            (Some(address), Some(AddressRole::Recipient | AddressRole::Creator)) => {
+                vec![emitter_filter(address)]
+            }
+            (Some(address), None) => vec![caller_filter(address), emitter_filter(address)],
+        };
+
+        // the same event can match several event filters
+        let mut seen = HashSet::new();
+        let mut events: Vec<SCOutputEvent> = event_filters
+            .into_iter()
+            .flat_map(|event_filter| {
+                self.0
+                    .execution_controller
+                    .get_filtered_sc_output_event(event_filter)
+            })
+            .filter(|event| {
+                seen.insert((
+                    event.context.slot,
+                    event.context.index_in_slot,
+                    event.context.is_final,
+                ))
+            })
+            .collect();
+        events.sort_by_key(|event| (event.context.slot, event.context.index_in_slot));
+
+        // match the operations that emitted the events
+        if filter.filters_operation_content() {
+            let read_ops = self.0.storage.read_operations();
+            events.retain(|event| {
+                event
+                    .context
+                    .origin_operation_id
+                    .and_then(|id| read_ops.get(&id))
+                    .map_or(false, |operation| {
+                        filter.matches_operation_content(&operation.content.op)
+                    })
+            });
+        }
+        Ok(events)
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         crate::wrong_api::<Vec<IpAddr>>()
     }
@@ -1341,29 +2112,37 @@ fn check_input_operation(
     let (rest, op): (&[u8], SecureShareOperation) = operation_deserializer
         .deserialize::<DeserializeError>(&op_serialized)
         .map_err(|err| ApiError::ModelsError(ModelsError::DeserializeError(err.to_string())))?;
+    // limits in force until the operation expires
+    let parameters = api_cfg
+        .chain_parameters
+        .parameters_at(last_slot.unwrap_or_else(|| Slot::new(0, 0)));
     match op.content.op {
         OperationType::CallSC { .. } => {
-            let gas_usage =
-                op.get_gas_usage(api_cfg.base_operation_gas_cost, api_cfg.sp_compilation_cost);
-            if gas_usage > api_cfg.max_gas_per_block {
+            let gas_usage = op.get_gas_usage(
+                parameters.base_operation_gas_cost,
+                api_cfg.sp_compilation_cost,
+            );
+            if gas_usage > parameters.max_gas_per_block {
                 let err_msg = format!("Upper gas limit for CallSC operation is {}. Your operation will never be included in a block.",
-                    api_cfg.max_gas_per_block.saturating_sub(api_cfg.base_operation_gas_cost));
+                    parameters.max_gas_per_block.saturating_sub(parameters.base_operation_gas_cost));
                 return Err(ApiError::InconsistencyError(err_msg).into());
             }
         }
         OperationType::ExecuteSC { .. } => {
-            let gas_usage =
-                op.get_gas_usage(api_cfg.base_operation_gas_cost, api_cfg.sp_compilation_cost);
-            if gas_usage > api_cfg.max_gas_per_block {
+            let gas_usage = op.get_gas_usage(
+                parameters.base_operation_gas_cost,
+                api_cfg.sp_compilation_cost,
+            );
+            if gas_usage > parameters.max_gas_per_block {
                 let err_msg = format!("Upper gas limit for ExecuteSC operation is {}. Your operation will never be included in a block.",
-                    api_cfg.max_gas_per_block.saturating_sub(api_cfg.base_operation_gas_cost).saturating_sub(api_cfg.sp_compilation_cost));
+                    parameters.max_gas_per_block.saturating_sub(parameters.base_operation_gas_cost).saturating_sub(api_cfg.sp_compilation_cost));
                 return Err(ApiError::InconsistencyError(err_msg).into());
             }
         }
         _ => {}
     };
     if let Some(slot) = last_slot {
-        if op.content.expire_period < slot.period {
+        if op.is_expired_at(&slot, api_cfg.thread_count) {
             return Err(
                 ApiError::InconsistencyError(
                     "Operation expire_period is lower than the current period of this node. Your operation will never be included in a block.".into()
@@ -1371,6 +2150,14 @@ fn check_input_operation(
             );
         }
     }
+    if matches!(op.content.start_period, Some(start_period) if start_period > op.content.expire_period)
+    {
+        return Err(
+            ApiError::InconsistencyError(
+                "Operation start_period is greater than its expire_period. Your operation will never be included in a block.".into()
+            ).into()
+        );
+    }
     if rest.is_empty() {
         Ok(op)
     } else {
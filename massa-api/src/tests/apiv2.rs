@@ -8,15 +8,22 @@ use jsonrpsee::{
     ws_client::WsClientBuilder,
 };
 use massa_consensus_exports::MockConsensusController;
-use massa_execution_exports::MockExecutionController;
+use massa_execution_exports::{
+    EventStore, ExecutionOutput, MockExecutionController, SlotExecutionOutput,
+};
+use massa_final_state::StateChanges;
+use massa_ledger_exports::{LedgerChanges, LedgerEntry, SetUpdateOrDelete};
 use massa_models::{
     address::Address,
+    amount::Amount,
     block::{FilledBlock, SecureShareBlock},
     block_header::BlockHeader,
     block_id::BlockId,
     config::VERSION,
     operation::SecureShareOperation,
+    prehash::PreHashMap,
     secure_share::SecureShare,
+    slot::Slot,
 };
 use massa_protocol_exports::test_exports::tools::{
     create_block, create_operation_with_expire_period,
@@ -333,3 +340,100 @@ async fn subscribe_new_operations() {
 
     api_handle.stop().await;
 }
+
+fn finalized_slot_output(slot: Slot, address: Address) -> SlotExecutionOutput {
+    let mut ledger_changes = PreHashMap::default();
+    ledger_changes.insert(
+        address,
+        SetUpdateOrDelete::Set(LedgerEntry {
+            balance: Amount::from_str("42").unwrap(),
+            ..Default::default()
+        }),
+    );
+    SlotExecutionOutput::FinalizedSlot(ExecutionOutput {
+        slot,
+        block_info: None,
+        state_changes: StateChanges {
+            ledger_changes: LedgerChanges(ledger_changes),
+            ..Default::default()
+        },
+        events: EventStore::default(),
+        #[cfg(feature = "execution-trace")]
+        slot_trace: None,
+        #[cfg(feature = "dump-block")]
+        storage: None,
+        deferred_credits_execution: vec![],
+        cancel_async_message_execution: vec![],
+        auto_sell_execution: vec![],
+    })
+}
+
+#[tokio::test]
+async fn subscribe_final_ledger_changes() {
+    let addr: SocketAddr = "[::]:5058".parse().unwrap();
+    let (mut api_server, api_config) = get_apiv2_server(&addr);
+
+    let uri = Url::parse(&format!(
+        "ws://localhost:{}",
+        addr.to_string().split(':').last().unwrap()
+    ))
+    .unwrap();
+    let (tx, _rx) = tokio::sync::broadcast::channel::<SlotExecutionOutput>(10);
+    api_server.0.execution_channels.slot_execution_output_sender = tx.clone();
+    let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+
+    let api_handle = api_server
+        .serve(&addr, &api_config)
+        .await
+        .expect("failed to start MASSA API V2");
+
+    // finalized before the subscription, kept in the history
+    tx.send(finalized_slot_output(Slot::new(1, 0), address))
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client1 = WsClientBuilder::default().build(&uri).await.unwrap();
+    let mut sub1: Subscription<Value> = client1
+        .subscribe(
+            "subscribe_final_ledger_changes",
+            rpc_params![Slot::new(1, 0)],
+            "unsubscribe_final_ledger_changes",
+        )
+        .await
+        .unwrap();
+
+    let replayed = tokio::time::timeout(Duration::from_secs(4), sub1.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(replayed["slot"]["period"], 1);
+    assert_eq!(replayed["slot"]["thread"], 0);
+    assert!(replayed["ledger_changes"]
+        .as_object()
+        .unwrap()
+        .contains_key(&address.to_string()));
+
+    // finalized after the subscription
+    tx.send(finalized_slot_output(Slot::new(1, 1), address))
+        .unwrap();
+    let live = tokio::time::timeout(Duration::from_secs(4), sub1.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(live["slot"]["thread"], 1);
+
+    // slots before the history window cannot be resumed from
+    let client2 = WsClientBuilder::default().build(&uri).await.unwrap();
+    let res: Result<Subscription<Value>, _> = client2
+        .subscribe(
+            "subscribe_final_ledger_changes",
+            rpc_params![Slot::new(0, 0)],
+            "unsubscribe_final_ledger_changes",
+        )
+        .await;
+    assert!(res.is_err());
+
+    api_handle.stop().await;
+}
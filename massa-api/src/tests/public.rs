@@ -13,16 +13,28 @@ use jsonrpsee::{
     rpc_params,
 };
 use massa_api_exports::{
-    address::{AddressFilter, AddressInfo},
-    block::{BlockInfo, BlockSummary},
+    address::{AddressFilter, AddressInfo, RewardsRequest},
+    block::{BlockInfo, BlockSummary, NetworkLoad},
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
+    error::{ApiErrorCategory, ApiErrorData},
     execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    operation::{OperationInfo, OperationInput},
+    filter::{AddressRole, OperationKind, QueryFilter},
+    node::NodeAddressLinkInfo,
+    operation::{
+        OperationCancellationInfo, OperationCancellationStatus, OperationInclusionProof,
+        OperationInfo, OperationInput, OperationReplacement,
+    },
+    rolls::StakerLiveness,
     TimeInterval,
 };
 use massa_consensus_exports::{
-    block_graph_export::BlockGraphExport, block_status::ExportCompiledBlock,
+    block_graph_export::BlockGraphExport,
+    block_status::ExportCompiledBlock,
+    dependencies::{BlockDependencies, BlockedBlock, WishedBlock},
+    parent_selection::{
+        ParentRejectionReason, ParentSelectionDiagnostics, RejectedParent, ThreadParentSelection,
+    },
     MockConsensusController,
 };
 use massa_pool_exports::MockPoolController;
@@ -30,31 +42,41 @@ use massa_pos_exports::MockSelectorController;
 
 use crate::{tests::mock::start_public_api, RpcServer};
 use massa_execution_exports::{
-    ExecutionAddressInfo, ExecutionQueryResponse, ExecutionQueryResponseItem,
-    MockExecutionController, ReadOnlyExecutionOutput,
+    ExecutionAddressInfo, ExecutionQueryFinalAndCandidate, ExecutionQueryRequestItem,
+    ExecutionQueryResponse, ExecutionQueryResponseItem, MockExecutionController,
+    ReadOnlyExecutionOutput,
 };
 use massa_models::{
-    address::Address,
+    address::{Address, AddressCycleRewards, AddressRewards},
     amount::Amount,
     block::{Block, BlockGraphStatus},
+    block_id::BlockId,
     bytecode::Bytecode,
     clique::Clique,
+    config::CHAINID,
     endorsement::EndorsementId,
     execution::EventFilter,
+    heartbeat::{Heartbeat, HeartbeatSerializer},
+    name_registry::{name_registry_address, NameRecord, NameRecordSerializer},
     node::NodeId,
+    node_link::{NodeAddressLink, NodeAddressLinkProof},
     operation::OperationId,
     output_event::SCOutputEvent,
     prehash::{CapacityAllocator, PreHashMap},
+    secure_share::SecureShareContent,
     slot::Slot,
     stats::{ConsensusStats, ExecutionStats, NetworkStats},
 };
 use massa_protocol_exports::{
     test_exports::tools::{
-        create_block, create_call_sc_op_with_too_much_gas, create_endorsement,
-        create_execute_sc_op_with_too_much_gas, create_operation_with_expire_period,
+        create_block, create_block_with_operations, create_call_sc_op_with_too_much_gas,
+        create_endorsement, create_execute_sc_op_with_too_much_gas,
+        create_operation_with_expire_period,
     },
-    MockProtocolController,
+    MockProtocolController, NetSplitAlert, PeerAgent, PeerConnectionType, PeerFeatures, PeerId,
+    PeerLatency,
 };
+use massa_serialization::Serializer;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serde_json::Value;
@@ -96,10 +118,45 @@ async fn get_status() {
                 known_peer_count: 6,
                 banned_peer_count: 0,
                 active_node_count: 15,
+                reachable: None,
+                network_time_offset: None,
             },
             HashMap::new(),
         ))
     });
+    let peer_keypair = KeyPair::generate(0).unwrap();
+    let peer_id = PeerId::from_public_key(peer_keypair.get_public_key());
+    protocol_ctrl.expect_get_peer_agents().returning(move || {
+        Ok(HashMap::from([(
+            peer_id,
+            PeerAgent {
+                version: "TEST.1.0".parse().unwrap(),
+                user_agent: Some("massa-node/TEST.1.0".to_string()),
+                features: PeerFeatures::SUPPORTED,
+                features_version: PeerFeatures::VERSION,
+            },
+        )]))
+    });
+    protocol_ctrl
+        .expect_get_peer_latencies()
+        .returning(move || {
+            Ok(HashMap::from([(
+                peer_id,
+                PeerLatency {
+                    rtt: MassaTime::from_millis(80),
+                    jitter: MassaTime::from_millis(20),
+                },
+            )]))
+        });
+    protocol_ctrl.expect_get_net_split_alert().returning(|| {
+        Ok(Some(NetSplitAlert {
+            connected_peers: 1,
+            connected_subnets: 1,
+            final_blocks: 32,
+            final_block_origins: 1,
+            final_block_origin_subnets: 1,
+        }))
+    });
 
     let mut pool_ctrl = MockPoolController::new();
     pool_ctrl.expect_get_operation_count().returning(|| 1024);
@@ -128,6 +185,38 @@ async fn get_status() {
     assert_eq!(response.network_stats.in_connection_count, 10);
     assert_eq!(response.network_stats.out_connection_count, 5);
     assert_eq!(response.config.thread_count, 32);
+    let agent = response
+        .connected_nodes_agents
+        .get(&NodeId::new(peer_keypair.get_public_key()))
+        .unwrap();
+    assert_eq!(agent.version.to_string(), "TEST.1.0");
+    assert_eq!(agent.user_agent.as_deref(), Some("massa-node/TEST.1.0"));
+    let latency = response
+        .connected_nodes_latencies
+        .get(&NodeId::new(peer_keypair.get_public_key()))
+        .unwrap();
+    assert_eq!(latency.rtt, MassaTime::from_millis(80));
+    assert!(!response.maintenance_mode);
+    // the only peer supports features disabled in the mock config
+    assert_eq!(
+        response.protocol_feature_warnings,
+        vec![
+            "100% of peers support pending operations requests; enable `pool_warmup_enabled`"
+                .to_string(),
+            "100% of peers support compressed block operations; enable `compress_block_operations`"
+                .to_string(),
+            "100% of peers support compressed operation messages; enable `compress_operation_messages`"
+                .to_string(),
+            "100% of peers support pending operations sketches; enable `pending_operations_sync_enabled`"
+                .to_string(),
+            "100% of peers support peer discovery lookups; enable `peer_discovery_enabled`"
+                .to_string(),
+        ]
+    );
+    assert!(response
+        .net_split_warning
+        .unwrap()
+        .starts_with("possible eclipse or network split: the 32 blocks finalized recently"));
     // Chain id == 77 for Node in sandbox mode otherwise it is always greater
     assert!(response.chain_id >= 77);
 
@@ -166,6 +255,117 @@ async fn get_cliques() {
     api_public_handle.stop().await;
 }
 
+#[tokio::test]
+async fn get_block_dependencies() {
+    let addr: SocketAddr = "[::]:5052".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let missing = BlockId::generate_from_hash(massa_hash::Hash::compute_from(b"missing"));
+    let blocked = BlockId::generate_from_hash(massa_hash::Hash::compute_from(b"blocked"));
+    let mut consensus_ctrl = MockConsensusController::new();
+    consensus_ctrl
+        .expect_get_block_dependencies()
+        .returning(move || BlockDependencies {
+            wishlist: vec![WishedBlock {
+                block_id: missing,
+                header_known: false,
+                blocked_blocks: vec![blocked],
+            }],
+            blocked: vec![BlockedBlock {
+                block_id: blocked,
+                slot: Slot::new(2, 0),
+                missing_dependencies: vec![missing],
+                blocked_since: MassaTime::from_millis(0),
+            }],
+        });
+
+    api_public.0.consensus_controller = Box::new(consensus_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+    let response: BlockDependencies = client
+        .request("get_block_dependencies", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(response.wishlist.len(), 1);
+    assert_eq!(response.wishlist[0].blocked_blocks, vec![blocked]);
+    assert_eq!(response.blocked[0].missing_dependencies, vec![missing]);
+
+    api_public_handle.stop().await;
+}
+
+#[tokio::test]
+async fn get_parent_selection() {
+    let addr: SocketAddr = "[::]:5053".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let parent = BlockId::generate_from_hash(massa_hash::Hash::compute_from(b"parent"));
+    let late = BlockId::generate_from_hash(massa_hash::Hash::compute_from(b"late"));
+    let mut consensus_ctrl = MockConsensusController::new();
+    consensus_ctrl
+        .expect_get_parent_selection()
+        .returning(move || {
+            Ok(ParentSelectionDiagnostics {
+                timestamp: MassaTime::from_millis(0),
+                current_slot: Some(Slot::new(3, 0)),
+                threads: vec![ThreadParentSelection {
+                    thread: 0,
+                    parent_id: parent,
+                    parent_period: 1,
+                    rejected: vec![
+                        RejectedParent {
+                            slot: Slot::new(2, 0),
+                            block_id: Some(late),
+                            reason: ParentRejectionReason::Late,
+                        },
+                        RejectedParent {
+                            slot: Slot::new(3, 0),
+                            block_id: None,
+                            reason: ParentRejectionReason::Missing,
+                        },
+                    ],
+                }],
+            })
+        });
+
+    api_public.0.consensus_controller = Box::new(consensus_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+    let response: ParentSelectionDiagnostics = client
+        .request("get_parent_selection", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(response.threads[0].parent_id, parent);
+    assert_eq!(response.threads[0].rejected[0].block_id, Some(late));
+    assert_eq!(
+        response.threads[0].rejected[1].reason,
+        ParentRejectionReason::Missing
+    );
+
+    api_public_handle.stop().await;
+}
+
 #[tokio::test]
 async fn get_operations() {
     let addr: SocketAddr = "[::]:5003".parse().unwrap();
@@ -210,6 +410,237 @@ async fn get_operations() {
     api_public_handle.stop().await;
 }
 
+#[tokio::test]
+async fn operations_cancellation() {
+    let addr: SocketAddr = "[::]:5060".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+    let keypair = KeyPair::generate(0).unwrap();
+    let pending_op = create_operation_with_expire_period(&keypair, u64::MAX);
+    let expired_op = create_operation_with_expire_period(&keypair, 10);
+    let final_op = create_operation_with_expire_period(&keypair, u64::MAX);
+    let unknown_op_id =
+        OperationId::from_str("O1q4CBcuYo8YANEV34W4JRWVHrzcYns19VJfyAB7jT4qfitAnMC").unwrap();
+
+    api_public.0.storage.store_operations(vec![
+        pending_op.clone(),
+        expired_op.clone(),
+        final_op.clone(),
+    ]);
+
+    let pending_op_id = pending_op.id;
+    let mut pool_ctrl = MockPoolController::new();
+    pool_ctrl
+        .expect_contains_operations()
+        .returning(move |ids| ids.iter().map(|id| *id == pending_op_id).collect());
+    pool_ctrl.expect_clone_box().returning(|| {
+        let mut pool_ctrl = MockPoolController::new();
+        pool_ctrl.expect_add_operations().returning(|_a| ());
+        Box::new(pool_ctrl)
+    });
+
+    let final_op_id = final_op.id;
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_get_ops_exec_status()
+        .returning(move |ids| {
+            ids.iter()
+                .map(|id| {
+                    if *id == final_op_id {
+                        (Some(true), Some(true))
+                    } else {
+                        (None, None)
+                    }
+                })
+                .collect()
+        });
+
+    let mut protocol_ctrl = MockProtocolController::new();
+    protocol_ctrl.expect_clone_box().returning(|| {
+        let mut protocol_ctrl = MockProtocolController::new();
+        protocol_ctrl
+            .expect_propagate_operations()
+            .returning(|_a| Ok(()));
+        Box::new(protocol_ctrl)
+    });
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+    api_public.0.pool_command_sender = Box::new(pool_ctrl);
+    api_public.0.protocol_controller = Box::new(protocol_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+    let params = rpc_params![vec![
+        pending_op.id,
+        expired_op.id,
+        final_op.id,
+        unknown_op_id
+    ]];
+    let response: Vec<OperationCancellationInfo> = client
+        .request("get_operations_cancellation_status", params)
+        .await
+        .unwrap();
+    let statuses: Vec<OperationCancellationStatus> =
+        response.iter().map(|info| info.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            OperationCancellationStatus::Pending,
+            OperationCancellationStatus::Expired,
+            OperationCancellationStatus::Final,
+            OperationCancellationStatus::Unknown,
+        ]
+    );
+    assert!(response[3].expire_slot.is_none());
+
+    // a replacement of the pending operation by its sender is advisory
+    let replacement = create_operation_with_expire_period(&keypair, u64::MAX);
+    let input = OperationInput {
+        creator_public_key: keypair.get_public_key(),
+        signature: replacement.signature,
+        serialized_content: replacement.serialized_data.clone(),
+    };
+    let response: OperationReplacement = client
+        .request(
+            "send_replacement_operation",
+            rpc_params![pending_op.id, input],
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.replacement_id, replacement.id);
+    assert_eq!(
+        response.replaced.status,
+        OperationCancellationStatus::Pending
+    );
+    assert!(response.advisory);
+
+    // a final operation cannot be replaced
+    let input = OperationInput {
+        creator_public_key: keypair.get_public_key(),
+        signature: replacement.signature,
+        serialized_content: replacement.serialized_data.clone(),
+    };
+    let response: Result<OperationReplacement, _> = client
+        .request(
+            "send_replacement_operation",
+            rpc_params![final_op.id, input],
+        )
+        .await;
+    assert!(response.unwrap_err().to_string().contains("already final"));
+
+    // the replacement must come from the same sender
+    let other_keypair = KeyPair::generate(0).unwrap();
+    let other_replacement = create_operation_with_expire_period(&other_keypair, u64::MAX);
+    let input = OperationInput {
+        creator_public_key: other_keypair.get_public_key(),
+        signature: other_replacement.signature,
+        serialized_content: other_replacement.serialized_data,
+    };
+    let response: Result<OperationReplacement, _> = client
+        .request(
+            "send_replacement_operation",
+            rpc_params![pending_op.id, input],
+        )
+        .await;
+    assert!(response
+        .unwrap_err()
+        .to_string()
+        .contains("not sent by the sender"));
+
+    api_public_handle.stop().await;
+}
+
+#[tokio::test]
+async fn get_filtered_operations() {
+    let addr: SocketAddr = "[::]:5051".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+    let keypair = KeyPair::generate(0).unwrap();
+    let creator = Address::from_public_key(&keypair.get_public_key());
+    let op = create_operation_with_expire_period(&keypair, 500000);
+    let other_op = create_operation_with_expire_period(&KeyPair::generate(0).unwrap(), 500000);
+
+    api_public
+        .0
+        .storage
+        .store_operations(vec![op.clone(), other_op]);
+
+    let mut pool_ctrl = MockPoolController::new();
+    pool_ctrl
+        .expect_contains_operations()
+        .returning(|ids| ids.iter().map(|_id| true).collect());
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_get_ops_exec_status()
+        .returning(|op| op.iter().map(|_op| (None, None)).collect());
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+    api_public.0.pool_command_sender = Box::new(pool_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    // neither the creator index nor the slot index can be used
+    let response: Result<Vec<OperationInfo>, Error> = client
+        .request(
+            "get_filtered_operations",
+            rpc_params![QueryFilter {
+                address: Some(creator),
+                ..Default::default()
+            }],
+        )
+        .await;
+    assert!(response.is_err());
+
+    let response: Vec<OperationInfo> = client
+        .request(
+            "get_filtered_operations",
+            rpc_params![QueryFilter {
+                address: Some(creator),
+                role: Some(AddressRole::Sender),
+                operation_types: Some(vec![OperationKind::Transaction]),
+                ..Default::default()
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.len(), 1);
+    assert_eq!(response[0].id, op.id);
+
+    let response: Vec<OperationInfo> = client
+        .request(
+            "get_filtered_operations",
+            rpc_params![QueryFilter {
+                address: Some(creator),
+                role: Some(AddressRole::Creator),
+                operation_types: Some(vec![OperationKind::RollBuy]),
+                ..Default::default()
+            }],
+        )
+        .await
+        .unwrap();
+    assert!(response.is_empty());
+
+    api_public_handle.stop().await;
+}
+
 #[tokio::test]
 async fn get_endorsements() {
     let addr: SocketAddr = "[::]:5005".parse().unwrap();
@@ -312,6 +743,209 @@ async fn get_blocks() {
     api_public_handle.stop().await;
 }
 
+#[tokio::test]
+async fn get_operation_inclusion_proof() {
+    let addr: SocketAddr = "[::]:5055".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+    let keypair = KeyPair::generate(0).unwrap();
+    let operations: Vec<_> = (0..3)
+        .map(|i| create_operation_with_expire_period(&keypair, 500 + i))
+        .collect();
+    let block = create_block_with_operations(&keypair, Slot::new(1, 0), operations.clone());
+
+    api_public.0.storage.store_block(block.clone());
+
+    let mut consensus_ctrl = MockConsensusController::new();
+    consensus_ctrl
+        .expect_get_block_statuses()
+        .returning(|param| param.iter().map(|_| BlockGraphStatus::Final).collect());
+
+    api_public.0.consensus_controller = Box::new(consensus_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let proof: OperationInclusionProof = client
+        .request(
+            "get_operation_inclusion_proof",
+            rpc_params![operations[1].id],
+        )
+        .await
+        .unwrap();
+    assert_eq!(proof.header.id, block.id);
+    assert_eq!(proof.index, 1);
+    assert!(proof.is_final);
+    assert!(proof.verify());
+
+    // a proof for another operation does not verify
+    let mut forged = proof.clone();
+    forged.operation_id = operations[2].id;
+    assert!(!forged.verify());
+
+    let unknown_op = create_operation_with_expire_period(&keypair, 1000);
+    let response: Result<OperationInclusionProof, Error> = client
+        .request("get_operation_inclusion_proof", rpc_params![unknown_op.id])
+        .await;
+    // the error comes with its machine-readable description
+    match response {
+        Err(Error::Call(err)) => {
+            assert_eq!(err.code(), -32004);
+            let data: ApiErrorData = serde_json::from_str(err.data().unwrap().get()).unwrap();
+            assert_eq!(data.code, -32004);
+            assert_eq!(data.category, ApiErrorCategory::NotFound);
+            assert!(!data.retryable);
+        }
+        other => panic!("unexpected response: {:?}", other),
+    }
+
+    api_public_handle.stop().await;
+}
+
+#[tokio::test]
+async fn resolve_name() {
+    let addr: SocketAddr = "[::]:5056".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+    let owner = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    let record = NameRecord {
+        owner,
+        target: Some(owner),
+        expire_period: u64::MAX,
+    };
+    let mut record_bytes = Vec::new();
+    NameRecordSerializer::new()
+        .serialize(&record, &mut record_bytes)
+        .unwrap();
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl.expect_query_state().returning(move |query| {
+        let responses = query
+            .requests
+            .iter()
+            .map(|item| {
+                let ExecutionQueryRequestItem::AddressDatastoreValue { addr, key } = item else {
+                    panic!("unexpected query");
+                };
+                assert_eq!(*addr, name_registry_address());
+                Ok(ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(
+                    ExecutionQueryFinalAndCandidate {
+                        final_value: None,
+                        candidate_value: (key == b"massa").then(|| record_bytes.clone()),
+                    },
+                ))
+            })
+            .collect();
+        ExecutionQueryResponse {
+            responses,
+            candidate_cursor: Slot::new(1, 2),
+            final_cursor: Slot::new(1, 7),
+            final_state_fingerprint: massa_hash::Hash::compute_from(&Vec::new()),
+        }
+    });
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let response: Option<NameRecord> = client
+        .request("resolve_name", rpc_params!["massa"])
+        .await
+        .unwrap();
+    assert_eq!(response, Some(record));
+
+    let response: Option<NameRecord> = client
+        .request("resolve_name", rpc_params!["unknown"])
+        .await
+        .unwrap();
+    assert!(response.is_none());
+
+    let response: Result<Option<NameRecord>, Error> = client
+        .request("resolve_name", rpc_params!["Not A Name"])
+        .await;
+    assert!(response.is_err());
+
+    api_public_handle.stop().await;
+}
+
+#[tokio::test]
+async fn get_rewards() {
+    let addr: SocketAddr = "[::]:5057".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+    let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    let rewards = AddressRewards {
+        block_rewards: Amount::from_str("1.02").unwrap(),
+        endorsement_rewards: Amount::from_str("0.1").unwrap(),
+        fees: Amount::from_str("0.003").unwrap(),
+    };
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_get_address_rewards()
+        .returning(move |requested_address, cycles| {
+            assert_eq!(*requested_address, address);
+            cycles
+                .iter()
+                .filter(|cycle| **cycle <= 3)
+                .map(|cycle| AddressCycleRewards {
+                    cycle: *cycle,
+                    rewards,
+                })
+                .collect()
+        });
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let response: Vec<AddressCycleRewards> = client
+        .request(
+            "get_rewards",
+            rpc_params![RewardsRequest {
+                address,
+                cycles: vec![2, 3, 4],
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        vec![
+            AddressCycleRewards { cycle: 2, rewards },
+            AddressCycleRewards { cycle: 3, rewards }
+        ]
+    );
+
+    api_public_handle.stop().await;
+}
+
 #[tokio::test]
 async fn get_blockclique_block_by_slot() {
     let addr: SocketAddr = "[::]:5007".parse().unwrap();
@@ -362,6 +996,50 @@ async fn get_blockclique_block_by_slot() {
     api_public_handle.stop().await;
 }
 
+#[tokio::test]
+async fn get_network_load() {
+    let addr: SocketAddr = "[::]:5054".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let block = create_block(&KeyPair::generate(0).unwrap());
+    let id = block.id;
+    api_public.0.storage.store_block(block);
+    // make sure genesis is reached
+    api_public.0.api_settings.genesis_timestamp = MassaTime::from_millis(0);
+
+    let mut consensus_ctrl = MockConsensusController::new();
+    consensus_ctrl
+        .expect_get_blockclique_block_at_slot()
+        .returning(move |_s| Some(id));
+    api_public.0.consensus_controller = Box::new(consensus_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+    let response: NetworkLoad = client
+        .request("get_network_load", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.slot_count,
+        config.network_load_periods * config.thread_count as u64
+    );
+    assert_eq!(response.block_count, response.slot_count);
+    assert_eq!(response.operation_count, 0);
+    assert_eq!(response.load, 0.0);
+
+    api_public_handle.stop().await;
+}
+
 #[tokio::test]
 async fn get_graph_interval() {
     let addr: SocketAddr = "[::]:5008".parse().unwrap();
@@ -848,7 +1526,14 @@ async fn get_addresses() {
                 final_datastore_keys: std::collections::BTreeSet::new(),
                 candidate_roll_count: 12,
                 candidate_datastore_keys: std::collections::BTreeSet::new(),
-                future_deferred_credits: BTreeMap::new(),
+                future_deferred_credits: BTreeMap::from([
+                    (Slot::new(10, 0), Amount::from_str("300").unwrap()),
+                    (Slot::new(12, 0), Amount::from_str("200").unwrap()),
+                ]),
+                final_future_deferred_credits: BTreeMap::from([(
+                    Slot::new(10, 0),
+                    Amount::from_str("300").unwrap(),
+                )]),
                 cycle_infos: vec![],
             })
             .collect()
@@ -890,6 +1575,26 @@ async fn get_addresses() {
 
     assert!(response.len() == 1);
 
+    // locked coins: rolls at roll price and deferred credits,
+    // the final breakdown only counts the final deferred credits
+    let final_breakdown = response[0].final_balance_breakdown;
+    assert_eq!(
+        final_breakdown.spendable,
+        Amount::from_str("80000").unwrap()
+    );
+    assert_eq!(final_breakdown.in_rolls, Amount::from_str("5500").unwrap());
+    assert_eq!(final_breakdown.deferred, Amount::from_str("300").unwrap());
+    assert_eq!(final_breakdown.total, Amount::from_str("85800").unwrap());
+    let candidate_breakdown = response[0].candidate_balance_breakdown;
+    assert_eq!(
+        candidate_breakdown.deferred,
+        Amount::from_str("500").unwrap()
+    );
+    assert_eq!(
+        candidate_breakdown.total,
+        Amount::from_str("101700").unwrap()
+    );
+
     api_public_handle.stop().await;
 }
 
@@ -902,9 +1607,12 @@ async fn get_addresses_bytecode() {
     exec_ctrl
         .expect_query_state()
         .returning(|_| ExecutionQueryResponse {
-            responses: vec![Ok(ExecutionQueryResponseItem::Bytecode(Bytecode(
-                "massa".as_bytes().to_vec(),
-            )))],
+            responses: vec![Ok(ExecutionQueryResponseItem::BytecodeFinalAndCandidate(
+                ExecutionQueryFinalAndCandidate {
+                    final_value: Some(Bytecode("massa".as_bytes().to_vec())),
+                    candidate_value: None,
+                },
+            ))],
             candidate_cursor: massa_models::slot::Slot::new(1, 2),
             final_cursor: Slot::new(1, 7),
             final_state_fingerprint: massa_hash::Hash::compute_from(&Vec::new()),
@@ -952,12 +1660,19 @@ async fn get_datastore_entries() {
 
     let mut exec_ctrl = MockExecutionController::new();
     exec_ctrl
-        .expect_get_final_and_active_data_entry()
-        .returning(|_a| {
-            vec![(
-                Some("massa".as_bytes().to_vec()),
-                Some("blockchain".as_bytes().to_vec()),
-            )]
+        .expect_query_state()
+        .returning(|_| ExecutionQueryResponse {
+            responses: vec![Ok(
+                ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(
+                    ExecutionQueryFinalAndCandidate {
+                        final_value: Some("massa".as_bytes().to_vec()),
+                        candidate_value: Some("blockchain".as_bytes().to_vec()),
+                    },
+                ),
+            )],
+            candidate_cursor: Slot::new(1, 2),
+            final_cursor: Slot::new(1, 7),
+            final_state_fingerprint: massa_hash::Hash::compute_from(&Vec::new()),
         });
 
     api_public.0.execution_controller = Box::new(exec_ctrl);
@@ -1047,6 +1762,13 @@ async fn wrong_api() {
         .unwrap_err()
         .to_string()
         .contains("The wrong API (either Public or Private) was called"));
+    let response: Result<(), Error> = client
+        .request("get_staking_addresses_activation", params.clone())
+        .await;
+    assert!(response
+        .unwrap_err()
+        .to_string()
+        .contains("The wrong API (either Public or Private) was called"));
 
     let response: Result<(), Error> = client
         .request("node_ban_by_ip", rpc_params![Vec::<IpAddr>::new()])
@@ -1345,3 +2067,141 @@ async fn get_stakers() {
 
     api_public_handle.stop().await;
 }
+
+#[tokio::test]
+async fn get_stakers_liveness() {
+    let addr: SocketAddr = "[::]:5050".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let online_keypair = KeyPair::generate(0).unwrap();
+    let online_address = Address::from_public_key(&online_keypair.get_public_key());
+    let offline_address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_get_cycle_active_rolls()
+        .returning(move |_| {
+            let mut map = std::collections::BTreeMap::new();
+            map.insert(online_address, 10_u64);
+            map.insert(offline_address, 20_u64);
+            map
+        });
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+
+    let mut protocol_ctrl = MockProtocolController::new();
+    protocol_ctrl
+        .expect_get_staker_heartbeats()
+        .returning(move || {
+            Ok(vec![Heartbeat {
+                timestamp: MassaTime::from_millis(1_000),
+                last_produced_slot: Some(Slot::new(3, 1)),
+            }
+            .new_verifiable(HeartbeatSerializer::new(), &online_keypair, *CHAINID)
+            .unwrap()])
+        });
+    api_public.0.protocol_controller = Box::new(protocol_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let response: Vec<StakerLiveness> = client
+        .request("get_stakers_liveness", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(response.len(), 2);
+    // sorted by active rolls
+    assert_eq!(response[0].address, offline_address);
+    assert!(response[0].last_heartbeat.is_none());
+    assert_eq!(response[1].address, online_address);
+    assert_eq!(
+        response[1].last_heartbeat,
+        Some(MassaTime::from_millis(1_000))
+    );
+    assert_eq!(response[1].last_produced_slot, Some(Slot::new(3, 1)));
+
+    api_public_handle.stop().await;
+}
+
+#[tokio::test]
+async fn get_node_address_links() {
+    let addr: SocketAddr = "[::]:5059".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let staking_keypair = KeyPair::generate(0).unwrap();
+    let staking_address = Address::from_public_key(&staking_keypair.get_public_key());
+    let node_keypair = KeyPair::generate(0).unwrap();
+    let node_id = NodeId::new(node_keypair.get_public_key());
+
+    let mut protocol_ctrl = MockProtocolController::new();
+    protocol_ctrl.expect_get_stats().returning(move || {
+        Ok((
+            NetworkStats {
+                in_connection_count: 1,
+                out_connection_count: 0,
+                known_peer_count: 1,
+                banned_peer_count: 0,
+                active_node_count: 1,
+                reachable: None,
+                network_time_offset: None,
+            },
+            HashMap::from([(
+                PeerId::from_public_key(node_id.get_public_key()),
+                ("127.0.0.1:31244".parse().unwrap(), PeerConnectionType::IN),
+            )]),
+        ))
+    });
+    protocol_ctrl
+        .expect_get_node_address_links()
+        .returning(move || {
+            Ok(vec![NodeAddressLinkProof::new(
+                NodeAddressLink {
+                    node_id,
+                    timestamp: MassaTime::from_millis(1_000),
+                },
+                &staking_keypair,
+                &node_keypair,
+                *CHAINID,
+            )
+            .unwrap()])
+        });
+    api_public.0.protocol_controller = Box::new(protocol_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let response: Vec<NodeAddressLinkInfo> = client
+        .request("get_node_address_links", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response,
+        vec![NodeAddressLinkInfo {
+            address: staking_address,
+            node_id,
+            timestamp: MassaTime::from_millis(1_000),
+            connected: true,
+        }]
+    );
+
+    api_public_handle.stop().await;
+}
@@ -8,16 +8,11 @@ use massa_models::amount::{AmountDeserializer, AmountSerializer};
 use massa_models::config::GENESIS_KEY;
 use massa_models::serialization::{StringDeserializer, StringSerializer};
 use massa_models::slot::{SlotDeserializer, SlotSerializer};
-use massa_models::{
-    address::Address,
-    amount::Amount,
-    serialization::{VecU8Deserializer, VecU8Serializer},
-    slot::Slot,
-};
+use massa_models::{address::Address, amount::Amount, slot::Slot};
 use massa_serialization::{
     BoolDeserializer, BoolSerializer, Deserializer, OptionDeserializer, OptionSerializer,
     SerializeError, Serializer, U16VarIntDeserializer, U16VarIntSerializer, U64VarIntDeserializer,
-    U64VarIntSerializer,
+    U64VarIntSerializer, VecU8Deserializer, VecU8Serializer,
 };
 use nom::error::{context, ContextError, ParseError};
 use nom::sequence::tuple;
@@ -11,9 +11,10 @@ use massa_hash::Hash;
 use massa_models::config::{
     MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES, SIGNATURE_DESER_SIZE,
 };
-use massa_models::serialization::{DeserializeMinBEInt, SerializeMinBEInt};
 use massa_models::version::{Version, VersionSerializer};
-use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_serialization::{
+    DeserializeError, DeserializeMinBEInt, Deserializer, SerializeMinBEInt, Serializer,
+};
 use massa_signature::{PublicKey, Signature};
 use rand::{rngs::StdRng, RngCore, SeedableRng};
 use std::time::Instant;
@@ -10,9 +10,10 @@ use crate::settings::BootstrapSrvBindCfg;
 use massa_hash::Hash;
 use massa_hash::HASH_SIZE_BYTES;
 use massa_models::config::{MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES};
-use massa_models::serialization::{DeserializeMinBEInt, SerializeMinBEInt};
 use massa_models::version::{Version, VersionDeserializer, VersionSerializer};
-use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_serialization::{
+    DeserializeError, DeserializeMinBEInt, Deserializer, SerializeMinBEInt, Serializer,
+};
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use std::io;
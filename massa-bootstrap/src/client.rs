@@ -1,9 +1,14 @@
 use humantime::format_duration;
+use massa_consensus_exports::bootstrapable_graph::BootstrapableGraph;
 use massa_db_exports::DBBatch;
 use massa_final_state::{FinalStateController, FinalStateError};
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
-use massa_models::{node::NodeId, slot::Slot, streaming_step::StreamingStep, version::Version};
+use massa_models::{
+    node::NodeId, slot::Slot, streaming_step::StreamingStep, timeslots::get_block_slot_timestamp,
+    version::Version,
+};
+use massa_protocol_exports::Socks5Proxy;
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use massa_versioning::versioning::{ComponentStateTypeId, MipInfo, MipState, StateAtError};
@@ -42,9 +47,23 @@ pub trait BSConnector {
     ) -> io::Result<TcpStream>;
 }
 
-/// Initiates a connection with given timeout in milliseconds
-#[derive(Debug)]
-pub struct DefaultConnector;
+/// Timeout of a connection through the SOCKS5 proxy when none is given
+const PROXY_CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Initiates a connection with given timeout in milliseconds, directly or through a SOCKS5 proxy
+#[derive(Debug, Default)]
+pub struct DefaultConnector {
+    socks5_proxy: Option<Socks5Proxy>,
+}
+
+impl DefaultConnector {
+    /// Connector opening the connections through the SOCKS5 proxy at `socks5_proxy`, if any
+    pub fn new(socks5_proxy: Option<SocketAddr>) -> Self {
+        DefaultConnector {
+            socks5_proxy: socks5_proxy.map(Socks5Proxy::new),
+        }
+    }
+}
 
 impl BSConnector for DefaultConnector {
     /// Tries to connect to address
@@ -56,6 +75,10 @@ impl BSConnector for DefaultConnector {
         addr: SocketAddr,
         duration: Option<MassaTime>,
     ) -> io::Result<TcpStream> {
+        if let Some(proxy) = &self.socks5_proxy {
+            let timeout = duration.map_or(PROXY_CONNECT_TIMEOUT, |duration| duration.to_duration());
+            return proxy.connect(addr, timeout);
+        }
         let Some(duration) = duration else {
             return TcpStream::connect(addr);
         };
@@ -197,6 +220,53 @@ fn stream_final_state_and_consensus(
     }
 }
 
+/// Checks that a bootstrapped state was produced by a network sharing our thread count and genesis timestamp
+///
+/// # Arguments
+/// * `final_slot`: slot of the bootstrapped final state, if known
+/// * `graph`: bootstrapped consensus graph, if any
+pub(crate) fn check_bootstrapped_network(
+    cfg: &BootstrapConfig,
+    genesis_timestamp: MassaTime,
+    server_time: MassaTime,
+    final_slot: Option<Slot>,
+    graph: Option<&BootstrapableGraph>,
+) -> Result<(), BootstrapError> {
+    if let Some(slot) = final_slot {
+        if slot.thread >= cfg.thread_count {
+            return Err(BootstrapError::NetworkMismatch(format!(
+                "the final state is at slot {} but the local thread count is {}",
+                slot, cfg.thread_count
+            )));
+        }
+        // the final slot is in the past of the server, it can only look in the future
+        // if the server network started before our genesis timestamp
+        let slot_timestamp =
+            get_block_slot_timestamp(cfg.thread_count, cfg.t0, genesis_timestamp, slot)?;
+        if slot_timestamp > server_time.saturating_add(cfg.max_clock_delta) {
+            return Err(BootstrapError::NetworkMismatch(format!(
+                "the final state is at slot {} which starts at {} with the local genesis timestamp, after the server time {}",
+                slot, slot_timestamp, server_time
+            )));
+        }
+    }
+    for export in graph.iter().flat_map(|graph| graph.final_blocks.iter()) {
+        let header = &export.block.content.header.content;
+        if header.slot.thread >= cfg.thread_count
+            || (header.slot.period > 0 && header.parents.len() != cfg.thread_count as usize)
+        {
+            return Err(BootstrapError::NetworkMismatch(format!(
+                "final block {} at slot {} has {} parents but the local thread count is {}",
+                export.block.id,
+                header.slot,
+                header.parents.len(),
+                cfg.thread_count
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Gets the state from a bootstrap server (internal private function)
 /// needs to be CANCELLABLE
 pub(crate) fn bootstrap_from_server(
@@ -205,6 +275,7 @@ pub(crate) fn bootstrap_from_server(
     next_bootstrap_message: &mut BootstrapClientMessage,
     global_bootstrap_state: &mut GlobalBootstrapState,
     our_version: Version,
+    genesis_timestamp: MassaTime,
 ) -> Result<(), BootstrapError> {
     massa_trace!("bootstrap.lib.bootstrap_from_server", {});
 
@@ -297,6 +368,33 @@ pub(crate) fn bootstrap_from_server(
                     next_bootstrap_message,
                     global_bootstrap_state,
                 )?;
+                // the state is complete: make sure it belongs to our network before going further
+                let final_slot = global_bootstrap_state
+                    .final_state
+                    .read()
+                    .get_database()
+                    .read()
+                    .get_change_id()
+                    .ok();
+                if let Err(err) = check_bootstrapped_network(
+                    cfg,
+                    genesis_timestamp,
+                    server_time,
+                    final_slot,
+                    global_bootstrap_state.graph.as_ref(),
+                ) {
+                    // do not resume a bootstrap from another network with the next server
+                    *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
+                        last_slot: None,
+                        last_state_step: StreamingStep::Started,
+                        last_versioning_step: StreamingStep::Started,
+                        last_consensus_step: StreamingStep::Started,
+                        send_last_start_period: true,
+                    };
+                    global_bootstrap_state.graph = None;
+                    global_bootstrap_state.final_state.write().reset();
+                    return Err(err);
+                }
             }
             BootstrapClientMessage::AskBootstrapPeers => {
                 let peers = match send_client_message(
@@ -519,6 +617,7 @@ pub fn get_state(
                         &mut next_bootstrap_message,
                         &mut global_bootstrap_state,
                         version,
+                        genesis_timestamp,
                     );
                     // cancellable
                     match bs {
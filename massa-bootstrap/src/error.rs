@@ -55,6 +55,8 @@ pub enum BootstrapError {
     MissingKeyError,
     /// incompatible version: {0}
     IncompatibleVersionError(String),
+    /// network mismatch: {0}
+    NetworkMismatch(String),
     /// Received error: {0}
     ReceivedError(String),
     /// clock error: {0}
@@ -10,9 +10,7 @@ use massa_db_exports::StreamBatch;
 use massa_models::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
 
 use massa_models::prehash::PreHashSet;
-use massa_models::serialization::{
-    PreHashSetDeserializer, PreHashSetSerializer, VecU8Deserializer, VecU8Serializer,
-};
+use massa_models::serialization::{PreHashSetDeserializer, PreHashSetSerializer};
 use massa_models::slot::{Slot, SlotDeserializer, SlotSerializer};
 use massa_models::streaming_step::{
     StreamingStep, StreamingStepDeserializer, StreamingStepSerializer,
@@ -24,7 +22,7 @@ use massa_protocol_exports::{
 use massa_serialization::{
     BoolDeserializer, BoolSerializer, Deserializer, OptionDeserializer, OptionSerializer,
     SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer,
-    U64VarIntSerializer,
+    U64VarIntSerializer, VecU8Deserializer, VecU8Serializer,
 };
 
 use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
@@ -102,7 +100,7 @@ impl ToString for BootstrapServerMessage {
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
 #[repr(u32)]
-enum MessageServerTypeId {
+pub(crate) enum MessageServerTypeId {
     BootstrapTime = 0u32,
     Peers = 1u32,
     FinalStatePart = 2u32,
@@ -664,7 +662,7 @@ pub enum BootstrapClientMessage {
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
 #[repr(u32)]
-enum MessageClientTypeId {
+pub(crate) enum MessageClientTypeId {
     AskBootstrapPeers = 0u32,
     AskFinalStatePart = 1u32,
     BootstrapError = 2u32,
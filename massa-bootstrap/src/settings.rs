@@ -67,6 +67,8 @@ pub struct BootstrapConfig {
     pub thread_count: u8,
     /// period per cycle
     pub periods_per_cycle: u64,
+    /// period duration
+    pub t0: MassaTime,
     /// max datastore key length
     pub max_datastore_key_length: u8,
     /// randomness size bytes
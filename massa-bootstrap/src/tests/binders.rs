@@ -451,6 +451,8 @@ fn test_staying_connected_without_message_trigger_read_timeout() {
         max_versioning_elements_size: 100_000_000,
         thread_count: THREAD_COUNT,
         max_ledger_backups: 10,
+        disk_soft_quota: None,
+        disk_hard_quota: None,
     }))
         as Box<(dyn MassaDBController + 'static)>));
     let rolls_path = PathBuf::from_str("../massa-node/base_config/initial_rolls.json").unwrap();
@@ -549,6 +551,8 @@ fn test_staying_connected_pass_handshake_but_deadline_after() {
         max_versioning_elements_size: 100_000_000,
         thread_count: THREAD_COUNT,
         max_ledger_backups: 10,
+        disk_soft_quota: None,
+        disk_hard_quota: None,
     }))
         as Box<(dyn MassaDBController + 'static)>));
     let rolls_path = PathBuf::from_str("../massa-node/base_config/initial_rolls.json").unwrap();
@@ -647,6 +651,8 @@ fn test_staying_connected_pass_handshake_but_deadline_during_data_exchange() {
         max_versioning_elements_size: 100_000_000,
         thread_count: THREAD_COUNT,
         max_ledger_backups: 10,
+        disk_soft_quota: None,
+        disk_hard_quota: None,
     }))
         as Box<(dyn MassaDBController + 'static)>));
     let rolls_path = PathBuf::from_str("../massa-node/base_config/initial_rolls.json").unwrap();
@@ -13,7 +13,7 @@ use massa_models::{
         MAX_LEDGER_CHANGES_COUNT, MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
         MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
         MAX_PARAMETERS_SIZE, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
-        MIP_STORE_STATS_BLOCK_CONSIDERED, PERIODS_PER_CYCLE, THREAD_COUNT,
+        MIP_STORE_STATS_BLOCK_CONSIDERED, PERIODS_PER_CYCLE, T0, THREAD_COUNT,
     },
     node::NodeId,
 };
@@ -53,6 +53,7 @@ impl Default for BootstrapConfig {
             randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
             thread_count: THREAD_COUNT,
             periods_per_cycle: PERIODS_PER_CYCLE,
+            t0: T0,
             endorsement_count: ENDORSEMENT_COUNT,
             max_advertise_length: MAX_ADVERTISE_LENGTH,
             max_bootstrap_blocks_length: MAX_BOOTSTRAP_BLOCKS,
@@ -7,3 +7,4 @@ mod scenarios;
 pub(crate) mod tools;
 mod universe_client;
 mod universe_server;
+mod wire_spec;
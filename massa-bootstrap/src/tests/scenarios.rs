@@ -2,14 +2,16 @@
 
 use super::universe_client::{BootstrapClientForeignControllers, BootstrapClientTestUniverse};
 use super::universe_server::BootstrapServerTestUniverseBuilder;
+use crate::client::check_bootstrapped_network;
 use crate::BootstrapConfig;
 use crate::BootstrapError;
 use massa_models::amount::Amount;
 use massa_models::bytecode::Bytecode;
 use massa_models::datastore::Datastore;
-use massa_models::{address::Address, node::NodeId};
+use massa_models::{address::Address, config::GENESIS_TIMESTAMP, node::NodeId, slot::Slot};
 use massa_signature::KeyPair;
 use massa_test_framework::TestUniverse;
+use massa_time::MassaTime;
 use serial_test::serial;
 use std::path::PathBuf;
 
@@ -78,3 +80,42 @@ fn test_bootstrap_accept_err() {
         .build();
     drop(server_universe);
 }
+
+#[test]
+fn test_bootstrap_network_mismatch() {
+    let config = BootstrapConfig::default();
+    let server_time = MassaTime::now();
+
+    check_bootstrapped_network(
+        &config,
+        *GENESIS_TIMESTAMP,
+        server_time,
+        Some(Slot::new(1, config.thread_count - 1)),
+        None,
+    )
+    .unwrap();
+
+    // slot on a thread that does not exist locally
+    assert!(matches!(
+        check_bootstrapped_network(
+            &config,
+            *GENESIS_TIMESTAMP,
+            server_time,
+            Some(Slot::new(1, config.thread_count)),
+            None,
+        ),
+        Err(BootstrapError::NetworkMismatch(_))
+    ));
+
+    // slot that is not reached yet with the local genesis timestamp
+    assert!(matches!(
+        check_bootstrapped_network(
+            &config,
+            server_time,
+            server_time,
+            Some(Slot::new(1000, 0)),
+            None,
+        ),
+        Err(BootstrapError::NetworkMismatch(_))
+    ));
+}
@@ -8,7 +8,8 @@ use massa_db_worker::MassaDB;
 use massa_final_state::MockFinalStateController;
 use massa_models::{
     config::{
-        MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE, MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE, THREAD_COUNT,
+        GENESIS_TIMESTAMP, MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE,
+        MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE, THREAD_COUNT,
     },
     node::NodeId,
     streaming_step::StreamingStep,
@@ -60,6 +61,8 @@ impl TestUniverse for BootstrapClientTestUniverse {
             max_final_state_elements_size: MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE as usize,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         }))
             as Box<(dyn MassaDBController + 'static)>));
         controllers
@@ -138,6 +141,7 @@ impl BootstrapClientTestUniverse {
             &mut next_bootstrap_message,
             &mut self.global_bootstrap_state,
             version,
+            *GENESIS_TIMESTAMP,
         )
     }
 
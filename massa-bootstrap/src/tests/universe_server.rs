@@ -57,6 +57,8 @@ impl BootstrapServerForeignControllers {
             max_final_state_elements_size: MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE as usize,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         }))
             as Box<(dyn MassaDBController + 'static)>));
         Self {
@@ -134,6 +136,7 @@ impl Default for BootstrapServerTestUniverseBuilder {
                 initial_ledger_path: file.path().to_path_buf(),
                 max_key_length: MAX_DATASTORE_KEY_LENGTH,
                 max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+                hot_cache_size: 0,
             },
             controllers.database.clone(),
         );
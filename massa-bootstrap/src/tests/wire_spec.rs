@@ -0,0 +1,288 @@
+//! Describes the bootstrap messages from their actual definitions, in the same format as the
+//! network one (see the protocol worker): the `wire-spec/bootstrap-<version>.json` artifact
+//! lists the message type identifiers, the bounds and test vectors produced by the node's
+//! serializers.
+//!
+//! Each test vector is checked against its expected byte layout and must round trip through
+//! the node's deserializers. The artifact is written by an ignored test:
+//! `cargo test -p massa_bootstrap generate_bootstrap_wire_spec -- --ignored`
+
+use std::str::FromStr;
+
+use massa_models::{
+    config::{
+        CHAINID, ENDORSEMENT_COUNT, MAX_ADVERTISE_LENGTH, MAX_BOOTSTRAP_BLOCKS,
+        MAX_BOOTSTRAP_ERROR_LENGTH, MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE,
+        MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES,
+        MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE, MAX_CONSENSUS_BLOCKS_IDS,
+        MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH,
+        MAX_DEFERRED_CREDITS_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        MAX_DENUNCIATION_CHANGES_LENGTH, MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH,
+        MAX_LEDGER_CHANGES_COUNT, MAX_LISTENERS_PER_PEER, MAX_OPERATIONS_PER_BLOCK,
+        MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, MIP_STORE_STATS_BLOCK_CONSIDERED,
+        THREAD_COUNT, VERSION,
+    },
+    streaming_step::StreamingStep,
+    test_exports::{enum_ids, to_hex, varint, write_wire_spec},
+    version::Version,
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_time::MassaTime;
+use serde_json::{json, Value};
+
+use crate::messages::{
+    BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer,
+    BootstrapServerMessage, BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer,
+    MessageClientTypeId, MessageServerTypeId,
+};
+use crate::BootstrapServerMessageDeserializerArgs;
+
+const ERROR: &str = "error";
+
+/// `varint(message type id) || payload`
+fn frame(type_id: u64, payload: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = varint(type_id);
+    for part in payload {
+        bytes.extend_from_slice(part);
+    }
+    bytes
+}
+
+/// Server test vectors of the spec: `(name, message, expected encoding)`
+fn server_test_vectors() -> Vec<(&'static str, BootstrapServerMessage, Vec<u8>)> {
+    // errors are `varint(length) || utf-8 bytes`
+    let error_bytes = [varint(ERROR.len() as u64), ERROR.as_bytes().to_vec()].concat();
+    // versions are `instance || varint(major) || varint(minor)`
+    let version_bytes = [b"TEST".to_vec(), varint(1), varint(10)].concat();
+    vec![
+        (
+            "BootstrapTime",
+            BootstrapServerMessage::BootstrapTime {
+                server_time: MassaTime::from_millis(0),
+                version: Version::from_str("TEST.1.10").unwrap(),
+            },
+            frame(0, &[&varint(0), &version_bytes]),
+        ),
+        (
+            "BootstrapFinished",
+            BootstrapServerMessage::BootstrapFinished,
+            frame(3, &[]),
+        ),
+        (
+            "SlotTooOld",
+            BootstrapServerMessage::SlotTooOld,
+            frame(4, &[]),
+        ),
+        (
+            "BootstrapError",
+            BootstrapServerMessage::BootstrapError {
+                error: ERROR.to_string(),
+            },
+            frame(5, &[&error_bytes]),
+        ),
+    ]
+}
+
+/// Client test vectors of the spec: `(name, message, expected encoding)`
+fn client_test_vectors() -> Vec<(&'static str, BootstrapClientMessage, Vec<u8>)> {
+    let error_bytes = [varint(ERROR.len() as u64), ERROR.as_bytes().to_vec()].concat();
+    vec![
+        (
+            "AskBootstrapPeers",
+            BootstrapClientMessage::AskBootstrapPeers,
+            frame(0, &[]),
+        ),
+        (
+            // without a last slot, the steps are not sent
+            "AskBootstrapPart(started)",
+            BootstrapClientMessage::AskBootstrapPart {
+                last_slot: None,
+                last_state_step: StreamingStep::Started,
+                last_versioning_step: StreamingStep::Started,
+                last_consensus_step: StreamingStep::Started,
+                send_last_start_period: true,
+            },
+            frame(1, &[]),
+        ),
+        (
+            "BootstrapError",
+            BootstrapClientMessage::BootstrapError {
+                error: ERROR.to_string(),
+            },
+            frame(2, &[&error_bytes]),
+        ),
+        (
+            "BootstrapSuccess",
+            BootstrapClientMessage::BootstrapSuccess,
+            frame(3, &[]),
+        ),
+    ]
+}
+
+fn server_message_deserializer() -> BootstrapServerMessageDeserializer {
+    BootstrapServerMessageDeserializer::new(BootstrapServerMessageDeserializerArgs {
+        thread_count: THREAD_COUNT,
+        endorsement_count: ENDORSEMENT_COUNT,
+        max_advertise_length: MAX_ADVERTISE_LENGTH,
+        max_listeners_per_peer: MAX_LISTENERS_PER_PEER as u32,
+        max_bootstrap_blocks_length: MAX_BOOTSTRAP_BLOCKS,
+        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        max_final_state_elements_size: MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE,
+        max_versioning_elements_size: MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE,
+        max_ledger_changes_count: MAX_LEDGER_CHANGES_COUNT,
+        max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+        max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+        max_datastore_entry_count: MAX_DATASTORE_ENTRY_COUNT,
+        max_bootstrap_error_length: MAX_BOOTSTRAP_ERROR_LENGTH,
+        max_changes_slot_count: 1000,
+        max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+        max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+        max_credits_length: MAX_DEFERRED_CREDITS_LENGTH,
+        max_executed_ops_length: MAX_EXECUTED_OPS_LENGTH,
+        max_ops_changes_length: MAX_EXECUTED_OPS_CHANGES_LENGTH,
+        mip_store_stats_block_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        max_denunciation_changes_length: MAX_DENUNCIATION_CHANGES_LENGTH,
+        chain_id: *CHAINID,
+    })
+}
+
+#[test]
+fn test_bootstrap_server_wire_layout() {
+    let serializer = BootstrapServerMessageSerializer::new();
+    let deserializer = server_message_deserializer();
+    for (name, message, expected) in server_test_vectors() {
+        let mut buffer = Vec::new();
+        serializer.serialize(&message, &mut buffer).unwrap();
+        assert_eq!(
+            to_hex(&buffer),
+            to_hex(&expected),
+            "unexpected layout for {}",
+            name
+        );
+        assert!(buffer.len() <= MAX_BOOTSTRAP_MESSAGE_SIZE as usize);
+        let (rest, decoded) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap_or_else(|e| panic!("could not decode {}: {}", name, e));
+        assert!(rest.is_empty(), "bytes left after {}", name);
+        assert!(decoded.equals(&message), "{} decoded differently", name);
+    }
+}
+
+#[test]
+fn test_bootstrap_client_wire_layout() {
+    let serializer = BootstrapClientMessageSerializer::new();
+    let deserializer = BootstrapClientMessageDeserializer::new(
+        THREAD_COUNT,
+        MAX_DATASTORE_KEY_LENGTH,
+        MAX_CONSENSUS_BLOCKS_IDS,
+    );
+    for (name, message, expected) in client_test_vectors() {
+        let mut buffer = Vec::new();
+        serializer.serialize(&message, &mut buffer).unwrap();
+        assert_eq!(
+            to_hex(&buffer),
+            to_hex(&expected),
+            "unexpected layout for {}",
+            name
+        );
+        assert!(buffer.len() <= MAX_BOOTSTRAP_MESSAGE_SIZE as usize);
+        let (rest, decoded) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap_or_else(|e| panic!("could not decode {}: {}", name, e));
+        assert!(rest.is_empty(), "bytes left after {}", name);
+        assert!(decoded.equals(&message), "{} decoded differently", name);
+    }
+}
+
+#[test]
+fn test_bootstrap_wire_type_ids() {
+    let names = |values: Vec<Value>| -> Vec<(String, u64)> {
+        values
+            .iter()
+            .map(|v| {
+                (
+                    v["name"].as_str().unwrap().to_string(),
+                    v["id"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    };
+    let expected = |names: &[&str]| -> Vec<(String, u64)> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.to_string(), id as u64))
+            .collect()
+    };
+    assert_eq!(
+        names(enum_ids::<u32, MessageServerTypeId>()),
+        expected(&[
+            "BootstrapTime",
+            "Peers",
+            "FinalStatePart",
+            "FinalStateFinished",
+            "SlotTooOld",
+            "BootstrapError",
+        ])
+    );
+    assert_eq!(
+        names(enum_ids::<u32, MessageClientTypeId>()),
+        expected(&[
+            "AskBootstrapPeers",
+            "AskFinalStatePart",
+            "BootstrapError",
+            "BootstrapSuccess",
+        ])
+    );
+}
+
+#[test]
+#[ignore]
+fn generate_bootstrap_wire_spec() {
+    let server_serializer = BootstrapServerMessageSerializer::new();
+    let server_vectors: Vec<Value> = server_test_vectors()
+        .into_iter()
+        .map(|(name, message, _)| {
+            let mut buffer = Vec::new();
+            server_serializer.serialize(&message, &mut buffer).unwrap();
+            json!({ "name": name, "hex": to_hex(&buffer) })
+        })
+        .collect();
+
+    let client_serializer = BootstrapClientMessageSerializer::new();
+    let client_vectors: Vec<Value> = client_test_vectors()
+        .into_iter()
+        .map(|(name, message, _)| {
+            let mut buffer = Vec::new();
+            client_serializer.serialize(&message, &mut buffer).unwrap();
+            json!({ "name": name, "hex": to_hex(&buffer) })
+        })
+        .collect();
+
+    let spec = json!({
+        "spec": "massa-bootstrap-wire",
+        "node_version": VERSION.to_string(),
+        "framing": {
+            "server": "signature || big-endian message length || message, the signature covering the hash of the previous signature (if any) || message",
+            "client": "hash of the previous exchange (if any) || big-endian message length || message",
+            "message": "varint(message type id) || payload, varints are unsigned LEB128",
+        },
+        "server_message_types": enum_ids::<u32, MessageServerTypeId>(),
+        "client_message_types": enum_ids::<u32, MessageClientTypeId>(),
+        "bounds": {
+            "max_bootstrap_message_size": MAX_BOOTSTRAP_MESSAGE_SIZE,
+            "message_length_bytes": MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES,
+            "max_bootstrap_blocks": MAX_BOOTSTRAP_BLOCKS,
+            "max_bootstrap_error_length": MAX_BOOTSTRAP_ERROR_LENGTH,
+            "max_final_state_parts_size": MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE,
+            "max_versioning_elements_size": MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE,
+        },
+        "test_vectors": {
+            "server": server_vectors,
+            "client": client_vectors,
+        },
+    });
+
+    write_wire_spec("bootstrap", &spec);
+}
@@ -2,6 +2,7 @@
 //! expose for each channel :
 //! - actual length of channel (can be inc() when sending msg or dec() when receive)
 //! - total received messages (inc() when receive)
+//! - fill level of bounded channels (length / capacity), to spot saturated channels before they block their senders
 //!
 //! # Example
 //! ```
@@ -51,6 +52,13 @@ impl MassaChannel {
         )
         .expect("Failed to create counter");
 
+        // Create gauge for fill level of channel, only registered for bounded channels
+        let fill_ratio = Gauge::new(
+            format!("{}_channel_fill_ratio", name),
+            "Fill level of channel (length / capacity)",
+        )
+        .expect("Failed to create gauge");
+
         // Register metrics in prometheus
         // error here if metrics already registered (ex : ProtocolController>::get_stats )
 
@@ -64,18 +72,26 @@ impl MassaChannel {
             if let Err(e) = prometheus::register(Box::new(received.clone())) {
                 debug!("Failed to register received counter for {} : {}", name, e);
             }
+
+            if capacity.is_some() {
+                if let Err(e) = prometheus::register(Box::new(fill_ratio.clone())) {
+                    debug!("Failed to register fill_ratio gauge for {} : {}", name, e);
+                }
+            }
         }
 
         let sender = MassaSender {
             sender: s,
             name: name.clone(),
             actual_len: actual_len.clone(),
+            fill_ratio: fill_ratio.clone(),
         };
 
         let receiver = MassaReceiver {
             receiver: r,
             name,
             actual_len,
+            fill_ratio,
             received,
             ref_counter: Arc::new(()),
         };
@@ -15,6 +15,8 @@ pub struct MassaReceiver<T> {
     pub(crate) name: String,
     /// channel size
     pub(crate) actual_len: Gauge,
+    /// channel size / channel capacity
+    pub(crate) fill_ratio: Gauge,
     /// total received messages
     pub(crate) received: Counter,
     /// reference counter to know how many receiver are cloned
@@ -41,6 +43,10 @@ impl<T> MassaReceiver<T> {
         // use the len of the channel for actual_len instead of actual_len.dec()
         // because for each send we call recv more than one time
         self.actual_len.set(self.receiver.len() as f64);
+        if let Some(capacity) = self.receiver.capacity().filter(|c| *c > 0) {
+            self.fill_ratio
+                .set(self.receiver.len() as f64 / capacity as f64);
+        }
 
         self.received.inc();
     }
@@ -55,6 +61,16 @@ impl<T> MassaReceiver<T> {
             );
         }
 
+        if self.receiver.capacity().is_some() {
+            if let Err(e) = prometheus::unregister(Box::new(self.fill_ratio.clone())) {
+                trace!(
+                    "promethetus error unregister fill_ratio for {} : {}",
+                    self.name,
+                    e
+                );
+            }
+        }
+
         if let Err(e) = prometheus::unregister(Box::new(self.received.clone())) {
             trace!(
                 "promethetus error unregister received for {} : {}",
@@ -13,14 +13,25 @@ pub struct MassaSender<T> {
     pub(crate) name: String,
     /// channel size
     pub(crate) actual_len: Gauge,
+    /// channel size / channel capacity
+    pub(crate) fill_ratio: Gauge,
 }
 
 impl<T> MassaSender<T> {
+    /// update the fill level of the channel, if it is bounded
+    fn update_fill_ratio(&self) {
+        if let Some(capacity) = self.sender.capacity().filter(|c| *c > 0) {
+            self.fill_ratio
+                .set(self.sender.len() as f64 / capacity as f64);
+        }
+    }
+
     /// Send a message to the channel
     pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
         match self.sender.send(msg) {
             Ok(()) => {
                 self.actual_len.inc();
+                self.update_fill_ratio();
                 Ok(())
             }
             Err(e) => Err(e),
@@ -31,6 +42,7 @@ impl<T> MassaSender<T> {
         match self.sender.send_timeout(msg, duration) {
             Ok(()) => {
                 self.actual_len.inc();
+                self.update_fill_ratio();
                 Ok(())
             }
             Err(e) => Err(e),
@@ -41,6 +53,7 @@ impl<T> MassaSender<T> {
         match self.sender.send_deadline(msg, deadline) {
             Ok(()) => {
                 self.actual_len.inc();
+                self.update_fill_ratio();
                 Ok(())
             }
             Err(e) => Err(e),
@@ -51,6 +64,7 @@ impl<T> MassaSender<T> {
         match self.sender.try_send(msg) {
             Ok(()) => {
                 self.actual_len.inc();
+                self.update_fill_ratio();
                 Ok(())
             }
             Err(e) => Err(e),
@@ -22,8 +22,9 @@ use massa_models::{
     operation::{Operation, OperationId, OperationType},
     slot::Slot,
 };
-use massa_sdk::Client;
+use massa_sdk::{Client, OperationTemplate};
 use massa_signature::KeyPair;
+use massa_wallet::shamir::{self, KeyShare};
 use massa_wallet::Wallet;
 
 use serde::Serialize;
@@ -93,6 +94,13 @@ pub enum Command {
     )]
     node_stop,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "true|false", pwd_not_needed = "true"),
+        message = "enable or disable maintenance mode: the node stops producing blocks and endorsements but keeps relaying and syncing"
+    )]
+    node_set_maintenance_mode,
+
     #[strum(
         ascii_case_insensitive,
         props(pwd_not_needed = "true"),
@@ -100,6 +108,13 @@ pub enum Command {
     )]
     node_get_staking_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the activation state of the staking addresses (awaiting rolls, awaiting cycle or active)"
+    )]
+    node_get_staking_addresses_activation,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address1 Address2 ..."),
@@ -184,6 +199,13 @@ pub enum Command {
     )]
     get_operations,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OperationId1 OperationId2 ...", pwd_not_needed = "true"),
+        message = "show whether a list of operations can still be included (pending, included, final or expired)"
+    )]
+    get_operations_cancellation_status,
+
     #[strum(
         ascii_case_insensitive,
         props(
@@ -228,6 +250,20 @@ pub enum Command {
     )]
     wallet_add_secret_keys,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address Threshold ShareCount"),
+        message = "split the secret key of the given address into ShareCount shares, any Threshold of which rebuild it"
+    )]
+    wallet_export_key_shares,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "KeyShare1 KeyShare2 ..."),
+        message = "rebuild a secret key from its shares and add it to the wallet"
+    )]
+    wallet_import_key_shares,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address1 Address2 ..."),
@@ -263,6 +299,22 @@ pub enum Command {
     )]
     send_transaction,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "SenderAddress ReceiverAddress Amount Fee FeeIncrement MaxFee BumpAfterPeriods"
+        ),
+        message = "send coins from a wallet address, re-signing with a fee increased by FeeIncrement (up to MaxFee) each time an attempt expires after BumpAfterPeriods periods without being included"
+    )]
+    send_transaction_with_fee_bump,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "ReplacedOperationId SenderAddress ReceiverAddress Amount Fee"),
+        message = "send coins from a wallet address as a replacement of one of its operations that is not final yet; the replaced operation may still be executed until it expires"
+    )]
+    replace_transaction,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress PathToBytecode MaxGas MaxCoins Fee"),
@@ -524,6 +576,22 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_set_maintenance_mode => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let enabled = parameters[0].parse::<bool>()?;
+                match client.private.node_set_maintenance_mode(enabled).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Maintenance mode successfully set to {}", enabled)
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                };
+                Ok(Box::new(()))
+            }
+
             Command::node_get_staking_addresses => {
                 match client.private.get_staking_addresses().await {
                     Ok(staking_addresses) => Ok(Box::new(staking_addresses)),
@@ -531,6 +599,13 @@ impl Command {
                 }
             }
 
+            Command::node_get_staking_addresses_activation => {
+                match client.private.get_staking_addresses_activation().await {
+                    Ok(activations) => Ok(Box::new(activations)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::node_testnet_rewards_program_ownership_proof => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
@@ -620,6 +695,18 @@ impl Command {
                 }
             }
 
+            Command::get_operations_cancellation_status => {
+                let operations = parse_vec::<OperationId>(parameters)?;
+                match client
+                    .public
+                    .get_operations_cancellation_status(operations)
+                    .await
+                {
+                    Ok(cancellation_infos) => Ok(Box::new(cancellation_infos)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_filtered_sc_output_event => {
                 let p_list: [&str; 7] = [
                     "start",
@@ -818,6 +905,50 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::wallet_export_key_shares => {
+                if parameters.len() != 3 {
+                    bail!("wrong number of parameters");
+                }
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                let addr = parameters[0].parse::<Address>()?;
+                let threshold = parameters[1].parse::<u8>()?;
+                let share_count = parameters[2].parse::<u8>()?;
+                let keypair = match wallet.get_full_wallet().get(&addr) {
+                    Some(keypair) => keypair,
+                    None => bail!("address {} is not in the wallet", addr),
+                };
+                let shares: Vec<String> = shamir::split_keypair(keypair, threshold, share_count)?
+                    .iter()
+                    .map(|share| share.to_string())
+                    .collect();
+                if !json {
+                    client_warning!(format!(
+                        "do not store the shares together: any {} of them rebuild the secret key",
+                        threshold
+                    ));
+                }
+                Ok(Box::new(shares))
+            }
+
+            Command::wallet_import_key_shares => {
+                if parameters.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                let shares = parse_vec::<KeyShare>(parameters)?;
+                let keypair = shamir::combine_key_shares(&shares)?;
+                let address = wallet.add_keypairs(vec![keypair])?[0];
+                if json {
+                    return Ok(Box::new(address.to_string()));
+                } else {
+                    println!("Rebuilt and added address {} to the wallet.", address);
+                    println!("Type `node_start_staking <address>` to start staking with the corresponding key.\n");
+                }
+                Ok(Box::new(()))
+            }
+
             Command::wallet_remove_addresses => {
                 if parameters.is_empty() {
                     bail!("wrong number of parameters");
@@ -974,6 +1105,77 @@ impl Command {
                 )
                 .await
             }
+            Command::replace_transaction => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 5 {
+                    bail!("wrong number of parameters");
+                }
+                let replaced = parameters[0].parse::<OperationId>()?;
+                let addr = parameters[1].parse::<Address>()?;
+                let recipient_address = parameters[2].parse::<Address>()?;
+                let amount = parameters[3].parse::<Amount>()?;
+                let fee = parameters[4].parse::<Amount>()?;
+
+                let input = create_operation_input(
+                    client,
+                    wallet,
+                    OperationType::Transaction {
+                        recipient_address,
+                        amount,
+                    },
+                    fee,
+                    addr,
+                )
+                .await?;
+                match client
+                    .public
+                    .send_replacement_operation(replaced, input)
+                    .await
+                {
+                    Ok(replacement) => Ok(Box::new(replacement)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+            Command::send_transaction_with_fee_bump => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 7 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = parameters[0].parse::<Address>()?;
+                let recipient_address = parameters[1].parse::<Address>()?;
+                let amount = parameters[2].parse::<Amount>()?;
+                let fee = parameters[3].parse::<Amount>()?;
+                let fee_increment = parameters[4].parse::<Amount>()?;
+                let max_fee = parameters[5].parse::<Amount>()?;
+                let bump_after_periods = parameters[6].parse::<u64>()?;
+
+                if max_fee < fee {
+                    bail!("MaxFee must be greater than or equal to Fee");
+                }
+                if bump_after_periods == 0 {
+                    bail!("BumpAfterPeriods must be strictly positive");
+                }
+
+                send_operation_with_fee_bump(
+                    client,
+                    wallet,
+                    OperationTemplate::new(
+                        addr,
+                        OperationType::Transaction {
+                            recipient_address,
+                            amount,
+                        },
+                        fee,
+                    ),
+                    fee_increment,
+                    max_fee,
+                    bump_after_periods,
+                    json,
+                )
+                .await
+            }
             Command::when_moon => {
                 let res = "At night 🌔.";
                 if !json {
@@ -1360,6 +1562,26 @@ async fn send_operation(
     addr: Address,
     json: bool,
 ) -> Result<Box<dyn Output>> {
+    let input = create_operation_input(client, wallet, op, fee, addr).await?;
+    match client.public.send_operations(vec![input]).await {
+        Ok(operation_ids) => {
+            if !json {
+                println!("Sent operation IDs:");
+            }
+            Ok(Box::new(operation_ids))
+        }
+        Err(e) => rpc_error!(e),
+    }
+}
+
+/// helper to sign an operation with proper validity period
+async fn create_operation_input(
+    client: &Client,
+    wallet: &Wallet,
+    op: OperationType,
+    fee: Amount,
+    addr: Address,
+) -> Result<OperationInput> {
     let status = match client.public.get_status().await {
         Ok(node_status) => node_status,
         Err(e) => rpc_error!(e),
@@ -1379,36 +1601,147 @@ async fn send_operation(
         status.config.genesis_timestamp,
     )?
     .unwrap_or_else(|| Slot::new(0, 0));
-    let mut expire_period = slot.period + status.config.operation_validity_periods;
-    if slot.thread >= addr.get_thread(status.config.thread_count) {
-        expire_period += 1;
-    };
+    let expire_period = Operation::compute_expire_period(
+        slot,
+        addr.get_thread(status.config.thread_count),
+        status.config.operation_validity_periods,
+    );
 
     let op = wallet.create_operation(
         Operation {
             fee,
             expire_period,
+            start_period: None,
+            fee_payer: None,
             op,
         },
         addr,
     )?;
 
-    match client
-        .public
-        .send_operations(vec![OperationInput {
-            creator_public_key: op.content_creator_pub_key,
-            serialized_content: op.serialized_data,
-            signature: op.signature,
-        }])
-        .await
-    {
-        Ok(operation_ids) => {
-            if !json {
-                println!("Sent operation IDs:");
+    Ok(OperationInput {
+        creator_public_key: op.content_creator_pub_key,
+        serialized_content: op.serialized_data,
+        signature: op.signature,
+    })
+}
+
+/// Sends the operation described by `template` with a short validity of `bump_after_periods` periods.
+/// Each time an attempt expires without being included, the template is re-signed with a fee
+/// increased by `fee_increment` (capped to `max_fee`) and sent again.
+/// A new attempt is only sent once the previous one can no longer be included, so that at most
+/// one of them gets executed.
+async fn send_operation_with_fee_bump(
+    client: &Client,
+    wallet: &Wallet,
+    mut template: OperationTemplate,
+    fee_increment: Amount,
+    max_fee: Amount,
+    bump_after_periods: u64,
+    json: bool,
+) -> Result<Box<dyn Output>> {
+    let status = match client.public.get_status().await {
+        Ok(node_status) => node_status,
+        Err(e) => rpc_error!(e),
+    };
+    if template.fee.checked_sub(status.minimal_fees).is_none() {
+        bail!(format!(
+            "fee is too low provided: {} , minimal_fees required: {}",
+            template.fee, status.minimal_fees
+        ));
+    }
+    let thread_count = status.config.thread_count;
+    let t0 = status.config.t0;
+    let genesis_timestamp = status.config.genesis_timestamp;
+    let sender_thread = template.sender_address.get_thread(thread_count);
+
+    loop {
+        let slot = get_current_latest_block_slot(thread_count, t0, genesis_timestamp)?
+            .unwrap_or_else(|| Slot::new(0, 0));
+        let expire_period =
+            Operation::compute_expire_period(slot, sender_thread, bump_after_periods);
+
+        let op = wallet.create_operation(
+            template.to_operation(expire_period),
+            template.sender_address,
+        )?;
+        let operation_ids = match client
+            .public
+            .send_operations(vec![OperationInput {
+                creator_public_key: op.content_creator_pub_key,
+                serialized_content: op.serialized_data,
+                signature: op.signature,
+            }])
+            .await
+        {
+            Ok(operation_ids) => operation_ids,
+            Err(e) => rpc_error!(e),
+        };
+        let Some(operation_id) = operation_ids.first().copied() else {
+            bail!("the node did not return any operation ID");
+        };
+        if !json {
+            println!(
+                "Sent operation {} with fee {}, expiring after period {}",
+                operation_id, template.fee, expire_period
+            );
+        }
+
+        // wait until the attempt is included or can no longer be included
+        loop {
+            match client.public.is_operation_included(operation_id).await {
+                Ok(true) => {
+                    if !json {
+                        println!("Included operation ID:");
+                    }
+                    return Ok(Box::new(operation_ids));
+                }
+                Ok(false) => {}
+                Err(e) => rpc_error!(e),
             }
-            Ok(Box::new(operation_ids))
+            let slot = get_current_latest_block_slot(thread_count, t0, genesis_timestamp)?
+                .unwrap_or_else(|| Slot::new(0, 0));
+            if slot.period > expire_period {
+                break;
+            }
+            tokio::time::sleep(t0.to_duration()).await;
+        }
+
+        // the attempt may still sit in a block that is not final yet:
+        // wait until its last valid slot is final, then check its status again
+        let last_valid_slot = Slot::new(expire_period, sender_thread);
+        loop {
+            let final_cursor = match client.public.get_status().await {
+                Ok(node_status) => node_status.execution_stats.final_cursor,
+                Err(e) => rpc_error!(e),
+            };
+            if final_cursor >= last_valid_slot {
+                break;
+            }
+            tokio::time::sleep(t0.to_duration()).await;
+        }
+        match client.public.is_operation_included(operation_id).await {
+            Ok(true) => {
+                if !json {
+                    println!("Included operation ID:");
+                }
+                return Ok(Box::new(operation_ids));
+            }
+            Ok(false) => {}
+            Err(e) => rpc_error!(e),
+        }
+
+        if !template.bump_fee(fee_increment, max_fee) {
+            bail!(format!(
+                "operation {} expired without being included and the fee already reached {}",
+                operation_id, max_fee
+            ));
+        }
+        if !json {
+            println!(
+                "Operation {} expired without being included, bumping fee to {}",
+                operation_id, template.fee
+            );
         }
-        Err(e) => rpc_error!(e),
     }
 }
 
@@ -4,9 +4,16 @@ use crate::cmds::ExtendedWallet;
 use console::style;
 use erased_serde::{Serialize, Serializer};
 use massa_api_exports::{
-    address::AddressInfo, block::BlockInfo, datastore::DatastoreEntryOutput,
-    endorsement::EndorsementInfo, execution::ExecuteReadOnlyResponse, node::NodeStatus,
-    operation::OperationInfo,
+    address::AddressInfo,
+    block::BlockInfo,
+    datastore::DatastoreEntryOutput,
+    endorsement::EndorsementInfo,
+    execution::ExecuteReadOnlyResponse,
+    node::NodeStatus,
+    operation::{
+        OperationCancellationInfo, OperationCancellationStatus, OperationInfo, OperationReplacement,
+    },
+    rolls::StakingAddressActivation,
 };
 use massa_models::composite::PubkeySig;
 use massa_models::output_event::SCOutputEvent;
@@ -154,6 +161,13 @@ impl Output for ExtendedWallet {
                 Style::Pending.style("candidate"),
                 Style::Coins.style(entry.address_info.candidate_balance),
             );
+            println!(
+                "\tTotal balance (including locked coins): {}={}, {}={}",
+                Style::Finished.style("final"),
+                Style::Coins.style(entry.address_info.final_total_balance),
+                Style::Pending.style("candidate"),
+                Style::Coins.style(entry.address_info.candidate_total_balance),
+            );
             println!(
                 "\tRolls: {}={}, {}={}, {}={}",
                 Style::Good.style("active"),
@@ -216,6 +230,10 @@ impl Output for &str {
 
 impl Output for NodeStatus {
     fn pretty_print(&self) {
+        if let Some(warning) = &self.net_split_warning {
+            println!("{}", Style::Bad.style(format!("WARNING: {}", warning)));
+            println!();
+        }
         println!("Node's ID: {}", Style::Id.style(self.node_id));
         if self.node_ip.is_some() {
             println!(
@@ -228,6 +246,13 @@ impl Output for NodeStatus {
         println!();
 
         println!("Version: {}", Style::Id.style(self.version));
+        if self.maintenance_mode {
+            println!(
+                "{}",
+                Style::Bad
+                    .style("Maintenance mode: enabled (not producing blocks nor endorsements)")
+            );
+        }
         self.config.pretty_print();
         println!();
 
@@ -243,6 +268,10 @@ impl Output for NodeStatus {
             );
         }
         println!("Next slot: {}", Style::Protocol.style(self.next_slot));
+        println!(
+            "Chain parameters version: {}",
+            Style::Protocol.style(self.chain_parameters_version)
+        );
         println!();
 
         self.consensus_stats.pretty_print();
@@ -264,12 +293,19 @@ impl Output for NodeStatus {
         if !self.connected_nodes.is_empty() {
             println!("Connected nodes:");
             for (node_id, (ip_addr, is_outgoing)) in &self.connected_nodes {
-                println!(
+                print!(
                     "Node's ID: {} / IP address: {} / {} connection",
                     Style::Id.style(node_id),
                     Style::Protocol.style(ip_addr),
                     if *is_outgoing { "Out" } else { "In" }
-                )
+                );
+                if let Some(latency) = self.connected_nodes_latencies.get(node_id) {
+                    print!(" / Latency: {}", Style::Protocol.style(latency));
+                }
+                match self.connected_nodes_agents.get(node_id) {
+                    Some(agent) => println!(" / Version: {}", Style::Protocol.style(agent)),
+                    None => println!(),
+                }
             }
         }
 
@@ -331,6 +367,21 @@ impl Output for NetworkStats {
             "\tActive nodes: {}",
             Style::Good.style(self.active_node_count)
         );
+        match self.reachable {
+            Some(true) => println!("\tRoutable address reachable: {}", Style::Good.style("yes")),
+            Some(false) => println!("\tRoutable address reachable: {}", Style::Bad.style("no")),
+            None => println!(
+                "\tRoutable address reachable: {}",
+                Style::Unknown.style("unknown")
+            ),
+        }
+        match self.network_time_offset {
+            Some(offset) => println!(
+                "\tNetwork time offset: {}",
+                Style::Time.style(format!("{} ms", offset))
+            ),
+            None => println!("\tNetwork time offset: {}", Style::Unknown.style("unknown")),
+        }
     }
 }
 
@@ -409,6 +460,14 @@ impl Output for PreHashSet<Address> {
     }
 }
 
+impl Output for Vec<StakingAddressActivation> {
+    fn pretty_print(&self) {
+        for activation in self {
+            println!("{}", activation);
+        }
+    }
+}
+
 impl Output for Vec<AddressInfo> {
     fn pretty_print(&self) {
         for info in self {
@@ -425,6 +484,13 @@ impl Output for Vec<AddressInfo> {
                 Style::Pending.style("candidate"),
                 Style::Coins.style(info.candidate_balance),
             );
+            println!(
+                "\tTotal balance (including locked coins): {}={}, {}={}",
+                Style::Finished.style("final"),
+                Style::Coins.style(info.final_balance_breakdown.total),
+                Style::Pending.style("candidate"),
+                Style::Coins.style(info.candidate_balance_breakdown.total),
+            );
             println!(
                 "\tRolls: {}={}, {}={}",
                 Style::Finished.style("final"),
@@ -478,6 +544,38 @@ impl Output for Vec<DatastoreEntryOutput> {
     }
 }
 
+impl Output for Vec<OperationCancellationInfo> {
+    fn pretty_print(&self) {
+        for info in self {
+            let status = match info.status {
+                OperationCancellationStatus::Pending | OperationCancellationStatus::Included => {
+                    Style::Pending.style(info.status)
+                }
+                OperationCancellationStatus::Final => Style::Finished.style(info.status),
+                OperationCancellationStatus::Expired => Style::Good.style(info.status),
+                OperationCancellationStatus::Unknown => Style::Unknown.style(info.status),
+            };
+            print!("Operation {}: {}", Style::Id.style(info.id), status);
+            if let Some(expire_slot) = info.expire_slot {
+                print!(
+                    " (expires after slot {})",
+                    Style::Protocol.style(expire_slot)
+                );
+            }
+            println!();
+            for block_id in &info.in_blocks {
+                println!("\t- in block {}", Style::Block.style(block_id));
+            }
+        }
+    }
+}
+
+impl Output for OperationReplacement {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
 impl Output for Vec<EndorsementInfo> {
     fn pretty_print(&self) {
         for endorsement_info in self {
@@ -571,6 +669,14 @@ impl Output for Vec<OperationId> {
     }
 }
 
+impl Output for Vec<String> {
+    fn pretty_print(&self) {
+        for line in self {
+            println!("{}", line);
+        }
+    }
+}
+
 impl Output for Vec<Address> {
     fn pretty_print(&self) {
         for addr in self {
@@ -0,0 +1,215 @@
+//! On-disk archive of the final blocks and of their operations, used by archival nodes
+//! to keep the full history beyond the pruning horizons of consensus and storage.
+
+use massa_models::{
+    address::Address,
+    block::SecureShareBlock,
+    block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer},
+    operation::{
+        OperationId, OperationIdDeserializer, OperationIdSerializer, SecureShareOperation,
+    },
+    prehash::{PreHashSet, PreHashed},
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use nom::IResult;
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use std::{hash::Hash, path::PathBuf, sync::Arc};
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const ID_SER_ERROR: &str = "critical: archived id serialization failed";
+const DATA_SER_ERROR: &str = "critical: archived data serialization failed";
+const DATA_DESER_ERROR: &str = "critical: archived data deserialization failed";
+const BLOCK_IDENT: u8 = 0u8;
+const OPERATION_IDENT: u8 = 1u8;
+const OPERATION_BLOCK_IDENT: u8 = 2u8;
+const ADDRESS_BLOCK_IDENT: u8 = 3u8;
+const ADDRESS_OPERATION_IDENT: u8 = 4u8;
+
+/// Archive of the final blocks and operations, shared by consensus that fills it
+/// and by the API that answers the queries about the pruned history from it
+#[derive(Clone)]
+pub struct BlockArchive {
+    /// RocksDB database
+    db: Arc<DB>,
+}
+
+impl BlockArchive {
+    /// Open the archive stored at `path`, creating it if needed
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            db: Arc::new(DB::open_default(path).expect(OPEN_ERROR)),
+        }
+    }
+
+    /// Archive a final block and its operations, and index them by creator address
+    pub fn archive_block(&self, block: &SecureShareBlock, operations: &[SecureShareOperation]) {
+        let block_id = block_id_bytes(&block.id);
+        let mut batch = WriteBatch::default();
+        batch.put(
+            [&[BLOCK_IDENT], &block_id[..]].concat(),
+            serde_json::to_vec(block).expect(DATA_SER_ERROR),
+        );
+        batch.put(
+            [
+                &[ADDRESS_BLOCK_IDENT],
+                &block.content_creator_address.to_prefixed_bytes()[..],
+                &block_id[..],
+            ]
+            .concat(),
+            b"",
+        );
+        for operation in operations {
+            let operation_id = operation_id_bytes(&operation.id);
+            batch.put(
+                [&[OPERATION_IDENT], &operation_id[..]].concat(),
+                serde_json::to_vec(operation).expect(DATA_SER_ERROR),
+            );
+            batch.put(
+                [&[OPERATION_BLOCK_IDENT], &operation_id[..], &block_id[..]].concat(),
+                b"",
+            );
+            batch.put(
+                [
+                    &[ADDRESS_OPERATION_IDENT],
+                    &operation.content_creator_address.to_prefixed_bytes()[..],
+                    &operation_id[..],
+                ]
+                .concat(),
+                b"",
+            );
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get an archived block
+    pub fn get_block(&self, block_id: &BlockId) -> Option<SecureShareBlock> {
+        self.db
+            .get([&[BLOCK_IDENT], &block_id_bytes(block_id)[..]].concat())
+            .expect(CRUD_ERROR)
+            .map(|bytes| serde_json::from_slice(&bytes).expect(DATA_DESER_ERROR))
+    }
+
+    /// Get an archived operation
+    pub fn get_operation(&self, operation_id: &OperationId) -> Option<SecureShareOperation> {
+        self.db
+            .get([&[OPERATION_IDENT], &operation_id_bytes(operation_id)[..]].concat())
+            .expect(CRUD_ERROR)
+            .map(|bytes| serde_json::from_slice(&bytes).expect(DATA_DESER_ERROR))
+    }
+
+    /// Get the archived blocks that contain an operation
+    pub fn get_blocks_by_operation(&self, operation_id: &OperationId) -> PreHashSet<BlockId> {
+        let deserializer = BlockIdDeserializer::new();
+        self.indexed_ids(
+            [
+                &[OPERATION_BLOCK_IDENT],
+                &operation_id_bytes(operation_id)[..],
+            ]
+            .concat(),
+            |bytes| deserializer.deserialize::<DeserializeError>(bytes),
+        )
+    }
+
+    /// Get the archived blocks created by an address
+    pub fn get_blocks_created_by(&self, address: &Address) -> PreHashSet<BlockId> {
+        let deserializer = BlockIdDeserializer::new();
+        self.indexed_ids(
+            [&[ADDRESS_BLOCK_IDENT], &address.to_prefixed_bytes()[..]].concat(),
+            |bytes| deserializer.deserialize::<DeserializeError>(bytes),
+        )
+    }
+
+    /// Get the archived operations created by an address
+    pub fn get_operations_created_by(&self, address: &Address) -> PreHashSet<OperationId> {
+        let deserializer = OperationIdDeserializer::new();
+        self.indexed_ids(
+            [&[ADDRESS_OPERATION_IDENT], &address.to_prefixed_bytes()[..]].concat(),
+            |bytes| deserializer.deserialize::<DeserializeError>(bytes),
+        )
+    }
+
+    /// Collect the ids that follow `prefix` in the keys of an index
+    fn indexed_ids<ID, F>(&self, prefix: Vec<u8>, deserialize: F) -> PreHashSet<ID>
+    where
+        ID: PreHashed + Eq + Hash,
+        F: Fn(&[u8]) -> IResult<&[u8], ID, DeserializeError>,
+    {
+        self.db
+            .iterator(IteratorMode::From(&prefix, Direction::Forward))
+            .map(|entry| entry.expect(CRUD_ERROR).0)
+            .take_while(|key| key.starts_with(&prefix))
+            .map(|key| deserialize(&key[prefix.len()..]).expect(DATA_DESER_ERROR).1)
+            .collect()
+    }
+}
+
+fn block_id_bytes(block_id: &BlockId) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    BlockIdSerializer::new()
+        .serialize(block_id, &mut bytes)
+        .expect(ID_SER_ERROR);
+    bytes
+}
+
+fn operation_id_bytes(operation_id: &OperationId) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    OperationIdSerializer::new()
+        .serialize(operation_id, &mut bytes)
+        .expect(ID_SER_ERROR);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::slot::Slot;
+    use massa_protocol_exports::test_exports::tools::{
+        create_block, create_block_with_operations, create_operation_with_expire_period,
+    };
+    use massa_signature::KeyPair;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_archive_block() {
+        let tmp_dir = TempDir::new().unwrap();
+        let archive = BlockArchive::new(tmp_dir.path().to_path_buf());
+        let block_keypair = KeyPair::generate(0).unwrap();
+        let op_keypair = KeyPair::generate(0).unwrap();
+        let operations = vec![
+            create_operation_with_expire_period(&op_keypair, 10),
+            create_operation_with_expire_period(&op_keypair, 11),
+        ];
+        let block =
+            create_block_with_operations(&block_keypair, Slot::new(1, 0), operations.clone());
+        archive.archive_block(&block, &operations);
+
+        assert_eq!(
+            archive.get_block(&block.id).unwrap().serialized_data,
+            block.serialized_data
+        );
+        assert_eq!(
+            archive
+                .get_operation(&operations[0].id)
+                .unwrap()
+                .serialized_data,
+            operations[0].serialized_data
+        );
+        assert!(archive.get_block(&create_block(&op_keypair).id).is_none());
+        assert_eq!(
+            archive.get_blocks_by_operation(&operations[1].id),
+            [block.id].into_iter().collect()
+        );
+        assert_eq!(
+            archive.get_blocks_created_by(&block.content_creator_address),
+            [block.id].into_iter().collect()
+        );
+        assert_eq!(
+            archive.get_operations_created_by(&operations[0].content_creator_address),
+            operations.iter().map(|op| op.id).collect()
+        );
+        assert!(archive
+            .get_blocks_created_by(&operations[0].content_creator_address)
+            .is_empty());
+    }
+}
@@ -142,6 +142,21 @@ pub enum BlockStatus {
     },
 }
 
+impl BlockStatus {
+    /// Gets the slot of the block, whatever its status
+    pub fn get_slot(&self) -> Slot {
+        match self {
+            BlockStatus::Incoming(header_or_block)
+            | BlockStatus::WaitingForSlot(header_or_block)
+            | BlockStatus::WaitingForDependencies {
+                header_or_block, ..
+            } => header_or_block.get_slot(),
+            BlockStatus::Active { a_block, .. } => a_block.slot,
+            BlockStatus::Discarded { slot, .. } => *slot,
+        }
+    }
+}
+
 /// Block status in the graph that can be exported.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExportBlockStatus {
@@ -8,6 +8,7 @@ use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolController;
 
+use crate::archive::BlockArchive;
 use crate::events::ConsensusEvent;
 
 /// Contains links to other modules of the node to be able to interact with them.
@@ -25,6 +26,8 @@ pub struct ConsensusChannels {
     pub controller_event_tx: MassaSender<ConsensusEvent>,
     /// Structure used by consensus to broadcast all the information about the blocks
     pub broadcasts: ConsensusBroadcasts,
+    /// Archive in which final blocks are kept if the node is archival
+    pub block_archive: Option<BlockArchive>,
 }
 
 /// Structure used to broadcast all the information about the blocks
@@ -1,4 +1,7 @@
 use crate::block_graph_export::BlockGraphExport;
+use crate::dependencies::BlockDependencies;
+use crate::equivocation::EquivocationEvidence;
+use crate::parent_selection::ParentSelectionDiagnostics;
 use crate::{bootstrapable_graph::BootstrapableGraph, error::ConsensusError};
 use massa_models::prehash::PreHashSet;
 use massa_models::streaming_step::StreamingStep;
@@ -121,6 +124,15 @@ pub trait ConsensusController: Send + Sync {
     /// * `header`: the header of the block to mark as invalid
     fn mark_invalid_block(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>);
 
+    /// Get the equivocations detected recently, oldest first
+    fn get_equivocations(&self) -> Vec<EquivocationEvidence>;
+
+    /// Get the blocks waiting for missing dependencies and the blocks wished from the network
+    fn get_block_dependencies(&self) -> BlockDependencies;
+
+    /// Get the best parents a block produced now would use, and why more recent blocks were not selected
+    fn get_parent_selection(&self) -> Result<ParentSelectionDiagnostics, ConsensusError>;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ConsensusController>`.
     fn clone_box(&self) -> Box<dyn ConsensusController>;
@@ -0,0 +1,38 @@
+//! Blocks waiting for missing dependencies and blocks wished from the network
+
+use massa_models::{block_id::BlockId, slot::Slot};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Block that cannot be processed until some of its dependencies are received
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedBlock {
+    /// id of the blocked block
+    pub block_id: BlockId,
+    /// slot of the blocked block
+    pub slot: Slot,
+    /// dependencies (parents or blocks they depend on) that are not processed yet
+    pub missing_dependencies: Vec<BlockId>,
+    /// time since which the block is waiting
+    pub blocked_since: MassaTime,
+}
+
+/// Block asked to the network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WishedBlock {
+    /// id of the wished block
+    pub block_id: BlockId,
+    /// true if the header of the block is already known
+    pub header_known: bool,
+    /// blocks waiting for this one
+    pub blocked_blocks: Vec<BlockId>,
+}
+
+/// Snapshot of the blocks waiting for dependencies and of the blocks wished from the network
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockDependencies {
+    /// blocks asked to the network, sorted by id
+    pub wishlist: Vec<WishedBlock>,
+    /// blocks waiting for missing dependencies, sorted by slot
+    pub blocked: Vec<BlockedBlock>,
+}
@@ -0,0 +1,21 @@
+//! Evidence of block producers signing several headers for the same slot
+
+use massa_models::{address::Address, block_header::SecuredHeader, slot::Slot};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Two different headers signed by the same creator for the same slot.
+/// Both headers carry their own signature, so the evidence can be checked by anyone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquivocationEvidence {
+    /// slot of both headers
+    pub slot: Slot,
+    /// address of the creator of both headers
+    pub creator_address: Address,
+    /// first header received for the slot
+    pub header_1: SecuredHeader,
+    /// conflicting header received afterwards
+    pub header_2: SecuredHeader,
+    /// time at which the equivocation was detected
+    pub detected_at: MassaTime,
+}
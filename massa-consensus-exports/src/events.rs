@@ -1,3 +1,7 @@
+use massa_models::{block_id::BlockId, slot::Slot};
+
+use crate::equivocation::EquivocationEvidence;
+
 /// Events that are emitted by consensus.
 #[derive(Debug, Clone)]
 pub enum ConsensusEvent {
@@ -5,4 +9,11 @@ pub enum ConsensusEvent {
     NeedSync,
     /// Network is ended should be send after `end_timestamp`
     Stop,
+    /// a block producer signed two different headers for the same slot
+    EquivocationDetected(Box<EquivocationEvidence>),
+    /// non-final blocks left the blockclique, sorted by slot
+    Reorg {
+        /// blocks that are not in the blockclique anymore
+        removed_blocks: Vec<(Slot, BlockId)>,
+    },
 }
@@ -5,12 +5,16 @@ mod channels;
 mod controller_trait;
 mod settings;
 
+pub mod archive;
 pub mod block_graph_export;
 pub mod block_status;
 pub mod bootstrapable_graph;
+pub mod dependencies;
+pub mod equivocation;
 pub mod error;
 pub mod events;
 pub mod export_active_block;
+pub mod parent_selection;
 
 pub use channels::{ConsensusBroadcasts, ConsensusChannels};
 pub use controller_trait::{ConsensusController, ConsensusManager};
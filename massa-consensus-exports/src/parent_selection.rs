@@ -0,0 +1,53 @@
+//! Diagnostics of the selection of the parents used by the block factory
+
+use massa_models::{block_id::BlockId, slot::Slot};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Why a slot more recent than the selected parent of its thread did not provide the parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParentRejectionReason {
+    /// no block is known at that slot
+    Missing,
+    /// the block is known but not processed yet (waiting for its slot, its dependencies or processing)
+    Late,
+    /// the block is active but not part of the blockclique
+    NotInBlockclique,
+    /// the block was discarded (invalid or stale)
+    Discarded,
+}
+
+/// Slot more recent than the selected parent of its thread, and why it did not provide the parent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedParent {
+    /// slot of the candidate
+    pub slot: Slot,
+    /// id of the candidate block, if any block is known at that slot
+    pub block_id: Option<BlockId>,
+    /// why the candidate was rejected
+    pub reason: ParentRejectionReason,
+}
+
+/// Parent selected in a thread and the more recent candidates that were rejected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadParentSelection {
+    /// thread of the parent
+    pub thread: u8,
+    /// id of the selected parent
+    pub parent_id: BlockId,
+    /// period of the selected parent
+    pub parent_period: u64,
+    /// rejected candidates, sorted by slot
+    pub rejected: Vec<RejectedParent>,
+}
+
+/// Snapshot of the best parents a block produced now would use, one per thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParentSelectionDiagnostics {
+    /// time of the snapshot
+    pub timestamp: MassaTime,
+    /// latest slot at the time of the snapshot, if genesis is reached
+    pub current_slot: Option<Slot>,
+    /// parent selection in each thread
+    pub threads: Vec<ThreadParentSelection>,
+}
@@ -20,8 +20,6 @@ pub struct ConsensusConfig {
     pub max_dependency_blocks: usize,
     /// old blocks are pruned every `block_db_prune_interval`
     pub block_db_prune_interval: MassaTime,
-    /// Max gas per block for the execution configuration
-    pub max_gas_per_block: u64,
     /// Threshold for fitness.
     pub delta_f0: u64,
     /// Maximum operation validity period count
@@ -34,6 +32,10 @@ pub struct ConsensusConfig {
     pub force_keep_final_periods_without_ops: u64,
     /// target number of endorsement per block
     pub endorsement_count: u32,
+    /// minimum number of endorsements a block must include (0 to disable the requirement)
+    pub min_block_endorsements: u32,
+    /// the endorsement requirement is lowered by one for each such number of periods without block since the parent
+    pub endorsement_relaxation_periods: u64,
     /// TESTNET: time when the blockclique is ended.
     pub end_timestamp: Option<MassaTime>,
     /// stats time span
@@ -54,4 +56,10 @@ pub struct ConsensusConfig {
     pub last_start_period: u64,
     /// chain id
     pub chain_id: u64,
+    /// number of periods after finality during which headers are kept to detect equivocations
+    pub equivocation_history_periods: u64,
+    /// maximum number of detected equivocations kept in memory
+    pub max_equivocation_records: usize,
+    /// headers more than this number of periods ahead of the current slot are not tracked for equivocations
+    pub max_equivocation_future_periods: u64,
 }
@@ -1,7 +1,8 @@
 use massa_models::config::{
     constants::{
-        CHANNEL_SIZE, DELTA_F0, ENDORSEMENT_COUNT, GENESIS_KEY, GENESIS_TIMESTAMP,
-        MAX_GAS_PER_BLOCK, OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, T0, THREAD_COUNT,
+        CHANNEL_SIZE, DELTA_F0, DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT,
+        ENDORSEMENT_RELAXATION_PERIODS, GENESIS_KEY, GENESIS_TIMESTAMP, MIN_BLOCK_ENDORSEMENTS,
+        OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, T0, THREAD_COUNT,
     },
     CHAINID, CONSENSUS_BOOTSTRAP_PART_SIZE,
 };
@@ -20,13 +21,14 @@ impl Default for ConsensusConfig {
             max_future_processing_blocks: 100,
             max_dependency_blocks: 2048,
             block_db_prune_interval: MassaTime::from_millis(5000),
-            max_gas_per_block: MAX_GAS_PER_BLOCK,
             delta_f0: DELTA_F0,
             operation_validity_periods: OPERATION_VALIDITY_PERIODS,
             periods_per_cycle: PERIODS_PER_CYCLE,
             force_keep_final_periods: 20,
             force_keep_final_periods_without_ops: 128,
             endorsement_count: ENDORSEMENT_COUNT,
+            min_block_endorsements: MIN_BLOCK_ENDORSEMENTS,
+            endorsement_relaxation_periods: ENDORSEMENT_RELAXATION_PERIODS,
             end_timestamp: None,
             stats_timespan: MassaTime::from_millis(60000),
             channel_size: CHANNEL_SIZE,
@@ -37,6 +39,9 @@ impl Default for ConsensusConfig {
             broadcast_filled_blocks_channel_capacity: 128,
             last_start_period: 0,
             chain_id: *CHAINID,
+            equivocation_history_periods: DENUNCIATION_EXPIRE_PERIODS,
+            max_equivocation_records: 1000,
+            max_equivocation_future_periods: 2,
         }
     }
 }
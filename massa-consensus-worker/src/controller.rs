@@ -2,8 +2,10 @@ use massa_channel::sender::MassaSender;
 use massa_consensus_exports::ConsensusBroadcasts;
 use massa_consensus_exports::{
     block_graph_export::BlockGraphExport, block_status::BlockStatus,
-    bootstrapable_graph::BootstrapableGraph, error::ConsensusError,
-    export_active_block::ExportActiveBlock, ConsensusController,
+    bootstrapable_graph::BootstrapableGraph, dependencies::BlockDependencies,
+    equivocation::EquivocationEvidence, error::ConsensusError,
+    export_active_block::ExportActiveBlock, parent_selection::ParentSelectionDiagnostics,
+    ConsensusController,
 };
 use massa_models::{
     block::{BlockGraphStatus, FilledBlock},
@@ -315,6 +317,23 @@ impl ConsensusController for ConsensusControllerImpl {
         }
     }
 
+    fn get_equivocations(&self) -> Vec<EquivocationEvidence> {
+        self.shared_state
+            .read()
+            .equivocations
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    fn get_block_dependencies(&self) -> BlockDependencies {
+        self.shared_state.read().get_block_dependencies()
+    }
+
+    fn get_parent_selection(&self) -> Result<ParentSelectionDiagnostics, ConsensusError> {
+        self.shared_state.read().get_parent_selection()
+    }
+
     fn clone_box(&self) -> Box<dyn ConsensusController> {
         Box::new(self.clone())
     }
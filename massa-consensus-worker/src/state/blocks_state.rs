@@ -1,7 +1,14 @@
 use core::panic;
+use std::{
+    collections::{btree_map, BTreeMap},
+    ops::RangeBounds,
+};
 
-use massa_consensus_exports::block_status::{BlockStatus, BlockStatusId};
+use massa_consensus_exports::block_status::{
+    BlockStatus, BlockStatusId, HeaderOrBlock, StorageOrBlock,
+};
 use massa_models::{
+    active_block::ActiveBlock,
     block_id::BlockId,
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
@@ -23,6 +30,8 @@ pub struct BlocksState {
     discarded_index: PreHashSet<BlockId>,
     /// ids of active blocks
     active_index: PreHashSet<BlockId>,
+    /// ids of all blocks, ordered by slot for range queries
+    slot_index: BTreeMap<Slot, PreHashSet<BlockId>>,
 }
 
 impl BlocksState {
@@ -36,6 +45,7 @@ impl BlocksState {
             waiting_for_dependencies_index: PreHashSet::default(),
             discarded_index: PreHashSet::default(),
             active_index: PreHashSet::default(),
+            slot_index: BTreeMap::new(),
         }
     }
 
@@ -83,9 +93,24 @@ impl BlocksState {
     fn update_indexes(
         &mut self,
         block_id: &BlockId,
+        slot: Slot,
         old_block_status: Option<&BlockStatusId>,
         new_block_status: Option<&BlockStatusId>,
     ) {
+        match (old_block_status, new_block_status) {
+            (Some(_), None) => {
+                if let btree_map::Entry::Occupied(mut occ) = self.slot_index.entry(slot) {
+                    occ.get_mut().remove(block_id);
+                    if occ.get().is_empty() {
+                        occ.remove();
+                    }
+                }
+            }
+            (None, Some(_)) => {
+                self.slot_index.entry(slot).or_default().insert(*block_id);
+            }
+            _ => {}
+        }
         if let Some(old_block_status) = old_block_status {
             match old_block_status {
                 BlockStatusId::Incoming => {
@@ -169,11 +194,82 @@ impl BlocksState {
         self.block_statuses.iter()
     }
 
+    /// Get an iterator over the blocks stored in the `BlocksState` whose slot is within `slot_range`, ordered by slot
+    pub fn iter_slot_range<R: RangeBounds<Slot>>(
+        &self,
+        slot_range: R,
+    ) -> impl Iterator<Item = (&BlockId, &BlockStatus)> + '_ {
+        self.slot_index
+            .range(slot_range)
+            .flat_map(|(_, block_ids)| block_ids.iter())
+            .filter_map(|block_id| {
+                self.block_statuses
+                    .get(block_id)
+                    .map(|status| (block_id, status))
+            })
+    }
+
     /// Get the number of blocks stored in the `BlocksState`
     pub fn len(&self) -> usize {
         self.block_statuses.len()
     }
 
+    /// Approximate memory used by the block statuses and their indexes in bytes.
+    /// The blocks and operations referenced in storage are not counted.
+    pub fn memory_usage(&self) -> usize {
+        let id_size = std::mem::size_of::<BlockId>();
+        let header_size = |header_or_block: &HeaderOrBlock| match header_or_block {
+            HeaderOrBlock::Header(header) => header.serialized_size(),
+            HeaderOrBlock::Block { .. } => 0,
+        };
+        let statuses: usize = self
+            .block_statuses
+            .values()
+            .map(|status| match status {
+                BlockStatus::Incoming(header_or_block)
+                | BlockStatus::WaitingForSlot(header_or_block) => header_size(header_or_block),
+                BlockStatus::WaitingForDependencies {
+                    header_or_block,
+                    unsatisfied_dependencies,
+                    ..
+                } => header_size(header_or_block) + unsatisfied_dependencies.len() * id_size,
+                BlockStatus::Active {
+                    a_block,
+                    storage_or_block,
+                } => {
+                    let links = a_block.parents.len()
+                        + a_block
+                            .children
+                            .iter()
+                            .map(|children| children.len())
+                            .sum::<usize>();
+                    let block_size = match storage_or_block {
+                        StorageOrBlock::Storage(_) => 0,
+                        StorageOrBlock::Block(block) => block.serialized_size(),
+                    };
+                    std::mem::size_of::<ActiveBlock>()
+                        + links * std::mem::size_of::<(BlockId, u64)>()
+                        + a_block.descendants.len() * id_size
+                        + block_size
+                }
+                BlockStatus::Discarded { parents, .. } => parents.len() * id_size,
+            })
+            .sum();
+        let indexes = self.incoming_index.capacity()
+            + self.waiting_for_slot_index.capacity()
+            + self.waiting_for_dependencies_index.capacity()
+            + self.discarded_index.capacity()
+            + self.active_index.capacity()
+            + self
+                .slot_index
+                .values()
+                .map(|block_ids| block_ids.len())
+                .sum::<usize>();
+        self.block_statuses.capacity() * std::mem::size_of::<(BlockId, BlockStatus)>()
+            + statuses
+            + indexes * id_size
+    }
+
     /// Change the state of a block
     /// Steps are:
     /// 1. Remove the block from state
@@ -189,8 +285,9 @@ impl BlocksState {
     ) {
         match self.block_statuses.remove(block_id) {
             Some(block) => {
+                let slot = block.get_slot();
                 let old_state_id = BlockStatusId::from(&block);
-                self.update_indexes(block_id, Some(&old_state_id), None);
+                self.update_indexes(block_id, slot, Some(&old_state_id), None);
                 let Some(mut new_state) = callback(Some(block), &mut self.block_statuses) else {
                     return;
                 };
@@ -308,7 +405,7 @@ impl BlocksState {
                         );
                     }
                 }
-                self.update_indexes(block_id, None, Some(&new_state_id));
+                self.update_indexes(block_id, slot, None, Some(&new_state_id));
             }
             None => {
                 let new_state = callback(None, &mut self.block_statuses);
@@ -320,10 +417,106 @@ impl BlocksState {
                             state, block_id
                         );
                     }
+                    let slot = new_state.get_slot();
                     self.block_statuses.insert(*block_id, new_state);
-                    self.update_indexes(block_id, None, Some(&state));
+                    self.update_indexes(block_id, slot, None, Some(&state));
                 }
             }
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use massa_consensus_exports::block_status::DiscardReason;
+    use massa_models::address::Address;
+    use massa_signature::KeyPair;
+    use massa_storage::Storage;
+
+    use super::*;
+
+    fn block_id(i: u64) -> BlockId {
+        BlockId::generate_from_hash(massa_hash::Hash::compute_from(&i.to_be_bytes()))
+    }
+
+    fn ids_in_range<R: RangeBounds<Slot>>(state: &BlocksState, range: R) -> Vec<BlockId> {
+        state
+            .iter_slot_range(range)
+            .map(|(block_id, _)| *block_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_slot_index_across_status_changes_and_pruning() {
+        let storage = Storage::create_root();
+        let creator = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let blocks = [
+            (block_id(0), Slot::new(1, 0)),
+            (block_id(1), Slot::new(1, 1)),
+            (block_id(2), Slot::new(2, 0)),
+        ];
+        let mut state = BlocksState::new();
+        for (id, slot) in blocks {
+            state.transition_map(&id, |_, _| {
+                Some(BlockStatus::Incoming(HeaderOrBlock::Block {
+                    id,
+                    slot,
+                    storage: storage.clone(),
+                }))
+            });
+        }
+
+        // range queries are ordered by slot and honor the bounds
+        assert_eq!(
+            ids_in_range(&state, ..),
+            vec![block_id(0), block_id(1), block_id(2)]
+        );
+        assert_eq!(
+            ids_in_range(&state, Slot::new(1, 0)..Slot::new(2, 0)),
+            vec![block_id(0), block_id(1)]
+        );
+        assert_eq!(
+            ids_in_range(&state, (Bound::Excluded(Slot::new(1, 0)), Bound::Unbounded)),
+            vec![block_id(1), block_id(2)]
+        );
+        assert!(ids_in_range(&state, Slot::new(3, 0)..).is_empty());
+
+        // status changes keep the block indexed at its slot with its new status
+        state.transition_map(&block_id(1), |status, _| match status {
+            Some(BlockStatus::Incoming(header_or_block)) => {
+                Some(BlockStatus::WaitingForSlot(header_or_block))
+            }
+            _ => panic!("unexpected status"),
+        });
+        state.transition_map(&block_id(1), |_, _| {
+            Some(BlockStatus::Discarded {
+                slot: Slot::new(1, 1),
+                creator,
+                parents: Vec::new(),
+                reason: DiscardReason::Stale,
+                sequence_number: 0,
+            })
+        });
+        let in_range: Vec<(BlockId, BlockStatusId)> = state
+            .iter_slot_range(Slot::new(1, 1)..=Slot::new(1, 1))
+            .map(|(block_id, status)| (*block_id, BlockStatusId::from(status)))
+            .collect();
+        assert_eq!(in_range, vec![(block_id(1), BlockStatusId::Discarded)]);
+        assert_eq!(
+            ids_in_range(&state, ..),
+            vec![block_id(0), block_id(1), block_id(2)]
+        );
+
+        // pruned blocks leave the index, and so do their emptied slots
+        state.transition_map(&block_id(1), |_, _| None);
+        state.transition_map(&block_id(2), |_, _| None);
+        assert_eq!(ids_in_range(&state, ..), vec![block_id(0)]);
+        assert_eq!(
+            state.slot_index.keys().copied().collect::<Vec<_>>(),
+            vec![Slot::new(1, 0)]
+        );
+        assert_eq!(state.len(), 1);
+    }
+}
@@ -0,0 +1,133 @@
+use massa_consensus_exports::{
+    block_status::{BlockStatus, HeaderOrBlock},
+    dependencies::{BlockDependencies, BlockedBlock, WishedBlock},
+};
+use massa_models::{
+    block_header::SecuredHeader,
+    block_id::BlockId,
+    prehash::{PreHashMap, PreHashSet},
+};
+use massa_time::MassaTime;
+use tracing::debug;
+
+use super::blocks_state::BlocksState;
+
+/// Keeps track of which blocks are waiting for which dependencies
+#[derive(Debug, Clone, Default)]
+pub struct DependencyTracker {
+    /// blocks waiting for dependencies, with their unsatisfied dependencies and the time since which they wait
+    blocked: PreHashMap<BlockId, (PreHashSet<BlockId>, MassaTime)>,
+    /// unsatisfied dependencies, with the blocks waiting for them
+    waited_by: PreHashMap<BlockId, PreHashSet<BlockId>>,
+}
+
+impl DependencyTracker {
+    /// Synchronizes the tracker with the blocks waiting for dependencies,
+    /// logging the blocks that start or stop waiting
+    pub fn update(&mut self, blocks_state: &BlocksState) {
+        let now = MassaTime::now();
+        let mut blocked = PreHashMap::default();
+        let mut waited_by: PreHashMap<BlockId, PreHashSet<BlockId>> = PreHashMap::default();
+        for block_id in blocks_state.waiting_for_dependencies_blocks() {
+            let Some(BlockStatus::WaitingForDependencies {
+                header_or_block,
+                unsatisfied_dependencies,
+                ..
+            }) = blocks_state.get(block_id)
+            else {
+                continue;
+            };
+            if unsatisfied_dependencies.is_empty() {
+                continue;
+            }
+            let blocked_since = match self.blocked.get(block_id) {
+                Some((_, since)) => *since,
+                None => {
+                    debug!(
+                        "block {} at slot {} is waiting for dependencies {:?}",
+                        block_id,
+                        header_or_block.get_slot(),
+                        unsatisfied_dependencies
+                    );
+                    now
+                }
+            };
+            for dependency in unsatisfied_dependencies {
+                waited_by.entry(*dependency).or_default().insert(*block_id);
+            }
+            blocked.insert(*block_id, (unsatisfied_dependencies.clone(), blocked_since));
+        }
+        for block_id in self.blocked.keys() {
+            if !blocked.contains_key(block_id) {
+                debug!("block {} is not waiting for dependencies anymore", block_id);
+            }
+        }
+        self.blocked = blocked;
+        self.waited_by = waited_by;
+    }
+
+    /// Blocks to ask to the network: the unsatisfied dependencies that are unknown,
+    /// or of which only the header is known
+    pub fn wishlist(
+        &self,
+        blocks_state: &BlocksState,
+    ) -> PreHashMap<BlockId, Option<SecuredHeader>> {
+        self.waited_by
+            .keys()
+            .filter_map(|dependency| match blocks_state.get(dependency) {
+                Some(BlockStatus::WaitingForDependencies {
+                    header_or_block: HeaderOrBlock::Header(header),
+                    ..
+                }) => Some((*dependency, Some(header.clone()))),
+                None => Some((*dependency, None)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Snapshot of the wished and blocked blocks
+    pub fn export(&self, blocks_state: &BlocksState) -> BlockDependencies {
+        let mut wishlist: Vec<WishedBlock> = self
+            .wishlist(blocks_state)
+            .into_iter()
+            .map(|(block_id, header)| {
+                let mut blocked_blocks: Vec<BlockId> = self
+                    .waited_by
+                    .get(&block_id)
+                    .map(|blocks| blocks.iter().copied().collect())
+                    .unwrap_or_default();
+                blocked_blocks.sort_unstable();
+                WishedBlock {
+                    block_id,
+                    header_known: header.is_some(),
+                    blocked_blocks,
+                }
+            })
+            .collect();
+        wishlist.sort_unstable_by_key(|wished| wished.block_id);
+
+        let mut blocked: Vec<BlockedBlock> = self
+            .blocked
+            .iter()
+            .filter_map(|(block_id, (dependencies, blocked_since))| {
+                let slot = match blocks_state.get(block_id) {
+                    Some(BlockStatus::WaitingForDependencies {
+                        header_or_block, ..
+                    }) => header_or_block.get_slot(),
+                    _ => return None,
+                };
+                let mut missing_dependencies: Vec<BlockId> = dependencies.iter().copied().collect();
+                missing_dependencies.sort_unstable();
+                Some(BlockedBlock {
+                    block_id: *block_id,
+                    slot,
+                    missing_dependencies,
+                    blocked_since: *blocked_since,
+                })
+            })
+            .collect();
+        blocked.sort_unstable_by_key(|b| (b.slot, b.block_id));
+
+        BlockDependencies { wishlist, blocked }
+    }
+}
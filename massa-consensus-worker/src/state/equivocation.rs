@@ -0,0 +1,95 @@
+use std::collections::hash_map::Entry;
+
+use massa_consensus_exports::{equivocation::EquivocationEvidence, events::ConsensusEvent};
+use massa_models::{block_header::SecuredHeader, slot::Slot};
+use massa_time::MassaTime;
+use tracing::{debug, warn};
+
+use super::ConsensusState;
+
+impl ConsensusState {
+    /// Check whether the creator of `header` already signed a different header for the same slot.
+    /// Each equivocation is reported once per creator and slot: it is recorded and an
+    /// `EquivocationDetected` event is emitted with both headers as evidence.
+    ///
+    /// Only the headers of the producer drawn for their slot are tracked, and only up to
+    /// `max_equivocation_future_periods` periods ahead of `current_slot`: anyone can sign headers,
+    /// which must neither forge evidence nor grow the tracked headers without bound.
+    pub(crate) fn detect_equivocation(
+        &mut self,
+        header: &SecuredHeader,
+        current_slot: Option<Slot>,
+    ) {
+        let slot = header.content.slot;
+        let Some(current_slot) = current_slot else {
+            return;
+        };
+        if slot.period
+            > current_slot
+                .period
+                .saturating_add(self.config.max_equivocation_future_periods)
+        {
+            debug!(
+                "header {} for slot {} is too far in the future to be tracked for equivocations",
+                header.id, slot
+            );
+            return;
+        }
+        match self.channels.selector_controller.get_producer(slot) {
+            Ok(producer) if producer == header.content_creator_address => {}
+            Ok(_) => {
+                debug!(
+                    "header {} was not created by the producer drawn for slot {}",
+                    header.id, slot
+                );
+                return;
+            }
+            Err(e) => {
+                debug!("cannot get the producer of slot {}: {}", slot, e);
+                return;
+            }
+        }
+
+        let headers_at_slot = self.recent_headers_per_slot.entry(slot).or_default();
+        let (known_header, reported) = match headers_at_slot.entry(header.content_creator_address) {
+            Entry::Vacant(entry) => {
+                entry.insert((header.clone(), false));
+                return;
+            }
+            Entry::Occupied(entry) => entry.into_mut(),
+        };
+        if *reported || known_header.id == header.id {
+            return;
+        }
+        *reported = true;
+
+        warn!(
+            "equivocation detected: address {} signed blocks {} and {} for slot {}",
+            header.content_creator_address, known_header.id, header.id, slot
+        );
+        let evidence = EquivocationEvidence {
+            slot,
+            creator_address: header.content_creator_address,
+            header_1: known_header.clone(),
+            header_2: header.clone(),
+            detected_at: MassaTime::now(),
+        };
+        if self.equivocations.len() >= self.config.max_equivocation_records {
+            self.equivocations.pop_front();
+        }
+        self.equivocations.push_back(evidence.clone());
+        let _ = self
+            .channels
+            .controller_event_tx
+            .send(ConsensusEvent::EquivocationDetected(Box::new(evidence)));
+    }
+
+    /// Forget the headers of slots that are final since more than `equivocation_history_periods` periods
+    pub(crate) fn prune_recent_headers(&mut self) {
+        let history_periods = self.config.equivocation_history_periods;
+        let latest_final_periods = &self.latest_final_blocks_periods;
+        self.recent_headers_per_slot.retain(|s, _| {
+            s.period.saturating_add(history_periods) > latest_final_periods[s.thread as usize].1
+        });
+    }
+}
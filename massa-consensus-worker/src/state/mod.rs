@@ -1,11 +1,14 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, VecDeque},
+    ops::Bound,
     vec,
 };
 
 use massa_consensus_exports::{
     block_graph_export::BlockGraphExport,
-    block_status::{BlockStatus, ExportCompiledBlock, HeaderOrBlock, StorageOrBlock},
+    block_status::{BlockStatus, ExportCompiledBlock, StorageOrBlock},
+    dependencies::BlockDependencies,
+    equivocation::EquivocationEvidence,
     error::ConsensusError,
     ConsensusChannels, ConsensusConfig,
 };
@@ -26,10 +29,14 @@ use massa_time::MassaTime;
 use tracing::debug;
 
 use self::blocks_state::BlocksState;
+use self::dependencies::DependencyTracker;
 
 pub mod blocks_state;
 mod clique_computation;
+mod dependencies;
+mod equivocation;
 mod graph;
+mod parent_selection;
 mod process;
 mod process_commands;
 mod prune;
@@ -85,16 +92,51 @@ pub struct ConsensusState {
     pub stats_desync_detection_timespan: MassaTime,
     /// blocks we want
     pub wishlist: PreHashMap<BlockId, Option<SecuredHeader>>,
+    /// which blocks are waiting for which dependencies
+    pub dependency_tracker: DependencyTracker,
     /// previous blockclique notified to Execution
     pub prev_blockclique: PreHashMap<BlockId, Slot>,
     /// Blocks indexed by slot (used for multi-stake limiting). Blocks
     /// should be saved in this map when we receive the header or the full block directly.
     pub nonfinal_active_blocks_per_slot: HashMap<Slot, PreHashSet<BlockId>>,
+    /// Recently received headers indexed by slot and creator, used to detect equivocations.
+    /// The boolean tells whether an equivocation was already reported for this creator and slot.
+    pub recent_headers_per_slot: BTreeMap<Slot, PreHashMap<Address, (SecuredHeader, bool)>>,
+    /// Recently detected equivocations, oldest first
+    pub equivocations: VecDeque<EquivocationEvidence>,
     /// massa metrics
     pub(crate) massa_metrics: MassaMetrics,
 }
 
 impl ConsensusState {
+    /// Approximate memory used by the block graph in bytes.
+    /// The blocks and operations referenced in storage are not counted.
+    pub fn memory_usage(&self) -> usize {
+        let id_size = std::mem::size_of::<BlockId>();
+        let incompatibilities: usize = self
+            .gi_head
+            .values()
+            .map(|incompatible| (1 + incompatible.len()) * id_size)
+            .sum();
+        let cliques: usize = self
+            .max_cliques
+            .iter()
+            .map(|clique| std::mem::size_of::<Clique>() + clique.block_ids.len() * id_size)
+            .sum();
+        let recent_headers: usize = self
+            .recent_headers_per_slot
+            .values()
+            .flat_map(|headers| headers.values())
+            .map(|(header, _)| header.serialized_size())
+            .sum();
+        self.blocks_state.memory_usage()
+            + incompatibilities
+            + cliques
+            + self.active_index_without_ops.len() * id_size
+            + self.wishlist.len() * std::mem::size_of::<(BlockId, Option<SecuredHeader>)>()
+            + recent_headers
+    }
+
     /// Get a full active block
     pub fn get_full_active_block(
         &self,
@@ -397,21 +439,11 @@ impl ConsensusState {
             max_cliques: self.max_cliques.clone(),
         };
 
-        let filter = |&s| {
-            if let Some(s_start) = slot_start {
-                if s < s_start {
-                    return false;
-                }
-            }
-            if let Some(s_end) = slot_end {
-                if s >= s_end {
-                    return false;
-                }
-            }
-            true
-        };
-
-        for (block_id, block) in self.blocks_state.iter() {
+        let slot_range = (
+            slot_start.map_or(Bound::Unbounded, Bound::Included),
+            slot_end.map_or(Bound::Unbounded, Bound::Excluded),
+        );
+        for (block_id, block) in self.blocks_state.iter_slot_range(slot_range) {
             match block {
                 BlockStatus::Discarded {
                     slot,
@@ -420,33 +452,29 @@ impl ConsensusState {
                     reason,
                     ..
                 } => {
-                    if filter(slot) {
-                        export.discarded_blocks.insert(
-                            *block_id,
-                            (reason.clone(), (*slot, *creator, parents.clone())),
-                        );
-                    }
+                    export.discarded_blocks.insert(
+                        *block_id,
+                        (reason.clone(), (*slot, *creator, parents.clone())),
+                    );
                 }
                 BlockStatus::Active {
                     a_block,
                     storage_or_block,
                 } => {
-                    if filter(&a_block.slot) {
-                        export.active_blocks.insert(
-                            *block_id,
-                            ExportCompiledBlock {
-                                header: storage_or_block.clone_block(block_id).content.header,
-                                children: a_block
-                                    .children
-                                    .iter()
-                                    .map(|thread| {
-                                        thread.keys().copied().collect::<PreHashSet<BlockId>>()
-                                    })
-                                    .collect(),
-                                is_final: a_block.is_final,
-                            },
-                        );
-                    }
+                    export.active_blocks.insert(
+                        *block_id,
+                        ExportCompiledBlock {
+                            header: storage_or_block.clone_block(block_id).content.header,
+                            children: a_block
+                                .children
+                                .iter()
+                                .map(|thread| {
+                                    thread.keys().copied().collect::<PreHashSet<BlockId>>()
+                                })
+                                .collect(),
+                            is_final: a_block.is_final,
+                        },
+                    );
                 }
                 _ => continue,
             }
@@ -492,35 +520,16 @@ impl ConsensusState {
             .collect()
     }
 
-    /// get the current block wish list, including the operations hash.
-    pub fn get_block_wishlist(
-        &self,
-    ) -> Result<PreHashMap<BlockId, Option<SecuredHeader>>, ConsensusError> {
-        let mut wishlist = PreHashMap::<BlockId, Option<SecuredHeader>>::default();
-        for block_id in self.blocks_state.waiting_for_dependencies_blocks().iter() {
-            if let Some(BlockStatus::WaitingForDependencies {
-                unsatisfied_dependencies,
-                ..
-            }) = self.blocks_state.get(block_id)
-            {
-                for unsatisfied_h in unsatisfied_dependencies.iter() {
-                    match self.blocks_state.get(unsatisfied_h) {
-                        Some(BlockStatus::WaitingForDependencies {
-                            header_or_block: HeaderOrBlock::Header(header),
-                            ..
-                        }) => {
-                            wishlist.insert(header.id, Some(header.clone()));
-                        }
-                        None => {
-                            wishlist.insert(*unsatisfied_h, None);
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
+    /// Get the current block wish list, along with the header of the wished blocks if known.
+    /// The dependency tracker is updated beforehand.
+    pub fn get_block_wishlist(&mut self) -> PreHashMap<BlockId, Option<SecuredHeader>> {
+        self.dependency_tracker.update(&self.blocks_state);
+        self.dependency_tracker.wishlist(&self.blocks_state)
+    }
 
-        Ok(wishlist)
+    /// Get the blocks waiting for missing dependencies and the blocks wished from the network
+    pub fn get_block_dependencies(&self) -> BlockDependencies {
+        self.dependency_tracker.export(&self.blocks_state)
     }
 
     /// Gets a block and all its descendants
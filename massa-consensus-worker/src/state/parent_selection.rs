@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use massa_consensus_exports::{
+    block_status::BlockStatus,
+    error::ConsensusError,
+    parent_selection::{
+        ParentRejectionReason, ParentSelectionDiagnostics, RejectedParent, ThreadParentSelection,
+    },
+};
+use massa_models::{block_id::BlockId, slot::Slot, timeslots::get_latest_block_slot_at_timestamp};
+use massa_time::MassaTime;
+
+use super::ConsensusState;
+
+/// Maximum number of periods after the selected parent of a thread for which missing blocks are reported
+const MAX_DIAGNOSED_PERIODS: u64 = 32;
+
+impl ConsensusState {
+    /// Explain the current choice of best parents: for each thread, list the slots more recent than
+    /// the selected parent and why they did not provide the parent.
+    pub fn get_parent_selection(&self) -> Result<ParentSelectionDiagnostics, ConsensusError> {
+        let timestamp = MassaTime::now();
+        let current_slot = get_latest_block_slot_at_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            timestamp,
+        )?;
+        let blockclique = self
+            .max_cliques
+            .iter()
+            .find(|clique| clique.is_blockclique)
+            .map(|clique| &clique.block_ids);
+
+        // known blocks more recent than the selected parent of their thread
+        let mut candidates: BTreeMap<Slot, Vec<(BlockId, ParentRejectionReason)>> = BTreeMap::new();
+        for (block_id, status) in self.blocks_state.iter() {
+            let (slot, reason) = match status {
+                BlockStatus::Incoming(header_or_block)
+                | BlockStatus::WaitingForSlot(header_or_block)
+                | BlockStatus::WaitingForDependencies {
+                    header_or_block, ..
+                } => (header_or_block.get_slot(), ParentRejectionReason::Late),
+                BlockStatus::Active { a_block, .. } => {
+                    if blockclique.map_or(false, |ids| ids.contains(block_id)) {
+                        continue;
+                    }
+                    (a_block.slot, ParentRejectionReason::NotInBlockclique)
+                }
+                BlockStatus::Discarded { slot, .. } => (*slot, ParentRejectionReason::Discarded),
+            };
+            let Some((_, parent_period)) = self.best_parents.get(slot.thread as usize) else {
+                continue;
+            };
+            if slot.period > *parent_period {
+                candidates
+                    .entry(slot)
+                    .or_default()
+                    .push((*block_id, reason));
+            }
+        }
+
+        let threads = self
+            .best_parents
+            .iter()
+            .enumerate()
+            .map(|(thread, (parent_id, parent_period))| {
+                let thread = thread as u8;
+                let mut rejected = Vec::new();
+                let latest_period = current_slot.map_or(0, |slot| {
+                    if slot.thread >= thread {
+                        slot.period
+                    } else {
+                        slot.period.saturating_sub(1)
+                    }
+                });
+                let last_period =
+                    latest_period.min(parent_period.saturating_add(MAX_DIAGNOSED_PERIODS));
+                for period in parent_period.saturating_add(1)..=last_period {
+                    let slot = Slot::new(period, thread);
+                    match candidates.get(&slot) {
+                        Some(blocks) => rejected.extend(blocks.iter().map(|(block_id, reason)| {
+                            RejectedParent {
+                                slot,
+                                block_id: Some(*block_id),
+                                reason: *reason,
+                            }
+                        })),
+                        None => rejected.push(RejectedParent {
+                            slot,
+                            block_id: None,
+                            reason: ParentRejectionReason::Missing,
+                        }),
+                    }
+                }
+                ThreadParentSelection {
+                    thread,
+                    parent_id: *parent_id,
+                    parent_period: *parent_period,
+                    rejected,
+                }
+            })
+            .collect();
+
+        Ok(ParentSelectionDiagnostics {
+            timestamp,
+            current_slot,
+            threads,
+        })
+    }
+}
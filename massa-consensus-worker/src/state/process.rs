@@ -6,6 +6,7 @@ use std::{
 use massa_consensus_exports::{
     block_status::{BlockStatus, DiscardReason, HeaderOrBlock, StorageOrBlock},
     error::ConsensusError,
+    events::ConsensusEvent,
 };
 use massa_execution_exports::ExecutionBlockMetadata;
 use massa_logging::massa_trace;
@@ -22,7 +23,7 @@ use massa_models::{
 use massa_signature::PublicKey;
 use massa_storage::Storage;
 use massa_time::MassaTime;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::state::{
     clique_computation::compute_max_cliques,
@@ -591,6 +592,40 @@ impl ConsensusState {
         }
     }
 
+    /// Archive the blocks that became final, with their operations, if the node is archival.
+    ///
+    /// # Arguments:
+    /// * `finalized_blocks`: Blocks that became final
+    fn archive_final_blocks(&self, finalized_blocks: &HashMap<Slot, BlockId>) {
+        let Some(block_archive) = &self.channels.block_archive else {
+            return;
+        };
+        for b_id in finalized_blocks.values() {
+            let Some(BlockStatus::Active {
+                storage_or_block: StorageOrBlock::Storage(storage),
+                ..
+            }) = self.blocks_state.get(b_id)
+            else {
+                panic!("final block not found in active blocks and/or its operations are missing");
+            };
+            let block = storage
+                .read_blocks()
+                .get(b_id)
+                .cloned()
+                .expect("final block not found in storage");
+            let operations: Vec<_> = {
+                let ops = storage.read_operations();
+                block
+                    .content
+                    .operations
+                    .iter()
+                    .filter_map(|op_id| ops.get(op_id).cloned())
+                    .collect()
+            };
+            block_archive.archive_block(&block, &operations);
+        }
+    }
+
     /// Notify execution about blockclique changes and finalized blocks.
     ///
     /// # Arguments:
@@ -654,6 +689,26 @@ impl ConsensusState {
             // If `prev_blockclique` is not empty here, it means that it contained elements that are not in the new blockclique anymore.
             // In that case, we mark the blockclique as having changed.
             blockclique_changed = true;
+
+            // The blocks that left the blockclique without becoming final were reorganized out of it.
+            let mut removed_blocks: Vec<(Slot, BlockId)> = self
+                .prev_blockclique
+                .iter()
+                .filter(|(b_id, slot)| finalized_blocks.get(slot) != Some(b_id))
+                .map(|(b_id, slot)| (*slot, *b_id))
+                .collect();
+            if !removed_blocks.is_empty() {
+                removed_blocks.sort_unstable();
+                debug!(
+                    "reorg: {} blocks left the blockclique: {:?}",
+                    removed_blocks.len(),
+                    removed_blocks
+                );
+                let _ = self
+                    .channels
+                    .controller_event_tx
+                    .send(ConsensusEvent::Reorg { removed_blocks });
+            }
         }
         // Overwrite previous blockclique.
         // Should still be done even if unchanged because elements were removed from it above.
@@ -749,19 +804,32 @@ impl ConsensusState {
             final_block_slots
         };
 
+        // keep the final blocks on disk if the node is archival
+        self.archive_final_blocks(&final_block_slots);
+
+        // notify protocol, to credit the peers that first sent us the final blocks and their endorsements
+        if !final_block_slots.is_empty() {
+            let final_block_ids: Vec<BlockId> = final_block_slots.values().copied().collect();
+            if let Err(err) = self
+                .channels
+                .protocol_controller
+                .notify_final_blocks(final_block_ids)
+            {
+                warn!("could not notify protocol of the final blocks: {}", err);
+            }
+        }
+
         // notify execution
         self.notify_execution(final_block_slots);
 
-        // notify protocol of block wishlist
-        let new_wishlist = self.get_block_wishlist()?;
+        // notify protocol of block wishlist: new wished blocks, and wished blocks whose header became known
+        let new_wishlist = self.get_block_wishlist();
         let new_blocks: PreHashMap<BlockId, Option<SecuredHeader>> = new_wishlist
             .iter()
-            .filter_map(|(id, header)| {
-                if !self.wishlist.contains_key(id) {
-                    Some((*id, header.clone()))
-                } else {
-                    None
-                }
+            .filter_map(|(id, header)| match self.wishlist.get(id) {
+                None => Some((*id, header.clone())),
+                Some(None) if header.is_some() => Some((*id, header.clone())),
+                _ => None,
             })
             .collect();
         let remove_blocks: PreHashSet<BlockId> = self
@@ -35,6 +35,8 @@ impl ConsensusState {
             return Ok(());
         }
 
+        self.detect_equivocation(&header, current_slot);
+
         let de_p = DenunciationPrecursor::from(&header);
         self.channels
             .pool_controller
@@ -84,8 +86,13 @@ impl ConsensusState {
             return Ok(());
         }
 
-        if let Some(verifiable_block) = storage.read_blocks().get(&block_id) {
-            let de_p = DenunciationPrecursor::from(&verifiable_block.content.header);
+        let header = storage
+            .read_blocks()
+            .get(&block_id)
+            .map(|verifiable_block| verifiable_block.content.header.clone());
+        if let Some(header) = header {
+            self.detect_equivocation(&header, current_slot);
+            let de_p = DenunciationPrecursor::from(&header);
             self.channels
                 .pool_controller
                 .add_denunciation_precursor(de_p);
@@ -349,6 +349,9 @@ impl ConsensusState {
         // Step 5: prune nonfinal blocks per slot
         self.prune_nonfinal_blocks_per_slot();
 
+        // Step 6: prune headers kept for equivocation detection
+        self.prune_recent_headers();
+
         let after = self.max_cliques.len();
         if before != after {
             debug!(
@@ -2,7 +2,7 @@ use std::collections::BTreeSet;
 
 use massa_consensus_exports::{block_status::BlockStatus, error::ConsensusError};
 use massa_logging::massa_trace;
-use massa_models::{block_id::BlockId, slot::Slot};
+use massa_models::{block_id::BlockId, slot::Slot, timeslots::get_block_slot_timestamp};
 
 use super::ConsensusState;
 
@@ -48,6 +48,9 @@ impl ConsensusState {
         // take care of block db changes
         self.block_db_changed()?;
 
+        // let protocol know how far behind the network we are
+        self.notify_sync_lag(current_slot)?;
+
         for i in 0..self.latest_final_blocks_periods.len() {
             if let Some((_blockid, period)) = self.latest_final_blocks_periods.get(i) {
                 self.massa_metrics.set_consensus_period(i, *period);
@@ -61,7 +64,31 @@ impl ConsensusState {
             self.blocks_state.len(),
             self.active_index_without_ops.len(),
         );
+        self.massa_metrics
+            .set_memory_usage("consensus_graph", self.memory_usage());
 
         Ok(())
     }
+
+    /// Notify protocol of the time elapsed between the latest block of the blockclique and `current_slot`
+    fn notify_sync_lag(&self, current_slot: Slot) -> Result<(), ConsensusError> {
+        let latest_slot = self
+            .best_parents
+            .iter()
+            .enumerate()
+            .map(|(thread, (_, period))| Slot::new(*period, thread as u8))
+            .max()
+            .unwrap_or_else(|| Slot::new(0, 0));
+        let slot_timestamp = |slot| {
+            get_block_slot_timestamp(
+                self.config.thread_count,
+                self.config.t0,
+                self.config.genesis_timestamp,
+                slot,
+            )
+        };
+        let lag = slot_timestamp(current_slot)?.saturating_sub(slot_timestamp(latest_slot)?);
+        self.channels.protocol_controller.notify_sync_lag(lag)?;
+        Ok(())
+    }
 }
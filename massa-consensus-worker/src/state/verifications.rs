@@ -2,7 +2,10 @@ use super::{process::BlockInfos, ConsensusState};
 use massa_consensus_exports::block_status::{BlockStatus, DiscardReason, HeaderOrBlock};
 use massa_logging::massa_trace;
 use massa_models::{
-    block_header::SecuredHeader, block_id::BlockId, prehash::PreHashSet, slot::Slot,
+    block_header::{required_endorsement_count, SecuredHeader},
+    block_id::BlockId,
+    prehash::PreHashSet,
+    slot::Slot,
 };
 use tracing::warn;
 
@@ -159,6 +162,7 @@ impl ConsensusState {
     /// - Check if it was the creator's turn to create this block.
     /// - Check parents are present.
     /// - Check the topological consistency of the parents.
+    /// - Check the number of endorsements.
     /// - Check endorsements.
     /// - Check thread incompatibility test.
     /// - Check grandpa incompatibility test.
@@ -325,6 +329,22 @@ impl ConsensusState {
             }
         }
 
+        // check that the block includes enough endorsements
+        let required_endorsements = required_endorsement_count(
+            self.config.min_block_endorsements,
+            self.config.endorsement_relaxation_periods,
+            header.content.slot.period,
+            parents[header.content.slot.thread as usize].1,
+        );
+        if (header.content.endorsements.len() as u32) < required_endorsements {
+            return HeaderCheckOutcome::Discard(DiscardReason::Invalid(format!(
+                "block in slot {} has {} endorsements, {} required",
+                header.content.slot,
+                header.content.endorsements.len(),
+                required_endorsements
+            )));
+        }
+
         // check endorsements
         match self.check_endorsements(header) {
             EndorsementsCheckOutcome::Proceed => {}
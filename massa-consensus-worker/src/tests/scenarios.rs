@@ -7,9 +7,10 @@ use super::{
     tools::{consensus_test, register_block},
     universe::{ConsensusForeignControllers, ConsensusTestUniverse},
 };
-use crate::tests::tools::create_block;
+use crate::tests::tools::{create_block, create_block_with_merkle_root};
 use massa_consensus_exports::ConsensusConfig;
 use massa_execution_exports::MockExecutionController;
+use massa_hash::Hash;
 use massa_models::{
     address::Address, block::BlockGraphStatus, block_id::BlockId, config::ENDORSEMENT_COUNT,
     slot::Slot,
@@ -400,3 +401,238 @@ fn test_parent_in_the_future() {
         "wrong status"
     );
 }
+
+/// Two different headers signed by the same creator for the same slot must be reported once as an equivocation.
+#[test]
+fn test_equivocation_detection() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        genesis_key: staking_key.clone(),
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    let slot = Slot::new(1, 0);
+    let block_1 = create_block_with_merkle_root(
+        Hash::compute_from("first".as_bytes()),
+        slot,
+        genesis_hashes.clone(),
+        &staking_key,
+    );
+    let block_2 = create_block_with_merkle_root(
+        Hash::compute_from("second".as_bytes()),
+        slot,
+        genesis_hashes.clone(),
+        &staking_key,
+    );
+    let block_3 = create_block_with_merkle_root(
+        Hash::compute_from("third".as_bytes()),
+        slot,
+        genesis_hashes,
+        &staking_key,
+    );
+    register_block(
+        &universe.module_controller,
+        block_1.clone(),
+        storage.clone(),
+    );
+    // registering the same block twice is not an equivocation
+    register_block(
+        &universe.module_controller,
+        block_1.clone(),
+        storage.clone(),
+    );
+    register_block(
+        &universe.module_controller,
+        block_2.clone(),
+        storage.clone(),
+    );
+    register_block(&universe.module_controller, block_3, storage);
+    std::thread::sleep(Duration::from_millis(500));
+
+    let equivocations = universe.module_controller.get_equivocations();
+    assert_eq!(equivocations.len(), 1, "wrong number of equivocations");
+    let evidence = &equivocations[0];
+    assert_eq!(evidence.slot, slot);
+    assert_eq!(evidence.creator_address, staking_address);
+    assert_eq!(evidence.header_1.id, block_1.content.header.id);
+    assert_eq!(evidence.header_2.id, block_2.content.header.id);
+}
+
+/// Headers signed by a creator that was not drawn for their slot, or too far in the future,
+/// must not be tracked for equivocations.
+#[test]
+fn test_equivocation_ignores_undrawn_and_future_headers() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let other_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(1000),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now(),
+        genesis_key: staking_key.clone(),
+        max_equivocation_future_periods: 2,
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    let conflicting_blocks = |slot: Slot, key: &KeyPair| {
+        ["first", "second"].map(|root| {
+            create_block_with_merkle_root(
+                Hash::compute_from(root.as_bytes()),
+                slot,
+                genesis_hashes.clone(),
+                key,
+            )
+        })
+    };
+    // two headers for the same slot signed by an address that was not drawn
+    for block in conflicting_blocks(Slot::new(1, 0), &other_key) {
+        register_block(&universe.module_controller, block, storage.clone());
+    }
+    // two headers of the drawn producer for a slot too far in the future
+    for block in conflicting_blocks(Slot::new(10, 0), &staking_key) {
+        register_block(&universe.module_controller, block, storage.clone());
+    }
+    // two headers of the drawn producer for a close slot are still reported
+    let reported = conflicting_blocks(Slot::new(1, 1), &staking_key);
+    for block in reported.clone() {
+        register_block(&universe.module_controller, block, storage.clone());
+    }
+    std::thread::sleep(Duration::from_millis(500));
+
+    let equivocations = universe.module_controller.get_equivocations();
+    assert_eq!(equivocations.len(), 1, "wrong number of equivocations");
+    assert_eq!(equivocations[0].slot, Slot::new(1, 1));
+    assert_eq!(equivocations[0].creator_address, staking_address);
+    assert_eq!(equivocations[0].header_1.id, reported[0].content.header.id);
+}
+
+/// A block without the required endorsements is rejected, while the same header is accepted
+/// once enough periods were missed in its thread to relax the requirement.
+#[test]
+fn test_min_block_endorsements() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let t0 = MassaTime::from_millis(1000);
+    let cfg = ConsensusConfig {
+        t0,
+        thread_count: 2,
+        // the tested slots are already reached
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(5)),
+        genesis_key: staking_key.clone(),
+        min_block_endorsements: 1,
+        endorsement_relaxation_periods: 2,
+        ..ConsensusConfig::default()
+    };
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    let mut foreign_controllers = ConsensusForeignControllers::new_with_mocks();
+    let storage = foreign_controllers.storage.clone();
+    foreign_controllers
+        .execution_controller
+        .expect_update_blockclique_status()
+        .returning(|_, _, _| {});
+    foreign_controllers
+        .pool_controller
+        .expect_notify_final_cs_periods()
+        .returning(|_| {});
+    foreign_controllers
+        .pool_controller
+        .expect_add_denunciation_precursor()
+        .returning(|_| {});
+    foreign_controllers
+        .selector_controller
+        .expect_get_producer()
+        .returning(move |_| Ok(staking_address));
+    foreign_controllers
+        .selector_controller
+        .expect_get_selection()
+        .returning(move |_| {
+            Ok(Selection {
+                producer: staking_address,
+                endorsements: vec![staking_address; ENDORSEMENT_COUNT as usize],
+            })
+        });
+    let universe = ConsensusTestUniverse::new(foreign_controllers, cfg);
+    let genesis_hashes = universe
+        .module_controller
+        .get_block_graph_status(None, None)
+        .expect("could not get block graph status")
+        .genesis_blocks;
+
+    // the period following its parent: one endorsement is required
+    let consecutive = create_block(Slot::new(1, 0), genesis_hashes.clone(), &staking_key);
+    // two missed periods after its parent: the requirement is relaxed to none
+    let relaxed = create_block(Slot::new(3, 0), genesis_hashes, &staking_key);
+    register_block(
+        &universe.module_controller,
+        consecutive.clone(),
+        storage.clone(),
+    );
+    register_block(&universe.module_controller, relaxed.clone(), storage);
+    std::thread::sleep(Duration::from_millis(500));
+
+    let status = universe
+        .module_controller
+        .get_block_statuses(&[consecutive.id, relaxed.id]);
+    assert_eq!(
+        status,
+        vec![
+            BlockGraphStatus::Discarded,
+            BlockGraphStatus::ActiveInBlockclique
+        ],
+        "wrong status"
+    );
+}
@@ -45,6 +45,12 @@ pub fn consensus_test<F>(
     protocol_controller
         .expect_notify_block_attack()
         .returning(|_| Ok(()));
+    protocol_controller
+        .expect_notify_sync_lag()
+        .returning(|_| Ok(()));
+    protocol_controller
+        .expect_notify_final_blocks()
+        .returning(|_| Ok(()));
     // launch consensus controller
     let (consensus_event_sender, _) = MassaChannel::new(String::from("consensus_event"), Some(10));
 
@@ -65,6 +71,7 @@ pub fn consensus_test<F>(
             protocol_controller,
             pool_controller,
             selector_controller,
+            block_archive: None,
         },
         None,
         storage.clone(),
@@ -55,6 +55,14 @@ impl TestUniverse for ConsensusTestUniverse {
             .protocol_controller
             .expect_notify_block_attack()
             .returning(|_| Ok(()));
+        foreign_controllers
+            .protocol_controller
+            .expect_notify_sync_lag()
+            .returning(|_| Ok(()));
+        foreign_controllers
+            .protocol_controller
+            .expect_notify_final_blocks()
+            .returning(|_| Ok(()));
         // launch consensus controller
         let (consensus_event_sender, _) =
             MassaChannel::new(String::from("consensus_event"), Some(10));
@@ -76,6 +84,7 @@ impl TestUniverse for ConsensusTestUniverse {
                 protocol_controller: foreign_controllers.protocol_controller,
                 pool_controller: foreign_controllers.pool_controller,
                 selector_controller: foreign_controllers.selector_controller,
+                block_archive: None,
             },
             None,
             foreign_controllers.storage.clone(),
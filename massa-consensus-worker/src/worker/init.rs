@@ -19,7 +19,7 @@ use massa_models::{
     timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
 };
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{MassaTime, SlotTicker};
 use parking_lot::RwLock;
 use std::{
     collections::{HashMap, VecDeque},
@@ -138,6 +138,9 @@ impl ConsensusWorker {
             next_slot,
         )?
         .estimate_instant()?;
+        let slot_ticker =
+            SlotTicker::new(config.genesis_timestamp, config.t0, config.thread_count)?
+                .with_min_index(next_slot.to_index(config.thread_count)? + 1);
 
         info!(
             "Started node at time {}, cycle {}, period {}, thread {}",
@@ -198,6 +201,7 @@ impl ConsensusWorker {
             previous_slot,
             next_slot,
             next_instant,
+            slot_ticker,
         };
 
         // If the node starts after the genesis timestamp then it has to initialize its graph
@@ -1,11 +1,7 @@
 use std::time::Instant;
 
 use massa_consensus_exports::{error::ConsensusError, events::ConsensusEvent};
-use massa_models::{
-    slot::Slot,
-    timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
-};
-use massa_time::MassaTime;
+use massa_models::slot::Slot;
 use tracing::{info, warn};
 
 use crate::commands::ConsensusCommand;
@@ -74,40 +70,15 @@ impl ConsensusWorker {
 
     /// Gets the next slot and the instant when it will happen.
     /// Slots can be skipped if we waited too much in-between.
-    /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
-    fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
-        // get current absolute time
-        let now = MassaTime::now();
-
-        // get closest slot according to the current absolute time
-        let mut next_slot = get_closest_slot_to_timestamp(
-            self.config.thread_count,
-            self.config.t0,
-            self.config.genesis_timestamp,
-            now,
-        );
-
-        // protection against double-production on unexpected system clock adjustment
-        if let Some(prev_slot) = previous_slot {
-            if next_slot <= prev_slot {
-                next_slot = prev_slot
-                    .get_next_slot(self.config.thread_count)
-                    .expect("could not compute next slot");
-            }
-        }
-
-        // get the timestamp of the target slot
-        let next_instant = get_block_slot_timestamp(
-            self.config.thread_count,
-            self.config.t0,
-            self.config.genesis_timestamp,
-            next_slot,
+    fn get_next_slot(&mut self) -> (Slot, Instant) {
+        let (next_index, next_instant) = self
+            .slot_ticker
+            .next_tick()
+            .expect("could not compute next slot");
+        (
+            Slot::from_index(next_index, self.config.thread_count),
+            next_instant,
         )
-        .expect("could not get block slot timestamp")
-        .estimate_instant()
-        .expect("could not estimate block slot instant");
-
-        (next_slot, next_instant)
     }
 
     /// Runs in loop forever. This loop must stop every slot to perform operations on stats and graph
@@ -159,7 +130,7 @@ impl ConsensusWorker {
                         last_prune = Instant::now();
                     }
                     self.previous_slot = Some(self.next_slot);
-                    (self.next_slot, self.next_instant) = self.get_next_slot(Some(self.next_slot));
+                    (self.next_slot, self.next_instant) = self.get_next_slot();
                 }
                 WaitingStatus::Disconnected => {
                     break;
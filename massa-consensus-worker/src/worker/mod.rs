@@ -10,7 +10,7 @@ use massa_models::config::CHANNEL_SIZE;
 use massa_models::prehash::PreHashSet;
 use massa_models::slot::Slot;
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{MassaTime, SlotTicker};
 use parking_lot::RwLock;
 use std::sync::Arc;
 use std::thread;
@@ -35,6 +35,8 @@ pub struct ConsensusWorker {
     next_slot: Slot,
     /// Next slot instant
     next_instant: Instant,
+    /// Computes the slots following `next_slot`
+    slot_ticker: SlotTicker,
 }
 
 mod init;
@@ -88,6 +90,7 @@ pub fn start_consensus_worker(
         stale_block_stats: Default::default(),
         protocol_blocks: Default::default(),
         wishlist: Default::default(),
+        dependency_tracker: Default::default(),
         launch_time: MassaTime::now(),
         stats_desync_detection_timespan,
         stats_history_timespan: std::cmp::max(
@@ -96,6 +99,8 @@ pub fn start_consensus_worker(
         ),
         prev_blockclique: Default::default(),
         nonfinal_active_blocks_per_slot: Default::default(),
+        recent_headers_per_slot: Default::default(),
+        equivocations: Default::default(),
         massa_metrics,
     }));
 
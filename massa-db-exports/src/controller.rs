@@ -23,8 +23,18 @@ pub trait MassaDBController: Send + Sync + Debug {
     fn set_initial_change_id(&self, change_id: Slot);
 
     /// Writes the batch to the DB
+    ///
+    /// Panics if the write fails: use `try_write_batch` where the failure can be handled.
     fn write_batch(&mut self, batch: DBBatch, versioning_batch: DBBatch, change_id: Option<Slot>);
 
+    /// Writes the batch to the DB, or returns why it could not be written (e.g. the disk hard quota is exceeded)
+    fn try_write_batch(
+        &mut self,
+        batch: DBBatch,
+        versioning_batch: DBBatch,
+        change_id: Option<Slot>,
+    ) -> Result<(), MassaDBError>;
+
     /// Utility function to put / update a key & value in the batch
     fn put_or_update_entry_value(&self, batch: &mut DBBatch, key: Vec<u8>, value: &[u8]);
 
@@ -21,4 +21,6 @@ pub enum MassaDBError {
     HashError(String),
     /// serialization error: {0}
     SerializeError(String),
+    /// disk quota exceeded: {0}
+    DiskQuotaExceeded(String),
 }
@@ -15,4 +15,8 @@ pub struct MassaDBConfig {
     pub thread_count: u8,
     /// Maximum number of ledger backups to keep
     pub max_ledger_backups: u64,
+    /// Disk usage (in bytes) of the database directory above which warnings are emitted (None: disabled)
+    pub disk_soft_quota: Option<u64>,
+    /// Disk usage (in bytes) of the database directory above which backups are pruned and writes are refused (None: disabled)
+    pub disk_hard_quota: Option<u64>,
 }
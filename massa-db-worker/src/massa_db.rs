@@ -12,17 +12,44 @@ use massa_models::{
 };
 use massa_serialization::{DeserializeError, Deserializer, Serializer, U64VarIntSerializer};
 use parking_lot::Mutex;
+use rayon::prelude::*;
 use rocksdb::{
-    checkpoint::Checkpoint, ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch,
-    DB,
+    checkpoint::Checkpoint, ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options,
+    WriteBatch, DB,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{
     collections::BTreeMap,
     format,
     ops::Bound::{self, Excluded, Included, Unbounded},
     sync::Arc,
+    time::{Duration, Instant},
 };
+use tracing::warn;
+
+/// Minimum delay between two measurements of the disk usage of the database directory
+const DISK_USAGE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of changes from which their contributions to the state hash are computed in parallel
+const PARALLEL_HASH_MIN_CHANGES: usize = 1_000;
+
+/// Contribution to the XOR state hash of setting `key` to `value` in the state column (None: deletion).
+/// The previous value of the key, if any, is read from the database and removed from the hash.
+fn change_hash_delta(
+    db: &DB,
+    handle_state: &ColumnFamily,
+    key: &[u8],
+    value: Option<&[u8]>,
+) -> HashXof<HASH_XOF_SIZE_BYTES> {
+    let mut delta = HashXof([0u8; HASH_XOF_SIZE_BYTES]);
+    if let Ok(Some(prev_value)) = db.get_cf(handle_state, key) {
+        delta ^= HashXof::compute_from_tuple(&[key, prev_value.as_slice()]);
+    }
+    if let Some(value) = value {
+        delta ^= HashXof::compute_from_tuple(&[key, value]);
+    }
+    delta
+}
 
 /// Wrapped RocksDB database
 ///
@@ -54,6 +81,8 @@ pub struct RawMassaDB<
     pub change_id_deserializer: ChangeIDDeserializer,
     /// The current RocksDB batch of the database, in a Mutex to share it
     pub current_batch: Arc<Mutex<WriteBatch>>,
+    /// Last measured disk usage of the database directory (in bytes), with the instant of the measure
+    pub disk_usage_cache: Option<(Instant, u64)>,
 }
 
 impl<ChangeID, ChangeIDSerializer, ChangeIDDeserializer> std::fmt::Debug
@@ -357,35 +386,39 @@ where
             }
         }
 
+        self.check_disk_quota()?;
+
         let handle_state = self.db.cf_handle(STATE_CF).expect(CF_ERROR);
         let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
         let handle_versioning = self.db.cf_handle(VERSIONING_CF).expect(CF_ERROR);
 
-        let mut current_xor_hash = self.get_xof_db_hash();
+        // the contributions of the keys to the XOR hash are independent:
+        // large batches (typically bootstrap parts) are hashed across worker threads
+        let db = self.db.as_ref();
+        let hash_delta = |(key, value): (&Key, &Option<Value>)| {
+            change_hash_delta(db, handle_state, key, value.as_deref())
+        };
+        let zero_hash = || HashXof([0u8; HASH_XOF_SIZE_BYTES]);
+        let changes_hash = if changes.len() >= PARALLEL_HASH_MIN_CHANGES {
+            changes
+                .par_iter()
+                .map(hash_delta)
+                .reduce(zero_hash, |a, b| a ^ b)
+        } else {
+            changes
+                .iter()
+                .map(hash_delta)
+                .fold(zero_hash(), |a, b| a ^ b)
+        };
+        let current_xor_hash = self.get_xof_db_hash() ^ changes_hash;
 
         *self.current_batch.lock() = WriteBatch::default();
 
         for (key, value) in changes.iter() {
             if let Some(value) = value {
                 self.current_batch.lock().put_cf(handle_state, key, value);
-
-                // Compute the XOR in all cases
-                if let Ok(Some(prev_value)) = self.db.get_cf(handle_state, key) {
-                    let prev_hash =
-                        HashXof::compute_from_tuple(&[key.as_slice(), prev_value.as_slice()]);
-                    current_xor_hash ^= prev_hash;
-                };
-                let new_hash = HashXof::compute_from_tuple(&[key.as_slice(), value.as_slice()]);
-                current_xor_hash ^= new_hash;
             } else {
                 self.current_batch.lock().delete_cf(handle_state, key);
-
-                // Compute the XOR in all cases
-                if let Ok(Some(prev_value)) = self.db.get_cf(handle_state, key) {
-                    let prev_hash =
-                        HashXof::compute_from_tuple(&[key.as_slice(), prev_value.as_slice()]);
-                    current_xor_hash ^= prev_hash;
-                };
             }
         }
 
@@ -411,9 +444,8 @@ where
             .put_cf(handle_metadata, STATE_HASH_KEY, current_xor_hash.0);
 
         {
-            let mut current_batch_guard = self.current_batch.lock();
-            let batch = WriteBatch::from_data(current_batch_guard.data());
-            current_batch_guard.clear();
+            // moved out rather than copied, so that a large batch is not held twice in memory
+            let batch = std::mem::take(&mut *self.current_batch.lock());
 
             self.db.write(batch).map_err(|e| {
                 MassaDBError::RocksDBError(format!("Can't write batch to disk: {}", e))
@@ -459,6 +491,88 @@ where
         Ok(())
     }
 
+    /// Check the disk usage of the database directory against the configured quotas.
+    ///
+    /// * above the soft quota, a warning is emitted
+    /// * above the hard quota, the ledger backups are pruned. If the usage is still above the hard quota,
+    ///   an error is returned so that the caller does not write to a full disk.
+    pub fn check_disk_quota(&mut self) -> Result<(), MassaDBError> {
+        if self.config.disk_soft_quota.is_none() && self.config.disk_hard_quota.is_none() {
+            return Ok(());
+        }
+
+        let disk_usage = match self.disk_usage_cache {
+            Some((measured_at, disk_usage))
+                if measured_at.elapsed() < DISK_USAGE_CHECK_INTERVAL =>
+            {
+                disk_usage
+            }
+            _ => self.measure_disk_usage()?,
+        };
+
+        if let Some(hard_quota) = self.config.disk_hard_quota {
+            if disk_usage > hard_quota {
+                warn!(
+                    "database disk usage ({} bytes) is above the hard quota ({} bytes), pruning ledger backups",
+                    disk_usage, hard_quota
+                );
+                self.prune_backups()?;
+                let disk_usage = self.measure_disk_usage()?;
+                if disk_usage > hard_quota {
+                    return Err(MassaDBError::DiskQuotaExceeded(format!(
+                        "database disk usage ({} bytes) is above the hard quota ({} bytes) even after pruning, refusing to write",
+                        disk_usage, hard_quota
+                    )));
+                }
+                return Ok(());
+            }
+        }
+
+        if let Some(soft_quota) = self.config.disk_soft_quota {
+            if disk_usage > soft_quota {
+                warn!(
+                    "database disk usage ({} bytes) is above the soft quota ({} bytes)",
+                    disk_usage, soft_quota
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Measure the disk usage of the database directory and update the cached value
+    fn measure_disk_usage(&mut self) -> Result<u64, MassaDBError> {
+        let disk_usage = dir_disk_usage(self.db.path()).map_err(|e| {
+            MassaDBError::RocksDBError(format!("Cannot compute database disk usage: {}", e))
+        })?;
+        self.disk_usage_cache = Some((Instant::now(), disk_usage));
+        Ok(disk_usage)
+    }
+
+    /// Remove all the ledger backups stored in the database directory
+    fn prune_backups(&self) -> Result<(), MassaDBError> {
+        let entries = std::fs::read_dir(self.db.path())
+            .map_err(|e| MassaDBError::RocksDBError(format!("Cannot walk db path: {}", e)))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_backup = path
+                .file_name()
+                .and_then(|f| f.to_str())
+                .map(|f| f.starts_with("backup_"))
+                .unwrap_or(false);
+            if is_backup && path.is_dir() {
+                std::fs::remove_dir_all(&path).map_err(|e| {
+                    MassaDBError::RocksDBError(format!(
+                        "Cannot remove backup {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
     /// Get the current change_id attached to the database.
     pub fn get_change_id(&self) -> Result<ChangeID, ModelsError> {
         let db = &self.db;
@@ -514,6 +628,8 @@ where
         stream_changes: StreamBatch<ChangeID>,
         stream_changes_versioning: StreamBatch<ChangeID>,
     ) -> Result<(StreamingStep<Key>, StreamingStep<Key>), MassaDBError> {
+        // the received elements are moved into the changes rather than cloned,
+        // so that the memory used stays close to the size of the received part
         let mut changes = BTreeMap::new();
 
         let new_cursor: StreamingStep<Vec<u8>> = match stream_changes.new_elements.last_key_value()
@@ -526,8 +642,8 @@ where
         changes.extend(
             stream_changes
                 .new_elements
-                .iter()
-                .map(|(k, v)| (k.clone(), Some(v.clone()))),
+                .into_iter()
+                .map(|(k, v)| (k, Some(v))),
         );
 
         let mut versioning_changes = BTreeMap::new();
@@ -541,8 +657,8 @@ where
         versioning_changes.extend(
             stream_changes_versioning
                 .new_elements
-                .iter()
-                .map(|(k, v)| (k.clone(), Some(v.clone()))),
+                .into_iter()
+                .map(|(k, v)| (k, Some(v))),
         );
 
         self.write_changes(
@@ -573,6 +689,21 @@ where
     }
 }
 
+/// Total size (in bytes) of the files stored under `path`, recursively
+pub fn dir_disk_usage(path: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            total += dir_disk_usage(&entry.path())?;
+        } else {
+            total += metadata.len();
+        }
+    }
+    Ok(total)
+}
+
 impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
     /// Returns a new `MassaDB` instance
     pub fn new(config: MassaDBConfig) -> Self {
@@ -616,6 +747,7 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
             change_id_serializer: SlotSerializer::new(),
             change_id_deserializer,
             current_batch,
+            disk_usage_cache: None,
         };
 
         if massa_db.get_change_id().is_err() {
@@ -679,10 +811,20 @@ impl MassaDBController for RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
 
     /// Writes the batch to the DB
     fn write_batch(&mut self, batch: DBBatch, versioning_batch: DBBatch, change_id: Option<Slot>) {
-        self.write_changes(batch, versioning_batch, change_id, false)
+        self.try_write_batch(batch, versioning_batch, change_id)
             .expect(CRUD_ERROR);
     }
 
+    /// Writes the batch to the DB, or returns why it could not be written
+    fn try_write_batch(
+        &mut self,
+        batch: DBBatch,
+        versioning_batch: DBBatch,
+        change_id: Option<Slot>,
+    ) -> Result<(), MassaDBError> {
+        self.write_changes(batch, versioning_batch, change_id, false)
+    }
+
     /// Utility function to put / update a key & value in the batch
     fn put_or_update_entry_value(&self, batch: &mut DBBatch, key: Vec<u8>, value: &[u8]) {
         batch.insert(key, Some(value.to_vec()));
@@ -925,6 +1067,8 @@ mod test {
             max_versioning_elements_size: 100,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -955,6 +1099,8 @@ mod test {
             max_versioning_elements_size: 100,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -1039,6 +1185,8 @@ mod test {
             max_versioning_elements_size: 100,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -1124,6 +1272,8 @@ mod test {
             max_versioning_elements_size: 100,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -1173,6 +1323,8 @@ mod test {
                 max_versioning_elements_size: 100,
                 thread_count: THREAD_COUNT,
                 max_ledger_backups: 10,
+                disk_soft_quota: None,
+                disk_hard_quota: None,
             };
             let mut db_backup_1_opts = MassaDB::default_db_opts();
             db_backup_1_opts.create_if_missing(false);
@@ -1197,6 +1349,8 @@ mod test {
                 max_versioning_elements_size: 100,
                 thread_count: THREAD_COUNT,
                 max_ledger_backups: 10,
+                disk_soft_quota: None,
+                disk_hard_quota: None,
             };
             let mut db_backup_2_opts = MassaDB::default_db_opts();
             db_backup_2_opts.create_if_missing(false);
@@ -1230,6 +1384,8 @@ mod test {
             max_versioning_elements_size: 100,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -1277,6 +1433,8 @@ mod test {
                 max_versioning_elements_size: 100,
                 thread_count: THREAD_COUNT,
                 max_ledger_backups: 10,
+                disk_soft_quota: None,
+                disk_hard_quota: None,
             };
             // let db_backup_2_opts = MassaDB::default_db_opts();
 
@@ -1309,6 +1467,99 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_disk_quota() {
+        // 1- Init a db with a soft quota only: writes are accepted
+        // 2- Backup then set a tiny hard quota: backups are pruned and writes are refused
+
+        let temp_dir_db = tempdir().expect("Unable to create a temp folder");
+        let db_config = MassaDBConfig {
+            path: temp_dir_db.path().to_path_buf(),
+            max_history_length: 100,
+            max_final_state_elements_size: 100,
+            max_versioning_elements_size: 100,
+            thread_count: THREAD_COUNT,
+            max_ledger_backups: 10,
+            disk_soft_quota: Some(1),
+            disk_hard_quota: None,
+        };
+        let mut db_opts = MassaDB::default_db_opts();
+        // Additional checks (only for testing)
+        db_opts.set_paranoid_checks(true);
+
+        let mut db = MassaDB::new_with_options(db_config, db_opts).unwrap();
+
+        let batch = DBBatch::from([(vec![1, 2, 3], Some(vec![4, 5, 6]))]);
+        db.write_changes(batch, DBBatch::new(), Some(Slot::new(1, 0)), false)
+            .unwrap();
+        let backup_path = db.backup_db(Slot::new(1, 0));
+        assert!(backup_path.exists());
+
+        db.config.disk_hard_quota = Some(1);
+        let batch = DBBatch::from([(vec![7, 8, 9], Some(vec![10, 11, 12]))]);
+        let res = db.write_changes(batch, DBBatch::new(), Some(Slot::new(2, 0)), false);
+        assert_matches!(res, Err(MassaDBError::DiskQuotaExceeded(_)));
+        assert!(!backup_path.exists());
+        assert_eq!(db.get_change_id().unwrap(), Slot::new(1, 0));
+        assert_eq!(db.get_cf(STATE_CF, vec![7, 8, 9]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parallel_hash() {
+        // The same changes written in one large batch (hashed in parallel)
+        // and in small batches (hashed sequentially) give the same state hash
+
+        let new_db = |dir: &Path| {
+            let db_config = MassaDBConfig {
+                path: dir.to_path_buf(),
+                max_history_length: 100,
+                max_final_state_elements_size: 100,
+                max_versioning_elements_size: 100,
+                thread_count: THREAD_COUNT,
+                max_ledger_backups: 10,
+                disk_soft_quota: None,
+                disk_hard_quota: None,
+            };
+            MassaDB::new_with_options(db_config, MassaDB::default_db_opts()).unwrap()
+        };
+        let (temp_dir_a, temp_dir_b) = (tempdir().unwrap(), tempdir().unwrap());
+        let mut db_a = new_db(temp_dir_a.path());
+        let mut db_b = new_db(temp_dir_b.path());
+
+        let key = |i: u32| i.to_be_bytes().to_vec();
+        let insertions: DBBatch = (0..2 * PARALLEL_HASH_MIN_CHANGES as u32)
+            .map(|i| (key(i), Some(vec![i as u8; 8])))
+            .collect();
+        // updates and deletions of existing keys, and deletions of missing keys
+        let updates: DBBatch = (0..3 * PARALLEL_HASH_MIN_CHANGES as u32)
+            .map(|i| (key(i), (i % 2 == 0).then(|| vec![!(i as u8); 8])))
+            .collect();
+        for (slot, batch) in [(1, insertions), (2, updates)] {
+            db_a.write_changes(
+                batch.clone(),
+                DBBatch::new(),
+                Some(Slot::new(slot, 0)),
+                false,
+            )
+            .unwrap();
+            let entries: Vec<_> = batch.into_iter().collect();
+            for chunk in entries.chunks(PARALLEL_HASH_MIN_CHANGES / 10) {
+                db_b.write_changes(
+                    chunk.iter().cloned().collect(),
+                    DBBatch::new(),
+                    Some(Slot::new(slot, 0)),
+                    false,
+                )
+                .unwrap();
+            }
+            assert_eq!(db_a.get_xof_db_hash(), db_b.get_xof_db_hash());
+        }
+        assert_ne!(
+            Hash::compute_from(db_a.get_xof_db_hash().to_bytes()),
+            initial_hash()
+        );
+    }
+
     #[test]
     fn test_db_stream() {
         // Init db + add data
@@ -1326,6 +1577,8 @@ mod test {
             max_versioning_elements_size: 100,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -1419,6 +1672,8 @@ mod test {
             max_versioning_elements_size: 100,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -1507,6 +1762,8 @@ mod test {
             max_versioning_elements_size: 10,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -1592,6 +1849,8 @@ mod test {
             max_versioning_elements_size: 20,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let mut db_opts = MassaDB::default_db_opts();
         // Additional checks (only for testing)
@@ -1704,6 +1963,8 @@ mod test {
             max_versioning_elements_size: 20,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
 
         let slot_1 = Slot::new(1, 0);
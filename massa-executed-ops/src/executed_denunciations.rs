@@ -249,6 +249,8 @@ mod test {
             max_final_state_elements_size: 100,
             max_versioning_elements_size: 100,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
             thread_count: THREAD_COUNT,
         };
         let db = Arc::new(RwLock::new(
@@ -8,7 +8,7 @@ use crate::types::{
 
 use crate::ExecutionError;
 use crate::{ExecutionAddressInfo, ReadOnlyExecutionOutput};
-use massa_models::address::Address;
+use massa_models::address::{Address, AddressCycleRewards};
 use massa_models::amount::Amount;
 use massa_models::block_id::BlockId;
 use massa_models::denunciation::DenunciationIndex;
@@ -69,16 +69,6 @@ pub trait ExecutionController: Send + Sync {
     /// Otherwise, the status is a boolean indicating whether the execution was successful (true) or if there was an error (false.)
     fn get_ops_exec_status(&self, batch: &[OperationId]) -> Vec<(Option<bool>, Option<bool>)>;
 
-    /// Get a copy of a single datastore entry with its final and active values
-    ///
-    /// # Return value
-    /// * `(final_data_entry, active_data_entry)`
-    #[allow(clippy::type_complexity)]
-    fn get_final_and_active_data_entry(
-        &self,
-        input: Vec<(Address, Vec<u8>)>,
-    ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>;
-
     /// Returns for a given cycle the stakers taken into account
     /// by the selector. That correspond to the `roll_counts` in `cycle - 3`.
     ///
@@ -115,6 +105,10 @@ pub trait ExecutionController: Send + Sync {
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats;
 
+    /// Get the rewards earned by an address on the given cycles, aggregated over final slots.
+    /// Cycles that are not in the rewards history are omitted.
+    fn get_address_rewards(&self, address: &Address, cycles: &[u64]) -> Vec<AddressCycleRewards>;
+
     #[cfg(feature = "execution-trace")]
     /// Get the abi call stack for a given operation id
     fn get_operation_abi_call_stack(&self, operation_id: OperationId) -> Option<Vec<AbiTrace>>;
@@ -36,6 +36,9 @@ pub enum ExecutionError {
     /// `Transaction` error: {0}
     TransactionError(String),
 
+    /// Name registry error: {0}
+    NameRegistryError(String),
+
     /// Block gas error: {0}
     BlockGasError(String),
 
@@ -62,6 +65,14 @@ pub enum ExecutionError {
         error: VMError,
     },
 
+    /// Execution of bytecode {bytecode_hash} in {context} context overran its wall-clock budget
+    TimeBudgetExceeded {
+        /// execution context in which the budget was exceeded
+        context: String,
+        /// hash of the offending bytecode
+        bytecode_hash: massa_hash::Hash,
+    },
+
     /// Cache error: {0}
     CacheError(#[from] CacheError),
 
@@ -265,6 +265,28 @@ fn to_execution_query_result(
                 },
             )
         }
+        // the gRPC API queries a single state per item and never requests both values:
+        // they are answered with the candidate value
+        ExecutionQueryResponseItem::AmountFinalAndCandidate(result) => {
+            grpc_api::execution_query_response_item::ResponseItem::Amount(
+                result.candidate_value.unwrap_or_default().into(),
+            )
+        }
+        ExecutionQueryResponseItem::RollCountFinalAndCandidate(result) => {
+            grpc_api::execution_query_response_item::ResponseItem::RollCount(
+                result.candidate_value.unwrap_or_default(),
+            )
+        }
+        ExecutionQueryResponseItem::BytecodeFinalAndCandidate(result) => {
+            grpc_api::execution_query_response_item::ResponseItem::Bytes(
+                result.candidate_value.unwrap_or_default().0,
+            )
+        }
+        ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(result) => {
+            grpc_api::execution_query_response_item::ResponseItem::Bytes(
+                result.candidate_value.unwrap_or_default(),
+            )
+        }
     };
 
     grpc_api::ExecutionQueryResponseItem {
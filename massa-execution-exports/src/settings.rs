@@ -3,6 +3,7 @@
 //! This module provides the structures used to provide configuration parameters to the Execution system
 
 use massa_models::amount::Amount;
+use massa_models::config::ChainParametersSchedule;
 use massa_sc_runtime::GasCosts;
 use massa_time::MassaTime;
 use num::rational::Ratio;
@@ -30,12 +31,16 @@ pub struct ExecutionConfig {
     pub max_async_gas: u64,
     /// constant cost for async messages
     pub async_msg_cst_gas_cost: u64,
-    /// maximum gas per block
-    pub max_gas_per_block: u64,
+    /// chain parameters versions, giving the gas available to a block and the base gas of an operation at its slot
+    pub chain_parameters: ChainParametersSchedule,
     /// number of threads
     pub thread_count: u8,
     /// price of a roll inside the network
     pub roll_price: Amount,
+    /// cost of the registration or renewal of a name
+    pub name_registration_cost: Amount,
+    /// number of periods added to the registration of a name by a registration or renewal
+    pub name_registration_periods: u64,
     /// extra lag to add on the execution cursor to improve performance
     pub cursor_delay: MassaTime,
     /// genesis timestamp
@@ -68,10 +73,11 @@ pub struct ExecutionConfig {
     pub storage_costs_constants: StorageCostsConstants,
     /// Max gas for read only executions
     pub max_read_only_gas: u64,
+    /// wall-clock budget of a single read-only smart contract execution (0 to disable).
+    /// Slot executions overrunning it are only logged, as their outcome must be the same on every node.
+    pub max_execution_duration: MassaTime,
     /// Gas costs
     pub gas_costs: GasCosts,
-    /// Gas used by a transaction, a roll buy or a roll sell)
-    pub base_operation_gas_cost: u64,
     /// last start period, used to attach to the correct execution slot if the network has restarted
     pub last_start_period: u64,
     /// Path to the hard drive cache storage
@@ -102,4 +108,8 @@ pub struct ExecutionConfig {
     pub max_execution_traces_slot_limit: usize,
     /// Where to dump blocks
     pub block_dump_folder_path: PathBuf,
+    /// delete the ledger entries left empty (no balance, rolls, deferred credits, bytecode nor datastore) at the end of each slot
+    pub delete_empty_ledger_entries: bool,
+    /// number of most recent cycles for which the final rewards of each address are kept in memory
+    pub rewards_history_cycles: u64,
 }
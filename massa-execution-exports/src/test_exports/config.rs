@@ -34,10 +34,12 @@ impl Default for ExecutionConfig {
             async_msg_cst_gas_cost: ASYNC_MSG_CST_GAS_COST,
             thread_count: THREAD_COUNT,
             roll_price: ROLL_PRICE,
+            name_registration_cost: NAME_REGISTRATION_COST,
+            name_registration_periods: NAME_REGISTRATION_PERIODS,
             cursor_delay: MassaTime::from_millis(0),
             block_reward: BLOCK_REWARD,
             endorsement_count: ENDORSEMENT_COUNT as u64,
-            max_gas_per_block: MAX_GAS_PER_BLOCK,
+            chain_parameters: ChainParametersSchedule::default(),
             operation_validity_period: OPERATION_VALIDITY_PERIODS,
             periods_per_cycle: PERIODS_PER_CYCLE,
             // reset genesis timestamp because we are in test mode that can take a while to process
@@ -50,6 +52,7 @@ impl Default for ExecutionConfig {
             max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
             storage_costs_constants,
             max_read_only_gas: 1_000_000_000,
+            max_execution_duration: MassaTime::from_millis(0),
             gas_costs: GasCosts::new(
                 concat!(
                     env!("CARGO_MANIFEST_DIR"),
@@ -63,7 +66,6 @@ impl Default for ExecutionConfig {
                 .into(),
             )
             .unwrap(),
-            base_operation_gas_cost: BASE_OPERATION_GAS_COST,
             last_start_period: 0,
             hd_cache_path,
             lru_cache_size: 1000,
@@ -81,6 +83,8 @@ impl Default for ExecutionConfig {
             broadcast_slot_execution_traces_channel_capacity: 5000,
             max_execution_traces_slot_limit: 320,
             block_dump_folder_path,
+            delete_empty_ledger_entries: DELETE_EMPTY_LEDGER_ENTRIES,
+            rewards_history_cycles: 10,
         }
     }
 }
@@ -15,7 +15,8 @@ use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::{
-    address::Address, address::ExecutionAddressCycleInfo, amount::Amount, slot::Slot,
+    address::Address, address::AddressRewards, address::ExecutionAddressCycleInfo, amount::Amount,
+    slot::Slot,
 };
 use massa_pos_exports::ProductionStats;
 use massa_storage::Storage;
@@ -123,6 +124,20 @@ pub enum ExecutionQueryRequestItem {
 
     /// get filtered events. Returns ExecutionQueryResponseItem::Events
     Events(EventFilter),
+
+    /// gets the final and candidate balances of an address, returns ExecutionQueryResponseItem::AmountFinalAndCandidate(balances)
+    AddressBalance(Address),
+    /// gets the final and candidate roll counts of an address, returns ExecutionQueryResponseItem::RollCountFinalAndCandidate(rolls)
+    AddressRolls(Address),
+    /// gets the final and candidate bytecodes of an address, returns ExecutionQueryResponseItem::BytecodeFinalAndCandidate(bytecodes)
+    AddressBytecode(Address),
+    /// gets the final and candidate values of a datastore entry, returns ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(values)
+    AddressDatastoreValue {
+        /// Address for which to query the datastore
+        addr: Address,
+        /// Key of the entry
+        key: Vec<u8>,
+    },
 }
 
 /// Execution state query response item
@@ -147,6 +162,23 @@ pub enum ExecutionQueryResponseItem {
     CycleInfos(ExecutionQueryCycleInfos),
     /// Events
     Events(Vec<SCOutputEvent>),
+    /// final and candidate amount values
+    AmountFinalAndCandidate(ExecutionQueryFinalAndCandidate<Amount>),
+    /// final and candidate roll count values
+    RollCountFinalAndCandidate(ExecutionQueryFinalAndCandidate<u64>),
+    /// final and candidate bytecodes
+    BytecodeFinalAndCandidate(ExecutionQueryFinalAndCandidate<Bytecode>),
+    /// final and candidate datastore values
+    DatastoreValueFinalAndCandidate(ExecutionQueryFinalAndCandidate<Vec<u8>>),
+}
+
+/// Final and candidate values of the same item, read from a single state of the execution.
+/// A value is `None` when the item does not exist in the corresponding state.
+pub struct ExecutionQueryFinalAndCandidate<T> {
+    /// value in the final state
+    pub final_value: Option<T>,
+    /// value in the candidate (speculative) state
+    pub candidate_value: Option<T>,
 }
 
 /// Execution status of an operation or denunciation
@@ -200,8 +232,10 @@ pub struct ExecutionAddressInfo {
     /// candidate datastore keys of the address
     pub candidate_datastore_keys: BTreeSet<Vec<u8>>,
 
-    /// future deferred credits
+    /// candidate future deferred credits
     pub future_deferred_credits: BTreeMap<Slot, Amount>,
+    /// final future deferred credits
+    pub final_future_deferred_credits: BTreeMap<Slot, Amount>,
 
     /// cycle information
     pub cycle_infos: Vec<ExecutionAddressCycleInfo>,
@@ -226,6 +260,8 @@ pub struct ExecutedBlockInfo {
     pub current_version: u32,
     /// Announced network version (see Versioning doc)
     pub announced_version: Option<u32>,
+    /// Coins credited by the block execution to block and endorsement producers
+    pub rewards: BTreeMap<Address, AddressRewards>,
 }
 
 /// structure describing the output of a single execution
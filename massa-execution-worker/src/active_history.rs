@@ -37,6 +37,17 @@ pub enum SlotIndexPosition {
 }
 
 impl ActiveHistory {
+    /// Approximate memory used by the history in bytes, counting the ledger changes of each slot
+    pub fn memory_usage(&self) -> usize {
+        self.0
+            .iter()
+            .map(|output| {
+                std::mem::size_of::<ExecutionOutput>()
+                    + output.state_changes.ledger_changes.memory_usage()
+            })
+            .sum()
+    }
+
     /// Remove `slot` and the slots after it from history
     pub fn truncate_from(&mut self, slot: &Slot, thread_count: u8) {
         match self.get_slot_index(slot, thread_count) {
@@ -27,6 +27,7 @@ use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::block_id::BlockIdSerializer;
 use massa_models::bytecode::Bytecode;
 use massa_models::denunciation::DenunciationIndex;
+use massa_models::name_registry::name_registry_address;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_models::{
     address::Address,
@@ -39,6 +40,7 @@ use massa_models::{
 use massa_module_cache::controller::ModuleCache;
 use massa_pos_exports::PoSChanges;
 use massa_serialization::Serializer;
+use massa_time::MassaTime;
 use massa_versioning::address_factory::{AddressArgs, AddressFactory};
 use massa_versioning::versioning::MipStore;
 use massa_versioning::versioning_factory::{FactoryStrategy, VersioningFactory};
@@ -47,6 +49,7 @@ use rand::SeedableRng;
 use rand_xoshiro::Xoshiro256PlusPlus;
 use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, warn};
 
 /// A snapshot taken from an `ExecutionContext` and that represents its current state.
@@ -179,6 +182,12 @@ pub struct ExecutionContext {
     /// The gas remaining before the last subexecution.
     /// so *excluding* the gas used by the last sc call.
     pub gas_remaining_before_subexecution: Option<u64>,
+
+    /// wall-clock deadline of the ongoing smart contract execution, if the watchdog is armed
+    execution_deadline: Option<Instant>,
+
+    /// true if the ongoing smart contract execution overran its wall-clock deadline
+    execution_deadline_exceeded: bool,
 }
 
 impl ExecutionContext {
@@ -244,7 +253,43 @@ impl ExecutionContext {
             address_factory: AddressFactory { mip_store },
             execution_trail_hash,
             gas_remaining_before_subexecution: None,
+            execution_deadline: None,
+            execution_deadline_exceeded: false,
+        }
+    }
+
+    /// Arm the wall-clock watchdog for a smart contract execution starting now.
+    /// Once `budget` has elapsed, every ABI call of a read-only execution fails so that it is aborted.
+    /// Slot executions are never interrupted, as their outcome must not depend on the speed of the node:
+    /// overrunning the budget is only reported when disarming the watchdog.
+    /// A zero `budget` leaves the watchdog disarmed.
+    pub fn arm_watchdog(&mut self, budget: MassaTime) {
+        self.execution_deadline_exceeded = false;
+        self.execution_deadline =
+            (budget.as_millis() > 0).then(|| Instant::now() + budget.to_duration());
+    }
+
+    /// Returns true if the ongoing read-only execution overran its wall-clock deadline
+    pub fn watchdog_expired(&mut self) -> bool {
+        if !self.read_only {
+            return false;
+        }
+        if !self.execution_deadline_exceeded {
+            self.execution_deadline_exceeded = self
+                .execution_deadline
+                .map_or(false, |deadline| Instant::now() >= deadline);
         }
+        self.execution_deadline_exceeded
+    }
+
+    /// Disarm the wall-clock watchdog at the end of a smart contract execution.
+    /// Returns true if the execution overran its deadline.
+    pub fn disarm_watchdog(&mut self) -> bool {
+        let overran = self
+            .execution_deadline
+            .take()
+            .map_or(false, |deadline| Instant::now() >= deadline);
+        std::mem::take(&mut self.execution_deadline_exceeded) || overran
     }
 
     /// Returns a snapshot containing the clone of the current execution state.
@@ -588,6 +633,24 @@ impl ExecutionContext {
             .set_data_entry(&self.get_current_address()?, address, key, data)
     }
 
+    /// Sets a datastore entry of the name registry address, bypassing the access rights
+    /// as the registry is only written by the native name operations.
+    /// The registry address must exist.
+    ///
+    /// # Arguments
+    /// * payer: the address paying the storage costs
+    /// * key: the name
+    /// * data: the serialized name record
+    pub fn set_name_registry_entry(
+        &mut self,
+        payer: &Address,
+        key: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        self.speculative_ledger
+            .set_data_entry(payer, &name_registry_address(), key, data)
+    }
+
     /// Appends data to a datastore entry for an address in the speculative ledger.
     /// Fail if the address is absent from the ledger.
     /// Fails if the datastore entry is absent for that address.
@@ -894,6 +957,11 @@ impl ExecutionContext {
         // execute the deferred credits coming from roll sells
         let deferred_credits_transfers = self.execute_deferred_credits(&slot);
 
+        // clean up the ledger entries left empty by this slot
+        if self.config.delete_empty_ledger_entries {
+            self.delete_empty_ledger_entries();
+        }
+
         // take the ledger changes first as they are needed for async messages and cache
         let ledger_changes = self.speculative_ledger.take();
 
@@ -960,6 +1028,35 @@ impl ExecutionContext {
         }
     }
 
+    /// Deletes the ledger entries changed during the slot that are left with no balance, no rolls,
+    /// no pending deferred credits, no bytecode and no datastore.
+    /// A deleted entry is created again from scratch if it receives coins later on.
+    fn delete_empty_ledger_entries(&mut self) {
+        let slot = self.slot;
+        for addr in self.speculative_ledger.get_changed_addresses() {
+            if !self.speculative_ledger.is_entry_empty(&addr) {
+                continue;
+            }
+            if self.speculative_roll_state.get_rolls(&addr) > 0 {
+                continue;
+            }
+            // the deferred credits of the current slot were already paid
+            if !self
+                .speculative_roll_state
+                .get_address_deferred_credits(
+                    &addr,
+                    (std::ops::Bound::Excluded(slot), std::ops::Bound::Unbounded),
+                )
+                .values()
+                .all(|amount| amount.is_zero())
+            {
+                continue;
+            }
+            debug!("deleting empty ledger entry of address {}", addr);
+            self.speculative_ledger.delete_entry(&addr);
+        }
+    }
+
     /// Sets a bytecode for an address in the speculative ledger.
     /// Fail if the address is absent from the ledger.
     ///
@@ -9,15 +9,20 @@ use massa_channel::MassaChannel;
 use massa_execution_exports::{
     ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionConfig, ExecutionController,
     ExecutionError, ExecutionManager, ExecutionQueryError, ExecutionQueryExecutionStatus,
-    ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionQueryResponse,
-    ExecutionQueryResponseItem, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    ExecutionQueryFinalAndCandidate, ExecutionQueryRequest, ExecutionQueryRequestItem,
+    ExecutionQueryResponse, ExecutionQueryResponseItem, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest,
 };
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::execution::EventFilter;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
 use massa_models::stats::ExecutionStats;
-use massa_models::{address::Address, amount::Amount, operation::OperationId};
+use massa_models::{
+    address::{Address, AddressCycleRewards},
+    amount::Amount,
+    operation::OperationId,
+};
 use massa_models::{block_id::BlockId, slot::Slot};
 use parking_lot::{Condvar, Mutex, RwLock};
 use std::collections::{BTreeMap, HashMap};
@@ -331,6 +336,46 @@ impl ExecutionController for ExecutionControllerImpl {
                         execution_lock.get_filtered_sc_output_event(filter),
                     ))
                 }
+                ExecutionQueryRequestItem::AddressBalance(addr) => {
+                    let (final_value, candidate_value) =
+                        execution_lock.get_final_and_candidate_balance(&addr);
+                    Ok(ExecutionQueryResponseItem::AmountFinalAndCandidate(
+                        ExecutionQueryFinalAndCandidate {
+                            final_value,
+                            candidate_value,
+                        },
+                    ))
+                }
+                ExecutionQueryRequestItem::AddressRolls(addr) => {
+                    let (final_rolls, candidate_rolls) =
+                        execution_lock.get_final_and_candidate_rolls(&addr);
+                    Ok(ExecutionQueryResponseItem::RollCountFinalAndCandidate(
+                        ExecutionQueryFinalAndCandidate {
+                            final_value: Some(final_rolls),
+                            candidate_value: Some(candidate_rolls),
+                        },
+                    ))
+                }
+                ExecutionQueryRequestItem::AddressBytecode(addr) => {
+                    let (final_value, candidate_value) =
+                        execution_lock.get_final_and_active_bytecode(&addr);
+                    Ok(ExecutionQueryResponseItem::BytecodeFinalAndCandidate(
+                        ExecutionQueryFinalAndCandidate {
+                            final_value,
+                            candidate_value,
+                        },
+                    ))
+                }
+                ExecutionQueryRequestItem::AddressDatastoreValue { addr, key } => {
+                    let (final_value, candidate_value) =
+                        execution_lock.get_final_and_active_data_entry(&addr, &key);
+                    Ok(ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(
+                        ExecutionQueryFinalAndCandidate {
+                            final_value,
+                            candidate_value,
+                        },
+                    ))
+                }
             };
             resp.responses.push(resp_item);
         }
@@ -365,22 +410,6 @@ impl ExecutionController for ExecutionControllerImpl {
         result
     }
 
-    /// Get a copy of a single datastore entry with its final and active values
-    ///
-    /// # Return value
-    /// * `Vec<(final_data_entry, active_data_entry)>`
-    fn get_final_and_active_data_entry(
-        &self,
-        input: Vec<(Address, Vec<u8>)>,
-    ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)> {
-        let mut result = Vec::with_capacity(input.len());
-        let execution_state_lock = self.execution_state.read();
-        for (addr, key) in input {
-            result.push(execution_state_lock.get_final_and_active_data_entry(&addr, &key));
-        }
-        result
-    }
-
     /// Return the active rolls distribution for the given `cycle`
     fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64> {
         self.execution_state.read().get_cycle_active_rolls(cycle)
@@ -454,6 +483,8 @@ impl ExecutionController for ExecutionControllerImpl {
                 exec_state.get_final_and_candidate_rolls(addr);
             let future_deferred_credits =
                 exec_state.get_address_future_deferred_credits(addr, deferred_credits_max_slot);
+            let final_future_deferred_credits = exec_state
+                .get_address_final_future_deferred_credits(addr, deferred_credits_max_slot);
             res.push(ExecutionAddressInfo {
                 final_datastore_keys: final_datastore_keys.unwrap_or_default(),
                 candidate_datastore_keys: candidate_datastore_keys.unwrap_or_default(),
@@ -462,6 +493,7 @@ impl ExecutionController for ExecutionControllerImpl {
                 final_roll_count,
                 candidate_roll_count,
                 future_deferred_credits,
+                final_future_deferred_credits,
                 cycle_infos: exec_state.get_address_cycle_infos(addr),
             });
         }
@@ -473,6 +505,13 @@ impl ExecutionController for ExecutionControllerImpl {
         self.execution_state.read().get_stats()
     }
 
+    /// Get the final rewards earned by an address on the given cycles
+    fn get_address_rewards(&self, address: &Address, cycles: &[u64]) -> Vec<AddressCycleRewards> {
+        self.execution_state
+            .read()
+            .get_address_rewards(address, cycles)
+    }
+
     #[cfg(feature = "execution-trace")]
     fn get_operation_abi_call_stack(&self, operation_id: OperationId) -> Option<Vec<AbiTrace>> {
         self.execution_state
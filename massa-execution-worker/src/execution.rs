@@ -24,14 +24,17 @@ use massa_execution_exports::{
 use massa_final_state::FinalStateController;
 use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
 use massa_metrics::MassaMetrics;
-use massa_models::address::ExecutionAddressCycleInfo;
+use massa_models::address::{AddressCycleRewards, AddressRewards, ExecutionAddressCycleInfo};
 use massa_models::bytecode::Bytecode;
 
 use massa_models::datastore::get_prefix_bounds;
 use massa_models::denunciation::{Denunciation, DenunciationIndex};
 use massa_models::execution::EventFilter;
+use massa_models::name_registry::{
+    check_name, name_registry_address, NameRecord, NameRecordDeserializer, NameRecordSerializer,
+};
 use massa_models::output_event::SCOutputEvent;
-use massa_models::prehash::PreHashSet;
+use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::stats::ExecutionStats;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_models::{
@@ -44,6 +47,7 @@ use massa_module_cache::config::ModuleCacheConfig;
 use massa_module_cache::controller::ModuleCache;
 use massa_pos_exports::SelectorController;
 use massa_sc_runtime::{Interface, Response, VMError};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_versioning::versioning::MipStore;
 use massa_wallet::Wallet;
 use parking_lot::{Mutex, RwLock};
@@ -61,11 +65,8 @@ use massa_execution_exports::{AbiTrace, SlotAbiCallStack, Transfer};
 #[cfg(feature = "dump-block")]
 use massa_models::block::FilledBlock;
 #[cfg(feature = "execution-trace")]
-use massa_models::config::{BASE_OPERATION_GAS_COST, MAX_GAS_PER_BLOCK, MAX_OPERATIONS_PER_BLOCK};
 #[cfg(feature = "dump-block")]
 use massa_models::operation::Operation;
-#[cfg(feature = "execution-trace")]
-use massa_models::prehash::PreHashMap;
 #[cfg(feature = "dump-block")]
 use massa_models::secure_share::SecureShare;
 #[cfg(feature = "dump-block")]
@@ -110,6 +111,8 @@ pub(crate) struct ExecutionState {
     pub final_cursor: Slot,
     // store containing execution events that became final
     final_events: EventStore,
+    // rewards credited by final slots, per cycle and per address, for the most recent cycles
+    final_rewards: BTreeMap<u64, PreHashMap<Address, AddressRewards>>,
     // final state with atomic R/W access
     final_state: Arc<RwLock<dyn FinalStateController>>,
     // execution context (see documentation in context.rs)
@@ -206,6 +209,8 @@ impl ExecutionState {
             active_history,
             // empty final event store: it is not recovered through bootstrap
             final_events: Default::default(),
+            // empty rewards history: it is not recovered through bootstrap
+            final_rewards: Default::default(),
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
@@ -219,10 +224,13 @@ impl ExecutionState {
             #[cfg(feature = "execution-trace")]
             trace_history: Arc::new(RwLock::new(TraceHistory::new(
                 config.max_execution_traces_slot_limit as u32,
-                std::cmp::min(
-                    MAX_OPERATIONS_PER_BLOCK,
-                    (MAX_GAS_PER_BLOCK / BASE_OPERATION_GAS_COST) as u32,
-                ),
+                {
+                    let bounds = config.chain_parameters.upper_bounds();
+                    std::cmp::min(
+                        bounds.max_operations_per_block,
+                        (bounds.max_gas_per_block / bounds.base_operation_gas_cost) as u32,
+                    )
+                },
             ))),
             #[cfg(feature = "execution-info")]
             execution_info: Arc::new(RwLock::new(ExecutionInfo::new(
@@ -245,6 +253,41 @@ impl ExecutionState {
             .get_stats(self.active_cursor, self.final_cursor)
     }
 
+    /// Get the final rewards earned by an address on the given cycles.
+    /// Cycles that are not in the rewards history are omitted.
+    pub fn get_address_rewards(
+        &self,
+        address: &Address,
+        cycles: &[u64],
+    ) -> Vec<AddressCycleRewards> {
+        cycles
+            .iter()
+            .filter_map(|cycle| {
+                self.final_rewards
+                    .get(cycle)
+                    .map(|cycle_rewards| AddressCycleRewards {
+                        cycle: *cycle,
+                        rewards: cycle_rewards.get(address).copied().unwrap_or_default(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Aggregate the rewards credited by a final block into the rewards history
+    fn record_final_rewards(&mut self, slot: &Slot, rewards: &BTreeMap<Address, AddressRewards>) {
+        let cycle_rewards = self
+            .final_rewards
+            .entry(slot.get_cycle(self.config.periods_per_cycle))
+            .or_default();
+        for (address, address_rewards) in rewards {
+            let entry = cycle_rewards.entry(*address).or_default();
+            *entry = entry.saturating_add(*address_rewards);
+        }
+        while self.final_rewards.len() as u64 > self.config.rewards_history_cycles {
+            self.final_rewards.pop_first();
+        }
+    }
+
     /// Applies the output of an execution to the final execution state.
     /// The newly applied final output should be from the slot just after the last executed final slot
     ///
@@ -271,6 +314,11 @@ impl ExecutionState {
         // as it will also write the MIP store on disk
         self.update_versioning_stats(&exec_out.block_info, &exec_out.slot);
 
+        // aggregate the rewards credited at this slot
+        if let Some(block_info) = &exec_out.block_info {
+            self.record_final_rewards(&exec_out.slot, &block_info.rewards);
+        }
+
         let exec_out_2 = exec_out.clone();
         #[cfg(feature = "slot-replayer")]
         {
@@ -305,8 +353,13 @@ impl ExecutionState {
         self.massa_metrics.inc_operations_final_counter(
             exec_out_2.state_changes.executed_ops_changes.len() as u64,
         );
-        self.massa_metrics
-            .set_active_history(self.active_history.read().0.len());
+        {
+            let active_history = self.active_history.read();
+            self.massa_metrics
+                .set_active_history(active_history.0.len());
+            self.massa_metrics
+                .set_memory_usage("execution_active_history", active_history.memory_usage());
+        }
 
         self.massa_metrics
             .inc_sc_messages_final_by(exec_out_2.state_changes.async_pool_changes.0.len());
@@ -420,8 +473,11 @@ impl ExecutionState {
         self.active_history.write().0.push_back(exec_out);
 
         // update the prometheus metrics
+        let active_history = self.active_history.read();
         self.massa_metrics
-            .set_active_history(self.active_history.read().0.len())
+            .set_active_history(active_history.0.len());
+        self.massa_metrics
+            .set_memory_usage("execution_active_history", active_history.memory_usage());
     }
 
     /// Helper function.
@@ -430,7 +486,7 @@ impl ExecutionState {
     ///
     /// # Arguments
     /// * `operation`: operation to be schedule
-    /// * `sender_addr`: sender address for the operation (for fee transfer)
+    /// * `sender_addr`: sender address for the operation (for fee transfer, unless the operation has a fee payer)
     fn prepare_operation_for_execution(
         &self,
         operation: &SecureShareOperation,
@@ -458,9 +514,33 @@ impl ExecutionState {
                 .saturating_sub(operation.get_max_spending(self.config.roll_price)),
         );
 
-        // debit the fee from the operation sender
+        // the fee payer of a sponsored operation must be in the thread of the sender
+        if let Err(err) = operation.check_fee_payer_thread(self.config.thread_count) {
+            return Err(ExecutionError::IncludeOperationError(err.to_string()));
+        }
+
+        // the format of the operation must be active at the slot of the block
+        let slot_ts = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            context.slot,
+        )
+        .expect("Cannot get timestamp from slot");
+        if !self
+            .mip_store
+            .is_operation_format_active_at(&operation.content, slot_ts)
+        {
+            return Err(ExecutionError::IncludeOperationError(format!(
+                "version {} of the operation format is not active",
+                operation.content.get_format_version()
+            )));
+        }
+
+        // debit the fee from the operation sender, or from the fee payer of a sponsored operation
+        let fee_payer_addr = operation.get_fee_payer_address();
         if let Err(err) =
-            context.transfer_coins(Some(sender_addr), None, operation.content.fee, false)
+            context.transfer_coins(Some(fee_payer_addr), None, operation.content.fee, false)
         {
             let error = format!("could not spend fees: {}", err);
             let event = context.event_create(error.clone(), true);
@@ -498,17 +578,21 @@ impl ExecutionState {
         remaining_block_gas: &mut u64,
         block_credits: &mut Amount,
     ) -> Result<ExecutionResult, ExecutionError> {
-        // check validity period
-        if !(operation
-            .get_validity_range(self.config.operation_validity_period)
-            .contains(&block_slot.period))
-        {
+        // check that the block slot is within the operation validity window, in the operation thread
+        if !operation.is_valid_in_slot(
+            &block_slot,
+            self.config.thread_count,
+            self.config.operation_validity_period,
+        ) {
             return Err(ExecutionError::InvalidSlotRange);
         }
 
         // check remaining block gas
         let op_gas = operation.get_gas_usage(
-            self.config.base_operation_gas_cost,
+            self.config
+                .chain_parameters
+                .parameters_at(block_slot)
+                .base_operation_gas_cost,
             self.config.gas_costs.sp_compilation_cost,
         );
         let new_remaining_block_gas = remaining_block_gas.checked_sub(op_gas).ok_or_else(|| {
@@ -520,16 +604,6 @@ impl ExecutionState {
         // get the operation's sender address
         let sender_addr = operation.content_creator_address;
 
-        // get the thread to which the operation belongs
-        let op_thread = sender_addr.get_thread(self.config.thread_count);
-
-        // check block/op thread compatibility
-        if op_thread != block_slot.thread {
-            return Err(ExecutionError::IncludeOperationError(
-                "operation vs block thread mismatch".to_string(),
-            ));
-        }
-
         // get operation ID
         let operation_id = operation.id;
 
@@ -566,6 +640,11 @@ impl ExecutionState {
             OperationType::Transaction { .. } => self
                 .execute_transaction_op(&operation.content.op, sender_addr)
                 .map(|_| res),
+            OperationType::RegisterName { .. }
+            | OperationType::SetNameTarget { .. }
+            | OperationType::TransferName { .. } => self
+                .execute_name_registry_op(&operation.content.op, sender_addr)
+                .map(|_| res),
         };
 
         {
@@ -593,7 +672,7 @@ impl ExecutionState {
                     context.insert_executed_op(
                         operation_id,
                         true,
-                        Slot::new(operation.content.expire_period, op_thread),
+                        operation.get_expire_slot(self.config.thread_count),
                     );
                     #[cfg(feature = "execution-trace")]
                     {
@@ -617,7 +696,7 @@ impl ExecutionState {
                     context.insert_executed_op(
                         operation_id,
                         false,
-                        Slot::new(operation.content.expire_period, op_thread),
+                        operation.get_expire_slot(self.config.thread_count),
                     );
                     #[cfg(feature = "execution-trace")]
                     {
@@ -783,6 +862,7 @@ impl ExecutionState {
 
     /// Execute an operation of type `RollSell`
     /// Will panic if called with another operation type
+    /// Either all the `roll_count` rolls are sold or none of them is.
     ///
     /// # Arguments
     /// * `operation`: the `WrappedOperation` to process, must be an `RollSell`
@@ -822,6 +902,7 @@ impl ExecutionState {
 
     /// Execute an operation of type `RollBuy`
     /// Will panic if called with another operation type
+    /// Either all the `roll_count` rolls are bought or none of them is.
     ///
     /// # Arguments
     /// * `operation`: the `WrappedOperation` to process, must be an `RollBuy`
@@ -920,6 +1001,123 @@ impl ExecutionState {
         Ok(())
     }
 
+    /// Execute an operation of the name registry: `RegisterName`, `SetNameTarget` or `TransferName`
+    /// Will panic if called with another operation type
+    ///
+    /// The name records are stored in the datastore of the registry address,
+    /// the storage costs being paid by the sender.
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be a name registry operation
+    /// * `sender_addr`: address of the sender
+    pub fn execute_name_registry_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process name registry operations only
+        let name = match operation {
+            OperationType::RegisterName { name }
+            | OperationType::SetNameTarget { name, .. }
+            | OperationType::TransferName { name, .. } => name,
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: sender_addr,
+            coins: Amount::default(),
+            owned_addresses: vec![sender_addr],
+            operation_datastore: None,
+        }];
+
+        check_name(name).map_err(|err| ExecutionError::NameRegistryError(err.to_string()))?;
+
+        let registry = name_registry_address();
+        let period = context.slot.period;
+        let record = match context.get_data_entry(&registry, name.as_bytes()) {
+            Some(bytes) => Some(
+                NameRecordDeserializer::new()
+                    .deserialize::<DeserializeError>(&bytes)
+                    .map_err(|err| {
+                        ExecutionError::NameRegistryError(format!(
+                            "invalid record of name {}: {}",
+                            name, err
+                        ))
+                    })?
+                    .1,
+            ),
+            None => None,
+        }
+        // expired records are free
+        .filter(|record| !record.is_expired(period));
+
+        let new_record = match (operation, record) {
+            (OperationType::RegisterName { .. }, Some(record)) if record.owner != sender_addr => {
+                return Err(ExecutionError::NameRegistryError(format!(
+                    "name {} is owned by {} until period {}",
+                    name, record.owner, record.expire_period
+                )));
+            }
+            (OperationType::RegisterName { .. }, record) => {
+                // a renewal extends the current registration and keeps its target
+                let (target, start_period) = match record {
+                    Some(record) => (record.target, record.expire_period),
+                    None => (None, period),
+                };
+                if let Err(err) = context.transfer_coins(
+                    Some(sender_addr),
+                    Some(registry),
+                    self.config.name_registration_cost,
+                    false,
+                ) {
+                    return Err(ExecutionError::NameRegistryError(format!(
+                        "{} failed to pay the registration of name {}: {}",
+                        sender_addr, name, err
+                    )));
+                }
+                NameRecord {
+                    owner: sender_addr,
+                    target,
+                    expire_period: start_period
+                        .saturating_add(self.config.name_registration_periods),
+                }
+            }
+            (_, None) => {
+                return Err(ExecutionError::NameRegistryError(format!(
+                    "name {} is not registered",
+                    name
+                )));
+            }
+            (_, Some(record)) if record.owner != sender_addr => {
+                return Err(ExecutionError::NameRegistryError(format!(
+                    "name {} is owned by {}, not by {}",
+                    name, record.owner, sender_addr
+                )));
+            }
+            (OperationType::SetNameTarget { target, .. }, Some(record)) => NameRecord {
+                target: *target,
+                ..record
+            },
+            (OperationType::TransferName { new_owner, .. }, Some(record)) => NameRecord {
+                owner: *new_owner,
+                ..record
+            },
+            _ => panic!("unexpected operation type"),
+        };
+
+        // write the record, the sender paying its storage costs
+        let mut bytes = Vec::new();
+        NameRecordSerializer::new()
+            .serialize(&new_record, &mut bytes)
+            .map_err(|err| ExecutionError::NameRegistryError(err.to_string()))?;
+        context.set_name_registry_entry(&sender_addr, name.as_bytes().to_vec(), bytes)
+    }
+
     /// Execute an operation of type `ExecuteSC`
     /// Will panic if called with another operation type
     ///
@@ -964,14 +1162,16 @@ impl ExecutionState {
             .module_cache
             .read()
             .load_tmp_module(bytecode, *max_gas)?;
-        // run the VM
-        let _res = massa_sc_runtime::run_main(
+        // run the VM under the wall-clock watchdog
+        context_guard!(self).arm_watchdog(self.config.max_execution_duration);
+        let res = massa_sc_runtime::run_main(
             &*self.execution_interface,
             module,
             *max_gas,
             self.config.gas_costs.clone(),
-        )
-        .map_err(|error| ExecutionError::VMError {
+        );
+        self.report_watchdog("ExecuteSC", bytecode);
+        let _res = res.map_err(|error| ExecutionError::VMError {
             context: "ExecuteSC".to_string(),
             error,
         })?;
@@ -1063,6 +1263,7 @@ impl ExecutionState {
         // load and execute the compiled module
         // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
         let module = self.module_cache.write().load_module(&bytecode, max_gas)?;
+        context_guard!(self).arm_watchdog(self.config.max_execution_duration);
         let response = massa_sc_runtime::run_function(
             &*self.execution_interface,
             module,
@@ -1080,6 +1281,7 @@ impl ExecutionState {
             }
             _ => (),
         }
+        self.report_watchdog("CallSC", &bytecode);
         let _response = response.map_err(|error| ExecutionError::VMError {
             context: "CallSC".to_string(),
             error,
@@ -1094,6 +1296,22 @@ impl ExecutionState {
         }
     }
 
+    /// Disarm the wall-clock watchdog after a smart contract execution,
+    /// and log the offending bytecode if the execution overran its budget.
+    /// Returns true if the budget was overrun.
+    fn report_watchdog(&self, context: &str, bytecode: &[u8]) -> bool {
+        if !context_guard!(self).disarm_watchdog() {
+            return false;
+        }
+        warn!(
+            "{} execution of bytecode {} overran its wall-clock budget of {} ms",
+            context,
+            massa_hash::Hash::compute_from(bytecode),
+            self.config.max_execution_duration.as_millis()
+        );
+        true
+    }
+
     /// Tries to execute an asynchronous message
     /// If the execution failed reimburse the message sender.
     ///
@@ -1179,6 +1397,7 @@ impl ExecutionState {
             .module_cache
             .write()
             .load_module(&bytecode, message.max_gas)?;
+        context_guard!(self).arm_watchdog(self.config.max_execution_duration);
         let response = massa_sc_runtime::run_function(
             &*self.execution_interface,
             module,
@@ -1187,6 +1406,7 @@ impl ExecutionState {
             message.max_gas,
             self.config.gas_costs.clone(),
         );
+        self.report_watchdog("Asynchronous Message", &bytecode);
         match response {
             Ok(res) => {
                 self.module_cache
@@ -1294,6 +1514,7 @@ impl ExecutionState {
         }
 
         let mut block_info: Option<ExecutedBlockInfo> = None;
+        let mut block_rewards: BTreeMap<Address, AddressRewards> = BTreeMap::new();
 
         // Check if there is a block at this slot
         if let Some((block_id, block_metadata)) = exec_target {
@@ -1313,6 +1534,7 @@ impl ExecutionState {
                 block_id: *block_id,
                 current_version: stored_block.content.header.content.current_version,
                 announced_version: stored_block.content.header.content.announced_version,
+                rewards: Default::default(),
             });
 
             // gather all operations
@@ -1330,6 +1552,16 @@ impl ExecutionState {
                     .collect::<Vec<_>>()
             };
 
+            // load the final ledger entries involved in the operations in a single disk access
+            let involved_addresses: PreHashSet<Address> = operations
+                .iter()
+                .flat_map(|op| op.get_ledger_involved_addresses())
+                .collect();
+            self.final_state
+                .read()
+                .get_ledger()
+                .prefetch_entries(&involved_addresses.into_iter().collect::<Vec<_>>());
+
             debug!("executing {} operations at slot {}", operations.len(), slot);
 
             // gather all available endorsement creators and target blocks
@@ -1346,7 +1578,11 @@ impl ExecutionState {
                 .expect("same thread parent creator missing");
 
             // Set remaining block gas
-            let mut remaining_block_gas = self.config.max_gas_per_block;
+            let mut remaining_block_gas = self
+                .config
+                .chain_parameters
+                .parameters_at(*slot)
+                .max_gas_per_block;
 
             // Set block credits
             let mut block_credits = self.config.block_reward;
@@ -1442,6 +1678,9 @@ impl ExecutionState {
                 }
             }
 
+            // The credits accumulated so far beyond the block reward are operation fees
+            let block_fees = block_credits.saturating_sub(self.config.block_reward);
+
             // Try executing the denunciations of this block
             for denunciation in &stored_block.content.header.content.denunciations {
                 match self.execute_denunciation(
@@ -1489,6 +1728,14 @@ impl ExecutionState {
                 ) {
                     Ok(_) => {
                         remaining_credit = remaining_credit.saturating_sub(block_credit_part);
+                        record_reward(
+                            &mut block_rewards,
+                            endorsement_creator,
+                            block_credit_part,
+                            block_fees,
+                            block_credits,
+                            true,
+                        );
 
                         #[cfg(feature = "execution-info")]
                         exec_info
@@ -1512,6 +1759,14 @@ impl ExecutionState {
                 ) {
                     Ok(_) => {
                         remaining_credit = remaining_credit.saturating_sub(block_credit_part);
+                        record_reward(
+                            &mut block_rewards,
+                            endorsement_target_creator,
+                            block_credit_part,
+                            block_fees,
+                            block_credits,
+                            true,
+                        );
                         #[cfg(feature = "execution-info")]
                         {
                             exec_info.endorsement_target_reward =
@@ -1536,6 +1791,14 @@ impl ExecutionState {
                     remaining_credit, block_creator_addr, err
                 )
             } else {
+                record_reward(
+                    &mut block_rewards,
+                    block_creator_addr,
+                    remaining_credit,
+                    block_fees,
+                    block_credits,
+                    false,
+                );
                 #[cfg(feature = "execution-info")]
                 {
                     exec_info.block_producer_reward = Some((block_creator_addr, remaining_credit));
@@ -1558,6 +1821,10 @@ impl ExecutionState {
             .write()
             .save_transfers_for_slot(*slot, transfers.clone());
 
+        if let Some(info) = block_info.as_mut() {
+            info.rewards = block_rewards;
+        }
+
         // Finish slot
         #[allow(unused_mut)]
         let mut exec_out = context_guard!(self).settle_slot(block_info);
@@ -1789,14 +2056,21 @@ impl ExecutionState {
                     .read()
                     .load_tmp_module(&bytecode, req.max_gas)?;
 
-                // run the VM
-                massa_sc_runtime::run_main(
+                // run the VM under the wall-clock watchdog
+                context_guard!(self).arm_watchdog(self.config.max_execution_duration);
+                let res = massa_sc_runtime::run_main(
                     &*self.execution_interface,
                     module,
                     req.max_gas,
                     self.config.gas_costs.clone(),
-                )
-                .map_err(|error| ExecutionError::VMError {
+                );
+                if self.report_watchdog("ReadOnlyExecutionTarget::BytecodeExecution", &bytecode) {
+                    return Err(ExecutionError::TimeBudgetExceeded {
+                        context: "ReadOnlyExecutionTarget::BytecodeExecution".to_string(),
+                        bytecode_hash: massa_hash::Hash::compute_from(&bytecode),
+                    });
+                }
+                res.map_err(|error| ExecutionError::VMError {
                     context: "ReadOnlyExecutionTarget::BytecodeExecution".to_string(),
                     error,
                 })?
@@ -1842,6 +2116,7 @@ impl ExecutionState {
                     .write()
                     .load_module(&bytecode, req.max_gas)?;
 
+                context_guard!(self).arm_watchdog(self.config.max_execution_duration);
                 let response = massa_sc_runtime::run_function(
                     &*self.execution_interface,
                     module,
@@ -1860,6 +2135,12 @@ impl ExecutionState {
                     }
                     _ => (),
                 }
+                if self.report_watchdog("ReadOnlyExecutionTarget::FunctionCall", &bytecode) {
+                    return Err(ExecutionError::TimeBudgetExceeded {
+                        context: "ReadOnlyExecutionTarget::FunctionCall".to_string(),
+                        bytecode_hash: massa_hash::Hash::compute_from(&bytecode),
+                    });
+                }
 
                 response.map_err(|error| ExecutionError::VMError {
                     context: "ReadOnlyExecutionTarget::FunctionCall".to_string(),
@@ -2191,6 +2472,35 @@ impl ExecutionState {
         )
     }
 
+    /// Get the future deferred credits of an address in the final state
+    /// With optionally a limit slot (excluded)
+    pub fn get_address_final_future_deferred_credits(
+        &self,
+        address: &Address,
+        max_slot: std::ops::Bound<Slot>,
+    ) -> BTreeMap<Slot, Amount> {
+        let min_slot = self
+            .final_cursor
+            .get_next_slot(self.config.thread_count)
+            .expect("unexpected slot overflow in get_address_final_future_deferred_credits");
+        self.final_state
+            .read()
+            .get_pos_state()
+            .get_deferred_credits_range(
+                (std::ops::Bound::Included(min_slot), max_slot),
+                Some(address),
+            )
+            .credits
+            .into_iter()
+            .filter_map(|(slot, addr_amount)| {
+                addr_amount
+                    .get(address)
+                    .filter(|amount| !amount.is_zero())
+                    .map(|amount| (slot, *amount))
+            })
+            .collect()
+    }
+
     /// Get future deferred credits of an address
     /// Returns tuple: (speculative, final)
     pub fn get_address_deferred_credits(
@@ -2272,3 +2582,31 @@ impl ExecutionState {
         );
     }
 }
+
+/// Record coins credited to an address by a block execution.
+/// The fee share of the credited amount is proportional to the part of the block credits that comes from operation fees.
+fn record_reward(
+    rewards: &mut BTreeMap<Address, AddressRewards>,
+    address: Address,
+    credited: Amount,
+    block_fees: Amount,
+    block_credits: Amount,
+    from_endorsement: bool,
+) {
+    let fee_share = if block_credits.is_zero() {
+        Amount::zero()
+    } else {
+        Amount::from_raw(
+            (credited.to_raw() as u128 * block_fees.to_raw() as u128
+                / block_credits.to_raw() as u128) as u64,
+        )
+    };
+    let reward = credited.saturating_sub(fee_share);
+    let entry = rewards.entry(address).or_default();
+    if from_endorsement {
+        entry.endorsement_rewards = entry.endorsement_rewards.saturating_add(reward);
+    } else {
+        entry.block_rewards = entry.block_rewards.saturating_add(reward);
+    }
+    entry.fees = entry.fees.saturating_add(fee_share);
+}
@@ -51,11 +51,17 @@ use tracing::warn;
 ))]
 use massa_models::datastore::Datastore;
 
-/// helper for locking the context mutex
+/// helper for locking the context mutex.
+/// Fails the ABI call if the ongoing read-only execution overran its wall-clock budget
+/// (see `ExecutionContext::arm_watchdog`).
 macro_rules! context_guard {
-    ($self:ident) => {
-        $self.context.lock()
-    };
+    ($self:ident) => {{
+        let mut context = $self.context.lock();
+        if context.watchdog_expired() {
+            bail!("execution wall-clock budget exceeded");
+        }
+        context
+    }};
 }
 
 /// an implementation of the Interface trait (see massa-sc-runtime crate)
@@ -116,6 +122,8 @@ impl InterfaceImpl {
             max_versioning_elements_size: 100_000,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
 
         let db = Arc::new(RwLock::new(
@@ -1737,6 +1745,43 @@ mod tests {
         assert!(op_keys.contains(&b"k2".to_vec()));
     }
 
+    // Tests that the ABI calls of a read-only execution fail once its wall-clock watchdog expired,
+    // while a slot execution only reports the overrun.
+    #[test]
+    fn test_watchdog() {
+        let sender_addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let interface = InterfaceImpl::new_default(sender_addr, None);
+
+        // slot execution: not interrupted
+        interface
+            .context
+            .lock()
+            .arm_watchdog(MassaTime::from_millis(1));
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        interface
+            .set_ds_value_wasmv1(b"k1", b"v1", Some(sender_addr.to_string()))
+            .unwrap();
+        assert!(interface.context.lock().disarm_watchdog());
+
+        // read-only execution: aborted
+        interface.context.lock().read_only = true;
+        interface
+            .context
+            .lock()
+            .arm_watchdog(MassaTime::from_millis(1));
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert!(interface
+            .set_ds_value_wasmv1(b"k1", b"v1", Some(sender_addr.to_string()))
+            .is_err());
+        assert!(interface.context.lock().disarm_watchdog());
+
+        // once disarmed, ABI calls succeed again
+        interface
+            .set_ds_value_wasmv1(b"k1", b"v1", Some(sender_addr.to_string()))
+            .unwrap();
+        assert!(!interface.context.lock().disarm_watchdog());
+    }
+
     #[test]
     fn test_native_amount() {
         let sender_addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
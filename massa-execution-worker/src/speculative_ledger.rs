@@ -103,6 +103,42 @@ impl SpeculativeLedger {
         self.added_changes = snapshot;
     }
 
+    /// Gets the addresses whose ledger entry was changed since the creation of the `SpeculativeLedger`
+    pub fn get_changed_addresses(&self) -> Vec<Address> {
+        self.added_changes.0.keys().copied().collect()
+    }
+
+    /// Checks whether the ledger entry of an address exists and is empty:
+    /// zero balance, no bytecode and no datastore entry.
+    ///
+    /// # Arguments:
+    /// `addr`: the address to query
+    pub fn is_entry_empty(&self, addr: &Address) -> bool {
+        // cheapest checks first, the datastore keys listing is done last
+        match self.get_balance(addr) {
+            Some(balance) if balance.is_zero() => {}
+            _ => return false,
+        }
+        if self
+            .get_bytecode(addr)
+            .is_some_and(|bytecode| !bytecode.0.is_empty())
+        {
+            return false;
+        }
+        self.get_keys(addr, &[])
+            .map_or(true, |keys| keys.is_empty())
+    }
+
+    /// Deletes the ledger entry of an address
+    ///
+    /// # Arguments:
+    /// `addr`: the address whose entry is deleted
+    pub fn delete_entry(&mut self, addr: &Address) {
+        self.added_changes
+            .0
+            .insert(*addr, SetUpdateOrDelete::Delete);
+    }
+
     /// Gets the effective balance of an address
     ///
     /// # Arguments:
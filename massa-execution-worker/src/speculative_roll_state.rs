@@ -60,8 +60,8 @@ impl SpeculativeRollState {
         self.added_changes = snapshot;
     }
 
-    /// Internal function to retrieve the rolls of a given address
-    fn get_rolls(&self, addr: &Address) -> u64 {
+    /// Retrieve the rolls of a given address
+    pub fn get_rolls(&self, addr: &Address) -> u64 {
         self.added_changes
             .roll_changes
             .get(addr)
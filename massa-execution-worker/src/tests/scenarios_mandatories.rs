@@ -4,8 +4,8 @@ use massa_async_pool::{AsyncMessage, AsyncPool, AsyncPoolChanges, AsyncPoolConfi
 use massa_db_exports::{DBBatch, ShareableMassaDBController};
 use massa_executed_ops::{ExecutedDenunciations, ExecutedDenunciationsConfig};
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionStackElement,
-    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionConfig, ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionQueryResponseItem,
+    ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
 use massa_final_state::test_exports::get_initials;
 use massa_final_state::MockFinalStateController;
@@ -19,23 +19,34 @@ use massa_models::config::{
 };
 use massa_models::prehash::PreHashMap;
 use massa_models::test_exports::gen_endorsements_for_denunciation;
-use massa_models::{address::Address, amount::Amount, slot::Slot};
+use massa_models::{
+    address::{Address, AddressCycleRewards, AddressRewards},
+    amount::Amount,
+    slot::Slot,
+};
 use massa_models::{
     denunciation::Denunciation,
     execution::EventFilter,
-    operation::{Operation, OperationSerializer, OperationType},
+    name_registry::{name_registry_address, NameRecord, NameRecordDeserializer},
+    operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
     secure_share::SecureShareContent,
 };
 use massa_pos_exports::{
     CycleInfo, MockSelectorControllerWrapper, PoSConfig, PoSFinalState, ProductionStats, Selection,
 };
+use massa_serialization::{DeserializeError, Deserializer};
 use massa_signature::KeyPair;
 use massa_test_framework::{TestUniverse, WaitPoint};
 use mockall::predicate;
 use num::rational::Ratio;
 use parking_lot::RwLock;
 use std::sync::Arc;
-use std::{cmp::Reverse, collections::BTreeMap, str::FromStr, time::Duration};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+    time::Duration,
+};
 
 use super::universe::{ExecutionForeignControllers, ExecutionTestUniverse};
 
@@ -119,6 +130,9 @@ fn final_state_boilerplate(
         ledger_controller
             .expect_entry_exists()
             .returning(move |_| false);
+        ledger_controller
+            .expect_prefetch_entries()
+            .returning(move |_| ());
     });
     mock_final_state
         .write()
@@ -1271,6 +1285,8 @@ fn send_and_receive_transaction() {
         Operation {
             fee: Amount::from_str("10").unwrap(),
             expire_period: 10,
+            start_period: None,
+            fee_payer: None,
             op: OperationType::Transaction {
                 recipient_address,
                 amount: Amount::from_str("90").unwrap(),
@@ -1295,6 +1311,291 @@ fn send_and_receive_transaction() {
     finalized_waitpoint.wait();
 }
 
+#[test]
+fn sponsored_transaction() {
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    let finalized_waitpoint = WaitPoint::new();
+    let finalized_waitpoint_trigger_handle = finalized_waitpoint.get_trigger_handle();
+    // the sender and the sponsor must be in the thread of the block
+    let keypair_in_thread_0 = || loop {
+        let keypair = KeyPair::generate(0).unwrap();
+        if Address::from_public_key(&keypair.get_public_key()).get_thread(exec_cfg.thread_count)
+            == 0
+        {
+            break keypair;
+        }
+    };
+    let sender_keypair = keypair_in_thread_0();
+    let sponsor_keypair = keypair_in_thread_0();
+    let sponsor_address = Address::from_public_key(&sponsor_keypair.get_public_key());
+    let recipient_address =
+        Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    foreign_controllers
+        .final_state
+        .write()
+        .expect_finalize()
+        .times(1)
+        .with(predicate::eq(Slot::new(1, 0)), predicate::always())
+        .returning(move |_, changes| {
+            // the sender could send its whole balance as it did not pay the fee
+            assert_eq!(
+                changes
+                    .ledger_changes
+                    .get_balance_or_else(&recipient_address, || None),
+                Some(Amount::from_str("200").unwrap())
+            );
+            // the sponsor paid the fee
+            assert_eq!(
+                changes
+                    .ledger_changes
+                    .get_balance_or_else(&sponsor_address, || None),
+                Some(Amount::from_str("90").unwrap())
+            );
+            finalized_waitpoint_trigger_handle.trigger();
+        });
+    let mut universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg.clone());
+
+    // the sender sends its whole balance, the sponsor pays the fee
+    let operation = Operation::new_verifiable(
+        Operation {
+            fee: Amount::from_str("10").unwrap(),
+            expire_period: 10,
+            start_period: None,
+            fee_payer: None,
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str("100").unwrap(),
+            },
+        }
+        .sponsor(&sponsor_keypair, &sender_keypair.get_public_key(), *CHAINID)
+        .unwrap(),
+        OperationSerializer::new(),
+        &sender_keypair,
+        *CHAINID,
+    )
+    .unwrap();
+    universe.storage.store_operations(vec![operation.clone()]);
+    let block = ExecutionTestUniverse::create_block(
+        &KeyPair::from_str(TEST_SK_1).unwrap(),
+        Slot::new(1, 0),
+        vec![operation],
+        vec![],
+        vec![],
+    );
+    universe.send_and_finalize(&KeyPair::from_str(TEST_SK_1).unwrap(), block);
+    finalized_waitpoint.wait();
+}
+
+#[test]
+fn name_registry() {
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    let finalized_waitpoint = WaitPoint::new();
+    let finalized_waitpoint_trigger_handle = finalized_waitpoint.get_trigger_handle();
+    let keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+    let owner = Address::from_public_key(&keypair.get_public_key());
+    let target = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    foreign_controllers
+        .ledger_controller
+        .set_expectations(|ledger_controller| {
+            ledger_controller
+                .expect_get_data_entry()
+                .returning(move |_, _| None);
+        });
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    let registration_cost = exec_cfg.name_registration_cost;
+    let registration_periods = exec_cfg.name_registration_periods;
+    foreign_controllers
+        .final_state
+        .write()
+        .expect_finalize()
+        .times(1)
+        .with(predicate::eq(Slot::new(1, 0)), predicate::always())
+        .returning(move |_, changes| {
+            let registry = name_registry_address();
+            // 100 in the get_balance in the `final_state_boilerplate` and the registration cost
+            assert_eq!(
+                changes
+                    .ledger_changes
+                    .get_balance_or_else(&registry, || None),
+                Some(
+                    Amount::from_str("100")
+                        .unwrap()
+                        .saturating_add(registration_cost)
+                )
+            );
+            let record = changes
+                .ledger_changes
+                .get_data_entry_or_else(&registry, b"massa", || None)
+                .expect("name record not written");
+            let (_, record) = NameRecordDeserializer::new()
+                .deserialize::<DeserializeError>(&record)
+                .unwrap();
+            assert_eq!(
+                record,
+                NameRecord {
+                    owner,
+                    target: Some(target),
+                    expire_period: 1 + registration_periods,
+                }
+            );
+            // the registration of an invalid name fails
+            assert!(changes
+                .ledger_changes
+                .get_data_entry_or_else(&registry, b"Massa", || None)
+                .is_none());
+            finalized_waitpoint_trigger_handle.trigger();
+        });
+    let mut universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg);
+    let operations: Vec<_> = [
+        OperationType::RegisterName {
+            name: "massa".to_string(),
+        },
+        OperationType::SetNameTarget {
+            name: "massa".to_string(),
+            target: Some(target),
+        },
+        OperationType::RegisterName {
+            name: "Massa".to_string(),
+        },
+    ]
+    .into_iter()
+    .map(|op| {
+        Operation::new_verifiable(
+            Operation {
+                fee: Amount::from_str("10").unwrap(),
+                expire_period: 10,
+                start_period: None,
+                fee_payer: None,
+                op,
+            },
+            OperationSerializer::new(),
+            &keypair,
+            *CHAINID,
+        )
+        .unwrap()
+    })
+    .collect();
+    universe.storage.store_operations(operations.clone());
+    let block =
+        ExecutionTestUniverse::create_block(&keypair, Slot::new(1, 0), operations, vec![], vec![]);
+    universe.send_and_finalize(&keypair, block);
+    finalized_waitpoint.wait();
+}
+
+#[test]
+fn delete_empty_ledger_entries() {
+    let exec_cfg = ExecutionConfig {
+        delete_empty_ledger_entries: true,
+        ..Default::default()
+    };
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    let finalized_waitpoint = WaitPoint::new();
+    let finalized_waitpoint_trigger_handle = finalized_waitpoint.get_trigger_handle();
+    // the sender must be in the thread of the block
+    let sender_keypair = loop {
+        let keypair = KeyPair::generate(0).unwrap();
+        if Address::from_public_key(&keypair.get_public_key()).get_thread(exec_cfg.thread_count)
+            == 0
+        {
+            break keypair;
+        }
+    };
+    let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+    let recipient_address =
+        Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    foreign_controllers
+        .ledger_controller
+        .set_expectations(|ledger_controller| {
+            ledger_controller
+                .expect_get_bytecode()
+                .returning(move |_| None);
+            ledger_controller
+                .expect_get_datastore_keys()
+                .returning(move |_, _| Some(BTreeSet::new()));
+        });
+    foreign_controllers
+        .final_state
+        .write()
+        .expect_finalize()
+        .times(1)
+        .with(predicate::eq(Slot::new(1, 0)), predicate::always())
+        .returning(move |_, changes| {
+            // the sender spent its whole balance and owns nothing else: its entry is deleted
+            assert_eq!(
+                changes.ledger_changes.0.get(&sender_address),
+                Some(&SetUpdateOrDelete::Delete)
+            );
+            // the recipient entry is kept
+            assert_eq!(
+                changes
+                    .ledger_changes
+                    .get_balance_or_else(&recipient_address, || None),
+                Some(Amount::from_str("200").unwrap())
+            );
+            finalized_waitpoint_trigger_handle.trigger();
+        });
+    let mut universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg.clone());
+
+    // the sender sends its whole balance
+    let operation = Operation::new_verifiable(
+        Operation {
+            fee: Amount::zero(),
+            expire_period: 10,
+            start_period: None,
+            fee_payer: None,
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str("100").unwrap(),
+            },
+        },
+        OperationSerializer::new(),
+        &sender_keypair,
+        *CHAINID,
+    )
+    .unwrap();
+    universe.storage.store_operations(vec![operation.clone()]);
+    let block = ExecutionTestUniverse::create_block(
+        &KeyPair::from_str(TEST_SK_1).unwrap(),
+        Slot::new(1, 0),
+        vec![operation],
+        vec![],
+        vec![],
+    );
+    universe.send_and_finalize(&KeyPair::from_str(TEST_SK_1).unwrap(), block);
+    finalized_waitpoint.wait();
+}
+
 #[test]
 fn roll_buy() {
     // setup
@@ -1344,6 +1645,8 @@ fn roll_buy() {
         Operation {
             fee: Amount::zero(),
             expire_period: 10,
+            start_period: None,
+            fee_payer: None,
             op: OperationType::RollBuy { roll_count: 1 },
         },
         OperationSerializer::new(),
@@ -1365,6 +1668,79 @@ fn roll_buy() {
     finalized_waitpoint.wait();
 }
 
+#[test]
+fn roll_buy_all_or_nothing() {
+    // setup
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    let finalized_waitpoint = WaitPoint::new();
+    let finalized_waitpoint_trigger_handle = finalized_waitpoint.get_trigger_handle();
+    let keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+    let address = Address::from_public_key(&keypair.get_public_key());
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    foreign_controllers
+        .final_state
+        .write()
+        .expect_finalize()
+        .times(1)
+        .with(predicate::eq(Slot::new(1, 0)), predicate::always())
+        .returning(move |_, changes| {
+            // the 2 rolls purchase cannot be afforded and fails as a whole,
+            // only the following single roll purchase is applied: 100 base + 1 bought
+            assert_eq!(changes.pos_changes.roll_changes.len(), 1);
+            assert_eq!(changes.pos_changes.roll_changes.get(&address), Some(&101));
+
+            // address has 100 coins, only one roll price is spent
+            // -> (100 (balance) - 100 (roll price)) + 1.02 (block reward)
+            assert_eq!(
+                changes.ledger_changes.0.get(&address).unwrap(),
+                &SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                    balance: massa_ledger_exports::SetOrKeep::Set(exec_cfg.block_reward),
+                    bytecode: massa_ledger_exports::SetOrKeep::Keep,
+                    datastore: BTreeMap::new()
+                })
+            );
+
+            finalized_waitpoint_trigger_handle.trigger();
+        });
+    let mut universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg.clone());
+    // create the operations
+    let operations: Vec<SecureShareOperation> = [2, 1]
+        .into_iter()
+        .map(|roll_count| {
+            Operation::new_verifiable(
+                Operation {
+                    fee: Amount::zero(),
+                    expire_period: 10,
+                    start_period: None,
+                    fee_payer: None,
+                    op: OperationType::RollBuy { roll_count },
+                },
+                OperationSerializer::new(),
+                &keypair,
+                *CHAINID,
+            )
+            .unwrap()
+        })
+        .collect();
+    // create the block containing the roll buy operations
+    universe.storage.store_operations(operations.clone());
+    let block =
+        ExecutionTestUniverse::create_block(&keypair, Slot::new(1, 0), operations, vec![], vec![]);
+    // set our block as a final block so the purchases are processed
+    universe.send_and_finalize(&keypair, block);
+    finalized_waitpoint.wait();
+}
+
 #[test]
 fn roll_sell() {
     // setup
@@ -1456,6 +1832,8 @@ fn roll_sell() {
         Operation {
             fee: Amount::zero(),
             expire_period: 10,
+            start_period: None,
+            fee_payer: None,
             op: OperationType::RollSell {
                 roll_count: roll_sell_1,
             },
@@ -1469,6 +1847,8 @@ fn roll_sell() {
         Operation {
             fee: Amount::zero(),
             expire_period: 10,
+            start_period: None,
+            fee_payer: None,
             op: OperationType::RollSell {
                 roll_count: roll_sell_2,
             },
@@ -1698,6 +2078,8 @@ fn roll_slash() {
         Operation {
             fee: Amount::zero(),
             expire_period: 6,
+            start_period: None,
+            fee_payer: None,
             op: OperationType::RollSell { roll_count: 97 },
         },
         OperationSerializer::new(),
@@ -1816,6 +2198,8 @@ fn roll_slash_2() {
         Operation {
             fee: Amount::zero(),
             expire_period: 6,
+            start_period: None,
+            fee_payer: None,
             op: OperationType::RollSell { roll_count: 100 },
         },
         OperationSerializer::new(),
@@ -2205,6 +2589,46 @@ fn datastore_manipulations() {
                 ExecutionQueryRequestItem::Events(EventFilter::default()),
             ],
         });
+
+    // the final and candidate values are answered together and match the single-state queries
+    let mut responses = universe
+        .module_controller
+        .query_state(ExecutionQueryRequest {
+            requests: vec![
+                ExecutionQueryRequestItem::AddressBalance(addr),
+                ExecutionQueryRequestItem::AddressBalanceFinal(addr),
+                ExecutionQueryRequestItem::AddressBalanceCandidate(addr),
+                ExecutionQueryRequestItem::AddressRolls(addr),
+                ExecutionQueryRequestItem::AddressRollsFinal(addr),
+                ExecutionQueryRequestItem::AddressRollsCandidate(addr),
+            ],
+        })
+        .responses
+        .into_iter();
+    let Some(Ok(ExecutionQueryResponseItem::AmountFinalAndCandidate(balances))) = responses.next()
+    else {
+        panic!("unexpected balances response");
+    };
+    let mut next_amount = || match responses.next() {
+        Some(Ok(ExecutionQueryResponseItem::Amount(amount))) => Some(amount),
+        Some(Err(_)) => None,
+        _ => panic!("unexpected balance response"),
+    };
+    assert_eq!(balances.final_value, next_amount());
+    assert_eq!(balances.candidate_value, next_amount());
+    let Some(Ok(ExecutionQueryResponseItem::RollCountFinalAndCandidate(rolls))) = responses.next()
+    else {
+        panic!("unexpected rolls response");
+    };
+    for expected in [rolls.final_value, rolls.candidate_value] {
+        match responses.next() {
+            Some(Ok(ExecutionQueryResponseItem::RollCount(count))) => {
+                assert_eq!(expected, Some(count))
+            }
+            _ => panic!("unexpected roll count response"),
+        }
+    }
+
     // Just checking that is works no asserts for now
     universe
         .module_controller
@@ -2302,6 +2726,8 @@ fn not_enough_instance_gas() {
         Operation {
             fee: Amount::const_init(10, 0),
             expire_period: 10,
+            start_period: None,
+            fee_payer: None,
             op: OperationType::ExecuteSC {
                 max_coins: Amount::const_init(0, 0),
                 data: bytecode.to_vec(),
@@ -2586,6 +3012,36 @@ fn test_rewards() {
     );
     universe.send_and_finalize(&keypair, block);
     finalized_waitpoint.wait();
+
+    // check the rewards aggregated over the cycle
+    let block_credit_part = exec_cfg
+        .block_reward
+        .checked_div_u64(3 * (1 + (ENDORSEMENT_COUNT as u64)))
+        .expect("critical: block_credits checked_div factor is 0");
+    let rewards = universe
+        .module_controller
+        .get_address_rewards(&endorsement_producer_address, &[0, 1]);
+    assert_eq!(
+        rewards,
+        vec![AddressCycleRewards {
+            cycle: 0,
+            rewards: AddressRewards {
+                block_rewards: Amount::zero(),
+                endorsement_rewards: block_credit_part
+                    .saturating_mul_u64(2 + ENDORSEMENT_COUNT as u64),
+                fees: Amount::zero(),
+            },
+        }]
+    );
+    let rewards = universe
+        .module_controller
+        .get_address_rewards(&keypair2_address, &[0]);
+    assert_eq!(
+        rewards[0].rewards.block_rewards,
+        exec_cfg
+            .block_reward
+            .saturating_sub(block_credit_part.saturating_mul_u64(2 * ENDORSEMENT_COUNT as u64))
+    );
 }
 
 #[test]
@@ -2928,6 +3384,8 @@ fn test_dump_block() {
         Operation {
             fee: Amount::from_str("10").unwrap(),
             expire_period: 10,
+            start_period: None,
+            fee_payer: None,
             op: OperationType::Transaction {
                 recipient_address,
                 amount: Amount::from_str("90").unwrap(),
@@ -24,10 +24,13 @@ use massa_models::{
     amount::Amount,
     block::SecureShareBlock,
     block_id::BlockId,
-    config::{MIP_STORE_STATS_BLOCK_CONSIDERED, THREAD_COUNT},
+    config::THREAD_COUNT,
     datastore::Datastore,
     execution::EventFilter,
-    operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
+    operation::{
+        Operation, OperationSerializer, OperationType, SecureShareOperation,
+        EXTENDED_OPERATION_FORMAT_VERSION,
+    },
     prehash::PreHashMap,
     secure_share::SecureShareContent,
     slot::Slot,
@@ -36,9 +39,10 @@ use massa_pos_exports::MockSelectorControllerWrapper;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use massa_test_framework::TestUniverse;
-use massa_versioning::versioning::{MipStatsConfig, MipStore};
+use massa_versioning::{
+    test_helpers::versioning_helpers::mip_store_with_active_components, versioning::MipComponent,
+};
 use massa_wallet::test_exports::create_test_wallet;
-use num::rational::Ratio;
 use parking_lot::RwLock;
 use tempfile::TempDir;
 use tokio::sync::broadcast;
@@ -65,6 +69,8 @@ impl ExecutionForeignControllers {
             max_versioning_elements_size: 100_000,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
 
         let db = Arc::new(RwLock::new(
@@ -97,11 +103,11 @@ impl TestUniverse for ExecutionTestUniverse {
 
     fn new(controllers: Self::ForeignControllers, config: Self::Config) -> Self {
         let storage = Storage::create_root();
-        let mip_stats_config = MipStatsConfig {
-            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
-            warn_announced_version_ratio: Ratio::new_raw(30, 100),
-        };
-        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+        // the name, sponsored and deferred operations are accepted
+        let mip_store = mip_store_with_active_components(BTreeMap::from([(
+            MipComponent::Operation,
+            EXTENDED_OPERATION_FORMAT_VERSION,
+        )]));
         let (tx, rx) = broadcast::channel(16);
         #[cfg(feature = "execution-trace")]
         let (tx_traces, rx_traces) = broadcast::channel(16);
@@ -186,6 +192,8 @@ impl ExecutionTestUniverse {
             Operation {
                 fee: Amount::const_init(10, 0),
                 expire_period: 10,
+                start_period: None,
+                fee_payer: None,
                 op,
             },
             OperationSerializer::new(),
@@ -216,6 +224,8 @@ impl ExecutionTestUniverse {
             Operation {
                 fee,
                 expire_period: 10,
+                start_period: None,
+                fee_payer: None,
                 op,
             },
             OperationSerializer::new(),
@@ -2,6 +2,7 @@
 
 //! This file defines the factory settings
 
+use massa_models::config::ChainParametersSchedule;
 use massa_time::MassaTime;
 
 /// Structure defining the settings of the factory
@@ -15,12 +16,12 @@ pub struct FactoryConfig {
     pub t0: MassaTime,
     /// initial delay before starting production, to avoid double-production on node restart
     pub initial_delay: MassaTime,
-    /// maximal block size in bytes
-    pub max_block_size: u64,
-    /// maximal block gas
-    pub max_block_gas: u64,
-    /// maximum number of operation ids in block
-    pub max_operations_per_block: u32,
+    /// chain parameters versions, bounding the operations of a block at its slot
+    pub chain_parameters: ChainParametersSchedule,
+    /// minimum number of endorsements a block must include (0 to disable the requirement)
+    pub min_block_endorsements: u32,
+    /// the endorsement requirement is lowered by one for each such number of periods without block since the parent
+    pub endorsement_relaxation_periods: u64,
     /// last start period, to deduce genesis blocks
     pub last_start_period: u64,
     /// cycle duration in periods
@@ -31,4 +32,8 @@ pub struct FactoryConfig {
     pub stop_production_when_zero_connections: bool,
     /// chain id
     pub chain_id: u64,
+    /// interval between two liveness heartbeats of the staking addresses (None to disable them)
+    pub heartbeat_interval: Option<MassaTime>,
+    /// interval between two links of the staking addresses to the node id (None to disable them)
+    pub node_link_interval: Option<MassaTime>,
 }
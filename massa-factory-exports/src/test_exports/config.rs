@@ -11,14 +11,16 @@ impl Default for FactoryConfig {
             genesis_timestamp: MassaTime::now(),
             t0: T0,
             initial_delay: MassaTime::from_millis(0),
-            max_block_size: MAX_BLOCK_SIZE as u64,
-            max_block_gas: MAX_GAS_PER_BLOCK,
-            max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+            chain_parameters: ChainParametersSchedule::default(),
+            min_block_endorsements: MIN_BLOCK_ENDORSEMENTS,
+            endorsement_relaxation_periods: ENDORSEMENT_RELAXATION_PERIODS,
             last_start_period: 0,
             periods_per_cycle: PERIODS_PER_CYCLE,
             denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
             stop_production_when_zero_connections: false,
             chain_id: *CHAINID,
+            heartbeat_interval: None,
+            node_link_interval: None,
         }
     }
 }
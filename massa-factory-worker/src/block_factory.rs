@@ -3,30 +3,53 @@
 use massa_channel::receiver::MassaReceiver;
 use massa_factory_exports::{FactoryChannels, FactoryConfig};
 use massa_models::{
+    address::Address,
     block::{Block, BlockSerializer},
-    block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
+    block_header::{required_endorsement_count, BlockHeader, BlockHeaderSerializer, SecuredHeader},
     block_id::BlockId,
     endorsement::SecureShareEndorsement,
+    heartbeat::{Heartbeat, HeartbeatSerializer, SecureShareHeartbeat},
+    node::NodeId,
+    node_link::{NodeAddressLink, NodeAddressLinkProof},
     operation::{compute_operations_hash, OperationIdSerializer},
+    prehash::PreHashMap,
     secure_share::SecureShareContent,
     slot::Slot,
-    timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
-use massa_time::MassaTime;
+use massa_signature::KeyPair;
+use massa_time::{MassaTime, SlotTicker};
 use massa_versioning::versioning::MipStore;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
-use std::{sync::Arc, thread, time::Instant};
-use tracing::{info, warn};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
+use tracing::{debug, info, warn};
 
 /// Structure gathering all elements needed by the factory thread
 pub(crate) struct BlockFactoryWorker {
     cfg: FactoryConfig,
     wallet: Arc<RwLock<Wallet>>,
+    /// keypair of the node, countersigning the links of our staking addresses to it
+    node_keypair: KeyPair,
+    maintenance_mode: Arc<AtomicBool>,
     channels: FactoryChannels,
     factory_receiver: MassaReceiver<()>,
     mip_store: MipStore,
     op_id_serializer: OperationIdSerializer,
+    /// last slot at which each of our staking addresses produced a block
+    last_produced_slots: PreHashMap<Address, Slot>,
+    /// last time our liveness heartbeats were broadcast
+    last_heartbeat: Option<MassaTime>,
+    /// last time the links of our staking addresses to the node were broadcast
+    last_node_link: Option<MassaTime>,
+    /// computes the slots at which blocks may be produced
+    slot_ticker: SlotTicker,
 }
 
 impl BlockFactoryWorker {
@@ -35,6 +58,8 @@ impl BlockFactoryWorker {
     pub(crate) fn spawn(
         cfg: FactoryConfig,
         wallet: Arc<RwLock<Wallet>>,
+        node_keypair: KeyPair,
+        maintenance_mode: Arc<AtomicBool>,
         channels: FactoryChannels,
         factory_receiver: MassaReceiver<()>,
         mip_store: MipStore,
@@ -42,13 +67,28 @@ impl BlockFactoryWorker {
         thread::Builder::new()
             .name("block-factory".into())
             .spawn(|| {
+                // ignore genesis
+                let slot_ticker = SlotTicker::new(cfg.genesis_timestamp, cfg.t0, cfg.thread_count)
+                    .expect("could not create slot ticker")
+                    .with_initial_delay(cfg.initial_delay)
+                    .with_min_index(
+                        Slot::new(cfg.last_start_period + 1, 0)
+                            .to_index(cfg.thread_count)
+                            .expect("could not compute first slot index"),
+                    );
                 let mut this = Self {
+                    slot_ticker,
                     cfg,
                     wallet,
+                    node_keypair,
+                    maintenance_mode,
                     channels,
                     factory_receiver,
                     mip_store,
                     op_id_serializer: OperationIdSerializer::new(),
+                    last_produced_slots: PreHashMap::default(),
+                    last_heartbeat: None,
+                    last_node_link: None,
                 };
                 this.run();
             })
@@ -57,52 +97,15 @@ impl BlockFactoryWorker {
 
     /// Gets the next slot and the instant when it will happen.
     /// Slots can be skipped if we waited too much in-between.
-    /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
-    fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
-        // get current absolute time
-        let now = MassaTime::now();
-
-        // if it's the first computed slot, add a time shift to prevent double-production on node restart with clock skew
-        let base_time = if previous_slot.is_none() {
-            now.saturating_add(self.cfg.initial_delay)
-        } else {
-            now
-        };
-
-        // get closest slot according to the current absolute time
-        let mut next_slot = get_closest_slot_to_timestamp(
-            self.cfg.thread_count,
-            self.cfg.t0,
-            self.cfg.genesis_timestamp,
-            base_time,
-        );
-
-        // ignore genesis
-        if next_slot.period <= self.cfg.last_start_period {
-            next_slot = Slot::new(self.cfg.last_start_period + 1, 0);
-        }
-
-        // protection against double-production on unexpected system clock adjustment
-        if let Some(prev_slot) = previous_slot {
-            if next_slot <= prev_slot {
-                next_slot = prev_slot
-                    .get_next_slot(self.cfg.thread_count)
-                    .expect("could not compute next slot");
-            }
-        }
-
-        // get the timestamp of the target slot
-        let next_instant = get_block_slot_timestamp(
-            self.cfg.thread_count,
-            self.cfg.t0,
-            self.cfg.genesis_timestamp,
-            next_slot,
+    fn get_next_slot(&mut self) -> (Slot, Instant) {
+        let (next_index, next_instant) = self
+            .slot_ticker
+            .next_tick()
+            .expect("could not compute next slot");
+        (
+            Slot::from_index(next_index, self.cfg.thread_count),
+            next_instant,
         )
-        .expect("could not get block slot timestamp")
-        .estimate_instant()
-        .expect("could not estimate block slot instant");
-
-        (next_slot, next_instant)
     }
 
     /// Wait and interrupt or wait until an instant or a stop signal
@@ -145,6 +148,13 @@ impl BlockFactoryWorker {
             // the selected block producer is not managed locally => quit
             return;
         };
+        if self.maintenance_mode.load(Ordering::Relaxed) {
+            info!(
+                "block factory skipped block production for slot {} with address {}: node in maintenance mode",
+                slot, block_producer_addr
+            );
+            return;
+        }
         let mut block_storage = self.channels.storage.clone_without_refs();
         {
             let block_lock = block_storage.read_blocks();
@@ -176,7 +186,7 @@ impl BlockFactoryWorker {
 
         // get the parent in the same thread, with its period
         // will not panic because the thread is validated before the call
-        let (same_thread_parent_id, _) = parents[slot.thread as usize];
+        let (same_thread_parent_id, same_thread_parent_period) = parents[slot.thread as usize];
 
         // gather endorsements
         let (endorsements_ids, endo_storage) = self
@@ -197,11 +207,32 @@ impl BlockFactoryWorker {
                 })
                 .collect()
         };
+        // a block without enough endorsements would be rejected by the network
+        let required_endorsements = required_endorsement_count(
+            self.cfg.min_block_endorsements,
+            self.cfg.endorsement_relaxation_periods,
+            slot.period,
+            same_thread_parent_period,
+        );
+        if (endorsements.len() as u32) < required_endorsements {
+            warn!(
+                "block factory skipped block production for slot {}: {} endorsements available, {} required",
+                slot,
+                endorsements.len(),
+                required_endorsements
+            );
+            return;
+        }
         block_storage.extend(endo_storage);
 
         // gather operations and compute global operations hash
         let (op_ids, op_storage) = self.channels.pool.get_block_operations(&slot);
-        if op_ids.len() > self.cfg.max_operations_per_block as usize {
+        let max_operations = self
+            .cfg
+            .chain_parameters
+            .parameters_at(slot)
+            .max_operations_per_block;
+        if op_ids.len() > max_operations as usize {
             warn!("Too many operations returned");
             return;
         }
@@ -253,14 +284,92 @@ impl BlockFactoryWorker {
         self.channels
             .consensus
             .register_block(block_id, slot, block_storage, true);
+
+        self.last_produced_slots.insert(block_producer_addr, slot);
+    }
+
+    /// Broadcast a signed liveness heartbeat for each of our staking addresses, if the heartbeat interval has elapsed.
+    fn send_heartbeats(&mut self) {
+        let Some(interval) = self.cfg.heartbeat_interval else {
+            return;
+        };
+        let now = MassaTime::now();
+        if let Some(last_heartbeat) = self.last_heartbeat {
+            if now.saturating_sub(last_heartbeat) < interval {
+                return;
+            }
+        }
+        self.last_heartbeat = Some(now);
+
+        let heartbeats: Vec<SecureShareHeartbeat> = self
+            .wallet
+            .read()
+            .keys
+            .iter()
+            .filter_map(|(addr, keypair)| {
+                Heartbeat {
+                    timestamp: now,
+                    last_produced_slot: self.last_produced_slots.get(addr).copied(),
+                }
+                .new_verifiable(HeartbeatSerializer::new(), keypair, self.cfg.chain_id)
+                .map_err(|err| warn!("could not sign heartbeat for address {}: {}", addr, err))
+                .ok()
+            })
+            .collect();
+        if heartbeats.is_empty() {
+            return;
+        }
+        if let Err(err) = self.channels.protocol.propagate_heartbeats(heartbeats) {
+            debug!("could not propagate staker heartbeats: {}", err);
+        }
+    }
+
+    /// Broadcast a link of each of our staking addresses to our node id, if the operator opted in and the interval has elapsed.
+    fn send_node_links(&mut self) {
+        let Some(interval) = self.cfg.node_link_interval else {
+            return;
+        };
+        let now = MassaTime::now();
+        if let Some(last_node_link) = self.last_node_link {
+            if now.saturating_sub(last_node_link) < interval {
+                return;
+            }
+        }
+        self.last_node_link = Some(now);
+
+        let node_id = NodeId::new(self.node_keypair.get_public_key());
+        let links: Vec<NodeAddressLinkProof> = self
+            .wallet
+            .read()
+            .keys
+            .iter()
+            .filter_map(|(addr, keypair)| {
+                NodeAddressLinkProof::new(
+                    NodeAddressLink {
+                        node_id,
+                        timestamp: now,
+                    },
+                    keypair,
+                    &self.node_keypair,
+                    self.cfg.chain_id,
+                )
+                .map_err(|err| warn!("could not sign node link for address {}: {}", addr, err))
+                .ok()
+            })
+            .collect();
+        if links.is_empty() {
+            return;
+        }
+        if let Err(err) = self.channels.protocol.propagate_node_address_links(links) {
+            debug!("could not propagate node address links: {}", err);
+        }
     }
 
     /// main run loop of the block creator thread
     fn run(&mut self) {
-        let mut prev_slot = None;
         loop {
             // get next slot
-            let (slot, block_instant) = self.get_next_slot(prev_slot);
+            let (slot, block_instant) = self.get_next_slot();
 
             // wait until slot
             if !self.interruptible_wait_until(block_instant) {
@@ -270,8 +379,11 @@ impl BlockFactoryWorker {
             // process slot
             self.process_slot(slot);
 
-            // update previous slot
-            prev_slot = Some(slot);
+            // broadcast our liveness heartbeats if needed
+            self.send_heartbeats();
+
+            // broadcast the links of our staking addresses to the node if needed
+            self.send_node_links();
         }
     }
 }
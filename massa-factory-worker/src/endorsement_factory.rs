@@ -7,22 +7,29 @@ use massa_models::{
     endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement},
     secure_share::SecureShareContent,
     slot::Slot,
-    timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
 use massa_signature::KeyPair;
-use massa_time::MassaTime;
+use massa_time::SlotTicker;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
-use std::{sync::Arc, thread, time::Instant};
-use tracing::{debug, warn};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
+use tracing::{debug, info, warn};
 
 /// Structure gathering all elements needed by the factory thread
 pub(crate) struct EndorsementFactoryWorker {
     cfg: FactoryConfig,
     wallet: Arc<RwLock<Wallet>>,
+    maintenance_mode: Arc<AtomicBool>,
     channels: FactoryChannels,
     factory_receiver: MassaReceiver<()>,
-    half_t0: MassaTime,
+    slot_ticker: SlotTicker,
     endorsement_serializer: EndorsementSerializer,
 }
 
@@ -32,19 +39,32 @@ impl EndorsementFactoryWorker {
     pub(crate) fn spawn(
         cfg: FactoryConfig,
         wallet: Arc<RwLock<Wallet>>,
+        maintenance_mode: Arc<AtomicBool>,
         channels: FactoryChannels,
         factory_receiver: MassaReceiver<()>,
     ) -> thread::JoinHandle<()> {
         thread::Builder::new()
             .name("endorsement-factory".into())
             .spawn(|| {
+                // endorsements are made half a period before their slot, ignoring genesis
+                let slot_ticker = SlotTicker::new(cfg.genesis_timestamp, cfg.t0, cfg.thread_count)
+                    .expect("could not create slot ticker")
+                    .with_offset(
+                        cfg.t0
+                            .checked_div_u64(2)
+                            .expect("could not compute half_t0"),
+                    )
+                    .with_initial_delay(cfg.initial_delay)
+                    .with_min_index(
+                        Slot::new(cfg.last_start_period + 1, 0)
+                            .to_index(cfg.thread_count)
+                            .expect("could not compute first slot index"),
+                    );
                 let mut this = Self {
-                    half_t0: cfg
-                        .t0
-                        .checked_div_u64(2)
-                        .expect("could not compute half_t0"),
+                    slot_ticker,
                     cfg,
                     wallet,
+                    maintenance_mode,
                     channels,
                     factory_receiver,
                     endorsement_serializer: EndorsementSerializer::new(),
@@ -56,53 +76,15 @@ impl EndorsementFactoryWorker {
 
     /// Gets the next slot and the instant when the corresponding endorsements should be made.
     /// Slots can be skipped if we waited too much in-between.
-    /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
-    fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
-        // get delayed time
-        let now = MassaTime::now();
-
-        // if it's the first computed slot, add a time shift to prevent double-production on node restart with clock skew
-        let base_time = if previous_slot.is_none() {
-            now.saturating_add(self.cfg.initial_delay)
-        } else {
-            now
-        };
-
-        // get closest slot according to the current absolute time
-        let mut next_slot = get_closest_slot_to_timestamp(
-            self.cfg.thread_count,
-            self.cfg.t0,
-            self.cfg.genesis_timestamp,
-            base_time,
-        );
-
-        // protection against double-production on unexpected system clock adjustment
-        if let Some(prev_slot) = previous_slot {
-            if next_slot <= prev_slot {
-                next_slot = prev_slot
-                    .get_next_slot(self.cfg.thread_count)
-                    .expect("could not compute next slot");
-            }
-        }
-
-        // ignore genesis
-        if next_slot.period <= self.cfg.last_start_period {
-            next_slot = Slot::new(self.cfg.last_start_period + 1, 0);
-        }
-
-        // get the timestamp of the target slot
-        let next_instant = get_block_slot_timestamp(
-            self.cfg.thread_count,
-            self.cfg.t0,
-            self.cfg.genesis_timestamp,
-            next_slot,
+    fn get_next_slot(&mut self) -> (Slot, Instant) {
+        let (next_index, next_instant) = self
+            .slot_ticker
+            .next_tick()
+            .expect("could not compute next slot");
+        (
+            Slot::from_index(next_index, self.cfg.thread_count),
+            next_instant,
         )
-        .expect("could not get block slot timestamp")
-        .saturating_sub(self.half_t0)
-        .estimate_instant()
-        .expect("could not estimate block slot instant");
-
-        (next_slot, next_instant)
     }
 
     /// Wait and interrupt or wait until an instant or a stop signal
@@ -156,6 +138,14 @@ impl EndorsementFactoryWorker {
         if producers_indices.is_empty() {
             return;
         }
+        if self.maintenance_mode.load(Ordering::Relaxed) {
+            info!(
+                "endorsement factory skipped {} endorsement(s) for slot {}: node in maintenance mode",
+                producers_indices.len(),
+                slot
+            );
+            return;
+        }
 
         // check if we need to have connections to produce a block and in this case, check if we have enough.
         #[cfg(not(feature = "sandbox"))]
@@ -213,10 +203,9 @@ impl EndorsementFactoryWorker {
 
     /// main run loop of the endorsement creator thread
     fn run(&mut self) {
-        let mut prev_slot = None;
         loop {
             // get next slot
-            let (slot, endorsement_instant) = self.get_next_slot(prev_slot);
+            let (slot, endorsement_instant) = self.get_next_slot();
 
             // wait until slot
             if !self.interruptible_wait_until(endorsement_instant) {
@@ -225,9 +214,6 @@ impl EndorsementFactoryWorker {
 
             // process slot
             self.process_slot(slot);
-
-            // update previous slot
-            prev_slot = Some(slot);
         }
     }
 }
@@ -3,13 +3,14 @@
 use massa_channel::MassaChannel;
 use massa_versioning::versioning::MipStore;
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
 
 use crate::{
     block_factory::BlockFactoryWorker, endorsement_factory::EndorsementFactoryWorker,
     manager::FactoryManagerImpl,
 };
 use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
+use massa_signature::KeyPair;
 use massa_wallet::Wallet;
 
 /// Start factory
@@ -17,6 +18,8 @@ use massa_wallet::Wallet;
 /// # Arguments
 /// * `cfg`: factory configuration
 /// * `wallet`: atomic reference to the node wallet
+/// * `node_keypair`: keypair of the node, countersigning the links of the staking addresses to the node id
+/// * `maintenance_mode`: shared flag, production is skipped while it is set
 /// * `channels`: channels to communicate with other modules
 ///
 /// # Return value
@@ -24,6 +27,8 @@ use massa_wallet::Wallet;
 pub fn start_factory(
     cfg: FactoryConfig,
     wallet: Arc<RwLock<Wallet>>,
+    node_keypair: KeyPair,
+    maintenance_mode: Arc<AtomicBool>,
     channels: FactoryChannels,
     mip_store: MipStore,
 ) -> Box<dyn FactoryManager> {
@@ -39,14 +44,21 @@ pub fn start_factory(
     let block_worker_handle = BlockFactoryWorker::spawn(
         cfg.clone(),
         wallet.clone(),
+        node_keypair,
+        maintenance_mode.clone(),
         channels.clone(),
         block_worker_rx,
         mip_store,
     );
 
     // start endorsement factory worker
-    let endorsement_worker_handle =
-        EndorsementFactoryWorker::spawn(cfg, wallet, channels, endorsement_worker_rx);
+    let endorsement_worker_handle = EndorsementFactoryWorker::spawn(
+        cfg,
+        wallet,
+        maintenance_mode,
+        channels,
+        endorsement_worker_rx,
+    );
 
     // create factory manager
     let manager = FactoryManagerImpl {
@@ -2,6 +2,7 @@ use std::{str::FromStr, sync::Arc};
 
 use super::BlockTestFactory;
 use massa_consensus_exports::MockConsensusController;
+use massa_factory_exports::FactoryConfig;
 use massa_hash::Hash;
 use massa_models::config::CHAINID;
 use massa_models::{
@@ -133,6 +134,8 @@ fn basic_creation_with_operation() {
             let content = Operation {
                 fee: Amount::from_str("0.01").unwrap(),
                 expire_period: 2,
+                start_period: None,
+                fee_payer: None,
                 op: OperationType::RollBuy { roll_count: 1 },
             };
             let operation = Operation::new_verifiable(
@@ -187,3 +190,71 @@ fn basic_creation_with_operation() {
     }
     test_factory.stop();
 }
+
+/// Skips the block when the pool does not have the endorsements it requires.
+#[test]
+#[serial]
+fn skip_creation_without_enough_endorsements() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+    let keypair = KeyPair::generate(0).unwrap();
+    let storage = Storage::create_root();
+    let staking_address = Address::from_public_key(&keypair.get_public_key());
+    let parent = BlockId::generate_from_hash(Hash::compute_from("test".as_bytes()));
+    // the parent in the thread of slot (1, 0) is at period 0: no period was missed
+    let mut parents = Vec::new();
+    for i in 0..THREAD_COUNT as u64 {
+        parents.push((parent, i));
+    }
+    let pair = Arc::new((Mutex::new(false), Condvar::new()));
+    let pair2 = pair.clone();
+    let mut consensus_controller = Box::new(MockConsensusController::new());
+    consensus_controller
+        .expect_get_best_parents()
+        .times(1)
+        .return_once(move || parents);
+    consensus_controller.expect_register_block().never();
+    let mut selector_controller = Box::new(MockSelectorController::new());
+    selector_controller
+        .expect_get_producer()
+        .times(1)
+        .return_once(move |_| Ok(staking_address));
+    let mut pool_controller = Box::new(MockPoolController::new());
+    pool_controller
+        .expect_get_block_denunciations()
+        .returning(|_| vec![]);
+    pool_controller
+        .expect_get_block_endorsements()
+        .times(1)
+        .return_once(move |_, slot| {
+            assert_eq!(*slot, Slot::new(1, 0));
+            let (lock, cvar) = &*pair2;
+            let mut started = lock.lock();
+            *started = true;
+            cvar.notify_one();
+            (vec![], Storage::create_root())
+        });
+    pool_controller.expect_get_block_operations().never();
+    let factory_config = FactoryConfig {
+        min_block_endorsements: 1,
+        endorsement_relaxation_periods: 2,
+        ..FactoryConfig::default()
+    };
+    let mut test_factory = BlockTestFactory::new_with_config(
+        &keypair,
+        storage,
+        consensus_controller,
+        selector_controller,
+        pool_controller,
+        factory_config,
+    );
+    let (lock, cvar) = &*pair;
+    let mut started = lock.lock();
+    if !*started {
+        cvar.wait(&mut started);
+    }
+    test_factory.stop();
+}
@@ -6,7 +6,7 @@ use massa_versioning::versioning::MipStatsConfig;
 use massa_versioning::versioning::MipStore;
 use num::rational::Ratio;
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
 use std::thread::JoinHandle;
 
 use massa_factory_exports::{test_exports::create_empty_block, FactoryChannels, FactoryConfig};
@@ -38,18 +38,36 @@ impl BlockTestFactory {
     /// Returns
     /// - `TestFactory`: the structure that will be used to manage the tests
     pub fn new(
+        default_keypair: &KeyPair,
+        storage: Storage,
+        consensus_controller: Box<MockConsensusController>,
+        selector_controller: Box<MockSelectorController>,
+        pool_controller: Box<MockPoolController>,
+    ) -> BlockTestFactory {
+        BlockTestFactory::new_with_config(
+            default_keypair,
+            storage,
+            consensus_controller,
+            selector_controller,
+            pool_controller,
+            FactoryConfig::default(),
+        )
+    }
+
+    /// Same as `new`, with the given factory config
+    pub fn new_with_config(
         default_keypair: &KeyPair,
         mut storage: Storage,
         consensus_controller: Box<MockConsensusController>,
         selector_controller: Box<MockSelectorController>,
         pool_controller: Box<MockPoolController>,
+        mut factory_config: FactoryConfig,
     ) -> BlockTestFactory {
         let mut protocol_controller = Box::new(MockProtocolController::new());
         let block_protocol_controller = Box::new(MockProtocolController::new());
         protocol_controller
             .expect_clone_box()
             .return_once(move || block_protocol_controller);
-        let mut factory_config = FactoryConfig::default();
         factory_config.genesis_timestamp = factory_config
             .genesis_timestamp
             .checked_sub(factory_config.t0.checked_div_u64(2).unwrap())
@@ -80,6 +98,8 @@ impl BlockTestFactory {
         let join_handle = BlockFactoryWorker::spawn(
             factory_config.clone(),
             Arc::new(RwLock::new(wallet)),
+            KeyPair::generate(0).unwrap(),
+            Arc::new(AtomicBool::new(false)),
             FactoryChannels {
                 selector: selector_controller,
                 consensus: consensus_controller,
@@ -154,6 +174,7 @@ impl EndorsementTestFactory {
         let join_handle = EndorsementFactoryWorker::spawn(
             factory_config.clone(),
             Arc::new(RwLock::new(wallet)),
+            Arc::new(AtomicBool::new(false)),
             FactoryChannels {
                 selector: selector_controller,
                 consensus: consensus_controller,
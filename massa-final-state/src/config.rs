@@ -2,6 +2,7 @@
 
 //! This file defines a configuration structure containing all settings for final state management
 
+use crate::error::FinalStateError;
 use massa_async_pool::AsyncPoolConfig;
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
 use massa_ledger_exports::LedgerConfig;
@@ -46,3 +47,40 @@ pub struct FinalStateConfig {
     /// Interval of periods between creation of each ledger backup
     pub ledger_backup_periods_interval: u64,
 }
+
+impl FinalStateConfig {
+    /// Checks that the thread count is usable and shared by all the sub-configurations
+    pub fn check_thread_count(&self) -> Result<(), FinalStateError> {
+        if self.thread_count == 0 {
+            return Err(FinalStateError::InvalidConfig(
+                "thread count must be at least 1".to_string(),
+            ));
+        }
+        if self.t0.as_millis() % u64::from(self.thread_count) != 0 {
+            return Err(FinalStateError::InvalidConfig(format!(
+                "t0 ({}ms) must be a multiple of the thread count ({})",
+                self.t0.as_millis(),
+                self.thread_count
+            )));
+        }
+        let sub_configs = [
+            ("ledger", self.ledger_config.thread_count),
+            ("async pool", self.async_pool_config.thread_count),
+            ("PoS", self.pos_config.thread_count),
+            ("executed ops", self.executed_ops_config.thread_count),
+            (
+                "executed denunciations",
+                self.executed_denunciations_config.thread_count,
+            ),
+        ];
+        for (name, thread_count) in sub_configs {
+            if thread_count != self.thread_count {
+                return Err(FinalStateError::InvalidConfig(format!(
+                    "{} thread count ({}) differs from the final state thread count ({})",
+                    name, thread_count, self.thread_count
+                )));
+            }
+        }
+        Ok(())
+    }
+}
@@ -34,8 +34,14 @@ pub trait FinalStateController: Send + Sync {
     /// Once this is called, the state is attached at the output of the provided slot.
     ///
     /// Panics if the new slot is not the one coming just after the current one.
+    ///
+    /// If the changes cannot be written because the disk hard quota is exceeded, the final state
+    /// stops writing and reports the error through `get_write_error`.
     fn finalize(&mut self, slot: Slot, changes: StateChanges);
 
+    /// Gets the error that stopped the writes of the final state, if any: the node must then stop
+    fn get_write_error(&self) -> Option<String>;
+
     /// After bootstrap or load from disk, recompute all the caches.
     fn recompute_caches(&mut self);
 
@@ -13,6 +13,8 @@ use massa_versioning::versioning::{ExtendFromDbError, IsConsistentWithShutdownPe
 pub enum FinalStateError {
     /// invalid slot: {0}
     InvalidSlot(String),
+    /// invalid config: {0}
+    InvalidConfig(String),
     /// ledger error: {0}
     LedgerError(String),
     /// PoS error: {0}
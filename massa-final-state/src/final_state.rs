@@ -11,7 +11,7 @@ use crate::{config::FinalStateConfig, error::FinalStateError, state_changes::Sta
 use anyhow::{anyhow, Result as AnyResult};
 use massa_async_pool::AsyncPool;
 use massa_db_exports::{
-    DBBatch, MassaIteratorMode, ShareableMassaDBController, ASYNC_POOL_PREFIX,
+    DBBatch, MassaDBError, MassaIteratorMode, ShareableMassaDBController, ASYNC_POOL_PREFIX,
     CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX, EXECUTED_DENUNCIATIONS_PREFIX,
     EXECUTED_OPS_PREFIX, LEDGER_PREFIX, MIP_STORE_PREFIX, STATE_CF,
 };
@@ -26,7 +26,7 @@ use massa_models::slot::Slot;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_pos_exports::{PoSFinalState, SelectorController};
 use massa_versioning::versioning::MipStore;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 /// Represents a final state `(ledger, async pool, executed_ops, executed_de and the state of the PoS)`
 pub struct FinalState {
@@ -56,6 +56,8 @@ pub struct FinalState {
     pub last_slot_before_downtime: Option<Slot>,
     /// the RocksDB instance used to write every final_state struct on disk
     pub db: ShareableMassaDBController,
+    /// error that stopped the writes of the final state on disk, if any
+    pub write_error: Option<String>,
 }
 
 impl FinalState {
@@ -74,6 +76,8 @@ impl FinalState {
         mip_store: MipStore,
         reset_final_state: bool,
     ) -> Result<Self, FinalStateError> {
+        config.check_thread_count()?;
+
         let db_slot = db
             .read()
             .get_change_id()
@@ -117,6 +121,7 @@ impl FinalState {
             last_start_period: 0,
             last_slot_before_downtime: None,
             db,
+            write_error: None,
         };
 
         if reset_final_state {
@@ -490,7 +495,7 @@ impl FinalState {
 
         self.db
             .write()
-            .write_batch(db_batch, db_versioning_batch, Some(slot));
+            .try_write_batch(db_batch, db_versioning_batch, Some(slot))?;
 
         let final_state_hash = self.db.read().get_xof_db_hash();
 
@@ -795,7 +800,26 @@ impl FinalStateController for FinalState {
     }
 
     fn finalize(&mut self, slot: Slot, changes: StateChanges) {
-        self._finalize(slot, changes).unwrap()
+        if self.write_error.is_some() {
+            // the node is stopping, the state is no longer written
+            return;
+        }
+        if let Err(err) = self._finalize(slot, changes) {
+            match err.downcast_ref::<MassaDBError>() {
+                Some(MassaDBError::DiskQuotaExceeded(msg)) => {
+                    error!(
+                        "could not write the final state at slot {}: {}, the node is going to stop",
+                        slot, msg
+                    );
+                    self.write_error = Some(msg.clone());
+                }
+                _ => panic!("{}", err),
+            }
+        }
+    }
+
+    fn get_write_error(&self) -> Option<String> {
+        self.write_error.clone()
     }
 
     fn get_execution_trail_hash(&self) -> Hash {
@@ -922,7 +946,7 @@ impl FinalStateController for FinalState {
 #[cfg(test)]
 mod test {
     use std::collections::BTreeMap;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use std::str::FromStr;
     use std::sync::Arc;
 
@@ -965,6 +989,7 @@ mod test {
             initial_ledger_path: massa_node_base.join("base_config/initial_ledger.json"),
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            hot_cache_size: 0,
         };
         let async_pool_config = AsyncPoolConfig {
             max_length: MAX_ASYNC_POOL_LENGTH,
@@ -1018,18 +1043,23 @@ mod test {
     }
 
     fn get_final_state() -> FinalState {
-        let (final_state_config, ledger_config) = get_final_state_config();
-
         let temp_dir_db = tempdir().expect("Unable to create a temp folder");
         // println!("Using temp dir: {:?}", temp_dir.path());
+        get_final_state_in(temp_dir_db.path(), None)
+    }
+
+    fn get_final_state_in(path: &Path, disk_hard_quota: Option<u64>) -> FinalState {
+        let (final_state_config, ledger_config) = get_final_state_config();
 
         let db_config = MassaDBConfig {
-            path: temp_dir_db.path().to_path_buf(),
+            path: path.to_path_buf(),
             max_history_length: 100,
             max_final_state_elements_size: 100,
             max_versioning_elements_size: 100,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -1150,6 +1180,28 @@ mod test {
         assert_eq!(fstate.get_slot(), ok_next_slot);
     }
 
+    #[test]
+    fn test_final_state_finalize_above_disk_quota() {
+        // The writes refused because of the disk hard quota stop the final state without panicking
+
+        let temp_dir_db = tempdir().expect("Unable to create a temp folder");
+        let mut fstate = get_final_state_in(temp_dir_db.path(), Some(1));
+        let mut batch = DBBatch::new();
+        fstate.pos_state.create_initial_cycle(&mut batch);
+        assert_eq!(fstate.get_write_error(), None);
+
+        fstate.finalize(Slot::new(0, 1), get_state_changes());
+        assert!(fstate
+            .get_write_error()
+            .unwrap()
+            .contains("above the hard quota"));
+        assert_eq!(fstate.get_slot(), Slot::new(0, 0));
+
+        // the next slots are no longer written
+        fstate.finalize(Slot::new(0, 2), get_state_changes());
+        assert_eq!(fstate.get_slot(), Slot::new(0, 0));
+    }
+
     #[test]
     fn test_final_state_from_snapshot_1() {
         // 0- Create a final state
@@ -52,6 +52,7 @@ impl FinalState {
             last_start_period: 0,
             last_slot_before_downtime: None,
             db,
+            write_error: None,
         }
     }
 }
@@ -82,7 +83,7 @@ impl Default for FinalStateConfig {
                 initial_deferred_credits_path: None,
             },
             final_history_length: 10,
-            thread_count: 2,
+            thread_count: THREAD_COUNT,
             periods_per_cycle: 100,
             initial_rolls_path: PathBuf::new(),
             endorsement_count: ENDORSEMENT_COUNT,
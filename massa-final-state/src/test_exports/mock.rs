@@ -52,6 +52,7 @@ pub fn create_final_state(
         last_start_period: 0,
         last_slot_before_downtime: None,
         db,
+        write_error: None,
     }
 }
 
@@ -4,5 +4,7 @@
 
 mod config;
 mod mock;
+mod scripted;
 
 pub use mock::*;
+pub use scripted::ScriptedFinalState;
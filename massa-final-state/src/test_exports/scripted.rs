@@ -0,0 +1,83 @@
+//! Final state controller mock answering from in-memory state
+use std::sync::{Arc, RwLock};
+
+use massa_ledger_exports::{
+    test_exports::scripted::{apply_ledger_changes, script_ledger, ScriptedLedgerEntries},
+    LedgerController, MockLedgerController,
+};
+use massa_models::{operation::OperationId, prehash::PreHashMap, slot::Slot};
+
+use crate::controller_trait::MockFinalStateController;
+
+/// State read and written by a scripted `MockFinalStateController`.
+/// Clones share the same state, so that tests can seed it and check it after finalizations.
+#[derive(Clone)]
+pub struct ScriptedFinalState {
+    /// slot at the end of which the state is attached
+    pub slot: Arc<RwLock<Slot>>,
+    /// ledger entries
+    pub ledger: ScriptedLedgerEntries,
+    /// executed operations, with whether their execution succeeded
+    pub executed_ops: Arc<RwLock<PreHashMap<OperationId, bool>>>,
+    /// last start period
+    pub last_start_period: u64,
+}
+
+impl ScriptedFinalState {
+    /// Empty state attached at the end of `slot`
+    pub fn new(slot: Slot) -> Self {
+        ScriptedFinalState {
+            slot: Arc::new(RwLock::new(slot)),
+            ledger: Default::default(),
+            executed_ops: Default::default(),
+            last_start_period: 0,
+        }
+    }
+
+    /// Scripts `final_state` to answer from this state, and its finalizations to change it.
+    /// Its ledger is a `MockLedgerController` scripted on the same entries.
+    /// The async pool, the PoS state, the executed denunciations and the MIP store keep their own expectations.
+    pub fn script(&self, final_state: &mut MockFinalStateController) {
+        let slot = self.slot.clone();
+        final_state
+            .expect_get_slot()
+            .returning(move || *slot.read().unwrap());
+
+        let mut ledger = MockLedgerController::new();
+        script_ledger(&mut ledger, self.ledger.clone());
+        let ledger: Box<dyn LedgerController> = Box::new(ledger);
+        final_state.expect_get_ledger().return_const(ledger);
+
+        let executed_ops = self.executed_ops.clone();
+        final_state
+            .expect_executed_ops_contains()
+            .returning(move |op_id| executed_ops.read().unwrap().contains_key(op_id));
+        let executed_ops = self.executed_ops.clone();
+        final_state
+            .expect_get_ops_exec_status()
+            .returning(move |batch| {
+                let executed_ops = executed_ops.read().unwrap();
+                batch
+                    .iter()
+                    .map(|op_id| executed_ops.get(op_id).copied())
+                    .collect()
+            });
+        final_state
+            .expect_get_last_start_period()
+            .return_const(self.last_start_period);
+
+        let state = self.clone();
+        final_state
+            .expect_finalize()
+            .returning(move |slot, changes| {
+                apply_ledger_changes(&mut state.ledger.write().unwrap(), changes.ledger_changes);
+                state.executed_ops.write().unwrap().extend(
+                    changes
+                        .executed_ops_changes
+                        .into_iter()
+                        .map(|(op_id, (success, _))| (op_id, success)),
+                );
+                *state.slot.write().unwrap() = slot;
+            });
+    }
+}
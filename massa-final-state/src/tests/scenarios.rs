@@ -3,7 +3,7 @@
 use crate::controller_trait::FinalStateController;
 use crate::{
     /*test_exports::{assert_eq_final_state, assert_eq_final_state_hash},*/
-    FinalState, FinalStateConfig, StateChanges,
+    FinalState, FinalStateConfig, FinalStateError, StateChanges,
 };
 use massa_async_pool::{AsyncMessage, AsyncPoolChanges, AsyncPoolConfig};
 use massa_db_exports::{DBBatch, MassaDBConfig, MassaDBController};
@@ -25,37 +25,25 @@ use massa_models::config::{
 use massa_models::{config::MAX_DATASTORE_VALUE_LENGTH, slot::Slot};
 use massa_pos_exports::{PoSConfig, SelectorConfig};
 use massa_pos_worker::start_selector_worker;
+use massa_time::MassaTime;
 use parking_lot::RwLock;
 use std::collections::BTreeMap;
 use std::path::Path;
 use std::{path::PathBuf, str::FromStr, sync::Arc};
 use tempfile::TempDir;
 
-fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock<FinalState>> {
-    let thread_count = 2;
-
-    let db_config = MassaDBConfig {
-        path: temp_dir.path().to_path_buf(),
-        max_history_length: 10,
-        max_final_state_elements_size: 100_000,
-        max_versioning_elements_size: 100_000,
-        thread_count,
-        max_ledger_backups: 10,
-    };
-    let db = Arc::new(RwLock::new(
-        Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
-    ));
-
+/// Final state configuration of a network of `thread_count` threads
+fn final_state_config(thread_count: u8) -> FinalStateConfig {
     let rolls_path = PathBuf::from_str("../massa-node/base_config/initial_rolls.json").unwrap();
-
     let periods_per_cycle = 2;
 
-    let final_state_local_config = FinalStateConfig {
+    FinalStateConfig {
         ledger_config: LedgerConfig {
             thread_count,
             initial_ledger_path: "".into(),
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            hot_cache_size: 1_000_000,
         },
         async_pool_config: AsyncPoolConfig {
             thread_count,
@@ -94,7 +82,30 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
         ledger_backup_periods_interval: 10,
+    }
+}
+
+fn create_final_state(
+    temp_dir: &TempDir,
+    reset_final_state: bool,
+    thread_count: u8,
+) -> Arc<RwLock<FinalState>> {
+    let db_config = MassaDBConfig {
+        path: temp_dir.path().to_path_buf(),
+        max_history_length: 10,
+        max_final_state_elements_size: 100_000,
+        max_versioning_elements_size: 100_000,
+        thread_count,
+        max_ledger_backups: 10,
+        disk_soft_quota: None,
+        disk_hard_quota: None,
     };
+    let db = Arc::new(RwLock::new(
+        Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+    ));
+
+    let final_state_local_config = final_state_config(thread_count);
+    let periods_per_cycle = final_state_local_config.periods_per_cycle;
 
     // setup selector local config
     let selector_local_config = SelectorConfig {
@@ -153,12 +164,19 @@ fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()>
 
 #[test]
 fn test_final_state() {
+    for thread_count in [2, 4, 8] {
+        final_state_restart(thread_count);
+    }
+}
+
+/// Finalizes the slot following the genesis slots, and restarts from the saved state
+fn final_state_restart(thread_count: u8) {
     let temp_dir = TempDir::new().unwrap();
     let temp_dir2 = TempDir::new().unwrap();
 
     let hash;
     {
-        let fs = create_final_state(&temp_dir, true);
+        let fs = create_final_state(&temp_dir, true, thread_count);
 
         let mut batch = DBBatch::new();
         let versioning_batch = DBBatch::new();
@@ -219,8 +237,27 @@ fn test_final_state() {
 
     copy_dir_all(temp_dir.path(), temp_dir2.path()).unwrap();
 
-    let fs2 = create_final_state(&temp_dir2, false);
+    let fs2 = create_final_state(&temp_dir2, false, thread_count);
     let hash2 = fs2.read().db.read().get_xof_db_hash();
 
     assert_eq!(hash, hash2);
 }
+
+#[test]
+fn test_final_state_config_thread_count() {
+    let mut config = final_state_config(4);
+    config.check_thread_count().unwrap();
+
+    config.pos_config.thread_count = 32;
+    assert!(matches!(
+        config.check_thread_count(),
+        Err(FinalStateError::InvalidConfig(_))
+    ));
+
+    config.pos_config.thread_count = 4;
+    config.t0 = MassaTime::from_millis(1001);
+    assert!(matches!(
+        config.check_thread_count(),
+        Err(FinalStateError::InvalidConfig(_))
+    ));
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2023 MASSA LABS <info@massa.net>
 
 use massa_models::amount::Amount;
+use massa_models::config::ChainParametersSchedule;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serde::Deserialize;
@@ -66,8 +67,9 @@ pub struct GrpcConfig {
     pub max_frame_size: Option<u32>,
     /// thread count
     pub thread_count: u8,
-    /// max operations per block
-    pub max_operations_per_block: u32,
+    /// chain parameters versions, bounding the operations of a block at its slot
+    #[serde(skip)]
+    pub chain_parameters: ChainParametersSchedule,
     /// endorsement count
     pub endorsement_count: u32,
     /// max endorsements per message
@@ -88,8 +90,6 @@ pub struct GrpcConfig {
     pub max_parameter_size: u32,
     /// max operations per message in the network to avoid sending to big data packet
     pub max_operations_per_message: u32,
-    /// max gas per block
-    pub max_gas_per_block: u64,
     /// `genesis_timestamp`
     pub genesis_timestamp: MassaTime,
     /// t0
@@ -13,7 +13,7 @@ use massa_models::slot::Slot;
 use massa_models::timeslots::get_latest_block_slot_at_timestamp;
 use massa_proto_rs::massa::api::v1 as grpc_api;
 use massa_proto_rs::massa::model::v1 as grpc_model;
-use massa_protocol_exports::{PeerConnectionType, PeerId};
+use massa_protocol_exports::{BanEntry, BanTarget, IpSubnet, PeerConnectionType, PeerId};
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use tracing::warn;
@@ -73,15 +73,16 @@ pub(crate) fn add_to_bootstrap_whitelist(
 
     Ok(grpc_api::AddToBootstrapWhitelistResponse {})
 }
-/// Add IP addresses to node peers whitelist. No confirmation to expect.
-/// Note: If the ip was unknown it adds it to the known peers, otherwise it updates the peer type
+/// Add IP addresses to node peers whitelist, exempting them from the banned subnets. No confirmation to expect.
 pub(crate) fn add_to_peers_whitelist(
-    _grpc: &MassaPrivateGrpc,
-    _request: tonic::Request<grpc_api::AddToPeersWhitelistRequest>,
+    grpc: &MassaPrivateGrpc,
+    request: tonic::Request<grpc_api::AddToPeersWhitelistRequest>,
 ) -> Result<grpc_api::AddToPeersWhitelistResponse, GrpcError> {
-    Err(GrpcError::Unimplemented(
-        "add_to_peers_whitelist".to_string(),
-    ))
+    let ips = parse_ips(request.into_inner().ips);
+
+    grpc.protocol_controller.add_to_peers_whitelist(ips)?;
+
+    Ok(grpc_api::AddToPeersWhitelistResponse {})
 }
 /// Add staking secret keys to wallet
 pub(crate) fn add_staking_secret_keys(
@@ -148,10 +149,20 @@ pub(crate) fn ban_nodes_by_ids(
 
 /// Ban multiple nodes by their individual IP addresses
 pub(crate) fn ban_nodes_by_ips(
-    _grpc: &MassaPrivateGrpc,
-    _request: tonic::Request<grpc_api::BanNodesByIpsRequest>,
+    grpc: &MassaPrivateGrpc,
+    request: tonic::Request<grpc_api::BanNodesByIpsRequest>,
 ) -> Result<grpc_api::BanNodesByIpsResponse, GrpcError> {
-    Err(GrpcError::Unimplemented("ban_nodes_by_ips".to_string()))
+    let bans = parse_ips(request.into_inner().ips)
+        .into_iter()
+        .map(|ip| BanEntry {
+            target: BanTarget::Subnet(IpSubnet::from_address(ip)),
+            expires_at: None,
+        })
+        .collect();
+
+    grpc.protocol_controller.add_bans(bans)?;
+
+    Ok(grpc_api::BanNodesByIpsResponse {})
 }
 
 /// Get node bootstrap blacklist IP addresses
@@ -313,10 +324,17 @@ pub(crate) fn get_node_status(
 }
 /// Get node peers whitelist IP addresses
 pub(crate) fn get_peers_whitelist(
-    _grpc: &MassaPrivateGrpc,
+    grpc: &MassaPrivateGrpc,
     _request: tonic::Request<grpc_api::GetPeersWhitelistRequest>,
 ) -> Result<grpc_api::GetPeersWhitelistResponse, GrpcError> {
-    Err(GrpcError::Unimplemented("get_peers_whitelist".to_string()))
+    let ips = grpc
+        .protocol_controller
+        .get_peers_whitelist()?
+        .into_iter()
+        .map(|ip| ip.to_string())
+        .collect();
+
+    Ok(grpc_api::GetPeersWhitelistResponse { ips })
 }
 /// Remove from bootstrap blacklist given IP addresses
 pub(crate) fn remove_from_bootstrap_blacklist(
@@ -372,12 +390,14 @@ pub(crate) fn remove_from_bootstrap_whitelist(
 }
 /// Remove from peers whitelist given IP addresses
 pub(crate) fn remove_from_peers_whitelist(
-    _grpc: &MassaPrivateGrpc,
-    _request: tonic::Request<grpc_api::RemoveFromPeersWhitelistRequest>,
+    grpc: &MassaPrivateGrpc,
+    request: tonic::Request<grpc_api::RemoveFromPeersWhitelistRequest>,
 ) -> Result<grpc_api::RemoveFromPeersWhitelistResponse, GrpcError> {
-    Err(GrpcError::Unimplemented(
-        "remove_from_peers_whitelist".to_string(),
-    ))
+    let ips = parse_ips(request.into_inner().ips);
+
+    grpc.protocol_controller.remove_from_peers_whitelist(ips)?;
+
+    Ok(grpc_api::RemoveFromPeersWhitelistResponse {})
 }
 /// Remove addresses from staking
 pub(crate) fn remove_staking_addresses(
@@ -466,8 +486,28 @@ pub(crate) fn unban_nodes_by_ids(
 
 /// Unban multiple nodes by their individual IP addresses
 pub(crate) fn unban_nodes_by_ips(
-    _grpc: &MassaPrivateGrpc,
-    _request: tonic::Request<grpc_api::UnbanNodesByIpsRequest>,
+    grpc: &MassaPrivateGrpc,
+    request: tonic::Request<grpc_api::UnbanNodesByIpsRequest>,
 ) -> Result<grpc_api::UnbanNodesByIpsResponse, GrpcError> {
-    Err(GrpcError::Unimplemented("unban_nodes_by_ips".to_string()))
+    let targets = parse_ips(request.into_inner().ips)
+        .into_iter()
+        .map(|ip| BanTarget::Subnet(IpSubnet::from_address(ip)))
+        .collect();
+
+    grpc.protocol_controller.remove_bans(targets)?;
+
+    Ok(grpc_api::UnbanNodesByIpsResponse {})
+}
+
+/// Parses IP addresses, skipping the invalid ones
+fn parse_ips(ips: Vec<String>) -> Vec<IpAddr> {
+    ips.into_iter()
+        .filter_map(|ip| match IpAddr::from_str(&ip) {
+            Ok(ip_addr) => Some(ip_addr),
+            Err(e) => {
+                warn!("error when parsing address : {}", e);
+                None
+            }
+        })
+        .collect()
 }
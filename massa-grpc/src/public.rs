@@ -9,7 +9,8 @@ use massa_execution_exports::mapping_grpc::{
     to_event_filter, to_execution_query_response, to_querystate_filter,
 };
 use massa_execution_exports::{
-    ExecutionQueryRequest, ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionQueryResponseItem,
+    ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
 use massa_models::address::Address;
 use massa_models::amount::Amount;
@@ -248,14 +249,17 @@ pub(crate) fn get_datastore_entries(
         )));
     }
 
-    let filters: Vec<(Address, Vec<u8>)> = inner_req
+    let queries: Vec<ExecutionQueryRequestItem> = inner_req
         .filters
         .into_iter()
         .filter_map(|filter| {
             filter.filter.and_then(|filter| match filter {
                 grpc_api::get_datastore_entry_filter::Filter::AddressKey(addrs) => {
                     if let Ok(add) = &Address::from_str(&addrs.address) {
-                        Some((*add, addrs.key))
+                        Some(ExecutionQueryRequestItem::AddressDatastoreValue {
+                            addr: *add,
+                            key: addrs.key,
+                        })
                     } else {
                         None
                     }
@@ -266,13 +270,22 @@ pub(crate) fn get_datastore_entries(
 
     let entries = grpc
         .execution_controller
-        .get_final_and_active_data_entry(filters)
+        .query_state(ExecutionQueryRequest { requests: queries })
+        .responses
         .into_iter()
-        .map(|output| grpc_model::DatastoreEntry {
-            final_value: output.0.unwrap_or_default(),
-            candidate_value: output.1.unwrap_or_default(),
+        .map(|response| match response {
+            Ok(ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(values)) => {
+                Ok(grpc_model::DatastoreEntry {
+                    final_value: values.final_value.unwrap_or_default(),
+                    candidate_value: values.candidate_value.unwrap_or_default(),
+                })
+            }
+            Ok(_) => Err(GrpcError::InternalServerError(
+                "unexpected response type".to_string(),
+            )),
+            Err(err) => Err(GrpcError::InternalServerError(err.to_string())),
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
 
     Ok(grpc_api::GetDatastoreEntriesResponse {
         datastore_entries: entries,
@@ -59,7 +59,10 @@ pub(crate) async fn send_blocks(
                     // Create a block deserializer arguments
                     let args = BlockDeserializerArgs {
                         thread_count: config.thread_count,
-                        max_operations_per_block: config.max_operations_per_block,
+                        max_operations_per_block: config
+                            .chain_parameters
+                            .upper_bounds()
+                            .max_operations_per_block,
                         endorsement_count: config.endorsement_count,
                         max_denunciations_per_block_header: config
                             .max_denunciations_per_block_header,
@@ -109,6 +112,22 @@ pub(crate) async fn send_blocks(
 
                             let block_id = res_block.id;
                             let slot = res_block.content.header.content.slot;
+                            let max_operations = config
+                                .chain_parameters
+                                .parameters_at(slot)
+                                .max_operations_per_block;
+                            if res_block.content.operations.len() > max_operations as usize {
+                                report_error(
+                                    tx.clone(),
+                                    tonic::Code::InvalidArgument,
+                                    format!(
+                                        "the block holds more than the {} operations allowed at its slot",
+                                        max_operations
+                                    ),
+                                )
+                                .await;
+                                continue;
+                            }
                             let mut block_storage = storage.clone_without_refs();
 
                             // Add the received block to the graph
@@ -5,6 +5,7 @@ use crate::server::MassaPublicGrpc;
 use futures_util::StreamExt;
 use massa_models::operation::{OperationDeserializer, OperationType, SecureShareOperation};
 use massa_models::secure_share::SecureShareDeserializer;
+use massa_models::slot::Slot;
 use massa_models::timeslots::get_latest_block_slot_at_timestamp;
 use massa_proto_rs::massa::api::v1 as grpc_api;
 use massa_proto_rs::massa::model::v1 as grpc_model;
@@ -70,6 +71,11 @@ pub(crate) async fn send_operations(
                             .await;
                             continue;
                         };
+                        // block gas limit in force until the operations expire
+                        let max_gas_per_block = config
+                            .chain_parameters
+                            .parameters_at(last_slot.unwrap_or_else(|| Slot::new(0, 0)))
+                            .max_gas_per_block;
                         // If there are too many operations in the incoming message, send an error message back to the client
                         if req_content.operations.len() as u32 > config.max_operations_per_message {
                             report_error(
@@ -100,25 +106,30 @@ pub(crate) async fn send_operations(
                                             let (rest, res_operation): (&[u8], SecureShareOperation) = tuple;
                                             match res_operation.content.op {
                                                 OperationType::CallSC { max_gas, .. } | OperationType::ExecuteSC { max_gas, .. } => {
-                                                    if max_gas > config.max_gas_per_block {
+                                                    if max_gas > max_gas_per_block {
                                                         return Err(GrpcError::InvalidArgument("Gas limit of the operation is higher than the block gas limit. Your operation will never be included in a block.".into()));
                                                     }
                                                 },
                                                 _ => {}
                                             };
                                             if let Some(slot) = last_slot {
-                                                if res_operation.content.expire_period < slot.period {
+                                                if res_operation.is_expired_at(&slot, config.thread_count) {
                                                     return Err(GrpcError::InvalidArgument("Operation expire_period is lower than the current period of this node. Your operation will never be included in a block.".into()));
                                                 }
                                             }
 
 
+                                            if matches!(res_operation.content.start_period, Some(start_period) if start_period > res_operation.content.expire_period) {
+                                                return Err(GrpcError::InvalidArgument("Operation start_period is greater than its expire_period. Your operation will never be included in a block.".into()));
+                                            }
+
                                             if res_operation.content.fee.checked_sub(config.minimal_fees).is_none() {
                                                 return Err(GrpcError::InvalidArgument("Operation fee is lower than the minimal fee. Your operation will never be included in a block.".into()));
                                             }
 
                                             if rest.is_empty() {
                                                 res_operation.verify_signature()
+                                                    .and_then(|_| res_operation.verify_fee_payer(config.chain_id, config.thread_count))
                                                     .map(|_| (res_operation.id.to_string(), res_operation))
                                                     .map_err(|e| e.into())
                                             } else {
@@ -8,8 +8,8 @@ use massa_execution_exports::{ExecutionChannels, MockExecutionController};
 use massa_models::amount::Amount;
 use massa_models::{
     config::{
-        ENDORSEMENT_COUNT, MAX_DATASTORE_VALUE_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
-        MAX_ENDORSEMENTS_PER_MESSAGE, MAX_FUNCTION_NAME_LENGTH, MAX_OPERATIONS_PER_BLOCK,
+        ChainParametersSchedule, ENDORSEMENT_COUNT, MAX_DATASTORE_VALUE_LENGTH,
+        MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_ENDORSEMENTS_PER_MESSAGE, MAX_FUNCTION_NAME_LENGTH,
         MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
         MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
         MIP_STORE_STATS_BLOCK_CONSIDERED, PERIODS_PER_CYCLE, T0, THREAD_COUNT, VERSION,
@@ -63,7 +63,6 @@ pub(crate) fn grpc_public_service(addr: &SocketAddr) -> MassaPublicGrpc {
         send_compressed: None,
         max_decoding_message_size: 4194304,
         max_encoding_message_size: 4194304,
-        max_gas_per_block: u32::MAX as u64,
         concurrency_limit_per_connection: 5,
         timeout: Default::default(),
         initial_stream_window_size: None,
@@ -77,7 +76,7 @@ pub(crate) fn grpc_public_service(addr: &SocketAddr) -> MassaPublicGrpc {
         http2_adaptive_window: None,
         max_frame_size: None,
         thread_count: THREAD_COUNT,
-        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        chain_parameters: ChainParametersSchedule::default(),
         endorsement_count: ENDORSEMENT_COUNT,
         max_endorsements_per_message: MAX_ENDORSEMENTS_PER_MESSAGE,
         max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
@@ -2,7 +2,10 @@
 
 use crate::tests::mock::grpc_public_service;
 use massa_consensus_exports::MockConsensusController;
-use massa_execution_exports::{EventStore, MockExecutionController};
+use massa_execution_exports::{
+    EventStore, ExecutionQueryFinalAndCandidate, ExecutionQueryResponseItem,
+    MockExecutionController,
+};
 use massa_models::address::Address;
 use massa_models::block::BlockGraphStatus;
 use massa_models::block_id::BlockId;
@@ -296,8 +299,20 @@ async fn get_datastore_entries() {
 
     let mut exec_ctrl = Box::new(MockExecutionController::new());
     exec_ctrl
-        .expect_get_final_and_active_data_entry()
-        .returning(|_| vec![(Some("toto".as_bytes().to_vec()), None)]);
+        .expect_query_state()
+        .returning(|_| massa_execution_exports::ExecutionQueryResponse {
+            responses: vec![Ok(
+                ExecutionQueryResponseItem::DatastoreValueFinalAndCandidate(
+                    ExecutionQueryFinalAndCandidate {
+                        final_value: Some("toto".as_bytes().to_vec()),
+                        candidate_value: None,
+                    },
+                ),
+            )],
+            candidate_cursor: massa_models::slot::Slot::new(0, 2),
+            final_cursor: Slot::new(0, 0),
+            final_state_fingerprint: massa_hash::Hash::compute_from(&Vec::new()),
+        });
 
     public_server.execution_controller = exec_ctrl;
     let config = public_server.grpc_config.clone();
@@ -15,4 +15,6 @@ pub struct LedgerConfig {
     pub max_key_length: u8,
     /// max datastore value length
     pub max_datastore_value_length: u64,
+    /// max size in bytes of the most frequently read ledger sub-entries kept in memory (0 to disable)
+    pub hot_cache_size: usize,
 }
@@ -46,6 +46,10 @@ pub trait LedgerController: Send + Sync {
     /// A `BTreeSet` of the datastore keys
     fn get_datastore_keys(&self, addr: &Address, prefix: &[u8]) -> Option<BTreeSet<Vec<u8>>>;
 
+    /// Loads the balance and bytecode of the given addresses in memory ahead of their reads.
+    /// Entries read often stay in memory while the others are read from disk on demand.
+    fn prefetch_entries(&self, addrs: &[Address]);
+
     /// Reset the ledger
     ///
     /// USED FOR BOOTSTRAP ONLY
@@ -1,10 +1,8 @@
 use massa_db_exports::LEDGER_PREFIX;
-use massa_models::{
-    address::{Address, AddressDeserializer, AddressSerializer},
-    serialization::{VecU8Deserializer, VecU8Serializer},
-};
+use massa_models::address::{Address, AddressDeserializer, AddressSerializer};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+    VecU8Deserializer, VecU8Serializer,
 };
 use nom::error::{ContextError, ParseError};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
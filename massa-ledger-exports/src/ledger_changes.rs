@@ -12,9 +12,9 @@ use massa_models::address::{Address, AddressDeserializer, AddressSerializer};
 use massa_models::amount::{Amount, AmountDeserializer, AmountSerializer};
 use massa_models::bytecode::{Bytecode, BytecodeDeserializer, BytecodeSerializer};
 use massa_models::prehash::PreHashMap;
-use massa_models::serialization::{VecU8Deserializer, VecU8Serializer};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+    VecU8Deserializer, VecU8Serializer,
 };
 use nom::error::{context, ContextError, ParseError};
 use nom::multi::length_count;
@@ -529,6 +529,43 @@ impl LedgerChanges {
         self.0.get(addr)
     }
 
+    /// Approximate memory used by the changes, in bytes
+    pub fn memory_usage(&self) -> usize {
+        let changes: usize = self
+            .0
+            .values()
+            .map(|change| match change {
+                SetUpdateOrDelete::Set(entry) => {
+                    entry.bytecode.0.len()
+                        + entry
+                            .datastore
+                            .iter()
+                            .map(|(key, value)| key.len() + value.len())
+                            .sum::<usize>()
+                }
+                SetUpdateOrDelete::Update(update) => {
+                    let bytecode = match &update.bytecode {
+                        SetOrKeep::Set(bytecode) => bytecode.0.len(),
+                        SetOrKeep::Keep => 0,
+                    };
+                    bytecode
+                        + update
+                            .datastore
+                            .iter()
+                            .map(|(key, value)| match value {
+                                SetOrDelete::Set(value) => key.len() + value.len(),
+                                SetOrDelete::Delete => key.len(),
+                            })
+                            .sum::<usize>()
+                }
+                SetUpdateOrDelete::Delete => 0,
+            })
+            .sum();
+        self.0.capacity()
+            * std::mem::size_of::<(Address, SetUpdateOrDelete<LedgerEntry, LedgerEntryUpdate>)>()
+            + changes
+    }
+
     /// Retrieves all the bytcode updates contained in the current changes
     pub fn get_bytecode_updates(&self) -> Vec<Bytecode> {
         let mut v = Vec::new();
@@ -20,6 +20,7 @@ impl Default for LedgerConfig {
             thread_count: THREAD_COUNT,
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            hot_cache_size: 1_000_000,
         }
     }
 }
@@ -40,6 +41,7 @@ impl LedgerConfig {
                 max_key_length: MAX_DATASTORE_KEY_LENGTH,
                 thread_count: THREAD_COUNT,
                 max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+                hot_cache_size: 1_000_000,
             },
             initial_ledger,
         )
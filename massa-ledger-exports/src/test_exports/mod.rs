@@ -1,3 +1,4 @@
 //! Exports testing utilities
 
 pub mod config;
+pub mod scripted;
@@ -0,0 +1,156 @@
+//! Ledger controller mock answering from in-memory entries
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, RwLock},
+};
+
+use massa_models::address::Address;
+
+use crate::{Applicable, LedgerChanges, LedgerEntry, MockLedgerController, SetUpdateOrDelete};
+
+/// Ledger entries read and written by a scripted `MockLedgerController`.
+/// Tests keep a clone to seed the entries and to check them after the changes are applied.
+pub type ScriptedLedgerEntries = Arc<RwLock<BTreeMap<Address, LedgerEntry>>>;
+
+/// Applies `changes` to `entries` the way the final ledger does
+pub fn apply_ledger_changes(entries: &mut BTreeMap<Address, LedgerEntry>, changes: LedgerChanges) {
+    for (addr, change) in changes.0 {
+        match change {
+            SetUpdateOrDelete::Set(entry) => {
+                entries.insert(addr, entry);
+            }
+            SetUpdateOrDelete::Update(update) => {
+                entries.entry(addr).or_default().apply(update);
+            }
+            SetUpdateOrDelete::Delete => {
+                entries.remove(&addr);
+            }
+        }
+    }
+}
+
+/// Scripts the reads of `ledger` to answer from `entries`, and its changes to be applied to them.
+/// The loading, reset and validity checks keep their own expectations.
+pub fn script_ledger(ledger: &mut MockLedgerController, entries: ScriptedLedgerEntries) {
+    let balances = entries.clone();
+    ledger.expect_get_balance().returning(move |addr| {
+        balances
+            .read()
+            .unwrap()
+            .get(addr)
+            .map(|entry| entry.balance)
+    });
+    let bytecodes = entries.clone();
+    ledger.expect_get_bytecode().returning(move |addr| {
+        bytecodes
+            .read()
+            .unwrap()
+            .get(addr)
+            .map(|entry| entry.bytecode.clone())
+    });
+    let existing = entries.clone();
+    ledger
+        .expect_entry_exists()
+        .returning(move |addr| existing.read().unwrap().contains_key(addr));
+    let data_entries = entries.clone();
+    ledger.expect_get_data_entry().returning(move |addr, key| {
+        data_entries
+            .read()
+            .unwrap()
+            .get(addr)
+            .and_then(|entry| entry.datastore.get(key).cloned())
+    });
+    let datastore_keys = entries.clone();
+    ledger
+        .expect_get_datastore_keys()
+        .returning(move |addr, prefix| {
+            datastore_keys.read().unwrap().get(addr).map(|entry| {
+                entry
+                    .datastore
+                    .keys()
+                    .filter(|key| key.starts_with(prefix))
+                    .cloned()
+                    .collect::<BTreeSet<_>>()
+            })
+        });
+    ledger.expect_prefetch_entries().returning(|_| ());
+    let changed = entries.clone();
+    ledger
+        .expect_apply_changes_to_batch()
+        .returning(move |changes, _| apply_ledger_changes(&mut changed.write().unwrap(), changes));
+    let addresses = entries.clone();
+    ledger.expect_get_every_address().returning(move || {
+        addresses
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(addr, entry)| (*addr, entry.balance))
+            .collect()
+    });
+    ledger.expect_get_entire_datastore().returning(move |addr| {
+        entries
+            .read()
+            .unwrap()
+            .get(addr)
+            .map(|entry| entry.datastore.clone())
+            .unwrap_or_default()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use massa_db_exports::DBBatch;
+    use massa_models::{amount::Amount, bytecode::Bytecode};
+
+    use super::*;
+    use crate::{LedgerController, LedgerEntryUpdate, SetOrKeep};
+
+    #[test]
+    fn test_scripted_ledger() {
+        let addr =
+            Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+        let entries = ScriptedLedgerEntries::default();
+        entries.write().unwrap().insert(
+            addr,
+            LedgerEntry {
+                balance: Amount::from_str("10").unwrap(),
+                bytecode: Bytecode(vec![1]),
+                datastore: BTreeMap::from([(b"key".to_vec(), b"value".to_vec())]),
+            },
+        );
+        let mut ledger = MockLedgerController::new();
+        script_ledger(&mut ledger, entries.clone());
+
+        assert!(ledger.entry_exists(&addr));
+        assert_eq!(
+            ledger.get_balance(&addr),
+            Some(Amount::from_str("10").unwrap())
+        );
+        assert_eq!(
+            ledger.get_data_entry(&addr, b"key"),
+            Some(b"value".to_vec())
+        );
+        assert_eq!(
+            ledger.get_datastore_keys(&addr, b"other"),
+            Some(BTreeSet::new())
+        );
+
+        // the changes are applied to the shared entries
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            addr,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_str("3").unwrap()),
+                ..Default::default()
+            }),
+        );
+        ledger.apply_changes_to_batch(changes, &mut DBBatch::default());
+        assert_eq!(
+            entries.read().unwrap()[&addr].balance,
+            Amount::from_str("3").unwrap()
+        );
+        assert_eq!(ledger.get_bytecode(&addr), Some(Bytecode(vec![1])));
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! In-memory tier of the disk ledger, keeping the most frequently read sub-entries.
+//!
+//! Every read of a sub-entry is counted. When the cache is full, the entries read the least often
+//! (the oldest ones first among equals) are evicted, so that only the hot entries stay in memory
+//! while the cold ones are read from disk on demand.
+//! The counters are halved periodically so that entries that are no longer read eventually cool down.
+
+use massa_models::{address::Address, prehash::PreHashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Memory accounted for each cached sub-entry in addition to its key and value
+const ENTRY_OVERHEAD_BYTES: usize = 64;
+
+/// Minimal number of reads between two agings of the read counters
+const MIN_AGING_INTERVAL: u64 = 1024;
+
+/// Cached sub-entry
+struct HotEntry {
+    /// address owning the sub-entry
+    addr: Address,
+    /// value of the sub-entry, `None` if it does not exist in the ledger
+    value: Option<Vec<u8>>,
+    /// number of reads of the sub-entry since it was cached (halved at each aging)
+    hits: u32,
+    /// time of the last read of the sub-entry, in number of cache reads
+    last_read: u64,
+}
+
+impl HotEntry {
+    fn size(&self, key: &[u8]) -> usize {
+        // the key is stored both in the map and in the eviction order
+        2 * key.len() + self.value.as_ref().map_or(0, |v| v.len()) + ENTRY_OVERHEAD_BYTES
+    }
+}
+
+/// Cache of the most frequently read ledger sub-entries, indexed by serialized key
+pub(crate) struct HotCache {
+    /// maximal size of the cached sub-entries in bytes, 0 to disable the cache
+    max_bytes: usize,
+    /// current size of the cached sub-entries in bytes
+    bytes: usize,
+    /// cached sub-entries
+    entries: HashMap<Vec<u8>, HotEntry>,
+    /// keys of the cached sub-entries of each address
+    by_address: PreHashMap<Address, HashSet<Vec<u8>>>,
+    /// eviction order: least read first, then least recently read
+    eviction_order: BTreeSet<(u32, u64, Vec<u8>)>,
+    /// number of reads since the cache was created
+    clock: u64,
+    /// number of reads since the last aging
+    reads_since_aging: u64,
+}
+
+impl HotCache {
+    /// Creates a cache holding at most `max_bytes` of sub-entries
+    pub fn new(max_bytes: usize) -> Self {
+        HotCache {
+            max_bytes,
+            bytes: 0,
+            entries: HashMap::new(),
+            by_address: PreHashMap::default(),
+            eviction_order: BTreeSet::new(),
+            clock: 0,
+            reads_since_aging: 0,
+        }
+    }
+
+    /// Returns true if the cache is disabled
+    pub fn is_disabled(&self) -> bool {
+        self.max_bytes == 0
+    }
+
+    /// Reads a sub-entry, counting the read.
+    ///
+    /// # Returns
+    /// `None` if the sub-entry is not cached, otherwise the cached value (`Some(None)` if it does not exist in the ledger)
+    pub fn get(&mut self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        let entry = self.entries.get_mut(key)?;
+        self.clock += 1;
+        self.reads_since_aging += 1;
+        self.eviction_order
+            .remove(&(entry.hits, entry.last_read, key.to_vec()));
+        entry.hits = entry.hits.saturating_add(1);
+        entry.last_read = self.clock;
+        self.eviction_order
+            .insert((entry.hits, entry.last_read, key.to_vec()));
+        let value = entry.value.clone();
+        self.age_if_needed();
+        Some(value)
+    }
+
+    /// Returns true if the sub-entry is cached, without counting a read
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Caches a sub-entry read from disk.
+    /// `read` is false for the sub-entries loaded ahead of their reads (prefetching),
+    /// so that they are evicted first if they end up not being read.
+    pub fn insert(&mut self, addr: Address, key: Vec<u8>, value: Option<Vec<u8>>, read: bool) {
+        if self.is_disabled() {
+            return;
+        }
+        self.remove(&key);
+        if read {
+            self.clock += 1;
+            self.reads_since_aging += 1;
+        }
+        let entry = HotEntry {
+            addr,
+            value,
+            hits: read as u32,
+            last_read: self.clock,
+        };
+        let size = entry.size(&key);
+        if size > self.max_bytes {
+            return;
+        }
+        // evict the coldest entries to make room
+        while self.bytes + size > self.max_bytes {
+            let Some((_, _, coldest_key)) = self.eviction_order.first().cloned() else {
+                break;
+            };
+            self.remove(&coldest_key);
+        }
+        self.bytes += size;
+        self.eviction_order
+            .insert((entry.hits, entry.last_read, key.clone()));
+        self.by_address.entry(addr).or_default().insert(key.clone());
+        self.entries.insert(key, entry);
+        self.age_if_needed();
+    }
+
+    /// Removes all the cached sub-entries of an address
+    pub fn invalidate_address(&mut self, addr: &Address) {
+        let Some(keys) = self.by_address.remove(addr) else {
+            return;
+        };
+        for key in keys {
+            if let Some(entry) = self.entries.remove(&key) {
+                self.bytes -= entry.size(&key);
+                self.eviction_order
+                    .remove(&(entry.hits, entry.last_read, key));
+            }
+        }
+    }
+
+    /// Removes all the cached sub-entries
+    pub fn clear(&mut self) {
+        self.bytes = 0;
+        self.entries.clear();
+        self.by_address.clear();
+        self.eviction_order.clear();
+        self.reads_since_aging = 0;
+    }
+
+    /// Removes a cached sub-entry
+    fn remove(&mut self, key: &[u8]) {
+        let Some(entry) = self.entries.remove(key) else {
+            return;
+        };
+        self.bytes -= entry.size(key);
+        self.eviction_order
+            .remove(&(entry.hits, entry.last_read, key.to_vec()));
+        if let Some(keys) = self.by_address.get_mut(&entry.addr) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.by_address.remove(&entry.addr);
+            }
+        }
+    }
+
+    /// Halves the read counters once enough reads happened since the last aging
+    fn age_if_needed(&mut self) {
+        let interval = (self.entries.len() as u64)
+            .saturating_mul(4)
+            .max(MIN_AGING_INTERVAL);
+        if self.reads_since_aging < interval {
+            return;
+        }
+        self.reads_since_aging = 0;
+        for entry in self.entries.values_mut() {
+            entry.hits /= 2;
+        }
+        self.eviction_order = self
+            .entries
+            .iter()
+            .map(|(key, entry)| (entry.hits, entry.last_read, key.clone()))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HotCache;
+    use massa_models::address::Address;
+    use massa_signature::KeyPair;
+
+    fn addr() -> Address {
+        Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_hot_cache_keeps_frequently_read_entries() {
+        // room for two entries of 100 bytes
+        let mut cache = HotCache::new(2 * (2 + 100 + 64));
+        let addr_0 = addr();
+        cache.insert(addr_0, vec![0], Some(vec![0; 100]), true);
+        cache.insert(addr(), vec![1], Some(vec![1; 100]), true);
+        // the first entry is read again: the second one is now the coldest
+        assert_eq!(cache.get(&[0]), Some(Some(vec![0; 100])));
+        cache.insert(addr(), vec![2], Some(vec![2; 100]), true);
+        assert!(cache.contains(&[0]));
+        assert!(!cache.contains(&[1]));
+        assert!(cache.contains(&[2]));
+
+        // a prefetched entry is evicted before the entries that were read
+        cache.insert(addr(), vec![1], None, false);
+        assert!(!cache.contains(&[2]));
+        cache.insert(addr(), vec![2], Some(vec![2; 100]), true);
+        assert!(!cache.contains(&[1]));
+        assert!(cache.contains(&[0]));
+
+        // the changes of an address drop its entries
+        cache.invalidate_address(&addr_0);
+        assert_eq!(cache.get(&[0]), None);
+        assert_eq!(cache.bytes, 2 + 100 + 64);
+        cache.clear();
+        assert_eq!(cache.bytes, 0);
+    }
+
+    #[test]
+    fn test_hot_cache_disabled() {
+        let mut cache = HotCache::new(0);
+        cache.insert(addr(), vec![0], Some(vec![0]), true);
+        assert_eq!(cache.get(&[0]), None);
+    }
+}
@@ -37,6 +37,7 @@ impl FinalLedger {
             config.thread_count,
             config.max_key_length,
             config.max_datastore_value_length,
+            config.hot_cache_size,
         );
 
         // generate the final ledger
@@ -111,6 +112,11 @@ impl LedgerController for FinalLedger {
             })
     }
 
+    /// Loads the balance and bytecode of the given addresses in memory ahead of their reads
+    fn prefetch_entries(&self, addrs: &[Address]) {
+        self.sorted_ledger.prefetch_entries(addrs);
+    }
+
     /// Checks if a ledger entry exists
     ///
     /// # Returns
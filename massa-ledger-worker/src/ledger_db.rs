@@ -16,9 +16,12 @@ use massa_models::{
 use massa_serialization::{
     DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
+use parking_lot::Mutex;
 use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
 
+use crate::hot_cache::HotCache;
+
 use massa_models::amount::Amount;
 use std::ops::Bound;
 
@@ -61,6 +64,8 @@ pub struct LedgerDB {
     bytecode_deserializer: BytecodeDeserializer,
     max_datastore_value_length: u64,
     max_datastore_key_length: u8,
+    /// most frequently read sub-entries, kept in memory
+    hot_cache: Mutex<HotCache>,
 }
 
 impl Debug for LedgerDB {
@@ -75,11 +80,13 @@ impl LedgerDB {
     ///
     /// # Arguments
     /// * path: path to the desired disk ledger db directory
+    /// * `hot_cache_size`: max size in bytes of the most frequently read sub-entries kept in memory (0 to disable)
     pub fn new(
         db: ShareableMassaDBController,
         thread_count: u8,
         max_datastore_key_length: u8,
         max_datastore_value_length: u64,
+        hot_cache_size: usize,
     ) -> Self {
         LedgerDB {
             db,
@@ -100,6 +107,7 @@ impl LedgerDB {
             ),
             max_datastore_value_length,
             max_datastore_key_length,
+            hot_cache: Mutex::new(HotCache::new(hot_cache_size)),
         }
     }
 
@@ -118,6 +126,7 @@ impl LedgerDB {
             Default::default(),
             Some(Slot::new(0, self.thread_count.saturating_sub(1))),
         );
+        self.hot_cache.get_mut().clear();
     }
 
     /// Allows applying `LedgerChanges` to the disk ledger
@@ -158,13 +167,62 @@ impl LedgerDB {
     /// # Returns
     /// An Option of the sub-entry value as bytes
     pub fn get_sub_entry(&self, addr: &Address, ty: LedgerSubEntry) -> Option<Vec<u8>> {
-        let db = self.db.read();
         let key = ty.derive_key(addr);
         let mut serialized_key = Vec::new();
         self.key_serializer_db
             .serialize(&key, &mut serialized_key)
             .expect(KEY_SER_ERROR);
-        db.get_cf(STATE_CF, serialized_key).expect(CRUD_ERROR)
+        if let Some(value) = self.hot_cache.lock().get(&serialized_key) {
+            return value;
+        }
+        let value = self
+            .db
+            .read()
+            .get_cf(STATE_CF, serialized_key.clone())
+            .expect(CRUD_ERROR);
+        self.hot_cache
+            .lock()
+            .insert(*addr, serialized_key, value.clone(), true);
+        value
+    }
+
+    /// Loads the version, balance and bytecode of the given addresses in memory ahead of their reads,
+    /// in a single disk access.
+    pub fn prefetch_entries(&self, addrs: &[Address]) {
+        let mut keys = Vec::new();
+        {
+            let hot_cache = self.hot_cache.lock();
+            if hot_cache.is_disabled() {
+                return;
+            }
+            for addr in addrs {
+                for ty in [
+                    LedgerSubEntry::Version,
+                    LedgerSubEntry::Balance,
+                    LedgerSubEntry::Bytecode,
+                ] {
+                    let mut serialized_key = Vec::new();
+                    self.key_serializer_db
+                        .serialize(&ty.derive_key(addr), &mut serialized_key)
+                        .expect(KEY_SER_ERROR);
+                    if !hot_cache.contains(&serialized_key) {
+                        keys.push((*addr, serialized_key));
+                    }
+                }
+            }
+        }
+        if keys.is_empty() {
+            return;
+        }
+        let values = self.db.read().multi_get_cf(
+            keys.iter()
+                .map(|(_, serialized_key)| (STATE_CF, serialized_key.clone()))
+                .collect(),
+        );
+        let mut hot_cache = self.hot_cache.lock();
+        for ((addr, serialized_key), value) in keys.into_iter().zip(values) {
+            hot_cache.insert(addr, serialized_key, value.expect(CRUD_ERROR), false);
+        }
     }
 
     /// Get every key of the datastore for a given address.
@@ -212,6 +270,7 @@ impl LedgerDB {
 
     pub fn reset(&self) {
         self.db.write().delete_prefix(LEDGER_PREFIX, STATE_CF, None);
+        self.hot_cache.lock().clear();
     }
 
     /// Deserializes the key and value, useful after bootstrap
@@ -284,6 +343,8 @@ impl LedgerDB {
     /// * `ledger_entry`: complete entry to be added
     /// * `batch`: the given operation batch to update
     fn put_entry(&self, addr: &Address, ledger_entry: LedgerEntry, batch: &mut DBBatch) {
+        // the cached sub-entries of the address are read again from disk once the batch is written
+        self.hot_cache.lock().invalidate_address(addr);
         let db = self.db.read();
 
         // Version
@@ -360,6 +421,8 @@ impl LedgerDB {
     /// * `entry_update`: a descriptor of the entry updates to be applied
     /// * `batch`: the given operation batch to update
     fn update_entry(&self, addr: &Address, entry_update: LedgerEntryUpdate, batch: &mut DBBatch) {
+        // drop the cached sub-entries of the updated entry
+        self.hot_cache.lock().invalidate_address(addr);
         let db = self.db.read();
 
         // balance
@@ -433,6 +496,8 @@ impl LedgerDB {
     /// # Arguments
     /// * batch: the given operation batch to update
     fn delete_entry(&self, addr: &Address, batch: &mut DBBatch) {
+        // drop the cached sub-entries of the deleted entry
+        self.hot_cache.lock().invalidate_address(addr);
         let db = self.db.read();
 
         // version
@@ -560,7 +625,9 @@ mod tests {
     use massa_db_exports::{MassaDBConfig, MassaDBController, STATE_HASH_INITIAL_BYTES};
     use massa_db_worker::MassaDB;
     use massa_hash::HashXof;
-    use massa_ledger_exports::{LedgerEntry, LedgerEntryUpdate, SetOrKeep};
+    use massa_ledger_exports::{
+        LedgerChanges, LedgerEntry, LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete,
+    };
     use massa_models::{
         address::Address,
         amount::{Amount, AmountDeserializer},
@@ -601,6 +668,8 @@ mod tests {
             max_final_state_elements_size: 100_000,
             max_versioning_elements_size: 100_000,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
             thread_count: 32,
         };
 
@@ -608,7 +677,7 @@ mod tests {
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
         ));
 
-        let ledger_db = LedgerDB::new(db.clone(), 32, 255, 1000);
+        let ledger_db = LedgerDB::new(db.clone(), 32, 255, 1000, 1_000_000);
         let mut batch = DBBatch::new();
 
         ledger_db.put_entry(&addr, entry, &mut batch);
@@ -675,6 +744,102 @@ mod tests {
         assert!(ledger_db.get_entire_datastore(&addr).is_empty());
     }
 
+    /// A deleted entry must be recreated from scratch, without its former datastore
+    #[test]
+    fn test_recreate_after_delete() {
+        let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let (ledger_db, _) = init_test_ledger(addr);
+
+        // delete the entry and give it coins back within the same changes
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(addr, SetUpdateOrDelete::Delete);
+        changes.set_balance(addr, Amount::from_str("7").unwrap());
+        assert_eq!(
+            changes.0.get(&addr),
+            Some(&SetUpdateOrDelete::Set(LedgerEntry {
+                balance: Amount::from_str("7").unwrap(),
+                ..Default::default()
+            }))
+        );
+
+        // delete the entry in a first batch
+        let mut batch = DBBatch::new();
+        let mut deletion = LedgerChanges::default();
+        deletion.0.insert(addr, SetUpdateOrDelete::Delete);
+        ledger_db.apply_changes_to_batch(deletion, &mut batch);
+        ledger_db
+            .db
+            .write()
+            .write_batch(batch, Default::default(), None);
+        assert!(ledger_db
+            .get_sub_entry(&addr, LedgerSubEntry::Balance)
+            .is_none());
+
+        // recreate it in a second batch
+        let mut batch = DBBatch::new();
+        ledger_db.apply_changes_to_batch(changes, &mut batch);
+        ledger_db
+            .db
+            .write()
+            .write_batch(batch, Default::default(), None);
+
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        assert_eq!(
+            amount_deserializer
+                .deserialize::<DeserializeError>(
+                    &ledger_db
+                        .get_sub_entry(&addr, LedgerSubEntry::Balance)
+                        .unwrap()
+                )
+                .unwrap()
+                .1,
+            Amount::from_str("7").unwrap()
+        );
+        assert!(ledger_db.get_entire_datastore(&addr).is_empty());
+    }
+
+    /// Prefetched and cached sub-entries must follow the changes written to disk
+    #[test]
+    fn test_hot_cache_follows_changes() {
+        let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let unknown_addr =
+            Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let (ledger_db, _) = init_test_ledger(addr);
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        let get_balance = |addr: &Address| {
+            ledger_db
+                .get_sub_entry(addr, LedgerSubEntry::Balance)
+                .map(|bytes| {
+                    amount_deserializer
+                        .deserialize::<DeserializeError>(&bytes)
+                        .unwrap()
+                        .1
+                })
+        };
+
+        ledger_db.prefetch_entries(&[addr, unknown_addr]);
+        assert_eq!(get_balance(&addr), Some(Amount::from_str("21").unwrap()));
+        assert_eq!(get_balance(&unknown_addr), None);
+
+        let mut changes = LedgerChanges::default();
+        changes.set_balance(addr, Amount::from_str("5").unwrap());
+        changes.set_balance(unknown_addr, Amount::from_str("3").unwrap());
+        let mut batch = DBBatch::new();
+        ledger_db.apply_changes_to_batch(changes, &mut batch);
+        ledger_db
+            .db
+            .write()
+            .write_batch(batch, Default::default(), None);
+
+        assert_eq!(get_balance(&addr), Some(Amount::from_str("5").unwrap()));
+        assert_eq!(
+            get_balance(&unknown_addr),
+            Some(Amount::from_str("3").unwrap())
+        );
+    }
+
     #[test]
     fn test_end_prefix() {
         assert_eq!(end_prefix(&[5, 6, 7]), Some(vec![5, 6, 8]));
@@ -27,6 +27,9 @@
 //! Represents a list of changes to ledger entries that
 //! can be modified, combined or applied to the final ledger.
 //!
+//! ## `hot_cache.rs`
+//! Keeps the most frequently read ledger sub-entries in memory, the other ones being read from disk on demand.
+//!
 //! ## `bootstrap.rs`
 //! Provides serializable structures and tools for bootstrapping the final ledger.  
 //!
@@ -38,6 +41,7 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 
+mod hot_cache;
 mod ledger;
 mod ledger_db;
 
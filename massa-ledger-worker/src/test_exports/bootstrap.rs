@@ -20,6 +20,7 @@ pub fn create_final_ledger(
         config.thread_count,
         config.max_key_length,
         config.max_datastore_value_length,
+        config.hot_cache_size,
     );
     ledger_db.load_initial_ledger(initial_ledger);
     FinalLedger {
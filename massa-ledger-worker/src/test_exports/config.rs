@@ -22,6 +22,8 @@ impl Default for FinalLedger {
             max_versioning_elements_size: 100_000,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let db = MassaDB::new(db_config);
         let db = LedgerDB::new(
@@ -29,6 +31,7 @@ impl Default for FinalLedger {
             THREAD_COUNT,
             MAX_DATASTORE_KEY_LENGTH,
             MAX_DATASTORE_VALUE_LENGTH,
+            1_000_000,
         );
         FinalLedger {
             config: Default::default(),
@@ -5,7 +5,7 @@
 //!
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     net::SocketAddr,
     sync::{Arc, RwLock},
     thread::JoinHandle,
@@ -13,7 +13,10 @@ use std::{
 };
 
 use lazy_static::lazy_static;
-use prometheus::{register_int_gauge, Gauge, Histogram, IntCounter, IntGauge};
+use prometheus::{
+    core::Collector, register_int_gauge, Gauge, Histogram, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec, Opts,
+};
 use tokio::sync::oneshot::Sender;
 use tracing::warn;
 
@@ -120,6 +123,26 @@ pub struct MassaMetrics {
     protocol_known_peers: IntGauge,
     /// banned peers in protocol
     protocol_banned_peers: IntGauge,
+    /// connected peers by node version
+    protocol_peers_versions: IntGaugeVec,
+    /// processing time of the received messages by message type
+    protocol_message_processing_time: HistogramVec,
+    /// items (blocks, operations, endorsements) received by protocol, by item type
+    protocol_items_received: IntCounterVec,
+    /// items (blocks, operations, endorsements) sent by protocol, by item type
+    protocol_items_sent: IntCounterVec,
+    /// invalid items received from each connected peer, by item type
+    protocol_invalid_items: IntCounterVec,
+    /// blocks rejected by protocol before reaching consensus, by reason
+    protocol_invalid_blocks: IntCounterVec,
+    /// block asks that timed out and were retried
+    protocol_block_ask_retries: IntCounter,
+    /// blocks in the block retrieval wishlist
+    protocol_block_wishlist: IntGauge,
+    /// block asks waiting for an answer
+    protocol_asked_blocks: IntGauge,
+    /// batches of operations dropped because too many were waiting for block messages
+    protocol_dropped_operation_batches: IntCounter,
 
     /// executed final slot
     executed_final_slot: IntCounter,
@@ -150,6 +173,8 @@ pub struct MassaMetrics {
     operation_cache_checked_operations: IntGauge,
     operation_cache_checked_operations_prefix: IntGauge,
     operation_cache_ops_know_by_peer: IntGauge,
+    operation_cache_ops_know_by_peer_hits: IntCounter,
+    operation_cache_ops_know_by_peer_misses: IntCounter,
 
     // Consensus state
     consensus_state_active_index: IntGauge,
@@ -169,6 +194,16 @@ pub struct MassaMetrics {
     final_cursor_thread: IntGauge,
     final_cursor_period: IntGauge,
 
+    // tokio runtime, only collected when the node is built with `--cfg tokio_unstable`
+    tokio_workers: IntGauge,
+    tokio_alive_tasks: IntGauge,
+    tokio_global_queue_depth: IntGauge,
+    tokio_blocking_queue_depth: IntGauge,
+    tokio_mean_poll_time: Gauge,
+
+    /// approximate memory used by the big in-memory structures, by subsystem
+    memory_usage: IntGaugeVec,
+
     // peer bandwidth (bytes sent, bytes received)
     peers_bandwidth: Arc<RwLock<HashMap<String, (IntCounter, IntCounter)>>>,
 
@@ -291,6 +326,76 @@ impl MassaMetrics {
             "number of banned peers in protocol",
         )
         .unwrap();
+        let peers_versions = IntGaugeVec::new(
+            Opts::new(
+                "protocol_peers_versions",
+                "number of connected peers by node version",
+            ),
+            &["version"],
+        )
+        .unwrap();
+
+        let message_processing_time = HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "protocol_message_processing_time",
+                "processing time in seconds of the messages received by protocol",
+            )
+            .buckets(vec![
+                0.0001, 0.0005, 0.001, 0.005, 0.010, 0.050, 0.100, 0.500, 1.0, 5.0,
+            ]),
+            &["message_type"],
+        )
+        .unwrap();
+
+        let protocol_items_received = IntCounterVec::new(
+            Opts::new(
+                "protocol_items_received",
+                "number of items received by protocol",
+            ),
+            &["item_type"],
+        )
+        .unwrap();
+        let protocol_items_sent = IntCounterVec::new(
+            Opts::new("protocol_items_sent", "number of items sent by protocol"),
+            &["item_type"],
+        )
+        .unwrap();
+        let protocol_invalid_items = IntCounterVec::new(
+            Opts::new(
+                "protocol_invalid_items",
+                "number of invalid items received from each connected peer",
+            ),
+            &["peer_id", "item_type"],
+        )
+        .unwrap();
+        let protocol_invalid_blocks = IntCounterVec::new(
+            Opts::new(
+                "protocol_invalid_blocks",
+                "number of blocks rejected by protocol before reaching consensus, by reason",
+            ),
+            &["reason"],
+        )
+        .unwrap();
+        let protocol_block_ask_retries = IntCounter::new(
+            "protocol_block_ask_retries",
+            "number of block asks that timed out and were retried",
+        )
+        .unwrap();
+        let protocol_dropped_operation_batches = IntCounter::new(
+            "protocol_dropped_operation_batches",
+            "number of batches of operations dropped because too many were waiting for block messages",
+        )
+        .unwrap();
+        let protocol_block_wishlist = IntGauge::new(
+            "protocol_block_wishlist",
+            "number of blocks in the block retrieval wishlist",
+        )
+        .unwrap();
+        let protocol_asked_blocks = IntGauge::new(
+            "protocol_asked_blocks",
+            "number of block asks waiting for an answer",
+        )
+        .unwrap();
 
         // active cursor
         let active_cursor_thread =
@@ -344,6 +449,18 @@ impl MassaMetrics {
         )
         .unwrap();
 
+        let operation_cache_ops_know_by_peer_hits = IntCounter::new(
+            "operation_cache_ops_know_by_peer_hits",
+            "number of operations found known by a peer before announcing them",
+        )
+        .unwrap();
+
+        let operation_cache_ops_know_by_peer_misses = IntCounter::new(
+            "operation_cache_ops_know_by_peer_misses",
+            "number of operations not found known by a peer before announcing them",
+        )
+        .unwrap();
+
         // consensus state from tick.rs
         let consensus_state_active_index = IntGauge::new(
             "consensus_state_active_index",
@@ -406,6 +523,42 @@ impl MassaMetrics {
         )
         .unwrap();
 
+        let memory_usage = IntGaugeVec::new(
+            Opts::new(
+                "memory_usage_bytes",
+                "approximate memory in bytes used by the big in-memory structures, by subsystem",
+            ),
+            &["subsystem"],
+        )
+        .unwrap();
+
+        // tokio runtime
+        let tokio_workers = IntGauge::new(
+            "tokio_workers",
+            "number of worker threads of the tokio runtime",
+        )
+        .unwrap();
+        let tokio_alive_tasks = IntGauge::new(
+            "tokio_alive_tasks",
+            "number of alive tasks in the tokio runtime",
+        )
+        .unwrap();
+        let tokio_global_queue_depth = IntGauge::new(
+            "tokio_global_queue_depth",
+            "number of tasks waiting in the global queue of the tokio runtime",
+        )
+        .unwrap();
+        let tokio_blocking_queue_depth = IntGauge::new(
+            "tokio_blocking_queue_depth",
+            "number of tasks waiting in the blocking pool of the tokio runtime",
+        )
+        .unwrap();
+        let tokio_mean_poll_time = Gauge::new(
+            "tokio_mean_poll_time",
+            "mean duration in seconds of a task poll, averaged over the workers of the tokio runtime",
+        )
+        .unwrap();
+
         let mut stopper = MetricsStopper::default();
 
         if enabled {
@@ -421,6 +574,10 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(operation_cache_checked_operations.clone()));
                 let _ = prometheus::register(Box::new(active_in_connections.clone()));
                 let _ = prometheus::register(Box::new(operation_cache_ops_know_by_peer.clone()));
+                let _ =
+                    prometheus::register(Box::new(operation_cache_ops_know_by_peer_hits.clone()));
+                let _ =
+                    prometheus::register(Box::new(operation_cache_ops_know_by_peer_misses.clone()));
                 let _ = prometheus::register(Box::new(consensus_state_active_index.clone()));
                 let _ = prometheus::register(Box::new(
                     consensus_state_active_index_without_ops.clone(),
@@ -441,6 +598,16 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(rolls.clone()));
                 let _ = prometheus::register(Box::new(know_peers.clone()));
                 let _ = prometheus::register(Box::new(banned_peers.clone()));
+                let _ = prometheus::register(Box::new(peers_versions.clone()));
+                let _ = prometheus::register(Box::new(message_processing_time.clone()));
+                let _ = prometheus::register(Box::new(protocol_items_received.clone()));
+                let _ = prometheus::register(Box::new(protocol_items_sent.clone()));
+                let _ = prometheus::register(Box::new(protocol_invalid_items.clone()));
+                let _ = prometheus::register(Box::new(protocol_invalid_blocks.clone()));
+                let _ = prometheus::register(Box::new(protocol_block_ask_retries.clone()));
+                let _ = prometheus::register(Box::new(protocol_block_wishlist.clone()));
+                let _ = prometheus::register(Box::new(protocol_asked_blocks.clone()));
+                let _ = prometheus::register(Box::new(protocol_dropped_operation_batches.clone()));
                 let _ = prometheus::register(Box::new(executed_final_slot.clone()));
                 let _ = prometheus::register(Box::new(executed_final_slot_with_block.clone()));
                 let _ = prometheus::register(Box::new(active_history.clone()));
@@ -458,6 +625,12 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(current_time_period.clone()));
                 let _ = prometheus::register(Box::new(current_time_thread.clone()));
                 let _ = prometheus::register(Box::new(block_slot_delay.clone()));
+                let _ = prometheus::register(Box::new(tokio_workers.clone()));
+                let _ = prometheus::register(Box::new(tokio_alive_tasks.clone()));
+                let _ = prometheus::register(Box::new(tokio_global_queue_depth.clone()));
+                let _ = prometheus::register(Box::new(tokio_blocking_queue_depth.clone()));
+                let _ = prometheus::register(Box::new(tokio_mean_poll_time.clone()));
+                let _ = prometheus::register(Box::new(memory_usage.clone()));
 
                 stopper = server::bind_metrics(addr);
             }
@@ -485,6 +658,16 @@ impl MassaMetrics {
                 protocol_tester_failed,
                 protocol_known_peers: know_peers,
                 protocol_banned_peers: banned_peers,
+                protocol_peers_versions: peers_versions,
+                protocol_message_processing_time: message_processing_time,
+                protocol_items_received,
+                protocol_items_sent,
+                protocol_invalid_items,
+                protocol_invalid_blocks,
+                protocol_block_ask_retries,
+                protocol_block_wishlist,
+                protocol_asked_blocks,
+                protocol_dropped_operation_batches,
                 executed_final_slot,
                 executed_final_slot_with_block,
                 peernet_total_bytes_received,
@@ -498,6 +681,8 @@ impl MassaMetrics {
                 operation_cache_checked_operations,
                 operation_cache_checked_operations_prefix,
                 operation_cache_ops_know_by_peer,
+                operation_cache_ops_know_by_peer_hits,
+                operation_cache_ops_know_by_peer_misses,
                 consensus_state_active_index,
                 consensus_state_active_index_without_ops,
                 consensus_state_incoming_index,
@@ -512,6 +697,12 @@ impl MassaMetrics {
                 active_cursor_period,
                 final_cursor_thread,
                 final_cursor_period,
+                tokio_workers,
+                tokio_alive_tasks,
+                tokio_global_queue_depth,
+                tokio_blocking_queue_depth,
+                tokio_mean_poll_time,
+                memory_usage,
                 peers_bandwidth: Arc::new(RwLock::new(HashMap::new())),
                 tick_delay,
             },
@@ -584,6 +775,8 @@ impl MassaMetrics {
         checked_operations: usize,
         checked_operations_prefix: usize,
         ops_know_by_peer: usize,
+        ops_know_by_peer_hits: u64,
+        ops_know_by_peer_misses: u64,
     ) {
         self.operation_cache_checked_operations
             .set(checked_operations as i64);
@@ -591,6 +784,14 @@ impl MassaMetrics {
             .set(checked_operations_prefix as i64);
         self.operation_cache_ops_know_by_peer
             .set(ops_know_by_peer as i64);
+        // the cache keeps totals, the counters are increased by the difference
+        self.operation_cache_ops_know_by_peer_hits.inc_by(
+            ops_know_by_peer_hits.saturating_sub(self.operation_cache_ops_know_by_peer_hits.get()),
+        );
+        self.operation_cache_ops_know_by_peer_misses.inc_by(
+            ops_know_by_peer_misses
+                .saturating_sub(self.operation_cache_ops_know_by_peer_misses.get()),
+        );
     }
 
     pub fn set_endorsements_cache_metrics(
@@ -626,6 +827,86 @@ impl MassaMetrics {
         self.protocol_banned_peers.set(nb as i64);
     }
 
+    /// Sets the number of connected peers for each node version
+    pub fn set_peers_versions(&self, versions: HashMap<String, usize>) {
+        // versions no longer seen are dropped
+        self.protocol_peers_versions.reset();
+        for (version, nb) in versions {
+            self.protocol_peers_versions
+                .with_label_values(&[&version])
+                .set(nb as i64);
+        }
+    }
+
+    pub fn observe_protocol_message_processing_time(&self, message_type: &str, seconds: f64) {
+        self.protocol_message_processing_time
+            .with_label_values(&[message_type])
+            .observe(seconds);
+    }
+
+    pub fn inc_protocol_items_received(&self, item_type: &str, nb: usize) {
+        self.protocol_items_received
+            .with_label_values(&[item_type])
+            .inc_by(nb as u64);
+    }
+
+    pub fn inc_protocol_items_sent(&self, item_type: &str, nb: usize) {
+        self.protocol_items_sent
+            .with_label_values(&[item_type])
+            .inc_by(nb as u64);
+    }
+
+    pub fn inc_protocol_invalid_items(&self, peer_id: &str, item_type: &str) {
+        self.protocol_invalid_items
+            .with_label_values(&[peer_id, item_type])
+            .inc();
+    }
+
+    /// Removes the invalid items counters of the peers that are not connected anymore
+    pub fn retain_protocol_invalid_items(&self, connected_peers: &HashSet<String>) {
+        let mut to_remove = Vec::new();
+        for family in self.protocol_invalid_items.collect() {
+            for metric in family.get_metric() {
+                let label = |name: &str| {
+                    metric
+                        .get_label()
+                        .iter()
+                        .find(|pair| pair.get_name() == name)
+                        .map(|pair| pair.get_value().to_string())
+                        .unwrap_or_default()
+                };
+                let peer_id = label("peer_id");
+                if !connected_peers.contains(&peer_id) {
+                    to_remove.push((peer_id, label("item_type")));
+                }
+            }
+        }
+        for (peer_id, item_type) in to_remove {
+            let _ = self
+                .protocol_invalid_items
+                .remove_label_values(&[&peer_id, &item_type]);
+        }
+    }
+
+    pub fn inc_protocol_invalid_blocks(&self, reason: &str) {
+        self.protocol_invalid_blocks
+            .with_label_values(&[reason])
+            .inc();
+    }
+
+    pub fn inc_protocol_block_ask_retries(&self) {
+        self.protocol_block_ask_retries.inc();
+    }
+
+    pub fn inc_protocol_dropped_operation_batches(&self) {
+        self.protocol_dropped_operation_batches.inc();
+    }
+
+    pub fn set_protocol_block_retrieval(&self, wishlist: usize, asked_blocks: usize) {
+        self.protocol_block_wishlist.set(wishlist as i64);
+        self.protocol_asked_blocks.set(asked_blocks as i64);
+    }
+
     pub fn inc_executed_final_slot(&self) {
         self.executed_final_slot.inc();
     }
@@ -702,6 +983,59 @@ impl MassaMetrics {
         self.block_slot_delay.observe(delay);
     }
 
+    /// Sets the approximate memory in bytes used by the in-memory structures of a subsystem
+    pub fn set_memory_usage(&self, subsystem: &str, bytes: usize) {
+        self.memory_usage
+            .with_label_values(&[subsystem])
+            .set(bytes as i64);
+    }
+
+    /// Approximate memory in bytes used by the in-memory structures of each subsystem, as last reported
+    pub fn get_memory_usage(&self) -> BTreeMap<String, u64> {
+        let mut memory_usage = BTreeMap::new();
+        for family in self.memory_usage.collect() {
+            for metric in family.get_metric() {
+                if let Some(subsystem) = metric
+                    .get_label()
+                    .iter()
+                    .find(|pair| pair.get_name() == "subsystem")
+                {
+                    memory_usage.insert(
+                        subsystem.get_value().to_string(),
+                        metric.get_gauge().get_value() as u64,
+                    );
+                }
+            }
+        }
+        memory_usage
+    }
+
+    /// Update the metrics of the tokio runtime behind `handle`.
+    ///
+    /// The tokio runtime metrics are unstable: this does nothing unless the node is built with `--cfg tokio_unstable`.
+    #[allow(unused_variables)]
+    pub fn update_tokio_runtime(&self, handle: &tokio::runtime::Handle) {
+        #[cfg(tokio_unstable)]
+        {
+            let metrics = handle.metrics();
+            let workers = metrics.num_workers();
+            self.tokio_workers.set(workers as i64);
+            self.tokio_alive_tasks
+                .set(metrics.active_tasks_count() as i64);
+            self.tokio_global_queue_depth
+                .set(metrics.injection_queue_depth() as i64);
+            self.tokio_blocking_queue_depth
+                .set(metrics.blocking_queue_depth() as i64);
+            if workers > 0 {
+                let total_poll_time: f64 = (0..workers)
+                    .map(|worker| metrics.worker_mean_poll_time(worker).as_secs_f64())
+                    .sum();
+                self.tokio_mean_poll_time
+                    .set(total_poll_time / workers as f64);
+            }
+        }
+    }
+
     /// Update the bandwidth metrics for all peers
     /// HashMap<peer_id, (tx, rx)>
     pub fn update_peers_tx_rx(&self, data: HashMap<String, (u64, u64)>) {
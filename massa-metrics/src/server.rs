@@ -1,4 +1,7 @@
 use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use hyper::{
     body::Body,
@@ -7,38 +10,86 @@ use hyper::{
     Request, Response,
 };
 use prometheus::{Encoder, TextEncoder};
-use tracing::{error, info};
+use tokio::sync::oneshot::Receiver;
+use tracing::{error, info, warn};
 
 use crate::MetricsStopper;
 
+/// Delay before restarting the metrics server after it crashed
+#[allow(dead_code)]
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
 #[allow(dead_code)]
 pub(crate) fn bind_metrics(addr: SocketAddr) -> MetricsStopper {
-    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
-    let handle = std::thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .expect("error on build tokio runtime for metrics server");
+    let (tx, mut rx) = tokio::sync::oneshot::channel::<()>();
+    // the thread name is used by the node supervisor to isolate panics of the metrics server,
+    // which is restarted here until it is stopped
+    let handle = std::thread::Builder::new()
+        .name("metrics-server".into())
+        .spawn(move || {
+            let stop_received = AtomicBool::new(false);
+            run_until_stopped(
+                || serve(addr, &mut rx, &stop_received),
+                || stop_received.load(Ordering::SeqCst),
+                RESTART_DELAY,
+            );
+        })
+        .expect("failed to spawn thread : metrics-server");
+    MetricsStopper {
+        stopper: Some(tx),
+        stop_handle: Some(handle),
+    }
+}
 
-        rt.block_on(async {
-            let server = hyper::Server::bind(&addr).serve(make_service_fn(|_| async {
-                Ok::<_, hyper::Error>(service_fn(serve_req))
-            }));
+/// Runs `run` again after `restart_delay` each time it panics, until it returns or `stopped`
+/// is true. Returns the number of restarts.
+#[allow(dead_code)]
+fn run_until_stopped(
+    mut run: impl FnMut(),
+    stopped: impl Fn() -> bool,
+    restart_delay: Duration,
+) -> usize {
+    let mut restarts = 0;
+    while std::panic::catch_unwind(AssertUnwindSafe(&mut run)).is_err() && !stopped() {
+        restarts += 1;
+        warn!(
+            "METRICS | server crashed, restarting in {:?} (restart {})",
+            restart_delay, restarts
+        );
+        std::thread::sleep(restart_delay);
+    }
+    restarts
+}
+
+/// Serves the metrics until the stop signal is received
+#[allow(dead_code)]
+fn serve(addr: SocketAddr, stop_receiver: &mut Receiver<()>, stop_received: &AtomicBool) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("error on build tokio runtime for metrics server");
 
-            let graceful_server = server.with_graceful_shutdown(async {
-                rx.await.ok();
-            });
-            info!("METRICS | listening on http://{}", addr);
-            if let Err(e) = graceful_server.await {
-                error!("metrics server error: {}", e);
+    rt.block_on(async {
+        let server = match hyper::Server::try_bind(&addr) {
+            Ok(builder) => builder.serve(make_service_fn(|_| async {
+                Ok::<_, hyper::Error>(service_fn(serve_req))
+            })),
+            Err(e) => {
+                error!("METRICS | could not listen on {}: {}", addr, e);
+                return;
             }
-            info!("METRICS | server stopped");
+        };
+
+        let graceful_server = server.with_graceful_shutdown(async {
+            stop_receiver.await.ok();
+            stop_received.store(true, Ordering::SeqCst);
         });
+        info!("METRICS | listening on http://{}", addr);
+        if let Err(e) = graceful_server.await {
+            error!("metrics server error: {}", e);
+        }
+        info!("METRICS | server stopped");
     });
-    MetricsStopper {
-        stopper: Some(tx),
-        stop_handle: Some(handle),
-    }
 }
 
 #[allow(dead_code)]
@@ -65,3 +116,41 @@ async fn serve_req(req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_run_until_stopped() {
+        // a crashed server is restarted until it returns
+        let runs = Cell::new(0);
+        let restarts = run_until_stopped(
+            || {
+                runs.set(runs.get() + 1);
+                if runs.get() < 3 {
+                    panic!("metrics server crash");
+                }
+            },
+            || false,
+            Duration::ZERO,
+        );
+        assert_eq!(restarts, 2);
+        assert_eq!(runs.get(), 3);
+
+        // a server crashing after it was stopped is not restarted
+        let runs = Cell::new(0);
+        let restarts = run_until_stopped(
+            || {
+                runs.set(runs.get() + 1);
+                panic!("metrics server crash");
+            },
+            || true,
+            Duration::ZERO,
+        );
+        assert_eq!(restarts, 0);
+        assert_eq!(runs.get(), 1);
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::amount::Amount;
 use crate::error::ModelsError;
 use crate::prehash::PreHashed;
 use massa_hash::{Hash, HashDeserializer, HASH_SIZE_BYTES};
@@ -339,10 +340,11 @@ impl UserAddress {
     }
 
     /// Gets the associated thread. Depends on the `thread_count`
+    ///
+    /// The first byte of the hash is mapped proportionally onto the threads,
+    /// which is the same as keeping its highest bits when `thread_count` is a power of two
     fn get_thread(&self, thread_count: u8) -> u8 {
-        (self.0.to_bytes()[0])
-            .checked_shr(8 - thread_count.trailing_zeros())
-            .unwrap_or(0)
+        ((u16::from(self.0.to_bytes()[0]) * u16::from(thread_count)) >> 8) as u8
     }
 
     /// Deserialize the address without considering the version byte
@@ -660,6 +662,39 @@ pub struct ExecutionAddressCycleInfo {
     pub active_rolls: Option<u64>,
 }
 
+/// Coins credited to an address for its staking activity, split by origin
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressRewards {
+    /// coins credited for producing blocks, excluding their fee share
+    pub block_rewards: Amount,
+    /// coins credited for endorsing blocks or for producing endorsed blocks, excluding their fee share
+    pub endorsement_rewards: Amount,
+    /// share of the credited coins that comes from the fees of the included operations
+    pub fees: Amount,
+}
+
+impl AddressRewards {
+    /// Sum two reward records, saturating on overflow
+    pub fn saturating_add(self, other: AddressRewards) -> AddressRewards {
+        AddressRewards {
+            block_rewards: self.block_rewards.saturating_add(other.block_rewards),
+            endorsement_rewards: self
+                .endorsement_rewards
+                .saturating_add(other.endorsement_rewards),
+            fees: self.fees.saturating_add(other.fees),
+        }
+    }
+}
+
+/// Rewards earned by a given address on a given cycle
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressCycleRewards {
+    /// cycle number
+    pub cycle: u64,
+    /// rewards earned during that cycle, aggregated over its final slots
+    pub rewards: AddressRewards,
+}
+
 #[cfg(test)]
 mod test {
     use crate::config::THREAD_COUNT;
@@ -729,6 +764,35 @@ mod test {
         assert_ne!(thread_addr_0, thread_addr_1);
     }
 
+    #[test]
+    fn test_address_get_thread_any_thread_count() {
+        let addresses: Vec<Address> = (0u32..2000)
+            .map(|i| {
+                let hash = massa_hash::Hash::compute_from(&i.to_be_bytes());
+                Address::User(UserAddress::UserAddressV0(UserAddressV0(hash)))
+            })
+            .collect();
+        for thread_count in 1..=THREAD_COUNT {
+            let mut used_threads = vec![false; thread_count as usize];
+            for address in &addresses {
+                let thread = address.get_thread(thread_count);
+                assert!(thread < thread_count);
+                used_threads[thread as usize] = true;
+                if thread_count.is_power_of_two() {
+                    // same as keeping the highest bits of the first byte of the hash
+                    let Address::User(UserAddress::UserAddressV0(addr)) = address else {
+                        unreachable!()
+                    };
+                    let expected = addr.0.to_bytes()[0]
+                        .checked_shr(8 - thread_count.trailing_zeros())
+                        .unwrap_or(0);
+                    assert_eq!(thread, expected);
+                }
+            }
+            assert!(used_threads.iter().all(|used| *used));
+        }
+    }
+
     #[test]
     fn test_address_serde() {
         let expected_addr =
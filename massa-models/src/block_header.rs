@@ -590,6 +590,36 @@ impl std::fmt::Display for BlockHeader {
     }
 }
 
+/// Minimum number of endorsements a block must include, given the period of its parent in its own thread.
+///
+/// The requirement `min_endorsements` is lowered by one for each `relaxation_periods` periods without block
+/// in the thread since the parent, so that the chain does not stall when the drawn endorsers are offline.
+/// It only depends on the slots of the block and of its parent: every node computes the same requirement.
+/// A null `relaxation_periods` never relaxes the requirement.
+///
+/// ```
+/// # use massa_models::block_header::required_endorsement_count;
+/// // consecutive periods: full requirement
+/// assert_eq!(required_endorsement_count(4, 2, 11, 10), 4);
+/// // 5 missed periods: relaxed by 2
+/// assert_eq!(required_endorsement_count(4, 2, 16, 10), 2);
+/// // never below 0
+/// assert_eq!(required_endorsement_count(4, 2, 100, 10), 0);
+/// ```
+pub fn required_endorsement_count(
+    min_endorsements: u32,
+    relaxation_periods: u64,
+    period: u64,
+    parent_period: u64,
+) -> u32 {
+    if relaxation_periods == 0 {
+        return min_endorsements;
+    }
+    let missed_periods = period.saturating_sub(parent_period).saturating_sub(1);
+    let relaxation = u32::try_from(missed_periods / relaxation_periods).unwrap_or(u32::MAX);
+    min_endorsements.saturating_sub(relaxation)
+}
+
 /// A denunciation data for block header
 #[derive(Debug)]
 pub struct BlockHeaderDenunciationData {
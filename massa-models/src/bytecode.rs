@@ -1,6 +1,6 @@
-use crate::serialization::{VecU8Deserializer, VecU8Serializer};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+    VecU8Deserializer, VecU8Serializer,
 };
 use nom::error::{ContextError, ParseError};
 use nom::IResult;
@@ -0,0 +1,195 @@
+//! Chain parameters defined per protocol version, each version being in force from its activation slot.
+//!
+//! Unlike the MIPs of `massa-versioning`, activated by a vote of the stakers, these versions activate
+//! at a slot known in advance by all the nodes running a given release, so that they all switch
+//! at the same block. The modules read the parameters in force at the slot they work on
+//! instead of the hard-coded constants.
+
+use serde::Serialize;
+
+use crate::error::ModelsError;
+use crate::slot::Slot;
+
+use super::constants::{
+    BASE_OPERATION_GAS_COST, MAX_BLOCK_SIZE, MAX_GAS_PER_BLOCK, MAX_OPERATIONS_PER_BLOCK,
+};
+
+/// Chain parameters that may change from one protocol version to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ChainParameters {
+    /// maximal size of a block in bytes
+    pub max_block_size: u32,
+    /// maximal gas of the operations of a block
+    pub max_gas_per_block: u64,
+    /// maximal number of operations in a block
+    pub max_operations_per_block: u32,
+    /// gas used by any operation on top of its execution
+    pub base_operation_gas_cost: u64,
+}
+
+/// Chain parameters of a protocol version, in force from its activation slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ChainParametersVersion {
+    /// protocol version
+    pub version: u32,
+    /// first slot at which the parameters are in force
+    pub activation_slot: Slot,
+    /// parameters of the version
+    pub parameters: ChainParameters,
+}
+
+/// Chain parameters in force from genesis
+pub const GENESIS_CHAIN_PARAMETERS: ChainParameters = ChainParameters {
+    max_block_size: MAX_BLOCK_SIZE,
+    max_gas_per_block: MAX_GAS_PER_BLOCK,
+    max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+    base_operation_gas_cost: BASE_OPERATION_GAS_COST,
+};
+
+/// Chain parameters versions known by this release, by increasing activation slot
+pub static CHAIN_PARAMETERS_VERSIONS: [ChainParametersVersion; 1] = [ChainParametersVersion {
+    version: 0,
+    activation_slot: Slot {
+        period: 0,
+        thread: 0,
+    },
+    parameters: GENESIS_CHAIN_PARAMETERS,
+}];
+
+/// Chain parameters versions ordered by activation slot, the first one being in force from genesis
+#[derive(Debug, Clone, Copy)]
+pub struct ChainParametersSchedule {
+    versions: &'static [ChainParametersVersion],
+}
+
+impl ChainParametersSchedule {
+    /// Checks that the versions start at genesis, and that both their numbers
+    /// and their activation slots strictly increase
+    pub fn new(versions: &'static [ChainParametersVersion]) -> Result<Self, ModelsError> {
+        let Some(first) = versions.first() else {
+            return Err(ModelsError::InvalidVersionError(
+                "no chain parameters version".to_string(),
+            ));
+        };
+        if first.activation_slot != Slot::new(0, 0) {
+            return Err(ModelsError::InvalidVersionError(format!(
+                "the first chain parameters version activates at {} instead of genesis",
+                first.activation_slot
+            )));
+        }
+        for pair in versions.windows(2) {
+            if pair[1].version <= pair[0].version
+                || pair[1].activation_slot <= pair[0].activation_slot
+            {
+                return Err(ModelsError::InvalidVersionError(format!(
+                    "chain parameters version {} does not follow version {}",
+                    pair[1].version, pair[0].version
+                )));
+            }
+        }
+        Ok(ChainParametersSchedule { versions })
+    }
+
+    /// Version in force at `slot`
+    pub fn active_at(&self, slot: Slot) -> &ChainParametersVersion {
+        let activated = self
+            .versions
+            .partition_point(|version| version.activation_slot <= slot);
+        // the first version activates at genesis: at least one version is activated
+        &self.versions[activated.saturating_sub(1)]
+    }
+
+    /// Parameters in force at `slot`
+    pub fn parameters_at(&self, slot: Slot) -> &ChainParameters {
+        &self.active_at(slot).parameters
+    }
+
+    /// Largest value of each parameter over all the versions.
+    /// Bounds what can be received before knowing the slot it applies to,
+    /// like the number of operations of a block on the wire.
+    pub fn upper_bounds(&self) -> ChainParameters {
+        self.versions.iter().map(|version| version.parameters).fold(
+            self.versions[0].parameters,
+            |bounds, parameters| ChainParameters {
+                max_block_size: bounds.max_block_size.max(parameters.max_block_size),
+                max_gas_per_block: bounds.max_gas_per_block.max(parameters.max_gas_per_block),
+                max_operations_per_block: bounds
+                    .max_operations_per_block
+                    .max(parameters.max_operations_per_block),
+                base_operation_gas_cost: bounds
+                    .base_operation_gas_cost
+                    .max(parameters.base_operation_gas_cost),
+            },
+        )
+    }
+
+    /// All the versions, by increasing activation slot
+    pub fn versions(&self) -> &[ChainParametersVersion] {
+        self.versions
+    }
+}
+
+impl Default for ChainParametersSchedule {
+    /// Versions known by this release
+    fn default() -> Self {
+        ChainParametersSchedule::new(&CHAIN_PARAMETERS_VERSIONS)
+            .expect("invalid chain parameters versions")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_parameters_schedule() {
+        // the versions of this release are valid
+        let schedule = ChainParametersSchedule::default();
+        assert_eq!(
+            schedule.parameters_at(Slot::new(0, 0)).max_block_size,
+            MAX_BLOCK_SIZE
+        );
+
+        const V0: ChainParametersVersion = ChainParametersVersion {
+            version: 0,
+            activation_slot: Slot {
+                period: 0,
+                thread: 0,
+            },
+            parameters: GENESIS_CHAIN_PARAMETERS,
+        };
+        const V1: ChainParametersVersion = ChainParametersVersion {
+            version: 1,
+            activation_slot: Slot {
+                period: 10,
+                thread: 3,
+            },
+            parameters: ChainParameters {
+                max_block_size: 2 * MAX_BLOCK_SIZE,
+                ..GENESIS_CHAIN_PARAMETERS
+            },
+        };
+        static VERSIONS: [ChainParametersVersion; 2] = [V0, V1];
+        let schedule = ChainParametersSchedule::new(&VERSIONS).unwrap();
+        assert_eq!(schedule.active_at(Slot::new(10, 2)).version, 0);
+        assert_eq!(schedule.active_at(Slot::new(10, 3)).version, 1);
+        assert_eq!(
+            schedule.parameters_at(Slot::new(1000, 0)).max_block_size,
+            2 * MAX_BLOCK_SIZE
+        );
+        assert_eq!(
+            schedule.upper_bounds(),
+            ChainParameters {
+                max_block_size: 2 * MAX_BLOCK_SIZE,
+                ..GENESIS_CHAIN_PARAMETERS
+            }
+        );
+
+        // invalid schedules are refused
+        static LATE_START: [ChainParametersVersion; 1] = [V1];
+        static UNORDERED: [ChainParametersVersion; 2] = [V1, V0];
+        assert!(ChainParametersSchedule::new(&[]).is_err());
+        assert!(ChainParametersSchedule::new(&LATE_START).is_err());
+        assert!(ChainParametersSchedule::new(&UNORDERED).is_err());
+    }
+}
@@ -15,7 +15,8 @@
 //! (`default_testing.rs`) But as for the current file you shouldn't modify it.
 use std::str::FromStr;
 
-use crate::{amount::Amount, serialization::u32_be_bytes_min_length, version::Version};
+use crate::{amount::Amount, version::Version};
+use massa_serialization::u32_be_bytes_min_length;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use num::rational::Ratio;
@@ -126,6 +127,9 @@ pub const LEDGER_COST_PER_BYTE: Amount = Amount::const_init(1, 4);
 pub const LEDGER_ENTRY_BASE_COST: Amount = Amount::const_init(1, 3);
 /// Base size of a empty datastore entry (not counting the key nor the value)
 pub const LEDGER_ENTRY_DATASTORE_BASE_SIZE: usize = 4;
+/// Whether ledger entries left with no balance, rolls, deferred credits, bytecode nor datastore are deleted at the end of each slot.
+/// Changing this value changes the ledger state, it must be activated network-wide.
+pub const DELETE_EMPTY_LEDGER_ENTRIES: bool = false;
 /// Time between the periods in the same thread.
 pub const T0: MassaTime = MassaTime::from_millis(16000);
 /// Proof of stake seed for the initial draw
@@ -134,6 +138,13 @@ pub const INITIAL_DRAW_SEED: &str = "massa_genesis_seed";
 pub const THREAD_COUNT: u8 = 32;
 /// Number of endorsement
 pub const ENDORSEMENT_COUNT: u32 = 16;
+/// Minimum number of endorsements a block must include (0 to disable the requirement).
+/// Changing this value changes the block validity rules, it must be activated network-wide.
+pub const MIN_BLOCK_ENDORSEMENTS: u32 = 0;
+/// The endorsement requirement of a block is lowered by one for each such number of periods without block
+/// in its thread since its parent (0 to never relax it).
+/// Changing this value changes the block validity rules, it must be activated network-wide.
+pub const ENDORSEMENT_RELAXATION_PERIODS: u64 = 2;
 /// Threshold for fitness.
 pub const DELTA_F0: u64 = 64 * (ENDORSEMENT_COUNT as u64 + 1);
 /// Maximum number of operations per block
@@ -195,6 +206,12 @@ pub const MAX_LEDGER_CHANGES_COUNT: u64 =
 pub const MAX_DATASTORE_ENTRY_COUNT: u64 = u64::MAX;
 /// Maximum number of key/values in the datastore of a `ExecuteSC` operation
 pub const MAX_OPERATION_DATASTORE_ENTRY_COUNT: u64 = 128;
+/// Maximum length of a name of the name registry
+pub const MAX_NAME_LENGTH: u16 = 64;
+/// Coins spent to register or renew a name
+pub const NAME_REGISTRATION_COST: Amount = Amount::const_init(1, 0);
+/// Number of periods during which a name registration is valid (about one year)
+pub const NAME_REGISTRATION_PERIODS: u64 = 1_971_000;
 /// Maximum length function name in call SC
 pub const MAX_FUNCTION_NAME_LENGTH: u16 = u16::MAX;
 /// Maximum size of parameters in call SC
@@ -316,6 +333,12 @@ pub const MAX_SIZE_CHANNEL_NETWORK_TO_PEER_HANDLER: usize = 10000;
 pub const MAX_PEERS_IN_ANNOUNCEMENT_LIST: u64 = 100;
 /// Maximum number of listeners for a peer
 pub const MAX_LISTENERS_PER_PEER: u64 = 100;
+/// Maximum length of the user agent announced by a peer during the handshake
+pub const MAX_USER_AGENT_LENGTH: u32 = 128;
+/// Maximum number of staker heartbeats in a single message
+pub const MAX_HEARTBEATS_PER_MESSAGE: u64 = 1024;
+/// Maximum number of node address links in a single message
+pub const MAX_NODE_LINKS_PER_MESSAGE: u64 = 1024;
 //
 // Constants used in versioning
 //
@@ -66,6 +66,12 @@ pub use constants::*;
 mod compact_config;
 pub use compact_config::CompactConfig;
 
+mod chain_parameters;
+pub use chain_parameters::{
+    ChainParameters, ChainParametersSchedule, ChainParametersVersion, CHAIN_PARAMETERS_VERSIONS,
+    GENESIS_CHAIN_PARAMETERS,
+};
+
 // Export tool to read user setting file
 mod massa_settings;
 pub use massa_settings::build_massa_settings;
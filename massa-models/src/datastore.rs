@@ -1,8 +1,8 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::serialization::{VecU8Deserializer, VecU8Serializer};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+    VecU8Deserializer, VecU8Serializer,
 };
 use nom::error::{context, ContextError, ParseError};
 use nom::multi::length_count;
@@ -62,6 +62,10 @@ pub enum ModelsError {
     OutdatedBootstrapCursor,
     /// Error raised {0}
     ErrorRaised(String),
+    /// invalid fee payer: {0}
+    InvalidFeePayer(String),
+    /// invalid name: {0}
+    InvalidName(String),
 }
 
 impl From<nom::Err<nom::error::Error<&[u8]>>> for ModelsError {
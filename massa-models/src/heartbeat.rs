@@ -0,0 +1,199 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Signed liveness heartbeats gossiped by staking nodes.
+
+use crate::secure_share::{Id, SecureShare, SecureShareContent};
+use crate::slot::{Slot, SlotDeserializer, SlotSerializer};
+use massa_hash::Hash;
+use massa_serialization::{
+    Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
+};
+use massa_signature::PublicKey;
+use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
+use nom::error::{context, ContextError, ParseError};
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::ops::Bound::{Excluded, Included};
+
+/// Domain separation tag mixed in the heartbeat hash, so that a heartbeat signature
+/// can never be mistaken for the signature of another kind of content
+const HEARTBEAT_HASH_DOMAIN: &[u8] = b"MASSA_HEARTBEAT";
+
+/// Heartbeat identifier
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct HeartbeatId(Hash);
+
+impl Id for HeartbeatId {
+    fn new(hash: Hash) -> Self {
+        HeartbeatId(hash)
+    }
+
+    fn get_hash(&self) -> &Hash {
+        &self.0
+    }
+}
+
+impl Display for HeartbeatId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Liveness heartbeat periodically broadcast by a staking address
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    /// time at which the heartbeat was emitted
+    pub timestamp: MassaTime,
+    /// last slot in which the staking address produced a block, if any
+    pub last_produced_slot: Option<Slot>,
+}
+
+/// Signed heartbeat
+pub type SecureShareHeartbeat = SecureShare<Heartbeat, HeartbeatId>;
+
+impl Display for Heartbeat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Heartbeat at {}", self.timestamp.as_millis())?;
+        if let Some(slot) = self.last_produced_slot {
+            write!(f, ", last produced slot {}", slot)?;
+        }
+        Ok(())
+    }
+}
+
+impl SecureShareContent for Heartbeat {
+    fn compute_hash(
+        &self,
+        content_serialized: &[u8],
+        content_creator_pub_key: &PublicKey,
+        chain_id: u64,
+    ) -> Hash {
+        let mut hash_data = Vec::new();
+        hash_data.extend(HEARTBEAT_HASH_DOMAIN);
+        // chain id prevents heartbeats from being replayed on another network
+        hash_data.extend(chain_id.to_be_bytes());
+        hash_data.extend(content_creator_pub_key.to_bytes());
+        hash_data.extend(content_serialized);
+        Hash::compute_from(&hash_data)
+    }
+}
+
+/// Serializer for `Heartbeat`
+pub struct HeartbeatSerializer {
+    time_serializer: MassaTimeSerializer,
+    slot_serializer: OptionSerializer<Slot, SlotSerializer>,
+}
+
+impl HeartbeatSerializer {
+    /// Creates a new `HeartbeatSerializer`
+    pub fn new() -> Self {
+        Self {
+            time_serializer: MassaTimeSerializer::new(),
+            slot_serializer: OptionSerializer::new(SlotSerializer::new()),
+        }
+    }
+}
+
+impl Default for HeartbeatSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<Heartbeat> for HeartbeatSerializer {
+    fn serialize(&self, value: &Heartbeat, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        self.time_serializer.serialize(&value.timestamp, buffer)?;
+        self.slot_serializer
+            .serialize(&value.last_produced_slot, buffer)?;
+        Ok(())
+    }
+}
+
+/// Deserializer for `Heartbeat`
+pub struct HeartbeatDeserializer {
+    time_deserializer: MassaTimeDeserializer,
+    slot_deserializer: OptionDeserializer<Slot, SlotDeserializer>,
+}
+
+impl HeartbeatDeserializer {
+    /// Creates a new `HeartbeatDeserializer`
+    pub fn new(thread_count: u8) -> Self {
+        Self {
+            time_deserializer: MassaTimeDeserializer::new((
+                Included(MassaTime::from_millis(0)),
+                Included(MassaTime::max()),
+            )),
+            slot_deserializer: OptionDeserializer::new(SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            )),
+        }
+    }
+}
+
+impl Deserializer<Heartbeat> for HeartbeatDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Heartbeat, E> {
+        context(
+            "Failed Heartbeat deserialization",
+            tuple((
+                context("Failed timestamp deserialization", |input| {
+                    self.time_deserializer.deserialize(input)
+                }),
+                context("Failed last_produced_slot deserialization", |input| {
+                    self.slot_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(|(timestamp, last_produced_slot)| Heartbeat {
+            timestamp,
+            last_produced_slot,
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CHAINID, THREAD_COUNT};
+    use crate::secure_share::{SecureShareDeserializer, SecureShareSerializer};
+    use massa_serialization::DeserializeError;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_heartbeat_serialization() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let heartbeat = Heartbeat {
+            timestamp: MassaTime::from_millis(1_000),
+            last_produced_slot: Some(Slot::new(10, 3)),
+        };
+        let secured: SecureShareHeartbeat = heartbeat
+            .new_verifiable(HeartbeatSerializer::new(), &keypair, *CHAINID)
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        SecureShareSerializer::new()
+            .serialize(&secured, &mut buffer)
+            .unwrap();
+        let (rest, deserialized): (&[u8], SecureShareHeartbeat) =
+            SecureShareDeserializer::new(HeartbeatDeserializer::new(THREAD_COUNT), *CHAINID)
+                .deserialize::<DeserializeError>(&buffer)
+                .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized.content, secured.content);
+        assert_eq!(deserialized.id, secured.id);
+        deserialized.verify_signature().unwrap();
+
+        // a heartbeat from another chain must not verify
+        let (_, other_chain): (&[u8], SecureShareHeartbeat) =
+            SecureShareDeserializer::new(HeartbeatDeserializer::new(THREAD_COUNT), *CHAINID + 1)
+                .deserialize::<DeserializeError>(&buffer)
+                .unwrap();
+        assert!(other_chain.verify_signature().is_err());
+    }
+}
@@ -36,12 +36,18 @@ pub mod endorsement;
 pub mod error;
 /// execution related structures
 pub mod execution;
+/// staking nodes liveness heartbeats
+pub mod heartbeat;
 /// ledger related structures
 pub mod ledger;
 /// mapping grpc
 pub mod mapping_grpc;
+/// name registry primitives
+pub mod name_registry;
 /// node related structure
 pub mod node;
+/// links between node ids and staking addresses
+pub mod node_link;
 /// operations
 pub mod operation;
 /// smart contract output events
@@ -227,6 +227,10 @@ impl From<OperationType> for grpc_model::OperationType {
                 grpc_operation_type.r#type =
                     Some(grpc_model::operation_type::Type::CallSc(call_sc));
             }
+            // name registry operations have no gRPC representation yet
+            OperationType::RegisterName { .. }
+            | OperationType::SetNameTarget { .. }
+            | OperationType::TransferName { .. } => {}
         }
 
         grpc_operation_type
@@ -251,6 +255,9 @@ impl From<OperationType> for grpc_model::OpType {
             OperationType::RollSell { .. } => grpc_model::OpType::RollSell,
             OperationType::ExecuteSC { .. } => grpc_model::OpType::ExecuteSc,
             OperationType::CallSC { .. } => grpc_model::OpType::CallSc,
+            OperationType::RegisterName { .. }
+            | OperationType::SetNameTarget { .. }
+            | OperationType::TransferName { .. } => grpc_model::OpType::Unspecified,
         }
     }
 }
@@ -0,0 +1,207 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Primitives of the built-in name registry.
+//!
+//! A name is registered by a `RegisterName` operation for `NAME_REGISTRATION_PERIODS` periods,
+//! during which its owner can point it to an address (`SetNameTarget`), give it away (`TransferName`)
+//! or renew it (`RegisterName` again). Once expired, a name can be registered by anyone.
+//!
+//! Name records are stored in the datastore of the registry address, keyed by name.
+
+use crate::{
+    address::{Address, AddressDeserializer, AddressSerializer, SCAddress, SCAddressV0},
+    config::MAX_NAME_LENGTH,
+    error::ModelsError,
+};
+use massa_hash::Hash;
+use massa_serialization::{
+    Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
+    U64VarIntDeserializer, U64VarIntSerializer,
+};
+use nom::{
+    error::{context, ContextError, ParseError},
+    sequence::tuple,
+    IResult, Parser,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Bound::Included;
+
+/// Address in the datastore of which the name records are stored
+pub fn name_registry_address() -> Address {
+    Address::SC(SCAddress::SCAddressV0(SCAddressV0(Hash::compute_from(
+        b"MASSA_NAME_REGISTRY",
+    ))))
+}
+
+/// Checks that a name is made of `MAX_NAME_LENGTH` lowercase letters, digits or inner dashes at most
+pub fn check_name(name: &str) -> Result<(), ModelsError> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH as usize {
+        return Err(ModelsError::InvalidName(format!(
+            "length of {:?} must be in [1..={}]",
+            name, MAX_NAME_LENGTH
+        )));
+    }
+    if !name
+        .bytes()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-')
+        || name.starts_with('-')
+        || name.ends_with('-')
+    {
+        return Err(ModelsError::InvalidName(format!(
+            "{:?} must only contain lowercase letters, digits and inner dashes",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Registration of a name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NameRecord {
+    /// address owning the name
+    pub owner: Address,
+    /// address the name points to, if any
+    pub target: Option<Address>,
+    /// last period during which the registration is valid
+    pub expire_period: u64,
+}
+
+impl NameRecord {
+    /// Whether the registration is over at `period`
+    pub fn is_expired(&self, period: u64) -> bool {
+        period > self.expire_period
+    }
+}
+
+/// Serializer for `NameRecord`
+#[derive(Clone)]
+pub struct NameRecordSerializer {
+    address_serializer: AddressSerializer,
+    target_serializer: OptionSerializer<Address, AddressSerializer>,
+    u64_serializer: U64VarIntSerializer,
+}
+
+impl NameRecordSerializer {
+    /// Creates a new `NameRecordSerializer`
+    pub fn new() -> Self {
+        Self {
+            address_serializer: AddressSerializer::new(),
+            target_serializer: OptionSerializer::new(AddressSerializer::new()),
+            u64_serializer: U64VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Default for NameRecordSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<NameRecord> for NameRecordSerializer {
+    fn serialize(&self, value: &NameRecord, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        self.address_serializer.serialize(&value.owner, buffer)?;
+        self.target_serializer.serialize(&value.target, buffer)?;
+        self.u64_serializer
+            .serialize(&value.expire_period, buffer)?;
+        Ok(())
+    }
+}
+
+/// Deserializer for `NameRecord`
+#[derive(Clone)]
+pub struct NameRecordDeserializer {
+    address_deserializer: AddressDeserializer,
+    target_deserializer: OptionDeserializer<Address, AddressDeserializer>,
+    u64_deserializer: U64VarIntDeserializer,
+}
+
+impl NameRecordDeserializer {
+    /// Creates a new `NameRecordDeserializer`
+    pub fn new() -> Self {
+        Self {
+            address_deserializer: AddressDeserializer::new(),
+            target_deserializer: OptionDeserializer::new(AddressDeserializer::new()),
+            u64_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        }
+    }
+}
+
+impl Default for NameRecordDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deserializer<NameRecord> for NameRecordDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], NameRecord, E> {
+        context(
+            "Failed NameRecord deserialization",
+            tuple((
+                context("Failed owner deserialization", |input| {
+                    self.address_deserializer.deserialize(input)
+                }),
+                context("Failed target deserialization", |input| {
+                    self.target_deserializer.deserialize(input)
+                }),
+                context("Failed expire_period deserialization", |input| {
+                    self.u64_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(|(owner, target, expire_period)| NameRecord {
+            owner,
+            target,
+            expire_period,
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_serialization::DeserializeError;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("massa").is_ok());
+        assert!(check_name("my-node-42").is_ok());
+        assert!(check_name("").is_err());
+        assert!(check_name("Massa").is_err());
+        assert!(check_name("-massa").is_err());
+        assert!(check_name("massa.mas").is_err());
+        assert!(check_name(&"a".repeat(MAX_NAME_LENGTH as usize + 1)).is_err());
+    }
+
+    #[test]
+    fn test_name_record_serialization() {
+        let owner = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        for target in [None, Some(name_registry_address())] {
+            let record = NameRecord {
+                owner,
+                target,
+                expire_period: 1234,
+            };
+            let mut buffer = Vec::new();
+            NameRecordSerializer::new()
+                .serialize(&record, &mut buffer)
+                .unwrap();
+            let (rest, deserialized) = NameRecordDeserializer::new()
+                .deserialize::<DeserializeError>(&buffer)
+                .unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(deserialized, record);
+        }
+        assert!(!NameRecord {
+            owner,
+            target: None,
+            expire_period: 10
+        }
+        .is_expired(10));
+    }
+}
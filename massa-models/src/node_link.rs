@@ -0,0 +1,328 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Links between a node id and a staking address, signed by both keys and gossiped by the nodes
+//! whose operator opted in, so that the blocks produced by a staker can be attributed to a peer.
+
+use crate::error::ModelsError;
+use crate::node::NodeId;
+use crate::secure_share::{
+    Id, SecureShare, SecureShareContent, SecureShareDeserializer, SecureShareSerializer,
+};
+use massa_hash::Hash;
+use massa_serialization::{Deserializer, SerializeError, Serializer};
+use massa_signature::{KeyPair, PublicKeyDeserializer, Signature, SignatureDeserializer};
+use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
+use nom::error::{context, ContextError, ParseError};
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::ops::Bound::Included;
+
+/// Domain separation tag mixed in the link hash, so that a link signature
+/// can never be mistaken for the signature of another kind of content
+const NODE_LINK_HASH_DOMAIN: &[u8] = b"MASSA_NODE_ADDRESS_LINK";
+
+/// Node address link identifier
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct NodeAddressLinkId(Hash);
+
+impl Id for NodeAddressLinkId {
+    fn new(hash: Hash) -> Self {
+        NodeAddressLinkId(hash)
+    }
+
+    fn get_hash(&self) -> &Hash {
+        &self.0
+    }
+}
+
+impl Display for NodeAddressLinkId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Statement of a staking address that it is run by a node
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeAddressLink {
+    /// node running the staking address
+    pub node_id: NodeId,
+    /// time at which the link was signed
+    pub timestamp: MassaTime,
+}
+
+/// Link signed by the staking address
+pub type SecureShareNodeAddressLink = SecureShare<NodeAddressLink, NodeAddressLinkId>;
+
+impl Display for NodeAddressLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Link to node {} at {}",
+            self.node_id,
+            self.timestamp.as_millis()
+        )
+    }
+}
+
+impl SecureShareContent for NodeAddressLink {
+    fn compute_hash(
+        &self,
+        content_serialized: &[u8],
+        content_creator_pub_key: &massa_signature::PublicKey,
+        chain_id: u64,
+    ) -> Hash {
+        let mut hash_data = Vec::new();
+        hash_data.extend(NODE_LINK_HASH_DOMAIN);
+        // chain id prevents links from being replayed on another network
+        hash_data.extend(chain_id.to_be_bytes());
+        hash_data.extend(content_creator_pub_key.to_bytes());
+        hash_data.extend(content_serialized);
+        Hash::compute_from(&hash_data)
+    }
+}
+
+/// Link signed by the staking address, and countersigned by the node so that
+/// neither side can be attributed a link it did not agree to
+#[derive(Clone, Debug)]
+pub struct NodeAddressLinkProof {
+    /// link signed by the staking address
+    pub link: SecureShareNodeAddressLink,
+    /// signature of the link id by the node
+    pub node_signature: Signature,
+}
+
+impl NodeAddressLinkProof {
+    /// Sign a link with the keys of the staking address and of the node
+    pub fn new(
+        link: NodeAddressLink,
+        staking_keypair: &KeyPair,
+        node_keypair: &KeyPair,
+        chain_id: u64,
+    ) -> Result<Self, ModelsError> {
+        if link.node_id != NodeId::new(node_keypair.get_public_key()) {
+            return Err("the node keypair does not match the linked node id".into());
+        }
+        let link =
+            link.new_verifiable(NodeAddressLinkSerializer::new(), staking_keypair, chain_id)?;
+        let node_signature = node_keypair.sign(link.id.get_hash())?;
+        Ok(NodeAddressLinkProof {
+            link,
+            node_signature,
+        })
+    }
+
+    /// Check the signatures of both the staking address and the node
+    pub fn verify(&self) -> Result<(), ModelsError> {
+        self.link.verify_signature()?;
+        self.link
+            .content
+            .node_id
+            .get_public_key()
+            .verify_signature(self.link.id.get_hash(), &self.node_signature)?;
+        Ok(())
+    }
+}
+
+/// Serializer for `NodeAddressLink`
+pub struct NodeAddressLinkSerializer {
+    time_serializer: MassaTimeSerializer,
+}
+
+impl NodeAddressLinkSerializer {
+    /// Creates a new `NodeAddressLinkSerializer`
+    pub fn new() -> Self {
+        Self {
+            time_serializer: MassaTimeSerializer::new(),
+        }
+    }
+}
+
+impl Default for NodeAddressLinkSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<NodeAddressLink> for NodeAddressLinkSerializer {
+    fn serialize(
+        &self,
+        value: &NodeAddressLink,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        buffer.extend(value.node_id.get_public_key().to_bytes());
+        self.time_serializer.serialize(&value.timestamp, buffer)?;
+        Ok(())
+    }
+}
+
+/// Deserializer for `NodeAddressLink`
+pub struct NodeAddressLinkDeserializer {
+    public_key_deserializer: PublicKeyDeserializer,
+    time_deserializer: MassaTimeDeserializer,
+}
+
+impl NodeAddressLinkDeserializer {
+    /// Creates a new `NodeAddressLinkDeserializer`
+    pub fn new() -> Self {
+        Self {
+            public_key_deserializer: PublicKeyDeserializer::new(),
+            time_deserializer: MassaTimeDeserializer::new((
+                Included(MassaTime::from_millis(0)),
+                Included(MassaTime::max()),
+            )),
+        }
+    }
+}
+
+impl Default for NodeAddressLinkDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deserializer<NodeAddressLink> for NodeAddressLinkDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], NodeAddressLink, E> {
+        context(
+            "Failed NodeAddressLink deserialization",
+            tuple((
+                context("Failed node_id deserialization", |input| {
+                    self.public_key_deserializer.deserialize(input)
+                }),
+                context("Failed timestamp deserialization", |input| {
+                    self.time_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(|(public_key, timestamp)| NodeAddressLink {
+            node_id: NodeId::new(public_key),
+            timestamp,
+        })
+        .parse(buffer)
+    }
+}
+
+/// Serializer for `NodeAddressLinkProof`
+pub struct NodeAddressLinkProofSerializer {
+    secure_share_serializer: SecureShareSerializer,
+}
+
+impl NodeAddressLinkProofSerializer {
+    /// Creates a new `NodeAddressLinkProofSerializer`
+    pub fn new() -> Self {
+        Self {
+            secure_share_serializer: SecureShareSerializer::new(),
+        }
+    }
+}
+
+impl Default for NodeAddressLinkProofSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<NodeAddressLinkProof> for NodeAddressLinkProofSerializer {
+    fn serialize(
+        &self,
+        value: &NodeAddressLinkProof,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.secure_share_serializer
+            .serialize(&value.link, buffer)?;
+        buffer.extend(value.node_signature.to_bytes());
+        Ok(())
+    }
+}
+
+/// Deserializer for `NodeAddressLinkProof`
+pub struct NodeAddressLinkProofDeserializer {
+    link_deserializer: SecureShareDeserializer<NodeAddressLink, NodeAddressLinkDeserializer>,
+    signature_deserializer: SignatureDeserializer,
+}
+
+impl NodeAddressLinkProofDeserializer {
+    /// Creates a new `NodeAddressLinkProofDeserializer`
+    pub fn new(chain_id: u64) -> Self {
+        Self {
+            link_deserializer: SecureShareDeserializer::new(
+                NodeAddressLinkDeserializer::new(),
+                chain_id,
+            ),
+            signature_deserializer: SignatureDeserializer::new(),
+        }
+    }
+}
+
+impl Deserializer<NodeAddressLinkProof> for NodeAddressLinkProofDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], NodeAddressLinkProof, E> {
+        context(
+            "Failed NodeAddressLinkProof deserialization",
+            tuple((
+                context("Failed link deserialization", |input| {
+                    self.link_deserializer.deserialize(input)
+                }),
+                context("Failed node_signature deserialization", |input| {
+                    self.signature_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(|(link, node_signature)| NodeAddressLinkProof {
+            link,
+            node_signature,
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CHAINID;
+    use massa_serialization::DeserializeError;
+
+    #[test]
+    fn test_node_address_link_proof() {
+        let staking_keypair = KeyPair::generate(0).unwrap();
+        let node_keypair = KeyPair::generate(0).unwrap();
+        let link = NodeAddressLink {
+            node_id: NodeId::new(node_keypair.get_public_key()),
+            timestamp: MassaTime::from_millis(1_000),
+        };
+        let proof =
+            NodeAddressLinkProof::new(link.clone(), &staking_keypair, &node_keypair, *CHAINID)
+                .unwrap();
+        proof.verify().unwrap();
+
+        let mut buffer = Vec::new();
+        NodeAddressLinkProofSerializer::new()
+            .serialize(&proof, &mut buffer)
+            .unwrap();
+        let (rest, deserialized) = NodeAddressLinkProofDeserializer::new(*CHAINID)
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized.link.content, link);
+        assert_eq!(deserialized.link.id, proof.link.id);
+        deserialized.verify().unwrap();
+
+        // a node cannot sign a link to another node
+        let other_keypair = KeyPair::generate(0).unwrap();
+        assert!(
+            NodeAddressLinkProof::new(link, &staking_keypair, &other_keypair, *CHAINID).is_err()
+        );
+        // nor claim a link signed for another node
+        let forged = NodeAddressLinkProof {
+            node_signature: other_keypair.sign(proof.link.id.get_hash()).unwrap(),
+            ..proof
+        };
+        assert!(forged.verify().is_err());
+    }
+}
@@ -1,7 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::address::AddressSerializer;
+use crate::config::{MAX_NAME_LENGTH, NAME_REGISTRATION_COST};
 use crate::datastore::{Datastore, DatastoreDeserializer, DatastoreSerializer};
+use crate::name_registry::name_registry_address;
 use crate::prehash::{PreHashSet, PreHashed};
 use crate::secure_share::{
     Id, SecureShare, SecureShareContent, SecureShareDeserializer, SecureShareSerializer,
@@ -10,15 +12,19 @@ use crate::{
     address::{Address, AddressDeserializer},
     amount::{Amount, AmountDeserializer, AmountSerializer},
     error::ModelsError,
-    serialization::{StringDeserializer, StringSerializer, VecU8Deserializer, VecU8Serializer},
+    serialization::{StringDeserializer, StringSerializer},
+    slot::Slot,
 };
 use massa_hash::{Hash, HashDeserializer};
 use massa_serialization::{
-    DeserializeError, Deserializer, SerializeError, Serializer, U16VarIntDeserializer,
-    U16VarIntSerializer, U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer,
-    U64VarIntSerializer,
+    DeserializeError, Deserializer, OptionDeserializer, OptionSerializer, SerializeError,
+    Serializer, U16VarIntDeserializer, U16VarIntSerializer, U32VarIntDeserializer,
+    U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer, VecU8Deserializer,
+    VecU8Serializer,
+};
+use massa_signature::{
+    KeyPair, PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer,
 };
-use massa_signature::PublicKey;
 use nom::error::{context, ErrorKind};
 use nom::multi::length_count;
 use nom::sequence::tuple;
@@ -386,6 +392,35 @@ enum OperationTypeId {
     RollSell = 2,
     ExecuteSC = 3,
     CallSC = 4,
+    RegisterName = 5,
+    SetNameTarget = 6,
+    TransferName = 7,
+}
+
+/// Tag written where the operation type id is expected to announce the format of sponsored operations:
+/// the tag is followed by the fee payer, then by the operation type.
+/// Operations without fee payer keep the original format, so that their ids are unchanged.
+const SPONSORED_OPERATION_FORMAT_TAG: u32 = u32::MAX;
+
+/// Tag written where the operation type id is expected to announce the format of deferred operations:
+/// the tag is followed by the start period, then by the fee payer tag (if any) and the operation type.
+/// Operations without start period keep the original format, so that their ids are unchanged.
+const DEFERRED_OPERATION_FORMAT_TAG: u32 = u32::MAX - 1;
+
+/// Version of the `Operation` versioning component from which the name operations,
+/// the sponsored operations and the deferred operations are accepted
+pub const EXTENDED_OPERATION_FORMAT_VERSION: u32 = 1;
+
+/// Domain separator of the hash signed by the fee payer of a sponsored operation
+const FEE_PAYER_HASH_DOMAIN: &[u8] = b"massa_fee_payer";
+
+/// Authorization given by an address to pay the fee of an operation created by another address
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeePayer {
+    /// public key of the address paying the fee
+    pub public_key: PublicKey,
+    /// signature of the hash computed by `Operation::compute_fee_payer_hash`
+    pub signature: Signature,
 }
 
 /// the operation as sent in the network
@@ -397,6 +432,12 @@ pub struct Operation {
     pub fee: Amount,
     /// after `expire_period` slot the operation won't be included in a block
     pub expire_period: u64,
+    /// before `start_period` slot the operation won't be included in a block (deferred operation), if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_period: Option<u64>,
+    /// address paying the fee instead of the creator (sponsored operation), if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<FeePayer>,
     /// the type specific operation part
     pub op: OperationType,
 }
@@ -405,11 +446,91 @@ impl std::fmt::Display for Operation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Fee: {}", self.fee)?;
         writeln!(f, "Expire period: {}", self.expire_period)?;
+        if let Some(start_period) = self.start_period {
+            writeln!(f, "Start period: {}", start_period)?;
+        }
+        if let Some(fee_payer) = &self.fee_payer {
+            writeln!(
+                f,
+                "Fee payer: {}",
+                Address::from_public_key(&fee_payer.public_key)
+            )?;
+        }
         writeln!(f, "Operation type: {}", self.op)?;
         Ok(())
     }
 }
 
+impl Operation {
+    /// Compute the largest expire period an operation of thread `op_thread` can have
+    /// so that its whole validity window lies strictly after `latest_slot`.
+    pub fn compute_expire_period(
+        latest_slot: Slot,
+        op_thread: u8,
+        operation_validity_periods: u64,
+    ) -> u64 {
+        let expire_period = latest_slot
+            .period
+            .saturating_add(operation_validity_periods);
+        if latest_slot.thread >= op_thread {
+            expire_period.saturating_add(1)
+        } else {
+            expire_period
+        }
+    }
+
+    /// Compute the hash signed by the fee payer of a sponsored operation.
+    /// It commits to the chain, to the creator and to the whole operation except the fee payer itself.
+    pub fn compute_fee_payer_hash(
+        &self,
+        content_creator_pub_key: &PublicKey,
+        chain_id: u64,
+    ) -> Result<Hash, ModelsError> {
+        let mut content_serialized = Vec::new();
+        OperationSerializer::new().serialize_without_fee_payer(self, &mut content_serialized)?;
+        let mut hash_data = Vec::new();
+        hash_data.extend(FEE_PAYER_HASH_DOMAIN);
+        hash_data.extend(chain_id.to_be_bytes());
+        hash_data.extend(content_creator_pub_key.to_bytes());
+        hash_data.extend(content_serialized);
+        Ok(Hash::compute_from(&hash_data))
+    }
+
+    /// Version of the `Operation` versioning component that must be active for this operation to be accepted:
+    /// 0 for the original formats, `EXTENDED_OPERATION_FORMAT_VERSION` for the name, sponsored and deferred operations
+    pub fn get_format_version(&self) -> u32 {
+        let extended = self.fee_payer.is_some()
+            || self.start_period.is_some()
+            || matches!(
+                self.op,
+                OperationType::RegisterName { .. }
+                    | OperationType::SetNameTarget { .. }
+                    | OperationType::TransferName { .. }
+            );
+        if extended {
+            EXTENDED_OPERATION_FORMAT_VERSION
+        } else {
+            0
+        }
+    }
+
+    /// Make the address of `fee_payer_keypair` pay the fee of this operation,
+    /// which is then to be signed by the creator owning `content_creator_pub_key`.
+    pub fn sponsor(
+        mut self,
+        fee_payer_keypair: &KeyPair,
+        content_creator_pub_key: &PublicKey,
+        chain_id: u64,
+    ) -> Result<Self, ModelsError> {
+        let hash = self.compute_fee_payer_hash(content_creator_pub_key, chain_id)?;
+        self.fee_payer = Some(FeePayer {
+            public_key: fee_payer_keypair.get_public_key(),
+            signature: fee_payer_keypair.sign(&hash)?,
+        });
+        Ok(self)
+    }
+}
+
 /// signed operation
 pub type SecureShareOperation = SecureShare<Operation, OperationId>;
 
@@ -434,6 +555,7 @@ impl SecureShareContent for Operation {
 /// Serializer for `Operation`
 pub struct OperationSerializer {
     u64_serializer: U64VarIntSerializer,
+    u32_serializer: U32VarIntSerializer,
     amount_serializer: AmountSerializer,
     op_type_serializer: OperationTypeSerializer,
 }
@@ -443,10 +565,39 @@ impl OperationSerializer {
     pub fn new() -> Self {
         Self {
             u64_serializer: U64VarIntSerializer::new(),
+            u32_serializer: U32VarIntSerializer::new(),
             amount_serializer: AmountSerializer::new(),
             op_type_serializer: OperationTypeSerializer::new(),
         }
     }
+
+    /// Serialize the fee, the expire period and the start period of the operation, if any
+    fn serialize_periods(
+        &self,
+        value: &Operation,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.amount_serializer.serialize(&value.fee, buffer)?;
+        self.u64_serializer
+            .serialize(&value.expire_period, buffer)?;
+        if let Some(start_period) = value.start_period {
+            self.u32_serializer
+                .serialize(&DEFERRED_OPERATION_FORMAT_TAG, buffer)?;
+            self.u64_serializer.serialize(&start_period, buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Serialize the operation as if it had no fee payer
+    fn serialize_without_fee_payer(
+        &self,
+        value: &Operation,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.serialize_periods(value, buffer)?;
+        self.op_type_serializer.serialize(&value.op, buffer)?;
+        Ok(())
+    }
 }
 
 impl Default for OperationSerializer {
@@ -472,14 +623,21 @@ impl Serializer<Operation> for OperationSerializer {
     ///   fee: Amount::from_str("20").unwrap(),
     ///   op,
     ///   expire_period: 50,
+    ///   start_period: None,
+    ///   fee_payer: None,
     /// };
     /// let mut buffer = Vec::new();
     /// OperationSerializer::new().serialize(&operation, &mut buffer).unwrap();
     /// ```
     fn serialize(&self, value: &Operation, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
-        self.amount_serializer.serialize(&value.fee, buffer)?;
-        self.u64_serializer
-            .serialize(&value.expire_period, buffer)?;
+        let Some(fee_payer) = &value.fee_payer else {
+            return self.serialize_without_fee_payer(value, buffer);
+        };
+        self.serialize_periods(value, buffer)?;
+        self.u32_serializer
+            .serialize(&SPONSORED_OPERATION_FORMAT_TAG, buffer)?;
+        buffer.extend(fee_payer.public_key.to_bytes());
+        buffer.extend(fee_payer.signature.to_bytes());
         self.op_type_serializer.serialize(&value.op, buffer)?;
         Ok(())
     }
@@ -489,6 +647,9 @@ impl Serializer<Operation> for OperationSerializer {
 pub struct OperationDeserializer {
     expire_period_deserializer: U64VarIntDeserializer,
     amount_deserializer: AmountDeserializer,
+    format_tag_deserializer: U32VarIntDeserializer,
+    public_key_deserializer: PublicKeyDeserializer,
+    signature_deserializer: SignatureDeserializer,
     op_type_deserializer: OperationTypeDeserializer,
 }
 
@@ -508,6 +669,12 @@ impl OperationDeserializer {
                 Included(Amount::MIN),
                 Included(Amount::MAX),
             ),
+            format_tag_deserializer: U32VarIntDeserializer::new(
+                Included(DEFERRED_OPERATION_FORMAT_TAG),
+                Included(SPONSORED_OPERATION_FORMAT_TAG),
+            ),
+            public_key_deserializer: PublicKeyDeserializer::new(),
+            signature_deserializer: SignatureDeserializer::new(),
             op_type_deserializer: OperationTypeDeserializer::new(
                 max_datastore_value_length,
                 max_function_name_length,
@@ -537,6 +704,8 @@ impl Deserializer<Operation> for OperationDeserializer {
     ///   fee: Amount::from_str("20").unwrap(),
     ///   op,
     ///   expire_period: 50,
+    ///   start_period: None,
+    ///   fee_payer: None,
     /// };
     /// let mut buffer = Vec::new();
     /// OperationSerializer::new().serialize(&operation, &mut buffer).unwrap();
@@ -568,17 +737,50 @@ impl Deserializer<Operation> for OperationDeserializer {
                 context("Failed expire_period deserialization", |input| {
                     self.expire_period_deserializer.deserialize(input)
                 }),
+                context("Failed start_period deserialization", |input| {
+                    // operations without the deferred format tag have no start period
+                    match self.format_tag_deserializer.deserialize::<E>(input) {
+                        Ok((rest, DEFERRED_OPERATION_FORMAT_TAG)) => self
+                            .expire_period_deserializer
+                            .deserialize(rest)
+                            .map(|(rest, start_period)| (rest, Some(start_period))),
+                        _ => Ok((input, None)),
+                    }
+                }),
+                context("Failed fee_payer deserialization", |input| {
+                    // operations without the sponsored format tag have no fee payer
+                    let Ok((rest, SPONSORED_OPERATION_FORMAT_TAG)) =
+                        self.format_tag_deserializer.deserialize::<E>(input)
+                    else {
+                        return Ok((input, None));
+                    };
+                    tuple((
+                        |input| self.public_key_deserializer.deserialize(input),
+                        |input| self.signature_deserializer.deserialize(input),
+                    ))
+                    .map(|(public_key, signature)| {
+                        Some(FeePayer {
+                            public_key,
+                            signature,
+                        })
+                    })
+                    .parse(rest)
+                }),
                 context("Failed op deserialization", |input| {
                     let (rest, op) = self.op_type_deserializer.deserialize(input)?;
                     Ok((rest, op))
                 }),
             )),
         )
-        .map(|(fee, expire_period, op)| Operation {
-            fee,
-            expire_period,
-            op,
-        })
+        .map(
+            |(fee, expire_period, start_period, fee_payer, op)| Operation {
+                fee,
+                expire_period,
+                start_period,
+                fee_payer,
+                op,
+            },
+        )
         .parse(buffer)
     }
 }
@@ -595,11 +797,17 @@ pub enum OperationType {
         amount: Amount,
     },
     /// the sender buys `roll_count` rolls. Roll price is defined in configuration
+    ///
+    /// The purchase is all-or-nothing: if the sender cannot afford `roll_count * roll_price`,
+    /// no roll is bought and no coin is spent (apart from the operation fee).
     RollBuy {
         /// roll count
         roll_count: u64,
     },
     /// the sender sells `roll_count` rolls. Roll price is defined in configuration
+    ///
+    /// The sale is all-or-nothing: if the sender owns less than `roll_count` rolls, no roll is sold.
+    /// Otherwise `roll_count * roll_price` coins are credited as a single deferred credit.
     RollSell {
         /// roll count
         roll_count: u64,
@@ -629,6 +837,26 @@ pub enum OperationType {
         /// Extra coins that are spent from the caller's balance and transferred to the target
         coins: Amount,
     },
+    /// the sender registers a free or expired name of the name registry, or renews one of its names.
+    /// The registration cost and duration are defined in configuration
+    RegisterName {
+        /// name to register
+        name: String,
+    },
+    /// the owner of a name points it to an address, or unsets its target
+    SetNameTarget {
+        /// registered name
+        name: String,
+        /// address the name points to
+        target: Option<Address>,
+    },
+    /// the owner of a name gives it to another address
+    TransferName {
+        /// registered name
+        name: String,
+        /// new owner of the name
+        new_owner: Address,
+    },
 }
 
 impl std::fmt::Display for OperationType {
@@ -674,6 +902,23 @@ impl std::fmt::Display for OperationType {
                 writeln!(f, "\t- max_gas:{}", max_gas)?;
                 writeln!(f, "\t- coins:{}", coins)?;
             }
+            OperationType::RegisterName { name } => {
+                writeln!(f, "RegisterName:")?;
+                writeln!(f, "\t- name:{}", name)?;
+            }
+            OperationType::SetNameTarget { name, target } => {
+                writeln!(f, "SetNameTarget:")?;
+                writeln!(f, "\t- name:{}", name)?;
+                match target {
+                    Some(target) => writeln!(f, "\t- target:{}", target)?,
+                    None => writeln!(f, "\t- target:none")?,
+                }
+            }
+            OperationType::TransferName { name, new_owner } => {
+                writeln!(f, "TransferName:")?;
+                writeln!(f, "\t- name:{}", name)?;
+                writeln!(f, "\t- new owner:{}", new_owner)?;
+            }
         }
         Ok(())
     }
@@ -688,6 +933,8 @@ pub struct OperationTypeSerializer {
     address_serializer: AddressSerializer,
     function_name_serializer: StringSerializer<U16VarIntSerializer, u16>,
     datastore_serializer: DatastoreSerializer,
+    name_serializer: StringSerializer<U16VarIntSerializer, u16>,
+    target_serializer: OptionSerializer<Address, AddressSerializer>,
 }
 
 impl OperationTypeSerializer {
@@ -701,6 +948,8 @@ impl OperationTypeSerializer {
             address_serializer: AddressSerializer::new(),
             function_name_serializer: StringSerializer::new(U16VarIntSerializer::new()),
             datastore_serializer: DatastoreSerializer::new(),
+            name_serializer: StringSerializer::new(U16VarIntSerializer::new()),
+            target_serializer: OptionSerializer::new(AddressSerializer::new()),
         }
     }
 }
@@ -781,6 +1030,23 @@ impl Serializer<OperationType> for OperationTypeSerializer {
                     .serialize(target_func, buffer)?;
                 self.vec_u8_serializer.serialize(param, buffer)?;
             }
+            OperationType::RegisterName { name } => {
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::RegisterName), buffer)?;
+                self.name_serializer.serialize(name, buffer)?;
+            }
+            OperationType::SetNameTarget { name, target } => {
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::SetNameTarget), buffer)?;
+                self.name_serializer.serialize(name, buffer)?;
+                self.target_serializer.serialize(target, buffer)?;
+            }
+            OperationType::TransferName { name, new_owner } => {
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::TransferName), buffer)?;
+                self.name_serializer.serialize(name, buffer)?;
+                self.address_serializer.serialize(new_owner, buffer)?;
+            }
         }
         Ok(())
     }
@@ -797,6 +1063,8 @@ pub struct OperationTypeDeserializer {
     function_name_deserializer: StringDeserializer<U16VarIntDeserializer, u16>,
     parameter_deserializer: VecU8Deserializer,
     datastore_deserializer: DatastoreDeserializer,
+    name_deserializer: StringDeserializer<U16VarIntDeserializer, u16>,
+    target_deserializer: OptionDeserializer<Address, AddressDeserializer>,
 }
 
 impl OperationTypeDeserializer {
@@ -835,6 +1103,11 @@ impl OperationTypeDeserializer {
                 max_op_datastore_key_length,
                 max_op_datastore_value_length,
             ),
+            name_deserializer: StringDeserializer::new(U16VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_NAME_LENGTH),
+            )),
+            target_deserializer: OptionDeserializer::new(AddressDeserializer::new()),
         }
     }
 }
@@ -969,6 +1242,39 @@ impl Deserializer<OperationType> for OperationTypeDeserializer {
                     },
                 )
                 .parse(input),
+                OperationTypeId::RegisterName => {
+                    context("Failed RegisterName deserialization", |input| {
+                        self.name_deserializer.deserialize(input)
+                    })
+                    .map(|name| OperationType::RegisterName { name })
+                    .parse(input)
+                }
+                OperationTypeId::SetNameTarget => context(
+                    "Failed SetNameTarget deserialization",
+                    tuple((
+                        context("Failed name deserialization", |input| {
+                            self.name_deserializer.deserialize(input)
+                        }),
+                        context("Failed target deserialization", |input| {
+                            self.target_deserializer.deserialize(input)
+                        }),
+                    )),
+                )
+                .map(|(name, target)| OperationType::SetNameTarget { name, target })
+                .parse(input),
+                OperationTypeId::TransferName => context(
+                    "Failed TransferName deserialization",
+                    tuple((
+                        context("Failed name deserialization", |input| {
+                            self.name_deserializer.deserialize(input)
+                        }),
+                        context("Failed new_owner deserialization", |input| {
+                            self.address_deserializer.deserialize(input)
+                        }),
+                    )),
+                )
+                .map(|(name, new_owner)| OperationType::TransferName { name, new_owner })
+                .parse(input),
             }
         })
         .parse(buffer)
@@ -977,15 +1283,54 @@ impl Deserializer<OperationType> for OperationTypeDeserializer {
 
 impl SecureShareOperation {
     /// get the range of periods during which an operation is valid
-    /// Range: `(op.expire_period - cfg.operation_validity_period) -> op.expire_period` (included)
+    /// Range: `max(op.expire_period - cfg.operation_validity_period, op.start_period) -> op.expire_period` (included)
     pub fn get_validity_range(&self, operation_validity_period: u64) -> RangeInclusive<u64> {
         let start = self
             .content
             .expire_period
-            .saturating_sub(operation_validity_period);
+            .saturating_sub(operation_validity_period)
+            .max(self.content.start_period.unwrap_or_default());
         start..=self.content.expire_period
     }
 
+    /// get the range of slots during which an operation is valid.
+    /// Only the slots of the thread of the operation creator within that range can include it.
+    pub fn validity_range(
+        &self,
+        thread_count: u8,
+        operation_validity_periods: u64,
+    ) -> RangeInclusive<Slot> {
+        let thread = self.content_creator_address.get_thread(thread_count);
+        let periods = self.get_validity_range(operation_validity_periods);
+        Slot::new(*periods.start(), thread)..=Slot::new(*periods.end(), thread)
+    }
+
+    /// get the last slot in which the operation can be included
+    pub fn get_expire_slot(&self, thread_count: u8) -> Slot {
+        Slot::new(
+            self.content.expire_period,
+            self.content_creator_address.get_thread(thread_count),
+        )
+    }
+
+    /// Check whether the operation can be included in a block at `slot`
+    pub fn is_valid_in_slot(
+        &self,
+        slot: &Slot,
+        thread_count: u8,
+        operation_validity_periods: u64,
+    ) -> bool {
+        slot.thread == self.content_creator_address.get_thread(thread_count)
+            && self
+                .get_validity_range(operation_validity_periods)
+                .contains(&slot.period)
+    }
+
+    /// Check whether the operation can no longer be included in any slot after `slot`
+    pub fn is_expired_at(&self, slot: &Slot, thread_count: u8) -> bool {
+        self.get_expire_slot(thread_count) <= *slot
+    }
+
     /// Get the maximum amount of gas used by the operation.
     ///
     /// base_operation_gas_cost comes from the configuration and
@@ -997,15 +1342,81 @@ impl SecureShareOperation {
             OperationType::RollBuy { .. } => 0,
             OperationType::RollSell { .. } => 0,
             OperationType::Transaction { .. } => 0,
+            OperationType::RegisterName { .. } => 0,
+            OperationType::SetNameTarget { .. } => 0,
+            OperationType::TransferName { .. } => 0,
         }
         .saturating_add(base_operation_gas_cost)
     }
 
+    /// get the address paying the fee: the fee payer of a sponsored operation, the creator otherwise
+    pub fn get_fee_payer_address(&self) -> Address {
+        match &self.content.fee_payer {
+            Some(fee_payer) => Address::from_public_key(&fee_payer.public_key),
+            None => self.content_creator_address,
+        }
+    }
+
+    /// check that the fee payer of the operation is in the thread of its creator,
+    /// so that the fee is debited in the thread of the blocks including the operation
+    pub fn check_fee_payer_thread(&self, thread_count: u8) -> Result<(), ModelsError> {
+        let fee_payer_thread = self.get_fee_payer_address().get_thread(thread_count);
+        let creator_thread = self.content_creator_address.get_thread(thread_count);
+        if fee_payer_thread != creator_thread {
+            return Err(ModelsError::InvalidFeePayer(format!(
+                "the fee payer of operation {} is in thread {} while its creator is in thread {}",
+                self.id, fee_payer_thread, creator_thread
+            )));
+        }
+        Ok(())
+    }
+
+    /// get the hash, signature and public key to verify to check the authorization of the fee payer,
+    /// if the operation is sponsored
+    pub fn get_fee_payer_signature_data(
+        &self,
+        chain_id: u64,
+        thread_count: u8,
+    ) -> Result<Option<(Hash, Signature, PublicKey)>, ModelsError> {
+        let Some(fee_payer) = &self.content.fee_payer else {
+            return Ok(None);
+        };
+        if fee_payer.public_key == self.content_creator_pub_key {
+            return Err(ModelsError::InvalidFeePayer(format!(
+                "operation {} is sponsored by its own creator",
+                self.id
+            )));
+        }
+        self.check_fee_payer_thread(thread_count)?;
+        let hash = self
+            .content
+            .compute_fee_payer_hash(&self.content_creator_pub_key, chain_id)?;
+        Ok(Some((hash, fee_payer.signature, fee_payer.public_key)))
+    }
+
+    /// check that the fee payer of a sponsored operation is in the thread of its creator and authorized it
+    pub fn verify_fee_payer(&self, chain_id: u64, thread_count: u8) -> Result<(), ModelsError> {
+        if let Some((hash, signature, public_key)) =
+            self.get_fee_payer_signature_data(chain_id, thread_count)?
+        {
+            public_key
+                .verify_signature(&hash, &signature)
+                .map_err(|err| {
+                    ModelsError::InvalidFeePayer(format!(
+                        "wrong fee payer signature for operation {}: {}",
+                        self.id, err
+                    ))
+                })?;
+        }
+        Ok(())
+    }
+
     /// get the addresses that are involved in this operation from a ledger point of view
     pub fn get_ledger_involved_addresses(&self) -> PreHashSet<Address> {
         let mut res = PreHashSet::<Address>::default();
         let emitter_address = Address::from_public_key(&self.content_creator_pub_key);
         res.insert(emitter_address);
+        res.insert(self.get_fee_payer_address());
         match &self.content.op {
             OperationType::Transaction {
                 recipient_address, ..
@@ -1018,11 +1429,17 @@ impl SecureShareOperation {
             OperationType::CallSC { target_addr, .. } => {
                 res.insert(*target_addr);
             }
+            OperationType::RegisterName { .. }
+            | OperationType::SetNameTarget { .. }
+            | OperationType::TransferName { .. } => {
+                res.insert(name_registry_address());
+            }
         }
         res
     }
 
-    /// Gets the maximal amount of coins that may be spent by this operation (incl. fee)
+    /// Gets the maximal amount of coins that may be spent by the creator of this operation
+    /// (incl. fee, unless a fee payer pays it)
     pub fn get_max_spending(&self, roll_price: Amount) -> Amount {
         // compute the max amount of coins spent outside of the fees
         let max_non_fee_seq_spending = match &self.content.op {
@@ -1031,10 +1448,17 @@ impl SecureShareOperation {
             OperationType::RollSell { .. } => Amount::zero(),
             OperationType::ExecuteSC { max_coins, .. } => *max_coins,
             OperationType::CallSC { coins, .. } => *coins,
+            OperationType::RegisterName { .. } => NAME_REGISTRATION_COST,
+            OperationType::SetNameTarget { .. } => Amount::zero(),
+            OperationType::TransferName { .. } => Amount::zero(),
         };
 
-        // add all fees and return
-        max_non_fee_seq_spending.saturating_add(self.content.fee)
+        // add all fees paid by the creator and return
+        if self.content.fee_payer.is_some() {
+            max_non_fee_seq_spending
+        } else {
+            max_non_fee_seq_spending.saturating_add(self.content.fee)
+        }
     }
 
     /// get the addresses that are involved in this operation from a rolls point of view
@@ -1050,6 +1474,9 @@ impl SecureShareOperation {
             }
             OperationType::ExecuteSC { .. } => {}
             OperationType::CallSC { .. } => {}
+            OperationType::RegisterName { .. } => {}
+            OperationType::SetNameTarget { .. } => {}
+            OperationType::TransferName { .. } => {}
         }
         Ok(res)
     }
@@ -1354,6 +1781,8 @@ impl Serializer<Vec<SecureShareOperation>> for OperationsSerializer {
     ///   fee: Amount::from_str("20").unwrap(),
     ///   op,
     ///   expire_period: 50,
+    ///   start_period: None,
+    ///   fee_payer: None,
     /// };
     /// let op_secured = Operation::new_verifiable(content, OperationSerializer::new(), &keypair, *CHAINID).unwrap();
     /// let operations = vec![op_secured.clone(), op_secured.clone()];
@@ -1433,6 +1862,8 @@ impl Deserializer<Vec<SecureShareOperation>> for OperationsDeserializer {
     ///   fee: Amount::from_str("20").unwrap(),
     ///   op,
     ///   expire_period: 50,
+    ///   start_period: None,
+    ///   fee_payer: None,
     /// };
     /// let op_secured = Operation::new_verifiable(content, OperationSerializer::new(), &keypair, *CHAINID).unwrap();
     /// let operations = vec![op_secured.clone(), op_secured.clone()];
@@ -1493,7 +1924,7 @@ mod tests {
     use crate::config::{
         CHAINID, MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH,
         MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
-        MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE, THREAD_COUNT,
     };
 
     use super::*;
@@ -1530,6 +1961,8 @@ mod tests {
             fee: Amount::from_str(op_fee).unwrap(),
             op: op_type,
             expire_period: op_expiry_period,
+            start_period: None,
+            fee_payer: None,
         };
 
         let op_serializer = OperationSerializer::new();
@@ -1586,6 +2019,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            start_period: None,
+            fee_payer: None,
         };
 
         let mut ser_content = Vec::new();
@@ -1665,6 +2100,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            start_period: None,
+            fee_payer: None,
         };
 
         let mut ser_content = Vec::new();
@@ -1744,6 +2181,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            start_period: None,
+            fee_payer: None,
         };
 
         let mut ser_content = Vec::new();
@@ -1818,6 +2257,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            start_period: None,
+            fee_payer: None,
         };
 
         let serialized_operation = serde_json::to_string(&orig_operation).unwrap();
@@ -1859,6 +2300,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            start_period: None,
+            fee_payer: None,
         };
 
         let serialized_operation = serde_json::to_string(&orig_operation).unwrap();
@@ -1901,6 +2344,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            start_period: None,
+            fee_payer: None,
         };
 
         let serialized_operation = serde_json::to_string(&orig_operation).unwrap();
@@ -1909,4 +2354,288 @@ mod tests {
         assert_eq!(orig_operation.fee.to_string(), res_operation["fee"]);
         assert_eq!(orig_operation.expire_period, res_operation["expire_period"]);
     }
+
+    /// generates a key pair whose address is in the thread of `address` (`same_thread`) or in another one
+    fn keypair_in_thread_of(address: &Address, same_thread: bool) -> KeyPair {
+        let thread = address.get_thread(THREAD_COUNT);
+        loop {
+            let keypair = KeyPair::generate(0).unwrap();
+            let keypair_thread =
+                Address::from_public_key(&keypair.get_public_key()).get_thread(THREAD_COUNT);
+            if (keypair_thread == thread) == same_thread {
+                return keypair;
+            }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_sponsored_operation() {
+        let sender_keypair = KeyPair::generate(0).unwrap();
+        let sponsor_keypair = keypair_in_thread_of(
+            &Address::from_public_key(&sender_keypair.get_public_key()),
+            true,
+        );
+        let sponsor_addr = Address::from_public_key(&sponsor_keypair.get_public_key());
+
+        let op = OperationType::Transaction {
+            recipient_address: sponsor_addr,
+            amount: Amount::from_str("5").unwrap(),
+        };
+        let unsponsored = Operation {
+            fee: Amount::from_str("1").unwrap(),
+            op,
+            expire_period: 50,
+            start_period: None,
+            fee_payer: None,
+        };
+        let content = unsponsored
+            .clone()
+            .sponsor(&sponsor_keypair, &sender_keypair.get_public_key(), *CHAINID)
+            .unwrap();
+
+        // the sponsored format round-trips and differs from the original one
+        let mut ser_content = Vec::new();
+        OperationSerializer::new()
+            .serialize(&content, &mut ser_content)
+            .unwrap();
+        let mut ser_unsponsored = Vec::new();
+        OperationSerializer::new()
+            .serialize(&unsponsored, &mut ser_unsponsored)
+            .unwrap();
+        assert_ne!(ser_content, ser_unsponsored);
+        let deserializer = OperationDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let (rest, res_content) = deserializer
+            .deserialize::<DeserializeError>(&ser_content)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(res_content, content);
+        let (_, res_unsponsored) = deserializer
+            .deserialize::<DeserializeError>(&ser_unsponsored)
+            .unwrap();
+        assert_eq!(res_unsponsored, unsponsored);
+
+        let op = Operation::new_verifiable(
+            content.clone(),
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        op.verify_signature().unwrap();
+        op.verify_fee_payer(*CHAINID, THREAD_COUNT).unwrap();
+        assert_eq!(op.get_fee_payer_address(), sponsor_addr);
+        assert!(op.get_ledger_involved_addresses().contains(&sponsor_addr));
+        // the fee is not spent by the creator
+        assert_eq!(
+            op.get_max_spending(Amount::zero()),
+            Amount::from_str("5").unwrap()
+        );
+
+        // the authorization is bound to the operation and to the chain
+        assert!(op.verify_fee_payer(*CHAINID + 1, THREAD_COUNT).is_err());
+        let mut tampered = content.clone();
+        tampered.fee = Amount::from_str("2").unwrap();
+        let tampered = Operation::new_verifiable(
+            tampered,
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        assert!(tampered.verify_fee_payer(*CHAINID, THREAD_COUNT).is_err());
+
+        // an operation cannot be sponsored by its own creator
+        let self_sponsored = unsponsored
+            .clone()
+            .sponsor(&sender_keypair, &sender_keypair.get_public_key(), *CHAINID)
+            .unwrap();
+        let self_sponsored = Operation::new_verifiable(
+            self_sponsored,
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        assert!(self_sponsored
+            .verify_fee_payer(*CHAINID, THREAD_COUNT)
+            .is_err());
+
+        // nor by an address of another thread
+        let other_thread_sponsor = keypair_in_thread_of(
+            &Address::from_public_key(&sender_keypair.get_public_key()),
+            false,
+        );
+        let cross_thread = unsponsored
+            .sponsor(
+                &other_thread_sponsor,
+                &sender_keypair.get_public_key(),
+                *CHAINID,
+            )
+            .unwrap();
+        let cross_thread = Operation::new_verifiable(
+            cross_thread,
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        assert!(cross_thread.check_fee_payer_thread(THREAD_COUNT).is_err());
+        assert!(cross_thread
+            .verify_fee_payer(*CHAINID, THREAD_COUNT)
+            .is_err());
+        op.check_fee_payer_thread(THREAD_COUNT).unwrap();
+    }
+
+    #[test]
+    fn test_validity_window() {
+        let sender_keypair = KeyPair::generate(0).unwrap();
+        let thread_count = 32;
+        let content = Operation {
+            fee: Amount::from_str("1").unwrap(),
+            op: OperationType::RollBuy { roll_count: 1 },
+            expire_period: 50,
+            start_period: None,
+            fee_payer: None,
+        };
+        let op = Operation::new_verifiable(
+            content,
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        let thread = op.content_creator_address.get_thread(thread_count);
+        let other_thread = (thread + 1) % thread_count;
+
+        assert_eq!(
+            op.validity_range(thread_count, 10),
+            Slot::new(40, thread)..=Slot::new(50, thread)
+        );
+        assert_eq!(op.get_expire_slot(thread_count), Slot::new(50, thread));
+        assert!(op.is_valid_in_slot(&Slot::new(40, thread), thread_count, 10));
+        assert!(op.is_valid_in_slot(&Slot::new(50, thread), thread_count, 10));
+        assert!(!op.is_valid_in_slot(&Slot::new(39, thread), thread_count, 10));
+        assert!(!op.is_valid_in_slot(&Slot::new(51, thread), thread_count, 10));
+        assert!(!op.is_valid_in_slot(&Slot::new(45, other_thread), thread_count, 10));
+
+        assert!(!op.is_expired_at(&Slot::new(49, thread), thread_count));
+        assert!(op.is_expired_at(&Slot::new(50, thread), thread_count));
+        assert!(op.is_expired_at(&Slot::new(51, 0), thread_count));
+
+        // the computed expire period gives a window starting right after the latest slot
+        let expire_period = Operation::compute_expire_period(Slot::new(100, thread), thread, 10);
+        assert_eq!(expire_period, 111);
+        if thread > 0 {
+            assert_eq!(
+                Operation::compute_expire_period(Slot::new(100, thread - 1), thread, 10),
+                110
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_deferred_operation() {
+        let sender_keypair = KeyPair::generate(0).unwrap();
+        let sponsor_keypair = keypair_in_thread_of(
+            &Address::from_public_key(&sender_keypair.get_public_key()),
+            true,
+        );
+        let thread_count = 32;
+        let immediate = Operation {
+            fee: Amount::from_str("1").unwrap(),
+            op: OperationType::RollBuy { roll_count: 1 },
+            expire_period: 50,
+            start_period: None,
+            fee_payer: None,
+        };
+        let deferred = Operation {
+            start_period: Some(45),
+            ..immediate.clone()
+        };
+
+        // the deferred format round-trips, alone or with a fee payer, and differs from the original one
+        let sponsored = deferred
+            .clone()
+            .sponsor(&sponsor_keypair, &sender_keypair.get_public_key(), *CHAINID)
+            .unwrap();
+        let deserializer = OperationDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let mut ser_immediate = Vec::new();
+        OperationSerializer::new()
+            .serialize(&immediate, &mut ser_immediate)
+            .unwrap();
+        for content in [&deferred, &sponsored] {
+            let mut ser_content = Vec::new();
+            OperationSerializer::new()
+                .serialize(content, &mut ser_content)
+                .unwrap();
+            assert_ne!(ser_content, ser_immediate);
+            let (rest, res_content) = deserializer
+                .deserialize::<DeserializeError>(&ser_content)
+                .unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(&res_content, content);
+        }
+
+        // the fee payer authorization commits to the start period
+        let sponsored = Operation::new_verifiable(
+            sponsored,
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        sponsored.verify_fee_payer(*CHAINID, THREAD_COUNT).unwrap();
+        let mut tampered = sponsored.content.clone();
+        tampered.start_period = Some(41);
+        let tampered = Operation::new_verifiable(
+            tampered,
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        assert!(tampered.verify_fee_payer(*CHAINID, THREAD_COUNT).is_err());
+
+        // the operation can't be included before its start period
+        let op = Operation::new_verifiable(
+            deferred,
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        let thread = op.content_creator_address.get_thread(thread_count);
+        assert_eq!(op.get_validity_range(10), 45..=50);
+        assert!(!op.is_valid_in_slot(&Slot::new(44, thread), thread_count, 10));
+        assert!(op.is_valid_in_slot(&Slot::new(45, thread), thread_count, 10));
+
+        // a start period after the expire period leaves no validity window
+        let never_valid = Operation::new_verifiable(
+            Operation {
+                start_period: Some(51),
+                ..immediate
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+        assert!(never_valid.get_validity_range(10).is_empty());
+    }
 }
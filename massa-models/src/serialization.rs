@@ -1,6 +1,5 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::error::ModelsError;
 use crate::prehash::{PreHashSet, PreHashed};
 use bitvec::prelude::BitVec;
 use massa_serialization::{
@@ -18,115 +17,10 @@ use nom::{
 use num::integer::div_ceil;
 use std::convert::TryInto;
 use std::marker::PhantomData;
-use std::mem::size_of;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::ops::Bound;
 use Bound::Included;
 
-/// Serialize min big endian integer
-pub trait SerializeMinBEInt {
-    /// serializes with the minimal amount of big endian bytes
-    fn to_be_bytes_min(self, max_value: Self) -> Result<Vec<u8>, ModelsError>;
-}
-
-impl SerializeMinBEInt for u32 {
-    fn to_be_bytes_min(self, max_value: Self) -> Result<Vec<u8>, ModelsError> {
-        if self > max_value {
-            return Err(ModelsError::SerializeError("integer out of bounds".into()));
-        }
-        let skip_bytes = (max_value.leading_zeros() as usize) / 8;
-        Ok(self.to_be_bytes()[skip_bytes..].to_vec())
-    }
-}
-
-impl SerializeMinBEInt for u64 {
-    fn to_be_bytes_min(self, max_value: Self) -> Result<Vec<u8>, ModelsError> {
-        if self > max_value {
-            return Err(ModelsError::SerializeError("integer out of bounds".into()));
-        }
-        let skip_bytes = (max_value.leading_zeros() as usize) / 8;
-        Ok(self.to_be_bytes()[skip_bytes..].to_vec())
-    }
-}
-
-/// Deserialize min big endian
-pub trait DeserializeMinBEInt: Sized {
-    /// Deserializes a minimally sized big endian integer to Self from the provided buffer and checks that its value is within given bounds.
-    /// In case of success, return the deserialized data and the number of bytes read
-    fn from_be_bytes_min(buffer: &[u8], max_value: Self) -> Result<(Self, usize), ModelsError>;
-}
-
-/// Compute the minimal big endian deserialization size
-pub const fn u32_be_bytes_min_length(max_value: u32) -> usize {
-    size_of::<u32>() - (max_value.leading_zeros() as usize) / 8
-}
-
-/// Compute the minimal big endian deserialization size
-pub const fn u64_be_bytes_min_length(max_value: u64) -> usize {
-    size_of::<u64>() - (max_value.leading_zeros() as usize) / 8
-}
-impl DeserializeMinBEInt for u32 {
-    fn from_be_bytes_min(buffer: &[u8], max_value: Self) -> Result<(Self, usize), ModelsError> {
-        let read_bytes = u32_be_bytes_min_length(max_value);
-        let skip_bytes = size_of::<Self>() - read_bytes;
-        if buffer.len() < read_bytes {
-            return Err(ModelsError::SerializeError("unexpected buffer END".into()));
-        }
-        let mut buf = [0u8; size_of::<Self>()];
-        buf[skip_bytes..].clone_from_slice(&buffer[..read_bytes]);
-        let res = u32::from_be_bytes(buf);
-        if res > max_value {
-            return Err(ModelsError::SerializeError(
-                "integer outside of bounds".into(),
-            ));
-        }
-        Ok((res, read_bytes))
-    }
-}
-
-impl DeserializeMinBEInt for u64 {
-    fn from_be_bytes_min(buffer: &[u8], max_value: Self) -> Result<(Self, usize), ModelsError> {
-        let read_bytes = u64_be_bytes_min_length(max_value);
-        let skip_bytes = size_of::<Self>() - read_bytes;
-        if buffer.len() < read_bytes {
-            return Err(ModelsError::SerializeError("unexpected buffer END".into()));
-        }
-        let mut buf = [0u8; size_of::<Self>()];
-        buf[skip_bytes..].clone_from_slice(&buffer[..read_bytes]);
-        let res = u64::from_be_bytes(buf);
-        if res > max_value {
-            return Err(ModelsError::SerializeError(
-                "integer outside of bounds".into(),
-            ));
-        }
-        Ok((res, read_bytes))
-    }
-}
-
-/// array from slice
-pub fn array_from_slice<const ARRAY_SIZE: usize>(
-    buffer: &[u8],
-) -> Result<[u8; ARRAY_SIZE], ModelsError> {
-    if buffer.len() < ARRAY_SIZE {
-        return Err(ModelsError::BufferError(
-            "slice too small to extract array".into(),
-        ));
-    }
-    buffer[..ARRAY_SIZE].try_into().map_err(|err| {
-        ModelsError::BufferError(format!("could not extract array from slice: {}", err))
-    })
-}
-
-/// `u8` from slice
-pub fn u8_from_slice(buffer: &[u8]) -> Result<u8, ModelsError> {
-    if buffer.is_empty() {
-        return Err(ModelsError::BufferError(
-            "could not read u8 from empty buffer".into(),
-        ));
-    }
-    Ok(buffer[0])
-}
-
 /// Serializer for `IpAddr`
 #[derive(Default, Clone)]
 pub struct IpAddrSerializer;
@@ -221,178 +115,6 @@ impl Deserializer<IpAddr> for IpAddrDeserializer {
     }
 }
 
-/// Basic `Vec<u8>` serializer
-#[derive(Clone)]
-pub struct VecU8Serializer {
-    len_serializer: U64VarIntSerializer,
-}
-
-impl VecU8Serializer {
-    /// Creates a new `VecU8Serializer`
-    pub fn new() -> Self {
-        Self {
-            len_serializer: U64VarIntSerializer::new(),
-        }
-    }
-}
-
-impl Default for VecU8Serializer {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl Serializer<Vec<u8>> for VecU8Serializer {
-    /// ```
-    /// use std::ops::Bound::Included;
-    /// use massa_serialization::Serializer;
-    /// use massa_models::serialization::VecU8Serializer;
-    ///
-    /// let vec = vec![1, 2, 3];
-    /// let mut buffer = Vec::new();
-    /// let serializer = VecU8Serializer::new();
-    /// serializer.serialize(&vec, &mut buffer).unwrap();
-    /// ```
-    fn serialize(&self, value: &Vec<u8>, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
-        let len: u64 = value.len().try_into().map_err(|err| {
-            SerializeError::NumberTooBig(format!("too many entries data in VecU8: {}", err))
-        })?;
-        self.len_serializer.serialize(&len, buffer)?;
-        buffer.extend(value);
-        Ok(())
-    }
-}
-
-/// Basic `Vec<u8>` deserializer
-#[derive(Clone)]
-pub struct VecU8Deserializer {
-    varint_u64_deserializer: U64VarIntDeserializer,
-}
-
-impl VecU8Deserializer {
-    /// Creates a new `VecU8Deserializer`
-    pub const fn new(min_length: Bound<u64>, max_length: Bound<u64>) -> Self {
-        Self {
-            varint_u64_deserializer: U64VarIntDeserializer::new(min_length, max_length),
-        }
-    }
-}
-
-impl Deserializer<Vec<u8>> for VecU8Deserializer {
-    /// ```
-    /// use std::ops::Bound::Included;
-    /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
-    /// use massa_models::serialization::{VecU8Serializer, VecU8Deserializer};
-    ///
-    /// let vec = vec![1, 2, 3];
-    /// let mut serialized = Vec::new();
-    /// let serializer = VecU8Serializer::new();
-    /// let deserializer = VecU8Deserializer::new(Included(0), Included(1000000));
-    /// serializer.serialize(&vec, &mut serialized).unwrap();
-    /// let (rest, vec_deser) = deserializer.deserialize::<DeserializeError>(&serialized).unwrap();
-    /// assert!(rest.is_empty());
-    /// assert_eq!(vec, vec_deser);
-    /// ```
-    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
-        &self,
-        buffer: &'a [u8],
-    ) -> IResult<&'a [u8], Vec<u8>, E> {
-        context("Failed Vec<u8> deserialization", |input| {
-            length_data(|input| self.varint_u64_deserializer.deserialize(input))(input)
-        })
-        .map(|res| res.to_vec())
-        .parse(buffer)
-    }
-}
-
-/// Basic `Vec<_>` serializer
-#[derive(Clone)]
-pub struct VecSerializer<T, ST>
-where
-    ST: Serializer<T>,
-{
-    len_serializer: U64VarIntSerializer,
-    data_serializer: ST,
-    phantom_t: PhantomData<T>,
-}
-
-impl<T, ST> VecSerializer<T, ST>
-where
-    ST: Serializer<T>,
-{
-    /// Creates a new `VecSerializer`
-    pub fn new(data_serializer: ST) -> Self {
-        Self {
-            len_serializer: U64VarIntSerializer::new(),
-            data_serializer,
-            phantom_t: PhantomData,
-        }
-    }
-}
-
-impl<T, ST> Serializer<Vec<T>> for VecSerializer<T, ST>
-where
-    ST: Serializer<T>,
-{
-    fn serialize(&self, value: &Vec<T>, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
-        self.len_serializer
-            .serialize(&(value.len() as u64), buffer)?;
-        for elem in value {
-            self.data_serializer.serialize(elem, buffer)?;
-        }
-        Ok(())
-    }
-}
-
-/// Basic `Vec<_>` deserializer
-#[derive(Clone)]
-pub struct VecDeserializer<T, ST>
-where
-    ST: Deserializer<T> + Clone,
-{
-    varint_u64_deserializer: U64VarIntDeserializer,
-    data_deserializer: ST,
-    phantom_t: PhantomData<T>,
-}
-
-impl<T, ST> VecDeserializer<T, ST>
-where
-    ST: Deserializer<T> + Clone,
-{
-    /// Creates a new `VecDeserializer`
-    pub const fn new(
-        data_deserializer: ST,
-        min_length: Bound<u64>,
-        max_length: Bound<u64>,
-    ) -> Self {
-        Self {
-            varint_u64_deserializer: U64VarIntDeserializer::new(min_length, max_length),
-            data_deserializer,
-            phantom_t: PhantomData,
-        }
-    }
-}
-
-impl<T, ST> Deserializer<Vec<T>> for VecDeserializer<T, ST>
-where
-    ST: Deserializer<T> + Clone,
-{
-    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
-        &self,
-        buffer: &'a [u8],
-    ) -> IResult<&'a [u8], Vec<T>, E> {
-        context("Failed Vec<_> deserialization", |input| {
-            length_count(
-                context("length", |input| {
-                    self.varint_u64_deserializer.deserialize(input)
-                }),
-                context("data", |input| self.data_deserializer.deserialize(input)),
-            )(input)
-        })
-        .parse(buffer)
-    }
-}
-
 /// Basic `PreHashSet<_>` serializer
 #[derive(Clone)]
 pub struct PreHashSetSerializer<T, ST>
@@ -668,96 +390,3 @@ impl Deserializer<BitVec<u8>> for BitVecDeserializer {
         .parse(buffer)
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use massa_serialization::DeserializeError;
-    use serial_test::serial;
-    use std::ops::Bound::Included;
-    #[test]
-    #[serial]
-    fn vec_u8() {
-        let vec: Vec<u8> = vec![9, 8, 7];
-        let vec_u8_serializer = VecU8Serializer::new();
-        let vec_u8_deserializer = VecU8Deserializer::new(Included(u64::MIN), Included(u64::MAX));
-        let mut serialized = Vec::new();
-        vec_u8_serializer.serialize(&vec, &mut serialized).unwrap();
-        let (rest, new_vec) = vec_u8_deserializer
-            .deserialize::<DeserializeError>(&serialized)
-            .unwrap();
-        assert!(rest.is_empty());
-        assert_eq!(vec, new_vec);
-    }
-
-    #[test]
-    #[serial]
-    fn vec_u8_big_length() {
-        let vec: Vec<u8> = vec![9, 8, 7];
-        let len: u64 = 10;
-        let mut serialized = Vec::new();
-        U64VarIntSerializer::new()
-            .serialize(&len, &mut serialized)
-            .unwrap();
-        serialized.extend(vec);
-        let vec_u8_deserializer = VecU8Deserializer::new(Included(u64::MIN), Included(u64::MAX));
-        let _ = vec_u8_deserializer
-            .deserialize::<DeserializeError>(&serialized)
-            .expect_err("Should fail too long size");
-    }
-
-    #[test]
-    #[serial]
-    fn vec_u8_min_length() {
-        let vec: Vec<u8> = vec![9, 8, 7];
-        let len: u64 = 1;
-        let mut serialized = Vec::new();
-        U64VarIntSerializer::new()
-            .serialize(&len, &mut serialized)
-            .unwrap();
-        serialized.extend(vec);
-        let vec_u8_deserializer = VecU8Deserializer::new(Included(u64::MIN), Included(u64::MAX));
-        let (rest, res) = vec_u8_deserializer
-            .deserialize::<DeserializeError>(&serialized)
-            .unwrap();
-        assert_eq!(rest, &[8, 7]);
-        assert_eq!(res, &[9])
-    }
-
-    #[test]
-    #[serial]
-    fn test_be_min() {
-        let x32 = 70_000u32;
-        let x64 = 10_000_000_000u64;
-
-        // serialize
-        let mut res: Vec<u8> = Vec::new();
-        res.extend(x32.to_be_bytes_min(70_001).unwrap());
-        assert_eq!(res.len(), 3);
-        res.extend(x64.to_be_bytes_min(10_000_000_001).unwrap());
-        assert_eq!(res.len(), 3 + 5);
-
-        // serialize fail bounds
-        assert!(x32.to_be_bytes_min(69_999).is_err());
-        assert!(x64.to_be_bytes_min(9_999_999_999).is_err());
-
-        // deserialize
-        let buf = res.as_slice();
-        let mut cursor = 0;
-        let (out_x32, delta) = u32::from_be_bytes_min(&buf[cursor..], 70_001).unwrap();
-        assert_eq!(out_x32, x32);
-        cursor += delta;
-        let (out_x64, delta) = u64::from_be_bytes_min(&buf[cursor..], 10_000_000_001).unwrap();
-        assert_eq!(out_x64, x64);
-        cursor += delta;
-        assert_eq!(cursor, buf.len());
-    }
-
-    #[test]
-    #[serial]
-    fn test_array_from_slice_with_zero_u64() {
-        let zero: u64 = 0;
-        let res = array_from_slice(&zero.to_be_bytes()).unwrap();
-        assert_eq!(zero, u64::from_be_bytes(res));
-    }
-}
@@ -187,6 +187,19 @@ impl Slot {
         Ok(Slot { period, thread: 0 })
     }
 
+    /// slot from its index since genesis, as used by `massa_time::SlotTicker`
+    pub fn from_index(index: u64, thread_count: u8) -> Slot {
+        Slot {
+            period: index / thread_count as u64,
+            thread: (index % thread_count as u64) as u8,
+        }
+    }
+
+    /// index of the slot since genesis, as used by `massa_time::SlotTicker`
+    pub fn to_index(&self, thread_count: u8) -> Result<u64, ModelsError> {
+        self.slots_since(&Slot::min(), thread_count)
+    }
+
     /// returns the minimal slot
     pub const fn min() -> Slot {
         Slot {
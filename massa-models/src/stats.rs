@@ -64,6 +64,11 @@ pub struct NetworkStats {
     pub banned_peer_count: u64,
     /// active node count
     pub active_node_count: u64,
+    /// result of the dial back test of our routable address (None if not concluded or no routable ip set)
+    pub reachable: Option<bool>,
+    /// median offset in milliseconds of the clocks of the connected peers relative to ours,
+    /// positive if the network is ahead (None if too few peers were sampled)
+    pub network_time_offset: Option<i64>,
 }
 
 impl std::fmt::Display for NetworkStats {
@@ -74,6 +79,15 @@ impl std::fmt::Display for NetworkStats {
         writeln!(f, "\tKnown peers: {}", self.known_peer_count)?;
         writeln!(f, "\tBanned peers: {}", self.banned_peer_count)?;
         writeln!(f, "\tActive nodes: {}", self.active_node_count)?;
+        match self.reachable {
+            Some(true) => writeln!(f, "\tRoutable address reachable: yes")?,
+            Some(false) => writeln!(f, "\tRoutable address reachable: no")?,
+            None => writeln!(f, "\tRoutable address reachable: unknown")?,
+        }
+        match self.network_time_offset {
+            Some(offset) => writeln!(f, "\tNetwork time offset: {} ms", offset)?,
+            None => writeln!(f, "\tNetwork time offset: unknown")?,
+        }
         Ok(())
     }
 }
@@ -1,5 +1,7 @@
 mod data;
 mod tools;
+mod wire_spec;
 
 pub use data::*;
 pub use tools::*;
+pub use wire_spec::*;
@@ -0,0 +1,91 @@
+//! Helpers shared by the wire spec generators and tests of the network and bootstrap messages
+
+use std::fmt::Debug;
+use std::fs::{create_dir_all, File};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::VERSION;
+
+/// Lists the variants of a message id enum along with their numeric identifier
+pub fn enum_ids<I, T>() -> Vec<Value>
+where
+    I: From<u8> + Copy + Serialize,
+    T: TryFrom<I> + Debug,
+{
+    (0u8..=u8::MAX)
+        .map(I::from)
+        .map_while(|id| T::try_from(id).ok().map(|v| (id, v)))
+        .map(|(id, v)| json!({ "name": format!("{:?}", v), "id": id }))
+        .collect()
+}
+
+/// Encodes bytes as a lowercase hex string
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes a hex string, panicking if it is malformed
+pub fn from_hex(hex: &str) -> Vec<u8> {
+    assert!(hex.len() % 2 == 0, "odd length hex string");
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("invalid hex string"))
+        .collect()
+}
+
+/// Encodes a value as an unsigned LEB128 varint, independently of the node's serializers
+/// so that the layout assertions check the encoding described by the spec
+pub fn varint(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Directory in which the spec artifacts are written
+pub fn wire_spec_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../target"))
+        .join("wire-spec")
+}
+
+/// Writes the `<name>-<version>.json` spec artifact and returns its path
+pub fn write_wire_spec(name: &str, spec: &Value) -> PathBuf {
+    let dir = wire_spec_dir();
+    create_dir_all(&dir).expect("could not create wire spec directory");
+    let path = dir.join(format!("{}-{}.json", name, *VERSION));
+    let file = File::create(&path).expect("could not create wire spec file");
+    serde_json::to_writer_pretty(file, spec).expect("could not write wire spec");
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        assert_eq!(varint(0), vec![0x00]);
+        assert_eq!(varint(127), vec![0x7f]);
+        assert_eq!(varint(128), vec![0x80, 0x01]);
+        assert_eq!(varint(300), vec![0xac, 0x02]);
+        assert_eq!(varint(u64::MAX).len(), 10);
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = vec![0x00, 0x01, 0xab, 0xff];
+        assert_eq!(to_hex(&bytes), "0001abff");
+        assert_eq!(from_hex(&to_hex(&bytes)), bytes);
+    }
+}
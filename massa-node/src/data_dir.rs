@@ -0,0 +1,279 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Layout of the data directory holding everything the node persists,
+//! so that backing up or restoring a node is a single-directory operation.
+//!
+//! Layout version 1, relative to the data directory:
+//! * `LAYOUT_VERSION`: version of the layout
+//! * `node_privkey.key`: node key (not the staking keys)
+//! * `staking_wallets/`: staking keys
+//! * `ledger/rocks_db/`: final state database
+//! * `cache/rocks_db/`: bytecode cache
+//! * `dump/blocks/`: dumped blocks
+//! * `protocol/block_wishlist.bin`: blocks being retrieved at the last shutdown
+//! * `protocol/ban_list.json`: bans set by the node operator
+//! * `protocol/peers_whitelist.json`: addresses exempted from the bans by the node operator
+//! * `protocol/peer_store.json`: known peers and their reputation
+//! * `archive/rocks_db/`: final blocks and operations kept by archival nodes
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+/// Current version of the data directory layout
+pub const DATA_LAYOUT_VERSION: u32 = 1;
+
+/// Name of the file holding the layout version
+const VERSION_FILE: &str = "LAYOUT_VERSION";
+
+/// Persisted artifacts, with their location in the data directory
+/// and their location before the data directory existed, relative to the node working directory
+const ARTIFACTS: [(&str, &str, &str); 5] = [
+    ("node key", "node_privkey.key", "config/node_privkey.key"),
+    ("staking keys", "staking_wallets", "config/staking_wallets"),
+    ("ledger", "ledger/rocks_db", "storage/ledger/rocks_db"),
+    ("bytecode cache", "cache/rocks_db", "storage/cache/rocks_db"),
+    ("block dumps", "dump/blocks", "dump/blocks"),
+];
+
+/// An artifact moved by a migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigratedArtifact {
+    /// description of the artifact
+    pub name: &'static str,
+    /// where it was
+    pub from: PathBuf,
+    /// where it is now
+    pub to: PathBuf,
+}
+
+/// Paths of the persisted artifacts inside the data directory
+#[derive(Debug, Clone)]
+pub struct DataLayout {
+    root: PathBuf,
+}
+
+impl DataLayout {
+    /// Layout rooted at `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        DataLayout { root: root.into() }
+    }
+
+    /// Path of the node key file
+    pub fn node_keypair_file(&self) -> PathBuf {
+        self.root.join(ARTIFACTS[0].1)
+    }
+
+    /// Path of the staking wallets folder
+    pub fn staking_wallet_path(&self) -> PathBuf {
+        self.root.join(ARTIFACTS[1].1)
+    }
+
+    /// Path of the disk ledger database
+    pub fn disk_ledger_path(&self) -> PathBuf {
+        self.root.join(ARTIFACTS[2].1)
+    }
+
+    /// Path of the bytecode cache database
+    pub fn hd_cache_path(&self) -> PathBuf {
+        self.root.join(ARTIFACTS[3].1)
+    }
+
+    /// Path of the block dump folder
+    pub fn block_dump_folder_path(&self) -> PathBuf {
+        self.root.join(ARTIFACTS[4].1)
+    }
+
+    /// Path of the file in which the block wishlist is saved on shutdown
+    pub fn block_wishlist_file(&self) -> PathBuf {
+        self.root.join("protocol/block_wishlist.bin")
+    }
+
+    /// Path of the file in which the bans set by the node operator are kept
+    pub fn ban_list_file(&self) -> PathBuf {
+        self.root.join("protocol/ban_list.json")
+    }
+
+    /// Path of the file in which the addresses exempted from the bans are kept
+    pub fn peers_whitelist_file(&self) -> PathBuf {
+        self.root.join("protocol/peers_whitelist.json")
+    }
+
+    /// Path of the file in which the known peers and their reputation are kept
+    pub fn peer_store_file(&self) -> PathBuf {
+        self.root.join("protocol/peer_store.json")
+    }
+
+    /// Path of the archive of final blocks kept by archival nodes
+    pub fn block_archive_path(&self) -> PathBuf {
+        self.root.join("archive/rocks_db")
+    }
+
+    /// Version of the layout found on disk, `None` if the data directory is not initialized
+    pub fn read_version(&self) -> anyhow::Result<Option<u32>> {
+        let version_file = self.root.join(VERSION_FILE);
+        if !version_file.exists() {
+            return Ok(None);
+        }
+        let version = std::fs::read_to_string(&version_file)
+            .with_context(|| format!("could not read {}", version_file.display()))?;
+        let version = version
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid layout version in {}", version_file.display()))?;
+        Ok(Some(version))
+    }
+
+    /// Makes the data directory ready for the node: initializes it if needed,
+    /// and refuses to start if artifacts are still at their locations relative to `legacy_root`
+    /// or if the layout needs a migration.
+    pub fn open(&self, legacy_root: &Path) -> anyhow::Result<()> {
+        match self.read_version()? {
+            Some(DATA_LAYOUT_VERSION) => Ok(()),
+            Some(version) if version > DATA_LAYOUT_VERSION => bail!(
+                "data directory {} has layout version {}, which is newer than the version {} supported by this node",
+                self.root.display(),
+                version,
+                DATA_LAYOUT_VERSION
+            ),
+            Some(version) => bail!(
+                "data directory {} has layout version {}: run `massa-node migrate-data` to upgrade it to version {}",
+                self.root.display(),
+                version,
+                DATA_LAYOUT_VERSION
+            ),
+            None => {
+                if let Some((name, _, legacy_path)) = legacy_artifacts(legacy_root).next() {
+                    bail!(
+                        "found the {} at its legacy location {}: run `massa-node migrate-data` to move it into the data directory {}",
+                        name,
+                        legacy_path.display(),
+                        self.root.display()
+                    );
+                }
+                self.write_version()
+            }
+        }
+    }
+
+    /// Moves the artifacts found at their locations relative to `legacy_root` into the data directory.
+    ///
+    /// Nothing is moved if one of the destinations is already taken.
+    pub fn migrate_from(&self, legacy_root: &Path) -> anyhow::Result<Vec<MigratedArtifact>> {
+        if let Some(version) = self.read_version()? {
+            if version > DATA_LAYOUT_VERSION {
+                bail!(
+                    "data directory {} has layout version {}, which is newer than the version {} supported by this node",
+                    self.root.display(),
+                    version,
+                    DATA_LAYOUT_VERSION
+                );
+            }
+        }
+
+        // check all destinations before moving anything, to never leave a half migrated node
+        let mut migrated = Vec::new();
+        for (name, path, from) in legacy_artifacts(legacy_root) {
+            let to = self.root.join(path);
+            if to.exists() {
+                bail!(
+                    "cannot move the {} from {}: {} already exists",
+                    name,
+                    from.display(),
+                    to.display()
+                );
+            }
+            migrated.push(MigratedArtifact { name, from, to });
+        }
+
+        for artifact in &migrated {
+            if let Some(parent) = artifact.to.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("could not create {}", parent.display()))?;
+            }
+            std::fs::rename(&artifact.from, &artifact.to).with_context(|| {
+                format!(
+                    "could not move the {} from {} to {}",
+                    artifact.name,
+                    artifact.from.display(),
+                    artifact.to.display()
+                )
+            })?;
+        }
+        self.write_version()?;
+        Ok(migrated)
+    }
+
+    /// Creates the data directory if needed and stamps it with the current layout version
+    fn write_version(&self) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.root)
+            .with_context(|| format!("could not create {}", self.root.display()))?;
+        let version_file = self.root.join(VERSION_FILE);
+        std::fs::write(&version_file, DATA_LAYOUT_VERSION.to_string())
+            .with_context(|| format!("could not write {}", version_file.display()))
+    }
+}
+
+/// Artifacts present at their legacy location relative to `legacy_root`:
+/// their description, their location in the data directory and their legacy path
+fn legacy_artifacts(
+    legacy_root: &Path,
+) -> impl Iterator<Item = (&'static str, &'static str, PathBuf)> + '_ {
+    ARTIFACTS.iter().filter_map(move |(name, path, legacy)| {
+        let legacy_path = legacy_root.join(legacy);
+        legacy_path.exists().then_some((*name, *path, legacy_path))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_data_from_legacy_locations() {
+        let legacy_root = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let layout = DataLayout::new(data_dir.path().join("data"));
+
+        std::fs::create_dir_all(legacy_root.path().join("config/staking_wallets")).unwrap();
+        std::fs::write(legacy_root.path().join("config/node_privkey.key"), "key").unwrap();
+        std::fs::create_dir_all(legacy_root.path().join("storage/ledger/rocks_db")).unwrap();
+
+        // the node refuses to start with data at the legacy locations
+        assert!(layout.open(legacy_root.path()).is_err());
+        assert_eq!(layout.read_version().unwrap(), None);
+
+        let migrated = layout.migrate_from(legacy_root.path()).unwrap();
+        assert_eq!(migrated.len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(layout.node_keypair_file()).unwrap(),
+            "key"
+        );
+        assert!(layout.staking_wallet_path().is_dir());
+        assert!(layout.disk_ledger_path().is_dir());
+        assert!(!legacy_root.path().join("config/node_privkey.key").exists());
+
+        assert_eq!(layout.read_version().unwrap(), Some(DATA_LAYOUT_VERSION));
+        layout.open(legacy_root.path()).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_data_never_overwrites() {
+        let legacy_root = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let layout = DataLayout::new(data_dir.path());
+
+        std::fs::create_dir_all(legacy_root.path().join("config/staking_wallets")).unwrap();
+        std::fs::write(legacy_root.path().join("config/node_privkey.key"), "old").unwrap();
+        std::fs::write(layout.node_keypair_file(), "new").unwrap();
+
+        assert!(layout.migrate_from(legacy_root.path()).is_err());
+        // nothing was moved
+        assert!(legacy_root.path().join("config/staking_wallets").exists());
+        assert_eq!(
+            std::fs::read_to_string(layout.node_keypair_file()).unwrap(),
+            "new"
+        );
+        assert_eq!(layout.read_version().unwrap(), None);
+    }
+}
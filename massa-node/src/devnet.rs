@@ -0,0 +1,196 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Local single-node network for smart contract developers (`massa-node --devnet`).
+//!
+//! A fresh directory is filled with a genesis funding a newly generated key,
+//! which also holds all the initial rolls and is the staking key of the node,
+//! so that the node produces every block on its own.
+//! The node is isolated: no bootstrap, no peers, every listener on localhost.
+//! The settings are passed to the node as a configuration override file.
+//! Only available in sandbox builds, whose genesis is set a few seconds after launch.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use massa_ledger_exports::LedgerEntry;
+use massa_models::{address::Address, amount::Amount, config::CHAINID};
+use massa_signature::KeyPair;
+use massa_wallet::Wallet;
+
+use crate::data_dir::DataLayout;
+
+/// Password of the staking wallet of a devnet, used when none is given
+pub const DEVNET_PASSWORD: &str = "devnet";
+
+/// Balance of the funded key at genesis, in MAS
+const DEVNET_BALANCE: &str = "1000000000";
+
+/// Rolls of the funded key at genesis
+const DEVNET_ROLLS: u64 = 100;
+
+/// Settings of the devnet, overriding the base configuration
+const DEVNET_CONFIG: &str = r#"
+[data]
+    data_dir = {data_dir}
+
+[api]
+    bind_private = "127.0.0.1:33034"
+    bind_public = "127.0.0.1:33035"
+    bind_api = "127.0.0.1:33036"
+
+[grpc.public]
+    bind = "127.0.0.1:33037"
+
+[grpc.private]
+    bind = "127.0.0.1:33038"
+
+[ledger]
+    initial_ledger_path = {initial_ledger_path}
+    initial_deferred_credits_path = {initial_deferred_credits_path}
+
+[selector]
+    initial_rolls_path = {initial_rolls_path}
+
+[protocol]
+    bind = "127.0.0.1:31244"
+    initial_peers_file = {initial_peers_file}
+
+[bootstrap]
+    bootstrap_list = []
+    bind = "127.0.0.1:31245"
+
+[factory]
+    stop_production_when_zero_connections = false
+
+[metrics]
+    enabled = false
+"#;
+
+/// Files of a prepared devnet
+pub struct Devnet {
+    /// key funded at genesis and staking all the initial rolls
+    pub keypair: KeyPair,
+    /// address of the funded key
+    pub address: Address,
+    /// configuration override to pass to the node
+    pub config_path: PathBuf,
+}
+
+/// Writes a string path as a TOML string
+fn toml_path(path: &Path) -> anyhow::Result<String> {
+    // JSON string escapes are valid TOML basic string escapes
+    Ok(serde_json::to_string(
+        path.to_str().context("devnet path is not valid UTF-8")?,
+    )?)
+}
+
+/// Prepares a devnet in `root`, which must not exist yet
+pub fn prepare_devnet(root: &Path, password: &str) -> anyhow::Result<Devnet> {
+    if root.exists() {
+        anyhow::bail!("devnet directory {} already exists", root.display());
+    }
+    let genesis_dir = root.join("genesis");
+    fs::create_dir_all(&genesis_dir)
+        .with_context(|| format!("cannot create devnet directory {}", root.display()))?;
+
+    let keypair = KeyPair::generate(0)?;
+    let address = Address::from_public_key(&keypair.get_public_key());
+
+    let initial_ledger: HashMap<Address, LedgerEntry> = HashMap::from([(
+        address,
+        LedgerEntry {
+            balance: DEVNET_BALANCE.parse::<Amount>()?,
+            ..Default::default()
+        },
+    )]);
+    let initial_rolls: BTreeMap<Address, u64> = BTreeMap::from([(address, DEVNET_ROLLS)]);
+    let initial_ledger_path = genesis_dir.join("initial_ledger.json");
+    let initial_rolls_path = genesis_dir.join("initial_rolls.json");
+    let initial_deferred_credits_path = genesis_dir.join("deferred_credits.json");
+    let initial_peers_file = genesis_dir.join("initial_peers.json");
+    fs::write(
+        &initial_ledger_path,
+        serde_json::to_vec_pretty(&initial_ledger)?,
+    )?;
+    fs::write(
+        &initial_rolls_path,
+        serde_json::to_vec_pretty(&initial_rolls)?,
+    )?;
+    fs::write(&initial_deferred_credits_path, "{}")?;
+    fs::write(&initial_peers_file, "{}")?;
+
+    // the funded key stakes on the node
+    let data_dir = root.join("data");
+    let data_layout = DataLayout::new(&data_dir);
+    data_layout.open(root)?;
+    let mut wallet = Wallet::new(
+        data_layout.staking_wallet_path(),
+        password.to_string(),
+        *CHAINID,
+    )?;
+    wallet.add_keypairs(vec![keypair.clone()])?;
+
+    let config = DEVNET_CONFIG
+        .replace("{data_dir}", &toml_path(&data_dir)?)
+        .replace("{initial_ledger_path}", &toml_path(&initial_ledger_path)?)
+        .replace(
+            "{initial_deferred_credits_path}",
+            &toml_path(&initial_deferred_credits_path)?,
+        )
+        .replace("{initial_rolls_path}", &toml_path(&initial_rolls_path)?)
+        .replace("{initial_peers_file}", &toml_path(&initial_peers_file)?);
+    let config_path = root.join("config.toml");
+    fs::write(&config_path, config)?;
+
+    Ok(Devnet {
+        keypair,
+        address,
+        config_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_prepare_devnet() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("devnet");
+        let devnet = prepare_devnet(&root, DEVNET_PASSWORD).unwrap();
+
+        let initial_ledger: HashMap<Address, LedgerEntry> =
+            serde_json::from_slice(&fs::read(root.join("genesis/initial_ledger.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            initial_ledger[&devnet.address].balance,
+            DEVNET_BALANCE.parse::<Amount>().unwrap()
+        );
+        let initial_rolls: BTreeMap<Address, u64> =
+            serde_json::from_slice(&fs::read(root.join("genesis/initial_rolls.json")).unwrap())
+                .unwrap();
+        assert_eq!(initial_rolls[&devnet.address], DEVNET_ROLLS);
+
+        // the funded key is the staking key of the node
+        let wallet = Wallet::new(
+            DataLayout::new(root.join("data")).staking_wallet_path(),
+            DEVNET_PASSWORD.to_string(),
+            *CHAINID,
+        )
+        .unwrap();
+        assert!(wallet.get_wallet_address_list().contains(&devnet.address));
+
+        let config = fs::read_to_string(&devnet.config_path).unwrap();
+        assert!(!config.contains('{'));
+        assert!(config.contains(root.join("data").to_str().unwrap()));
+
+        // a devnet is never prepared over existing files
+        assert!(prepare_devnet(&root, DEVNET_PASSWORD).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Verification of the initial ledger file against its published hash, so that a node is not
+//! launched from a tampered or truncated genesis ledger.
+//!
+//! The initial ledger is loaded into a temporary database exactly as it is at genesis,
+//! and its hash is the state hash of that database.
+
+use std::{path::Path, sync::Arc};
+
+use anyhow::bail;
+use massa_db_exports::{MassaDBConfig, MassaDBController, ShareableMassaDBController};
+use massa_db_worker::MassaDB;
+use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
+use massa_ledger_exports::{LedgerConfig, LedgerController};
+use massa_ledger_worker::FinalLedger;
+use massa_models::config::{MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH, THREAD_COUNT};
+use parking_lot::RwLock;
+
+/// State hash of the initial ledger file at `initial_ledger_path`
+pub fn initial_ledger_hash(
+    initial_ledger_path: &Path,
+) -> anyhow::Result<HashXof<HASH_XOF_SIZE_BYTES>> {
+    let db_dir = tempfile::tempdir()?;
+    let db: ShareableMassaDBController =
+        Arc::new(RwLock::new(Box::new(MassaDB::new(MassaDBConfig {
+            path: db_dir.path().to_path_buf(),
+            max_history_length: 0,
+            max_final_state_elements_size: 0,
+            max_versioning_elements_size: 0,
+            thread_count: THREAD_COUNT,
+            max_ledger_backups: 0,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
+        }))));
+    let mut ledger = FinalLedger::new(
+        LedgerConfig {
+            thread_count: THREAD_COUNT,
+            initial_ledger_path: initial_ledger_path.to_path_buf(),
+            max_key_length: MAX_DATASTORE_KEY_LENGTH,
+            max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            hot_cache_size: 0,
+        },
+        db.clone(),
+    );
+    ledger.load_initial_ledger()?;
+    let hash = db.read().get_xof_db_hash();
+    Ok(hash)
+}
+
+/// Check that the initial ledger file at `initial_ledger_path` has the `expected_hash` state hash
+pub fn verify_initial_ledger(
+    initial_ledger_path: &Path,
+    expected_hash: &str,
+) -> anyhow::Result<()> {
+    let hash = initial_ledger_hash(initial_ledger_path)?.to_string();
+    if hash != expected_hash {
+        bail!(
+            "the initial ledger {} has hash {} instead of the expected {}: it may have been tampered with or truncated",
+            initial_ledger_path.display(),
+            hash,
+            expected_hash
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_initial_ledger() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger_path = dir.path().join("initial_ledger.json");
+        std::fs::write(
+            &ledger_path,
+            r#"{"AU1126Ex9N1bxdz16uwySmDXzWrkowSq4qZik4pEh8KzDXumWoUV": {"balance": "1000", "bytecode": [], "datastore": []}}"#,
+        )
+        .unwrap();
+        let hash = initial_ledger_hash(&ledger_path).unwrap().to_string();
+        verify_initial_ledger(&ledger_path, &hash).unwrap();
+
+        // a modified ledger is refused
+        std::fs::write(
+            &ledger_path,
+            r#"{"AU1126Ex9N1bxdz16uwySmDXzWrkowSq4qZik4pEh8KzDXumWoUV": {"balance": "1001", "bytecode": [], "datastore": []}}"#,
+        )
+        .unwrap();
+        assert!(verify_initial_ledger(&ledger_path, &hash).is_err());
+
+        // a truncated ledger is refused
+        std::fs::write(&ledger_path, "{").unwrap();
+        assert!(verify_initial_ledger(&ledger_path, &hash).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Export of the ledger of a final state database as CSV, for supply audits and analytics.
+//!
+//! The ledger is streamed from the database in key order, one line per address:
+//! `address,balance,rolls,datastore_key_count`.
+//! The roll counts are the ones of the latest cycle of the proof-of-stake history.
+
+use std::{collections::BTreeMap, io::Write, ops::Bound::Included, path::Path};
+
+use anyhow::bail;
+use massa_db_exports::{
+    MassaDBConfig, MassaDBController, CYCLE_HISTORY_PREFIX, LEDGER_PREFIX, STATE_CF,
+};
+use massa_db_worker::MassaDB;
+use massa_ledger_exports::{Key as LedgerKey, KeyDeserializer, KeyType};
+use massa_models::{
+    address::{Address, AddressDeserializer},
+    amount::{Amount, AmountDeserializer},
+    config::{MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT},
+    slot::Slot,
+};
+use massa_pos_exports::ROLL_COUNT_IDENT;
+use massa_serialization::{DeserializeError, Deserializer, U64VarIntDeserializer};
+
+/// Totals of an exported ledger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerExportSummary {
+    /// final slot of the exported state
+    pub slot: Option<Slot>,
+    /// number of exported addresses
+    pub address_count: usize,
+    /// sum of the balances
+    pub total_balance: Amount,
+    /// sum of the roll counts
+    pub total_rolls: u64,
+}
+
+/// Exported line of the ledger
+struct LedgerRow {
+    address: Address,
+    balance: Amount,
+    rolls: u64,
+    datastore_key_count: u64,
+}
+
+impl LedgerRow {
+    fn new(address: Address) -> Self {
+        LedgerRow {
+            address,
+            balance: Amount::zero(),
+            rolls: 0,
+            datastore_key_count: 0,
+        }
+    }
+}
+
+/// Open the final state database at `db_path` and write its ledger as CSV to `writer`
+pub fn export_ledger(
+    db_path: &Path,
+    writer: &mut impl Write,
+) -> anyhow::Result<LedgerExportSummary> {
+    if !db_path.join("CURRENT").is_file() {
+        bail!("{} is not a final state database", db_path.display());
+    }
+    let db = MassaDB::new(MassaDBConfig {
+        path: db_path.to_path_buf(),
+        max_history_length: 0,
+        max_final_state_elements_size: 0,
+        max_versioning_elements_size: 0,
+        thread_count: THREAD_COUNT,
+        max_ledger_backups: 0,
+        disk_soft_quota: None,
+        disk_hard_quota: None,
+    });
+    write_ledger_csv(&db, writer)
+}
+
+/// Write the ledger of `db` as CSV to `writer`
+pub fn write_ledger_csv(
+    db: &MassaDB,
+    writer: &mut impl Write,
+) -> anyhow::Result<LedgerExportSummary> {
+    let key_deserializer = KeyDeserializer::new(MAX_DATASTORE_KEY_LENGTH, false);
+    let amount_deserializer = AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+    let mut rolls = final_roll_counts(db)?;
+    let mut summary = LedgerExportSummary {
+        slot: db.get_change_id().ok(),
+        address_count: 0,
+        total_balance: Amount::zero(),
+        total_rolls: 0,
+    };
+    writeln!(writer, "address,balance,rolls,datastore_key_count")?;
+    let mut write_row = |row: LedgerRow| -> anyhow::Result<()> {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            row.address, row.balance, row.rolls, row.datastore_key_count
+        )?;
+        summary.address_count += 1;
+        summary.total_balance = summary.total_balance.saturating_add(row.balance);
+        summary.total_rolls = summary.total_rolls.saturating_add(row.rolls);
+        Ok(())
+    };
+
+    // the entries of an address are contiguous as ledger keys start with the address
+    let mut current: Option<LedgerRow> = None;
+    for (key, value) in db.prefix_iterator_cf(STATE_CF, LEDGER_PREFIX.as_bytes()) {
+        if !key.starts_with(LEDGER_PREFIX.as_bytes()) {
+            break;
+        }
+        let (_, LedgerKey { address, key_type }) = key_deserializer
+            .deserialize::<DeserializeError>(&key)
+            .map_err(|e| anyhow::anyhow!("invalid ledger key: {}", e))?;
+        if current.as_ref().map(|row| row.address) != Some(address) {
+            if let Some(row) = current.take() {
+                write_row(row)?;
+            }
+            let mut row = LedgerRow::new(address);
+            row.rolls = rolls.remove(&address).unwrap_or_default();
+            current = Some(row);
+        }
+        let row = current.as_mut().expect("current ledger row set above");
+        match key_type {
+            KeyType::BALANCE => {
+                row.balance = amount_deserializer
+                    .deserialize::<DeserializeError>(&value)
+                    .map_err(|e| anyhow::anyhow!("invalid balance of {}: {}", address, e))?
+                    .1;
+            }
+            KeyType::DATASTORE(_) => row.datastore_key_count += 1,
+            KeyType::VERSION | KeyType::BYTECODE => {}
+        }
+    }
+    if let Some(row) = current.take() {
+        write_row(row)?;
+    }
+    // roll owners missing from the ledger
+    for (address, rolls) in rolls {
+        let mut row = LedgerRow::new(address);
+        row.rolls = rolls;
+        write_row(row)?;
+    }
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// Roll counts of the latest cycle of the proof-of-stake history
+fn final_roll_counts(db: &MassaDB) -> anyhow::Result<BTreeMap<Address, u64>> {
+    let address_deserializer = AddressDeserializer::new();
+    let u64_deserializer = U64VarIntDeserializer::new(Included(u64::MIN), Included(u64::MAX));
+    let mut latest_cycle = None;
+    let mut roll_counts = BTreeMap::new();
+    // cycle history keys are `prefix | cycle (big endian) | ident | ...`, sorted by cycle
+    for (key, value) in db.prefix_iterator_cf(STATE_CF, CYCLE_HISTORY_PREFIX.as_bytes()) {
+        let Some(rest) = key.strip_prefix(CYCLE_HISTORY_PREFIX.as_bytes()) else {
+            break;
+        };
+        let Some((cycle, [ident, address @ ..])) = rest
+            .split_first_chunk::<8>()
+            .map(|(cycle, rest)| (u64::from_be_bytes(*cycle), rest))
+        else {
+            bail!("invalid cycle history key");
+        };
+        if latest_cycle != Some(cycle) {
+            latest_cycle = Some(cycle);
+            roll_counts.clear();
+        }
+        if *ident != ROLL_COUNT_IDENT {
+            continue;
+        }
+        let (_, address) = address_deserializer
+            .deserialize::<DeserializeError>(address)
+            .map_err(|e| anyhow::anyhow!("invalid roll count key: {}", e))?;
+        let (_, count) = u64_deserializer
+            .deserialize::<DeserializeError>(&value)
+            .map_err(|e| anyhow::anyhow!("invalid roll count of {}: {}", address, e))?;
+        roll_counts.insert(address, count);
+    }
+    Ok(roll_counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_db_exports::DBBatch;
+    use massa_ledger_exports::KeySerializer;
+    use massa_models::amount::AmountSerializer;
+    use massa_serialization::{Serializer, U64VarIntSerializer};
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    fn ledger_key(address: &Address, key_type: KeyType) -> Vec<u8> {
+        let mut key = Vec::new();
+        KeySerializer::new(false)
+            .serialize(&LedgerKey::new(address, key_type), &mut key)
+            .unwrap();
+        key
+    }
+
+    fn roll_count_key(cycle: u64, address: &Address) -> Vec<u8> {
+        [
+            CYCLE_HISTORY_PREFIX.as_bytes(),
+            &cycle.to_be_bytes(),
+            &[ROLL_COUNT_IDENT],
+            &address.to_prefixed_bytes(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_write_ledger_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = MassaDB::new(MassaDBConfig {
+            path: dir.path().to_path_buf(),
+            max_history_length: 100,
+            max_final_state_elements_size: 100,
+            max_versioning_elements_size: 100,
+            thread_count: THREAD_COUNT,
+            max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
+        });
+        let holder = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let staker = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+
+        let mut balance = Vec::new();
+        AmountSerializer::new()
+            .serialize(&Amount::from_str("12.5").unwrap(), &mut balance)
+            .unwrap();
+        let rolls = |count: u64| {
+            let mut value = Vec::new();
+            U64VarIntSerializer::new()
+                .serialize(&count, &mut value)
+                .unwrap();
+            value
+        };
+        let mut batch = DBBatch::new();
+        for (key, value) in [
+            (ledger_key(&holder, KeyType::BALANCE), balance),
+            (
+                ledger_key(&holder, KeyType::DATASTORE(b"a".to_vec())),
+                vec![1],
+            ),
+            (
+                ledger_key(&holder, KeyType::DATASTORE(b"b".to_vec())),
+                vec![2],
+            ),
+            (roll_count_key(3, &holder), rolls(7)),
+            (roll_count_key(4, &holder), rolls(2)),
+            (roll_count_key(4, &staker), rolls(5)),
+        ] {
+            db.put_or_update_entry_value(&mut batch, key, &value);
+        }
+        db.write_batch(batch, DBBatch::new(), Some(Slot::new(1, 0)));
+
+        let mut csv = Vec::new();
+        let summary = write_ledger_csv(&db, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "address,balance,rolls,datastore_key_count");
+        assert_eq!(lines[1], format!("{},12.5,2,2", holder));
+        assert_eq!(lines[2], format!("{},0,5,0", staker));
+        assert_eq!(
+            summary,
+            LedgerExportSummary {
+                slot: Some(Slot::new(1, 0)),
+                address_count: 2,
+                total_balance: Amount::from_str("12.5").unwrap(),
+                total_rolls: 7,
+            }
+        );
+    }
+}
@@ -6,12 +6,13 @@
 extern crate massa_logging;
 
 #[cfg(feature = "op_spammer")]
+use crate::data_dir::DataLayout;
 use crate::operation_injector::start_operation_injector;
 use crate::settings::SETTINGS;
 use crate::survey::MassaSurvey;
 
 use cfg_if::cfg_if;
-use clap::{crate_version, Parser};
+use clap::{crate_version, Parser, Subcommand};
 use crossbeam_channel::TryRecvError;
 use dialoguer::Password;
 use massa_api::{ApiServer, ApiV2, Private, Public, RpcServer, StopHandle, API};
@@ -24,6 +25,7 @@ use massa_bootstrap::{
 };
 use massa_channel::receiver::MassaReceiver;
 use massa_channel::MassaChannel;
+use massa_consensus_exports::archive::BlockArchive;
 use massa_consensus_exports::events::ConsensusEvent;
 use massa_consensus_exports::{
     ConsensusBroadcasts, ConsensusChannels, ConsensusConfig, ConsensusManager,
@@ -58,16 +60,17 @@ use massa_models::address::Address;
 use massa_models::amount::Amount;
 use massa_models::config::constants::{
     ASYNC_MSG_CST_GAS_COST, BLOCK_REWARD, BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CHANNEL_SIZE,
-    CONSENSUS_BOOTSTRAP_PART_SIZE, DELTA_F0, DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT,
-    END_TIMESTAMP, GENESIS_KEY, GENESIS_TIMESTAMP, INITIAL_DRAW_SEED, LEDGER_COST_PER_BYTE,
+    CONSENSUS_BOOTSTRAP_PART_SIZE, DELETE_EMPTY_LEDGER_ENTRIES, DELTA_F0,
+    DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, ENDORSEMENT_RELAXATION_PERIODS, END_TIMESTAMP,
+    GENESIS_KEY, GENESIS_TIMESTAMP, INITIAL_DRAW_SEED, LEDGER_COST_PER_BYTE,
     LEDGER_ENTRY_BASE_COST, LEDGER_ENTRY_DATASTORE_BASE_SIZE, MAX_ADVERTISE_LENGTH, MAX_ASYNC_GAS,
-    MAX_ASYNC_POOL_LENGTH, MAX_BLOCK_SIZE, MAX_BOOTSTRAP_BLOCKS, MAX_BOOTSTRAP_ERROR_LENGTH,
-    MAX_BYTECODE_LENGTH, MAX_CONSENSUS_BLOCKS_IDS, MAX_DATASTORE_ENTRY_COUNT,
-    MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH,
-    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_DENUNCIATION_CHANGES_LENGTH,
-    MAX_ENDORSEMENTS_PER_MESSAGE, MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH,
-    MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT, MAX_LISTENERS_PER_PEER,
-    MAX_OPERATIONS_PER_BLOCK, MAX_OPERATIONS_PER_MESSAGE, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    MAX_ASYNC_POOL_LENGTH, MAX_BOOTSTRAP_BLOCKS, MAX_BOOTSTRAP_ERROR_LENGTH, MAX_BYTECODE_LENGTH,
+    MAX_CONSENSUS_BLOCKS_IDS, MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH,
+    MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+    MAX_DENUNCIATION_CHANGES_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE, MAX_EXECUTED_OPS_CHANGES_LENGTH,
+    MAX_EXECUTED_OPS_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_HEARTBEATS_PER_MESSAGE,
+    MAX_LEDGER_CHANGES_COUNT, MAX_LISTENERS_PER_PEER, MAX_NODE_LINKS_PER_MESSAGE,
+    MAX_OPERATIONS_PER_MESSAGE, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
     MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
     MAX_OPERATION_STORAGE_TIME, MAX_PARAMETERS_SIZE, MAX_PEERS_IN_ANNOUNCEMENT_LIST,
     MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, MAX_SIZE_CHANNEL_COMMANDS_CONNECTIVITY,
@@ -78,25 +81,29 @@ use massa_models::config::constants::{
     MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_ENDORSEMENTS,
     MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_OPERATIONS, MAX_SIZE_CHANNEL_NETWORK_TO_BLOCK_HANDLER,
     MAX_SIZE_CHANNEL_NETWORK_TO_ENDORSEMENT_HANDLER, MAX_SIZE_CHANNEL_NETWORK_TO_OPERATION_HANDLER,
-    MAX_SIZE_CHANNEL_NETWORK_TO_PEER_HANDLER, MIP_STORE_STATS_BLOCK_CONSIDERED,
+    MAX_SIZE_CHANNEL_NETWORK_TO_PEER_HANDLER, MAX_USER_AGENT_LENGTH, MIN_BLOCK_ENDORSEMENTS,
+    MIP_STORE_STATS_BLOCK_CONSIDERED, NAME_REGISTRATION_COST, NAME_REGISTRATION_PERIODS,
     OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, POS_MISS_RATE_DEACTIVATION_THRESHOLD,
     POS_SAVED_CYCLES, PROTOCOL_CONTROLLER_CHANNEL_SIZE, PROTOCOL_EVENT_CHANNEL_SIZE,
     ROLL_COUNT_TO_SLASH_ON_DENUNCIATION, ROLL_PRICE, SELECTOR_DRAW_CACHE_SIZE, T0, THREAD_COUNT,
     VERSION,
 };
 use massa_models::config::{
-    BASE_OPERATION_GAS_COST, CHAINID, KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+    ChainParametersSchedule, CHAINID, KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
     MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE, MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE,
     MAX_EVENT_DATA_SIZE, MAX_MESSAGE_SIZE, POOL_CONTROLLER_DENUNCIATIONS_CHANNEL_SIZE,
     POOL_CONTROLLER_ENDORSEMENTS_CHANNEL_SIZE, POOL_CONTROLLER_OPERATIONS_CHANNEL_SIZE,
 };
+use massa_models::prehash::PreHashMap;
 use massa_models::slot::Slot;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_pool_exports::{PoolBroadcasts, PoolChannels, PoolConfig, PoolManager};
 use massa_pool_worker::start_pool_controller;
 use massa_pos_exports::{PoSConfig, SelectorConfig, SelectorManager};
 use massa_pos_worker::start_selector_worker;
-use massa_protocol_exports::{ProtocolConfig, ProtocolManager, TransportType};
+use massa_protocol_exports::{
+    ProtocolConfig, ProtocolController, ProtocolEvent, ProtocolManager, TransportType,
+};
 use massa_protocol_worker::{create_protocol_controller, start_protocol_controller};
 use massa_signature::KeyPair;
 use massa_storage::Storage;
@@ -110,20 +117,33 @@ use parking_lot::RwLock;
 use settings::GrpcSettings;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Condvar, Mutex};
 use std::time::Duration;
 use std::{path::Path, process, sync::Arc};
 
+use staking_activation::{
+    start_staking_activation_tracker, StakingActivationConfig, StakingActivationStopper,
+};
 use survey::MassaSurveyStopper;
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::filter::{filter_fn, LevelFilter};
+use webhooks::{WebhookConfig, WebhookEvent, WebhookNotifier};
 
+mod data_dir;
+#[cfg(feature = "sandbox")]
+mod devnet;
+mod genesis_check;
+mod ledger_export;
 #[cfg(feature = "op_spammer")]
 mod operation_injector;
 mod settings;
+mod staking_activation;
+mod state_diff;
+mod supervisor;
 mod survey;
+mod webhooks;
 
 async fn launch(
     args: &Args,
@@ -131,6 +151,7 @@ async fn launch(
     sig_int_toggled: Arc<(Mutex<bool>, Condvar)>,
 ) -> (
     MassaReceiver<ConsensusEvent>,
+    MassaReceiver<ProtocolEvent>,
     Option<BootstrapManager>,
     Box<dyn ConsensusManager>,
     Box<dyn ExecutionManager>,
@@ -145,6 +166,10 @@ async fn launch(
     Option<massa_grpc::server::StopHandle>,
     MetricsStopper,
     MassaSurveyStopper,
+    StakingActivationStopper,
+    WebhookNotifier,
+    Box<dyn ProtocolController>,
+    Arc<RwLock<dyn FinalStateController>>,
 ) {
     let now = MassaTime::now();
 
@@ -163,6 +188,7 @@ async fn launch(
         initial_ledger_path: SETTINGS.ledger.initial_ledger_path.clone(),
         max_key_length: MAX_DATASTORE_KEY_LENGTH,
         max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+        hot_cache_size: SETTINGS.ledger.hot_cache_size,
     };
     let async_pool_config = AsyncPoolConfig {
         max_length: MAX_ASYNC_POOL_LENGTH,
@@ -217,28 +243,32 @@ async fn launch(
         SETTINGS.metrics.tick_delay.to_duration(),
     );
 
+    let data_layout = DataLayout::new(&SETTINGS.data.data_dir);
+
     // Remove current disk ledger if there is one and we don't want to restart from snapshot
     // NOTE: this is temporary, since we cannot currently handle bootstrap from remaining ledger
     if args.keep_ledger || args.restart_from_snapshot_at_period.is_some() {
         info!("Loading old ledger for next episode");
     } else {
-        if SETTINGS.ledger.disk_ledger_path.exists() {
-            std::fs::remove_dir_all(SETTINGS.ledger.disk_ledger_path.clone())
+        if data_layout.disk_ledger_path().exists() {
+            std::fs::remove_dir_all(data_layout.disk_ledger_path())
                 .expect("disk ledger delete failed");
         }
-        if SETTINGS.execution.hd_cache_path.exists() {
-            std::fs::remove_dir_all(SETTINGS.execution.hd_cache_path.clone())
+        if data_layout.hd_cache_path().exists() {
+            std::fs::remove_dir_all(data_layout.hd_cache_path())
                 .expect("disk hd cache delete failed");
         }
     }
 
     let db_config = MassaDBConfig {
-        path: SETTINGS.ledger.disk_ledger_path.clone(),
+        path: data_layout.disk_ledger_path(),
         max_history_length: SETTINGS.ledger.final_history_length,
         max_final_state_elements_size: MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE.try_into().unwrap(),
         max_versioning_elements_size: MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE.try_into().unwrap(),
         thread_count: THREAD_COUNT,
         max_ledger_backups: SETTINGS.ledger.max_ledger_backups,
+        disk_soft_quota: SETTINGS.ledger.disk_soft_quota,
+        disk_hard_quota: SETTINGS.ledger.disk_hard_quota,
     };
     let db = Arc::new(RwLock::new(
         Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -317,6 +347,9 @@ async fn launch(
 
     let mip_store = final_state.read().get_mip_store().clone();
 
+    // block limits of each chain parameters version known by this release
+    let chain_parameters = ChainParametersSchedule::default();
+
     let bootstrap_config: BootstrapConfig = BootstrapConfig {
         bootstrap_list: SETTINGS.bootstrap.bootstrap_list.clone(),
         bootstrap_protocol: SETTINGS.bootstrap.bootstrap_protocol,
@@ -343,13 +376,14 @@ async fn launch(
         randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
         thread_count: THREAD_COUNT,
         periods_per_cycle: PERIODS_PER_CYCLE,
+        t0: T0,
         endorsement_count: ENDORSEMENT_COUNT,
         max_advertise_length: MAX_ADVERTISE_LENGTH,
         max_bootstrap_blocks_length: MAX_BOOTSTRAP_BLOCKS,
         max_bootstrap_error_length: MAX_BOOTSTRAP_ERROR_LENGTH,
         max_final_state_elements_size: MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE,
         max_versioning_elements_size: MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE,
-        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        max_operations_per_block: chain_parameters.upper_bounds().max_operations_per_block,
         max_datastore_entry_count: MAX_DATASTORE_ENTRY_COUNT,
         max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
         max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
@@ -375,7 +409,7 @@ async fn launch(
     let bootstrap_state = match get_state(
         &bootstrap_config,
         final_state.clone(),
-        DefaultConnector,
+        DefaultConnector::new(SETTINGS.network.socks5_proxy),
         *VERSION,
         *GENESIS_TIMESTAMP,
         *END_TIMESTAMP,
@@ -461,7 +495,7 @@ async fn launch(
     )
     .expect("Failed to load gas costs");
 
-    let block_dump_folder_path = SETTINGS.block_dump.block_dump_folder_path.clone();
+    let block_dump_folder_path = data_layout.block_dump_folder_path();
     if !block_dump_folder_path.exists() {
         info!("Current folder: {:?}", std::env::current_dir().unwrap());
         info!("Creating dump folder: {:?}", block_dump_folder_path);
@@ -476,8 +510,10 @@ async fn launch(
         cursor_delay: SETTINGS.execution.cursor_delay,
         max_async_gas: MAX_ASYNC_GAS,
         async_msg_cst_gas_cost: ASYNC_MSG_CST_GAS_COST,
-        max_gas_per_block: MAX_GAS_PER_BLOCK,
+        chain_parameters,
         roll_price: ROLL_PRICE,
+        name_registration_cost: NAME_REGISTRATION_COST,
+        name_registration_periods: NAME_REGISTRATION_PERIODS,
         thread_count: THREAD_COUNT,
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
@@ -492,10 +528,10 @@ async fn launch(
         max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
         storage_costs_constants,
         max_read_only_gas: SETTINGS.execution.max_read_only_gas,
+        max_execution_duration: SETTINGS.execution.max_execution_duration,
         gas_costs: gas_costs.clone(),
-        base_operation_gas_cost: BASE_OPERATION_GAS_COST,
         last_start_period: final_state.read().get_last_start_period(),
-        hd_cache_path: SETTINGS.execution.hd_cache_path.clone(),
+        hd_cache_path: data_layout.hd_cache_path(),
         lru_cache_size: SETTINGS.execution.lru_cache_size,
         hd_cache_size: SETTINGS.execution.hd_cache_size,
         snip_amount: SETTINGS.execution.snip_amount,
@@ -518,6 +554,8 @@ async fn launch(
             .broadcast_slot_execution_traces_channel_capacity,
         max_execution_traces_slot_limit: SETTINGS.execution.execution_traces_limit,
         block_dump_folder_path,
+        delete_empty_ledger_entries: DELETE_EMPTY_LEDGER_ENTRIES,
+        rewards_history_cycles: SETTINGS.execution.rewards_history_cycles,
     };
 
     let execution_channels = ExecutionChannels {
@@ -563,16 +601,17 @@ async fn launch(
     // launch pool controller
     let pool_config = PoolConfig {
         thread_count: THREAD_COUNT,
-        max_block_size: MAX_BLOCK_SIZE,
-        max_block_gas: MAX_GAS_PER_BLOCK,
-        base_operation_gas_cost: BASE_OPERATION_GAS_COST,
+        chain_parameters,
         sp_compilation_cost: gas_costs.sp_compilation_cost,
         roll_price: ROLL_PRICE,
         max_block_endorsement_count: ENDORSEMENT_COUNT,
         operation_validity_periods: OPERATION_VALIDITY_PERIODS,
-        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
         max_operation_pool_size: SETTINGS.pool.max_operation_pool_size,
         max_operation_pool_excess_items: SETTINGS.pool.max_operation_pool_excess_items,
+        max_scheduled_operations: SETTINGS.pool.max_scheduled_operations,
+        max_scheduled_operations_per_creator: SETTINGS.pool.max_scheduled_operations_per_creator,
+        max_scheduled_operations_size: SETTINGS.pool.max_scheduled_operations_size,
+        max_scheduled_start_periods: SETTINGS.pool.max_scheduled_start_periods,
         operation_pool_refresh_interval: SETTINGS.pool.operation_pool_refresh_interval,
         operation_max_future_start_delay: SETTINGS.pool.operation_max_future_start_delay,
         max_endorsements_pool_size_per_thread: SETTINGS.pool.max_endorsements_pool_size_per_thread,
@@ -611,20 +650,55 @@ async fn launch(
         &shared_storage,
         pool_channels.clone(),
         node_wallet.clone(),
+        mip_store.clone(),
     );
 
     // launch protocol controller
     let mut listeners = HashMap::default();
     listeners.insert(SETTINGS.protocol.bind, TransportType::Tcp);
+    if let Some(bind_ipv6) = SETTINGS.protocol.bind_ipv6 {
+        listeners.insert(bind_ipv6, TransportType::Tcp);
+    }
+    for bind in &SETTINGS.protocol.additional_binds {
+        listeners.insert(*bind, TransportType::Tcp);
+    }
+    if let Some(bind_quic) = SETTINGS.protocol.bind_quic {
+        if !cfg!(feature = "quic") {
+            warn!("`bind_quic` is ignored: the node was built without the `quic` feature");
+        } else if listeners.insert(bind_quic, TransportType::Quic).is_some() {
+            panic!("`bind_quic` must use a port different from the TCP listeners");
+        }
+    }
+    let advertised_listeners = SETTINGS.protocol.advertised_binds.as_ref().map(|binds| {
+        binds
+            .iter()
+            .map(|bind| {
+                if !listeners.contains_key(bind) {
+                    panic!("advertised bind {} is not a listened address", bind);
+                }
+                *bind
+            })
+            .collect()
+    });
     let protocol_config = ProtocolConfig {
         thread_count: THREAD_COUNT,
         ask_block_timeout: SETTINGS.protocol.ask_block_timeout,
+        ask_block_max_timeout: SETTINGS.protocol.ask_block_max_timeout,
+        max_ask_block_failures_per_node: SETTINGS.protocol.max_ask_block_failures_per_node,
+        max_parallel_block_asks: SETTINGS.protocol.max_parallel_block_asks,
+        block_wishlist_file: data_layout.block_wishlist_file(),
+        block_wishlist_max_age: SETTINGS.protocol.block_wishlist_max_age,
+        ban_list_file: data_layout.ban_list_file(),
+        peers_whitelist_file: data_layout.peers_whitelist_file(),
+        peer_store_file: data_layout.peer_store_file(),
+        peer_store_max_records: SETTINGS.protocol.peer_store_max_records,
         max_known_blocks_size: SETTINGS.protocol.max_known_blocks_size,
         max_node_known_blocks_size: SETTINGS.protocol.max_node_known_blocks_size,
         max_block_propagation_time: SETTINGS.protocol.max_block_propagation_time,
         max_node_wanted_blocks_size: SETTINGS.protocol.max_node_wanted_blocks_size,
         max_known_ops_size: SETTINGS.protocol.max_known_ops_size,
         max_node_known_ops_size: SETTINGS.protocol.max_node_known_ops_size,
+        node_known_ops_false_positive_rate: SETTINGS.protocol.node_known_ops_false_positive_rate,
         max_known_endorsements_size: SETTINGS.protocol.max_known_endorsements_size,
         max_node_known_endorsements_size: SETTINGS.protocol.max_node_known_endorsements_size,
         max_simultaneous_ask_blocks_per_node: SETTINGS
@@ -637,9 +711,11 @@ async fn launch(
             .operation_announcement_buffer_capacity,
         operation_batch_proc_period: SETTINGS.protocol.operation_batch_proc_period,
         operation_announcement_interval: SETTINGS.protocol.operation_announcement_interval,
+        operation_send_interval: SETTINGS.protocol.operation_send_interval,
         max_operations_per_message: SETTINGS.protocol.max_operations_per_message,
-        max_serialized_operations_size_per_block: MAX_BLOCK_SIZE as usize,
-        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        compress_block_operations: SETTINGS.protocol.compress_block_operations,
+        compress_operation_messages: SETTINGS.protocol.compress_operation_messages,
+        chain_parameters,
         controller_channel_size: PROTOCOL_CONTROLLER_CHANNEL_SIZE,
         event_channel_size: PROTOCOL_EVENT_CHANNEL_SIZE,
         genesis_timestamp: *GENESIS_TIMESTAMP,
@@ -648,13 +724,17 @@ async fn launch(
         max_message_size: MAX_MESSAGE_SIZE as usize,
         max_ops_kept_for_propagation: SETTINGS.protocol.max_ops_kept_for_propagation,
         max_operations_propagation_time: SETTINGS.protocol.max_operations_propagation_time,
+        propagation_fanout: SETTINGS.protocol.propagation_fanout,
+        peer_knowledge_retention: SETTINGS.protocol.peer_knowledge_retention,
         max_endorsements_propagation_time: SETTINGS.protocol.max_endorsements_propagation_time,
+        endorsement_propagation_interval: SETTINGS.protocol.endorsement_propagation_interval,
         last_start_period: final_state.read().get_last_start_period(),
         max_endorsements_per_message: MAX_ENDORSEMENTS_PER_MESSAGE as u64,
         max_denunciations_in_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
         initial_peers: SETTINGS.protocol.initial_peers_file.clone(),
         listeners,
-        keypair_file: SETTINGS.protocol.keypair_file.clone(),
+        advertised_listeners,
+        keypair_file: data_layout.node_keypair_file(),
         max_blocks_kept_for_propagation: SETTINGS.protocol.max_blocks_kept_for_propagation,
         block_propagation_tick: SETTINGS.protocol.block_propagation_tick,
         asked_operations_buffer_capacity: SETTINGS.protocol.asked_operations_buffer_capacity,
@@ -686,31 +766,76 @@ async fn launch(
         max_size_function_name: MAX_FUNCTION_NAME_LENGTH,
         max_size_call_sc_parameter: MAX_PARAMETERS_SIZE,
         max_size_listeners_per_peer: MAX_LISTENERS_PER_PEER,
+        max_user_agent_length: MAX_USER_AGENT_LENGTH,
         max_size_peers_announcement: MAX_PEERS_IN_ANNOUNCEMENT_LIST,
         read_write_limit_bytes_per_second: SETTINGS.protocol.read_write_limit_bytes_per_second
             as u128,
         try_connection_timer: SETTINGS.protocol.try_connection_timer,
         unban_everyone_timer: SETTINGS.protocol.unban_everyone_timer,
         max_in_connections: SETTINGS.protocol.max_in_connections,
+        max_handshakes_per_ip_per_minute: SETTINGS.protocol.max_handshakes_per_ip_per_minute,
+        handshake_cooldown: SETTINGS.protocol.handshake_cooldown,
         timeout_connection: SETTINGS.protocol.timeout_connection,
+        dns_timeout: SETTINGS.network.dns_timeout,
+        dial_timeout: SETTINGS.network.dial_timeout,
+        prefer_ipv6: SETTINGS.network.prefer_ipv6,
+        socks5_proxy: SETTINGS.network.socks5_proxy,
         message_timeout: SETTINGS.protocol.message_timeout,
         tester_timeout: SETTINGS.protocol.tester_timeout,
         routable_ip: SETTINGS
             .protocol
             .routable_ip
             .or(SETTINGS.network.routable_ip),
+        routable_ipv6: SETTINGS.protocol.routable_ipv6,
+        nat_traversal: SETTINGS.protocol.nat_traversal,
+        nat_gateway: SETTINGS.protocol.nat_gateway,
+        nat_mapping_lifetime: SETTINGS.protocol.nat_mapping_lifetime,
         debug: false,
         peers_categories: SETTINGS.protocol.peers_categories.clone(),
         default_category_info: SETTINGS.protocol.default_category_info,
         version: *VERSION,
+        user_agent: format!("massa-node/{}", *VERSION),
         try_connection_timer_same_peer: SETTINGS.protocol.try_connection_timer_same_peer,
         test_oldest_peer_cooldown: SETTINGS.protocol.test_oldest_peer_cooldown,
         rate_limit: SETTINGS.protocol.rate_limit,
         chain_id: *CHAINID,
+        max_heartbeats_per_message: MAX_HEARTBEATS_PER_MESSAGE,
+        heartbeat_validity: SETTINGS.protocol.heartbeat_validity,
+        max_known_heartbeats: SETTINGS.protocol.max_known_heartbeats,
+        max_node_links_per_message: MAX_NODE_LINKS_PER_MESSAGE,
+        node_link_validity: SETTINGS.protocol.node_link_validity,
+        max_known_node_links: SETTINGS.protocol.max_known_node_links,
+        slow_message_threshold: SETTINGS.protocol.slow_message_threshold,
+        max_sync_lag_for_gossip: SETTINGS.protocol.max_sync_lag_for_gossip,
+        pool_warmup_enabled: SETTINGS.protocol.pool_warmup_enabled,
+        pending_operations_request_min_interval: SETTINGS
+            .protocol
+            .pending_operations_request_min_interval,
+        pending_operations_sync_enabled: SETTINGS.protocol.pending_operations_sync_enabled,
+        max_header_future_time: SETTINGS.protocol.max_header_future_time,
+        max_header_past_periods: SETTINGS.protocol.max_header_past_periods,
+        max_out_of_bounds_headers_per_peer: SETTINGS.protocol.max_out_of_bounds_headers_per_peer,
+        invalid_items_ban_threshold: SETTINGS.protocol.invalid_items_ban_threshold,
+        invalid_items_window: SETTINGS.protocol.invalid_items_window,
+        operations_rate_limit: SETTINGS.protocol.operations_rate_limit,
+        endorsements_rate_limit: SETTINGS.protocol.endorsements_rate_limit,
+        peer_discovery_enabled: SETTINGS.protocol.peer_discovery_enabled,
+        peer_discovery_bootstrap_nodes: SETTINGS.protocol.peer_discovery_bootstrap_nodes.clone(),
+        peer_discovery_bucket_size: SETTINGS.protocol.peer_discovery_bucket_size,
+        peer_discovery_refresh_interval: SETTINGS.protocol.peer_discovery_refresh_interval,
+        contribution_half_life: SETTINGS.protocol.contribution_half_life,
+        contribution_protected_peers: SETTINGS.protocol.contribution_protected_peers,
+        contribution_eviction_interval: SETTINGS.protocol.contribution_eviction_interval,
+        net_split_window: SETTINGS.protocol.net_split_window,
+        net_split_min_final_blocks: SETTINGS.protocol.net_split_min_final_blocks,
+        net_split_min_origin_peers: SETTINGS.protocol.net_split_min_origin_peers,
+        net_split_min_subnets: SETTINGS.protocol.net_split_min_subnets,
+        ping_interval: SETTINGS.protocol.ping_interval,
     };
 
     let (protocol_controller, protocol_channels) =
         create_protocol_controller(protocol_config.clone());
+    let protocol_event_receiver = protocol_channels.protocol_events.1.clone();
 
     let consensus_config = ConsensusConfig {
         genesis_timestamp: *GENESIS_TIMESTAMP,
@@ -727,8 +852,9 @@ async fn launch(
         stats_timespan: SETTINGS.consensus.stats_timespan,
         force_keep_final_periods: SETTINGS.consensus.force_keep_final_periods,
         endorsement_count: ENDORSEMENT_COUNT,
+        min_block_endorsements: MIN_BLOCK_ENDORSEMENTS,
+        endorsement_relaxation_periods: ENDORSEMENT_RELAXATION_PERIODS,
         block_db_prune_interval: SETTINGS.consensus.block_db_prune_interval,
-        max_gas_per_block: MAX_GAS_PER_BLOCK,
         channel_size: CHANNEL_SIZE,
         bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
         broadcast_enabled: SETTINGS.api.enable_broadcast,
@@ -744,8 +870,17 @@ async fn launch(
             .consensus
             .force_keep_final_periods_without_ops,
         chain_id: *CHAINID,
+        equivocation_history_periods: DENUNCIATION_EXPIRE_PERIODS,
+        max_equivocation_records: SETTINGS.consensus.max_equivocation_records,
+        max_equivocation_future_periods: SETTINGS.consensus.max_equivocation_future_periods,
     };
 
+    // archival nodes keep every final block and operation on disk
+    let block_archive = SETTINGS
+        .consensus
+        .archival
+        .then(|| BlockArchive::new(data_layout.block_archive_path()));
+
     let (consensus_event_sender, consensus_event_receiver) =
         MassaChannel::new("consensus_event".to_string(), Some(CHANNEL_SIZE));
     let consensus_channels = ConsensusChannels {
@@ -765,6 +900,7 @@ async fn launch(
             )
             .0,
         },
+        block_archive: block_archive.clone(),
     };
 
     let (consensus_controller, consensus_manager) = start_consensus_worker(
@@ -794,9 +930,9 @@ async fn launch(
         genesis_timestamp: *GENESIS_TIMESTAMP,
         t0: T0,
         initial_delay: SETTINGS.factory.initial_delay,
-        max_block_size: MAX_BLOCK_SIZE as u64,
-        max_block_gas: MAX_GAS_PER_BLOCK,
-        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        chain_parameters,
+        min_block_endorsements: MIN_BLOCK_ENDORSEMENTS,
+        endorsement_relaxation_periods: ENDORSEMENT_RELAXATION_PERIODS,
         last_start_period: final_state.read().get_last_start_period(),
         periods_per_cycle: PERIODS_PER_CYCLE,
         denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
@@ -804,6 +940,8 @@ async fn launch(
             .factory
             .stop_production_when_zero_connections,
         chain_id: *CHAINID,
+        heartbeat_interval: SETTINGS.factory.heartbeat_interval,
+        node_link_interval: SETTINGS.factory.node_link_interval,
     };
     let factory_channels = FactoryChannels {
         selector: selector_controller.clone(),
@@ -812,9 +950,12 @@ async fn launch(
         protocol: protocol_controller.clone(),
         storage: shared_storage.clone(),
     };
+    let maintenance_mode = Arc::new(AtomicBool::new(SETTINGS.factory.maintenance_mode));
     let factory_manager = start_factory(
         factory_config,
         node_wallet.clone(),
+        keypair.clone(),
+        maintenance_mode.clone(),
         factory_channels,
         mip_store.clone(),
     );
@@ -865,8 +1006,6 @@ async fn launch(
         max_op_datastore_entry_count: MAX_OPERATION_DATASTORE_ENTRY_COUNT,
         max_op_datastore_key_length: MAX_OPERATION_DATASTORE_KEY_LENGTH,
         max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
-        max_gas_per_block: MAX_GAS_PER_BLOCK,
-        base_operation_gas_cost: BASE_OPERATION_GAS_COST,
         sp_compilation_cost: gas_costs.sp_compilation_cost,
         max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
         max_parameter_size: MAX_PARAMETERS_SIZE,
@@ -879,6 +1018,10 @@ async fn launch(
         chain_id: *CHAINID,
         deferred_credits_delta: SETTINGS.api.deferred_credits_delta,
         minimal_fees: SETTINGS.pool.minimal_fees,
+        roll_price: ROLL_PRICE,
+        chain_parameters,
+        network_load_periods: SETTINGS.api.network_load_periods,
+        final_history_length: SETTINGS.ledger.final_history_length,
     };
 
     // spawn Massa API
@@ -886,6 +1029,7 @@ async fn launch(
         consensus_controller.clone(),
         consensus_channels.broadcasts.clone(),
         execution_controller.clone(),
+        execution_channels.clone(),
         pool_channels.broadcasts.clone(),
         api_config.clone(),
         *VERSION,
@@ -912,6 +1056,7 @@ async fn launch(
             keypair.clone(),
             &final_state,
             SETTINGS.pool.minimal_fees,
+            chain_parameters,
         );
 
         let grpc_public_api = MassaPublicGrpc {
@@ -953,6 +1098,7 @@ async fn launch(
             keypair.clone(),
             &final_state,
             SETTINGS.pool.minimal_fees,
+            chain_parameters,
         );
 
         let bs_white_black_list = bootstrap_manager
@@ -999,6 +1145,37 @@ async fn launch(
         args.nb_op,
     );
 
+    // notify the selected node events to the webhooks
+    let webhook_notifier = WebhookNotifier::new(
+        WebhookConfig {
+            urls: SETTINGS.webhooks.urls.clone(),
+            events: SETTINGS.webhooks.events.clone(),
+            min_reorg_depth: SETTINGS.webhooks.min_reorg_depth,
+            missed_draws_threshold: SETTINGS.webhooks.missed_draws_threshold,
+            max_retries: SETTINGS.webhooks.max_retries,
+            retry_delay: SETTINGS.webhooks.retry_delay,
+            timeout: SETTINGS.webhooks.timeout,
+        },
+        keypair.clone(),
+        tokio::runtime::Handle::current(),
+    );
+
+    // track the activation state of the staking addresses
+    let staking_activations = Arc::new(RwLock::new(PreHashMap::default()));
+    let staking_activation_stopper = start_staking_activation_tracker(
+        StakingActivationConfig {
+            thread_count: api_config.thread_count,
+            t0: api_config.t0,
+            genesis_timestamp: api_config.genesis_timestamp,
+            periods_per_cycle: api_config.periods_per_cycle,
+            last_start_period: api_config.last_start_period,
+        },
+        node_wallet.clone(),
+        execution_controller.clone(),
+        staking_activations.clone(),
+        webhook_notifier.clone(),
+    );
+
     // spawn private API
     let api_private = API::<Private>::new(
         protocol_controller.clone(),
@@ -1006,6 +1183,9 @@ async fn launch(
         api_config.clone(),
         sig_int_toggled,
         node_wallet,
+        maintenance_mode.clone(),
+        staking_activations,
+        massa_metrics.clone(),
     );
     let api_private_handle = api_private
         .serve(&SETTINGS.api.bind_private, &api_config)
@@ -1029,6 +1209,8 @@ async fn launch(
         node_id,
         shared_storage.clone(),
         mip_store.clone(),
+        maintenance_mode,
+        block_archive,
     );
     let api_public_handle = api_public
         .serve(&SETTINGS.api.bind_public, &api_config)
@@ -1044,6 +1226,7 @@ async fn launch(
         execution_controller,
         pool_controller,
         massa_metrics,
+        tokio::runtime::Handle::current(),
         (
             api_config.thread_count,
             api_config.t0,
@@ -1084,6 +1267,7 @@ async fn launch(
     }
     (
         consensus_event_receiver,
+        protocol_event_receiver,
         bootstrap_manager,
         consensus_manager,
         execution_manager,
@@ -1098,6 +1282,10 @@ async fn launch(
         grpc_public_handle,
         metrics_stopper,
         massa_survey_stopper,
+        staking_activation_stopper,
+        webhook_notifier,
+        protocol_controller,
+        final_state,
     )
 }
 
@@ -1108,6 +1296,7 @@ fn configure_grpc(
     keypair: KeyPair,
     final_state: &Arc<RwLock<dyn FinalStateController>>,
     minimal_fees: Amount,
+    chain_parameters: ChainParametersSchedule,
 ) -> GrpcConfig {
     GrpcConfig {
         name,
@@ -1138,7 +1327,7 @@ fn configure_grpc(
         http2_adaptive_window: settings.http2_adaptive_window,
         max_frame_size: settings.max_frame_size,
         thread_count: THREAD_COUNT,
-        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        chain_parameters,
         endorsement_count: ENDORSEMENT_COUNT,
         max_endorsements_per_message: MAX_ENDORSEMENTS_PER_MESSAGE,
         max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
@@ -1149,7 +1338,6 @@ fn configure_grpc(
         max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
         max_parameter_size: MAX_PARAMETERS_SIZE,
         max_operations_per_message: MAX_OPERATIONS_PER_MESSAGE,
-        max_gas_per_block: MAX_GAS_PER_BLOCK,
         genesis_timestamp: *GENESIS_TIMESTAMP,
         t0: T0,
         periods_per_cycle: PERIODS_PER_CYCLE,
@@ -1207,6 +1395,7 @@ async fn stop(
     grpc_public_handle: Option<massa_grpc::server::StopHandle>,
     mut metrics_stopper: MetricsStopper,
     mut massa_survey_stopper: MassaSurveyStopper,
+    mut staking_activation_stopper: StakingActivationStopper,
 ) {
     // stop bootstrap
     if let Some(bootstrap_manager) = bootstrap_manager {
@@ -1247,6 +1436,9 @@ async fn stop(
     // stop massa survey thread
     massa_survey_stopper.stop();
 
+    // stop staking activation tracking thread
+    staking_activation_stopper.stop();
+
     // stop factory
     factory_manager.stop();
 
@@ -1286,6 +1478,11 @@ struct Args {
     #[arg(long = "restart-from-snapshot-at-period")]
     restart_from_snapshot_at_period: Option<u64>,
 
+    #[cfg(feature = "sandbox")]
+    /// Start a local single-node network with a funded key, for smart contract development
+    #[arg(long = "devnet")]
+    devnet: bool,
+
     #[cfg(feature = "op_spammer")]
     /// number of operations
     #[arg(
@@ -1306,6 +1503,146 @@ struct Args {
         default_value = "10"
     )]
     dl_interval: u64,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Move the files of a node using the locations of older versions into the data directory
+    MigrateData {
+        /// Directory the older node was run from
+        #[arg(long = "from", default_value = ".")]
+        from: PathBuf,
+    },
+    /// Compare two final state snapshots (databases of stopped nodes or their backups) and report where they diverge
+    DiffState {
+        /// Final state database of the first node
+        left: PathBuf,
+        /// Final state database of the second node
+        right: PathBuf,
+        /// Maximum number of divergent entries listed for each part of the final state
+        #[arg(long = "max-entries", default_value_t = 20)]
+        max_entries: usize,
+        /// Print the diff as JSON
+        #[arg(long = "json")]
+        json: bool,
+    },
+    /// Export the ledger of a final state database (address, balance, rolls, datastore key count) as CSV
+    ExportLedger {
+        /// CSV file to write, `-` for the standard output
+        output: PathBuf,
+        /// Final state database to export (a stopped node database or one of its backups),
+        /// the database of the node by default
+        #[arg(long = "db")]
+        db: Option<PathBuf>,
+    },
+    /// Hash the initial ledger file and compare it to the expected hash of the configuration
+    VerifyGenesis {
+        /// Initial ledger file to hash, the one of the configuration by default
+        #[arg(long = "ledger")]
+        ledger: Option<PathBuf>,
+        /// Expected hash, the one of the configuration by default
+        #[arg(long = "expected-hash")]
+        expected_hash: Option<String>,
+    },
+}
+
+/// Move the files of an older node into the data directory, then exit
+fn migrate_data(from: &Path) -> anyhow::Result<()> {
+    let data_layout = DataLayout::new(&SETTINGS.data.data_dir);
+    let migrated = data_layout.migrate_from(from)?;
+    if migrated.is_empty() {
+        println!("Nothing to migrate from {}", from.display());
+    }
+    for artifact in migrated {
+        println!(
+            "Moved the {} from {} to {}",
+            artifact.name,
+            artifact.from.display(),
+            artifact.to.display()
+        );
+    }
+    println!(
+        "Data directory {} is up to date",
+        SETTINGS.data.data_dir.display()
+    );
+    Ok(())
+}
+
+/// Print the divergences between two final state snapshots, then exit
+fn diff_state(left: &Path, right: &Path, max_entries: usize, json: bool) -> anyhow::Result<()> {
+    let diff = state_diff::diff_state_snapshots(left, right, max_entries)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{}", diff);
+    }
+    Ok(())
+}
+
+/// Export the ledger of a final state database as CSV, then exit
+fn export_ledger(output: &Path, db: Option<&Path>) -> anyhow::Result<()> {
+    let db = match db {
+        Some(db) => db.to_path_buf(),
+        None => DataLayout::new(&SETTINGS.data.data_dir).disk_ledger_path(),
+    };
+    let summary = if output == Path::new("-") {
+        ledger_export::export_ledger(&db, &mut std::io::stdout().lock())?
+    } else {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(output)?);
+        ledger_export::export_ledger(&db, &mut file)?
+    };
+    eprintln!(
+        "Exported {} addresses at slot {} (total balance {}, total rolls {})",
+        summary.address_count,
+        summary
+            .slot
+            .map_or("unknown".to_string(), |slot| slot.to_string()),
+        summary.total_balance,
+        summary.total_rolls
+    );
+    Ok(())
+}
+
+/// Print the hash of an initial ledger file and check it against the expected one, then exit
+fn verify_genesis(ledger: Option<&Path>, expected_hash: Option<&str>) -> anyhow::Result<()> {
+    let ledger = ledger.unwrap_or(&SETTINGS.ledger.initial_ledger_path);
+    match expected_hash.or(SETTINGS.ledger.initial_ledger_hash.as_deref()) {
+        Some(expected_hash) => {
+            genesis_check::verify_initial_ledger(ledger, expected_hash)?;
+            println!(
+                "Initial ledger {} matches the expected hash {}",
+                ledger.display(),
+                expected_hash
+            );
+        }
+        None => println!(
+            "Initial ledger {} has hash {} (no expected hash configured)",
+            ledger.display(),
+            genesis_check::initial_ledger_hash(ledger)?
+        ),
+    }
+    Ok(())
+}
+
+/// Prepare a devnet in a new temporary directory and use its settings
+#[cfg(feature = "sandbox")]
+fn start_devnet(args: &mut Args) -> anyhow::Result<()> {
+    let password = args
+        .password
+        .get_or_insert_with(|| devnet::DEVNET_PASSWORD.to_string())
+        .clone();
+    let root = std::env::temp_dir().join(format!("massa-devnet-{}", MassaTime::now().as_millis()));
+    let devnet = devnet::prepare_devnet(&root, &password)?;
+    std::env::set_var("MASSA_CONFIG_OVERRIDE_PATH", &devnet.config_path);
+    println!("Devnet prepared in {}", root.display());
+    println!("Funded address: {}", devnet.address);
+    println!("Funded secret key: {}", devnet.keypair);
+    println!("Staking wallet password: {}", password);
+    println!("Public API: http://127.0.0.1:33035, private API: http://127.0.0.1:33034");
+    Ok(())
 }
 
 /// Load wallet, asking for passwords if necessary
@@ -1338,13 +1675,41 @@ fn load_wallet(
 }
 
 fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
+    #[allow(unused_mut)]
+    let mut args = Args::parse();
+
+    match &args.command {
+        Some(Command::MigrateData { from }) => return migrate_data(from),
+        Some(Command::DiffState {
+            left,
+            right,
+            max_entries,
+            json,
+        }) => return diff_state(left, right, *max_entries, *json),
+        Some(Command::ExportLedger { output, db }) => return export_ledger(output, db.as_deref()),
+        Some(Command::VerifyGenesis {
+            ledger,
+            expected_hash,
+        }) => return verify_genesis(ledger.as_deref(), expected_hash.as_deref()),
+        None => {}
+    }
+
+    // the devnet settings must be in place before the settings are first read
+    #[cfg(feature = "sandbox")]
+    if args.devnet {
+        start_devnet(&mut args)?;
+    }
+
+    // refuse to start from an initial ledger that does not match its published hash
+    if let Some(expected_hash) = &SETTINGS.ledger.initial_ledger_hash {
+        genesis_check::verify_initial_ledger(&SETTINGS.ledger.initial_ledger_path, expected_hash)?;
+    }
 
     let tokio_rt = tokio::runtime::Builder::new_multi_thread()
         .thread_name_fn(|| {
             static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
             let id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
-            format!("tokio-node-{}", id)
+            format!("{}{}", supervisor::NODE_RUNTIME_THREAD_PREFIX, id)
         })
         .enable_all()
         .build()
@@ -1355,6 +1720,7 @@ fn main() -> anyhow::Result<()> {
 
 async fn run(args: Args) -> anyhow::Result<()> {
     let mut cur_args = args;
+    let mut write_error_stop = false;
     use tracing_subscriber::prelude::*;
     // spawn the console server in the background, returning a `Layer`:
     let tracing_layer = tracing_subscriber::fmt::layer()
@@ -1374,22 +1740,20 @@ async fn run(args: Args) -> anyhow::Result<()> {
         .with(tracing_layer)
         .init();
 
-    // Setup panic handlers,
-    // and when a panic occurs,
-    // run default handler,
-    // and then shutdown.
-    let default_panic = std::panic::take_hook();
-    std::panic::set_hook(Box::new(move |info| {
-        default_panic(info);
-        std::process::exit(1);
-    }));
+    // Setup panic handlers:
+    // crashes are reported and either isolated or followed by an orderly shutdown
+    supervisor::install_panic_hook();
 
     info!("Node version : {}", *VERSION);
 
+    // check the data directory before touching any persisted file
+    let data_layout = DataLayout::new(&SETTINGS.data.data_dir);
+    data_layout.open(Path::new("."))?;
+
     // load or create wallet, asking for password if necessary
     let node_wallet = load_wallet(
         cur_args.password.clone(),
-        &SETTINGS.factory.staking_wallet_path,
+        &data_layout.staking_wallet_path(),
         *CHAINID,
     )?;
 
@@ -1406,12 +1770,28 @@ async fn run(args: Args) -> anyhow::Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
+    // hangup signal listener, to reload the IP lists edited on disk
+    let sig_hup_toggled = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = signal(SignalKind::hangup()).expect("Error setting SIGHUP handler");
+        let sig_hup_toggled = Arc::clone(&sig_hup_toggled);
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                sig_hup_toggled.store(true, Ordering::Relaxed);
+            }
+        });
+    }
+
     #[cfg(feature = "resync_check")]
     let mut resync_check = Some(std::time::Instant::now() + std::time::Duration::from_secs(10));
 
     loop {
         let (
             consensus_event_receiver,
+            protocol_event_receiver,
             bootstrap_manager,
             consensus_manager,
             execution_manager,
@@ -1426,6 +1806,10 @@ async fn run(args: Args) -> anyhow::Result<()> {
             grpc_public_handle,
             metrics_stopper,
             massa_survey_stopper,
+            staking_activation_stopper,
+            webhook_notifier,
+            protocol_controller,
+            final_state,
         ) = launch(&cur_args, node_wallet.clone(), Arc::clone(&sig_int_toggled)).await;
 
         // loop over messages
@@ -1434,12 +1818,23 @@ async fn run(args: Args) -> anyhow::Result<()> {
             match consensus_event_receiver.try_recv() {
                 Ok(evt) => match evt {
                     ConsensusEvent::NeedSync => {
+                        webhook_notifier.notify(WebhookEvent::Desync);
                         warn!("in response to a desynchronization, the node is going to bootstrap again");
                         break true;
                     }
                     ConsensusEvent::Stop => {
                         break false;
                     }
+                    ConsensusEvent::EquivocationDetected(evidence) => {
+                        // already logged by consensus and exposed through the API
+                        webhook_notifier.notify(WebhookEvent::from(evidence.as_ref()));
+                    }
+                    ConsensusEvent::Reorg { removed_blocks } => {
+                        webhook_notifier.notify(WebhookEvent::Reorg {
+                            depth: removed_blocks.len(),
+                            removed_blocks,
+                        });
+                    }
                 },
                 Err(TryRecvError::Disconnected) => {
                     error!("consensus_event_receiver.wait_event disconnected");
@@ -1447,6 +1842,24 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 }
                 _ => {}
             };
+            // already logged by protocol
+            match protocol_event_receiver.try_recv() {
+                Ok(ProtocolEvent::PeerBannedForInvalidItems {
+                    peer_id,
+                    invalid_items,
+                    item_type,
+                }) => {
+                    webhook_notifier.notify(WebhookEvent::PeerBanned {
+                        peer_id: peer_id.to_string(),
+                        invalid_items,
+                        item_type,
+                    });
+                }
+                Ok(ProtocolEvent::NetSplitSuspected(alert)) => {
+                    webhook_notifier.notify(WebhookEvent::from(&alert));
+                }
+                Err(_) => {}
+            }
 
             // every 100ms/or when alerted, check if sigint toggled
             // if toggled, break loop
@@ -1462,6 +1875,27 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 info!("interrupt signal received");
                 break false;
             }
+            if sig_hup_toggled.swap(false, Ordering::Relaxed) {
+                info!("hangup signal received, reloading the ban list and the peers whitelist");
+                if let Err(err) = protocol_controller.reload_ip_lists() {
+                    warn!(
+                        "failed to reload the ban list and the peers whitelist: {}",
+                        err
+                    );
+                }
+            }
+            if supervisor::crash_shutdown_requested() {
+                error!("a node worker crashed, stopping the node");
+                break false;
+            }
+            if let Some(msg) = final_state.read().get_write_error() {
+                error!(
+                    "the final state can no longer be written ({}), stopping the node",
+                    msg
+                );
+                write_error_stop = true;
+                break false;
+            }
 
             // Elements of the system that involve stopping and restarting should be checked by forcing a relaunch.
             // This check allows the system to start up as normal, wait 10s, then force a relaunch. If Things take too long
@@ -1493,6 +1927,7 @@ async fn run(args: Args) -> anyhow::Result<()> {
             grpc_public_handle,
             metrics_stopper,
             massa_survey_stopper,
+            staking_activation_stopper,
         )
         .await;
 
@@ -1502,5 +1937,8 @@ async fn run(args: Args) -> anyhow::Result<()> {
         // If we restart because of a desync, then we do not want to restart from a snapshot
         cur_args.restart_from_snapshot_at_period = None;
     }
+    if supervisor::crash_shutdown_requested() || write_error_stop {
+        process::exit(1);
+    }
     Ok(())
 }
@@ -55,6 +55,8 @@ pub fn start_operation_injector(
                         Operation {
                             fee: Amount::const_init(0, 0),
                             expire_period: final_slot.period + 8,
+                            start_period: None,
+                            fee_payer: None,
                             op: OperationType::Transaction {
                                 recipient_address: addr,
                                 amount: Amount::const_init(10000, 0),
@@ -96,6 +98,8 @@ pub fn start_operation_injector(
                     let content = Operation {
                         fee: Amount::const_init(0, 0),
                         expire_period: final_slot.period + 8,
+                        start_period: None,
+                        fee_payer: None,
                         op: OperationType::Transaction {
                             recipient_address: return_addr,
                             amount: Amount::from_mantissa_scale(amount, 8).unwrap(),
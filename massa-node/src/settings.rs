@@ -5,10 +5,12 @@ use std::{collections::HashMap, path::PathBuf};
 
 use massa_bootstrap::IpType;
 use massa_models::{amount::Amount, config::build_massa_settings, node::NodeId};
-use massa_protocol_exports::PeerCategoryInfo;
+use massa_protocol_exports::{PeerCategoryInfo, PeerId, PeerRateLimit};
 use massa_time::MassaTime;
 use serde::Deserialize;
-use std::net::{IpAddr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::webhooks::WebhookEventKind;
 
 lazy_static::lazy_static! {
     pub static ref SETTINGS: Settings = build_massa_settings("massa-node", "MASSA_NODE");
@@ -26,9 +28,9 @@ pub struct ExecutionSettings {
     pub cursor_delay: MassaTime,
     pub stats_time_window_duration: MassaTime,
     pub max_read_only_gas: u64,
+    pub max_execution_duration: MassaTime,
     pub abi_gas_costs_file: PathBuf,
     pub wasm_gas_costs_file: PathBuf,
-    pub hd_cache_path: PathBuf,
     pub lru_cache_size: u32,
     pub hd_cache_size: usize,
     pub snip_amount: usize,
@@ -37,6 +39,8 @@ pub struct ExecutionSettings {
     /// slot execution traces channel capacity
     pub broadcast_slot_execution_traces_channel_capacity: usize,
     pub execution_traces_limit: usize,
+    /// number of recent cycles for which per-address rewards are kept
+    pub rewards_history_cycles: u64,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -47,11 +51,16 @@ pub struct SelectionSettings {
 #[derive(Clone, Debug, Deserialize)]
 pub struct LedgerSettings {
     pub initial_ledger_path: PathBuf,
-    pub disk_ledger_path: PathBuf,
+    /// expected hash of the initial ledger, the node refuses to start if the initial ledger does not match it
+    pub initial_ledger_hash: Option<String>,
     pub final_history_length: usize,
     pub initial_deferred_credits_path: Option<PathBuf>,
     pub ledger_backup_periods_interval: u64,
     pub max_ledger_backups: u64,
+    pub disk_soft_quota: Option<u64>,
+    pub disk_hard_quota: Option<u64>,
+    /// max size in bytes of the most frequently read ledger sub-entries kept in memory (0 to disable)
+    pub hot_cache_size: usize,
 }
 
 /// Bootstrap configuration.
@@ -84,10 +93,14 @@ pub struct BootstrapSettings {
 pub struct FactorySettings {
     /// Initial delay
     pub initial_delay: MassaTime,
-    /// Staking wallet file
-    pub staking_wallet_path: PathBuf,
     /// stop the production in case we are not connected to anyone
     pub stop_production_when_zero_connections: bool,
+    /// start the node in maintenance mode: no block nor endorsement is produced
+    pub maintenance_mode: bool,
+    /// interval between two liveness heartbeats of the staking addresses (disabled if not set)
+    pub heartbeat_interval: Option<MassaTime>,
+    /// interval between two links of the staking addresses to the node id (disabled if not set)
+    pub node_link_interval: Option<MassaTime>,
 }
 
 /// Pool configuration, read from a file configuration
@@ -96,6 +109,10 @@ pub struct FactorySettings {
 pub struct PoolSettings {
     pub max_operation_pool_size: usize,
     pub max_operation_pool_excess_items: usize,
+    pub max_scheduled_operations: usize,
+    pub max_scheduled_operations_per_creator: usize,
+    pub max_scheduled_operations_size: usize,
+    pub max_scheduled_start_periods: u64,
     pub operation_max_future_start_delay: MassaTime,
     pub operation_pool_refresh_interval: MassaTime,
     pub max_endorsements_pool_size_per_thread: usize,
@@ -130,11 +147,13 @@ pub struct APISettings {
     // whether to broadcast for blocks, endorsement and operations
     pub enable_broadcast: bool,
     pub deferred_credits_delta: MassaTime,
+    pub network_load_periods: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub logging: LoggingSettings,
+    pub data: DataSettings,
     pub protocol: ProtocolSettings,
     pub consensus: ConsensusSettings,
     pub api: APISettings,
@@ -149,6 +168,7 @@ pub struct Settings {
     pub metrics: MetricsSettings,
     pub versioning: VersioningSettings,
     pub block_dump: BlockDumpSettings,
+    pub webhooks: WebhookSettings,
 }
 
 /// Consensus configuration
@@ -175,6 +195,13 @@ pub struct ConsensusSettings {
     pub broadcast_blocks_channel_capacity: usize,
     /// filled blocks channel capacity
     pub broadcast_filled_blocks_channel_capacity: usize,
+    /// maximum number of detected equivocations kept in RAM
+    pub max_equivocation_records: usize,
+    /// headers more than this number of periods ahead of the current slot are not tracked for equivocations
+    pub max_equivocation_future_periods: u64,
+    /// archival mode: keep every final block and operation on disk, indexed by creator address,
+    /// and answer the API queries about the pruned history from this archive
+    pub archival: bool,
 }
 
 // TODO: Remove one date. Kept for retro compatibility.
@@ -182,6 +209,14 @@ pub struct ConsensusSettings {
 pub struct NetworkSettings {
     /// Ip seen by others. If none the bind ip is used
     pub routable_ip: Option<IpAddr>,
+    /// timeout of the resolution of a peer host name
+    pub dns_timeout: MassaTime,
+    /// timeout of the connection to a peer, over all its addresses
+    pub dial_timeout: MassaTime,
+    /// try the IPv6 addresses of dual-stack peers first
+    pub prefer_ipv6: bool,
+    /// SOCKS5 proxy through which the outbound peer and bootstrap connections are opened
+    pub socks5_proxy: Option<SocketAddr>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -194,12 +229,41 @@ pub struct MetricsSettings {
     pub tick_delay: MassaTime,
 }
 
+/// Webhooks configuration, read from toml user configuration file
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookSettings {
+    /// URLs to which the selected events are POSTed
+    pub urls: Vec<String>,
+    /// kinds of events to notify
+    pub events: Vec<WebhookEventKind>,
+    /// minimum number of blocks leaving the blockclique for a reorg to be notified
+    pub min_reorg_depth: usize,
+    /// number of consecutive missed blocks of a staking address to notify
+    pub missed_draws_threshold: u64,
+    /// maximum number of retries of a failed delivery
+    pub max_retries: u32,
+    /// delay before the first retry, doubled at each retry
+    pub retry_delay: MassaTime,
+    /// timeout of a delivery attempt
+    pub timeout: MassaTime,
+}
+
 /// Protocol Configuration, read from toml user configuration file
 #[allow(dead_code)]
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProtocolSettings {
     /// after `ask_block_timeout` milliseconds we try to ask a block to another node
     pub ask_block_timeout: MassaTime,
+    /// max time to wait for the answer to an ask for block data: `ask_block_timeout` is doubled after each unanswered ask of the same block to the same node, up to this value (no backoff if below `ask_block_timeout`)
+    pub ask_block_max_timeout: MassaTime,
+    /// number of unanswered asks of a block after which a node is only asked again for it if no other node can be asked (0 to disable)
+    pub max_ask_block_failures_per_node: usize,
+    /// number of nodes asked at the same time for the data of a same block, the first useful answer cancelling the other asks (1 to ask a single node at a time)
+    pub max_parallel_block_asks: usize,
+    /// maximum age of the block wishlist saved on shutdown to be resumed on restart, 0 to disable
+    pub block_wishlist_max_age: MassaTime,
+    /// maximum number of peers kept in the peer store, the worst ranked unpinned peers being forgotten first
+    pub peer_store_max_records: usize,
     /// Max known blocks we keep during their propagation
     pub max_blocks_kept_for_propagation: usize,
     /// Time during which a block is expected to propagate
@@ -218,12 +282,18 @@ pub struct ProtocolSettings {
     pub asked_operations_buffer_capacity: usize,
     /// max known operations of foreign nodes we keep in memory (by node)
     pub max_node_known_ops_size: usize,
+    /// target false positive rate of the known operations of foreign nodes
+    pub node_known_ops_false_positive_rate: f64,
     /// max known endorsements by our node that we kept in memory
     pub max_known_endorsements_size: usize,
     /// max known endorsements of foreign nodes we keep in memory (by node)
     pub max_node_known_endorsements_size: usize,
     /// we ask for the same block `max_simultaneous_ask_blocks_per_node` times at the same time
     pub max_simultaneous_ask_blocks_per_node: usize,
+    /// ask peers for compressed operations when retrieving the operations of a block
+    pub compress_block_operations: bool,
+    /// send the full operations compressed to the peers accepting it
+    pub compress_operation_messages: bool,
     /// Max wait time for sending a Network or Node event.
     pub max_send_wait: MassaTime,
     /// Maximum number of batches in the memory buffer.
@@ -236,22 +306,44 @@ pub struct ProtocolSettings {
     pub operation_batch_proc_period: MassaTime,
     /// Interval at which operations are announced in batches.
     pub operation_announcement_interval: MassaTime,
+    /// Interval at which the operations asked by each peer are sent, merged per peer (0 to send them as soon as asked)
+    pub operation_send_interval: MassaTime,
     /// Maximum of operations sent in one message.
     pub max_operations_per_message: u64,
     /// MAx number of operations kept for propagation
     pub max_ops_kept_for_propagation: usize,
     /// Time threshold after which operation are not propagated
     pub max_operations_propagation_time: MassaTime,
+    /// max number of randomly chosen peers each operation is announced to (0 to announce it to every peer)
+    pub propagation_fanout: usize,
+    /// time during which the knowledge of a disconnected peer is kept to be restored if it reconnects (0 to forget it immediately)
+    pub peer_knowledge_retention: MassaTime,
     /// Time threshold after which operation are not propagated
     pub max_endorsements_propagation_time: MassaTime,
+    /// interval during which endorsements are buffered to be propagated in a single batch per peer (0 to propagate them immediately)
+    pub endorsement_propagation_interval: MassaTime,
     /// Path for initial peers
     pub initial_peers_file: PathBuf,
-    /// Keypair
-    pub keypair_file: PathBuf,
     /// Ip we are bind to listen to
     pub bind: SocketAddr,
+    /// Additional IPv6 address to listen to, when the `bind` socket only accepts IPv4
+    pub bind_ipv6: Option<SocketAddr>,
+    /// Other addresses to listen to, e.g. a LAN address besides the public one
+    pub additional_binds: Vec<SocketAddr>,
+    /// Addresses listened to whose port is announced to the other nodes, all of them if none
+    pub advertised_binds: Option<Vec<SocketAddr>>,
+    /// Address to listen to for QUIC connections, on a port different from the TCP listeners (needs the `quic` feature)
+    pub bind_quic: Option<SocketAddr>,
     /// Ip seen by others. If none the bind ip is used
     pub routable_ip: Option<IpAddr>,
+    /// IPv6 address seen by others, announced along with `routable_ip`
+    pub routable_ipv6: Option<Ipv6Addr>,
+    /// Request a port mapping from the router with NAT-PMP at startup
+    pub nat_traversal: bool,
+    /// Router asked for the port mapping, the default gateway if none
+    pub nat_gateway: Option<Ipv4Addr>,
+    /// Lifetime requested for the port mapping
+    pub nat_mapping_lifetime: MassaTime,
     /// Time threshold to have a connection to a node
     pub connect_timeout: MassaTime,
     /// Number of tester threads
@@ -272,6 +364,10 @@ pub struct ProtocolSettings {
     pub tester_timeout: MassaTime,
     /// Nb in connections
     pub max_in_connections: usize,
+    /// Number of handshakes attempted with an address within a minute after which it is put in cooldown (0 for no limit)
+    pub max_handshakes_per_ip_per_minute: usize,
+    /// Time during which the handshakes with an address exceeding `max_handshakes_per_ip_per_minute` are refused
+    pub handshake_cooldown: MassaTime,
     /// Peers limits per category
     pub peers_categories: HashMap<String, PeerCategoryInfo>,
     /// Limits for default category
@@ -280,6 +376,62 @@ pub struct ProtocolSettings {
     pub test_oldest_peer_cooldown: MassaTime,
     /// Rate limitation to apply to the data stream (per second)
     pub rate_limit: u64,
+    /// Duration during which a received staker heartbeat is considered fresh
+    pub heartbeat_validity: MassaTime,
+    /// Maximum number of staker heartbeats kept by the node
+    pub max_known_heartbeats: usize,
+    /// Duration during which a received node address link is considered fresh
+    pub node_link_validity: MassaTime,
+    /// Maximum number of node address links kept by the node
+    pub max_known_node_links: usize,
+    /// Processing time of a received message above which a warning is logged
+    pub slow_message_threshold: MassaTime,
+    /// Lag behind the network above which received operations and endorsements are not processed
+    pub max_sync_lag_for_gossip: MassaTime,
+    /// Ask the connected peers for their pending operations once synced after a start
+    pub pool_warmup_enabled: bool,
+    /// Minimal interval between two requests for pending operations answered to the same peer
+    pub pending_operations_request_min_interval: MassaTime,
+    /// Send a sketch of the pending operations to each newly connected peer
+    pub pending_operations_sync_enabled: bool,
+    /// Maximal time a received header slot can be in the future (0 to disable)
+    pub max_header_future_time: MassaTime,
+    /// Number of periods in the past beyond which a received header is dropped (0 to disable)
+    pub max_header_past_periods: u64,
+    /// Number of out of bounds headers a peer can send before being banned
+    pub max_out_of_bounds_headers_per_peer: u64,
+    /// Number of invalid items (blocks, headers, operations, endorsements) received from a peer within `invalid_items_window` after which it is banned (1 to ban it at the first one)
+    pub invalid_items_ban_threshold: usize,
+    /// Sliding window over which the invalid items received from a peer are counted
+    pub invalid_items_window: MassaTime,
+    /// Rate limit of the operations received from each peer
+    pub operations_rate_limit: PeerRateLimit,
+    /// Rate limit of the endorsements received from each peer
+    pub endorsements_rate_limit: PeerRateLimit,
+    /// Look up the peers closest to random node ids to discover new peers
+    pub peer_discovery_enabled: bool,
+    /// Peers asked first for the peers closest to our node id
+    pub peer_discovery_bootstrap_nodes: Vec<PeerId>,
+    /// Maximum number of peers per bucket of the discovery routing table
+    pub peer_discovery_bucket_size: usize,
+    /// Interval after which a bucket of the discovery routing table is looked up again
+    pub peer_discovery_refresh_interval: MassaTime,
+    /// Time after which the contribution of a peer to the final blocks and endorsements counts half
+    pub contribution_half_life: MassaTime,
+    /// Number of top contributing peers never evicted to make room for new in connections
+    pub contribution_protected_peers: usize,
+    /// Minimal interval between two evictions of a low contributing in connection (0 to disable)
+    pub contribution_eviction_interval: MassaTime,
+    /// Time window of the finalized blocks whose origin is checked for a network split
+    pub net_split_window: MassaTime,
+    /// Number of blocks finalized within the window before checking their origin (0 to disable)
+    pub net_split_min_final_blocks: usize,
+    /// Minimal number of distinct peers first sending the blocks finalized within the window
+    pub net_split_min_origin_peers: usize,
+    /// Minimal number of distinct subnets of these peers and of the connected peers
+    pub net_split_min_subnets: usize,
+    /// Interval between two keepalive pings to each connected peer (0 to disable)
+    pub ping_interval: MassaTime,
 }
 
 /// gRPC settings
@@ -381,6 +533,12 @@ pub struct GrpcApiSettings {
     pub private: GrpcSettings,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct DataSettings {
+    /// directory holding everything the node persists
+    pub data_dir: PathBuf,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct VersioningSettings {
     // Warn user to update its node if we reach this percentage for announced network versions
@@ -389,8 +547,6 @@ pub struct VersioningSettings {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct BlockDumpSettings {
-    /// Where to dump blocks
-    pub(crate) block_dump_folder_path: PathBuf,
     #[cfg(feature = "dump-block")]
     /// Number of blocks to keep
     pub(crate) max_blocks: u64,
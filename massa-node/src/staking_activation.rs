@@ -0,0 +1,343 @@
+//! Tracking of the activation state of the staking addresses of the node.
+//!
+//! Rolls are only taken into account in the draws 3 cycles after they became final: until then,
+//! a freshly registered staking address is not selected to produce anything. This thread keeps
+//! the activation state of each staking address up to date for the API and logs every change,
+//! in particular when an address starts being drawn.
+//! It also notifies the webhooks when an active staking address misses consecutive blocks.
+
+use std::ops::Bound;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{select, tick};
+use massa_api_exports::rolls::StakingKeyActivation;
+use massa_channel::{sender::MassaSender, MassaChannel};
+use massa_execution_exports::ExecutionController;
+use massa_models::{
+    address::Address, prehash::PreHashMap, slot::Slot,
+    timeslots::get_latest_block_slot_at_timestamp,
+};
+use massa_time::MassaTime;
+use massa_wallet::Wallet;
+use parking_lot::RwLock;
+use tracing::{info, warn};
+
+use crate::webhooks::{MissedDrawsTracker, WebhookEvent, WebhookEventKind, WebhookNotifier};
+
+/// Number of cycles between the finalization of rolls and their use in the draws
+const ROLL_ACTIVATION_CYCLES: u64 = 3;
+
+/// Time parameters of the network
+pub struct StakingActivationConfig {
+    /// thread count
+    pub thread_count: u8,
+    /// period duration
+    pub t0: MassaTime,
+    /// genesis timestamp
+    pub genesis_timestamp: MassaTime,
+    /// periods per cycle
+    pub periods_per_cycle: u64,
+    /// last start period
+    pub last_start_period: u64,
+}
+
+/// Handle on the staking activation tracking thread
+pub struct StakingActivationStopper {
+    tx_stopper: Option<MassaSender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl StakingActivationStopper {
+    pub fn stop(&mut self) {
+        if let Some(tx) = self.tx_stopper.take() {
+            if let Err(e) = tx.send(()) {
+                warn!(
+                    "failed to send stop signal to staking activation thread: {:?}",
+                    e
+                );
+            }
+        }
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                warn!("failed to join staking activation thread");
+            }
+        }
+    }
+}
+
+/// Starts the thread tracking the activation state of the staking addresses,
+/// refreshing `activations` at every period
+pub fn start_staking_activation_tracker(
+    config: StakingActivationConfig,
+    wallet: Arc<RwLock<Wallet>>,
+    execution_controller: Box<dyn ExecutionController>,
+    activations: Arc<RwLock<PreHashMap<Address, StakingKeyActivation>>>,
+    webhook_notifier: WebhookNotifier,
+) -> StakingActivationStopper {
+    let (tx_stop, rx_stop) = MassaChannel::new("staking_activation_stop".to_string(), Some(1));
+    let update_tick = tick(config.t0.to_duration());
+    let mut missed_draws = MissedDrawsTracker::default();
+    let spawn_res = std::thread::Builder::new()
+        .name("staking-activation".to_string())
+        .spawn(move || loop {
+            select! {
+                recv(rx_stop) -> _ => break,
+                recv(update_tick) -> _ => {
+                    let Some(cycle) = current_cycle(&config) else {
+                        continue;
+                    };
+                    let addresses: Vec<Address> =
+                        wallet.read().get_wallet_address_list().into_iter().collect();
+                    let new_activations =
+                        compute_activations(execution_controller.as_ref(), &addresses, cycle);
+                    if webhook_notifier.is_enabled(WebhookEventKind::MissedDraws) {
+                        notify_missed_draws(
+                            execution_controller.as_ref(),
+                            &webhook_notifier,
+                            &mut missed_draws,
+                            &new_activations,
+                        );
+                    }
+                    let mut activations = activations.write();
+                    for (address, activation) in &new_activations {
+                        log_activation_change(address, activations.get(address), activation);
+                    }
+                    *activations = new_activations;
+                }
+            }
+        });
+    match spawn_res {
+        Ok(handle) => StakingActivationStopper {
+            tx_stopper: Some(tx_stop),
+            handle: Some(handle),
+        },
+        Err(e) => {
+            warn!("failed to spawn staking activation thread: {:?}", e);
+            StakingActivationStopper {
+                tx_stopper: None,
+                handle: None,
+            }
+        }
+    }
+}
+
+/// Cycle of the current slot, the cycle of the last start period while it is not reached yet
+fn current_cycle(config: &StakingActivationConfig) -> Option<u64> {
+    match get_latest_block_slot_at_timestamp(
+        config.thread_count,
+        config.t0,
+        config.genesis_timestamp,
+        MassaTime::now(),
+    ) {
+        Ok(Some(slot)) if slot.period > config.last_start_period => {
+            Some(slot.get_cycle(config.periods_per_cycle))
+        }
+        Ok(_) => Some(Slot::new(config.last_start_period, 0).get_cycle(config.periods_per_cycle)),
+        Err(e) => {
+            warn!("failed to get the current slot: {}", e);
+            None
+        }
+    }
+}
+
+/// Computes the activation state of `addresses` at `cycle`
+fn compute_activations(
+    execution_controller: &dyn ExecutionController,
+    addresses: &[Address],
+    cycle: u64,
+) -> PreHashMap<Address, StakingKeyActivation> {
+    let mut activations = PreHashMap::default();
+    let mut pending: Vec<Address> = Vec::new();
+    let active_rolls = execution_controller.get_cycle_active_rolls(cycle);
+    for address in addresses {
+        match active_rolls.get(address) {
+            Some(rolls) if *rolls > 0 => {
+                activations.insert(
+                    *address,
+                    StakingKeyActivation::Active {
+                        active_rolls: *rolls,
+                    },
+                );
+            }
+            _ => pending.push(*address),
+        }
+    }
+
+    // rolls that are already final are drawn from a known upcoming cycle
+    for activation_cycle in (cycle + 1)..=(cycle + ROLL_ACTIVATION_CYCLES) {
+        if pending.is_empty() {
+            break;
+        }
+        let rolls = execution_controller.get_cycle_active_rolls(activation_cycle);
+        pending.retain(|address| match rolls.get(address) {
+            Some(count) if *count > 0 => {
+                activations.insert(
+                    *address,
+                    StakingKeyActivation::AwaitingCycle { activation_cycle },
+                );
+                false
+            }
+            _ => true,
+        });
+    }
+
+    // rolls that are not final yet are drawn at the earliest `ROLL_ACTIVATION_CYCLES` cycles later
+    if !pending.is_empty() {
+        let infos =
+            execution_controller.get_addresses_infos(&pending, Bound::Excluded(Slot::new(0, 0)));
+        for (address, info) in pending.iter().zip(infos) {
+            let activation = if info.candidate_roll_count > 0 {
+                StakingKeyActivation::AwaitingCycle {
+                    activation_cycle: cycle + ROLL_ACTIVATION_CYCLES,
+                }
+            } else {
+                StakingKeyActivation::AwaitingRolls
+            };
+            activations.insert(*address, activation);
+        }
+    }
+    activations
+}
+
+/// Notifies the webhooks of the active staking addresses that missed consecutive blocks
+fn notify_missed_draws(
+    execution_controller: &dyn ExecutionController,
+    webhook_notifier: &WebhookNotifier,
+    tracker: &mut MissedDrawsTracker,
+    activations: &PreHashMap<Address, StakingKeyActivation>,
+) {
+    let active: Vec<Address> = activations
+        .iter()
+        .filter(|(_, activation)| matches!(activation, StakingKeyActivation::Active { .. }))
+        .map(|(address, _)| *address)
+        .collect();
+    tracker.retain(&active);
+    if active.is_empty() {
+        return;
+    }
+    let infos = execution_controller.get_addresses_infos(&active, Bound::Excluded(Slot::new(0, 0)));
+    for (address, info) in active.iter().zip(infos) {
+        let Some(cycle_info) = info.cycle_infos.iter().max_by_key(|info| info.cycle) else {
+            continue;
+        };
+        if let Some(consecutive_misses) = tracker.update(
+            *address,
+            cycle_info,
+            webhook_notifier.missed_draws_threshold(),
+        ) {
+            warn!(
+                "staking address {} missed {} consecutive blocks",
+                address, consecutive_misses
+            );
+            webhook_notifier.notify(WebhookEvent::MissedDraws {
+                address: *address,
+                consecutive_misses,
+            });
+        }
+    }
+}
+
+/// Logs the activation state changes of a staking address
+fn log_activation_change(
+    address: &Address,
+    previous: Option<&StakingKeyActivation>,
+    current: &StakingKeyActivation,
+) {
+    match (previous, current) {
+        (Some(StakingKeyActivation::Active { .. }), StakingKeyActivation::Active { .. }) => {}
+        (Some(_), StakingKeyActivation::Active { active_rolls }) => info!(
+            "staking address {} is now active with {} rolls: it is drawn for block and endorsement production",
+            address, active_rolls
+        ),
+        // already active when the node started
+        (None, StakingKeyActivation::Active { .. }) => {}
+        (Some(previous), current) if previous == current => {}
+        (_, StakingKeyActivation::AwaitingCycle { activation_cycle }) => info!(
+            "staking address {} owns rolls, they will be taken into account in the draws from cycle {}",
+            address, activation_cycle
+        ),
+        (_, StakingKeyActivation::AwaitingRolls) => info!(
+            "staking address {} owns no roll: buy rolls to start staking",
+            address
+        ),
+        (_, StakingKeyActivation::Unknown) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_execution_exports::{ExecutionAddressInfo, MockExecutionController};
+    use massa_models::amount::Amount;
+    use massa_signature::KeyPair;
+    use std::collections::BTreeMap;
+
+    fn address_info(candidate_roll_count: u64) -> ExecutionAddressInfo {
+        ExecutionAddressInfo {
+            candidate_balance: Amount::zero(),
+            final_balance: Amount::zero(),
+            final_roll_count: 0,
+            final_datastore_keys: Default::default(),
+            candidate_roll_count,
+            candidate_datastore_keys: Default::default(),
+            future_deferred_credits: Default::default(),
+            final_future_deferred_credits: Default::default(),
+            cycle_infos: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compute_activations() {
+        let active = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let awaiting_cycle =
+            Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let awaiting_finality =
+            Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let awaiting_rolls =
+            Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+
+        let mut execution_controller = MockExecutionController::new();
+        execution_controller
+            .expect_get_cycle_active_rolls()
+            .returning(move |cycle| match cycle {
+                10 => BTreeMap::from([(active, 5)]),
+                12 => BTreeMap::from([(active, 5), (awaiting_cycle, 2)]),
+                _ => BTreeMap::from([(active, 5)]),
+            });
+        execution_controller
+            .expect_get_addresses_infos()
+            .returning(move |addresses, _| {
+                addresses
+                    .iter()
+                    .map(|address| address_info(u64::from(*address == awaiting_finality)))
+                    .collect()
+            });
+
+        let activations = compute_activations(
+            &execution_controller,
+            &[active, awaiting_cycle, awaiting_finality, awaiting_rolls],
+            10,
+        );
+        assert_eq!(
+            activations.get(&active),
+            Some(&StakingKeyActivation::Active { active_rolls: 5 })
+        );
+        assert_eq!(
+            activations.get(&awaiting_cycle),
+            Some(&StakingKeyActivation::AwaitingCycle {
+                activation_cycle: 12
+            })
+        );
+        assert_eq!(
+            activations.get(&awaiting_finality),
+            Some(&StakingKeyActivation::AwaitingCycle {
+                activation_cycle: 13
+            })
+        );
+        assert_eq!(
+            activations.get(&awaiting_rolls),
+            Some(&StakingKeyActivation::AwaitingRolls)
+        );
+    }
+}
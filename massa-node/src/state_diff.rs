@@ -0,0 +1,372 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Structured diff of two final state snapshots, to debug consensus splits.
+//!
+//! A snapshot is a final state database, either the one of a stopped node
+//! or one of the `backup_<period>_<thread>` copies made by the node.
+//! Both databases are walked in key order: ledger keys start with the address,
+//! so the first divergent ledger entry gives the first divergent address.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Write},
+    path::Path,
+};
+
+use anyhow::bail;
+use massa_db_exports::{
+    MassaDBConfig, MassaDBController, MassaIteratorMode, CYCLE_HISTORY_PREFIX,
+    DEFERRED_CREDITS_PREFIX, LEDGER_PREFIX, STATE_CF,
+};
+use massa_db_worker::MassaDB;
+use massa_ledger_exports::{Key as LedgerKey, KeyDeserializer};
+use massa_models::{
+    address::Address,
+    config::{MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT},
+    slot::Slot,
+};
+use massa_serialization::{DeserializeError, Deserializer};
+use serde::Serialize;
+
+/// Divergent entry of the final state
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryDiff {
+    /// hex encoded key of the entry
+    pub key: String,
+    /// address concerned by the entry, for ledger entries
+    pub address: Option<Address>,
+    /// hex encoded value in the left snapshot, `None` if the entry is absent
+    pub left: Option<String>,
+    /// hex encoded value in the right snapshot, `None` if the entry is absent
+    pub right: Option<String>,
+}
+
+/// Divergences of a part of the final state (ledger, deferred credits, ...)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SectionDiff {
+    /// number of entries only present in the left snapshot
+    pub only_left: usize,
+    /// number of entries only present in the right snapshot
+    pub only_right: usize,
+    /// number of entries present in both snapshots with different values
+    pub different: usize,
+    /// first divergent entries, in key order
+    pub entries: Vec<EntryDiff>,
+}
+
+impl SectionDiff {
+    /// Total number of divergent entries
+    pub fn count(&self) -> usize {
+        self.only_left + self.only_right + self.different
+    }
+}
+
+/// Structured diff of two final state snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct StateDiff {
+    /// slot of the left snapshot
+    pub left_slot: Option<Slot>,
+    /// slot of the right snapshot
+    pub right_slot: Option<Slot>,
+    /// final state hash of the left snapshot
+    pub left_hash: String,
+    /// final state hash of the right snapshot
+    pub right_hash: String,
+    /// first address whose ledger entries diverge
+    pub first_divergent_address: Option<Address>,
+    /// divergences by part of the final state, indexed by key prefix (e.g. `ledger`, `deferred_credits`)
+    pub sections: BTreeMap<String, SectionDiff>,
+}
+
+impl StateDiff {
+    /// Whether both snapshots hold the same final state
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Whether the proof-of-stake state diverges
+    pub fn pos_diverges(&self) -> bool {
+        [CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX]
+            .iter()
+            .any(|prefix| self.sections.contains_key(section_name(prefix.as_bytes())))
+    }
+}
+
+impl Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slot = |slot: &Option<Slot>| slot.map_or("unknown".to_string(), |s| s.to_string());
+        writeln!(
+            f,
+            "Left snapshot: slot {}, hash {}",
+            slot(&self.left_slot),
+            self.left_hash
+        )?;
+        writeln!(
+            f,
+            "Right snapshot: slot {}, hash {}",
+            slot(&self.right_slot),
+            self.right_hash
+        )?;
+        if self.left_slot != self.right_slot {
+            writeln!(f, "Warning: the snapshots are not at the same slot")?;
+        }
+        if self.is_empty() {
+            return writeln!(f, "The final states are identical");
+        }
+        match self.first_divergent_address {
+            Some(address) => writeln!(f, "First divergent address: {}", address)?,
+            None => writeln!(f, "The ledgers are identical")?,
+        }
+        if self.pos_diverges() {
+            writeln!(f, "The proof-of-stake states diverge")?;
+        }
+        for (name, section) in &self.sections {
+            writeln!(
+                f,
+                "{}: {} divergent entries ({} only left, {} only right, {} different)",
+                name,
+                section.count(),
+                section.only_left,
+                section.only_right,
+                section.different
+            )?;
+            for entry in &section.entries {
+                match entry.address {
+                    Some(address) => write!(f, "    {} ({})", entry.key, address)?,
+                    None => write!(f, "    {}", entry.key)?,
+                }
+                writeln!(
+                    f,
+                    ": {} -> {}",
+                    entry.left.as_deref().unwrap_or("absent"),
+                    entry.right.as_deref().unwrap_or("absent")
+                )?;
+            }
+            if section.entries.len() < section.count() {
+                writeln!(
+                    f,
+                    "    ... {} more",
+                    section.count() - section.entries.len()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Open the snapshots at `left` and `right` and diff them,
+/// listing at most `max_listed_entries` entries per part of the final state
+pub fn diff_state_snapshots(
+    left: &Path,
+    right: &Path,
+    max_listed_entries: usize,
+) -> anyhow::Result<StateDiff> {
+    let left = open_snapshot(left)?;
+    let right = open_snapshot(right)?;
+    Ok(diff_databases(&left, &right, max_listed_entries))
+}
+
+/// Open an existing final state database
+fn open_snapshot(path: &Path) -> anyhow::Result<MassaDB> {
+    if !path.join("CURRENT").is_file() {
+        bail!("{} is not a final state database", path.display());
+    }
+    Ok(MassaDB::new(MassaDBConfig {
+        path: path.to_path_buf(),
+        max_history_length: 0,
+        max_final_state_elements_size: 0,
+        max_versioning_elements_size: 0,
+        thread_count: THREAD_COUNT,
+        max_ledger_backups: 0,
+        disk_soft_quota: None,
+        disk_hard_quota: None,
+    }))
+}
+
+/// Diff the final states of two databases
+pub fn diff_databases(left: &MassaDB, right: &MassaDB, max_listed_entries: usize) -> StateDiff {
+    let ledger_key_deserializer = KeyDeserializer::new(MAX_DATASTORE_KEY_LENGTH, false);
+    let mut diff = StateDiff {
+        left_slot: left.get_change_id().ok(),
+        right_slot: right.get_change_id().ok(),
+        left_hash: left.get_xof_db_hash().to_string(),
+        right_hash: right.get_xof_db_hash().to_string(),
+        first_divergent_address: None,
+        sections: BTreeMap::new(),
+    };
+
+    let mut left_iter = left
+        .iterator_cf(STATE_CF, MassaIteratorMode::Start)
+        .peekable();
+    let mut right_iter = right
+        .iterator_cf(STATE_CF, MassaIteratorMode::Start)
+        .peekable();
+    loop {
+        // merge both sorted iterators
+        let (key, left_value, right_value) = match (left_iter.peek(), right_iter.peek()) {
+            (None, None) => break,
+            (Some(_), None) => {
+                let (key, value) = left_iter.next().unwrap();
+                (key, Some(value), None)
+            }
+            (None, Some(_)) => {
+                let (key, value) = right_iter.next().unwrap();
+                (key, None, Some(value))
+            }
+            (Some((left_key, _)), Some((right_key, _))) => match left_key.cmp(right_key) {
+                std::cmp::Ordering::Less => {
+                    let (key, value) = left_iter.next().unwrap();
+                    (key, Some(value), None)
+                }
+                std::cmp::Ordering::Greater => {
+                    let (key, value) = right_iter.next().unwrap();
+                    (key, None, Some(value))
+                }
+                std::cmp::Ordering::Equal => {
+                    let (key, left_value) = left_iter.next().unwrap();
+                    let (_, right_value) = right_iter.next().unwrap();
+                    if left_value == right_value {
+                        continue;
+                    }
+                    (key, Some(left_value), Some(right_value))
+                }
+            },
+        };
+
+        let address = if key.starts_with(LEDGER_PREFIX.as_bytes()) {
+            ledger_key_deserializer
+                .deserialize::<DeserializeError>(&key)
+                .ok()
+                .map(|(_, LedgerKey { address, .. })| address)
+        } else {
+            None
+        };
+        if diff.first_divergent_address.is_none() {
+            diff.first_divergent_address = address;
+        }
+
+        let section = diff
+            .sections
+            .entry(section_name(&key).to_string())
+            .or_default();
+        match (&left_value, &right_value) {
+            (Some(_), None) => section.only_left += 1,
+            (None, Some(_)) => section.only_right += 1,
+            _ => section.different += 1,
+        }
+        if section.entries.len() < max_listed_entries {
+            section.entries.push(EntryDiff {
+                key: to_hex(&key),
+                address,
+                left: left_value.as_deref().map(to_hex),
+                right: right_value.as_deref().map(to_hex),
+            });
+        }
+    }
+    diff
+}
+
+/// Name of the part of the final state a key belongs to: its prefix, without the trailing `/`
+fn section_name(key: &[u8]) -> &str {
+    key.iter()
+        .position(|b| *b == b'/')
+        .and_then(|end| std::str::from_utf8(&key[..end]).ok())
+        .unwrap_or("unknown")
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(2 * bytes.len()), |mut hex, b| {
+            let _ = write!(hex, "{:02x}", b);
+            hex
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_db_exports::DBBatch;
+    use massa_ledger_exports::{KeySerializer, KeyType};
+    use massa_serialization::Serializer;
+    use massa_signature::KeyPair;
+
+    fn ledger_key(address: &Address) -> Vec<u8> {
+        let mut key = Vec::new();
+        KeySerializer::new(false)
+            .serialize(&LedgerKey::new(address, KeyType::BALANCE), &mut key)
+            .unwrap();
+        key
+    }
+
+    fn open_test_db(path: &Path) -> MassaDB {
+        MassaDB::new(MassaDBConfig {
+            path: path.to_path_buf(),
+            max_history_length: 100,
+            max_final_state_elements_size: 100,
+            max_versioning_elements_size: 100,
+            thread_count: THREAD_COUNT,
+            max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
+        })
+    }
+
+    fn write(db: &mut MassaDB, entries: &[(Vec<u8>, &[u8])]) {
+        let mut batch = DBBatch::new();
+        for (key, value) in entries {
+            db.put_or_update_entry_value(&mut batch, key.clone(), value);
+        }
+        db.write_batch(batch, DBBatch::new(), Some(Slot::new(1, 0)));
+    }
+
+    #[test]
+    fn test_diff_state_snapshots() {
+        let left_dir = tempfile::tempdir().unwrap();
+        let right_dir = tempfile::tempdir().unwrap();
+        let mut left = open_test_db(left_dir.path());
+        let mut right = open_test_db(right_dir.path());
+
+        let address_1 = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let address_2 = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let (first, second) = if ledger_key(&address_1) < ledger_key(&address_2) {
+            (address_1, address_2)
+        } else {
+            (address_2, address_1)
+        };
+        let credits_key = format!("{}credit", DEFERRED_CREDITS_PREFIX).into_bytes();
+
+        write(
+            &mut left,
+            &[
+                (ledger_key(&first), b"same"),
+                (ledger_key(&second), b"left"),
+                (credits_key.clone(), b"credit"),
+            ],
+        );
+        write(
+            &mut right,
+            &[
+                (ledger_key(&first), b"same"),
+                (ledger_key(&second), b"right"),
+            ],
+        );
+        assert!(diff_databases(&left, &left, 10).is_empty());
+
+        let diff = diff_databases(&left, &right, 10);
+        assert_eq!(diff.first_divergent_address, Some(second));
+        assert!(diff.pos_diverges());
+        let ledger = &diff.sections["ledger"];
+        assert_eq!(
+            (ledger.only_left, ledger.only_right, ledger.different),
+            (0, 0, 1)
+        );
+        assert_eq!(ledger.entries[0].right, Some(to_hex(b"right")));
+        let credits = &diff.sections["deferred_credits"];
+        assert_eq!(
+            (credits.only_left, credits.only_right, credits.different),
+            (1, 0, 0)
+        );
+        assert_ne!(diff.left_hash, diff.right_hash);
+    }
+}
@@ -0,0 +1,154 @@
+//! Supervision of the node workers.
+//!
+//! Panics are classified by the thread they happen in:
+//! * the node runtime (`tokio-node-*` threads) only runs the API and gRPC servers and stateless
+//!   background tasks (webhooks, metrics survey, signal listeners): each request is served by its
+//!   own tokio task, so a panic only drops that task and the servers keep serving afterwards;
+//! * the metrics server runs on its own `metrics-server` thread, which restarts the server when
+//!   it panics;
+//! * the other workers (consensus, execution, pool, protocol...) and the runtimes not started by
+//!   the node hold node state that cannot be rebuilt in place: their failure triggers an orderly
+//!   shutdown of the node, enforced by a timeout;
+//! * a panic of the main thread cannot be recovered from and exits the process immediately.
+
+use std::panic::PanicInfo;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::error;
+
+/// Maximum time given to the node to stop after a worker crashed, after which the process exits
+const CRASH_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Thread name prefix of the runtime created by the node to serve the API and gRPC
+pub(crate) const NODE_RUNTIME_THREAD_PREFIX: &str = "tokio-node-";
+
+/// Name prefixes of the threads whose panics are contained to the failing task or server.
+/// The default `tokio-runtime-worker` name is not listed: the runtimes started by the
+/// dependencies are not known to be stateless.
+const ISOLATED_THREAD_PREFIXES: [&str; 2] = [NODE_RUNTIME_THREAD_PREFIX, "metrics-server"];
+
+/// Set when a stateful worker crashed and the node must stop
+static CRASH_SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Reaction to a panic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CrashPolicy {
+    /// the failing task is dropped, the worker keeps running
+    Isolate,
+    /// the node is stopped in an orderly way
+    Shutdown,
+    /// the process exits immediately
+    Abort,
+}
+
+fn crash_policy(thread_name: &str, shutdown_requested: bool) -> CrashPolicy {
+    if thread_name == "main" {
+        CrashPolicy::Abort
+    } else if shutdown_requested
+        || ISOLATED_THREAD_PREFIXES
+            .iter()
+            .any(|prefix| thread_name.starts_with(prefix))
+    {
+        // panics happening while the node is already stopping after a crash are usually caused by
+        // the first one (e.g. disconnected channels): the shutdown timeout takes care of them
+        CrashPolicy::Isolate
+    } else {
+        CrashPolicy::Shutdown
+    }
+}
+
+fn panic_message(info: &PanicInfo) -> String {
+    if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Installs the panic hook that reports worker crashes and applies the crash policy
+pub(crate) fn install_panic_hook() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("unnamed");
+        let policy = crash_policy(thread_name, CRASH_SHUTDOWN_REQUESTED.load(Ordering::SeqCst));
+        error!(
+            thread = thread_name,
+            location = info.location().map(|l| l.to_string()).unwrap_or_default(),
+            policy = ?policy,
+            "worker crashed: {}",
+            panic_message(info)
+        );
+
+        match policy {
+            CrashPolicy::Isolate => {}
+            CrashPolicy::Shutdown => {
+                CRASH_SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+                let watchdog = std::thread::Builder::new()
+                    .name("crash-shutdown-watchdog".into())
+                    .spawn(|| {
+                        std::thread::sleep(CRASH_SHUTDOWN_TIMEOUT);
+                        error!("node did not stop in time after a worker crash, exiting");
+                        std::process::exit(1);
+                    });
+                if watchdog.is_err() {
+                    std::process::exit(1);
+                }
+            }
+            CrashPolicy::Abort => std::process::exit(1),
+        }
+    }));
+}
+
+/// Returns true if a stateful worker crashed and the node must be stopped
+pub(crate) fn crash_shutdown_requested() -> bool {
+    CRASH_SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_policy() {
+        // the main thread always aborts
+        assert_eq!(crash_policy("main", false), CrashPolicy::Abort);
+        assert_eq!(crash_policy("main", true), CrashPolicy::Abort);
+
+        // the node runtime and the metrics server are isolated
+        assert_eq!(crash_policy("tokio-node-0", false), CrashPolicy::Isolate);
+        assert_eq!(crash_policy("tokio-node-12", false), CrashPolicy::Isolate);
+        assert_eq!(crash_policy("metrics-server", false), CrashPolicy::Isolate);
+
+        // stateful workers, unknown runtimes and unnamed threads stop the node
+        for thread_name in [
+            "consensus worker",
+            "execution",
+            "operation-pool",
+            "selector",
+            "block-factory",
+            "bs-main-loop",
+            "tokio-runtime-worker",
+            "tokio-node",
+            "unnamed",
+        ] {
+            assert_eq!(
+                crash_policy(thread_name, false),
+                CrashPolicy::Shutdown,
+                "{}",
+                thread_name
+            );
+        }
+
+        // once the node is stopping, the follow-up panics are isolated
+        assert_eq!(crash_policy("execution", true), CrashPolicy::Isolate);
+        assert_eq!(
+            crash_policy("tokio-runtime-worker", true),
+            CrashPolicy::Isolate
+        );
+    }
+}
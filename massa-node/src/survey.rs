@@ -44,6 +44,7 @@ impl MassaSurvey {
         execution_controller: Box<dyn ExecutionController>,
         pool_controller: Box<dyn PoolController>,
         massa_metrics: MassaMetrics,
+        tokio_handle: tokio::runtime::Handle,
         config: (u8, MassaTime, MassaTime, u64, u64),
     ) -> MassaSurveyStopper {
         if massa_metrics.is_enabled() {
@@ -124,12 +125,15 @@ impl MassaSurvey {
                                     massa_metrics.set_operations_pool(pool_controller.get_operation_count());
                                     massa_metrics.set_endorsements_pool(pool_controller.get_endorsement_count());
                                     massa_metrics.set_denunciations_pool(pool_controller.get_denunciation_count());
+                                    massa_metrics.set_memory_usage("pool", pool_controller.get_memory_usage());
 
                                     let count = std::thread::available_parallelism()
                                     .unwrap_or(std::num::NonZeroUsize::MIN)
                                     .get();
                                     massa_metrics.set_available_processors(count);
                                 }
+
+                                massa_metrics.update_tokio_runtime(&tokio_handle);
                             }
                         }
                     }) {
@@ -0,0 +1,414 @@
+//! Webhook notifications of noteworthy node events.
+//!
+//! On the selected events, the node POSTs a JSON payload to each configured URL.
+//! The payload is signed with the node keypair: the `X-Massa-Signature` header holds the signature
+//! of the hash of the body, which can be checked against the node id in the `X-Massa-Node-Id` header.
+//! Failed deliveries are retried with an exponential backoff.
+
+use std::sync::Arc;
+
+use hyper::{client::HttpConnector, Body, Client, Method, Request};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use massa_consensus_exports::equivocation::EquivocationEvidence;
+use massa_hash::Hash;
+use massa_models::{
+    address::{Address, ExecutionAddressCycleInfo},
+    block_id::BlockId,
+    node::NodeId,
+    prehash::PreHashMap,
+    slot::Slot,
+};
+use massa_protocol_exports::NetSplitAlert;
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Handle;
+use tracing::{debug, warn};
+
+/// Kinds of events that can be notified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    /// non-final blocks left the blockclique
+    Reorg,
+    /// the node is desynchronized and bootstraps again
+    Desync,
+    /// a block producer signed two different headers for the same slot
+    Equivocation,
+    /// a staking address missed consecutive block draws
+    MissedDraws,
+    /// a peer was banned for sending too many invalid items
+    PeerBanned,
+    /// the blocks finalized recently were first sent by a tiny subset of peers
+    NetSplit,
+}
+
+/// Event notified to the webhooks
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// non-final blocks left the blockclique
+    Reorg {
+        /// number of blocks that left the blockclique
+        depth: usize,
+        /// blocks that left the blockclique, sorted by slot
+        removed_blocks: Vec<(Slot, BlockId)>,
+    },
+    /// the node is desynchronized and bootstraps again
+    Desync,
+    /// a block producer signed two different headers for the same slot
+    Equivocation {
+        /// slot of both headers
+        slot: Slot,
+        /// address of the creator of both headers
+        creator_address: Address,
+        /// ids of both headers
+        block_ids: [BlockId; 2],
+    },
+    /// a staking address of the node missed consecutive block draws
+    MissedDraws {
+        /// staking address
+        address: Address,
+        /// number of consecutive missed blocks
+        consecutive_misses: u64,
+    },
+    /// a peer was banned for sending too many invalid items
+    PeerBanned {
+        /// id of the banned peer
+        peer_id: String,
+        /// number of invalid items sent by the peer within the strike window
+        invalid_items: usize,
+        /// type of the last invalid item sent by the peer
+        item_type: String,
+    },
+    /// the blocks finalized recently were first sent by a tiny subset of peers:
+    /// the node may be eclipsed or on the minority side of a network split
+    NetSplit {
+        /// number of connected peers
+        connected_peers: usize,
+        /// number of distinct subnets of the connected peers
+        connected_subnets: usize,
+        /// number of blocks finalized within the detection window
+        final_blocks: usize,
+        /// number of distinct peers that first sent these blocks
+        final_block_origins: usize,
+        /// number of distinct subnets of these peers
+        final_block_origin_subnets: usize,
+    },
+}
+
+impl WebhookEvent {
+    /// Kind of the event
+    pub fn kind(&self) -> WebhookEventKind {
+        match self {
+            WebhookEvent::Reorg { .. } => WebhookEventKind::Reorg,
+            WebhookEvent::Desync => WebhookEventKind::Desync,
+            WebhookEvent::Equivocation { .. } => WebhookEventKind::Equivocation,
+            WebhookEvent::MissedDraws { .. } => WebhookEventKind::MissedDraws,
+            WebhookEvent::PeerBanned { .. } => WebhookEventKind::PeerBanned,
+            WebhookEvent::NetSplit { .. } => WebhookEventKind::NetSplit,
+        }
+    }
+}
+
+impl From<&EquivocationEvidence> for WebhookEvent {
+    fn from(evidence: &EquivocationEvidence) -> Self {
+        WebhookEvent::Equivocation {
+            slot: evidence.slot,
+            creator_address: evidence.creator_address,
+            block_ids: [evidence.header_1.id, evidence.header_2.id],
+        }
+    }
+}
+
+impl From<&NetSplitAlert> for WebhookEvent {
+    fn from(alert: &NetSplitAlert) -> Self {
+        WebhookEvent::NetSplit {
+            connected_peers: alert.connected_peers,
+            connected_subnets: alert.connected_subnets,
+            final_blocks: alert.final_blocks,
+            final_block_origins: alert.final_block_origins,
+            final_block_origin_subnets: alert.final_block_origin_subnets,
+        }
+    }
+}
+
+/// Payload POSTed to the webhooks
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    /// id of the notifying node
+    node_id: NodeId,
+    /// time at which the event was notified
+    timestamp: MassaTime,
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+}
+
+/// Webhooks configuration
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// URLs to which the events are POSTed
+    pub urls: Vec<String>,
+    /// kinds of events to notify
+    pub events: Vec<WebhookEventKind>,
+    /// minimum number of blocks leaving the blockclique for a reorg to be notified
+    pub min_reorg_depth: usize,
+    /// number of consecutive missed blocks of a staking address to notify
+    pub missed_draws_threshold: u64,
+    /// maximum number of retries of a failed delivery
+    pub max_retries: u32,
+    /// delay before the first retry, doubled at each retry
+    pub retry_delay: MassaTime,
+    /// timeout of a delivery attempt
+    pub timeout: MassaTime,
+}
+
+/// Sends the node events to the configured webhooks
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    config: Arc<WebhookConfig>,
+    keypair: KeyPair,
+    client: Client<HttpsConnector<HttpConnector>>,
+    runtime: Handle,
+}
+
+impl WebhookNotifier {
+    /// Creates a notifier signing the payloads with `keypair`, delivering them on `runtime`
+    pub fn new(config: WebhookConfig, keypair: KeyPair, runtime: Handle) -> Self {
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+        WebhookNotifier {
+            config: Arc::new(config),
+            keypair,
+            client: Client::builder().build(connector),
+            runtime,
+        }
+    }
+
+    /// Whether the events of the given kind are notified
+    pub fn is_enabled(&self, kind: WebhookEventKind) -> bool {
+        !self.config.urls.is_empty() && self.config.events.contains(&kind)
+    }
+
+    /// Number of consecutive missed blocks of a staking address to notify
+    pub fn missed_draws_threshold(&self) -> u64 {
+        self.config.missed_draws_threshold
+    }
+
+    /// Notifies `event` to all the webhooks, if its kind is enabled and it is above the configured thresholds
+    pub fn notify(&self, event: WebhookEvent) {
+        if !self.is_enabled(event.kind()) {
+            return;
+        }
+        if let WebhookEvent::Reorg { depth, .. } = event {
+            if depth < self.config.min_reorg_depth {
+                return;
+            }
+        }
+        let body = match serde_json::to_vec(&WebhookPayload {
+            node_id: NodeId::new(self.keypair.get_public_key()),
+            timestamp: MassaTime::now(),
+            event: &event,
+        }) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("failed to serialize webhook event {:?}: {}", event, e);
+                return;
+            }
+        };
+        let signature = match self.keypair.sign(&Hash::compute_from(&body)) {
+            Ok(signature) => signature.to_string(),
+            Err(e) => {
+                warn!("failed to sign webhook event {:?}: {}", event, e);
+                return;
+            }
+        };
+        let node_id = NodeId::new(self.keypair.get_public_key()).to_string();
+        for url in &self.config.urls {
+            let delivery = Delivery {
+                client: self.client.clone(),
+                url: url.clone(),
+                body: body.clone(),
+                node_id: node_id.clone(),
+                signature: signature.clone(),
+            };
+            self.runtime.spawn(delivery.run(self.config.clone()));
+        }
+    }
+}
+
+/// Delivery of a payload to a webhook
+struct Delivery {
+    client: Client<HttpsConnector<HttpConnector>>,
+    url: String,
+    body: Vec<u8>,
+    node_id: String,
+    signature: String,
+}
+
+impl Delivery {
+    /// Posts the payload, retrying with an exponential backoff until it is accepted
+    async fn run(self, config: Arc<WebhookConfig>) {
+        let mut retry_delay = config.retry_delay.to_duration();
+        for attempt in 0..=config.max_retries {
+            if attempt > 0 {
+                tokio::time::sleep(retry_delay).await;
+                retry_delay = retry_delay.saturating_mul(2);
+            }
+            match tokio::time::timeout(config.timeout.to_duration(), self.post()).await {
+                Ok(Ok(())) => {
+                    debug!("webhook event delivered to {}", self.url);
+                    return;
+                }
+                Ok(Err(e)) => debug!(
+                    "webhook delivery to {} failed (attempt {}): {}",
+                    self.url,
+                    attempt + 1,
+                    e
+                ),
+                Err(_) => debug!(
+                    "webhook delivery to {} timed out (attempt {})",
+                    self.url,
+                    attempt + 1
+                ),
+            }
+        }
+        warn!(
+            "failed to deliver webhook event to {} after {} attempts",
+            self.url,
+            config.max_retries + 1
+        );
+    }
+
+    async fn post(&self) -> anyhow::Result<()> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(&self.url)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .header("X-Massa-Node-Id", &self.node_id)
+            .header("X-Massa-Signature", &self.signature)
+            .body(Body::from(self.body.clone()))?;
+        let response = self.client.request(request).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("status {}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// Block production of an address during a cycle, at the last update
+struct MissStreak {
+    cycle: u64,
+    ok_count: u64,
+    nok_count: u64,
+    consecutive_misses: u64,
+    reported: bool,
+}
+
+/// Counts the consecutive blocks missed by the staking addresses.
+///
+/// The production statistics are polled: misses are counted until a block is produced,
+/// and the order of the blocks produced and missed between two polls is ignored.
+#[derive(Default)]
+pub struct MissedDrawsTracker {
+    streaks: PreHashMap<Address, MissStreak>,
+}
+
+impl MissedDrawsTracker {
+    /// Updates the production statistics of `address` for the latest cycle.
+    /// Returns the number of consecutive misses the first time it reaches `threshold` in a streak.
+    pub fn update(
+        &mut self,
+        address: Address,
+        cycle_info: &ExecutionAddressCycleInfo,
+        threshold: u64,
+    ) -> Option<u64> {
+        let streak = self.streaks.entry(address).or_insert(MissStreak {
+            cycle: cycle_info.cycle,
+            ok_count: cycle_info.ok_count,
+            nok_count: cycle_info.nok_count,
+            consecutive_misses: 0,
+            reported: false,
+        });
+        if streak.cycle != cycle_info.cycle {
+            streak.cycle = cycle_info.cycle;
+            streak.ok_count = 0;
+            streak.nok_count = 0;
+        }
+        let new_ok = cycle_info.ok_count.saturating_sub(streak.ok_count);
+        let new_nok = cycle_info.nok_count.saturating_sub(streak.nok_count);
+        streak.ok_count = cycle_info.ok_count;
+        streak.nok_count = cycle_info.nok_count;
+        if new_ok > 0 {
+            streak.consecutive_misses = 0;
+            streak.reported = false;
+            return None;
+        }
+        streak.consecutive_misses += new_nok;
+        if threshold > 0 && streak.consecutive_misses >= threshold && !streak.reported {
+            streak.reported = true;
+            return Some(streak.consecutive_misses);
+        }
+        None
+    }
+
+    /// Forgets the addresses that are not staking anymore
+    pub fn retain(&mut self, addresses: &[Address]) {
+        self.streaks
+            .retain(|address, _| addresses.contains(address));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle_info(cycle: u64, ok_count: u64, nok_count: u64) -> ExecutionAddressCycleInfo {
+        ExecutionAddressCycleInfo {
+            cycle,
+            is_final: false,
+            ok_count,
+            nok_count,
+            active_rolls: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_missed_draws_tracker() {
+        let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let mut tracker = MissedDrawsTracker::default();
+
+        // misses before the first update are not counted
+        assert_eq!(tracker.update(address, &cycle_info(5, 3, 4), 3), None);
+        assert_eq!(tracker.update(address, &cycle_info(5, 3, 6), 3), None);
+        // the streak continues in the next cycle and is reported once
+        assert_eq!(tracker.update(address, &cycle_info(6, 0, 1), 3), Some(3));
+        assert_eq!(tracker.update(address, &cycle_info(6, 0, 2), 3), None);
+        // a produced block ends the streak
+        assert_eq!(tracker.update(address, &cycle_info(6, 1, 3), 3), None);
+        assert_eq!(tracker.update(address, &cycle_info(6, 1, 6), 3), Some(3));
+    }
+
+    #[test]
+    fn test_webhook_event_payload() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let address = Address::from_public_key(&keypair.get_public_key());
+        let event = WebhookEvent::MissedDraws {
+            address,
+            consecutive_misses: 4,
+        };
+        let payload = serde_json::to_value(WebhookPayload {
+            node_id: NodeId::new(keypair.get_public_key()),
+            timestamp: MassaTime::from_millis(1000),
+            event: &event,
+        })
+        .unwrap();
+        assert_eq!(payload["event"], "missed_draws");
+        assert_eq!(payload["address"], address.to_string());
+        assert_eq!(payload["consecutive_misses"], 4);
+        assert_eq!(event.kind(), WebhookEventKind::MissedDraws);
+    }
+}
@@ -1,6 +1,7 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::amount::Amount;
+use massa_models::config::ChainParametersSchedule;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 
@@ -9,12 +10,9 @@ use serde::{Deserialize, Serialize};
 pub struct PoolConfig {
     /// thread count
     pub thread_count: u8,
-    /// maximal total block operations size
-    pub max_block_size: u32,
-    /// maximal gas per block
-    pub max_block_gas: u64,
-    /// Gas used by any operation
-    pub base_operation_gas_cost: u64,
+    /// block size, gas and operation count limits of each protocol version
+    #[serde(skip)]
+    pub chain_parameters: ChainParametersSchedule,
     /// Amount required for a SinglePass compilation (ExecuteSC operation)
     pub sp_compilation_cost: u64,
     /// cost (in coins) of a single roll
@@ -25,12 +23,18 @@ pub struct PoolConfig {
     pub operation_pool_refresh_interval: MassaTime,
     /// max delay in the future for operation validity start
     pub operation_max_future_start_delay: MassaTime,
-    /// max operations per block
-    pub max_operations_per_block: u32,
     /// max operation pool size per thread (in number of operations)
     pub max_operation_pool_size: usize,
     /// max excess on pool size (in-between refreshes)
     pub max_operation_pool_excess_items: usize,
+    /// max number of deferred operations kept until their start period
+    pub max_scheduled_operations: usize,
+    /// max number of deferred operations of a single creator kept until their start period
+    pub max_scheduled_operations_per_creator: usize,
+    /// max total size (in bytes) of the deferred operations kept until their start period
+    pub max_scheduled_operations_size: usize,
+    /// max number of periods between the current period and the start period of a kept deferred operation
+    pub max_scheduled_start_periods: u64,
     /// max endorsement pool size per thread (in number of endorsements)
     pub max_endorsements_pool_size_per_thread: usize,
     /// max number of endorsements per block
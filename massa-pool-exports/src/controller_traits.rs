@@ -52,9 +52,15 @@ pub trait PoolController: Send + Sync {
     /// Check if the pool contains a list of operations. Returns one boolean per item.
     fn contains_operations(&self, operations: &[OperationId]) -> Vec<bool>;
 
+    /// Get the ids of the best scored operations of the pool, at most `max_count` of them
+    fn get_operation_ids(&self, max_count: usize) -> Vec<OperationId>;
+
     /// Get the number of denunciations in the pool
     fn get_denunciation_count(&self) -> usize;
 
+    /// Get the approximate memory used by the pools, in bytes
+    fn get_memory_usage(&self) -> usize;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn PoolController>`.
     fn clone_box(&self) -> Box<dyn PoolController>;
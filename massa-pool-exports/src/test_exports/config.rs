@@ -3,9 +3,9 @@
 use massa_models::{
     amount::Amount,
     config::{
-        BASE_OPERATION_GAS_COST, DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, MAX_BLOCK_SIZE,
-        MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_GAS_PER_BLOCK, MAX_OPERATIONS_PER_BLOCK,
-        OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, ROLL_PRICE, T0, THREAD_COUNT,
+        ChainParametersSchedule, DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT,
+        MAX_DENUNCIATIONS_PER_BLOCK_HEADER, OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE,
+        ROLL_PRICE, T0, THREAD_COUNT,
     },
 };
 use massa_time::MassaTime;
@@ -17,15 +17,16 @@ impl Default for PoolConfig {
         Self {
             thread_count: THREAD_COUNT,
             operation_validity_periods: OPERATION_VALIDITY_PERIODS,
-            max_block_gas: MAX_GAS_PER_BLOCK,
             sp_compilation_cost: 314_000_000,
             roll_price: ROLL_PRICE,
-            max_block_size: MAX_BLOCK_SIZE,
-            base_operation_gas_cost: BASE_OPERATION_GAS_COST,
+            chain_parameters: ChainParametersSchedule::default(),
             max_operation_pool_size: 32000,
             max_operation_pool_excess_items: 10000,
+            max_scheduled_operations: 10000,
+            max_scheduled_operations_per_creator: 100,
+            max_scheduled_operations_size: 10_000_000,
+            max_scheduled_start_periods: 10000,
             max_endorsements_pool_size_per_thread: 1000,
-            max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
             max_block_endorsement_count: ENDORSEMENT_COUNT,
             operations_channel_size: 1024,
             endorsements_channel_size: 1024,
@@ -216,11 +216,23 @@ impl PoolController for PoolControllerImpl {
         operations.iter().map(|id| lck.contains(id)).collect()
     }
 
+    /// Get the ids of the best scored operations of the pool, at most `max_count` of them
+    fn get_operation_ids(&self, max_count: usize) -> Vec<OperationId> {
+        self.operation_pool.read().get_operation_ids(max_count)
+    }
+
     /// Get the number of denunciations in the pool
     fn get_denunciation_count(&self) -> usize {
         self.denunciation_pool.read().len()
     }
 
+    /// Get the approximate memory used by the pools, in bytes
+    fn get_memory_usage(&self) -> usize {
+        self.operation_pool.read().memory_usage()
+            + self.endorsement_pool.read().memory_usage()
+            + self.denunciation_pool.read().memory_usage()
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn PoolController>`,
     fn clone_box(&self) -> Box<dyn PoolController> {
@@ -43,6 +43,12 @@ impl DenunciationPool {
             .count()
     }
 
+    /// Approximate memory used by the pool, in bytes
+    pub fn memory_usage(&self) -> usize {
+        self.denunciations_cache.len()
+            * std::mem::size_of::<(DenunciationIndex, DenunciationStatus)>()
+    }
+
     /// Checks whether an element is stored in the pool - only used in unit tests for now
     #[cfg(feature = "test-exports")]
     pub fn _contains(&self, denunciation: &Denunciation) -> bool {
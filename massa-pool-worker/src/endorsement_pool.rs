@@ -11,7 +11,7 @@ use massa_storage::Storage;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::{
-    collections::{hash_map::Entry, BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap},
     sync::Arc,
 };
 use tracing::{trace, warn};
@@ -20,7 +20,8 @@ pub struct EndorsementPool {
     /// configuration
     config: PoolConfig,
 
-    /// endorsements indexed by slot, index and block ID
+    /// endorsements indexed by slot, index and block ID.
+    /// Only the best endorsement of each (slot, index, block ID) triple is kept, see `is_better_endorsement`
     endorsements_indexed: HashMap<(Slot, u32, BlockId), EndorsementId>,
 
     /// endorsements sorted by increasing inclusion slot for pruning
@@ -63,6 +64,25 @@ impl EndorsementPool {
         self.storage.get_endorsement_refs().len()
     }
 
+    /// Approximate memory used by the pool in bytes, including the endorsements it references in storage
+    pub fn memory_usage(&self) -> usize {
+        let index_entries = self.endorsements_indexed.capacity()
+            + self
+                .endorsements_sorted
+                .iter()
+                .map(|sorted| sorted.len())
+                .sum::<usize>();
+        let endo_store = self.storage.read_endorsements();
+        let endorsements: usize = self
+            .storage
+            .get_endorsement_refs()
+            .iter()
+            .filter_map(|endo_id| endo_store.get(endo_id))
+            .map(|endo| endo.serialized_size())
+            .sum();
+        index_entries * std::mem::size_of::<((Slot, u32, BlockId), EndorsementId)>() + endorsements
+    }
+
     /// Checks whether an element is stored in the pool.
     pub fn contains(&self, id: &EndorsementId) -> bool {
         self.storage.get_endorsement_refs().contains(id)
@@ -143,6 +163,18 @@ impl EndorsementPool {
                     continue;
                 }
 
+                // ignore equivalent endorsements (same slot, index and endorsed block) that are not better than the stored one
+                let key = (
+                    endo.content.slot,
+                    endo.content.index,
+                    endo.content.endorsed_block,
+                );
+                if let Some(stored_id) = self.endorsements_indexed.get(&key) {
+                    if !is_better_endorsement(&endo.id, stored_id) {
+                        continue;
+                    }
+                }
+
                 // Broadcast endorsement to active channel subscribers.
                 if self.config.broadcast_enabled {
                     if let Err(err) = self
@@ -164,23 +196,19 @@ impl EndorsementPool {
                     continue;
                 }
 
-                // insert
-                let key = (
-                    endo.content.slot,
-                    endo.content.index,
-                    endo.content.endorsed_block,
-                );
-                // note that we don't want equivalent endorsements (slot, index, block etc...) to overwrite each other
-                if let Entry::Vacant(e) = self.endorsements_indexed.entry(key) {
-                    e.insert(endo.id);
-                    if self.endorsements_sorted[endo.content.slot.thread as usize]
-                        .insert(key, endo.id)
-                        .is_some()
-                    {
-                        panic!("endorsement is expected to be absent from endorsements_sorted at this point");
+                // insert, replacing the equivalent endorsement if any (it was checked to be worse above)
+                let replaced = self.endorsements_indexed.insert(key, endo.id);
+                if self.endorsements_sorted[endo.content.slot.thread as usize].insert(key, endo.id)
+                    != replaced
+                {
+                    panic!("endorsements_sorted is expected to be consistent with endorsements_indexed at this point");
+                }
+                if let Some(replaced_id) = replaced {
+                    if !added.remove(&replaced_id) {
+                        removed.insert(replaced_id);
                     }
-                    added.insert(endo.id);
                 }
+                added.insert(endo.id);
             }
         }
 
@@ -240,3 +268,11 @@ impl EndorsementPool {
         (endo_ids, endo_storage)
     }
 }
+
+/// Among equivalent endorsements (same slot, index and endorsed block), tells whether `candidate` should replace `stored`.
+///
+/// The endorsement with the lowest ID is preferred, so that all nodes converge on the same one
+/// whatever the order in which they received them.
+fn is_better_endorsement(candidate: &EndorsementId, stored: &EndorsementId) -> bool {
+    candidate < stored
+}
@@ -6,14 +6,21 @@ use massa_models::{
     operation::OperationId,
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
     slot::Slot,
-    timeslots::get_latest_block_slot_at_timestamp,
+    timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
 };
 use massa_pool_exports::{PoolChannels, PoolConfig};
 use massa_storage::Storage;
 use massa_time::MassaTime;
+use massa_versioning::versioning::{MipComponent, MipStore};
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
-use std::{cmp::max, cmp::Ordering, cmp::PartialOrd, collections::BTreeSet, sync::Arc};
+use std::{
+    cmp::max,
+    cmp::Ordering,
+    cmp::PartialOrd,
+    collections::{hash_map::Entry, BTreeMap, BTreeSet},
+    sync::Arc,
+};
 use tracing::{debug, trace, warn};
 
 use crate::types::OperationInfo;
@@ -25,6 +32,19 @@ pub struct OperationPool {
     /// operations map
     sorted_ops: Vec<OperationInfo>,
 
+    /// deferred operations whose start period is beyond the PoS draw search horizon, by start period.
+    /// They are kept in storage and moved to `sorted_ops` once their start period gets close.
+    scheduled_ops: BTreeMap<u64, PreHashMap<OperationId, OperationInfo>>,
+
+    /// operations of `scheduled_ops` with their start period, by ascending fee, to evict the cheapest ones first
+    scheduled_ops_by_fee: BTreeSet<(Amount, u64, OperationId)>,
+
+    /// number of operations in `scheduled_ops` per creator
+    scheduled_ops_per_creator: PreHashMap<Address, usize>,
+
+    /// total size of the operations in `scheduled_ops`, in bytes
+    scheduled_ops_size: usize,
+
     /// storage instance
     pub(crate) storage: Storage,
 
@@ -36,6 +56,9 @@ pub struct OperationPool {
 
     /// staking wallet, to know which addresses we are using to stake
     wallet: Arc<RwLock<Wallet>>,
+
+    /// versioning store, to only accept the operation formats that are active
+    mip_store: MipStore,
 }
 
 impl OperationPool {
@@ -44,6 +67,7 @@ impl OperationPool {
         storage: &Storage,
         channels: PoolChannels,
         wallet: Arc<RwLock<Wallet>>,
+        mip_store: MipStore,
     ) -> Self {
         OperationPool {
             sorted_ops: Vec::with_capacity(
@@ -51,18 +75,47 @@ impl OperationPool {
                     .max_operation_pool_size
                     .saturating_add(config.max_operation_pool_excess_items),
             ),
+            scheduled_ops: BTreeMap::new(),
+            scheduled_ops_by_fee: BTreeSet::new(),
+            scheduled_ops_per_creator: PreHashMap::default(),
+            scheduled_ops_size: 0,
             last_cs_final_periods: vec![0u64; config.thread_count as usize],
             config,
             storage: storage.clone_without_refs(),
             channels,
             wallet,
+            mip_store,
         }
     }
 
+    /// Get the last slot of interest for block production:
+    /// the slot after now() + max future start delay + margin
+    fn get_horizon_slot(&self) -> Option<Slot> {
+        get_latest_block_slot_at_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            MassaTime::now()
+                .saturating_add(self.config.operation_max_future_start_delay)
+                .saturating_add(self.config.t0.saturating_mul(2)),
+        )
+        .unwrap_or(Some(Slot::max(self.config.thread_count)))
+    }
+
+    /// Get the period of the current slot
+    fn get_current_period(&self) -> u64 {
+        get_latest_block_slot_at_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            MassaTime::now(),
+        )
+        .expect("could not get current slot")
+        .map_or(0, |slot| slot.period)
+    }
+
     /// Get the relevant PoS draws of our staking addresses
     fn get_pos_draws(&mut self) -> BTreeSet<Slot> {
-        let now = MassaTime::now();
-
         // min slot for PoS draw search = the earliest final slot
         let min_slot = self
             .last_cs_final_periods
@@ -72,15 +125,7 @@ impl OperationPool {
             .min()
             .expect("empty last_vs_final_periods in operation pool");
         // max slot for PoS draw search = the slot after now() + max future start delay + margin
-        let max_slot = get_latest_block_slot_at_timestamp(
-            self.config.thread_count,
-            self.config.t0,
-            self.config.genesis_timestamp,
-            now.saturating_add(self.config.operation_max_future_start_delay)
-                .saturating_add(self.config.t0.saturating_mul(2)),
-        )
-        .unwrap_or(Some(Slot::max(self.config.thread_count)))
-        .unwrap_or(min_slot);
+        let max_slot = self.get_horizon_slot().unwrap_or(min_slot);
         let max_slot = max(max_slot, min_slot);
 
         // search for all our PoS draws in the interval of interest
@@ -124,13 +169,20 @@ impl OperationPool {
             .collect()
     }
 
-    /// Get the candidate balances of the addresses sending the ops.
+    /// Get the candidate balances of the addresses sending the ops or paying their fee.
     /// Addresses that don't exist are not returned.
     fn get_sender_balances(&self) -> PreHashMap<Address, Amount> {
-        let addrs: Vec<Address> = self
-            .sorted_ops
-            .iter()
-            .map(|op_info| op_info.creator_address)
+        self.get_candidate_balances(self.sorted_ops.iter())
+    }
+
+    /// Get the candidate balances of the addresses sending the given ops or paying their fee.
+    /// Addresses that don't exist are not returned.
+    fn get_candidate_balances<'a>(
+        &self,
+        op_infos: impl Iterator<Item = &'a OperationInfo>,
+    ) -> PreHashMap<Address, Amount> {
+        let addrs: Vec<Address> = op_infos
+            .flat_map(|op_info| op_info.spendings().map(|(addr, _)| addr))
             .collect::<PreHashSet<Address>>()
             .into_iter()
             .collect();
@@ -153,17 +205,15 @@ impl OperationPool {
     ) {
         let mut removed = PreHashSet::default();
         self.sorted_ops.retain(|op_info| {
-            // filter out ops that use too much resources
-            let mut retain = (op_info.max_gas_usage <= self.config.max_block_gas)
-                && (op_info.size <= self.config.max_block_size as usize);
-
-            // filter out ops that are not valid during our PoS draws
-            if retain {
-                retain = pos_draws.iter().any(|slot| {
-                    op_info.thread == slot.thread
-                        && op_info.validity_period_range.contains(&slot.period)
-                });
-            }
+            // filter out ops that are not valid during our PoS draws,
+            // or that use too much resources for the blocks of those draws
+            let mut retain = pos_draws.iter().any(|slot| {
+                let parameters = self.config.chain_parameters.parameters_at(*slot);
+                op_info.thread == slot.thread
+                    && op_info.validity_period_range.contains(&slot.period)
+                    && op_info.max_gas_usage <= parameters.max_gas_per_block
+                    && op_info.size <= parameters.max_block_size as usize
+            });
 
             if retain {
                 // filter ops which doesn't have minimal fees
@@ -176,12 +226,15 @@ impl OperationPool {
                 retain = !exec_statuses.contains_key(&op_info.id);
             }
 
-            // filter out ops that spend more than the sender's balance
+            // filter out ops that spend more than the balance of the sender or of the fee payer
             if retain {
-                retain = match sender_balances.get(&op_info.creator_address) {
-                    Some(v) => &op_info.max_spending <= v,
-                    None => false, // filter out ops for which the sender does not exist
-                };
+                retain =
+                    op_info
+                        .spendings()
+                        .all(|(addr, spending)| match sender_balances.get(&addr) {
+                            Some(v) => &spending <= v,
+                            None => false, // filter out ops for which the sender or fee payer does not exist
+                        });
             }
 
             if !retain {
@@ -194,30 +247,31 @@ impl OperationPool {
         self.storage.drop_operation_refs(&removed);
     }
 
-    /// Eliminate all operations that would cause a sender balance overflow.
+    /// Eliminate all operations that would cause a sender or fee payer balance overflow.
     /// Assumes that the ops are sorted by ascending score.
     fn eliminate_balance_overflows(&mut self, sender_balances: &PreHashMap<Address, Amount>) {
-        let mut balance_cache = PreHashMap::default();
+        let mut balance_cache: PreHashMap<Address, Amount> = PreHashMap::default();
         let mut removed = PreHashSet::default();
         self.sorted_ops.retain(|op_info| {
-            let balance = balance_cache
-                .entry(op_info.creator_address)
-                .or_insert_with(|| {
-                    sender_balances
-                        .get(&op_info.creator_address)
-                        .copied()
-                        .unwrap_or_default()
-                });
-            match balance.checked_sub(op_info.max_spending) {
-                Some(v) => {
-                    *balance = v;
-                    true
-                }
-                None => {
-                    removed.insert(op_info.id);
-                    false
+            // compute the remaining balances without committing them, as all payers must afford the op
+            let mut new_balances = Vec::with_capacity(2);
+            for (addr, spending) in op_info.spendings() {
+                let balance = new_balances
+                    .iter()
+                    .find(|(a, _)| a == &addr)
+                    .map(|(_, b)| *b)
+                    .or_else(|| balance_cache.get(&addr).copied())
+                    .unwrap_or_else(|| sender_balances.get(&addr).copied().unwrap_or_default());
+                match balance.checked_sub(spending) {
+                    Some(v) => new_balances.push((addr, v)),
+                    None => {
+                        removed.insert(op_info.id);
+                        return false;
+                    }
                 }
             }
+            balance_cache.extend(new_balances);
+            true
         });
         // drop from storage
         self.storage.drop_operation_refs(&removed);
@@ -248,14 +302,16 @@ impl OperationPool {
         pos_draws: &BTreeSet<Slot>,
     ) -> PreHashMap<OperationId, f32> {
         let now = MassaTime::now();
-        let now_period = get_latest_block_slot_at_timestamp(
+        let now_slot = get_latest_block_slot_at_timestamp(
             self.config.thread_count,
             self.config.t0,
             self.config.genesis_timestamp,
             now,
         )
         .expect("could not get current slot")
-        .map_or(0, |s| s.period);
+        .unwrap_or_else(|| Slot::new(0, 0));
+        let now_period = now_slot.period;
+        let parameters = self.config.chain_parameters.parameters_at(now_slot);
 
         let mut scores = PreHashMap::with_capacity(self.sorted_ops.len());
         for op_info in &self.sorted_ops {
@@ -266,13 +322,13 @@ impl OperationPool {
             // size score:
             //    0% of block size => score 1
             //    100% of block size => score 0
-            let size_score = 1.0 - (op_info.size as f32) / (self.config.max_block_size as f32);
+            let size_score = 1.0 - (op_info.size as f32) / (parameters.max_block_size as f32);
 
             // gas score:
             //    0% of block gas => score 1
             //    100% of block gas => score 0
             let gas_score =
-                1.0 - (op_info.max_gas_usage as f32) / (self.config.max_block_gas as f32);
+                1.0 - (op_info.max_gas_usage as f32) / (parameters.max_gas_per_block as f32);
 
             // general resource score (mean of gas and size scores)
             let epsilon_resource_factor = 0.0001; // avoids zero score when gas and size are a perfect fit in the block
@@ -330,9 +386,161 @@ impl OperationPool {
         scores
     }
 
+    /// Move the deferred operations whose start period is now within the PoS draw search horizon
+    /// to the end of the sorted ops, so that they get scored.
+    fn release_scheduled_ops(&mut self) {
+        let horizon_period = self.get_horizon_slot().map_or(0, |slot| slot.period);
+        let released: Vec<(u64, OperationId)> = self
+            .scheduled_ops
+            .range(..=horizon_period)
+            .flat_map(|(start_period, ops)| ops.keys().map(|op_id| (*start_period, *op_id)))
+            .collect();
+        for (start_period, op_id) in released {
+            if let Some(op_info) = self.unschedule_op(start_period, &op_id) {
+                self.sorted_ops.push(op_info);
+            }
+        }
+    }
+
+    /// Stop holding a deferred operation, and return it
+    fn unschedule_op(&mut self, start_period: u64, op_id: &OperationId) -> Option<OperationInfo> {
+        let ops = self.scheduled_ops.get_mut(&start_period)?;
+        let op_info = ops.remove(op_id)?;
+        if ops.is_empty() {
+            self.scheduled_ops.remove(&start_period);
+        }
+        self.scheduled_ops_by_fee
+            .remove(&(op_info.fee, start_period, *op_id));
+        if let Entry::Occupied(mut count) = self
+            .scheduled_ops_per_creator
+            .entry(op_info.creator_address)
+        {
+            *count.get_mut() -= 1;
+            if *count.get() == 0 {
+                count.remove();
+            }
+        }
+        self.scheduled_ops_size -= op_info.size;
+        Some(op_info)
+    }
+
+    /// Hold a deferred operation until its start period gets close.
+    /// If the limits of the scheduled operations are reached, the scheduled operations paying lower fees are evicted
+    /// to make room for it, and they are returned.
+    /// Returns `None` if the operation can't be held.
+    fn schedule_op(
+        &mut self,
+        start_period: u64,
+        op_info: OperationInfo,
+    ) -> Option<Vec<OperationId>> {
+        let creator_count = self
+            .scheduled_ops_per_creator
+            .get(&op_info.creator_address)
+            .copied()
+            .unwrap_or_default();
+        if creator_count >= self.config.max_scheduled_operations_per_creator {
+            return None;
+        }
+
+        // find the cheapest scheduled operations to evict
+        let mut to_evict = Vec::new();
+        let mut freed_size = 0usize;
+        let mut cheapest = self.scheduled_ops_by_fee.iter();
+        while self.scheduled_ops_by_fee.len() - to_evict.len()
+            >= self.config.max_scheduled_operations
+            || (self.scheduled_ops_size - freed_size).saturating_add(op_info.size)
+                > self.config.max_scheduled_operations_size
+        {
+            let (fee, evicted_start_period, evicted_id) = cheapest.next()?;
+            if *fee >= op_info.fee {
+                return None;
+            }
+            freed_size += self.scheduled_ops[evicted_start_period][evicted_id].size;
+            to_evict.push((*evicted_start_period, *evicted_id));
+        }
+
+        let evicted = to_evict
+            .into_iter()
+            .filter_map(|(evicted_start_period, evicted_id)| {
+                self.unschedule_op(evicted_start_period, &evicted_id)
+                    .map(|evicted| evicted.id)
+            })
+            .collect();
+        *self
+            .scheduled_ops_per_creator
+            .entry(op_info.creator_address)
+            .or_default() += 1;
+        self.scheduled_ops_size += op_info.size;
+        self.scheduled_ops_by_fee
+            .insert((op_info.fee, start_period, op_info.id));
+        self.scheduled_ops
+            .entry(start_period)
+            .or_default()
+            .insert(op_info.id, op_info);
+        Some(evicted)
+    }
+
+    /// Hold the given deferred operations until their start period gets close.
+    /// Only the operations starting within `max_scheduled_start_periods` of the current period,
+    /// paying the minimal fees and whose sender and fee payer exist and can pay the fee are held.
+    /// Returns the ids of the held operations.
+    fn schedule_ops(&mut self, deferred_ops: Vec<(u64, OperationInfo)>) -> PreHashSet<OperationId> {
+        let mut scheduled_op_ids = PreHashSet::default();
+        if deferred_ops.is_empty() {
+            return scheduled_op_ids;
+        }
+        let max_start_period = self
+            .get_current_period()
+            .saturating_add(self.config.max_scheduled_start_periods);
+        let balances = self.get_candidate_balances(deferred_ops.iter().map(|(_, op_info)| op_info));
+
+        let mut evicted_op_ids = PreHashSet::default();
+        let mut dropped_scheduled = 0usize;
+        for (start_period, op_info) in deferred_ops {
+            let fee_payer = op_info.fee_payer.unwrap_or(op_info.creator_address);
+            let acceptable = start_period <= max_start_period
+                && op_info.fee >= self.config.minimal_fees
+                && op_info
+                    .spendings()
+                    .all(|(addr, _)| balances.contains_key(&addr))
+                && balances
+                    .get(&fee_payer)
+                    .is_some_and(|balance| *balance >= op_info.fee);
+            let op_id = op_info.id;
+            let evicted = if acceptable {
+                self.schedule_op(start_period, op_info)
+            } else {
+                None
+            };
+            let Some(evicted) = evicted else {
+                dropped_scheduled += 1;
+                continue;
+            };
+            scheduled_op_ids.insert(op_id);
+            for evicted_id in evicted {
+                // the operations evicted before being stored are just not stored
+                if !scheduled_op_ids.remove(&evicted_id) {
+                    evicted_op_ids.insert(evicted_id);
+                }
+                dropped_scheduled += 1;
+            }
+        }
+        self.storage.drop_operation_refs(&evicted_op_ids);
+        if dropped_scheduled > 0 {
+            warn!(
+                "Dropping {} deferred operations that can't be held until their start period.",
+                dropped_scheduled
+            );
+        }
+        scheduled_op_ids
+    }
+
     /// Refresh the pool.
     /// Note that this function is very heavy and we call it only periodically, timer-based.
     pub(crate) fn refresh(&mut self) {
+        // move the deferred ops that can soon be included to the scored ops
+        self.release_scheduled_ops();
+
         // get PoS draws
         let pos_draws = self.get_pos_draws();
 
@@ -370,11 +578,32 @@ impl OperationPool {
         self.sorted_ops.len()
     }
 
+    /// Approximate memory used by the pool in bytes, including the operations it references in storage
+    pub fn memory_usage(&self) -> usize {
+        self.sorted_ops.capacity() * std::mem::size_of::<OperationInfo>()
+            + self
+                .sorted_ops
+                .iter()
+                .map(|op_info| op_info.size)
+                .sum::<usize>()
+            + self.scheduled_ops_by_fee.len() * std::mem::size_of::<OperationInfo>()
+            + self.scheduled_ops_size
+    }
+
     /// Checks whether an element is stored in the pool.
     pub fn contains(&self, id: &OperationId) -> bool {
         self.storage.get_op_refs().contains(id)
     }
 
+    /// Get the ids of the best scored operations, at most `max_count` of them
+    pub fn get_operation_ids(&self, max_count: usize) -> Vec<OperationId> {
+        self.sorted_ops
+            .iter()
+            .take(max_count)
+            .map(|op_info| op_info.id)
+            .collect()
+    }
+
     /// notify of new final slot
     pub(crate) fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         // update internal final slot counter
@@ -391,6 +620,70 @@ impl OperationPool {
         // List all the new operations
         let mut new_op_ids = ops_storage.get_op_refs() - self.storage.get_op_refs();
 
+        // Drop the operations that can't be included after the last final slot of their thread anymore,
+        // the sponsored operations whose fee payer is in another thread than their creator,
+        // and the operations whose format is not active yet.
+        {
+            let now = MassaTime::now();
+            let ops = ops_storage.read_operations();
+            new_op_ids.retain(|op_id| {
+                let op = ops
+                    .get(op_id)
+                    .expect("operation not found in storage but listed as owned");
+                if op.check_fee_payer_thread(self.config.thread_count).is_err()
+                    || !self
+                        .mip_store
+                        .is_operation_format_active_at(&op.content, now)
+                {
+                    return false;
+                }
+                let thread = op
+                    .content_creator_address
+                    .get_thread(self.config.thread_count);
+                let last_final_slot =
+                    Slot::new(self.last_cs_final_periods[thread as usize], thread);
+                !op.is_expired_at(&last_final_slot, self.config.thread_count)
+            });
+        }
+
+        // Keep aside the deferred operations that can't be included before the PoS draw search horizon:
+        // they would be dropped by the next refresh otherwise.
+        // They are still stored, so that they are not fetched again from the network.
+        let scheduled_op_ids = {
+            let horizon_period = self.get_horizon_slot().map_or(0, |slot| slot.period);
+            let ops = ops_storage.read_operations();
+            let deferred_ops: Vec<(u64, OperationInfo)> = new_op_ids
+                .iter()
+                .filter_map(|op_id| {
+                    let op = ops
+                        .get(op_id)
+                        .expect("operation not found in storage but listed as owned");
+                    match op.content.start_period {
+                        Some(start_period) if start_period > horizon_period => Some((
+                            start_period,
+                            OperationInfo::from_op(
+                                op,
+                                self.config.operation_validity_periods,
+                                self.config.roll_price,
+                                self.config.thread_count,
+                                // gas of the operation once it can be included
+                                self.config
+                                    .chain_parameters
+                                    .parameters_at(Slot::new(start_period, 0))
+                                    .base_operation_gas_cost,
+                                self.config.sp_compilation_cost,
+                            ),
+                        )),
+                        _ => None,
+                    }
+                })
+                .collect();
+            for (_, op_info) in &deferred_ops {
+                new_op_ids.remove(&op_info.id);
+            }
+            self.schedule_ops(deferred_ops)
+        };
+
         // If there are too many extra operations,
         // we don't want the container to fill up too much in-between refreshes so we drop any excess.
         // This is because refreshing the container is very heavy and is only called periodically.
@@ -419,6 +712,11 @@ impl OperationPool {
         // so that they can still be picked for block production before refresh but with low priority
         // because in that case we don't know anything about their quality.
         {
+            let base_operation_gas_cost = self
+                .config
+                .chain_parameters
+                .parameters_at(Slot::new(self.get_current_period(), 0))
+                .base_operation_gas_cost;
             let ops = ops_storage.read_operations();
             for new_op_id in &new_op_ids {
                 let op = ops
@@ -437,7 +735,7 @@ impl OperationPool {
                     self.config.operation_validity_periods,
                     self.config.roll_price,
                     self.config.thread_count,
-                    self.config.base_operation_gas_cost,
+                    base_operation_gas_cost,
                     self.config.sp_compilation_cost,
                 ));
             }
@@ -449,6 +747,7 @@ impl OperationPool {
         // at the end of the scope ops_storage will be dropped and so the references will be only in `self.storage`
         // If the object wasn't in `self.storage` the reference will be transferred and so the number of owners doesn't change
         // and when we will drop `ops_storage` it doesn't have the references anymore and so doesn't drop those objects.
+        new_op_ids.extend(scheduled_op_ids);
         self.storage.extend(ops_storage.split_off(
             &Default::default(),
             &new_op_ids,
@@ -465,12 +764,25 @@ impl OperationPool {
         // init list of selected operation IDs
         let mut op_ids = Vec::new();
 
+        // limits of the block at this slot
+        let parameters = self.config.chain_parameters.parameters_at(*slot);
         // init remaining space
-        let mut remaining_space = self.config.max_block_size as usize;
+        let mut remaining_space = parameters.max_block_size as usize;
         // init remaining gas
-        let mut remaining_gas = self.config.max_block_gas;
+        let mut remaining_gas = parameters.max_gas_per_block;
         // init remaining number of operations
-        let mut remaining_ops = self.config.max_operations_per_block;
+        let mut remaining_ops = parameters.max_operations_per_block;
+        // latest operation format active at this slot
+        let slot_timestamp = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            *slot,
+        )
+        .expect("could not get slot timestamp");
+        let format_version = self
+            .mip_store
+            .get_latest_component_version_at(&MipComponent::Operation, slot_timestamp);
 
         // iterate over pool operations in the right thread, from best to worst
         for op_info in &self.sorted_ops {
@@ -489,6 +801,11 @@ impl OperationPool {
                 continue;
             }
 
+            // exclude ops whose format is not active at the block slot
+            if op_info.format_version > format_version {
+                continue;
+            }
+
             // exclude ops that are too large
             if op_info.size > remaining_space {
                 continue;
@@ -1,6 +1,10 @@
 use std::{collections::BTreeMap, time::Duration};
 
-use massa_models::{address::Address, config::THREAD_COUNT, slot::Slot};
+use massa_hash::Hash;
+use massa_models::{
+    address::Address, config::THREAD_COUNT, endorsement::EndorsementId, secure_share::Id,
+    slot::Slot,
+};
 use massa_pool_exports::PoolConfig;
 use massa_pos_exports::{MockSelectorController, Selection};
 use massa_signature::KeyPair;
@@ -255,3 +259,45 @@ fn test_get_block_endorsements_works() {
         },
     );
 }
+
+#[test]
+fn test_equivalent_endorsements_are_deduplicated() {
+    let sender_keypair = KeyPair::generate(0).unwrap();
+    let address = Address::from_public_key(&sender_keypair.get_public_key());
+    let execution_controller = default_mock_execution_controller();
+    let selector_controller = default_mock_selector(address);
+
+    pool_test(
+        PoolConfig::default(),
+        execution_controller,
+        selector_controller,
+        Some((address, sender_keypair.clone())),
+        |mut pool, mut storage| {
+            // two endorsements of the same block at the same slot and index, with different IDs
+            let endorsement = create_endorsement(&sender_keypair, 0, Slot::new(1, 2));
+            let mut equivalent = endorsement.clone();
+            equivalent.id = EndorsementId::new(Hash::compute_from("equivalent".as_bytes()));
+            let (best, worst) = if endorsement.id < equivalent.id {
+                (endorsement, equivalent)
+            } else {
+                (equivalent, endorsement)
+            };
+
+            // the worst is received first, then replaced by the best
+            storage.store_endorsements(vec![worst.clone()]);
+            pool.add_endorsements(storage.clone());
+            let mut best_storage = storage.clone_without_refs();
+            best_storage.store_endorsements(vec![best.clone()]);
+            pool.add_endorsements(best_storage);
+            // the worst is received again and ignored
+            pool.add_endorsements(storage.clone());
+            // Allow some time for the pool to add the endorsements
+            std::thread::sleep(Duration::from_secs(2));
+
+            assert_eq!(pool.get_endorsement_count(), 1);
+            let (endorsement_ids, _) =
+                pool.get_block_endorsements(&best.content.endorsed_block, &Slot::new(1, 2));
+            assert_eq!(endorsement_ids[0], Some(best.id));
+        },
+    );
+}
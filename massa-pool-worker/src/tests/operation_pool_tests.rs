@@ -20,11 +20,20 @@
 use crate::tests::tools::OpGenerator;
 
 use super::tools::{
-    create_some_operations, default_mock_execution_controller, pool_test, PoolTestBoilerPlate,
+    create_some_operations, default_mock_execution_controller, genesis_chain_parameters, pool_test,
+    PoolTestBoilerPlate,
 };
-use massa_models::{amount::Amount, config::ENDORSEMENT_COUNT, operation::OperationId, slot::Slot};
-use massa_pool_exports::PoolConfig;
+use massa_execution_exports::MockExecutionController;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    config::{ChainParameters, ENDORSEMENT_COUNT, GENESIS_CHAIN_PARAMETERS},
+    operation::OperationId,
+    slot::Slot,
+};
+use massa_pool_exports::{PoolConfig, PoolController};
 use massa_pos_exports::{MockSelectorController, Selection};
+use massa_signature::KeyPair;
 use std::{collections::BTreeMap, time::Duration};
 
 #[test]
@@ -79,6 +88,154 @@ fn test_add_operation() {
     );
 }
 
+/// Test that deferred operations starting far in the future are kept aside from the scored operations,
+/// within the scheduled operations limit.
+#[test]
+fn test_add_deferred_operation() {
+    let execution_controller = default_mock_execution_controller();
+    let selector_controller = {
+        let mut res = Box::new(MockSelectorController::new());
+        res.expect_clone_box().times(2).returning(|| {
+            let mut story = MockSelectorController::new();
+            story
+                .expect_get_available_selections_in_range()
+                .returning(|_, _| Ok(BTreeMap::new()));
+            Box::new(story)
+        });
+        res
+    };
+    let pool_config = PoolConfig {
+        max_scheduled_operations: 5,
+        ..Default::default()
+    };
+    pool_test(
+        pool_config,
+        execution_controller,
+        selector_controller,
+        None,
+        |mut operation_pool, mut storage| {
+            let op_gen = OpGenerator::default().start_period(1000).expirery(1010);
+            let ops = create_some_operations(10, &op_gen);
+            let op_ids: Vec<OperationId> = ops.iter().map(|op| op.id).collect();
+            storage.store_operations(ops);
+            operation_pool.add_operations(storage);
+            // Allow some time for the pool to add the operations
+            std::thread::sleep(Duration::from_secs(3));
+            assert_eq!(operation_pool.get_operation_count(), 0);
+            let contained = operation_pool.contains_operations(&op_ids);
+            assert_eq!(contained.into_iter().filter(|c| *c).count(), 5);
+        },
+    );
+}
+
+/// Test that the deferred operations are only held within the scheduled operations limits:
+/// per creator, in start period, for existing senders, and evicting the lowest fees first when full.
+#[test]
+fn test_scheduled_operation_limits() {
+    let unknown_keypair = KeyPair::generate(0).unwrap();
+    let unknown_address = Address::from_public_key(&unknown_keypair.get_public_key());
+    let mut execution_controller = Box::new(MockExecutionController::new());
+    execution_controller.expect_clone_box().returning(move || {
+        let mut story = MockExecutionController::new();
+        story
+            .expect_get_ops_exec_status()
+            .returning(|ops| vec![(None, None); ops.len()]);
+        story
+            .expect_get_final_and_candidate_balance()
+            .returning(move |addrs| {
+                addrs
+                    .iter()
+                    .map(|addr| {
+                        if *addr == unknown_address {
+                            (None, None)
+                        } else {
+                            let balance = Some(Amount::const_init(1_000_000_000, 0));
+                            (balance, balance)
+                        }
+                    })
+                    .collect()
+            });
+        Box::new(story)
+    });
+    let selector_controller = {
+        let mut res = Box::new(MockSelectorController::new());
+        res.expect_clone_box().times(2).returning(|| {
+            let mut story = MockSelectorController::new();
+            story
+                .expect_get_available_selections_in_range()
+                .returning(|_, _| Ok(BTreeMap::new()));
+            Box::new(story)
+        });
+        res
+    };
+    let pool_config = PoolConfig {
+        max_scheduled_operations: 3,
+        max_scheduled_operations_per_creator: 2,
+        max_scheduled_start_periods: 2000,
+        ..Default::default()
+    };
+    pool_test(
+        pool_config,
+        execution_controller,
+        selector_controller,
+        None,
+        |mut operation_pool, storage| {
+            let add = |operation_pool: &mut Box<dyn PoolController>, op_gen: OpGenerator| {
+                let op = op_gen.generate();
+                let op_id = op.id;
+                let mut ops_storage = storage.clone_without_refs();
+                ops_storage.store_operations(vec![op]);
+                operation_pool.add_operations(ops_storage);
+                // Allow some time for the pool to add the operation
+                std::thread::sleep(Duration::from_millis(200));
+                op_id
+            };
+            let deferred = |fee: u64| {
+                OpGenerator::default()
+                    .start_period(1000)
+                    .expirery(1010)
+                    .fee(Amount::from_raw(fee))
+            };
+            let creator = KeyPair::generate(0).unwrap();
+
+            // at most 2 operations per creator
+            let creator_ops: Vec<OperationId> = (0..3)
+                .map(|_| add(&mut operation_pool, deferred(1).creator(creator.clone())))
+                .collect();
+            // unknown senders and start periods too far away are ignored
+            let unknown_op = add(&mut operation_pool, deferred(1).creator(unknown_keypair));
+            let far_op = add(
+                &mut operation_pool,
+                deferred(1).start_period(3001).expirery(3010),
+            );
+            let other_op = add(&mut operation_pool, deferred(1));
+            assert_eq!(
+                operation_pool.contains_operations(&creator_ops),
+                vec![true, true, false]
+            );
+            assert_eq!(
+                operation_pool.contains_operations(&[unknown_op, far_op, other_op]),
+                vec![false, false, true]
+            );
+
+            // when full, an operation paying more evicts one of the cheapest ones, not an operation paying less
+            let cheap_op = add(&mut operation_pool, deferred(0));
+            let expensive_op = add(&mut operation_pool, deferred(5));
+            assert_eq!(
+                operation_pool.contains_operations(&[cheap_op, expensive_op]),
+                vec![false, true]
+            );
+            let kept = operation_pool
+                .contains_operations(&[creator_ops[0], creator_ops[1], other_op])
+                .into_iter()
+                .filter(|c| *c)
+                .count();
+            assert_eq!(kept, 2);
+            assert_eq!(operation_pool.get_operation_count(), 0);
+        },
+    );
+}
+
 /// Test if adding irrelevant operations make simply skip the add.
 /// # Initialization
 #[test]
@@ -138,8 +295,12 @@ fn test_add_irrelevant_operation() {
 
 #[test]
 fn test_pool() {
+    let max_operations_per_block = 10;
     let pool_config = PoolConfig {
-        max_operations_per_block: 10,
+        chain_parameters: genesis_chain_parameters(ChainParameters {
+            max_operations_per_block,
+            ..GENESIS_CHAIN_PARAMETERS
+        }),
         ..Default::default()
     };
     let execution_controller = default_mock_execution_controller();
@@ -208,7 +369,7 @@ fn test_pool() {
     // // sort from bigger fee to smaller and truncate
     for lst in thread_tx_lists.iter_mut() {
         lst.reverse();
-        lst.truncate(pool_config.max_operations_per_block as usize);
+        lst.truncate(max_operations_per_block as usize);
     }
 
     // // checks ops are the expected ones for thread 0 and 1 and various periods
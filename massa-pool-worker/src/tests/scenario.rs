@@ -16,11 +16,12 @@ use std::collections::BTreeMap;
 use std::time::Duration;
 
 use crate::tests::tools::create_some_operations;
+use crate::tests::tools::genesis_chain_parameters;
 use crate::tests::tools::OpGenerator;
 use massa_execution_exports::MockExecutionController;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
-use massa_models::config::ENDORSEMENT_COUNT;
+use massa_models::config::{ChainParameters, ENDORSEMENT_COUNT, GENESIS_CHAIN_PARAMETERS};
 use massa_models::operation::OperationId;
 use massa_models::prehash::PreHashSet;
 use massa_models::slot::Slot;
@@ -183,7 +184,10 @@ fn test_get_operations_overflow() {
         .take(MAX_OP_LEN)
         .fold(0, |acc, op| acc + op.serialized_size() as u32);
     let config = PoolConfig {
-        max_block_size,
+        chain_parameters: genesis_chain_parameters(ChainParameters {
+            max_block_size,
+            ..GENESIS_CHAIN_PARAMETERS
+        }),
         ..Default::default()
     };
     let creator_thread = creator_address.get_thread(config.thread_count);
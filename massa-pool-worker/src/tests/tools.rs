@@ -1,18 +1,24 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use crate::start_pool_controller;
 use crossbeam_channel as _;
 use massa_execution_exports::MockExecutionController;
 use massa_hash::Hash;
-use massa_models::config::CHAINID;
+use massa_models::config::{
+    ChainParameters, ChainParametersSchedule, ChainParametersVersion, CHAINID,
+};
 use massa_models::{
     address::Address,
     amount::Amount,
     block_id::BlockId,
     endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement},
-    operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
+    operation::{
+        Operation, OperationSerializer, OperationType, SecureShareOperation,
+        EXTENDED_OPERATION_FORMAT_VERSION,
+    },
     prehash::PreHashMap,
     secure_share::SecureShareContent,
     slot::Slot,
@@ -21,6 +27,10 @@ use massa_pool_exports::{PoolBroadcasts, PoolChannels, PoolConfig, PoolControlle
 use massa_pos_exports::MockSelectorController as AutoMockSelectorController;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
+use massa_versioning::{
+    test_helpers::versioning_helpers::mip_store_with_active_components,
+    versioning::{MipComponent, MipStore},
+};
 use massa_wallet::test_exports::create_test_wallet;
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
@@ -32,6 +42,7 @@ pub(crate) struct OpGenerator {
     fee: Option<Amount>,
     amount: Option<Amount>,
     expirery: Option<u64>,
+    start_period: Option<u64>,
 }
 
 impl OpGenerator {
@@ -40,6 +51,11 @@ impl OpGenerator {
         self
     }
 
+    pub(crate) fn start_period(mut self, start_period: u64) -> Self {
+        self.start_period = Some(start_period);
+        self
+    }
+
     #[allow(dead_code)]
     pub(crate) fn amount(mut self, amount: Amount) -> Self {
         self.amount = Some(amount);
@@ -83,6 +99,8 @@ impl OpGenerator {
             fee,
             op,
             expire_period: expirery,
+            start_period: self.start_period,
+            fee_payer: None,
         };
         Operation::new_verifiable(content, OperationSerializer::new(), &creator, *CHAINID).unwrap()
     }
@@ -93,6 +111,24 @@ pub(crate) fn create_some_operations(n: usize, op_gen: &OpGenerator) -> Vec<Secu
     (0..n).map(|_| op_gen.generate()).collect()
 }
 
+/// MIP store in which the name, sponsored and deferred operations are accepted
+pub(crate) fn operation_formats_mip_store() -> MipStore {
+    mip_store_with_active_components(BTreeMap::from([(
+        MipComponent::Operation,
+        EXTENDED_OPERATION_FORMAT_VERSION,
+    )]))
+}
+
+/// Chain parameters schedule with a single version, in force from genesis
+pub(crate) fn genesis_chain_parameters(parameters: ChainParameters) -> ChainParametersSchedule {
+    let versions = Box::leak(Box::new([ChainParametersVersion {
+        version: 0,
+        activation_slot: Slot::new(0, 0),
+        parameters,
+    }]));
+    ChainParametersSchedule::new(versions).unwrap()
+}
+
 pub struct PoolTestBoilerPlate {
     pub pool_manager: Box<dyn PoolManager>,
     pub pool_controller: Box<dyn PoolController>,
@@ -125,6 +161,7 @@ impl PoolTestBoilerPlate {
                 selector: selector_story,
             },
             wallet,
+            operation_formats_mip_store(),
         );
 
         Self {
@@ -167,6 +204,7 @@ pub fn pool_test<F>(
             selector,
         },
         wallet,
+        operation_formats_mip_store(),
     );
     test(pool_controller, storage);
     pool_manager.stop();
@@ -14,9 +14,13 @@ pub struct OperationInfo {
     pub creator_address: Address,
     pub thread: u8,
     pub fee: Amount,
+    /// address paying the fee instead of the sender, for sponsored operations
+    pub fee_payer: Option<Address>,
     /// max amount that the op might spend from the sender's balance
     pub max_spending: Amount,
     pub validity_period_range: RangeInclusive<u64>,
+    /// version of the Operation versioning component required to include the op
+    pub format_version: u32,
 }
 
 impl OperationInfo {
@@ -34,9 +38,21 @@ impl OperationInfo {
             max_gas_usage: op.get_gas_usage(base_operation_gas_cost, sp_compilation_cost),
             creator_address: op.content_creator_address,
             fee: op.content.fee,
+            fee_payer: op
+                .content
+                .fee_payer
+                .as_ref()
+                .map(|_| op.get_fee_payer_address()),
             thread: op.content_creator_address.get_thread(thread_count),
             validity_period_range: op.get_validity_range(operation_validity_periods),
             max_spending: op.get_max_spending(roll_price),
+            format_version: op.content.get_format_version(),
         }
     }
+
+    /// Max amounts that the op might spend from the balance of each address paying for it
+    pub fn spendings(&self) -> impl Iterator<Item = (Address, Amount)> {
+        std::iter::once((self.creator_address, self.max_spending))
+            .chain(self.fee_payer.map(|fee_payer| (fee_payer, self.fee)))
+    }
 }
@@ -9,6 +9,7 @@ use crate::{controller_impl::PoolControllerImpl, endorsement_pool::EndorsementPo
 use massa_pool_exports::PoolConfig;
 use massa_pool_exports::{PoolChannels, PoolController, PoolManager};
 use massa_storage::Storage;
+use massa_versioning::versioning::MipStore;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::time::Instant;
@@ -187,6 +188,7 @@ pub fn start_pool_controller(
     storage: &Storage,
     channels: PoolChannels,
     wallet: Arc<RwLock<Wallet>>,
+    mip_store: MipStore,
 ) -> (Box<dyn PoolManager>, Box<dyn PoolController>) {
     let (operations_input_sender, operations_input_receiver) =
         sync_channel(config.operations_channel_size);
@@ -199,6 +201,7 @@ pub fn start_pool_controller(
         storage,
         channels.clone(),
         wallet.clone(),
+        mip_store,
     )));
     let endorsement_pool = Arc::new(RwLock::new(EndorsementPool::init(
         config,
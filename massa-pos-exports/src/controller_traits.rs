@@ -5,7 +5,7 @@
 
 use std::collections::BTreeMap;
 
-use crate::PosResult;
+use crate::{CycleDrawAudit, PosResult};
 use massa_hash::Hash;
 use massa_models::{address::Address, prehash::PreHashSet, slot::Slot};
 
@@ -62,6 +62,13 @@ pub trait SelectorController: Send + Sync {
         restrict_to_addresses: Option<&'a PreHashSet<Address>>,
     ) -> PosResult<BTreeMap<Slot, Selection>>;
 
+    /// Returns whether an address owned rolls at the look back of the cycle of a slot,
+    /// that is whether it can be drawn in that cycle
+    fn has_rolls_at(&self, slot: Slot, address: &Address) -> PosResult<bool>;
+
+    /// Get the audit record of the draws of a cycle, including the draws of all its slots
+    fn get_cycle_draw_audit(&self, cycle: u64) -> PosResult<CycleDrawAudit>;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn SelectorController>`.
     fn clone_box(&self) -> Box<dyn SelectorController>;
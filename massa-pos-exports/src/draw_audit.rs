@@ -0,0 +1,117 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Audit records of the selector draws, allowing third parties to re-verify them.
+//!
+//! The draws of a cycle are fully determined by the look back seed and roll distribution:
+//! anyone holding them can re-run the selection and compare the resulting digest
+//! with the one recorded by the node.
+
+use std::collections::BTreeMap;
+
+use crate::Selection;
+use massa_hash::Hash;
+use massa_models::{address::Address, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// Inputs of the draws of a cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrawAuditInputs {
+    /// RNG seed at look back (`cycle-2`)
+    pub lookback_seed: Hash,
+    /// hash of the roll distribution at look back (`cycle-3`), see `compute_rolls_hash`
+    pub lookback_rolls_hash: Hash,
+    /// number of addresses owning rolls at look back
+    pub roll_owner_count: u64,
+    /// total number of rolls at look back
+    pub total_rolls: u64,
+}
+
+impl DrawAuditInputs {
+    /// Summarizes the inputs of a draw
+    pub fn new(lookback_rolls: &BTreeMap<Address, u64>, lookback_seed: Hash) -> Self {
+        DrawAuditInputs {
+            lookback_seed,
+            lookback_rolls_hash: compute_rolls_hash(lookback_rolls),
+            roll_owner_count: lookback_rolls.len() as u64,
+            total_rolls: lookback_rolls
+                .values()
+                .fold(0u64, |acc, rolls| acc.saturating_add(*rolls)),
+        }
+    }
+}
+
+/// Hashes a roll distribution: for each address in increasing order,
+/// its prefixed bytes followed by its big-endian roll count
+pub fn compute_rolls_hash(rolls: &BTreeMap<Address, u64>) -> Hash {
+    let mut data = Vec::new();
+    for (address, count) in rolls {
+        data.extend(address.to_prefixed_bytes());
+        data.extend(count.to_be_bytes());
+    }
+    Hash::compute_from(&data)
+}
+
+/// Incremental digest of the draws of a cycle.
+///
+/// Starting from a zero hash, each slot (in increasing order) is chained as
+/// `hash(previous || period (big-endian) || thread || producer || endorsers...)`,
+/// addresses being written as prefixed bytes.
+pub struct DrawsHasher {
+    current: Hash,
+}
+
+impl DrawsHasher {
+    /// Creates an empty digest
+    pub fn new() -> Self {
+        DrawsHasher {
+            current: Hash::zero(),
+        }
+    }
+
+    /// Adds the selection of the next slot to the digest
+    pub fn update(&mut self, slot: &Slot, selection: &Selection) {
+        let mut data = Vec::new();
+        data.extend(slot.period.to_be_bytes());
+        data.push(slot.thread);
+        data.extend(selection.producer.to_prefixed_bytes());
+        for endorser in &selection.endorsements {
+            data.extend(endorser.to_prefixed_bytes());
+        }
+        self.current = Hash::compute_from_tuple(&[self.current.to_bytes(), &data]);
+    }
+
+    /// Returns the digest of the slots added so far
+    pub fn finish(self) -> Hash {
+        self.current
+    }
+}
+
+impl Default for DrawsHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Selection drawn for a slot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotDraw {
+    /// slot
+    pub slot: Slot,
+    /// block producer
+    pub producer: Address,
+    /// endorsement creators, by endorsement index
+    pub endorsements: Vec<Address>,
+}
+
+/// Audit record of the draws of a cycle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CycleDrawAudit {
+    /// cycle
+    pub cycle: u64,
+    /// inputs of the draws
+    pub inputs: DrawAuditInputs,
+    /// digest of the draws, see `DrawsHasher`
+    pub draws_hash: Hash,
+    /// draws of every slot of the cycle, in increasing slot order
+    pub draws: Vec<SlotDraw>,
+}
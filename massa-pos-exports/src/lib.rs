@@ -11,6 +11,7 @@ mod config;
 mod controller_traits;
 mod cycle_info;
 mod deferred_credits;
+mod draw_audit;
 mod error;
 mod pos_changes;
 mod pos_final_state;
@@ -22,6 +23,7 @@ pub use controller_traits::{MockSelectorController, MockSelectorControllerWrappe
 pub use controller_traits::{Selection, SelectorController, SelectorManager};
 pub use cycle_info::*;
 pub use deferred_credits::*;
+pub use draw_audit::*;
 pub use error::*;
 pub use pos_changes::*;
 pub use pos_final_state::*;
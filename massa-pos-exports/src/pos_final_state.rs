@@ -34,7 +34,8 @@ fn buf_to_array_ctr<F: Fn(&[u8; N]) -> V, V, const N: usize>(
 const COMPLETE_IDENT: u8 = 0u8;
 const RNG_SEED_IDENT: u8 = 1u8;
 const FINAL_STATE_HASH_SNAPSHOT_IDENT: u8 = 2u8;
-const ROLL_COUNT_IDENT: u8 = 3u8;
+/// Ident of the roll count entries of a cycle in the cycle history
+pub const ROLL_COUNT_IDENT: u8 = 3u8;
 const PROD_STATS_IDENT: u8 = 4u8;
 
 // Production stats idents
@@ -1627,6 +1628,8 @@ mod tests {
             max_versioning_elements_size: 100_000,
             thread_count: 2,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -1761,6 +1764,8 @@ mod tests {
             max_versioning_elements_size: 100,
             thread_count: 2,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -1955,6 +1960,8 @@ mod tests {
             max_versioning_elements_size: 100_000,
             thread_count: 2,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -2055,6 +2062,8 @@ mod tests {
             max_versioning_elements_size: 100_000,
             thread_count: 2,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -2230,6 +2239,8 @@ mod tests {
             max_versioning_elements_size: 100,
             thread_count: 2,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -2335,6 +2346,8 @@ mod tests {
             max_final_state_elements_size: 100,
             max_versioning_elements_size: 100,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -8,7 +8,9 @@ use std::collections::BTreeMap;
 use crate::{Command, DrawCachePtr};
 use massa_hash::Hash;
 use massa_models::{address::Address, prehash::PreHashSet, slot::Slot};
-use massa_pos_exports::{PosError, PosResult, Selection, SelectorController, SelectorManager};
+use massa_pos_exports::{
+    CycleDrawAudit, PosError, PosResult, Selection, SelectorController, SelectorManager, SlotDraw,
+};
 #[cfg(feature = "test-exports")]
 use std::collections::{HashMap, VecDeque};
 use std::sync::mpsc::SyncSender;
@@ -186,6 +188,42 @@ impl SelectorController for SelectorControllerImpl {
         Ok(res)
     }
 
+    /// Returns whether an address owned rolls at the look back of the cycle of a slot
+    fn has_rolls_at(&self, slot: Slot, address: &Address) -> PosResult<bool> {
+        let cycle = slot.get_cycle(self.periods_per_cycle);
+        let (_cache_cv, cache_lock) = &*self.cache;
+        let cache_guard = cache_lock.read();
+        let cache = cache_guard.as_ref().map_err(|err| err.clone())?;
+        cache
+            .get(cycle)
+            .map(|cycle_draws| cycle_draws.roll_owners.contains(address))
+            .ok_or(PosError::CycleUnavailable(cycle))
+    }
+
+    /// Get the audit record of the draws of a cycle
+    fn get_cycle_draw_audit(&self, cycle: u64) -> PosResult<CycleDrawAudit> {
+        let (_cache_cv, cache_lock) = &*self.cache;
+        let cache_guard = cache_lock.read();
+        let cache = cache_guard.as_ref().map_err(|err| err.clone())?;
+        let cycle_draws = cache.get(cycle).ok_or(PosError::CycleUnavailable(cycle))?;
+        let mut draws: Vec<SlotDraw> = cycle_draws
+            .draws
+            .iter()
+            .map(|(slot, selection)| SlotDraw {
+                slot: *slot,
+                producer: selection.producer,
+                endorsements: selection.endorsements.clone(),
+            })
+            .collect();
+        draws.sort_unstable_by_key(|draw| draw.slot);
+        Ok(CycleDrawAudit {
+            cycle,
+            inputs: cycle_draws.audit_inputs,
+            draws_hash: cycle_draws.draws_hash,
+            draws,
+        })
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn SelectorController>`,
     /// see `massa-pos-exports/controller_traits.rs`
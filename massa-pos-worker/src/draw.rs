@@ -1,7 +1,9 @@
 use crate::CycleDraws;
 use massa_hash::Hash;
 use massa_models::{address::Address, slot::Slot};
-use massa_pos_exports::{PosError, PosResult, Selection, SelectorConfig};
+use massa_pos_exports::{
+    DrawAuditInputs, DrawsHasher, PosError, PosResult, Selection, SelectorConfig,
+};
 use rand::{distributions::Distribution, SeedableRng};
 use rand_distr::WeightedAliasIndex;
 use rand_xoshiro::Xoshiro256PlusPlus;
@@ -31,9 +33,13 @@ pub(crate) fn perform_draws(
     lookback_rolls: BTreeMap<Address, u64>,
     lookback_seed: Hash,
 ) -> PosResult<CycleDraws> {
+    // record the inputs for audit purposes
+    let audit_inputs = DrawAuditInputs::new(&lookback_rolls, lookback_seed);
+
     // get seeded RNG
     let mut rng = Xoshiro256PlusPlus::from_seed(*lookback_seed.to_bytes());
 
+    let roll_owners = lookback_rolls.keys().copied().collect();
     let (addresses, roll_counts): (Vec<_>, Vec<_>) = lookback_rolls.into_iter().unzip();
 
     // prepare distribution
@@ -57,7 +63,11 @@ pub(crate) fn perform_draws(
         draws: HashMap::with_capacity(
             (cfg.periods_per_cycle as usize) * (cfg.thread_count as usize),
         ),
+        roll_owners,
+        audit_inputs,
+        draws_hash: Hash::zero(),
     };
+    let mut draws_hasher = DrawsHasher::new();
 
     let mut five_first_slots: Vec<(Slot, Selection)> = Vec::new();
     let mut count = 0;
@@ -84,6 +94,7 @@ pub(crate) fn perform_draws(
             count += 1;
         }
         // add to draws
+        draws_hasher.update(&cur_slot, &selection);
         cycle_draws.draws.insert(cur_slot, selection);
 
         if cur_slot == last_slot {
@@ -94,6 +105,8 @@ pub(crate) fn perform_draws(
         })?;
     }
 
+    cycle_draws.draws_hash = draws_hasher.finish();
+
     debug!(
         "Draws for cycle {} complete. Look_back seed was {:#?}. Five first selections is : {:#?}",
         cycle,
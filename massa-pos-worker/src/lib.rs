@@ -8,8 +8,8 @@ mod draw;
 mod worker;
 
 use massa_hash::Hash;
-use massa_models::{address::Address, slot::Slot};
-use massa_pos_exports::{PosResult, Selection};
+use massa_models::{address::Address, prehash::PreHashSet, slot::Slot};
+use massa_pos_exports::{DrawAuditInputs, PosResult, Selection};
 
 use parking_lot::{Condvar, Mutex, RwLock, RwLockReadGuard};
 use std::{
@@ -83,6 +83,12 @@ pub(crate) struct CycleDraws {
     pub cycle: u64,
     /// cache of draws
     pub draws: HashMap<Slot, Selection>,
+    /// addresses owning rolls at look back, the only ones that can be drawn in the cycle
+    pub roll_owners: PreHashSet<Address>,
+    /// inputs of the draws
+    pub audit_inputs: DrawAuditInputs,
+    /// digest of the draws
+    pub draws_hash: Hash,
 }
 
 /// Structure of the shared pointer to the computed draws, or error if the draw system failed.
@@ -3,8 +3,8 @@ use massa_models::address::Address;
 use massa_models::config::PERIODS_PER_CYCLE;
 use massa_models::config::THREAD_COUNT;
 use massa_models::slot::Slot;
-use massa_pos_exports::PosError;
 use massa_pos_exports::SelectorConfig;
+use massa_pos_exports::{compute_rolls_hash, DrawsHasher, PosError};
 use rand::thread_rng;
 use rand::RngCore;
 use std::{collections::BTreeMap, str::FromStr};
@@ -13,8 +13,17 @@ use crate::start_selector_worker;
 
 #[test]
 fn test_standalone_selection() {
+    for thread_count in [2, 3, 4, 8, THREAD_COUNT] {
+        standalone_selection(thread_count);
+    }
+}
+
+fn standalone_selection(thread_count: u8) {
     // initialize the selector configuration and the test inputs
-    let cfg = SelectorConfig::default();
+    let cfg = SelectorConfig {
+        thread_count,
+        ..Default::default()
+    };
     let mut lookback_rolls: BTreeMap<Address, u64> = std::collections::BTreeMap::new();
     lookback_rolls.insert(
         Address::from_str("AU12Cyu2f7C7isA3ADAhoNuq9ZUFPKP24jmiGj3sh9D1pHoAWKDYY").unwrap(),
@@ -55,8 +64,8 @@ fn test_standalone_selection() {
         )
         .unwrap();
 
-    // 2 slots as inclusive range so THREAD_COUNT * 2 + 1 = 65 for 32 threads
-    assert_eq!(two_slot_selection.len(), (THREAD_COUNT as usize) * 2 + 1);
+    // 2 slots as inclusive range so thread_count * 2 + 1 = 65 for 32 threads
+    assert_eq!(two_slot_selection.len(), (thread_count as usize) * 2 + 1);
 
     // period (PERIODS_PER_CYCLE - 1) is the last of cycle 0
     // draws for this slot have been computed
@@ -110,3 +119,80 @@ fn test_invalid_roll_distribution() {
     // stop worker
     manager.stop();
 }
+
+#[test]
+fn test_cycle_draw_audit() {
+    // initialize the selector configuration and the test inputs
+    let cfg = SelectorConfig::default();
+    let mut lookback_rolls: BTreeMap<Address, u64> = std::collections::BTreeMap::new();
+    lookback_rolls.insert(
+        Address::from_str("AU12Cyu2f7C7isA3ADAhoNuq9ZUFPKP24jmiGj3sh9D1pHoAWKDYY").unwrap(),
+        3,
+    );
+    lookback_rolls.insert(
+        Address::from_str("AU12BTfZ7k1z6PsLEUZeHYNirz6WJ3NdrWto9H4TkVpkV9xE2TJg2").unwrap(),
+        2,
+    );
+    let lookback_seed = Hash::compute_from(b"audit seed");
+
+    // start the selector thread, get the controller and manager
+    let (mut manager, controller) = start_selector_worker(cfg).unwrap();
+    controller
+        .feed_cycle(0, lookback_rolls.clone(), lookback_seed)
+        .unwrap();
+    controller.wait_for_draws(0).unwrap();
+
+    // the recorded inputs match the fed ones
+    let audit = controller.get_cycle_draw_audit(0).unwrap();
+    assert_eq!(audit.cycle, 0);
+    assert_eq!(audit.inputs.lookback_seed, lookback_seed);
+    assert_eq!(
+        audit.inputs.lookback_rolls_hash,
+        compute_rolls_hash(&lookback_rolls)
+    );
+    assert_eq!(audit.inputs.roll_owner_count, 2);
+    assert_eq!(audit.inputs.total_rolls, 5);
+
+    // the published draws cover the cycle, match the selections and their digest
+    assert_eq!(
+        audit.draws.len(),
+        (PERIODS_PER_CYCLE as usize) * (THREAD_COUNT as usize)
+    );
+    let mut hasher = DrawsHasher::new();
+    for draw in &audit.draws {
+        let selection = controller.get_selection(draw.slot).unwrap();
+        assert_eq!(selection.producer, draw.producer);
+        assert_eq!(selection.endorsements, draw.endorsements);
+        hasher.update(&draw.slot, &selection);
+    }
+    assert_eq!(hasher.finish(), audit.draws_hash);
+
+    // only the look back roll owners can be drawn in the cycle
+    for address in lookback_rolls.keys() {
+        assert!(controller.has_rolls_at(Slot::new(1, 0), address).unwrap());
+    }
+    let non_staker =
+        Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+    assert!(!controller
+        .has_rolls_at(Slot::new(1, 0), &non_staker)
+        .unwrap());
+    assert!(matches!(
+        controller.has_rolls_at(Slot::new(PERIODS_PER_CYCLE, 0), &non_staker),
+        Err(PosError::CycleUnavailable(1))
+    ));
+
+    // the same inputs lead to the same draws
+    controller
+        .feed_cycle(1, lookback_rolls, lookback_seed)
+        .unwrap();
+    controller.wait_for_draws(1).unwrap();
+    let audit_1 = controller.get_cycle_draw_audit(1).unwrap();
+    assert_eq!(audit_1.inputs, audit.inputs);
+    assert!(matches!(
+        controller.get_cycle_draw_audit(2),
+        Err(PosError::CycleUnavailable(2))
+    ));
+
+    // stop worker
+    manager.stop();
+}
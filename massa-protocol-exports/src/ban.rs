@@ -0,0 +1,194 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::{
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{PeerId, ProtocolError};
+
+/// IP subnet, written `address/prefix_length`.
+/// A single address can be written without prefix length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpSubnet {
+    /// first address of the subnet
+    address: IpAddr,
+    /// number of leading bits shared by the addresses of the subnet
+    prefix_length: u8,
+}
+
+impl IpSubnet {
+    /// Creates the subnet of the given prefix length containing `address`
+    pub fn new(address: IpAddr, prefix_length: u8) -> Result<Self, ProtocolError> {
+        let max_length = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_length > max_length {
+            return Err(ProtocolError::GeneralProtocolError(format!(
+                "invalid prefix length {} for address {}",
+                prefix_length, address
+            )));
+        }
+        let address = match address {
+            IpAddr::V4(ip) => IpAddr::V4(Ipv4Addr::from(
+                u32::from(ip) & u32::MAX.checked_shl(32 - prefix_length as u32).unwrap_or(0),
+            )),
+            IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from(
+                u128::from(ip)
+                    & u128::MAX
+                        .checked_shl(128 - prefix_length as u32)
+                        .unwrap_or(0),
+            )),
+        };
+        Ok(IpSubnet {
+            address,
+            prefix_length,
+        })
+    }
+
+    /// Subnet containing only `address`
+    pub fn from_address(address: IpAddr) -> Self {
+        let prefix_length = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        IpSubnet {
+            address,
+            prefix_length,
+        }
+    }
+
+    /// Returns true if `address` belongs to the subnet.
+    /// IPv4-mapped IPv6 addresses should be converted to IPv4 beforehand.
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        match (self.address, address) {
+            (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => {
+                IpSubnet::new(*address, self.prefix_length)
+                    .map(|subnet| subnet.address == self.address)
+                    .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for IpSubnet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_length)
+    }
+}
+
+impl FromStr for IpSubnet {
+    type Err = ProtocolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_error =
+            || ProtocolError::GeneralProtocolError(format!("invalid IP subnet: {}", s));
+        match s.split_once('/') {
+            Some((address, prefix_length)) => IpSubnet::new(
+                address.parse().map_err(|_| parse_error())?,
+                prefix_length.parse().map_err(|_| parse_error())?,
+            ),
+            None => Ok(IpSubnet::from_address(
+                s.parse().map_err(|_| parse_error())?,
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for IpSubnet {
+    type Error = ProtocolError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<IpSubnet> for String {
+    fn from(value: IpSubnet) -> Self {
+        value.to_string()
+    }
+}
+
+/// What a ban applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BanTarget {
+    /// a peer, whatever its address
+    Peer(PeerId),
+    /// all the addresses of a subnet, whatever the peer
+    Subnet(IpSubnet),
+}
+
+impl Display for BanTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BanTarget::Peer(peer_id) => write!(f, "peer {}", peer_id),
+            BanTarget::Subnet(subnet) => write!(f, "subnet {}", subnet),
+        }
+    }
+}
+
+/// Ban kept across restarts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BanEntry {
+    /// banned peer or subnet
+    pub target: BanTarget,
+    /// time at which the ban is lifted, none for a permanent ban
+    pub expires_at: Option<MassaTime>,
+}
+
+impl BanEntry {
+    /// Returns true if the ban is lifted at `now`
+    pub fn is_expired(&self, now: MassaTime) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at <= now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_subnet_contains() {
+        let subnet: IpSubnet = "192.168.1.17/24".parse().unwrap();
+        assert_eq!(subnet.to_string(), "192.168.1.0/24");
+        assert!(subnet.contains(&"192.168.1.200".parse().unwrap()));
+        assert!(!subnet.contains(&"192.168.2.1".parse().unwrap()));
+        assert!(!subnet.contains(&"::1".parse().unwrap()));
+
+        let address: IpSubnet = "2001:db8::1".parse().unwrap();
+        assert_eq!(address.to_string(), "2001:db8::1/128");
+        assert!(address.contains(&"2001:db8::1".parse().unwrap()));
+        assert!(!address.contains(&"2001:db8::2".parse().unwrap()));
+
+        let everything: IpSubnet = "0.0.0.0/0".parse().unwrap();
+        assert!(everything.contains(&"8.8.8.8".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<IpSubnet>().is_err());
+        assert!("not an ip".parse::<IpSubnet>().is_err());
+    }
+
+    #[test]
+    fn test_ban_entry_serde() {
+        let entry = BanEntry {
+            target: BanTarget::Subnet("10.0.0.0/8".parse().unwrap()),
+            expires_at: Some(MassaTime::from_millis(1000)),
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(
+            json,
+            r#"{"target":{"subnet":"10.0.0.0/8"},"expires_at":1000}"#
+        );
+        assert_eq!(serde_json::from_str::<BanEntry>(&json).unwrap(), entry);
+        assert!(!entry.is_expired(MassaTime::from_millis(999)));
+        assert!(entry.is_expired(MassaTime::from_millis(1000)));
+    }
+}
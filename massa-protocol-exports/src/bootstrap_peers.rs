@@ -21,6 +21,9 @@ use std::ops::Bound::Included;
 pub struct PeerData {
     pub listeners: HashMap<SocketAddr, TransportType>,
     pub category: String,
+    /// `host:port` TCP listeners, resolved at startup
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hostnames: Vec<String>,
 }
 
 /// Peers that are transmitted during bootstrap
@@ -1,16 +1,22 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 
 use crate::error::ProtocolError;
 use crate::BootstrapPeers;
 
-use crate::PeerId;
+use crate::{
+    BanEntry, BanTarget, NetSplitAlert, NetworkTrafficStats, NodeKnowledge, PeerAgent,
+    PeerContribution, PeerId, PeerLatency, PeerRecord, PeerRecordEdit,
+};
+use massa_models::heartbeat::SecureShareHeartbeat;
+use massa_models::node_link::NodeAddressLinkProof;
 use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::stats::NetworkStats;
 use massa_models::{block_header::SecuredHeader, block_id::BlockId};
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use peernet::peer::PeerConnectionType;
 
 #[cfg(feature = "test-exports")]
@@ -46,6 +52,19 @@ pub trait ProtocolController: Send + Sync {
         remove: PreHashSet<BlockId>,
     ) -> Result<(), ProtocolError>;
 
+    /// Notify protocol of how far behind the network the node is.
+    /// Received operations and endorsements are not processed while the lag is too high.
+    ///
+    /// # Arguments
+    /// * `lag`: time elapsed between the latest block of the blockclique and the current slot
+    fn notify_sync_lag(&self, lag: MassaTime) -> Result<(), ProtocolError>;
+
+    /// Notify protocol of the blocks that became final, to credit the peers that first sent them and their endorsements
+    ///
+    /// # Arguments:
+    /// * `block_ids`: blocks that became final
+    fn notify_final_blocks(&self, block_ids: Vec<BlockId>) -> Result<(), ProtocolError>;
+
     /// Propagate a batch of operation (from pool).
     /// note: Full `OperationId` is replaced by a `OperationPrefixId` later by the worker.
     ///
@@ -81,6 +100,83 @@ pub trait ProtocolController: Send + Sync {
     /// Unban a list of Peer Id
     fn unban_peers(&self, peer_ids: Vec<PeerId>) -> Result<(), ProtocolError>;
 
+    /// Add bans kept across restarts, replacing the existing bans of the same targets
+    fn add_bans(&self, bans: Vec<BanEntry>) -> Result<(), ProtocolError>;
+
+    /// Remove bans kept across restarts, also lifting the current bans of the given peers
+    fn remove_bans(&self, targets: Vec<BanTarget>) -> Result<(), ProtocolError>;
+
+    /// Get the bans kept across restarts
+    fn get_bans(&self) -> Result<Vec<BanEntry>, ProtocolError>;
+
+    /// Exempt addresses from the subnet bans, kept across restarts
+    fn add_to_peers_whitelist(&self, ips: Vec<IpAddr>) -> Result<(), ProtocolError>;
+
+    /// Remove addresses from the peers whitelist
+    fn remove_from_peers_whitelist(&self, ips: Vec<IpAddr>) -> Result<(), ProtocolError>;
+
+    /// Get the addresses exempted from the subnet bans
+    fn get_peers_whitelist(&self) -> Result<Vec<IpAddr>, ProtocolError>;
+
+    /// Read the ban list and the peers whitelist again from disk, closing the connections of the peers now banned
+    fn reload_ip_lists(&self) -> Result<(), ProtocolError>;
+
+    /// Get the records of the given peers kept across restarts, or of all the peers if none is given, the best ranked first
+    fn get_peer_records(&self, peer_ids: Vec<PeerId>) -> Result<Vec<PeerRecord>, ProtocolError>;
+
+    /// Edit the records of peers kept across restarts, recording the unknown peers
+    fn edit_peer_records(&self, edits: Vec<PeerRecordEdit>) -> Result<(), ProtocolError>;
+
+    /// Forget the records of peers kept across restarts
+    fn remove_peer_records(&self, peer_ids: Vec<PeerId>) -> Result<(), ProtocolError>;
+
+    /// Broadcast signed liveness heartbeats of our staking addresses
+    ///
+    /// # Arguments:
+    /// * `heartbeats`: heartbeats to propagate
+    fn propagate_heartbeats(
+        &self,
+        heartbeats: Vec<SecureShareHeartbeat>,
+    ) -> Result<(), ProtocolError>;
+
+    /// Get the latest fresh heartbeat known for each staking address
+    fn get_staker_heartbeats(&self) -> Result<Vec<SecureShareHeartbeat>, ProtocolError>;
+
+    /// Broadcast the links of our staking addresses to our node id
+    ///
+    /// # Arguments:
+    /// * `links`: links to propagate, signed by the staking addresses and the node
+    fn propagate_node_address_links(
+        &self,
+        links: Vec<NodeAddressLinkProof>,
+    ) -> Result<(), ProtocolError>;
+
+    /// Get the latest fresh node link known for each staking address
+    fn get_node_address_links(&self) -> Result<Vec<NodeAddressLinkProof>, ProtocolError>;
+
+    /// Get the software announced by each connected peer during the handshake
+    fn get_peer_agents(&self) -> Result<HashMap<PeerId, PeerAgent>, ProtocolError>;
+
+    /// Get the round trip latency to each connected peer that answered the keepalive pings
+    fn get_peer_latencies(&self) -> Result<HashMap<PeerId, PeerLatency>, ProtocolError>;
+
+    /// Get the messages and bytes exchanged since the node started, per message type and per connected peer
+    fn get_network_stats(&self) -> Result<NetworkTrafficStats, ProtocolError>;
+
+    /// Get the number of blocks whose knowledge is tracked for each connected peer.
+    /// Each count is bounded by `max_node_known_blocks_size`, the least recently noted blocks being evicted first.
+    fn get_known_blocks_sizes(&self) -> Result<HashMap<PeerId, usize>, ProtocolError>;
+
+    /// Get the blocks, operations and endorsements each connected peer is believed to know,
+    /// and the blocks being asked to it
+    fn get_node_knowledge(&self) -> Result<HashMap<PeerId, NodeKnowledge>, ProtocolError>;
+
+    /// Get the rolling contribution of the peers to the final blocks and endorsements
+    fn get_peer_contributions(&self) -> Result<HashMap<PeerId, PeerContribution>, ProtocolError>;
+
+    /// Get the alert raised when the blocks finalized recently were first sent by a tiny subset of peers, if any
+    fn get_net_split_alert(&self) -> Result<Option<NetSplitAlert>, ProtocolError>;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ProtocolController>`.
     fn clone_box(&self) -> Box<dyn ProtocolController>;
@@ -47,6 +47,12 @@ pub enum ProtocolError {
     InvalidOperationError(String),
     /// Listener error: {0}
     ListenerError(String),
+    /// DNS resolution error: {0}
+    DnsResolutionError(String),
+    /// Dial timeout: {0}
+    DialTimeout(String),
+    /// NAT traversal error: {0}
+    NatTraversalError(String),
     /// Incompatible network version: local current is {local} received is {received}
     IncompatibleNetworkVersion {
         /// local current version
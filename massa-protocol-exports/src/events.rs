@@ -0,0 +1,17 @@
+use crate::{NetSplitAlert, PeerId};
+
+/// Events that are emitted by protocol, for the node operator to audit them.
+#[derive(Debug, Clone)]
+pub enum ProtocolEvent {
+    /// a peer was banned because it sent too many invalid items within the strike window
+    PeerBannedForInvalidItems {
+        /// banned peer
+        peer_id: PeerId,
+        /// number of invalid items it sent within the strike window
+        invalid_items: usize,
+        /// type of the last invalid item it sent (block, header, operation, endorsement)
+        item_type: String,
+    },
+    /// blocks are being finalized while they were first sent by a tiny subset of peers
+    NetSplitSuspected(NetSplitAlert),
+}
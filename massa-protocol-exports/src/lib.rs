@@ -1,18 +1,38 @@
+mod ban;
 mod bootstrap_peers;
 mod controller_trait;
 mod error;
+mod events;
+mod net_split;
+mod node_knowledge;
+mod peer_agent;
+mod peer_contribution;
 mod peer_id;
+mod peer_latency;
+mod peer_record;
 mod settings;
+mod socks5;
+mod traffic_stats;
 
+pub use ban::{BanEntry, BanTarget, IpSubnet};
 pub use bootstrap_peers::{
     BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, PeerData,
 };
 pub use controller_trait::{ProtocolController, ProtocolManager};
 pub use error::ProtocolError;
+pub use events::ProtocolEvent;
+pub use net_split::NetSplitAlert;
+pub use node_knowledge::NodeKnowledge;
+pub use peer_agent::{feature_adoption_warnings, PeerAgent, PeerFeatures};
+pub use peer_contribution::PeerContribution;
 pub use peer_id::{PeerId, PeerIdDeserializer, PeerIdSerializer};
+pub use peer_latency::PeerLatency;
+pub use peer_record::{PeerRecord, PeerRecordEdit};
 pub use peernet::peer::PeerConnectionType;
 pub use peernet::transports::TransportType;
-pub use settings::{PeerCategoryInfo, ProtocolConfig};
+pub use settings::{PeerCategoryInfo, PeerRateLimit, ProtocolConfig};
+pub use socks5::Socks5Proxy;
+pub use traffic_stats::{NetworkTrafficStats, TrafficCounters};
 
 #[cfg(any(test, feature = "test-exports"))]
 pub mod test_exports;
@@ -0,0 +1,33 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Signs that the node may be eclipsed or on the minority side of a network split:
+/// blocks keep being finalized while they were first sent by a tiny subset of peers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetSplitAlert {
+    /// number of connected peers
+    pub connected_peers: usize,
+    /// number of distinct subnets of the connected peers
+    pub connected_subnets: usize,
+    /// number of blocks finalized within the detection window
+    pub final_blocks: usize,
+    /// number of distinct peers that first sent us these blocks
+    pub final_block_origins: usize,
+    /// number of distinct subnets of the peers that first sent us these blocks
+    pub final_block_origin_subnets: usize,
+}
+
+impl Display for NetSplitAlert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "possible eclipse or network split: the {} blocks finalized recently were first sent by {} peers from {} subnets, with {} peers connected from {} subnets",
+            self.final_blocks,
+            self.final_block_origins,
+            self.final_block_origin_subnets,
+            self.connected_peers,
+            self.connected_subnets
+        )
+    }
+}
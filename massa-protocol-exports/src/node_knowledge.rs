@@ -0,0 +1,18 @@
+use massa_models::{block_id::BlockId, endorsement::EndorsementId};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// What we believe a connected peer knows, and what we are asking it, to debug the propagation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeKnowledge {
+    /// blocks the peer is believed to know
+    pub known_blocks: Vec<BlockId>,
+    /// blocks the peer is believed not to know
+    pub unknown_blocks: Vec<BlockId>,
+    /// number of operations the peer is believed to know: they are tracked in a bloom filter and cannot be listed
+    pub known_operations_count: usize,
+    /// endorsements the peer is believed to know
+    pub known_endorsements: Vec<EndorsementId>,
+    /// blocks whose data is being asked to the peer, with the time elapsed since the ask
+    pub asked_blocks: Vec<(BlockId, MassaTime)>,
+}
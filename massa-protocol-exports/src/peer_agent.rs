@@ -0,0 +1,225 @@
+use crate::{ProtocolConfig, TransportType};
+use massa_models::version::Version;
+use serde::{Deserialize, Serialize};
+
+/// Software announced by a peer during the handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerAgent {
+    /// node version of the peer
+    pub version: Version,
+    /// user agent of the peer, not announced by older nodes
+    pub user_agent: Option<String>,
+    /// optional wire features supported by the peer, none for older nodes
+    #[serde(default)]
+    pub features: PeerFeatures,
+    /// version of the feature bit set announced by the peer,
+    /// 0 for the nodes announcing their features without version
+    #[serde(default)]
+    pub features_version: u32,
+}
+
+/// Optional wire features supported by a node, announced as a bit set during the handshake
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerFeatures(pub u64);
+
+impl PeerFeatures {
+    /// Answers the requests for the operations of its pool
+    pub const PENDING_OPERATIONS: PeerFeatures = PeerFeatures(1);
+    /// Serves the operations of a block in compressed form
+    pub const COMPRESSED_OPERATIONS: PeerFeatures = PeerFeatures(1 << 1);
+    /// Answers the requests for its clock used to estimate the network time
+    pub const CLOCK_SAMPLING: PeerFeatures = PeerFeatures(1 << 2);
+    /// Accepts full operations sent in compressed form
+    pub const COMPRESSED_OPERATION_MESSAGES: PeerFeatures = PeerFeatures(1 << 3);
+    /// Answers the sketches of the pending operations of its peers
+    pub const PENDING_OPERATIONS_SKETCH: PeerFeatures = PeerFeatures(1 << 4);
+    /// Answers the requests to dial back the routable address of its peers (since version 1)
+    pub const REACHABILITY_TESTS: PeerFeatures = PeerFeatures(1 << 5);
+    /// Accepts the gossip of the liveness heartbeats of staking addresses (since version 1)
+    pub const HEARTBEATS: PeerFeatures = PeerFeatures(1 << 6);
+    /// Accepts the gossip of the links between node ids and staking addresses (since version 1)
+    pub const NODE_ADDRESS_LINKS: PeerFeatures = PeerFeatures(1 << 7);
+    /// Answers the lookups for the peers closest to a node id (since version 2)
+    pub const PEER_DISCOVERY: PeerFeatures = PeerFeatures(1 << 8);
+    /// Answers the keepalive pings measuring the latency of the connection (since version 3)
+    pub const PING: PeerFeatures = PeerFeatures(1 << 9);
+    /// Decodes the messages wrapped in a versioned envelope, skipping the newer message types it does not know (since version 4)
+    pub const MESSAGE_ENVELOPE: PeerFeatures = PeerFeatures(1 << 10);
+    /// Accepts connections over QUIC on its QUIC listeners (since version 5)
+    pub const QUIC: PeerFeatures = PeerFeatures(1 << 11);
+    /// Numbers its block data requests and echoes the number of the request in its block data responses (since version 6)
+    pub const SEQUENCED_BLOCK_DATA: PeerFeatures = PeerFeatures(1 << 12);
+    /// Features defined before the feature bit set was versioned
+    const VERSION_0: PeerFeatures = PeerFeatures(
+        Self::PENDING_OPERATIONS.0
+            | Self::COMPRESSED_OPERATIONS.0
+            | Self::CLOCK_SAMPLING.0
+            | Self::COMPRESSED_OPERATION_MESSAGES.0
+            | Self::PENDING_OPERATIONS_SKETCH.0,
+    );
+    /// Features defined at version 1 of the feature bit set
+    const VERSION_1: PeerFeatures = PeerFeatures(
+        Self::VERSION_0.0
+            | Self::REACHABILITY_TESTS.0
+            | Self::HEARTBEATS.0
+            | Self::NODE_ADDRESS_LINKS.0,
+    );
+    /// Features defined at version 2 of the feature bit set
+    const VERSION_2: PeerFeatures = PeerFeatures(Self::VERSION_1.0 | Self::PEER_DISCOVERY.0);
+    /// Features defined at version 3 of the feature bit set
+    const VERSION_3: PeerFeatures = PeerFeatures(Self::VERSION_2.0 | Self::PING.0);
+    /// Features defined at version 4 of the feature bit set
+    const VERSION_4: PeerFeatures = PeerFeatures(Self::VERSION_3.0 | Self::MESSAGE_ENVELOPE.0);
+    /// Features defined at version 5 of the feature bit set
+    const VERSION_5: PeerFeatures = PeerFeatures(Self::VERSION_4.0 | Self::QUIC.0);
+    /// Features supported by this node version
+    pub const SUPPORTED: PeerFeatures =
+        PeerFeatures(Self::VERSION_5.0 | Self::SEQUENCED_BLOCK_DATA.0);
+    /// Version of the feature bit set announced by this node version.
+    /// It is increased each time new features are defined.
+    pub const VERSION: u32 = 6;
+
+    /// Features announced by a peer using the feature bit set of `version`.
+    /// The bits that were not defined yet at that version are ignored,
+    /// while a newer version keeps its bits so that the unknown features can be reported.
+    pub fn from_announcement(bits: u64, version: u32) -> PeerFeatures {
+        match version {
+            0 => PeerFeatures(bits & Self::VERSION_0.0),
+            1 => PeerFeatures(bits & Self::VERSION_1.0),
+            2 => PeerFeatures(bits & Self::VERSION_2.0),
+            3 => PeerFeatures(bits & Self::VERSION_3.0),
+            4 => PeerFeatures(bits & Self::VERSION_4.0),
+            5 => PeerFeatures(bits & Self::VERSION_5.0),
+            v if v <= Self::VERSION => PeerFeatures(bits & Self::SUPPORTED.0),
+            _ => PeerFeatures(bits),
+        }
+    }
+
+    /// Returns true if all the features of `other` are supported
+    pub fn contains(&self, other: PeerFeatures) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if some features are unknown to this node version
+    pub fn has_unknown(&self) -> bool {
+        self.0 & !Self::SUPPORTED.0 != 0
+    }
+}
+
+/// Warnings about the optional wire features supported by a majority of the connected peers
+/// but not used by this node, either because they are disabled in its configuration
+/// or because they are unknown to its version.
+pub fn feature_adoption_warnings<'a>(
+    agents: impl IntoIterator<Item = &'a PeerAgent>,
+    config: &ProtocolConfig,
+) -> Vec<String> {
+    let features: Vec<PeerFeatures> = agents.into_iter().map(|agent| agent.features).collect();
+    let share = |supported: usize| supported * 100 / features.len();
+    let mut warnings = Vec::new();
+    if features.is_empty() {
+        return warnings;
+    }
+    let disabled = [
+        (
+            PeerFeatures::PENDING_OPERATIONS,
+            config.pool_warmup_enabled,
+            "pending operations requests",
+            "enable `pool_warmup_enabled`",
+        ),
+        (
+            PeerFeatures::COMPRESSED_OPERATIONS,
+            config.compress_block_operations,
+            "compressed block operations",
+            "enable `compress_block_operations`",
+        ),
+        (
+            PeerFeatures::COMPRESSED_OPERATION_MESSAGES,
+            config.compress_operation_messages,
+            "compressed operation messages",
+            "enable `compress_operation_messages`",
+        ),
+        (
+            PeerFeatures::PENDING_OPERATIONS_SKETCH,
+            config.pending_operations_sync_enabled,
+            "pending operations sketches",
+            "enable `pending_operations_sync_enabled`",
+        ),
+        (
+            PeerFeatures::PEER_DISCOVERY,
+            config.peer_discovery_enabled,
+            "peer discovery lookups",
+            "enable `peer_discovery_enabled`",
+        ),
+        (
+            PeerFeatures::PING,
+            config.ping_interval.as_millis() > 0,
+            "keepalive pings",
+            "set a non-zero `ping_interval`",
+        ),
+        (
+            PeerFeatures::QUIC,
+            config
+                .listeners
+                .values()
+                .any(|transport| *transport == TransportType::Quic),
+            "QUIC connections",
+            "build the node with the `quic` feature and set `bind_quic`",
+        ),
+    ];
+    for (feature, enabled, name, advice) in disabled {
+        let supported = features.iter().filter(|f| f.contains(feature)).count();
+        if !enabled && supported * 2 > features.len() {
+            warnings.push(format!(
+                "{}% of peers support {}; {}",
+                share(supported),
+                name,
+                advice
+            ));
+        }
+    }
+    let unknown = features.iter().filter(|f| f.has_unknown()).count();
+    if unknown * 2 > features.len() {
+        warnings.push(format!(
+            "{}% of peers support wire features unknown to this node version; upgrade",
+            share(unknown)
+        ));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PeerFeatures;
+
+    #[test]
+    fn test_features_announced_with_version() {
+        let bits = PeerFeatures::SUPPORTED.0 | (1 << 40);
+        // bits defined after the announced version are ignored
+        let features = PeerFeatures::from_announcement(bits, 0);
+        assert!(features.contains(PeerFeatures::PENDING_OPERATIONS_SKETCH));
+        assert!(!features.contains(PeerFeatures::HEARTBEATS));
+        assert!(!features.has_unknown());
+        let features = PeerFeatures::from_announcement(bits, 1);
+        assert!(features.contains(PeerFeatures::HEARTBEATS));
+        assert!(!features.contains(PeerFeatures::PEER_DISCOVERY));
+        let features = PeerFeatures::from_announcement(bits, 2);
+        assert!(features.contains(PeerFeatures::PEER_DISCOVERY));
+        assert!(!features.contains(PeerFeatures::PING));
+        let features = PeerFeatures::from_announcement(bits, 3);
+        assert!(features.contains(PeerFeatures::PING));
+        assert!(!features.contains(PeerFeatures::MESSAGE_ENVELOPE));
+        let features = PeerFeatures::from_announcement(bits, 4);
+        assert!(features.contains(PeerFeatures::MESSAGE_ENVELOPE));
+        assert!(!features.contains(PeerFeatures::QUIC));
+        let features = PeerFeatures::from_announcement(bits, 5);
+        assert!(features.contains(PeerFeatures::QUIC));
+        assert!(!features.contains(PeerFeatures::SEQUENCED_BLOCK_DATA));
+        let features = PeerFeatures::from_announcement(bits, PeerFeatures::VERSION);
+        assert!(features.contains(PeerFeatures::SUPPORTED));
+        assert!(!features.has_unknown());
+        // a newer version may define features unknown to this node
+        let features = PeerFeatures::from_announcement(bits, PeerFeatures::VERSION + 1);
+        assert!(features.contains(PeerFeatures::SUPPORTED));
+        assert!(features.has_unknown());
+    }
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// Rolling contribution of a peer to the blocks and endorsements that became final,
+/// counting the ones it was the first to send us
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerContribution {
+    /// final blocks first sent by the peer, decayed over time
+    pub blocks: f64,
+    /// final endorsements first sent by the peer, decayed over time
+    pub endorsements: f64,
+    /// combined score: the endorsements of a full block weigh as much as the block
+    pub score: f64,
+    /// whether the peer is currently connected to us
+    pub connected: bool,
+    /// whether the peer is protected from eviction as one of the top contributors
+    pub protected: bool,
+}
@@ -0,0 +1,11 @@
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Round trip latency to a connected peer, measured with keepalive pings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerLatency {
+    /// smoothed round trip time
+    pub rtt: MassaTime,
+    /// smoothed deviation of the round trip time
+    pub jitter: MassaTime,
+}
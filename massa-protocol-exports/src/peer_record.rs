@@ -0,0 +1,165 @@
+use std::net::SocketAddr;
+
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{PeerAgent, PeerId, PeerLatency};
+
+/// Number of bans of a peer kept in its history
+const MAX_BAN_HISTORY: usize = 16;
+
+/// Uptime after which a peer stops gaining reputation by staying connected
+const MAX_RANKED_UPTIME: MassaTime = MassaTime::from_millis(7 * 24 * 60 * 60 * 1000);
+/// Bans older than this do not weigh on the reputation anymore
+const BAN_MEMORY: MassaTime = MassaTime::from_millis(7 * 24 * 60 * 60 * 1000);
+
+/// What the node remembers of a peer across restarts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerRecord {
+    /// peer
+    pub peer_id: PeerId,
+    /// listeners announced by the peer, or set by the node operator
+    #[serde(default)]
+    pub listeners: Vec<SocketAddr>,
+    /// time at which the peer was first recorded
+    pub first_seen: MassaTime,
+    /// last time the peer was seen connected, none if it never was
+    pub last_seen: Option<MassaTime>,
+    /// total time the peer stayed connected
+    pub uptime: MassaTime,
+    /// number of successful handshakes with the peer
+    pub connections: u64,
+    /// number of handshakes with the peer that failed after it was identified
+    pub handshake_failures: u64,
+    /// times at which the peer was banned, the latest last
+    #[serde(default)]
+    pub bans: Vec<MassaTime>,
+    /// software announced by the peer during the last handshake
+    pub agent: Option<PeerAgent>,
+    /// last latency measured with the peer, none if it never answered a ping
+    #[serde(default)]
+    pub latency: Option<PeerLatency>,
+    /// whether the node operator asked to connect to the peer first, and to never forget it
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+impl PeerRecord {
+    /// Record of a peer seen for the first time at `now`
+    pub fn new(peer_id: PeerId, now: MassaTime) -> Self {
+        PeerRecord {
+            peer_id,
+            listeners: Vec::new(),
+            first_seen: now,
+            last_seen: None,
+            uptime: MassaTime::from_millis(0),
+            connections: 0,
+            handshake_failures: 0,
+            bans: Vec::new(),
+            agent: None,
+            latency: None,
+            pinned: false,
+        }
+    }
+
+    /// Notes a ban of the peer at `now`, forgetting the oldest bans
+    pub fn note_ban(&mut self, now: MassaTime) {
+        self.bans.push(now);
+        if self.bans.len() > MAX_BAN_HISTORY {
+            self.bans.drain(..self.bans.len() - MAX_BAN_HISTORY);
+        }
+    }
+
+    /// Score ranking the peer for the outbound connections at `now`, the higher the better.
+    ///
+    /// A minute of uptime is worth a point, up to a week, and each successful handshake ten points, up to a hundred handshakes.
+    /// Each failed handshake costs fifty points, up to a hundred failures, and each ban of the last week a thousand points.
+    pub fn reputation(&self, now: MassaTime) -> i64 {
+        let uptime = self.uptime.min(MAX_RANKED_UPTIME).as_millis() / 60_000;
+        let connections = self.connections.min(100) * 10;
+        let failures = self.handshake_failures.min(100) * 50;
+        let recent_bans = self
+            .bans
+            .iter()
+            .filter(|banned_at| now.saturating_sub(**banned_at) < BAN_MEMORY)
+            .count() as u64;
+        (uptime + connections) as i64 - (failures + recent_bans * 1000) as i64
+    }
+}
+
+/// Change of a peer record asked by the node operator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerRecordEdit {
+    /// peer, recorded if it is not known yet
+    pub peer_id: PeerId,
+    /// listeners replacing the known ones, if any
+    #[serde(default)]
+    pub listeners: Option<Vec<SocketAddr>>,
+    /// whether the peer is pinned, unchanged if none
+    #[serde(default)]
+    pub pinned: Option<bool>,
+    /// forget the handshake failures and the bans of the peer
+    #[serde(default)]
+    pub reset_reputation: bool,
+}
+
+impl PeerRecordEdit {
+    /// Applies the change to `record`
+    pub fn apply(&self, record: &mut PeerRecord) {
+        if let Some(listeners) = &self.listeners {
+            record.listeners = listeners.clone();
+        }
+        if let Some(pinned) = self.pinned {
+            record.pinned = pinned;
+        }
+        if self.reset_reputation {
+            record.handshake_failures = 0;
+            record.bans.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_signature::KeyPair;
+
+    use super::*;
+
+    #[test]
+    fn test_peer_record_reputation() {
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let now = MassaTime::from_millis(30 * 24 * 60 * 60 * 1000);
+        let mut record = PeerRecord::new(peer_id, MassaTime::from_millis(0));
+        assert_eq!(record.reputation(now), 0);
+
+        // an hour of uptime over two connections
+        record.uptime = MassaTime::from_millis(60 * 60 * 1000);
+        record.connections = 2;
+        assert_eq!(record.reputation(now), 80);
+        record.handshake_failures = 1;
+        assert_eq!(record.reputation(now), 30);
+
+        // only the recent bans weigh on the reputation
+        record.note_ban(MassaTime::from_millis(0));
+        assert_eq!(record.reputation(now), 30);
+        record.note_ban(now);
+        assert_eq!(record.reputation(now), -970);
+
+        // the ban history is bounded
+        for _ in 0..MAX_BAN_HISTORY {
+            record.note_ban(now);
+        }
+        assert_eq!(record.bans.len(), MAX_BAN_HISTORY);
+
+        PeerRecordEdit {
+            peer_id,
+            listeners: Some(vec!["198.51.100.1:31245".parse().unwrap()]),
+            pinned: Some(true),
+            reset_reputation: true,
+        }
+        .apply(&mut record);
+        assert!(record.pinned);
+        assert_eq!(record.listeners.len(), 1);
+        assert_eq!(record.reputation(now), 80);
+    }
+}
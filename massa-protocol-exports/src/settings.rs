@@ -1,11 +1,13 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use std::{
-    collections::HashMap,
-    net::{IpAddr, SocketAddr},
+    collections::{HashMap, HashSet},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
     path::PathBuf,
 };
 
+use crate::PeerId;
+use massa_models::config::ChainParametersSchedule;
 use massa_models::version::Version;
 use massa_time::MassaTime;
 use peernet::transports::TransportType;
@@ -19,6 +21,18 @@ pub struct PeerCategoryInfo {
     pub max_in_connections_per_ip: usize,
 }
 
+/// Token bucket limiting the number of items of a given type received from each peer
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct PeerRateLimit {
+    /// number of items per second refilled in the bucket of each peer (0 to disable the limit)
+    pub items_per_second: u64,
+    /// capacity of the bucket of each peer: number of items that can be received in a burst
+    pub burst: u64,
+    /// number of messages dropped because of the limit after which the peer is banned,
+    /// reset when the bucket of the peer is full again
+    pub max_throttled_messages: u64,
+}
+
 /// Dynamic protocol configuration mix in static settings and constants configurations.
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProtocolConfig {
@@ -26,10 +40,30 @@ pub struct ProtocolConfig {
     pub keypair_file: PathBuf,
     /// listeners from where we can receive messages
     pub listeners: HashMap<SocketAddr, TransportType>,
+    /// listeners announced to the other nodes at our routable ips, all of them if None
+    pub advertised_listeners: Option<HashSet<SocketAddr>>,
     /// initial peers path
     pub initial_peers: PathBuf,
     /// after `ask_block_timeout` milliseconds we try to ask a block to another node
     pub ask_block_timeout: MassaTime,
+    /// max time to wait for the answer to an ask for block data: `ask_block_timeout` is doubled after each unanswered ask of the same block to the same node, up to this value (no backoff if below `ask_block_timeout`)
+    pub ask_block_max_timeout: MassaTime,
+    /// number of unanswered asks of a block after which a node is only asked again for it if no other node can be asked (0 to disable)
+    pub max_ask_block_failures_per_node: usize,
+    /// number of nodes asked at the same time for the data of a same block, the first useful answer cancelling the other asks (1 to ask a single node at a time)
+    pub max_parallel_block_asks: usize,
+    /// file in which the block wishlist is saved on shutdown, to resume its retrieval after a restart
+    pub block_wishlist_file: PathBuf,
+    /// maximum age of a saved block wishlist to be resumed, 0 disables the persistence of the wishlist
+    pub block_wishlist_max_age: MassaTime,
+    /// file in which the bans set by the node operator are kept across restarts
+    pub ban_list_file: PathBuf,
+    /// file in which the addresses exempted from the subnet bans are kept across restarts
+    pub peers_whitelist_file: PathBuf,
+    /// file in which the peers and their reputation are kept across restarts
+    pub peer_store_file: PathBuf,
+    /// maximum number of peers kept in the peer store, the worst ranked unpinned peers being forgotten first
+    pub peer_store_max_records: usize,
     /// Max known blocks we keep during their propagation
     pub max_blocks_kept_for_propagation: usize,
     /// Time during which a block is expected to propagate
@@ -46,6 +80,8 @@ pub struct ProtocolConfig {
     pub max_known_ops_size: usize,
     /// max known operations of foreign nodes we keep in memory (by node)
     pub max_node_known_ops_size: usize,
+    /// target false positive rate of the known operations of foreign nodes
+    pub node_known_ops_false_positive_rate: f64,
     /// max known endorsements by our node that we kept in memory
     pub max_known_endorsements_size: usize,
     /// max known endorsements of foreign nodes we keep in memory (by node)
@@ -66,14 +102,20 @@ pub struct ProtocolConfig {
     pub asked_operations_buffer_capacity: usize,
     /// Interval at which operations are announced in batches.
     pub operation_announcement_interval: MassaTime,
+    /// Interval at which the operations asked by each peer are sent, merged per peer (0 to send them as soon as asked)
+    pub operation_send_interval: MassaTime,
     /// Maximum time we keep an operation in the storage
     pub max_operation_storage_time: MassaTime,
     /// Maximum of operations sent in one message.
     pub max_operations_per_message: u64,
-    /// Maximum of operations sent in one block.
-    pub max_operations_per_block: u32,
-    /// Maximum size in bytes of all serialized operations size in a block
-    pub max_serialized_operations_size_per_block: usize,
+    /// Chain parameters versions, bounding the number and the size of the operations of a block at its slot
+    #[serde(skip)]
+    pub chain_parameters: ChainParametersSchedule,
+    /// Ask peers for compressed operations when retrieving the operations of a block.
+    /// Only the peers announcing it during the handshake are asked for them.
+    pub compress_block_operations: bool,
+    /// Send the full operations compressed to the peers announcing they accept it
+    pub compress_operation_messages: bool,
     /// Controller channel size
     pub controller_channel_size: usize,
     /// Event channel size
@@ -86,8 +128,14 @@ pub struct ProtocolConfig {
     pub max_ops_kept_for_propagation: usize,
     /// max time we propagate operations
     pub max_operations_propagation_time: MassaTime,
+    /// max number of randomly chosen peers each operation is announced to (0 to announce it to every peer)
+    pub propagation_fanout: usize,
+    /// time during which the knowledge of a disconnected peer is kept to be restored if it reconnects (0 to forget it immediately)
+    pub peer_knowledge_retention: MassaTime,
     /// max time we propagate endorsements
     pub max_endorsements_propagation_time: MassaTime,
+    /// interval during which endorsements are buffered to be propagated in a single batch per peer (0 to propagate them immediately)
+    pub endorsement_propagation_interval: MassaTime,
     /// Max message size
     pub max_message_size: usize,
     /// number of thread tester
@@ -152,8 +200,20 @@ pub struct ProtocolConfig {
     pub unban_everyone_timer: MassaTime,
     /// Max in connections
     pub max_in_connections: usize,
+    /// Number of handshakes attempted with an address within a minute after which it is put in cooldown (0 for no limit)
+    pub max_handshakes_per_ip_per_minute: usize,
+    /// Time during which the handshakes with an address exceeding `max_handshakes_per_ip_per_minute` are refused
+    pub handshake_cooldown: MassaTime,
     /// Timeout connection
     pub timeout_connection: MassaTime,
+    /// Timeout of the resolution of a peer host name
+    pub dns_timeout: MassaTime,
+    /// Timeout of the connection to a peer, over all its addresses
+    pub dial_timeout: MassaTime,
+    /// Try the IPv6 addresses of dual-stack peers first
+    pub prefer_ipv6: bool,
+    /// SOCKS5 proxy through which the outbound connections are opened and the host names resolved
+    pub socks5_proxy: Option<SocketAddr>,
     /// Timeout message
     pub message_timeout: MassaTime,
     /// Timeout for the tester operations
@@ -162,6 +222,14 @@ pub struct ProtocolConfig {
     pub read_write_limit_bytes_per_second: u128,
     /// Optional routable ip
     pub routable_ip: Option<IpAddr>,
+    /// Optional routable IPv6 address, announced along with `routable_ip` by dual-stack nodes
+    pub routable_ipv6: Option<Ipv6Addr>,
+    /// Request a port mapping from the router with NAT-PMP at startup, and announce its external IP if `routable_ip` is not set
+    pub nat_traversal: bool,
+    /// Router asked for the port mapping, the default gateway if None
+    pub nat_gateway: Option<Ipv4Addr>,
+    /// Lifetime requested for the port mapping, which is renewed at half its lifetime
+    pub nat_mapping_lifetime: MassaTime,
     /// debug prints
     pub debug: bool,
     /// Peers categories infos
@@ -170,10 +238,74 @@ pub struct ProtocolConfig {
     pub default_category_info: PeerCategoryInfo,
     /// Version
     pub version: Version,
+    /// User agent announced to peers during the handshake
+    pub user_agent: String,
+    /// Maximum length of the user agent announced by a peer
+    pub max_user_agent_length: u32,
     /// Cooldown before testing again an old peer
     pub test_oldest_peer_cooldown: MassaTime,
     /// Rate limit to apply on the data stream
     pub rate_limit: u64,
     /// Chain id
     pub chain_id: u64,
+    /// Maximum number of staker heartbeats in a single message
+    pub max_heartbeats_per_message: u64,
+    /// Duration during which a received staker heartbeat is considered fresh
+    pub heartbeat_validity: MassaTime,
+    /// Maximum number of staker heartbeats kept by the node
+    pub max_known_heartbeats: usize,
+    /// Maximum number of node address links in a single message
+    pub max_node_links_per_message: u64,
+    /// Duration during which a received node address link is considered fresh
+    pub node_link_validity: MassaTime,
+    /// Maximum number of node address links kept by the node
+    pub max_known_node_links: usize,
+    /// Processing time of a received message above which a warning is logged
+    pub slow_message_threshold: MassaTime,
+    /// Lag behind the network above which received operations and endorsements are not processed
+    pub max_sync_lag_for_gossip: MassaTime,
+    /// Ask the connected peers for their pending operations once the node is synced after a start, to refill the pool
+    pub pool_warmup_enabled: bool,
+    /// Minimal interval between two requests for pending operations answered to the same peer
+    pub pending_operations_request_min_interval: MassaTime,
+    /// Send a sketch of the pending operations to each newly connected peer, so that it announces the pending operations we lack
+    pub pending_operations_sync_enabled: bool,
+    /// Maximal time a received header slot can be in the future, beyond which the header is dropped (0 to disable)
+    pub max_header_future_time: MassaTime,
+    /// Number of periods in the past beyond which a received header is considered long since final and dropped (0 to disable)
+    pub max_header_past_periods: u64,
+    /// Number of out of bounds headers a peer can send before being banned
+    pub max_out_of_bounds_headers_per_peer: u64,
+    /// Number of invalid items (blocks, headers, operations, endorsements) received from a peer within `invalid_items_window` after which it is banned (1 to ban it at the first one)
+    pub invalid_items_ban_threshold: usize,
+    /// Sliding window over which the invalid items received from a peer are counted
+    pub invalid_items_window: MassaTime,
+    /// Rate limit of the operations received from each peer
+    pub operations_rate_limit: PeerRateLimit,
+    /// Rate limit of the endorsements received from each peer
+    pub endorsements_rate_limit: PeerRateLimit,
+    /// Look up the peers closest to random node ids to discover peers beyond the ones exchanged by our connections
+    pub peer_discovery_enabled: bool,
+    /// Peers asked first for the peers closest to our own node id, their listeners are taken from the initial peers
+    pub peer_discovery_bootstrap_nodes: Vec<PeerId>,
+    /// Maximum number of peers kept in each bucket of the discovery routing table, also the number of peers answered to a lookup
+    pub peer_discovery_bucket_size: usize,
+    /// Interval after which a bucket of the discovery routing table that was not looked up is refreshed
+    pub peer_discovery_refresh_interval: MassaTime,
+    /// Time after which the contribution of a peer to the final blocks and endorsements counts half
+    pub contribution_half_life: MassaTime,
+    /// Number of top contributing peers that are never evicted to make room for new in connections
+    pub contribution_protected_peers: usize,
+    /// Minimal interval between two evictions of the lowest contributing in connection of a full category (0 to disable)
+    pub contribution_eviction_interval: MassaTime,
+    /// Time window of the finalized blocks whose origin is checked to detect an eclipse or a network split
+    pub net_split_window: MassaTime,
+    /// Number of blocks to finalize within the window before checking their origin (0 to disable the detection)
+    pub net_split_min_final_blocks: usize,
+    /// Alert when the blocks finalized within the window were first sent by fewer distinct peers
+    pub net_split_min_origin_peers: usize,
+    /// Alert when these peers, or the connected peers, are spread over fewer distinct subnets
+    pub net_split_min_subnets: usize,
+    /// Interval between two keepalive pings measuring the latency to each connected peer (0 to disable)
+    pub ping_interval: MassaTime,
 }
@@ -0,0 +1,246 @@
+//! Minimal SOCKS5 client (RFC 1928), without authentication, to route the outbound connections through a proxy such as Tor.
+//!
+//! Host names are resolved by the proxy with the RESOLVE command of Tor, so that no DNS request leaves the node.
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+const VERSION: u8 = 5;
+const NO_AUTHENTICATION: u8 = 0;
+const COMMAND_CONNECT: u8 = 1;
+/// Tor extension resolving a host name
+const COMMAND_RESOLVE: u8 = 0xF0;
+const ADDRESS_IPV4: u8 = 1;
+const ADDRESS_DOMAIN: u8 = 3;
+const ADDRESS_IPV6: u8 = 4;
+
+fn proxy_error(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("SOCKS5 proxy: {}", message))
+}
+
+/// Target of a request, as encoded in the request
+enum Target<'a> {
+    Addr(SocketAddr),
+    Domain(&'a str, u16),
+}
+
+/// Request sent once the method is negotiated
+fn request(command: u8, target: &Target) -> io::Result<Vec<u8>> {
+    let mut request = vec![VERSION, command, 0];
+    let port = match target {
+        Target::Addr(SocketAddr::V4(addr)) => {
+            request.push(ADDRESS_IPV4);
+            request.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        Target::Addr(SocketAddr::V6(addr)) => {
+            request.push(ADDRESS_IPV6);
+            request.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        Target::Domain(host, port) => {
+            let len = u8::try_from(host.len())
+                .map_err(|_| proxy_error(format!("host name too long: {}", host)))?;
+            request.push(ADDRESS_DOMAIN);
+            request.push(len);
+            request.extend_from_slice(host.as_bytes());
+            *port
+        }
+    };
+    request.extend_from_slice(&port.to_be_bytes());
+    Ok(request)
+}
+
+/// Message of a reply code
+fn reply_error(code: u8) -> io::Error {
+    let message = match code {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    };
+    proxy_error(format!("{} (reply {})", message, code))
+}
+
+/// Reads a reply, returning the bound address given by the proxy
+fn read_reply(stream: &mut impl Read) -> io::Result<SocketAddr> {
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header)?;
+    if header[0] != VERSION {
+        return Err(proxy_error(format!("unexpected version {}", header[0])));
+    }
+    if header[1] != 0 {
+        return Err(reply_error(header[1]));
+    }
+    let ip = match header[3] {
+        ADDRESS_IPV4 => {
+            let mut octets = [0u8; 4];
+            stream.read_exact(&mut octets)?;
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        ADDRESS_IPV6 => {
+            let mut octets = [0u8; 16];
+            stream.read_exact(&mut octets)?;
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        ADDRESS_DOMAIN => {
+            // the bound host name is not needed: skip it
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len)?;
+            let mut host = vec![0u8; len[0] as usize];
+            stream.read_exact(&mut host)?;
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+        }
+        address_type => {
+            return Err(proxy_error(format!(
+                "unexpected address type {}",
+                address_type
+            )))
+        }
+    };
+    let mut port = [0u8; 2];
+    stream.read_exact(&mut port)?;
+    Ok(SocketAddr::new(ip, u16::from_be_bytes(port)))
+}
+
+/// SOCKS5 proxy through which the outbound connections are opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Socks5Proxy {
+    addr: SocketAddr,
+}
+
+impl Socks5Proxy {
+    /// Proxy listening at `addr`
+    pub fn new(addr: SocketAddr) -> Self {
+        Socks5Proxy { addr }
+    }
+
+    /// Address of the proxy
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Sends a request to the proxy and reads its reply, everything within `timeout`
+    fn send_request(
+        &self,
+        command: u8,
+        target: &Target,
+        timeout: Duration,
+    ) -> io::Result<(TcpStream, SocketAddr)> {
+        let deadline = Instant::now() + timeout;
+        let remaining = || {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "SOCKS5 proxy: timed out",
+                ))
+            } else {
+                Ok(remaining)
+            }
+        };
+        let mut stream = TcpStream::connect_timeout(&self.addr, remaining()?)?;
+        stream.set_write_timeout(Some(remaining()?))?;
+        stream.set_read_timeout(Some(remaining()?))?;
+
+        stream.write_all(&[VERSION, 1, NO_AUTHENTICATION])?;
+        let mut method = [0u8; 2];
+        stream.read_exact(&mut method)?;
+        if method != [VERSION, NO_AUTHENTICATION] {
+            return Err(proxy_error(
+                "the proxy requires an authentication".to_string(),
+            ));
+        }
+
+        stream.set_write_timeout(Some(remaining()?))?;
+        stream.write_all(&request(command, target)?)?;
+        stream.set_read_timeout(Some(remaining()?))?;
+        let bound = read_reply(&mut stream)?;
+
+        stream.set_read_timeout(None)?;
+        stream.set_write_timeout(None)?;
+        Ok((stream, bound))
+    }
+
+    /// Opens a connection to `target` through the proxy, within `timeout`
+    pub fn connect(&self, target: SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+        self.send_request(COMMAND_CONNECT, &Target::Addr(target), timeout)
+            .map(|(stream, _)| stream)
+    }
+
+    /// Resolves `host` through the proxy, within `timeout`.
+    /// The proxy must support the RESOLVE extension of Tor.
+    pub fn resolve(&self, host: &str, timeout: Duration) -> io::Result<IpAddr> {
+        self.send_request(COMMAND_RESOLVE, &Target::Domain(host, 0), timeout)
+            .map(|(_, resolved)| resolved.ip())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::time::Duration;
+
+    use super::{read_reply, request, Socks5Proxy, Target, COMMAND_CONNECT, COMMAND_RESOLVE};
+
+    #[test]
+    fn test_socks5_messages() {
+        let v4: SocketAddr = "203.0.113.7:31244".parse().unwrap();
+        assert_eq!(
+            request(COMMAND_CONNECT, &Target::Addr(v4)).unwrap(),
+            [5, 1, 0, 1, 203, 0, 113, 7, 0x7a, 0x0c]
+        );
+        let v6: SocketAddr = "[2001:db8::7]:31244".parse().unwrap();
+        let encoded = request(COMMAND_CONNECT, &Target::Addr(v6)).unwrap();
+        assert_eq!(encoded.len(), 22);
+        assert_eq!(encoded[3], 4);
+        assert_eq!(
+            request(COMMAND_RESOLVE, &Target::Domain("massa.net", 0)).unwrap(),
+            [5, 0xF0, 0, 3, 9, b'm', b'a', b's', b's', b'a', b'.', b'n', b'e', b't', 0, 0]
+        );
+        assert!(request(COMMAND_RESOLVE, &Target::Domain(&"a".repeat(256), 0)).is_err());
+
+        let reply = [5, 0, 0, 1, 198, 51, 100, 1, 0x7a, 0x0c];
+        assert_eq!(
+            read_reply(&mut &reply[..]).unwrap(),
+            "198.51.100.1:31244".parse().unwrap()
+        );
+        // connection refused
+        assert!(read_reply(&mut &[5, 5, 0, 1, 0, 0, 0, 0, 0, 0][..]).is_err());
+    }
+
+    #[test]
+    fn test_socks5_connect() {
+        let target: SocketAddr = "203.0.113.7:31244".parse().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy = Socks5Proxy::new(listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut greeting = [0u8; 3];
+            stream.read_exact(&mut greeting).unwrap();
+            assert_eq!(greeting, [5, 1, 0]);
+            stream.write_all(&[5, 0]).unwrap();
+            let mut connect = [0u8; 10];
+            stream.read_exact(&mut connect).unwrap();
+            assert_eq!(connect, [5, 1, 0, 1, 203, 0, 113, 7, 0x7a, 0x0c]);
+            stream
+                .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0x7a, 0x0c])
+                .unwrap();
+            // the connection is then relayed to the target
+            stream.write_all(b"massa").unwrap();
+        });
+
+        let mut stream = proxy.connect(target, Duration::from_secs(5)).unwrap();
+        let mut data = [0u8; 5];
+        stream.read_exact(&mut data).unwrap();
+        assert_eq!(&data, b"massa");
+        server.join().unwrap();
+    }
+}
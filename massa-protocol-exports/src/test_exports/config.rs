@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
-use crate::{settings::PeerCategoryInfo, ProtocolConfig};
-use massa_models::config::{CHAINID, ENDORSEMENT_COUNT, MAX_MESSAGE_SIZE};
+use crate::{
+    settings::{PeerCategoryInfo, PeerRateLimit},
+    ProtocolConfig,
+};
+use massa_models::config::{ChainParametersSchedule, CHAINID, ENDORSEMENT_COUNT, MAX_MESSAGE_SIZE};
 use massa_time::MassaTime;
 use tempfile::NamedTempFile;
 
@@ -15,6 +18,27 @@ impl Default for ProtocolConfig {
                 .path()
                 .to_path_buf(),
             ask_block_timeout: MassaTime::from_millis(10000),
+            ask_block_max_timeout: MassaTime::from_millis(0),
+            max_ask_block_failures_per_node: 0,
+            max_parallel_block_asks: 1,
+            block_wishlist_file: NamedTempFile::new()
+                .expect("cannot create temp file")
+                .path()
+                .to_path_buf(),
+            block_wishlist_max_age: MassaTime::from_millis(0),
+            ban_list_file: NamedTempFile::new()
+                .expect("cannot create temp file")
+                .path()
+                .to_path_buf(),
+            peers_whitelist_file: NamedTempFile::new()
+                .expect("cannot create temp file")
+                .path()
+                .to_path_buf(),
+            peer_store_file: NamedTempFile::new()
+                .expect("cannot create temp file")
+                .path()
+                .to_path_buf(),
+            peer_store_max_records: 1000,
             max_blocks_kept_for_propagation: 300,
             max_block_propagation_time: MassaTime::from_millis(40000),
             block_propagation_tick: MassaTime::from_millis(1000),
@@ -25,6 +49,7 @@ impl Default for ProtocolConfig {
             max_send_wait: MassaTime::from_millis(100),
             max_known_ops_size: 1000,
             max_node_known_ops_size: 1000,
+            node_known_ops_false_positive_rate: 0.001,
             max_known_endorsements_size: 1000,
             max_node_known_endorsements_size: 1000,
             operation_batch_buffer_capacity: 1000,
@@ -33,22 +58,28 @@ impl Default for ProtocolConfig {
             operation_batch_proc_period: MassaTime::from_millis(200),
             asked_operations_buffer_capacity: 10000,
             operation_announcement_interval: MassaTime::from_millis(150),
+            operation_send_interval: MassaTime::from_millis(0),
             max_operations_per_message: 1024,
-            max_operations_per_block: 5000,
+            chain_parameters: ChainParametersSchedule::default(),
             thread_count: 32,
-            max_serialized_operations_size_per_block: 1024,
+            compress_block_operations: false,
+            compress_operation_messages: false,
             controller_channel_size: 1024,
             event_channel_size: 1024,
             genesis_timestamp: MassaTime::now(),
             t0: MassaTime::from_millis(16000),
             max_ops_kept_for_propagation: 10000,
             max_operations_propagation_time: MassaTime::from_millis(30000),
+            propagation_fanout: 0,
+            peer_knowledge_retention: MassaTime::from_millis(0),
             max_endorsements_propagation_time: MassaTime::from_millis(60000),
+            endorsement_propagation_interval: MassaTime::from_millis(0),
             initial_peers: NamedTempFile::new()
                 .expect("cannot create temp file")
                 .path()
                 .to_path_buf(),
             listeners: HashMap::default(),
+            advertised_listeners: None,
             thread_tester_count: 2,
             max_size_channel_commands_connectivity: 1000,
             max_size_channel_commands_retrieval_operations: 10000,
@@ -80,10 +111,20 @@ impl Default for ProtocolConfig {
             last_start_period: 0,
             read_write_limit_bytes_per_second: 1024 * 1000,
             timeout_connection: MassaTime::from_millis(1000),
+            dns_timeout: MassaTime::from_millis(2000),
+            dial_timeout: MassaTime::from_millis(3000),
+            prefer_ipv6: true,
+            socks5_proxy: None,
             try_connection_timer: MassaTime::from_millis(5000),
             unban_everyone_timer: MassaTime::from_millis(ONE_DAY_MS),
             routable_ip: None,
+            routable_ipv6: None,
+            nat_traversal: false,
+            nat_gateway: None,
+            nat_mapping_lifetime: MassaTime::from_millis(7200000),
             max_in_connections: 10,
+            max_handshakes_per_ip_per_minute: 0,
+            handshake_cooldown: MassaTime::from_millis(60000),
             debug: true,
             peers_categories: HashMap::default(),
             default_category_info: PeerCategoryInfo {
@@ -93,10 +134,50 @@ impl Default for ProtocolConfig {
                 max_in_connections_per_ip: 0,
             },
             version: "TEST.23.2".parse().unwrap(),
+            user_agent: "massa-node/TEST.23.2".to_string(),
+            max_user_agent_length: 128,
             try_connection_timer_same_peer: MassaTime::from_millis(1000),
             test_oldest_peer_cooldown: MassaTime::from_millis(720000),
             rate_limit: 1024 * 1024 * 2,
             chain_id: *CHAINID,
+            max_heartbeats_per_message: 1024,
+            heartbeat_validity: MassaTime::from_millis(120000),
+            max_known_heartbeats: 10000,
+            max_node_links_per_message: 1024,
+            node_link_validity: MassaTime::from_millis(1200000),
+            max_known_node_links: 10000,
+            slow_message_threshold: MassaTime::from_millis(100),
+            max_sync_lag_for_gossip: MassaTime::from_millis(32000),
+            pool_warmup_enabled: false,
+            pending_operations_request_min_interval: MassaTime::from_millis(60000),
+            pending_operations_sync_enabled: false,
+            max_header_future_time: MassaTime::from_millis(0),
+            max_header_past_periods: 0,
+            max_out_of_bounds_headers_per_peer: 10,
+            invalid_items_ban_threshold: 1,
+            invalid_items_window: MassaTime::from_millis(60000),
+            operations_rate_limit: PeerRateLimit {
+                items_per_second: 0,
+                burst: 0,
+                max_throttled_messages: 0,
+            },
+            endorsements_rate_limit: PeerRateLimit {
+                items_per_second: 0,
+                burst: 0,
+                max_throttled_messages: 0,
+            },
+            peer_discovery_enabled: false,
+            peer_discovery_bootstrap_nodes: Vec::new(),
+            peer_discovery_bucket_size: 20,
+            peer_discovery_refresh_interval: MassaTime::from_millis(60000),
+            contribution_half_life: MassaTime::from_millis(3600000),
+            contribution_protected_peers: 4,
+            contribution_eviction_interval: MassaTime::from_millis(60000),
+            net_split_window: MassaTime::from_millis(600000),
+            net_split_min_final_blocks: 32,
+            net_split_min_origin_peers: 3,
+            net_split_min_subnets: 2,
+            ping_interval: MassaTime::from_millis(10000),
         }
     }
 }
@@ -171,6 +171,8 @@ pub fn create_operation_with_expire_period(
         fee: Amount::default(),
         op,
         expire_period,
+        start_period: None,
+        fee_payer: None,
     };
     Operation::new_verifiable(content, OperationSerializer::new(), keypair, *CHAINID).unwrap()
 }
@@ -190,6 +192,8 @@ pub fn create_execute_sc_op_with_too_much_gas(
         fee: Amount::default(),
         op,
         expire_period,
+        start_period: None,
+        fee_payer: None,
     };
     Operation::new_verifiable(content, OperationSerializer::new(), keypair, *CHAINID).unwrap()
 }
@@ -214,6 +218,8 @@ pub fn create_call_sc_op_with_too_much_gas(
         fee: Amount::default(),
         op,
         expire_period,
+        start_period: None,
+        fee_payer: None,
     };
     Operation::new_verifiable(content, OperationSerializer::new(), keypair, *CHAINID).unwrap()
 }
@@ -0,0 +1,39 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::PeerId;
+
+/// Messages and bytes exchanged with the peers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrafficCounters {
+    /// number of messages sent
+    pub messages_sent: u64,
+    /// size of the messages sent, in bytes
+    pub bytes_sent: u64,
+    /// number of messages received
+    pub messages_received: u64,
+    /// size of the messages received, in bytes
+    pub bytes_received: u64,
+}
+
+impl TrafficCounters {
+    /// Notes a message of `bytes` sent
+    pub fn note_sent(&mut self, bytes: usize) {
+        self.messages_sent = self.messages_sent.saturating_add(1);
+        self.bytes_sent = self.bytes_sent.saturating_add(bytes as u64);
+    }
+
+    /// Notes a message of `bytes` received
+    pub fn note_received(&mut self, bytes: usize) {
+        self.messages_received = self.messages_received.saturating_add(1);
+        self.bytes_received = self.bytes_received.saturating_add(bytes as u64);
+    }
+}
+
+/// Traffic exchanged with the peers since the node started
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkTrafficStats {
+    /// traffic of each message type, named after the message and its sub-type such as `Block/Header`
+    pub by_message_type: BTreeMap<String, TrafficCounters>,
+    /// traffic with each connected peer
+    pub by_peer: HashMap<PeerId, TrafficCounters>,
+}
@@ -1,24 +1,41 @@
 use crossbeam::channel::tick;
 use crossbeam::select;
 use ip_rfc::global;
-use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
+use massa_channel::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
 use massa_consensus_exports::ConsensusController;
 use massa_metrics::MassaMetrics;
-use massa_models::stats::NetworkStats;
+use massa_models::{block_id::BlockId, stats::NetworkStats};
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
-use massa_protocol_exports::{PeerCategoryInfo, PeerId, ProtocolConfig, ProtocolError};
+use massa_protocol_exports::{
+    feature_adoption_warnings, NetSplitAlert, NetworkTrafficStats, NodeKnowledge, PeerAgent,
+    PeerCategoryInfo, PeerContribution, PeerId, PeerLatency, ProtocolConfig, ProtocolError,
+    ProtocolEvent, TransportType,
+};
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use massa_versioning::versioning::MipStore;
 use parking_lot::RwLock;
 use peernet::peer::PeerConnectionType;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::{collections::HashMap, net::IpAddr};
-use std::{thread::JoinHandle, time::Duration};
-use tracing::{debug, warn};
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+};
+use std::{
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
 
+use crate::contributions::PeerContributions;
+use crate::dialer::Dialer;
+use crate::handlers::peer_handler::ban_list::SharedBanList;
 use crate::handlers::peer_handler::models::ConnectionMetadata;
+use crate::net_split::NetSplitDetector;
+use crate::outbound::{allocation_order, OutboundCandidate};
+use crate::transport::{dial_transports, listeners_of};
 use crate::{
     handlers::peer_handler::models::{InitialPeers, PeerState, SharedPeerDB},
     ip::to_canonical,
@@ -27,7 +44,9 @@ use crate::{
 use crate::{handlers::peer_handler::PeerManagementHandler, messages::MessagesHandler};
 use crate::{
     handlers::{
-        block_handler::{cache::BlockCache, BlockHandler},
+        block_handler::{
+            cache::BlockCache, commands_retrieval::BlockHandlerRetrievalCommand, BlockHandler,
+        },
         endorsement_handler::{cache::EndorsementCache, EndorsementHandler},
         operation_handler::{cache::OperationCache, OperationHandler},
         peer_handler::models::PeerMessageTuple,
@@ -49,6 +68,29 @@ pub enum ConnectivityCommand {
             HashMap<PeerId, (SocketAddr, PeerConnectionType)>,
         )>,
     },
+    GetPeerAgents {
+        responder: MassaSender<HashMap<PeerId, PeerAgent>>,
+    },
+    GetPeerLatencies {
+        responder: MassaSender<HashMap<PeerId, PeerLatency>>,
+    },
+    GetNetworkStats {
+        responder: MassaSender<NetworkTrafficStats>,
+    },
+    GetKnownBlocksSizes {
+        responder: MassaSender<HashMap<PeerId, usize>>,
+    },
+    GetNodeKnowledge {
+        responder: MassaSender<HashMap<PeerId, NodeKnowledge>>,
+    },
+    /// blocks that became final, to credit the peers that first sent them and their endorsements
+    FinalBlocks(Vec<BlockId>),
+    GetPeerContributions {
+        responder: MassaSender<HashMap<PeerId, PeerContribution>>,
+    },
+    GetNetSplitAlert {
+        responder: MassaSender<Option<NetSplitAlert>>,
+    },
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -76,6 +118,7 @@ pub(crate) fn start_connectivity_thread(
     ),
     initial_peers: InitialPeers,
     peer_db: SharedPeerDB,
+    ban_list: SharedBanList,
     storage: Storage,
     protocol_channels: ProtocolChannels,
     messages_handler: MessagesHandler,
@@ -110,23 +153,30 @@ pub(crate) fn start_connectivity_thread(
             let total_out_slots = config.peers_categories.values().map(| v| v.target_out_connections).sum::<usize>() + config.default_category_info.target_out_connections + 1;
             let operation_cache = Arc::new(RwLock::new(OperationCache::new(
                 config.max_known_ops_size.try_into().unwrap(),
-                config.max_node_known_ops_size.try_into().unwrap()
+                config.max_node_known_ops_size,
+                config.node_known_ops_false_positive_rate,
+                config.peer_knowledge_retention.to_duration(),
             )));
             let endorsement_cache = Arc::new(RwLock::new(EndorsementCache::new(
                 config.max_known_endorsements_size.try_into().unwrap(),
-                (total_in_slots + total_out_slots).try_into().unwrap()
+                (total_in_slots + total_out_slots).try_into().unwrap(),
+                config.peer_knowledge_retention.to_duration(),
             )));
 
             let block_cache = Arc::new(RwLock::new(BlockCache::new(
                 config.max_known_blocks_size.try_into().unwrap(),
                 config.max_node_known_blocks_size.try_into().unwrap(),
+                config.peer_knowledge_retention.to_duration(),
             )));
 
+            let traffic = messages_handler.traffic.clone();
+
             // Start handlers
             let mut peer_management_handler = PeerManagementHandler::new(
                 initial_peers,
                 peer_id,
                 peer_db.clone(),
+                ban_list.clone(),
                 channel_peers,
                 protocol_channels.peer_management_handler,
                 messages_handler,
@@ -135,6 +185,8 @@ pub(crate) fn start_connectivity_thread(
                 config.default_category_info.target_out_connections,
                 &config,
                 massa_metrics.clone(),
+                protocol_channels.protocol_events.0.clone(),
+                selector_controller.clone(),
             );
 
             let mut operation_handler = OperationHandler::new(
@@ -149,6 +201,9 @@ pub(crate) fn start_connectivity_thread(
                 sender_operations_propagation_ext.clone(),
                 protocol_channels.operation_handler_propagation.1.clone(),
                 peer_management_handler.sender.command_sender.clone(),
+                peer_db.clone(),
+                channel_blocks.1.clone(),
+                mip_store.clone(),
                 massa_metrics.clone(),
             );
             let mut endorsement_handler = EndorsementHandler::new(
@@ -172,24 +227,32 @@ pub(crate) fn start_connectivity_thread(
                 consensus_controller,
                 pool_controller,
                 channel_blocks.1,
-                sender_blocks_retrieval_ext,
+                sender_blocks_retrieval_ext.clone(),
                 protocol_channels.block_handler_retrieval.1.clone(),
                 protocol_channels.block_handler_propagation.1.clone(),
                 sender_blocks_propagation_ext,
                 sender_operations_propagation_ext,
                 sender_endorsements_propagation_ext,
                 peer_management_handler.sender.command_sender.clone(),
+                peer_db.clone(),
                 config.clone(),
-                endorsement_cache,
-                operation_cache,
-                block_cache,
+                endorsement_cache.clone(),
+                operation_cache.clone(),
+                block_cache.clone(),
                 storage.clone_without_refs(),
                 mip_store,
                 massa_metrics.clone(),
             );
 
             let tick_metrics = tick(massa_metrics.tick_delay);
+            // only log the feature adoption warnings when they change
+            let mut last_feature_warnings: Vec<String> = Vec::new();
             let tick_try_connect = tick(config.try_connection_timer.to_duration());
+            let dialer = Dialer::new(&config);
+            let mut contributions = PeerContributions::new(&config);
+            let mut last_eviction = Instant::now();
+            let mut net_split_detector = NetSplitDetector::new(&config);
+            let protocol_event_sender = protocol_channels.protocol_events.0.clone();
             let tick_unban_everyone = tick(config.unban_everyone_timer.to_duration());
 
             //Try to connect to peers
@@ -217,9 +280,9 @@ pub(crate) fn start_connectivity_thread(
                                 let active_node_count = network_controller.get_active_connections().get_peer_ids_connected().len() as u64;
                                 let in_connection_count = network_controller.get_active_connections().get_nb_in_connections() as u64;
                                 let out_connection_count = network_controller.get_active_connections().get_nb_out_connections() as u64;
-                                let (banned_peer_count, known_peer_count) = {
+                                let (banned_peer_count, known_peer_count, reachable, network_time_offset) = {
                                     let peer_db_read = peer_db.read();
-                                    (peer_db_read.get_banned_peer_count(), peer_db_read.get_known_peer_count())
+                                    (peer_db_read.get_banned_peer_count(), peer_db_read.get_known_peer_count(), peer_db_read.get_reachability(), peer_db_read.get_network_time_offset())
                                 };
                                 let stats = NetworkStats {
                                     active_node_count,
@@ -227,12 +290,113 @@ pub(crate) fn start_connectivity_thread(
                                     out_connection_count,
                                     banned_peer_count,
                                     known_peer_count,
+                                    reachable,
+                                    network_time_offset,
                                 };
                                 let peers: HashMap<PeerId, (SocketAddr, PeerConnectionType)> = network_controller.get_active_connections().get_peers_connected().into_iter().map(|(peer_id, peer)| {
                                     (peer_id, (peer.0, peer.1))
                                 }).collect();
                                 responder.try_send((stats, peers)).unwrap_or_else(|_| warn!("Failed to send stats to responder"));
                             }
+                            Ok(ConnectivityCommand::GetPeerAgents { responder }) => {
+                                let peers_connected = network_controller.get_active_connections().get_peer_ids_connected();
+                                let agents: HashMap<PeerId, PeerAgent> = {
+                                    let peer_db_read = peer_db.read();
+                                    peers_connected.into_iter().filter_map(|peer_id| {
+                                        let agent = peer_db_read.get_peers().get(&peer_id)?.agent.clone()?;
+                                        Some((peer_id, agent))
+                                    }).collect()
+                                };
+                                responder.try_send(agents).unwrap_or_else(|_| warn!("Failed to send peer agents to responder"));
+                            }
+                            Ok(ConnectivityCommand::GetPeerLatencies { responder }) => {
+                                let latencies = peer_db.read().get_latencies().latencies().clone();
+                                responder.try_send(latencies).unwrap_or_else(|_| warn!("Failed to send peer latencies to responder"));
+                            }
+                            Ok(ConnectivityCommand::GetKnownBlocksSizes { responder }) => {
+                                let sizes: HashMap<PeerId, usize> = block_cache
+                                    .read()
+                                    .blocks_known_by_peer
+                                    .iter()
+                                    .map(|(peer_id, known_blocks)| (*peer_id, known_blocks.len()))
+                                    .collect();
+                                responder.try_send(sizes).unwrap_or_else(|_| warn!("Failed to send known blocks sizes to responder"));
+                            }
+                            Ok(ConnectivityCommand::GetNodeKnowledge { responder }) => {
+                                let mut knowledge: HashMap<PeerId, NodeKnowledge> = network_controller.get_active_connections().get_peer_ids_connected().into_iter().map(|peer_id| (peer_id, NodeKnowledge::default())).collect();
+                                {
+                                    let block_cache_read = block_cache.read();
+                                    let operation_cache_read = operation_cache.read();
+                                    let endorsement_cache_read = endorsement_cache.read();
+                                    for (peer_id, node_knowledge) in knowledge.iter_mut() {
+                                        if let Some(known_blocks) = block_cache_read.blocks_known_by_peer.get(peer_id) {
+                                            for (block_id, (known, _)) in known_blocks.iter() {
+                                                if *known {
+                                                    node_knowledge.known_blocks.push(*block_id);
+                                                } else {
+                                                    node_knowledge.unknown_blocks.push(*block_id);
+                                                }
+                                            }
+                                        }
+                                        if let Some(known_ops) = operation_cache_read.ops_known_by_peer.get(peer_id) {
+                                            node_knowledge.known_operations_count = known_ops.known_ops_count();
+                                        }
+                                        if let Some(known_endorsements) = endorsement_cache_read.endorsements_known_by_peer.get(peer_id) {
+                                            node_knowledge.known_endorsements = known_endorsements.iter().map(|(endorsement_id, _)| *endorsement_id).collect();
+                                        }
+                                    }
+                                }
+                                // the asks are tracked by the block retrieval thread
+                                let (sender, receiver) = MassaChannel::new("get_asked_blocks".to_string(), Some(1));
+                                if sender_blocks_retrieval_ext.try_send(BlockHandlerRetrievalCommand::GetAskedBlocks { responder: sender }).is_err() {
+                                    warn!("Failed to ask the asked blocks to the block retrieval thread");
+                                } else {
+                                    match receiver.recv_timeout(Duration::from_secs(5)) {
+                                        Ok(asked_blocks) => {
+                                            for (peer_id, asked_blocks) in asked_blocks {
+                                                if let Some(node_knowledge) = knowledge.get_mut(&peer_id) {
+                                                    node_knowledge.asked_blocks = asked_blocks;
+                                                }
+                                            }
+                                        }
+                                        Err(_) => warn!("Failed to receive the asked blocks from the block retrieval thread"),
+                                    }
+                                }
+                                responder.try_send(knowledge).unwrap_or_else(|_| warn!("Failed to send node knowledge to responder"));
+                            }
+                            Ok(ConnectivityCommand::FinalBlocks(block_ids)) => {
+                                let now = Instant::now();
+                                let peers_connected = network_controller.get_active_connections().get_peers_connected();
+                                let block_cache_read = block_cache.read();
+                                let endorsement_cache_read = endorsement_cache.read();
+                                for block_id in block_ids {
+                                    let first_sender = block_cache_read.first_senders.peek(&block_id).copied();
+                                    if let Some(peer_id) = first_sender {
+                                        contributions.credit_block(peer_id, now);
+                                    }
+                                    net_split_detector.note_final_block(
+                                        first_sender.map(|peer_id| (peer_id, peers_connected.get(&peer_id).map(|(addr, _, _)| addr.ip()))),
+                                        now,
+                                    );
+                                    let Some(header) = block_cache_read.checked_headers.peek(&block_id) else {
+                                        continue;
+                                    };
+                                    for endorsement in &header.content.endorsements {
+                                        if let Some(peer_id) = endorsement_cache_read.first_senders.peek(&endorsement.id) {
+                                            contributions.credit_endorsement(*peer_id, now);
+                                        }
+                                    }
+                                }
+                            }
+                            Ok(ConnectivityCommand::GetPeerContributions { responder }) => {
+                                responder.try_send(contributions.leaderboard(Instant::now())).unwrap_or_else(|_| warn!("Failed to send peer contributions to responder"));
+                            }
+                            Ok(ConnectivityCommand::GetNetworkStats { responder }) => {
+                                responder.try_send(traffic.lock().stats()).unwrap_or_else(|_| warn!("Failed to send network stats to responder"));
+                            }
+                            Ok(ConnectivityCommand::GetNetSplitAlert { responder }) => {
+                                responder.try_send(net_split_detector.alert().cloned()).unwrap_or_else(|_| warn!("Failed to send net split alert to responder"));
+                            }
                             Err(_) => {
                                 warn!("Channel to connectivity thread is closed. Stopping the protocol");
                                 break;
@@ -246,13 +410,75 @@ pub(crate) fn start_connectivity_thread(
                         massa_metrics.set_active_connections(active_conn.get_nb_in_connections(), active_conn.get_nb_out_connections());
                         let peers_map = active_conn.get_peers_connections_bandwidth();
                         massa_metrics.update_peers_tx_rx(peers_map);
+                        let connected_peers: HashSet<String> = active_conn.get_peer_ids_connected().iter().map(|peer_id| peer_id.to_string()).collect();
+                        massa_metrics.retain_protocol_invalid_items(&connected_peers);
                         let peer_db_read = peer_db.read();
                         massa_metrics.set_known_peers(peer_db_read.get_known_peer_count() as usize);
                         massa_metrics.set_banned_peers(peer_db_read.get_banned_peer_count() as usize);
+                        let mut peers_versions: HashMap<String, usize> = HashMap::new();
+                        let mut agents = Vec::new();
+                        for peer_id in active_conn.get_peer_ids_connected() {
+                            if let Some(agent) = peer_db_read.get_peers().get(&peer_id).and_then(|info| info.agent.as_ref()) {
+                                *peers_versions.entry(agent.version.to_string()).or_default() += 1;
+                                agents.push(agent);
+                            }
+                        }
+                        massa_metrics.set_peers_versions(peers_versions);
+                        let feature_warnings = feature_adoption_warnings(agents, &config);
+                        if feature_warnings != last_feature_warnings {
+                            for feature_warning in &feature_warnings {
+                                warn!(peers_count = active_conn.get_peer_ids_connected().len(), "protocol feature adoption: {}", feature_warning);
+                            }
+                            last_feature_warnings = feature_warnings;
+                        }
                     },
                     recv(tick_try_connect) -> _ => {
-                        let active_conn = network_controller.get_active_connections();
+                        let mut active_conn = network_controller.get_active_connections();
                         let peers_connected = active_conn.get_peers_connected();
+
+                        // from time to time, evict the in connection contributing the least from a full category to make room for new peers
+                        let now = Instant::now();
+                        contributions.update_connected(&peers_connected.keys().copied().collect(), now);
+                        traffic.lock().retain_connected(&peers_connected.keys().copied().collect());
+                        {
+                            let mut peer_db_write = peer_db.write();
+                            let latencies = peer_db_write.get_latencies().latencies().clone();
+                            let peer_store = peer_db_write.get_peer_store_mut();
+                            peer_store.update_connected(peers_connected.keys().copied(), MassaTime::now());
+                            // remember the latencies to rank the peers when they are dialed again
+                            peer_store.note_latencies(&latencies);
+                        }
+
+                        // check that the recently finalized blocks do not come from a tiny subset of peers
+                        let had_net_split_alert = net_split_detector.alert().is_some();
+                        if let Some(alert) = net_split_detector.update(peers_connected.values().map(|(addr, _, _)| addr.ip()), now) {
+                            warn!("{}: do not act on the final state before checking the connectivity of the node", alert);
+                            if let Err(err) = protocol_event_sender.try_send(ProtocolEvent::NetSplitSuspected(alert)) {
+                                debug!("error sending protocol event: {:?}", err);
+                            }
+                        } else if had_net_split_alert && net_split_detector.alert().is_none() {
+                            info!("the recently finalized blocks come again from diverse peers");
+                        }
+                        let eviction_interval = config.contribution_eviction_interval.to_duration();
+                        if !eviction_interval.is_zero() && now.saturating_duration_since(last_eviction) >= eviction_interval {
+                            let mut in_peers_by_category: HashMap<Option<&String>, Vec<PeerId>> = HashMap::new();
+                            for (peer_id, (_, connection_type, category)) in &peers_connected {
+                                if *connection_type == PeerConnectionType::IN {
+                                    in_peers_by_category.entry(category.as_ref()).or_default().push(*peer_id);
+                                }
+                            }
+                            let candidates = in_peers_by_category.into_iter().filter(|(category, in_peers)| {
+                                let max_in_connections = category
+                                    .and_then(|category| peer_categories.get(category))
+                                    .map_or(config.default_category_info.max_in_connections, |(_, infos)| infos.max_in_connections);
+                                in_peers.len() >= max_in_connections
+                            }).flat_map(|(_, in_peers)| in_peers);
+                            if let Some(evicted_peer_id) = contributions.eviction_candidate(candidates, eviction_interval, now) {
+                                debug!("Evicting the low contributing peer {} to make room for new peers", evicted_peer_id);
+                                active_conn.shutdown_connection(&evicted_peer_id);
+                                last_eviction = now;
+                            }
+                        }
                         let peers_connection_queue = active_conn.get_peer_ids_out_connection_queue();
 
                         let mut connection_slots = HashMap::new();
@@ -261,10 +487,12 @@ pub(crate) fn start_connectivity_thread(
                             connection_slots.insert(category, infos.1.target_out_connections);
                         }
 
-                        // Get all the addresses we can connect to, without any filter or prioritization done yet
+                        // Get all the peers we can connect to, without any prioritization done yet
                         let mut addresses_can_connect  = Vec::new();
                         {
                             let peer_db_read = peer_db.read();
+                            let ban_list_read = ban_list.read();
+                            let now = MassaTime::now();
                             for (peer_id, peer_info) in peer_db_read.get_peers() {
 
                                 // If peer already connected, decrement the slots for the given category, or default category if none
@@ -292,6 +520,9 @@ pub(crate) fn start_connectivity_thread(
 
                                         if let Some((addr, _)) = last_announce.listeners.iter().next() {
                                             let canonical_ip = to_canonical(addr.ip());
+                                            if ban_list_read.is_peer_banned(peer_id, now) || ban_list_read.is_ip_banned(&canonical_ip, now) {
+                                                continue;
+                                            }
                                             let mut allowed_local_ips = false;
                                             // Check if the peer is in a category and we didn't reached out target yet
                                             let mut category_found = None;
@@ -331,7 +562,31 @@ pub(crate) fn start_connectivity_thread(
                                                 continue;
                                             }
 
-                                            addresses_can_connect.push((*addr, connection_metadata, category_found));
+                                            // all the usable listeners of the peer are dialed, for dual-stack peers
+                                            let usable_listeners: HashMap<SocketAddr, TransportType> = last_announce.listeners.iter().filter(|(listener, _)| {
+                                                !config.listeners.contains_key(listener) && (global(&to_canonical(listener.ip())) || allowed_local_ips)
+                                            }).map(|(listener, transport)| (*listener, *transport)).collect();
+                                            // over each transport supported by both sides, the preferred one first
+                                            let peer_features = peer_info.agent.as_ref().map(|agent| agent.features).unwrap_or_default();
+                                            let dial_targets: Vec<(TransportType, Vec<SocketAddr>)> = dial_transports(&config, peer_features).into_iter()
+                                                .map(|transport| (transport, listeners_of(&usable_listeners, transport).collect::<Vec<_>>()))
+                                                .filter(|(_, dial_addrs)| !dial_addrs.is_empty())
+                                                .collect();
+                                            // a peer reachable over both families is ranked by its best address
+                                            let connection_metadata = usable_listeners.keys()
+                                                .map(|dial_addr| peer_db_read.get_connection_metadata_or_default(dial_addr))
+                                                .fold(connection_metadata, std::cmp::min);
+                                            let peer_store = peer_db_read.get_peer_store();
+                                            let (pinned, reputation) = peer_store.rank(peer_id, now);
+                                            addresses_can_connect.push(OutboundCandidate {
+                                                addr: *addr,
+                                                metadata: connection_metadata,
+                                                category: category_found,
+                                                dial_targets,
+                                                pinned,
+                                                reputation,
+                                                latency: peer_store.latency(peer_id),
+                                            });
                                         } else {
                                             tracing::warn!("No listeners for the peer {peer_id}");
                                         }
@@ -340,12 +595,12 @@ pub(crate) fn start_connectivity_thread(
                             }
                         }
 
-                        // Sort addresses using the metadata
-                        addresses_can_connect.sort_by(|a, b| a.1.cmp(&b.1));
+                        // Order the candidates by quality, spreading them over the subnets
+                        let candidates = allocation_order(addresses_can_connect, peers_connected.values().map(|(addr, _, _)| addr.ip()));
 
                         // Connect to the given addresses, trying to fill all the slots available
                         let mut addresses_connected = vec![];
-                        for (addr, _, category) in addresses_can_connect.iter() {
+                        for OutboundCandidate { addr, category, dial_targets, .. } in candidates.iter() {
                             if addresses_connected.contains(addr) {
                                 continue;
                             }
@@ -357,7 +612,7 @@ pub(crate) fn start_connectivity_thread(
                                     for (name, slots) in connection_slots.iter_mut() {
                                         if name == *cat && *slots > 0 {
                                             // In case the connection succeeds, we take a place in a slot
-                                            if try_connect_peer(*addr, &mut network_controller, &peer_db, &config).is_ok() {
+                                            if try_connect_peer(dial_targets, &dialer, &mut network_controller, &peer_db).is_ok() {
                                                 *slots = slots.saturating_sub(1);
                                                 addresses_connected.push(*addr);
                                             }
@@ -368,7 +623,7 @@ pub(crate) fn start_connectivity_thread(
                                 // Default category
                                 None if connection_slots["default"] > 0 => {
                                     // In case the connection succeeds, we take a place in a slot
-                                    if try_connect_peer(*addr, &mut network_controller, &peer_db, &config).is_err() {
+                                    if try_connect_peer(dial_targets, &dialer, &mut network_controller, &peer_db).is_err() {
                                         if let Some(v) = connection_slots.get_mut("default") {
                                             *v = v.saturating_sub(1);
                                         }
@@ -402,23 +657,34 @@ pub(crate) fn start_connectivity_thread(
     Ok((protocol_channels.connectivity_thread.0, handle))
 }
 
-// Attempt to connect to peer
+// Attempt to connect to peer, through any of its addresses, trying each transport in turn
 fn try_connect_peer(
-    addr: SocketAddr,
+    targets: &[(TransportType, Vec<SocketAddr>)],
+    dialer: &Dialer,
     network_controller: &mut Box<dyn NetworkController>,
     peer_db: &SharedPeerDB,
-    config: &ProtocolConfig,
-) -> Result<(), ProtocolError> {
-    debug!("Trying to connect to addr {}", addr);
-
-    let conn_res = network_controller.try_connect(addr, config.timeout_connection.to_duration());
-    {
-        let mut peer_db_write = peer_db.write();
-        peer_db_write.set_try_connect_success_or_insert(&addr);
-        if let Err(ref err) = conn_res {
-            debug!("Failed to connect to peer {:?}: {:?}", addr, err);
-            peer_db_write.set_try_connect_failure_or_insert(&addr);
+) -> Result<SocketAddr, ProtocolError> {
+    let mut last_err = ProtocolError::GeneralProtocolError("no address to dial".to_string());
+    for (transport, addrs) in targets {
+        debug!(
+            "Trying to connect to addrs {:?} over {:?}",
+            addrs, transport
+        );
+
+        let res = dialer.dial(addrs, |addr, timeout| {
+            let conn_res = network_controller.try_connect(*transport, addr, timeout);
+            let mut peer_db_write = peer_db.write();
+            peer_db_write.set_try_connect_success_or_insert(&addr);
+            if let Err(ref err) = conn_res {
+                debug!("Failed to connect to peer {:?}: {:?}", addr, err);
+                peer_db_write.set_try_connect_failure_or_insert(&addr);
+            }
+            conn_res
+        });
+        match res {
+            Ok(addr) => return Ok(addr),
+            Err(err) => last_err = err,
         }
     }
-    conn_res
+    Err(last_err)
 }
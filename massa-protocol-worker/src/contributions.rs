@@ -0,0 +1,266 @@
+//! Contribution of the peers to the final blocks and endorsements.
+//!
+//! A peer is credited with a block or an endorsement that became final when it was the first one to send it to us.
+//! The credits decay exponentially with the configured half-life, so that the scores reflect the recent contribution.
+//! When the in connections of a peer category are full, the connected peer that contributes the least is evicted
+//! from time to time to make room for new peers, the top contributors being protected from eviction.
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::{PeerContribution, PeerId, ProtocolConfig};
+
+/// Score below which the contribution of a disconnected peer is forgotten
+const MIN_SCORE: f64 = 0.01;
+
+/// Multiplier applied to a credit after `elapsed`
+fn decay_factor(elapsed: Duration, half_life: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 1.0;
+    }
+    if half_life.is_zero() {
+        return 0.0;
+    }
+    0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Credits {
+    blocks: f64,
+    endorsements: f64,
+    /// time at which the credits were last decayed
+    updated_at: Instant,
+}
+
+impl Credits {
+    fn decayed(&self, now: Instant, half_life: Duration) -> Credits {
+        let factor = decay_factor(now.saturating_duration_since(self.updated_at), half_life);
+        Credits {
+            blocks: self.blocks * factor,
+            endorsements: self.endorsements * factor,
+            updated_at: now.max(self.updated_at),
+        }
+    }
+}
+
+pub(crate) struct PeerContributions {
+    /// time after which a credit counts half
+    half_life: Duration,
+    /// endorsements per block, so that the endorsements of a full block weigh as much as the block
+    endorsement_count: u32,
+    /// number of top contributors protected from eviction
+    protected_peers: usize,
+    credits: HashMap<PeerId, Credits>,
+    /// time at which each connected peer was first seen connected
+    connected_since: HashMap<PeerId, Instant>,
+}
+
+impl PeerContributions {
+    pub(crate) fn new(config: &ProtocolConfig) -> Self {
+        PeerContributions {
+            half_life: config.contribution_half_life.to_duration(),
+            endorsement_count: config.endorsement_count,
+            protected_peers: config.contribution_protected_peers,
+            credits: HashMap::new(),
+            connected_since: HashMap::new(),
+        }
+    }
+
+    fn credit(&mut self, peer_id: PeerId, blocks: f64, endorsements: f64, now: Instant) {
+        let half_life = self.half_life;
+        let credits = self.credits.entry(peer_id).or_insert(Credits {
+            blocks: 0.0,
+            endorsements: 0.0,
+            updated_at: now,
+        });
+        *credits = credits.decayed(now, half_life);
+        credits.blocks += blocks;
+        credits.endorsements += endorsements;
+    }
+
+    /// Credits a peer with a final block it was the first to send us
+    pub(crate) fn credit_block(&mut self, peer_id: PeerId, now: Instant) {
+        self.credit(peer_id, 1.0, 0.0, now);
+    }
+
+    /// Credits a peer with a final endorsement it was the first to send us
+    pub(crate) fn credit_endorsement(&mut self, peer_id: PeerId, now: Instant) {
+        self.credit(peer_id, 0.0, 1.0, now);
+    }
+
+    fn score(&self, credits: &Credits) -> f64 {
+        credits.blocks + credits.endorsements / f64::from(self.endorsement_count.max(1))
+    }
+
+    /// Current score of a peer, 0 if it never contributed
+    fn current_score(&self, peer_id: &PeerId, now: Instant) -> f64 {
+        self.credits.get(peer_id).map_or(0.0, |credits| {
+            self.score(&credits.decayed(now, self.half_life))
+        })
+    }
+
+    /// Notes the connected peers, and forgets the negligible contributions of the disconnected ones
+    pub(crate) fn update_connected(&mut self, connected: &HashSet<PeerId>, now: Instant) {
+        self.connected_since
+            .retain(|peer_id, _| connected.contains(peer_id));
+        for peer_id in connected {
+            self.connected_since.entry(*peer_id).or_insert(now);
+        }
+        let half_life = self.half_life;
+        let endorsement_count = f64::from(self.endorsement_count.max(1));
+        let connected_since = &self.connected_since;
+        self.credits.retain(|peer_id, credits| {
+            let decayed = credits.decayed(now, half_life);
+            connected_since.contains_key(peer_id)
+                || decayed.blocks + decayed.endorsements / endorsement_count >= MIN_SCORE
+        });
+    }
+
+    /// Connected peers protected from eviction: the top contributors
+    fn protected(&self, now: Instant) -> HashSet<PeerId> {
+        let mut contributors: Vec<(PeerId, f64)> = self
+            .connected_since
+            .keys()
+            .map(|peer_id| (*peer_id, self.current_score(peer_id, now)))
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        contributors.sort_unstable_by(|a, b| b.1.total_cmp(&a.1));
+        contributors
+            .into_iter()
+            .take(self.protected_peers)
+            .map(|(peer_id, _)| peer_id)
+            .collect()
+    }
+
+    /// Picks the connection to evict among `candidates`: the one with the lowest score, then the most recently connected.
+    /// The protected peers and the peers connected for less than `min_age` are never picked.
+    pub(crate) fn eviction_candidate(
+        &self,
+        candidates: impl IntoIterator<Item = PeerId>,
+        min_age: Duration,
+        now: Instant,
+    ) -> Option<PeerId> {
+        let protected = self.protected(now);
+        candidates
+            .into_iter()
+            .filter(|peer_id| !protected.contains(peer_id))
+            .filter_map(|peer_id| {
+                let connected_since = *self.connected_since.get(&peer_id)?;
+                if now.saturating_duration_since(connected_since) < min_age {
+                    return None;
+                }
+                Some((peer_id, self.current_score(&peer_id, now), connected_since))
+            })
+            .min_by(|a, b| match a.1.total_cmp(&b.1) {
+                Ordering::Equal => b.2.cmp(&a.2),
+                ordering => ordering,
+            })
+            .map(|(peer_id, _, _)| peer_id)
+    }
+
+    /// Contribution of the connected peers and of the disconnected peers whose contribution is not negligible yet
+    pub(crate) fn leaderboard(&self, now: Instant) -> HashMap<PeerId, PeerContribution> {
+        let protected = self.protected(now);
+        self.credits
+            .keys()
+            .chain(self.connected_since.keys())
+            .map(|peer_id| {
+                let credits = self
+                    .credits
+                    .get(peer_id)
+                    .map(|credits| credits.decayed(now, self.half_life));
+                let contribution = PeerContribution {
+                    blocks: credits.map_or(0.0, |credits| credits.blocks),
+                    endorsements: credits.map_or(0.0, |credits| credits.endorsements),
+                    score: credits.map_or(0.0, |credits| self.score(&credits)),
+                    connected: self.connected_since.contains_key(peer_id),
+                    protected: protected.contains(peer_id),
+                };
+                (*peer_id, contribution)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        time::{Duration, Instant},
+    };
+
+    use massa_protocol_exports::{PeerId, ProtocolConfig};
+    use massa_signature::KeyPair;
+    use massa_time::MassaTime;
+
+    use super::PeerContributions;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    fn contributions(protected_peers: usize) -> PeerContributions {
+        PeerContributions::new(&ProtocolConfig {
+            contribution_half_life: MassaTime::from_millis(60_000),
+            contribution_protected_peers: protected_peers,
+            endorsement_count: 16,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_contributions_decay() {
+        let mut contributions = contributions(1);
+        let peer = peer_id();
+        let now = Instant::now();
+        contributions.update_connected(&HashSet::from([peer]), now);
+        contributions.credit_block(peer, now);
+        for _ in 0..16 {
+            contributions.credit_endorsement(peer, now);
+        }
+        let contribution = &contributions.leaderboard(now)[&peer];
+        assert_eq!(contribution.blocks, 1.0);
+        assert_eq!(contribution.endorsements, 16.0);
+        assert_eq!(contribution.score, 2.0);
+        assert!(contribution.connected && contribution.protected);
+
+        // the contribution counts half after the half-life
+        let later = now + Duration::from_secs(60);
+        let contribution = &contributions.leaderboard(later)[&peer];
+        assert!((contribution.score - 1.0).abs() < 1e-9);
+
+        // a negligible contribution is forgotten once the peer is disconnected
+        let much_later = now + Duration::from_secs(60 * 60);
+        contributions.update_connected(&HashSet::new(), much_later);
+        assert!(contributions.leaderboard(much_later).is_empty());
+    }
+
+    #[test]
+    fn test_eviction_protects_top_contributors() {
+        let mut contributions = contributions(1);
+        let (top, low, idle, newcomer) = (peer_id(), peer_id(), peer_id(), peer_id());
+        let start = Instant::now();
+        contributions.update_connected(&HashSet::from([top, low, idle]), start);
+        let now = start + Duration::from_secs(120);
+        contributions.update_connected(&HashSet::from([top, low, idle, newcomer]), now);
+        contributions.credit_block(top, now);
+        contributions.credit_block(top, now);
+        contributions.credit_block(low, now);
+
+        let min_age = Duration::from_secs(60);
+        // the idle peer contributes the least, the newcomer is given time to contribute
+        assert_eq!(
+            contributions.eviction_candidate([top, low, idle, newcomer], min_age, now),
+            Some(idle)
+        );
+        assert_eq!(
+            contributions.eviction_candidate([top, low], min_age, now),
+            Some(low)
+        );
+        // the top contributor is never evicted
+        assert_eq!(contributions.eviction_candidate([top], min_age, now), None);
+    }
+}
@@ -1,14 +1,25 @@
-use std::{collections::HashMap, net::SocketAddr, time::Duration};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
 use massa_channel::{sender::MassaSender, MassaChannel};
 use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
+    heartbeat::SecureShareHeartbeat,
+    node_link::NodeAddressLinkProof,
     prehash::{PreHashMap, PreHashSet},
     stats::NetworkStats,
 };
-use massa_protocol_exports::{BootstrapPeers, PeerId, ProtocolController, ProtocolError};
+use massa_protocol_exports::{
+    BanEntry, BanTarget, BootstrapPeers, NetSplitAlert, NetworkTrafficStats, NodeKnowledge,
+    PeerAgent, PeerContribution, PeerId, PeerLatency, PeerRecord, PeerRecordEdit,
+    ProtocolController, ProtocolError,
+};
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use peernet::peer::PeerConnectionType;
 
 use crate::{
@@ -18,8 +29,14 @@ use crate::{
             commands_propagation::BlockHandlerPropagationCommand,
             commands_retrieval::BlockHandlerRetrievalCommand,
         },
-        endorsement_handler::commands_propagation::EndorsementHandlerPropagationCommand,
-        operation_handler::commands_propagation::OperationHandlerPropagationCommand,
+        endorsement_handler::{
+            commands_propagation::EndorsementHandlerPropagationCommand,
+            commands_retrieval::EndorsementHandlerRetrievalCommand,
+        },
+        operation_handler::{
+            commands_propagation::OperationHandlerPropagationCommand,
+            commands_retrieval::OperationHandlerRetrievalCommand,
+        },
         peer_handler::models::PeerManagementCmd,
     },
 };
@@ -34,7 +51,10 @@ pub struct ProtocolControllerImpl {
     pub sender_block_retrieval_handler: Option<MassaSender<BlockHandlerRetrievalCommand>>,
     pub sender_block_handler: Option<MassaSender<BlockHandlerPropagationCommand>>,
     pub sender_operation_handler: Option<MassaSender<OperationHandlerPropagationCommand>>,
+    pub sender_operation_retrieval_handler: Option<MassaSender<OperationHandlerRetrievalCommand>>,
     pub sender_endorsement_handler: Option<MassaSender<EndorsementHandlerPropagationCommand>>,
+    pub sender_endorsement_retrieval_handler:
+        Option<MassaSender<EndorsementHandlerRetrievalCommand>>,
     pub sender_connectivity_thread: Option<MassaSender<ConnectivityCommand>>,
     pub sender_peer_management_thread: Option<MassaSender<PeerManagementCmd>>,
 }
@@ -44,7 +64,9 @@ impl ProtocolControllerImpl {
         sender_block_retrieval_handler: MassaSender<BlockHandlerRetrievalCommand>,
         sender_block_handler: MassaSender<BlockHandlerPropagationCommand>,
         sender_operation_handler: MassaSender<OperationHandlerPropagationCommand>,
+        sender_operation_retrieval_handler: MassaSender<OperationHandlerRetrievalCommand>,
         sender_endorsement_handler: MassaSender<EndorsementHandlerPropagationCommand>,
+        sender_endorsement_retrieval_handler: MassaSender<EndorsementHandlerRetrievalCommand>,
         sender_connectivity_thread: MassaSender<ConnectivityCommand>,
         sender_peer_management_thread: MassaSender<PeerManagementCmd>,
     ) -> Self {
@@ -52,7 +74,9 @@ impl ProtocolControllerImpl {
             sender_block_retrieval_handler: Some(sender_block_retrieval_handler),
             sender_block_handler: Some(sender_block_handler),
             sender_operation_handler: Some(sender_operation_handler),
+            sender_operation_retrieval_handler: Some(sender_operation_retrieval_handler),
             sender_endorsement_handler: Some(sender_endorsement_handler),
+            sender_endorsement_retrieval_handler: Some(sender_endorsement_retrieval_handler),
             sender_connectivity_thread: Some(sender_connectivity_thread),
             sender_peer_management_thread: Some(sender_peer_management_thread),
         }
@@ -65,6 +89,8 @@ impl ProtocolController for ProtocolControllerImpl {
         drop(self.sender_operation_handler.take());
         drop(self.sender_endorsement_handler.take());
         drop(self.sender_block_retrieval_handler.take());
+        drop(self.sender_operation_retrieval_handler.take());
+        drop(self.sender_endorsement_retrieval_handler.take());
     }
 
     /// Sends the order to propagate the header of a block
@@ -108,6 +134,33 @@ impl ProtocolController for ProtocolControllerImpl {
             })
     }
 
+    /// Notify the operation and endorsement handlers of the lag of the node behind the network
+    fn notify_sync_lag(&self, lag: MassaTime) -> Result<(), ProtocolError> {
+        self.sender_operation_retrieval_handler
+            .as_ref()
+            .unwrap()
+            .try_send(OperationHandlerRetrievalCommand::SyncLag(lag))
+            .map_err(|_| {
+                ProtocolError::ChannelError("notify_sync_lag command send error".into())
+            })?;
+        self.sender_endorsement_retrieval_handler
+            .as_ref()
+            .unwrap()
+            .try_send(EndorsementHandlerRetrievalCommand::SyncLag(lag))
+            .map_err(|_| ProtocolError::ChannelError("notify_sync_lag command send error".into()))
+    }
+
+    /// Notify the connectivity thread of the blocks that became final, to credit the peers that first sent them
+    fn notify_final_blocks(&self, block_ids: Vec<BlockId>) -> Result<(), ProtocolError> {
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::FinalBlocks(block_ids))
+            .map_err(|_| {
+                ProtocolError::ChannelError("notify_final_blocks command send error".into())
+            })
+    }
+
     /// Propagate a batch of operation ids (from pool).
     ///
     /// note: Full `OperationId` is replaced by a `OperationPrefixId` later by the worker.
@@ -156,6 +209,104 @@ impl ProtocolController for ProtocolControllerImpl {
             .map_err(|_| ProtocolError::ChannelError("get_stats command receive error".into()))
     }
 
+    fn get_peer_agents(&self) -> Result<HashMap<PeerId, PeerAgent>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peer_agents".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetPeerAgents { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peer_agents command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peer_agents command receive error".into())
+        })
+    }
+
+    fn get_peer_latencies(&self) -> Result<HashMap<PeerId, PeerLatency>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peer_latencies".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetPeerLatencies { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peer_latencies command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peer_latencies command receive error".into())
+        })
+    }
+
+    fn get_network_stats(&self) -> Result<NetworkTrafficStats, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_network_stats".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetNetworkStats { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_network_stats command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_network_stats command receive error".into())
+        })
+    }
+
+    fn get_known_blocks_sizes(&self) -> Result<HashMap<PeerId, usize>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_known_blocks_sizes".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetKnownBlocksSizes { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_known_blocks_sizes command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_known_blocks_sizes command receive error".into())
+        })
+    }
+
+    fn get_node_knowledge(&self) -> Result<HashMap<PeerId, NodeKnowledge>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_node_knowledge".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetNodeKnowledge { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_node_knowledge command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_node_knowledge command receive error".into())
+        })
+    }
+
+    fn get_peer_contributions(&self) -> Result<HashMap<PeerId, PeerContribution>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peer_contributions".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetPeerContributions { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peer_contributions command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peer_contributions command receive error".into())
+        })
+    }
+
+    fn get_net_split_alert(&self) -> Result<Option<NetSplitAlert>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_net_split_alert".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetNetSplitAlert { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_net_split_alert command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_net_split_alert command receive error".into())
+        })
+    }
+
     fn ban_peers(&self, peer_ids: Vec<PeerId>) -> Result<(), ProtocolError> {
         self.sender_peer_management_thread
             .as_ref()
@@ -172,6 +323,111 @@ impl ProtocolController for ProtocolControllerImpl {
             .map_err(|_| ProtocolError::ChannelError("unban_peers command send error".into()))
     }
 
+    fn add_bans(&self, bans: Vec<BanEntry>) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::AddBans(bans))
+            .map_err(|_| ProtocolError::ChannelError("add_bans command send error".into()))
+    }
+
+    fn remove_bans(&self, targets: Vec<BanTarget>) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::RemoveBans(targets))
+            .map_err(|_| ProtocolError::ChannelError("remove_bans command send error".into()))
+    }
+
+    fn get_bans(&self) -> Result<Vec<BanEntry>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_bans".to_string(), Some(1));
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::GetBans { responder: sender })
+            .map_err(|_| ProtocolError::ChannelError("get_bans command send error".into()))?;
+        receiver
+            .recv_timeout(Duration::from_secs(10))
+            .map_err(|_| ProtocolError::ChannelError("get_bans command receive error".into()))
+    }
+
+    fn add_to_peers_whitelist(&self, ips: Vec<IpAddr>) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::AddToWhitelist(ips))
+            .map_err(|_| {
+                ProtocolError::ChannelError("add_to_peers_whitelist command send error".into())
+            })
+    }
+
+    fn remove_from_peers_whitelist(&self, ips: Vec<IpAddr>) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::RemoveFromWhitelist(ips))
+            .map_err(|_| {
+                ProtocolError::ChannelError("remove_from_peers_whitelist command send error".into())
+            })
+    }
+
+    fn get_peers_whitelist(&self) -> Result<Vec<IpAddr>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peers_whitelist".to_string(), Some(1));
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::GetWhitelist { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peers_whitelist command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peers_whitelist command receive error".into())
+        })
+    }
+
+    fn reload_ip_lists(&self) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::ReloadIpLists)
+            .map_err(|_| ProtocolError::ChannelError("reload_ip_lists command send error".into()))
+    }
+
+    fn get_peer_records(&self, peer_ids: Vec<PeerId>) -> Result<Vec<PeerRecord>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peer_records".to_string(), Some(1));
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::GetPeerRecords {
+                peer_ids,
+                responder: sender,
+            })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peer_records command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peer_records command receive error".into())
+        })
+    }
+
+    fn edit_peer_records(&self, edits: Vec<PeerRecordEdit>) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::EditPeerRecords(edits))
+            .map_err(|_| ProtocolError::ChannelError("edit_peer_records command send error".into()))
+    }
+
+    fn remove_peer_records(&self, peer_ids: Vec<PeerId>) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::RemovePeerRecords(peer_ids))
+            .map_err(|_| {
+                ProtocolError::ChannelError("remove_peer_records command send error".into())
+            })
+    }
+
     fn get_bootstrap_peers(&self) -> Result<BootstrapPeers, ProtocolError> {
         let (sender, receiver) = MassaChannel::new("get_bootstrap_peers".to_string(), Some(1));
         self.sender_peer_management_thread
@@ -186,6 +442,62 @@ impl ProtocolController for ProtocolControllerImpl {
         })
     }
 
+    fn propagate_heartbeats(
+        &self,
+        heartbeats: Vec<SecureShareHeartbeat>,
+    ) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::PropagateHeartbeats(heartbeats))
+            .map_err(|_| {
+                ProtocolError::ChannelError("propagate_heartbeats command send error".into())
+            })
+    }
+
+    fn get_staker_heartbeats(&self) -> Result<Vec<SecureShareHeartbeat>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_staker_heartbeats".to_string(), Some(1));
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::GetHeartbeats { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_staker_heartbeats command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_staker_heartbeats command receive error".into())
+        })
+    }
+
+    fn propagate_node_address_links(
+        &self,
+        links: Vec<NodeAddressLinkProof>,
+    ) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::PropagateNodeAddressLinks(links))
+            .map_err(|_| {
+                ProtocolError::ChannelError(
+                    "propagate_node_address_links command send error".into(),
+                )
+            })
+    }
+
+    fn get_node_address_links(&self) -> Result<Vec<NodeAddressLinkProof>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_node_address_links".to_string(), Some(1));
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::GetNodeAddressLinks { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_node_address_links command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_node_address_links command receive error".into())
+        })
+    }
+
     fn clone_box(&self) -> Box<dyn ProtocolController> {
         Box::new(self.clone())
     }
@@ -0,0 +1,240 @@
+//! Outbound connection attempts.
+//!
+//! Host names are resolved in a dedicated thread so that a slow DNS server cannot stall the caller
+//! beyond the resolution timeout. Dual-stack targets are dialed happy-eyeballs style (RFC 8305):
+//! addresses are tried one at a time, alternating between address families,
+//! each attempt being bounded by the attempt timeout and the whole dial by the dial timeout.
+//! When a SOCKS5 proxy is configured, the host names are resolved and the connections opened through it.
+
+use std::io;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use massa_protocol_exports::{ProtocolConfig, ProtocolError, Socks5Proxy};
+
+// protocol-dns
+const DNS_THREAD_NAME: &str = "p-dns";
+static_assertions::const_assert!(DNS_THREAD_NAME.len() < 16);
+
+#[derive(Debug, Clone)]
+pub(crate) struct Dialer {
+    /// timeout of a host name resolution
+    dns_timeout: Duration,
+    /// timeout of a connection attempt to one address
+    attempt_timeout: Duration,
+    /// timeout of the connection to a target, over all its addresses
+    dial_timeout: Duration,
+    /// whether IPv6 addresses are tried first
+    prefer_ipv6: bool,
+    /// proxy through which the host names are resolved and the connections opened
+    socks5_proxy: Option<Socks5Proxy>,
+}
+
+impl Dialer {
+    pub(crate) fn new(config: &ProtocolConfig) -> Self {
+        Dialer {
+            dns_timeout: config.dns_timeout.to_duration(),
+            attempt_timeout: config.timeout_connection.to_duration(),
+            dial_timeout: config.dial_timeout.to_duration(),
+            prefer_ipv6: config.prefer_ipv6,
+            socks5_proxy: config.socks5_proxy.map(Socks5Proxy::new),
+        }
+    }
+
+    /// Opens a TCP connection to `addr`, through the proxy if any
+    pub(crate) fn connect(&self, addr: SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+        match &self.socks5_proxy {
+            Some(proxy) => proxy.connect(addr, timeout),
+            None => TcpStream::connect_timeout(&addr, timeout),
+        }
+    }
+
+    /// Resolves a `host:port` target, giving up after the DNS timeout
+    pub(crate) fn resolve(&self, target: &str) -> Result<Vec<SocketAddr>, ProtocolError> {
+        if let Ok(addr) = target.parse::<SocketAddr>() {
+            return Ok(vec![addr]);
+        }
+        let (sender, receiver) = crossbeam::channel::bounded(1);
+        let host = target.to_string();
+        let socks5_proxy = self.socks5_proxy;
+        let dns_timeout = self.dns_timeout;
+        std::thread::Builder::new()
+            .name(DNS_THREAD_NAME.to_string())
+            .spawn(move || {
+                let addrs = match socks5_proxy {
+                    // no DNS request must leave the node: the proxy resolves the host name
+                    Some(proxy) => resolve_through_proxy(&proxy, &host, dns_timeout),
+                    None => host
+                        .to_socket_addrs()
+                        .map(|addrs| addrs.collect::<Vec<_>>()),
+                };
+                // the receiver may be gone after a timeout
+                let _ = sender.send(addrs);
+            })?;
+        match receiver.recv_timeout(self.dns_timeout) {
+            Ok(Ok(addrs)) if !addrs.is_empty() => Ok(addrs),
+            Ok(Ok(_)) => Err(ProtocolError::DnsResolutionError(format!(
+                "no address found for {}",
+                target
+            ))),
+            Ok(Err(err)) => Err(ProtocolError::DnsResolutionError(format!(
+                "{}: {}",
+                target, err
+            ))),
+            Err(_) => Err(ProtocolError::DnsResolutionError(format!(
+                "{}: timed out after {:?}",
+                target, self.dns_timeout
+            ))),
+        }
+    }
+
+    /// Orders the addresses of a target: families alternate, starting with the preferred one
+    pub(crate) fn order_addresses(&self, addrs: &[SocketAddr]) -> Vec<SocketAddr> {
+        let mut unique: Vec<SocketAddr> = Vec::with_capacity(addrs.len());
+        for addr in addrs {
+            if !unique.contains(addr) {
+                unique.push(*addr);
+            }
+        }
+        let (mut preferred, mut other): (Vec<_>, Vec<_>) = unique
+            .into_iter()
+            .partition(|addr| addr.is_ipv6() == self.prefer_ipv6);
+        if preferred.is_empty() {
+            std::mem::swap(&mut preferred, &mut other);
+        }
+        let mut ordered = Vec::with_capacity(preferred.len() + other.len());
+        let mut preferred = preferred.into_iter();
+        let mut other = other.into_iter();
+        loop {
+            match (preferred.next(), other.next()) {
+                (None, None) => break,
+                (first, second) => ordered.extend(first.into_iter().chain(second)),
+            }
+        }
+        ordered
+    }
+
+    /// Dials the addresses of a target in happy-eyeballs order until one connection succeeds.
+    /// `connect` attempts a connection to an address within the given timeout.
+    /// Returns the address to which the connection succeeded.
+    pub(crate) fn dial(
+        &self,
+        addrs: &[SocketAddr],
+        mut connect: impl FnMut(SocketAddr, Duration) -> Result<(), ProtocolError>,
+    ) -> Result<SocketAddr, ProtocolError> {
+        let deadline = Instant::now() + self.dial_timeout;
+        let mut last_error = None;
+        for addr in self.order_addresses(addrs) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(ProtocolError::DialTimeout(format!(
+                    "{:?} after {:?}",
+                    addrs, self.dial_timeout
+                )));
+            }
+            match connect(addr, self.attempt_timeout.min(remaining)) {
+                Ok(()) => return Ok(addr),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            ProtocolError::GeneralProtocolError("no address to dial".to_string())
+        }))
+    }
+}
+
+/// Resolves a `host:port` target through a SOCKS5 proxy
+fn resolve_through_proxy(
+    proxy: &Socks5Proxy,
+    target: &str,
+    timeout: Duration,
+) -> io::Result<Vec<SocketAddr>> {
+    let (host, port) = target
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid host:port target"))?;
+    let ip = proxy.resolve(host, timeout)?;
+    Ok(vec![SocketAddr::new(ip, port)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dialer(prefer_ipv6: bool, dial_timeout: Duration) -> Dialer {
+        Dialer {
+            dns_timeout: Duration::from_secs(1),
+            attempt_timeout: Duration::from_millis(100),
+            dial_timeout,
+            prefer_ipv6,
+            socks5_proxy: None,
+        }
+    }
+
+    #[test]
+    fn test_order_addresses() {
+        let v4_1: SocketAddr = "1.1.1.1:31244".parse().unwrap();
+        let v4_2: SocketAddr = "2.2.2.2:31244".parse().unwrap();
+        let v4_3: SocketAddr = "3.3.3.3:31244".parse().unwrap();
+        let v6_1: SocketAddr = "[2001:db8::1]:31244".parse().unwrap();
+        let v6_2: SocketAddr = "[2001:db8::2]:31244".parse().unwrap();
+        let addrs = [v4_1, v4_2, v6_1, v4_1, v4_3, v6_2];
+
+        assert_eq!(
+            dialer(true, Duration::from_secs(1)).order_addresses(&addrs),
+            vec![v6_1, v4_1, v6_2, v4_2, v4_3]
+        );
+        assert_eq!(
+            dialer(false, Duration::from_secs(1)).order_addresses(&addrs),
+            vec![v4_1, v6_1, v4_2, v6_2, v4_3]
+        );
+        // single-stack targets keep their order
+        assert_eq!(
+            dialer(true, Duration::from_secs(1)).order_addresses(&[v4_2, v4_1]),
+            vec![v4_2, v4_1]
+        );
+    }
+
+    #[test]
+    fn test_dial_falls_back_and_times_out() {
+        let v4: SocketAddr = "1.1.1.1:31244".parse().unwrap();
+        let v6: SocketAddr = "[2001:db8::1]:31244".parse().unwrap();
+
+        // the IPv6 attempt fails, the IPv4 one succeeds
+        let mut attempts = Vec::new();
+        let connected = dialer(true, Duration::from_secs(1)).dial(&[v4, v6], |addr, timeout| {
+            attempts.push(addr);
+            assert!(timeout <= Duration::from_millis(100));
+            if addr.is_ipv6() {
+                Err(ProtocolError::GeneralProtocolError("unreachable".into()))
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(connected.unwrap(), v4);
+        assert_eq!(attempts, vec![v6, v4]);
+
+        // the dial timeout stops the attempts
+        let mut attempts = 0;
+        let result = dialer(true, Duration::from_millis(20)).dial(&[v4, v6], |_, timeout| {
+            attempts += 1;
+            std::thread::sleep(timeout);
+            Err(ProtocolError::GeneralProtocolError("timeout".into()))
+        });
+        assert!(matches!(result, Err(ProtocolError::DialTimeout(_))));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_resolve_ip_target() {
+        let dialer = dialer(true, Duration::from_secs(1));
+        assert_eq!(
+            dialer.resolve("127.0.0.1:31244").unwrap(),
+            vec!["127.0.0.1:31244".parse::<SocketAddr>().unwrap()]
+        );
+        assert!(matches!(
+            dialer.resolve("not a target"),
+            Err(ProtocolError::DnsResolutionError(_))
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+//! Structural checks of the body of a block, run by the block retrieval thread before the block is
+//! handed to consensus.
+//!
+//! The operation list of a block must hash to the operation merkle root of its header and hold no more
+//! operations than allowed by the chain parameters in force at the slot of the block, and every
+//! operation of the block must belong to the thread of the block and use an operation format active
+//! at the slot of the block. A block failing these checks is
+//! rejected as soon as the inconsistent part is received, and the sender is penalized, instead of
+//! leaving the detection to consensus.
+
+use std::fmt::Display;
+
+use massa_models::{
+    block_header::SecuredHeader,
+    config::ChainParametersSchedule,
+    operation::{
+        compute_operations_hash, OperationId, OperationIdSerializer, SecureShareOperation,
+    },
+};
+use massa_time::MassaTime;
+use massa_versioning::versioning::MipStore;
+
+/// Reason for rejecting the body of a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BlockBodyError {
+    /// the hash of the operation list does not match the operation merkle root of the header
+    OperationMerkleRootMismatch,
+    /// the block holds more operations than allowed at its slot
+    TooManyOperations { count: usize, max: u32 },
+    /// an operation of the block belongs to another thread than the block
+    OperationWrongThread {
+        operation_id: OperationId,
+        operation_thread: u8,
+        block_thread: u8,
+    },
+    /// an operation of the block uses a format whose version is not active at the slot of the block
+    OperationFormatInactive {
+        operation_id: OperationId,
+        format_version: u32,
+    },
+}
+
+impl BlockBodyError {
+    /// Reason code, used as metric label
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            BlockBodyError::OperationMerkleRootMismatch => "operation_merkle_root_mismatch",
+            BlockBodyError::TooManyOperations { .. } => "too_many_operations",
+            BlockBodyError::OperationWrongThread { .. } => "operation_wrong_thread",
+            BlockBodyError::OperationFormatInactive { .. } => "operation_format_inactive",
+        }
+    }
+}
+
+impl Display for BlockBodyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockBodyError::OperationMerkleRootMismatch => write!(
+                f,
+                "the operation list does not match the operation merkle root of the header"
+            ),
+            BlockBodyError::TooManyOperations { count, max } => write!(
+                f,
+                "the block holds {} operations but at most {} are allowed at its slot",
+                count, max
+            ),
+            BlockBodyError::OperationWrongThread {
+                operation_id,
+                operation_thread,
+                block_thread,
+            } => write!(
+                f,
+                "operation {} belongs to thread {} but the block is in thread {}",
+                operation_id, operation_thread, block_thread
+            ),
+            BlockBodyError::OperationFormatInactive {
+                operation_id,
+                format_version,
+            } => write!(
+                f,
+                "operation {} uses version {} of the operation format, not active at the slot of the block",
+                operation_id, format_version
+            ),
+        }
+    }
+}
+
+/// Checks that the operation list of a block matches the operation merkle root of its header
+pub(crate) fn check_operation_list(
+    header: &SecuredHeader,
+    operation_ids: &[OperationId],
+    operation_id_serializer: &OperationIdSerializer,
+) -> Result<(), BlockBodyError> {
+    if compute_operations_hash(operation_ids, operation_id_serializer)
+        != header.content.operation_merkle_root
+    {
+        return Err(BlockBodyError::OperationMerkleRootMismatch);
+    }
+    Ok(())
+}
+
+/// Checks that a block holds no more operations than allowed by the chain parameters in force at its slot
+pub(crate) fn check_operation_count(
+    header: &SecuredHeader,
+    operation_count: usize,
+    chain_parameters: &ChainParametersSchedule,
+) -> Result<(), BlockBodyError> {
+    let max = chain_parameters
+        .parameters_at(header.content.slot)
+        .max_operations_per_block;
+    if operation_count > max as usize {
+        return Err(BlockBodyError::TooManyOperations {
+            count: operation_count,
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that the operations of a block belong to the thread of the block
+pub(crate) fn check_operation_threads<'a>(
+    header: &SecuredHeader,
+    operations: impl IntoIterator<Item = &'a SecureShareOperation>,
+    thread_count: u8,
+) -> Result<(), BlockBodyError> {
+    let block_thread = header.content.slot.thread;
+    for operation in operations {
+        let operation_thread = operation.content_creator_address.get_thread(thread_count);
+        if operation_thread != block_thread {
+            return Err(BlockBodyError::OperationWrongThread {
+                operation_id: operation.id,
+                operation_thread,
+                block_thread,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the operations of a block use operation formats active at the timestamp of the slot of the block
+pub(crate) fn check_operation_formats<'a>(
+    operations: impl IntoIterator<Item = &'a SecureShareOperation>,
+    mip_store: &MipStore,
+    slot_timestamp: MassaTime,
+) -> Result<(), BlockBodyError> {
+    for operation in operations {
+        if !mip_store.is_operation_format_active_at(&operation.content, slot_timestamp) {
+            return Err(BlockBodyError::OperationFormatInactive {
+                operation_id: operation.id,
+                format_version: operation.content.get_format_version(),
+            });
+        }
+    }
+    Ok(())
+}
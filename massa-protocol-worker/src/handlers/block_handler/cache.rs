@@ -1,7 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use massa_models::{block_header::SecuredHeader, block_id::BlockId};
@@ -9,14 +9,20 @@ use massa_protocol_exports::PeerId;
 use parking_lot::RwLock;
 use schnellru::{ByLength, LruMap};
 
+use crate::handlers::peer_knowledge::RetainedPeerKnowledge;
+
 /// Cache on block knowledge by our node and its peers
 pub struct BlockCache {
     /// cache of previously checked headers
     pub checked_headers: LruMap<BlockId, SecuredHeader>,
+    /// peer that first sent us each checked header, to credit it once the block is final
+    pub first_senders: LruMap<BlockId, PeerId>,
     /// cache of blocks known by peers
     pub blocks_known_by_peer: HashMap<PeerId, LruMap<BlockId, (bool, Instant)>>,
     /// max number of blocks known in peer knowledge cache
     pub max_known_blocks_by_peer: u32,
+    /// blocks known by the recently disconnected peers
+    retained_knowledge: RetainedPeerKnowledge<LruMap<BlockId, (bool, Instant)>>,
 }
 
 impl BlockCache {
@@ -46,18 +52,27 @@ impl BlockCache {
 }
 
 impl BlockCache {
-    pub fn new(max_known_blocks: u32, max_known_blocks_by_peer: u32) -> Self {
+    pub fn new(
+        max_known_blocks: u32,
+        max_known_blocks_by_peer: u32,
+        peer_knowledge_retention: Duration,
+    ) -> Self {
         Self {
             checked_headers: LruMap::new(ByLength::new(max_known_blocks)),
+            first_senders: LruMap::new(ByLength::new(max_known_blocks)),
             blocks_known_by_peer: HashMap::new(),
             max_known_blocks_by_peer,
+            retained_knowledge: RetainedPeerKnowledge::new(peer_knowledge_retention),
         }
     }
 
     pub fn update_cache(&mut self, peers_connected: &HashSet<PeerId>) {
-        // Remove disconnected peers from cache
-        self.blocks_known_by_peer
-            .retain(|peer_id, _| peers_connected.contains(peer_id));
+        // Remove disconnected peers from cache, restore the knowledge of the peers that reconnected quickly
+        self.retained_knowledge.update(
+            &mut self.blocks_known_by_peer,
+            peers_connected,
+            Instant::now(),
+        );
 
         // Add new connected peers to cache
         for peer_id in peers_connected {
@@ -69,6 +84,29 @@ impl BlockCache {
             }
         }
     }
+
+    /// Approximate memory used by the cache, in bytes
+    pub fn memory_usage(&self) -> usize {
+        let checked_headers = self.checked_headers.memory_usage()
+            + self
+                .checked_headers
+                .iter()
+                .map(|(_, header)| header.serialized_size())
+                .sum::<usize>();
+        let known_by_peer = self.blocks_known_by_peer.capacity()
+            * std::mem::size_of::<(PeerId, LruMap<BlockId, (bool, Instant)>)>()
+            + self
+                .blocks_known_by_peer
+                .values()
+                .map(|known_blocks| known_blocks.memory_usage())
+                .sum::<usize>();
+        checked_headers
+            + self.first_senders.memory_usage()
+            + known_by_peer
+            + self
+                .retained_knowledge
+                .memory_usage(|known_blocks| known_blocks.memory_usage())
+    }
 }
 
 pub type SharedBlockCache = Arc<RwLock<BlockCache>>;
@@ -1,8 +1,13 @@
+use std::collections::HashMap;
+
+use massa_channel::sender::MassaSender;
 use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
     prehash::{PreHashMap, PreHashSet},
 };
+use massa_protocol_exports::PeerId;
+use massa_time::MassaTime;
 
 #[derive(Clone)]
 pub enum BlockHandlerRetrievalCommand {
@@ -14,4 +19,8 @@ pub enum BlockHandlerRetrievalCommand {
         /// remove from wish list
         remove: PreHashSet<BlockId>,
     },
+    /// Get the blocks being asked to each peer, with the time elapsed since the ask
+    GetAskedBlocks {
+        responder: MassaSender<HashMap<PeerId, Vec<(BlockId, MassaTime)>>>,
+    },
 }
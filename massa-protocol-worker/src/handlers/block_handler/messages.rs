@@ -14,6 +14,8 @@ use nom::{
     IResult, Parser,
 };
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::handlers::compression::{serialize_compressed, CompressedDeserializer};
 use std::ops::Bound::Included;
 
 /// Request block data
@@ -26,6 +28,8 @@ pub enum AskForBlockInfo {
     OperationIds,
     /// Ask for a subset of operations of the block
     Operations(Vec<OperationId>),
+    /// Ask for a subset of operations of the block, sent compressed
+    CompressedOperations(Vec<OperationId>),
 }
 
 /// Reply to a block data request
@@ -38,6 +42,8 @@ pub enum BlockInfoReply {
     OperationIds(Vec<OperationId>),
     /// Requested full operations of the block
     Operations(Vec<SecureShareOperation>),
+    /// Requested full operations of the block, compressed on the wire
+    CompressedOperations(Vec<SecureShareOperation>),
     /// Block not found
     NotFound,
 }
@@ -52,6 +58,9 @@ pub enum BlockMessage {
     DataRequest {
         /// ID of the block to ask info for.
         block_id: BlockId,
+        /// Sequence number of the request, increasing for each request sent on the connection.
+        /// Only sent to the peers announcing `PeerFeatures::SEQUENCED_BLOCK_DATA`.
+        sequence: Option<u64>,
         /// Block info to ask for.
         block_info: AskForBlockInfo,
     },
@@ -59,6 +68,8 @@ pub enum BlockMessage {
     DataResponse {
         /// ID of the block to reply info for.
         block_id: BlockId,
+        /// Sequence number of the request this message replies to, if the request had one.
+        sequence: Option<u64>,
         /// Block info reply.
         block_info: BlockInfoReply,
     },
@@ -70,14 +81,24 @@ pub enum MessageTypeId {
     Header,
     DataRequest,
     DataResponse,
+    /// `DataRequest` carrying a sequence number
+    SequencedDataRequest,
+    /// `DataResponse` carrying a sequence number
+    SequencedDataResponse,
 }
 
 impl From<&BlockMessage> for MessageTypeId {
     fn from(value: &BlockMessage) -> Self {
         match value {
             BlockMessage::Header(_) => MessageTypeId::Header,
-            BlockMessage::DataRequest { .. } => MessageTypeId::DataRequest,
-            BlockMessage::DataResponse { .. } => MessageTypeId::DataResponse,
+            BlockMessage::DataRequest { sequence: None, .. } => MessageTypeId::DataRequest,
+            BlockMessage::DataRequest {
+                sequence: Some(_), ..
+            } => MessageTypeId::SequencedDataRequest,
+            BlockMessage::DataResponse { sequence: None, .. } => MessageTypeId::DataResponse,
+            BlockMessage::DataResponse {
+                sequence: Some(_), ..
+            } => MessageTypeId::SequencedDataResponse,
         }
     }
 }
@@ -89,6 +110,7 @@ pub enum BlockInfoType {
     OperationIds = 1,
     Operations = 2,
     NotFound = 3,
+    CompressedOperations = 4,
 }
 
 #[derive(Default, Clone)]
@@ -97,16 +119,34 @@ pub struct BlockMessageSerializer {
     secure_share_serializer: SecureShareSerializer,
     length_serializer: U64VarIntSerializer,
     block_id_serializer: BlockIdSerializer,
+    sequence_serializer: U64VarIntSerializer,
     operation_id_serializer: OperationIdSerializer,
 }
 
 impl BlockMessageSerializer {
+    /// Serializes operations as in a `BlockInfoReply::Operations` and compresses them.
+    fn serialize_compressed_operations(
+        &self,
+        operations: &[SecureShareOperation],
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), massa_serialization::SerializeError> {
+        let mut raw = Vec::new();
+        self.length_serializer
+            .serialize(&(operations.len() as u64), &mut raw)?;
+        for operation in operations {
+            self.secure_share_serializer
+                .serialize(operation, &mut raw)?;
+        }
+        serialize_compressed(&raw, buffer)
+    }
+
     pub fn new() -> Self {
         Self {
             id_serializer: U64VarIntSerializer::new(),
             secure_share_serializer: SecureShareSerializer::new(),
             length_serializer: U64VarIntSerializer::new(),
             block_id_serializer: BlockIdSerializer::new(),
+            sequence_serializer: U64VarIntSerializer::new(),
             operation_id_serializer: OperationIdSerializer::new(),
         }
     }
@@ -126,9 +166,13 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
             }
             BlockMessage::DataRequest {
                 block_id,
+                sequence,
                 block_info,
             } => {
                 self.block_id_serializer.serialize(block_id, buffer)?;
+                if let Some(sequence) = sequence {
+                    self.sequence_serializer.serialize(sequence, buffer)?;
+                }
                 match block_info {
                     AskForBlockInfo::Header => {
                         self.id_serializer
@@ -138,9 +182,15 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
                         self.id_serializer
                             .serialize(&(BlockInfoType::OperationIds as u64), buffer)?;
                     }
-                    AskForBlockInfo::Operations(operations_ids) => {
-                        self.id_serializer
-                            .serialize(&(BlockInfoType::Operations as u64), buffer)?;
+                    AskForBlockInfo::Operations(operations_ids)
+                    | AskForBlockInfo::CompressedOperations(operations_ids) => {
+                        let info_type =
+                            if matches!(block_info, AskForBlockInfo::CompressedOperations(_)) {
+                                BlockInfoType::CompressedOperations
+                            } else {
+                                BlockInfoType::Operations
+                            };
+                        self.id_serializer.serialize(&(info_type as u64), buffer)?;
                         self.length_serializer
                             .serialize(&(operations_ids.len() as u64), buffer)?;
                         for operation_id in operations_ids {
@@ -152,9 +202,13 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
             }
             BlockMessage::DataResponse {
                 block_id,
+                sequence,
                 block_info,
             } => {
                 self.block_id_serializer.serialize(block_id, buffer)?;
+                if let Some(sequence) = sequence {
+                    self.sequence_serializer.serialize(sequence, buffer)?;
+                }
                 match block_info {
                     BlockInfoReply::Header(header) => {
                         self.id_serializer
@@ -180,6 +234,11 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
                             self.secure_share_serializer.serialize(operation, buffer)?;
                         }
                     }
+                    BlockInfoReply::CompressedOperations(operations) => {
+                        self.id_serializer
+                            .serialize(&(BlockInfoType::CompressedOperations as u64), buffer)?;
+                        self.serialize_compressed_operations(operations, buffer)?;
+                    }
                     BlockInfoReply::NotFound => {
                         self.id_serializer
                             .serialize(&(BlockInfoType::NotFound as u64), buffer)?;
@@ -195,8 +254,10 @@ pub struct BlockMessageDeserializer {
     id_deserializer: U64VarIntDeserializer,
     block_header_deserializer: SecureShareDeserializer<BlockHeader, BlockHeaderDeserializer>,
     block_id_deserializer: BlockIdDeserializer,
+    sequence_deserializer: U64VarIntDeserializer,
     operation_ids_deserializer: OperationIdsDeserializer,
     operations_deserializer: OperationsDeserializer,
+    compressed_deserializer: CompressedDeserializer,
 }
 
 pub struct BlockMessageDeserializerArgs {
@@ -212,6 +273,8 @@ pub struct BlockMessageDeserializerArgs {
     pub max_denunciations_in_block_header: u32,
     pub last_start_period: Option<u64>,
     pub chain_id: u64,
+    /// maximum size of the decompressed operations of a block info reply
+    pub max_decompressed_operations_size: u64,
 }
 
 impl BlockMessageDeserializer {
@@ -229,6 +292,7 @@ impl BlockMessageDeserializer {
                 args.chain_id,
             ),
             block_id_deserializer: BlockIdDeserializer::new(),
+            sequence_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
             operation_ids_deserializer: OperationIdsDeserializer::new(
                 args.max_operations_per_block,
             ),
@@ -242,8 +306,20 @@ impl BlockMessageDeserializer {
                 args.max_op_datastore_value_length,
                 args.chain_id,
             ),
+            compressed_deserializer: CompressedDeserializer::new(
+                args.max_decompressed_operations_size,
+            ),
         }
     }
+
+    /// Reads operations written by `BlockMessageSerializer::serialize_compressed_operations`.
+    fn deserialize_compressed_operations<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Vec<SecureShareOperation>, E> {
+        self.compressed_deserializer
+            .deserialize(&self.operations_deserializer, buffer)
+    }
 }
 
 impl Deserializer<BlockMessage> for BlockMessageDeserializer {
@@ -259,18 +335,30 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                     nom::error::ErrorKind::Eof,
                 ))
             })?;
+            let sequenced = matches!(
+                id,
+                MessageTypeId::SequencedDataRequest | MessageTypeId::SequencedDataResponse
+            );
             match id {
                 MessageTypeId::Header => context("Failed BlockHeader deserialization", |input| {
                     self.block_header_deserializer.deserialize(input)
                 })
                 .map(BlockMessage::Header)
                 .parse(buffer),
-                MessageTypeId::DataRequest => context(
+                MessageTypeId::DataRequest | MessageTypeId::SequencedDataRequest => context(
                     "Failed BlockDataRequest deserialization",
                     tuple((
                         context("Failed BlockId deserialization", |input| {
                             self.block_id_deserializer.deserialize(input)
                         }),
+                        context("Failed sequence deserialization", |input| {
+                            if !sequenced {
+                                return Ok((input, None));
+                            }
+                            self.sequence_deserializer
+                                .deserialize(input)
+                                .map(|(rest, sequence)| (rest, Some(sequence)))
+                        }),
                         context("Failed infos deserialization", |input| {
                             let (rest, raw_id) = self.id_deserializer.deserialize(input)?;
                             let info_type: BlockInfoType = raw_id.try_into().map_err(|_| {
@@ -290,6 +378,12 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                                     .map(|(rest, operation_ids)| {
                                         (rest, AskForBlockInfo::Operations(operation_ids))
                                     }),
+                                BlockInfoType::CompressedOperations => self
+                                    .operation_ids_deserializer
+                                    .deserialize(rest)
+                                    .map(|(rest, operation_ids)| {
+                                        (rest, AskForBlockInfo::CompressedOperations(operation_ids))
+                                    }),
                                 BlockInfoType::NotFound => {
                                     Err(nom::Err::Error(ParseError::from_error_kind(
                                         buffer,
@@ -300,17 +394,28 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                         }),
                     )),
                 )
-                .map(|(block_id, block_info)| BlockMessage::DataRequest {
-                    block_id,
-                    block_info,
-                })
+                .map(
+                    |(block_id, sequence, block_info)| BlockMessage::DataRequest {
+                        block_id,
+                        sequence,
+                        block_info,
+                    },
+                )
                 .parse(buffer),
-                MessageTypeId::DataResponse => context(
+                MessageTypeId::DataResponse | MessageTypeId::SequencedDataResponse => context(
                     "Failed BlockDataResponse deserialization",
                     tuple((
                         context("Failed BlockId deserialization", |input| {
                             self.block_id_deserializer.deserialize(input)
                         }),
+                        context("Failed sequence deserialization", |input| {
+                            if !sequenced {
+                                return Ok((input, None));
+                            }
+                            self.sequence_deserializer
+                                .deserialize(input)
+                                .map(|(rest, sequence)| (rest, Some(sequence)))
+                        }),
                         context("Failed infos deserialization", |input| {
                             let (rest, raw_id) = self.id_deserializer.deserialize(input)?;
                             let info_type: BlockInfoType = raw_id.try_into().map_err(|_| {
@@ -337,14 +442,22 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                                         (rest, BlockInfoReply::Operations(operations))
                                     }),
                                 BlockInfoType::NotFound => Ok((rest, BlockInfoReply::NotFound)),
+                                BlockInfoType::CompressedOperations => self
+                                    .deserialize_compressed_operations(rest)
+                                    .map(|(rest, operations)| {
+                                        (rest, BlockInfoReply::CompressedOperations(operations))
+                                    }),
                             }
                         }),
                     )),
                 )
-                .map(|(block_id, block_info)| BlockMessage::DataResponse {
-                    block_id,
-                    block_info,
-                })
+                .map(
+                    |(block_id, sequence, block_info)| BlockMessage::DataResponse {
+                        block_id,
+                        sequence,
+                        block_info,
+                    },
+                )
                 .parse(buffer),
             }
         })
@@ -365,6 +478,7 @@ mod tests {
         let message = super::BlockMessage::DataRequest {
             block_id: BlockId::from_str("B12DvrcQkzF1Wi8BVoNfc4n93CD3E2qhCNe7nVhnEQGWHZ24fEmg")
                 .unwrap(),
+            sequence: Some(7),
             block_info: super::AskForBlockInfo::Operations(vec![]),
         };
         let mut buffer = Vec::new();
@@ -384,6 +498,7 @@ mod tests {
                 max_denunciations_in_block_header: 1,
                 last_start_period: None,
                 chain_id: *CHAINID,
+                max_decompressed_operations_size: 1,
             });
         let (rest, deserialized_message) = deserializer
             .deserialize::<DeserializeError>(&buffer)
@@ -393,14 +508,17 @@ mod tests {
             (
                 super::BlockMessage::DataRequest {
                     block_id: block_id1,
+                    sequence: sequence1,
                     block_info: block_info1,
                 },
                 super::BlockMessage::DataRequest {
                     block_id: block_id2,
+                    sequence: sequence2,
                     block_info: block_info2,
                 },
             ) => {
                 assert_eq!(block_id1, block_id2);
+                assert_eq!(sequence1, sequence2);
                 assert_eq!(block_info1, block_info2);
             }
             _ => panic!("Wrong message type"),
@@ -408,6 +526,7 @@ mod tests {
         let message2 = super::BlockMessage::DataResponse {
             block_id: BlockId::from_str("B12DvrcQkzF1Wi8BVoNfc4n93CD3E2qhCNe7nVhnEQGWHZ24fEmg")
                 .unwrap(),
+            sequence: Some(7),
             block_info: super::BlockInfoReply::Operations(vec![]),
         };
         let mut buffer2 = Vec::new();
@@ -420,14 +539,17 @@ mod tests {
             (
                 super::BlockMessage::DataResponse {
                     block_id: block_id1,
+                    sequence: sequence1,
                     block_info: block_info1,
                 },
                 super::BlockMessage::DataResponse {
                     block_id: block_id2,
+                    sequence: sequence2,
                     block_info: block_info2,
                 },
             ) => {
                 assert_eq!(block_id1, block_id2);
+                assert_eq!(sequence1, sequence2);
                 match (block_info1, block_info2) {
                     (
                         super::BlockInfoReply::Operations(operations1),
@@ -442,11 +564,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_unsequenced_message() {
+        // without sequence number, the legacy message type and layout are used
+        let block_id =
+            BlockId::from_str("B12DvrcQkzF1Wi8BVoNfc4n93CD3E2qhCNe7nVhnEQGWHZ24fEmg").unwrap();
+        let serializer = super::BlockMessageSerializer::new();
+        let deserializer =
+            super::BlockMessageDeserializer::new(super::BlockMessageDeserializerArgs {
+                thread_count: 1,
+                endorsement_count: 1,
+                max_operations_per_block: 1,
+                max_datastore_value_length: 1,
+                max_function_name_length: 1,
+                max_parameters_size: 1,
+                max_op_datastore_entry_count: 1,
+                max_op_datastore_key_length: 1,
+                max_op_datastore_value_length: 1,
+                max_denunciations_in_block_header: 1,
+                last_start_period: None,
+                chain_id: *CHAINID,
+                max_decompressed_operations_size: 1,
+            });
+        for sequence in [None, Some(7)] {
+            let message = super::BlockMessage::DataRequest {
+                block_id,
+                sequence,
+                block_info: super::AskForBlockInfo::Header,
+            };
+            let mut buffer = Vec::new();
+            serializer.serialize(&message, &mut buffer).unwrap();
+            let expected_id = match sequence {
+                None => super::MessageTypeId::DataRequest,
+                Some(_) => super::MessageTypeId::SequencedDataRequest,
+            };
+            assert_eq!(buffer[0], u64::from(expected_id) as u8);
+            // block id and info type, plus the sequence number if any
+            assert_eq!(buffer.len(), 1 + 33 + 1 + usize::from(sequence.is_some()));
+            let (rest, deserialized_message) = deserializer
+                .deserialize::<DeserializeError>(&buffer)
+                .unwrap();
+            assert!(rest.is_empty());
+            match deserialized_message {
+                super::BlockMessage::DataRequest {
+                    block_id: deserialized_block_id,
+                    sequence: deserialized_sequence,
+                    block_info: super::AskForBlockInfo::Header,
+                } => {
+                    assert_eq!(deserialized_block_id, block_id);
+                    assert_eq!(deserialized_sequence, sequence);
+                }
+                _ => panic!("Wrong message type"),
+            }
+        }
+    }
+
     #[test]
     fn test_high_limit_message() {
         let message = super::BlockMessage::DataRequest {
             block_id: BlockId::from_str("B12DvrcQkzF1Wi8BVoNfc4n93CD3E2qhCNe7nVhnEQGWHZ24fEmg")
                 .unwrap(),
+            sequence: Some(7),
             block_info: super::AskForBlockInfo::Operations(vec![
                 OperationId::from_str("O1yrsTtyyhDJtPD7jZHkodstNCjUSsfGbVZ5xdG6bVZWABeze8y")
                     .unwrap(),
@@ -471,6 +649,7 @@ mod tests {
                 max_denunciations_in_block_header: 1,
                 last_start_period: None,
                 chain_id: *CHAINID,
+                max_decompressed_operations_size: 1,
             });
         deserializer
             .deserialize::<DeserializeError>(&buffer)
@@ -489,6 +668,7 @@ mod tests {
                 max_denunciations_in_block_header: 1,
                 last_start_period: None,
                 chain_id: *CHAINID,
+                max_decompressed_operations_size: 1,
             });
         let (rest, deserialized_message) = deserializer
             .deserialize::<DeserializeError>(&buffer)
@@ -498,17 +678,128 @@ mod tests {
             (
                 super::BlockMessage::DataRequest {
                     block_id: block_id1,
+                    sequence: sequence1,
                     block_info: block_info1,
                 },
                 super::BlockMessage::DataRequest {
                     block_id: block_id2,
+                    sequence: sequence2,
                     block_info: block_info2,
                 },
             ) => {
                 assert_eq!(block_id1, block_id2);
+                assert_eq!(sequence1, sequence2);
                 assert_eq!(block_info1, block_info2);
             }
             _ => panic!("Wrong message type"),
         }
     }
+
+    #[test]
+    fn test_compressed_operations_message() {
+        use massa_models::{
+            address::Address,
+            amount::Amount,
+            operation::{Operation, OperationSerializer, OperationType},
+            secure_share::SecureShareContent,
+        };
+        use massa_signature::KeyPair;
+
+        let keypair = KeyPair::generate(0).unwrap();
+        let operations: Vec<_> = (0..10)
+            .map(|expire_period| {
+                Operation::new_verifiable(
+                    Operation {
+                        fee: Amount::default(),
+                        expire_period,
+                        start_period: None,
+                        fee_payer: None,
+                        op: OperationType::Transaction {
+                            recipient_address: Address::from_public_key(&keypair.get_public_key()),
+                            amount: Amount::default(),
+                        },
+                    },
+                    OperationSerializer::new(),
+                    &keypair,
+                    *CHAINID,
+                )
+                .unwrap()
+            })
+            .collect();
+        let block_id =
+            BlockId::from_str("B12DvrcQkzF1Wi8BVoNfc4n93CD3E2qhCNe7nVhnEQGWHZ24fEmg").unwrap();
+        let serializer = super::BlockMessageSerializer::new();
+        let mut raw_buffer = Vec::new();
+        serializer
+            .serialize(
+                &super::BlockMessage::DataResponse {
+                    block_id,
+                    sequence: Some(7),
+                    block_info: super::BlockInfoReply::Operations(operations.clone()),
+                },
+                &mut raw_buffer,
+            )
+            .unwrap();
+        let mut buffer = Vec::new();
+        serializer
+            .serialize(
+                &super::BlockMessage::DataResponse {
+                    block_id,
+                    sequence: Some(7),
+                    block_info: super::BlockInfoReply::CompressedOperations(operations.clone()),
+                },
+                &mut buffer,
+            )
+            .unwrap();
+        // operations of the same sender share a lot of bytes
+        assert!(buffer.len() < raw_buffer.len());
+
+        let deserializer_args =
+            |max_decompressed_operations_size| super::BlockMessageDeserializerArgs {
+                thread_count: 32,
+                endorsement_count: 16,
+                max_operations_per_block: 10,
+                max_datastore_value_length: 1,
+                max_function_name_length: 1,
+                max_parameters_size: 1,
+                max_op_datastore_entry_count: 1,
+                max_op_datastore_key_length: 1,
+                max_op_datastore_value_length: 1,
+                max_denunciations_in_block_header: 1,
+                last_start_period: None,
+                chain_id: *CHAINID,
+                max_decompressed_operations_size,
+            };
+        let deserializer =
+            super::BlockMessageDeserializer::new(deserializer_args(raw_buffer.len() as u64));
+        let (rest, deserialized_message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deserialized_message {
+            super::BlockMessage::DataResponse {
+                block_id: deserialized_block_id,
+                sequence,
+                block_info: super::BlockInfoReply::CompressedOperations(deserialized_operations),
+            } => {
+                assert_eq!(deserialized_block_id, block_id);
+                assert_eq!(sequence, Some(7));
+                assert_eq!(
+                    deserialized_operations
+                        .iter()
+                        .map(|op| op.id)
+                        .collect::<Vec<_>>(),
+                    operations.iter().map(|op| op.id).collect::<Vec<_>>()
+                );
+            }
+            _ => panic!("Wrong message type"),
+        }
+
+        // the size limit applies to the decompressed operations
+        let deserializer =
+            super::BlockMessageDeserializer::new(deserializer_args(buffer.len() as u64));
+        deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .expect_err("decompressed operations exceed the size limit");
+    }
 }
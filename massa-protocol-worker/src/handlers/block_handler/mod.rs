@@ -17,12 +17,14 @@ use self::{
     retrieval::start_retrieval_thread,
 };
 
+mod body_validation;
 pub mod cache;
 pub mod commands_propagation;
 pub mod commands_retrieval;
 pub mod messages;
 mod propagation;
 mod retrieval;
+mod wishlist_snapshot;
 
 pub(crate) use messages::{BlockMessage, BlockMessageSerializer};
 
@@ -36,7 +38,7 @@ use super::{
     operation_handler::{
         cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
     },
-    peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+    peer_handler::models::{PeerManagementCmd, PeerMessageTuple, SharedPeerDB},
 };
 
 pub struct BlockHandler {
@@ -60,6 +62,7 @@ impl BlockHandler {
         sender_propagations_ops: MassaSender<OperationHandlerPropagationCommand>,
         sender_propagations_endorsements: MassaSender<EndorsementHandlerPropagationCommand>,
         peer_cmd_sender: MassaSender<PeerManagementCmd>,
+        peer_db: SharedPeerDB,
         config: ProtocolConfig,
         endorsement_cache: SharedEndorsementCache,
         operation_cache: SharedOperationCache,
@@ -79,13 +82,14 @@ impl BlockHandler {
             sender_propagations_ops,
             sender_propagations_endorsements,
             peer_cmd_sender.clone(),
+            peer_db,
             config.clone(),
             endorsement_cache,
             operation_cache,
             cache.clone(),
             storage.clone_without_refs(),
             mip_store,
-            massa_metrics,
+            massa_metrics.clone(),
         );
         let block_propagation_thread = start_propagation_thread(
             active_connections,
@@ -93,6 +97,7 @@ impl BlockHandler {
             peer_cmd_sender,
             config,
             cache,
+            massa_metrics,
         );
         Self {
             block_retrieval_thread: Some((sender_ext, block_retrieval_thread)),
@@ -22,6 +22,7 @@ use crate::{
 };
 use crossbeam::channel::RecvTimeoutError;
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
+use massa_metrics::MassaMetrics;
 use massa_models::block_header::SecuredHeader;
 use massa_models::block_id::BlockId;
 use massa_protocol_exports::PeerId;
@@ -61,6 +62,8 @@ pub struct PropagationThread {
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     /// Serializer for block-related messages
     block_serializer: MessagesSerializer,
+    /// Metrics of the node
+    massa_metrics: MassaMetrics,
 }
 
 impl PropagationThread {
@@ -193,6 +196,7 @@ impl PropagationThread {
                     Ok(()) => {
                         // mark the block as known by the peer
                         known_by_peer.insert(*block_id, (true, now));
+                        self.massa_metrics.inc_protocol_items_sent("block", 1);
                     }
                     Err(err) => {
                         warn!(
@@ -224,6 +228,7 @@ pub fn start_propagation_thread(
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     config: ProtocolConfig,
     cache: SharedBlockCache,
+    massa_metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
         .name(THREAD_NAME.to_string())
@@ -243,6 +248,7 @@ pub fn start_propagation_thread(
                 peer_cmd_sender,
                 active_connections,
                 block_serializer,
+                massa_metrics,
             };
             propagation_thread.run();
         })
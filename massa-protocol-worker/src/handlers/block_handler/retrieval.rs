@@ -1,7 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     thread::JoinHandle,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use crate::{
@@ -11,10 +11,14 @@ use crate::{
             commands_propagation::EndorsementHandlerPropagationCommand,
             note_endorsements_from_peer,
         },
+        message_timer::MessageTimer,
         operation_handler::{
             cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
         },
-        peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+        peer_handler::{
+            latency::latency_bucket,
+            models::{peer_supports, PeerManagementCmd, PeerMessageTuple, SharedPeerDB},
+        },
     },
     messages::{Message, MessagesSerializer},
     wrap_network::ActiveConnectionsTrait,
@@ -32,20 +36,18 @@ use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
     endorsement::EndorsementId,
-    operation::{
-        compute_operations_hash, OperationId, OperationIdSerializer, SecureShareOperation,
-    },
+    operation::{OperationId, OperationIdSerializer, SecureShareOperation},
     prehash::{PreHashMap, PreHashSet},
     secure_share::SecureShare,
     timeslots::get_block_slot_timestamp,
 };
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
-use massa_protocol_exports::PeerId;
+use massa_protocol_exports::{PeerFeatures, PeerId};
 use massa_protocol_exports::{ProtocolConfig, ProtocolError};
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_storage::Storage;
-use massa_time::TimeError;
+use massa_time::{MassaTime, TimeError};
 use massa_versioning::versioning::MipStore;
 use rand::thread_rng;
 use rand::{seq::SliceRandom, Rng};
@@ -53,13 +55,18 @@ use tracing::{debug, info, warn};
 
 use super::{
     super::operation_handler::note_operations_from_peer,
+    body_validation::{
+        check_operation_count, check_operation_formats, check_operation_list,
+        check_operation_threads, BlockBodyError,
+    },
     cache::SharedBlockCache,
     commands_propagation::BlockHandlerPropagationCommand,
     commands_retrieval::BlockHandlerRetrievalCommand,
     messages::{
         AskForBlockInfo, BlockInfoReply, BlockMessage, BlockMessageDeserializer,
-        BlockMessageDeserializerArgs,
+        BlockMessageDeserializerArgs, MessageTypeId,
     },
+    wishlist_snapshot::{load_wishlist, save_wishlist},
     BlockMessageSerializer,
 };
 
@@ -99,8 +106,22 @@ pub struct RetrievalThread {
     receiver: MassaReceiver<BlockHandlerRetrievalCommand>,
     block_message_serializer: MessagesSerializer,
     block_wishlist: PreHashMap<BlockId, BlockInfo>,
-    asked_blocks: HashMap<PeerId, PreHashMap<BlockId, Instant>>,
+    /// blocks restored from the wishlist snapshot that consensus did not wish again yet
+    restored_blocks: PreHashSet<BlockId>,
+    /// time after which the restored blocks that consensus did not wish again are forgotten
+    restored_blocks_deadline: Instant,
+    /// pending asks for block data, by peer: time of the ask and sequence number of the request,
+    /// if the peer announced `PeerFeatures::SEQUENCED_BLOCK_DATA`
+    asked_blocks: HashMap<PeerId, PreHashMap<BlockId, (Instant, Option<u64>)>>,
+    /// number of unanswered asks of each block, by peer
+    ask_block_failures: PreHashMap<BlockId, HashMap<PeerId, usize>>,
+    /// sequence number of the next block data request sent to each connected peer
+    next_ask_sequences: HashMap<PeerId, u64>,
+    /// number of unrequested headers with a slot out of the configured bounds, by peer
+    out_of_bounds_headers: HashMap<PeerId, u64>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    /// used to know the wire features announced by the peers
+    peer_db: SharedPeerDB,
     sender_propagation_ops: MassaSender<OperationHandlerPropagationCommand>,
     sender_propagation_endorsements: MassaSender<EndorsementHandlerPropagationCommand>,
     endorsement_cache: SharedEndorsementCache,
@@ -115,30 +136,109 @@ pub struct RetrievalThread {
 }
 
 impl RetrievalThread {
+    fn block_message_deserializer(&self) -> BlockMessageDeserializer {
+        BlockMessageDeserializer::new(BlockMessageDeserializerArgs {
+            thread_count: self.config.thread_count,
+            endorsement_count: self.config.endorsement_count,
+            max_operations_per_block: self
+                .config
+                .chain_parameters
+                .upper_bounds()
+                .max_operations_per_block,
+            max_datastore_value_length: self.config.max_size_value_datastore,
+            max_function_name_length: self.config.max_size_function_name,
+            max_parameters_size: self.config.max_size_call_sc_parameter,
+            max_op_datastore_entry_count: self.config.max_op_datastore_entry_count,
+            max_op_datastore_key_length: self.config.max_op_datastore_key_length,
+            max_op_datastore_value_length: self.config.max_op_datastore_value_length,
+            max_denunciations_in_block_header: self.config.max_denunciations_in_block_header,
+            last_start_period: Some(self.config.last_start_period),
+            chain_id: self.config.chain_id,
+            max_decompressed_operations_size: self.config.max_message_size as u64,
+        })
+    }
+
+    /// Resumes the retrieval of the blocks saved in the wishlist snapshot, if it is recent enough
+    fn restore_wishlist(&mut self) {
+        let max_age = self.config.block_wishlist_max_age;
+        if max_age == MassaTime::from_millis(0) {
+            return;
+        }
+        let blocks = match load_wishlist(
+            &self.config.block_wishlist_file,
+            &self.block_message_deserializer(),
+            MassaTime::now(),
+            max_age,
+            self.config.last_start_period,
+        ) {
+            Ok(blocks) => blocks,
+            Err(err) => {
+                warn!(
+                    "could not load the block wishlist snapshot {}: {}",
+                    self.config.block_wishlist_file.display(),
+                    err
+                );
+                return;
+            }
+        };
+        if blocks.is_empty() {
+            return;
+        }
+        info!("resuming the retrieval of {} blocks", blocks.len());
+        for (block_id, block) in blocks {
+            let mut storage = self.storage.clone_without_refs();
+            storage.store_operations(block.operations);
+            self.block_wishlist.insert(
+                block_id,
+                BlockInfo {
+                    header: block.header,
+                    operation_ids: block.operation_ids,
+                    storage,
+                },
+            );
+            self.restored_blocks.insert(block_id);
+        }
+        self.restored_blocks_deadline = Instant::now() + max_age.to_duration();
+    }
+
+    /// Saves the wishlist so that its retrieval resumes after a restart
+    fn save_wishlist(&self) {
+        if self.config.block_wishlist_max_age == MassaTime::from_millis(0)
+            || self.block_wishlist.is_empty()
+        {
+            return;
+        }
+        match save_wishlist(
+            &self.config.block_wishlist_file,
+            &self.block_wishlist,
+            MassaTime::now(),
+        ) {
+            Ok(()) => info!(
+                "saved the retrieval state of {} blocks",
+                self.block_wishlist.len()
+            ),
+            Err(err) => warn!(
+                "could not save the block wishlist snapshot {}: {}",
+                self.config.block_wishlist_file.display(),
+                err
+            ),
+        }
+    }
+
     fn run(&mut self) {
-        let block_message_deserializer =
-            BlockMessageDeserializer::new(BlockMessageDeserializerArgs {
-                thread_count: self.config.thread_count,
-                endorsement_count: self.config.endorsement_count,
-                max_operations_per_block: self.config.max_operations_per_block,
-                max_datastore_value_length: self.config.max_size_value_datastore,
-                max_function_name_length: self.config.max_size_function_name,
-                max_parameters_size: self.config.max_size_call_sc_parameter,
-                max_op_datastore_entry_count: self.config.max_op_datastore_entry_count,
-                max_op_datastore_key_length: self.config.max_op_datastore_key_length,
-                max_op_datastore_value_length: self.config.max_op_datastore_value_length,
-                max_denunciations_in_block_header: self.config.max_denunciations_in_block_header,
-                last_start_period: Some(self.config.last_start_period),
-                chain_id: self.config.chain_id,
-            });
+        let block_message_deserializer = self.block_message_deserializer();
 
         let tick_update_metrics = tick(self.massa_metrics.tick_delay);
+        // owned by the loop so that the message timers do not borrow `self`
+        let massa_metrics = self.massa_metrics.clone();
+        let slow_message_threshold = self.config.slow_message_threshold.to_duration();
         loop {
             select! {
                 recv(self.receiver_network) -> msg => {
                     self.receiver_network.update_metrics();
                     match msg {
                         Ok((peer_id, message)) => {
+                            let mut timer = MessageTimer::start(&massa_metrics, slow_message_threshold, peer_id, "Block");
                             let (rest, message) = match block_message_deserializer
                                 .deserialize::<DeserializeError>(&message) {
                                 Ok((rest, message)) => (rest, message),
@@ -151,13 +251,22 @@ impl RetrievalThread {
                                 println!("Error: message not fully consumed");
                                 return;
                             }
+                            timer.set_message_type(format!("Block::{:?}", MessageTypeId::from(&message)));
                             match message {
-                                BlockMessage::DataRequest{block_id, block_info} => {
-                                    self.on_ask_for_block_info_received(peer_id, block_id, block_info);
+                                BlockMessage::DataRequest{block_id, sequence, block_info} => {
+                                    self.on_ask_for_block_info_received(peer_id, block_id, sequence, block_info);
                                 }
-                                BlockMessage::DataResponse{block_id, block_info} => {
-                                   self.on_block_info_received(peer_id, block_id, block_info);
-                                   self.update_block_retrieval();
+                                BlockMessage::DataResponse{block_id, sequence, block_info} => {
+                                    if !self.take_asked_block(&peer_id, &block_id, sequence) {
+                                        debug!("discarding stale or replayed response {:?} for block {} from {}", sequence, block_id, peer_id);
+                                        continue;
+                                    }
+                                    if !matches!(block_info, BlockInfoReply::NotFound) {
+                                        // the answers to the same ask sent to other peers are dropped on arrival
+                                        self.cancel_other_asks(&peer_id, &block_id);
+                                    }
+                                    self.on_block_info_received(peer_id, block_id, block_info);
+                                    self.update_block_retrieval();
                                 }
                                 BlockMessage::Header(header) => {
                                     self.on_block_header_received(peer_id, header);
@@ -179,10 +288,22 @@ impl RetrievalThread {
                                 BlockHandlerRetrievalCommand::WishlistDelta { new, remove } => {
                                     massa_trace!("protocol.protocol_worker.process_command.wishlist_delta.begin", { "new": new, "remove": remove });
                                     for (block_id, header) in new.into_iter() {
-                                        self.block_wishlist.insert(
-                                            block_id,
-                                            BlockInfo::new(header, self.storage.clone_without_refs()),
-                                        );
+                                        self.restored_blocks.remove(&block_id);
+                                        match self.block_wishlist.get_mut(&block_id) {
+                                            // keep the retrieval progress of blocks already wished,
+                                            // only complete their header if it was unknown
+                                            Some(info) => {
+                                                if info.header.is_none() {
+                                                    info.header = header;
+                                                }
+                                            }
+                                            None => {
+                                                self.block_wishlist.insert(
+                                                    block_id,
+                                                    BlockInfo::new(header, self.storage.clone_without_refs()),
+                                                );
+                                            }
+                                        }
                                     }
                                     // Cleanup the knowledge that we asked this list of blocks to nodes.
                                     self.remove_asked_blocks(&remove);
@@ -190,11 +311,29 @@ impl RetrievalThread {
                                     // Remove from the wishlist.
                                     for block_id in remove.iter() {
                                         self.block_wishlist.remove(block_id);
+                                        self.restored_blocks.remove(block_id);
                                     }
 
                                     // update block asking process
                                     self.update_block_retrieval();
                                 },
+                                BlockHandlerRetrievalCommand::GetAskedBlocks { responder } => {
+                                    let now = Instant::now();
+                                    let asked_blocks = self
+                                        .asked_blocks
+                                        .iter()
+                                        .map(|(peer_id, asked_blocks)| {
+                                            let asked_blocks = asked_blocks
+                                                .iter()
+                                                .map(|(block_id, (ask_time, _))| {
+                                                    (*block_id, MassaTime::from_millis(now.saturating_duration_since(*ask_time).as_millis() as u64))
+                                                })
+                                                .collect();
+                                            (*peer_id, asked_blocks)
+                                        })
+                                        .collect();
+                                    responder.try_send(asked_blocks).unwrap_or_else(|_| warn!("Failed to send asked blocks to responder"));
+                                },
                                 BlockHandlerRetrievalCommand::Stop => {
                                     info!("Stop block retrieval thread from command receiver (Stop)");
                                     return;
@@ -221,16 +360,27 @@ impl RetrievalThread {
                             block_read.checked_headers.len(),
                             count,
                         );
+                        self.massa_metrics
+                            .set_memory_usage("protocol_block_cache", block_read.memory_usage());
                     }
 
+                    self.massa_metrics.set_protocol_block_retrieval(
+                        self.block_wishlist.len(),
+                        self.asked_blocks.values().map(|asked| asked.len()).sum(),
+                    );
+
                     {
                         let ope_read = self.operation_cache.read();
-                        let count: usize = ope_read.ops_known_by_peer.values().map(|v| v.len()).sum();
+                        let count: usize = ope_read.ops_known_by_peer.values().map(|v| v.known_ops_count()).sum();
                         self.massa_metrics.set_operations_cache_metrics(
                             ope_read.checked_operations.len(),
                             ope_read.checked_operations_prefix.len(),
                             count,
+                            ope_read.ops_known_by_peer_hits,
+                            ope_read.ops_known_by_peer_misses,
                         );
+                        self.massa_metrics
+                            .set_memory_usage("protocol_operation_cache", ope_read.memory_usage());
                     }
                 }
                 recv(at(self.next_timer_ask_block)) -> _ => {
@@ -243,11 +393,13 @@ impl RetrievalThread {
     /// A remote node asked the local node for block data
     ///
     /// We send the block's operation ids if the foreign node asked for `AskForBlockInfo::Info`
-    /// or a subset of the full operations of the block if it asked for `AskForBlockInfo::Operations`.
+    /// or a subset of the full operations of the block if it asked for `AskForBlockInfo::Operations`
+    /// (compressed if it asked for `AskForBlockInfo::CompressedOperations`).
     fn on_ask_for_block_info_received(
         &mut self,
         from_peer_id: PeerId,
         block_id: BlockId,
+        sequence: Option<u64>,
         info_requested: AskForBlockInfo,
     ) {
         debug!(
@@ -269,6 +421,8 @@ impl RetrievalThread {
             )
         });
 
+        let compress_operations =
+            matches!(info_requested, AskForBlockInfo::CompressedOperations(_));
         let block_info_response = match (stored_header_op_ids, info_requested) {
             (None, _) => BlockInfoReply::NotFound,
 
@@ -301,7 +455,11 @@ impl RetrievalThread {
 
                 BlockInfoReply::OperationIds(block_op_ids)
             }
-            (Some((_, block_op_ids)), AskForBlockInfo::Operations(mut asked_ops)) => {
+            (
+                Some((_, block_op_ids)),
+                AskForBlockInfo::Operations(mut asked_ops)
+                | AskForBlockInfo::CompressedOperations(mut asked_ops),
+            ) => {
                 // the peer asked for a list of full operations from the block
 
                 // retain only ops that belong to the block
@@ -330,7 +488,11 @@ impl RetrievalThread {
                         .collect::<PreHashSet<OperationId>>(),
                 );
 
-                BlockInfoReply::Operations(returned_ops)
+                if compress_operations {
+                    BlockInfoReply::CompressedOperations(returned_ops)
+                } else {
+                    BlockInfoReply::Operations(returned_ops)
+                }
             }
         };
 
@@ -345,6 +507,7 @@ impl RetrievalThread {
             &self.block_message_serializer,
             BlockMessage::DataResponse {
                 block_id,
+                sequence,
                 block_info: block_info_response,
             }
             .into(),
@@ -387,6 +550,39 @@ impl RetrievalThread {
         }
     }
 
+    /// Checks that a block data response answers the pending request for that block to that peer,
+    /// and consumes the request so that the response cannot be replayed.
+    ///
+    /// Responses arriving after the request timed out (and possibly got retried on another peer)
+    /// are rejected as well.
+    fn take_asked_block(
+        &mut self,
+        from_peer_id: &PeerId,
+        block_id: &BlockId,
+        sequence: Option<u64>,
+    ) -> bool {
+        let Some(asked_blocks) = self.asked_blocks.get_mut(from_peer_id) else {
+            return false;
+        };
+        match asked_blocks.get(block_id) {
+            Some((_, asked_sequence)) if *asked_sequence == sequence => {
+                asked_blocks.remove(block_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forgets the pending asks of `block_id` to other peers than `from_peer_id`,
+    /// so that their answers are not processed a second time
+    fn cancel_other_asks(&mut self, from_peer_id: &PeerId, block_id: &BlockId) {
+        for (peer_id, asked_blocks) in self.asked_blocks.iter_mut() {
+            if peer_id != from_peer_id {
+                asked_blocks.remove(block_id);
+            }
+        }
+    }
+
     /// A peer sent us a response to one of our requests for block data
     fn on_block_info_received(
         &mut self,
@@ -406,7 +602,8 @@ impl RetrievalThread {
                 // the block_header.
                 self.on_block_operation_list_received(from_peer_id, block_id, operation_list);
             }
-            BlockInfoReply::Operations(operations) => {
+            BlockInfoReply::Operations(operations)
+            | BlockInfoReply::CompressedOperations(operations) => {
                 // Send operations to pool,
                 // before performing the below checks,
                 // and wait for them to have been procesed(i.e. added to storage).
@@ -427,6 +624,15 @@ impl RetrievalThread {
 
         let block_id = header.id;
 
+        // Drop the headers we did not ask for whose slot is too far in the future or in the past,
+        // before any further processing
+        if !self.block_wishlist.contains_key(&block_id) {
+            if let Err(err) = self.check_header_slot_bounds(&header) {
+                self.on_out_of_bounds_header(from_peer_id, err);
+                return;
+            }
+        }
+
         // Check header and update knowledge info
         let is_new = match self.note_header_from_peer(&header, &from_peer_id) {
             Ok(is_new) => is_new,
@@ -435,9 +641,7 @@ impl RetrievalThread {
                     "peer {} sent us critically incorrect header: {}",
                     &from_peer_id, err
                 );
-                if let Err(err) = self.ban_peers(&[from_peer_id]) {
-                    warn!("Error while banning peer {} err: {:?}", &from_peer_id, err);
-                }
+                self.note_invalid_item(from_peer_id, "header");
                 return;
             }
         };
@@ -653,11 +857,85 @@ impl RetrievalThread {
 
             // mark us as knowing the header
             cache_lock.checked_headers.insert(block_id, header.clone());
+
+            // the sender peer is the first one that sent us the header
+            cache_lock.first_senders.insert(block_id, *from_peer_id);
         }
 
         Ok(true)
     }
 
+    /// Checks that the slot of a header is neither beyond the clock tolerance in the future,
+    /// nor so far in the past that it is long since final.
+    /// A bound set to 0 is not checked.
+    fn check_header_slot_bounds(&self, header: &SecuredHeader) -> Result<(), ProtocolError> {
+        let max_future_time = self.config.max_header_future_time;
+        let max_past_periods = self.config.max_header_past_periods;
+        if max_future_time == MassaTime::from_millis(0) && max_past_periods == 0 {
+            return Ok(());
+        }
+        let slot = header.content.slot;
+        let slot_timestamp = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            slot,
+        )?;
+        let now = MassaTime::now();
+        if max_future_time > MassaTime::from_millis(0)
+            && slot_timestamp > now.saturating_add(max_future_time)
+        {
+            return Err(ProtocolError::InvalidBlock(format!(
+                "header slot {} is too far in the future",
+                slot
+            )));
+        }
+        if max_past_periods > 0
+            && slot_timestamp.saturating_add(self.config.t0.saturating_mul(max_past_periods)) < now
+        {
+            return Err(ProtocolError::InvalidBlock(format!(
+                "header slot {} is long since final",
+                slot
+            )));
+        }
+        Ok(())
+    }
+
+    /// Counts an out of bounds header against the peer that sent it,
+    /// and bans the peer once it sent too many of them
+    fn on_out_of_bounds_header(&mut self, from_peer_id: PeerId, err: ProtocolError) {
+        debug!("dropping header from peer {}: {}", &from_peer_id, err);
+        self.massa_metrics
+            .inc_protocol_invalid_items(&from_peer_id.to_string(), "header");
+        let count = self.out_of_bounds_headers.entry(from_peer_id).or_default();
+        *count += 1;
+        if *count > self.config.max_out_of_bounds_headers_per_peer {
+            warn!(
+                "peer {} sent us too many headers with an out of bounds slot",
+                &from_peer_id
+            );
+            self.out_of_bounds_headers.remove(&from_peer_id);
+            if let Err(err) = self.ban_peers(&[from_peer_id]) {
+                warn!("Error while banning peer {} err: {:?}", &from_peer_id, err);
+            }
+        }
+    }
+
+    /// count an invalid item received from a peer: the peer handler bans it if it sends too many of them
+    fn note_invalid_item(&mut self, peer_id: PeerId, item_type: &'static str) {
+        self.massa_metrics
+            .inc_protocol_invalid_items(&peer_id.to_string(), item_type);
+        if let Err(err) = self
+            .peer_cmd_sender
+            .try_send(PeerManagementCmd::NoteInvalidItem { peer_id, item_type })
+        {
+            warn!(
+                "Error while noting invalid {} from peer {} err: {:?}",
+                item_type, peer_id, err
+            );
+        }
+    }
+
     /// send a ban peer command to the peer handler
     fn ban_peers(&mut self, peer_ids: &[PeerId]) -> Result<(), ProtocolError> {
         self.peer_cmd_sender
@@ -674,6 +952,16 @@ impl RetrievalThread {
         }
     }
 
+    /// Reject the body of a block received from a peer, penalizing the peer
+    fn reject_block_body(&mut self, from_peer_id: PeerId, block_id: &BlockId, err: BlockBodyError) {
+        warn!(
+            "Peer id {} sent us an invalid body for block id {}: {}",
+            from_peer_id, block_id, err
+        );
+        self.massa_metrics.inc_protocol_invalid_blocks(err.code());
+        self.note_invalid_item(from_peer_id, "block");
+    }
+
     /// Mark a block as invalid
     fn mark_block_as_invalid(&mut self, block_id: &BlockId) {
         // stop retrieving the block
@@ -724,7 +1012,8 @@ impl RetrievalThread {
             "received operation list for block {} from {}",
             block_id, &from_peer_id
         );
-        // Note that the length of the operation list was checked at deserialization to not overflow the max per block.
+        // Note that the length of the operation list was checked at deserialization to not overflow the max per block
+        // of any chain parameters version, the max in force at the slot of the block is checked below.
 
         // All operation ids sent into a set to deduplicate and search quickly for presence
         let operation_ids_set: PreHashSet<OperationId> = operation_ids.iter().cloned().collect();
@@ -755,21 +1044,23 @@ impl RetrievalThread {
         };
 
         // check that the hash of the received operations list matches the one in the header
-        let computed_operations_hash =
-            compute_operations_hash(&operation_ids, &self.operation_id_serializer);
-
-        if wishlist_info
+        let header = wishlist_info
             .header
             .as_ref()
-            .expect("header presence in wishlist should have been checked above")
-            .content
-            .operation_merkle_root
-            != computed_operations_hash
+            .expect("header presence in wishlist should have been checked above");
+        if let Err(err) =
+            check_operation_list(header, &operation_ids, &self.operation_id_serializer)
         {
-            warn!("Peer id {} sent us a operation list for block id {} but the hash in the header doesn't match.", from_peer_id, block_id);
-            if let Err(err) = self.ban_peers(&[from_peer_id]) {
-                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
-            }
+            self.reject_block_body(from_peer_id, &block_id, err);
+            return;
+        }
+
+        // The list is committed by the header: if it does not fit in a block at its slot, the block is invalid.
+        if let Err(err) =
+            check_operation_count(header, operation_ids.len(), &self.config.chain_parameters)
+        {
+            self.reject_block_body(from_peer_id, &block_id, err);
+            self.mark_block_as_invalid(&block_id);
             return;
         }
 
@@ -873,6 +1164,20 @@ impl RetrievalThread {
             );
         }
 
+        // The received operations are part of the block: if one of them is in the wrong thread, the block is invalid.
+        if let Err(err) = check_operation_threads(
+            wishlist_info
+                .header
+                .as_ref()
+                .expect("header presence in wishlist should have been checked above"),
+            operations.values(),
+            self.config.thread_count,
+        ) {
+            self.reject_block_body(from_peer_id, &block_id, err);
+            self.mark_block_as_invalid(&block_id);
+            return;
+        }
+
         // Here we know that we were looking for that block's operations and that the sender node sent us some of the missing ones.
 
         // Check the validity of the received operations.
@@ -887,14 +1192,13 @@ impl RetrievalThread {
             &from_peer_id,
             &mut self.sender_propagation_ops,
             &mut self.pool_controller,
+            &self.mip_store,
         ) {
             warn!(
                 "Peer id {} sent us operations for block id {} but they failed validity checks: {}",
                 from_peer_id, block_id, err
             );
-            if let Err(err) = self.ban_peers(&[from_peer_id]) {
-                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
-            }
+            self.note_invalid_item(from_peer_id, "operation");
             return;
         }
 
@@ -928,6 +1232,7 @@ impl RetrievalThread {
     /// function that updates the global state of block retrieval
     pub(crate) fn update_block_retrieval(&mut self) {
         let ask_block_timeout = self.config.ask_block_timeout.to_duration();
+        let ask_block_max_timeout = self.config.ask_block_max_timeout.to_duration();
 
         // Init timer for next tick
         let now = Instant::now();
@@ -936,6 +1241,13 @@ impl RetrievalThread {
             .ok_or(TimeError::TimeOverflowError)
             .expect("could not compute next block retrieval timer tick");
 
+        // forget the restored blocks that consensus did not wish again in time
+        if !self.restored_blocks.is_empty() && now >= self.restored_blocks_deadline {
+            for block_id in self.restored_blocks.drain() {
+                self.block_wishlist.remove(&block_id);
+            }
+        }
+
         if self.asked_blocks.is_empty() && self.block_wishlist.is_empty() {
             // Note: in mainnet and before genesis, no blocks are processed but the timer needs to be updated
             //       or the thread will use the CPU at 100%
@@ -957,20 +1269,52 @@ impl RetrievalThread {
             asked_blocks.retain(|block_id, _| self.block_wishlist.contains_key(block_id));
             !asked_blocks.is_empty()
         });
-
-        // list of blocks that need to be asked
-        let mut to_ask: PreHashSet<BlockId> = self.block_wishlist.keys().copied().collect();
+        self.ask_block_failures.retain(|block_id, failures| {
+            if !self.block_wishlist.contains_key(block_id) {
+                return false;
+            }
+            failures.retain(|peer_id, _| connected_peers.contains(peer_id));
+            !failures.is_empty()
+        });
+        self.next_ask_sequences
+            .retain(|peer_id, _| connected_peers.contains(peer_id));
+        self.out_of_bounds_headers
+            .retain(|peer_id, _| connected_peers.contains(peer_id));
+
+        // the number of peers a block can be asked to at the same time
+        let max_asks_per_block = self.config.max_parallel_block_asks.max(1);
+        // the number of pending asks of each block
+        let mut block_asks: PreHashMap<BlockId, usize> = Default::default();
         // the number of things already being asked to those peers
         let mut peer_loads: HashMap<PeerId, usize> = Default::default();
         for (peer_id, asked_blocks) in &mut self.asked_blocks {
             // init the list of items to remove from asked_blocks
             let mut to_remove_from_asked_blocks = Vec::new();
-            for (block_id, ask_time) in asked_blocks.iter() {
+            for (block_id, (ask_time, _)) in asked_blocks.iter() {
+                let failures = self
+                    .ask_block_failures
+                    .get(block_id)
+                    .and_then(|failures| failures.get(peer_id))
+                    .copied()
+                    .unwrap_or_default();
                 let expiry = ask_time
-                    .checked_add(ask_block_timeout)
+                    .checked_add(ask_block_backoff_timeout(
+                        ask_block_timeout,
+                        ask_block_max_timeout,
+                        failures,
+                    ))
                     .expect("could not compute block ask expiry");
                 if expiry <= now {
                     // the block has been asked for the block data a long time agp and did not respond
+                    self.massa_metrics.inc_protocol_block_ask_retries();
+
+                    // the next ask of this block to this peer will wait longer for an answer
+                    *self
+                        .ask_block_failures
+                        .entry(*block_id)
+                        .or_default()
+                        .entry(*peer_id)
+                        .or_default() += 1;
 
                     // we mark this peer as not knowing this block
                     self.cache
@@ -981,9 +1325,8 @@ impl RetrievalThread {
                     // This prevents us from re-detecting the timeout many times.
                     to_remove_from_asked_blocks.push(*block_id);
                 } else {
-                    // this block was recently asked to this peer: no need to ask for the block for now
-
-                    to_ask.remove(block_id);
+                    // this block was recently asked to this peer: no need to ask it again to this peer for now
+                    *block_asks.entry(*block_id).or_default() += 1;
 
                     // mark this peer as loaded with an angoing ask
                     peer_loads
@@ -1001,20 +1344,50 @@ impl RetrievalThread {
             }
         }
 
-        // for each block to ask, choose a peer to ask it from and perform the ask
-        let mut to_ask = to_ask.into_iter().collect::<Vec<_>>();
+        // for each block not asked to enough peers, choose peers to ask it from and perform the asks
+        let mut to_ask = self
+            .block_wishlist
+            .keys()
+            .filter(|block_id| {
+                block_asks.get(*block_id).copied().unwrap_or_default() < max_asks_per_block
+            })
+            .copied()
+            .collect::<Vec<_>>();
         to_ask.shuffle(&mut thread_rng()); // shuffle ask order
+        let peer_latencies = self.peer_db.read().get_latencies().latencies().clone();
         for block_id in to_ask {
-            // prioritize peers by (max knowledge, min knowledge age, min load, max random)
+            // no need to download a block we already hold
+            if self.serve_local_block(&block_id) {
+                continue;
+            }
+
+            // prioritize peers by (not rotated out, max knowledge, min latency, min knowledge age, min load, max random)
+            let block_failures = self.ask_block_failures.get(&block_id);
             let mut peer_scores: Vec<_> = connected_peers
                 .iter()
                 .filter_map(|peer_id| {
+                    // this peer is already being asked for this block
+                    if self
+                        .asked_blocks
+                        .get(peer_id)
+                        .is_some_and(|asked_blocks| asked_blocks.contains_key(&block_id))
+                    {
+                        return None;
+                    }
                     // Get the peer load. Look for the minimum score for asking.
                     let peer_load = peer_loads.get(peer_id).copied().unwrap_or_default();
                     if peer_load >= self.config.max_simultaneous_ask_blocks_per_node {
                         // this peer is already loaded with too many asks
                         return None;
                     }
+                    // peers that failed too many times to answer for this block are only asked
+                    // again if no other peer can be asked
+                    let rotated_out = self.config.max_ask_block_failures_per_node > 0
+                        && block_failures
+                            .and_then(|failures| failures.get(peer_id))
+                            .is_some_and(|failures| {
+                                *failures >= self.config.max_ask_block_failures_per_node
+                            });
                     // get peer knowledge info about that block
                     let peer_knowledge_of_block = self
                         .cache
@@ -1022,11 +1395,14 @@ impl RetrievalThread {
                         .blocks_known_by_peer
                         .get(peer_id)
                         .and_then(|blocks_known| blocks_known.peek(&block_id).copied());
+                    let latency = latency_bucket(peer_latencies.get(peer_id));
                     match peer_knowledge_of_block {
                         Some((false, info_t)) => {
                             // we think that the peer doesn't know the block
                             Some((
-                                1i8,                                                               // worst knowledge
+                                rotated_out,
+                                1i8,     // worst knowledge
+                                latency, // the lower the latency the better
                                 Some(-(now.saturating_duration_since(info_t).as_millis() as i64)), // the older the info the better
                                 peer_load,                 // the lower the load the better
                                 thread_rng().gen::<u64>(), // random tie breaker,
@@ -1036,7 +1412,9 @@ impl RetrievalThread {
                         None => {
                             // we don't know if the peer knows the block
                             Some((
+                                rotated_out,
                                 0i8,                       // medium knowledge
+                                latency,                   // the lower the latency the better
                                 None,                      // N/A
                                 peer_load,                 // the lower the load the better
                                 thread_rng().gen::<u64>(), // random tie breaker,
@@ -1046,7 +1424,9 @@ impl RetrievalThread {
                         Some((true, info_t)) => {
                             // we think that the peer knows the block
                             Some((
-                                -1i8,                                                           // best knowledge
+                                rotated_out,
+                                -1i8,    // best knowledge
+                                latency, // the lower the latency the better
                                 Some(now.saturating_duration_since(info_t).as_millis() as i64), // the newer the info the better
                                 peer_load,                 // the lower the load the better
                                 thread_rng().gen::<u64>(), // random tie breaker,
@@ -1077,6 +1457,9 @@ impl RetrievalThread {
                 (true, true) => {
                     // gather missing block operations and perform necessary followups
                     match self.gather_missing_block_ops(&block_id) {
+                        Some(ops) if self.config.compress_block_operations => {
+                            AskForBlockInfo::CompressedOperations(ops)
+                        }
                         Some(ops) => AskForBlockInfo::Operations(ops),
                         None => continue,
                     }
@@ -1084,18 +1467,38 @@ impl RetrievalThread {
                 _ => panic!("invalid wishlist state"),
             };
 
-            // try to ask peers from best to worst
-            for (_, _, _, _, peer_id) in peer_scores {
+            // try to ask peers from best to worst, until the block is asked to enough peers
+            let mut missing_asks =
+                max_asks_per_block - block_asks.get(&block_id).copied().unwrap_or_default();
+            for (_, _, _, _, _, _, peer_id) in peer_scores {
+                // only the peers that announced it are asked for compressed operations
+                let block_info = match &request {
+                    AskForBlockInfo::CompressedOperations(ops)
+                        if !peer_supports(
+                            &self.peer_db,
+                            &peer_id,
+                            PeerFeatures::COMPRESSED_OPERATIONS,
+                        ) =>
+                    {
+                        AskForBlockInfo::Operations(ops.clone())
+                    }
+                    _ => request.clone(),
+                };
                 debug!(
                     "Sending ask for block {} data to {}: {:?}",
-                    block_id, peer_id, &request
+                    block_id, peer_id, &block_info
                 );
+                // only the peers that announced it get numbered requests
+                let sequence =
+                    peer_supports(&self.peer_db, &peer_id, PeerFeatures::SEQUENCED_BLOCK_DATA)
+                        .then(|| self.next_ask_sequences.get(&peer_id).copied().unwrap_or(0));
                 if let Err(err) = self.active_connections.send_to_peer(
                     &peer_id,
                     &self.block_message_serializer,
                     Message::Block(Box::new(BlockMessage::DataRequest {
                         block_id,
-                        block_info: request.clone(),
+                        sequence,
+                        block_info,
                     })),
                     true,
                 ) {
@@ -1110,7 +1513,11 @@ impl RetrievalThread {
                     self.asked_blocks
                         .entry(peer_id)
                         .or_default()
-                        .insert(block_id, now);
+                        .insert(block_id, (now, sequence));
+                    if let Some(sequence) = sequence {
+                        self.next_ask_sequences
+                            .insert(peer_id, sequence.wrapping_add(1));
+                    }
 
                     // Increment the load of the peer.
                     peer_loads
@@ -1118,8 +1525,11 @@ impl RetrievalThread {
                         .and_modify(|v| *v += 1)
                         .or_insert(1);
 
-                    // No need to look for other peers.
-                    break;
+                    // No need to look for other peers once enough of them are asked.
+                    missing_asks -= 1;
+                    if missing_asks == 0 {
+                        break;
+                    }
                 }
             }
         }
@@ -1174,10 +1584,22 @@ impl RetrievalThread {
                 .to_vec(),
         );
 
-        // Check if the total size of the operations we know about is greater than the max block size.
+        // Check if the total size of the operations we know about is greater than the max block size at the slot of the block.
+        // The size is the one of the serialized operations, even if they were received compressed.
         // If it overflows, it means that the block is invalid because it is too big.
         // We should stop trying to retrieve the block and ban everyone who knows it.
-        if total_operations_size > self.config.max_serialized_operations_size_per_block {
+        let block_slot = wishlist_info
+            .header
+            .as_ref()
+            .expect("header presence in wishlist should have been checked above")
+            .content
+            .slot;
+        let max_block_size = self
+            .config
+            .chain_parameters
+            .parameters_at(block_slot)
+            .max_block_size as usize;
+        if total_operations_size > max_block_size {
             warn!(
                 "The operations we already have in our records exceed max block size for block {}.",
                 block_id
@@ -1195,12 +1617,87 @@ impl RetrievalThread {
             return Some((&op_id_set - &claimed_ops).into_iter().collect());
         }
 
+        // Check that all the operations, including those we already had, belong to the thread of the block
+        // and use operation formats active at its slot.
+        // Their IDs are committed by the header, so the block is invalid otherwise.
+        let operations_check = {
+            let header = wishlist_info
+                .header
+                .as_ref()
+                .expect("header presence in wishlist should have been checked above");
+            let operations = wishlist_info.storage.read_operations();
+            let block_operations = || op_id_set.iter().filter_map(|op_id| operations.get(op_id));
+            check_operation_threads(header, block_operations(), self.config.thread_count).and_then(
+                |_| {
+                    // the timestamp of the slot of an accepted header can always be computed
+                    let slot_timestamp = get_block_slot_timestamp(
+                        self.config.thread_count,
+                        self.config.t0,
+                        self.config.genesis_timestamp,
+                        header.content.slot,
+                    )
+                    .unwrap_or_else(|_| MassaTime::max());
+                    check_operation_formats(block_operations(), &self.mip_store, slot_timestamp)
+                },
+            )
+        };
+        if let Err(err) = operations_check {
+            warn!("Block {} is invalid: {}", block_id, err);
+            self.massa_metrics.inc_protocol_invalid_blocks(err.code());
+            self.mark_block_as_invalid(block_id);
+            return None;
+        }
+
         // there are no missing ops, we can finish the block
         self.fully_gathered_block(block_id);
 
         None
     }
 
+    /// If we already hold the wished block, send our local copy to consensus instead of downloading its body again.
+    /// Only a block with the same id is served locally: the id is the hash of the header, which commits
+    /// to the operations of the block, so any other block (such as an equivocation of the same creator
+    /// at the same slot) is downloaded.
+    ///
+    /// Returns true if the block was served from local storage.
+    fn serve_local_block(&mut self, block_id: &BlockId) -> bool {
+        let Some(header) = self
+            .block_wishlist
+            .get(block_id)
+            .and_then(|info| info.header.as_ref())
+        else {
+            return false;
+        };
+        let slot = header.content.slot;
+        let endorsements = header.content.endorsements.clone();
+
+        let operation_ids: PreHashSet<OperationId> = {
+            let blocks = self.storage.read_blocks();
+            let Some(block) = blocks.get(block_id) else {
+                return false;
+            };
+            block.content.operations.iter().copied().collect()
+        };
+
+        // gather the block and its operations, which must all be available locally
+        let mut block_storage = self.storage.clone_without_refs();
+        if block_storage.claim_operation_refs(&operation_ids).len() != operation_ids.len()
+            || block_storage
+                .claim_block_refs(&[*block_id].into_iter().collect())
+                .is_empty()
+        {
+            return false;
+        }
+        block_storage.store_endorsements(endorsements);
+
+        debug!("block {} served from local storage", block_id);
+        self.block_wishlist.remove(block_id);
+        self.consensus_controller
+            .register_block(*block_id, slot, block_storage, false);
+        self.remove_asked_blocks(&vec![*block_id].into_iter().collect());
+        true
+    }
+
     /// Called when we have fully gathered a block
     fn fully_gathered_block(&mut self, block_id: &BlockId) {
         debug!("Fully gathered block {}", block_id);
@@ -1253,12 +1750,27 @@ impl RetrievalThread {
         // Send to consensus
         self.consensus_controller
             .register_block(*block_id, slot, block_storage, false);
+        self.massa_metrics.inc_protocol_items_received("block", 1);
 
         // Remove from asked block history as it is not useful anymore
         self.remove_asked_blocks(&vec![*block_id].into_iter().collect());
     }
 }
 
+/// Time to wait for the answer to an ask for block data, doubled after each unanswered ask of the
+/// same block to the same peer, up to `max_timeout` (no backoff if it is below `timeout`)
+fn ask_block_backoff_timeout(
+    timeout: Duration,
+    max_timeout: Duration,
+    failures: usize,
+) -> Duration {
+    let factor = 1u32.checked_shl(failures as u32).unwrap_or(u32::MAX);
+    timeout
+        .checked_mul(factor)
+        .unwrap_or(Duration::MAX)
+        .min(max_timeout.max(timeout))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn start_retrieval_thread(
     active_connections: Box<dyn ActiveConnectionsTrait>,
@@ -1271,6 +1783,7 @@ pub fn start_retrieval_thread(
     sender_propagation_ops: MassaSender<OperationHandlerPropagationCommand>,
     sender_propagation_endorsements: MassaSender<EndorsementHandlerPropagationCommand>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_db: SharedPeerDB,
     config: ProtocolConfig,
     endorsement_cache: SharedEndorsementCache,
     operation_cache: SharedOperationCache,
@@ -1291,8 +1804,14 @@ pub fn start_retrieval_thread(
                 pool_controller,
                 next_timer_ask_block: Instant::now() + config.ask_block_timeout.to_duration(),
                 block_wishlist: PreHashMap::default(),
+                restored_blocks: PreHashSet::default(),
+                restored_blocks_deadline: Instant::now(),
                 asked_blocks: HashMap::default(),
+                ask_block_failures: PreHashMap::default(),
+                next_ask_sequences: HashMap::default(),
+                out_of_bounds_headers: HashMap::default(),
                 peer_cmd_sender,
+                peer_db,
                 sender_propagation_ops,
                 sender_propagation_endorsements,
                 receiver_network,
@@ -1308,7 +1827,43 @@ pub fn start_retrieval_thread(
                 massa_metrics,
                 operation_id_serializer: OperationIdSerializer::new(),
             };
+            retrieval_thread.restore_wishlist();
             retrieval_thread.run();
+            retrieval_thread.save_wishlist();
         })
         .expect("OS failed to start block retrieval thread")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ask_block_backoff_timeout() {
+        let timeout = Duration::from_millis(100);
+        let max_timeout = Duration::from_millis(1_000);
+        assert_eq!(ask_block_backoff_timeout(timeout, max_timeout, 0), timeout);
+        assert_eq!(
+            ask_block_backoff_timeout(timeout, max_timeout, 1),
+            Duration::from_millis(200)
+        );
+        assert_eq!(
+            ask_block_backoff_timeout(timeout, max_timeout, 3),
+            Duration::from_millis(800)
+        );
+        assert_eq!(
+            ask_block_backoff_timeout(timeout, max_timeout, 4),
+            max_timeout
+        );
+        assert_eq!(
+            ask_block_backoff_timeout(timeout, max_timeout, 64),
+            max_timeout
+        );
+
+        // no backoff when the max timeout is below the timeout
+        assert_eq!(
+            ask_block_backoff_timeout(timeout, Duration::ZERO, 5),
+            timeout
+        );
+    }
+}
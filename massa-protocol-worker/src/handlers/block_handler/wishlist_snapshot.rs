@@ -0,0 +1,355 @@
+//! Persistence of the block wishlist across restarts.
+//!
+//! When the retrieval thread stops, it saves the blocks it is retrieving along with what it already
+//! received of them: header, operation ids and operations. When it starts again, it loads the snapshot
+//! unless it is older than `block_wishlist_max_age`, so that the retrieval resumes where it stopped
+//! instead of waiting for consensus to derive the missing blocks again. A snapshot is loaded only once.
+//!
+//! The blocks are stored as block messages, so that they are read with the limits of the network:
+//! `version | saved_at | (message length | message)*`.
+
+use std::{fs, io::ErrorKind, ops::Bound::Included, path::Path};
+
+use massa_models::{
+    block_header::SecuredHeader,
+    block_id::BlockId,
+    operation::{
+        compute_operations_hash, OperationId, OperationIdSerializer, SecureShareOperation,
+    },
+    prehash::{PreHashMap, PreHashSet},
+};
+use massa_protocol_exports::ProtocolError;
+use massa_serialization::{
+    DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use massa_time::MassaTime;
+
+use super::{
+    messages::{AskForBlockInfo, BlockInfoReply, BlockMessage, BlockMessageDeserializer},
+    retrieval::BlockInfo,
+    BlockMessageSerializer,
+};
+
+/// Version of the snapshot format
+const SNAPSHOT_VERSION: u64 = 0;
+
+/// What was received of a block before the snapshot was saved
+#[derive(Debug, Default)]
+pub(crate) struct RestoredBlock {
+    /// header of the block, if received
+    pub(crate) header: Option<SecuredHeader>,
+    /// operation ids of the block, if received
+    pub(crate) operation_ids: Option<Vec<OperationId>>,
+    /// operations of the block received so far
+    pub(crate) operations: Vec<SecureShareOperation>,
+}
+
+impl RestoredBlock {
+    /// Drops the data that does not match the block id or the header.
+    /// Returns false if the block must be forgotten.
+    fn check(&mut self, block_id: &BlockId, last_start_period: u64) -> bool {
+        let Some(header) = &self.header else {
+            // only the id of the block is known
+            self.operation_ids = None;
+            self.operations.clear();
+            return true;
+        };
+        if header.id != *block_id || header.content.slot.period < last_start_period {
+            return false;
+        }
+        match &self.operation_ids {
+            Some(operation_ids)
+                if compute_operations_hash(operation_ids, &OperationIdSerializer::new())
+                    == header.content.operation_merkle_root =>
+            {
+                let operation_ids: PreHashSet<OperationId> =
+                    operation_ids.iter().copied().collect();
+                self.operations
+                    .retain(|operation| operation_ids.contains(&operation.id));
+            }
+            _ => {
+                self.operation_ids = None;
+                self.operations.clear();
+            }
+        }
+        true
+    }
+}
+
+fn invalid_snapshot(err: impl std::fmt::Display) -> ProtocolError {
+    ProtocolError::GeneralProtocolError(format!("invalid block wishlist snapshot: {}", err))
+}
+
+/// Saves the blocks of the wishlist and what was received of them to `path`
+pub(crate) fn save_wishlist(
+    path: &Path,
+    wishlist: &PreHashMap<BlockId, BlockInfo>,
+    now: MassaTime,
+) -> Result<(), ProtocolError> {
+    let message_serializer = BlockMessageSerializer::new();
+    let u64_serializer = U64VarIntSerializer::new();
+    let mut buffer = Vec::new();
+    let serialize_u64 = |value: u64, buffer: &mut Vec<u8>| {
+        u64_serializer
+            .serialize(&value, buffer)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))
+    };
+    serialize_u64(SNAPSHOT_VERSION, &mut buffer)?;
+    serialize_u64(now.as_millis(), &mut buffer)?;
+    for (block_id, info) in wishlist {
+        let mut messages = Vec::with_capacity(3);
+        messages.push(match &info.header {
+            Some(header) => BlockMessage::DataResponse {
+                block_id: *block_id,
+                sequence: None,
+                block_info: BlockInfoReply::Header(header.clone()),
+            },
+            None => BlockMessage::DataRequest {
+                block_id: *block_id,
+                sequence: None,
+                block_info: AskForBlockInfo::Header,
+            },
+        });
+        if let Some(operation_ids) = &info.operation_ids {
+            let operations: Vec<SecureShareOperation> = {
+                let stored_operations = info.storage.read_operations();
+                operation_ids
+                    .iter()
+                    .filter_map(|id| stored_operations.get(id).cloned())
+                    .collect()
+            };
+            messages.push(BlockMessage::DataResponse {
+                block_id: *block_id,
+                sequence: None,
+                block_info: BlockInfoReply::OperationIds(operation_ids.clone()),
+            });
+            if !operations.is_empty() {
+                messages.push(BlockMessage::DataResponse {
+                    block_id: *block_id,
+                    sequence: None,
+                    block_info: BlockInfoReply::Operations(operations),
+                });
+            }
+        }
+        for message in messages {
+            let mut message_buffer = Vec::new();
+            message_serializer
+                .serialize(&message, &mut message_buffer)
+                .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+            serialize_u64(message_buffer.len() as u64, &mut buffer)?;
+            buffer.extend(message_buffer);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // write then rename, so that a crash never leaves a truncated snapshot
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, &buffer)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Loads and deletes the snapshot at `path`.
+/// Returns no block if there is no snapshot or if it was saved more than `max_age` before `now`.
+/// Blocks of periods before `last_start_period` are dropped.
+pub(crate) fn load_wishlist(
+    path: &Path,
+    message_deserializer: &BlockMessageDeserializer,
+    now: MassaTime,
+    max_age: MassaTime,
+    last_start_period: u64,
+) -> Result<PreHashMap<BlockId, RestoredBlock>, ProtocolError> {
+    let buffer = match fs::read(path) {
+        Ok(buffer) => buffer,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(PreHashMap::default()),
+        Err(err) => return Err(err.into()),
+    };
+    fs::remove_file(path)?;
+
+    let u64_deserializer = U64VarIntDeserializer::new(Included(0), Included(u64::MAX));
+    let (rest, version) = u64_deserializer
+        .deserialize::<DeserializeError>(&buffer)
+        .map_err(invalid_snapshot)?;
+    if version != SNAPSHOT_VERSION {
+        return Err(invalid_snapshot(format!("unsupported version {}", version)));
+    }
+    let (mut rest, saved_at) = u64_deserializer
+        .deserialize::<DeserializeError>(rest)
+        .map_err(invalid_snapshot)?;
+    if now.saturating_sub(MassaTime::from_millis(saved_at)) > max_age {
+        return Ok(PreHashMap::default());
+    }
+
+    let mut blocks: PreHashMap<BlockId, RestoredBlock> = PreHashMap::default();
+    while !rest.is_empty() {
+        let (message_start, length) = u64_deserializer
+            .deserialize::<DeserializeError>(rest)
+            .map_err(invalid_snapshot)?;
+        if length > message_start.len() as u64 {
+            return Err(invalid_snapshot("truncated message"));
+        }
+        let (message_bytes, next) = message_start.split_at(length as usize);
+        let (left, message) = message_deserializer
+            .deserialize::<DeserializeError>(message_bytes)
+            .map_err(invalid_snapshot)?;
+        if !left.is_empty() {
+            return Err(invalid_snapshot("message not fully consumed"));
+        }
+        rest = next;
+        match message {
+            BlockMessage::Header(header) => {
+                blocks.entry(header.id).or_default().header = Some(header);
+            }
+            BlockMessage::DataRequest { block_id, .. } => {
+                blocks.entry(block_id).or_default();
+            }
+            BlockMessage::DataResponse {
+                block_id,
+                block_info,
+                ..
+            } => {
+                let block = blocks.entry(block_id).or_default();
+                match block_info {
+                    BlockInfoReply::Header(header) => block.header = Some(header),
+                    BlockInfoReply::OperationIds(operation_ids) => {
+                        block.operation_ids = Some(operation_ids)
+                    }
+                    BlockInfoReply::Operations(operations)
+                    | BlockInfoReply::CompressedOperations(operations) => {
+                        block.operations.extend(operations)
+                    }
+                    BlockInfoReply::NotFound => {}
+                }
+            }
+        }
+    }
+    blocks.retain(|block_id, block| block.check(block_id, last_start_period));
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::block_handler::messages::BlockMessageDeserializerArgs;
+    use massa_models::config::{
+        CHAINID, ENDORSEMENT_COUNT, MAX_DATASTORE_VALUE_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        MAX_FUNCTION_NAME_LENGTH, MAX_MESSAGE_SIZE, MAX_OPERATIONS_PER_BLOCK,
+        MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE, THREAD_COUNT,
+    };
+    use massa_models::slot::Slot;
+    use massa_protocol_exports::test_exports::tools::{
+        create_block_with_operations, create_operation_with_expire_period,
+    };
+    use massa_signature::KeyPair;
+    use massa_storage::Storage;
+
+    fn message_deserializer() -> BlockMessageDeserializer {
+        BlockMessageDeserializer::new(BlockMessageDeserializerArgs {
+            thread_count: THREAD_COUNT,
+            endorsement_count: ENDORSEMENT_COUNT,
+            max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+            max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+            max_parameters_size: MAX_PARAMETERS_SIZE,
+            max_op_datastore_entry_count: MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            max_op_datastore_key_length: MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+            max_denunciations_in_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            last_start_period: Some(0),
+            chain_id: *CHAINID,
+            max_decompressed_operations_size: MAX_MESSAGE_SIZE as u64,
+        })
+    }
+
+    #[test]
+    fn test_wishlist_snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("protocol/block_wishlist.bin");
+        let keypair = KeyPair::generate(0).unwrap();
+        let operations: Vec<_> = (0..3)
+            .map(|i| create_operation_with_expire_period(&keypair, 500 + i))
+            .collect();
+        let block = create_block_with_operations(&keypair, Slot::new(1, 0), operations.clone());
+        let unknown_block_id = create_block_with_operations(&keypair, Slot::new(2, 0), vec![]).id;
+
+        // the header, operation ids and one operation of the block were received
+        let mut storage = Storage::create_root();
+        storage.store_operations(vec![operations[1].clone()]);
+        let mut wishlist = PreHashMap::default();
+        wishlist.insert(
+            block.id,
+            BlockInfo {
+                header: Some(block.content.header.clone()),
+                operation_ids: Some(block.content.operations.clone()),
+                storage: storage.clone(),
+            },
+        );
+        wishlist.insert(
+            unknown_block_id,
+            BlockInfo {
+                header: None,
+                operation_ids: None,
+                storage: storage.clone_without_refs(),
+            },
+        );
+        let saved_at = MassaTime::from_millis(1_000_000);
+        save_wishlist(&path, &wishlist, saved_at).unwrap();
+
+        let restored = load_wishlist(
+            &path,
+            &message_deserializer(),
+            saved_at.saturating_add(MassaTime::from_millis(1000)),
+            MassaTime::from_millis(60000),
+            0,
+        )
+        .unwrap();
+        assert_eq!(restored.len(), 2);
+        let restored_block = &restored[&block.id];
+        assert_eq!(
+            restored_block.header.as_ref().map(|header| header.id),
+            Some(block.id)
+        );
+        assert_eq!(
+            restored_block.operation_ids.as_ref(),
+            Some(&block.content.operations)
+        );
+        assert_eq!(
+            restored_block
+                .operations
+                .iter()
+                .map(|op| op.id)
+                .collect::<Vec<_>>(),
+            vec![operations[1].id]
+        );
+        assert!(restored[&unknown_block_id].header.is_none());
+        // the snapshot is loaded only once
+        assert!(!path.exists());
+
+        // stale snapshots are ignored
+        save_wishlist(&path, &wishlist, saved_at).unwrap();
+        let restored = load_wishlist(
+            &path,
+            &message_deserializer(),
+            saved_at.saturating_add(MassaTime::from_millis(60001)),
+            MassaTime::from_millis(60000),
+            0,
+        )
+        .unwrap();
+        assert!(restored.is_empty());
+
+        // blocks before the last start period are dropped
+        save_wishlist(&path, &wishlist, saved_at).unwrap();
+        let restored = load_wishlist(
+            &path,
+            &message_deserializer(),
+            saved_at,
+            MassaTime::from_millis(60000),
+            2,
+        )
+        .unwrap();
+        assert_eq!(restored.keys().collect::<Vec<_>>(), vec![&unknown_block_id]);
+    }
+}
@@ -0,0 +1,106 @@
+//! Sending of item sets (operations, endorsements, ids) split into messages of bounded size,
+//! so that the handlers can send sets of any size without exceeding the per-message limits of the peers.
+
+use massa_protocol_exports::{PeerId, ProtocolError};
+
+use crate::{
+    messages::{Message, MessagesSerializer},
+    wrap_network::ActiveConnectionsTrait,
+};
+
+/// Sends `items` to a peer in as few messages of at most `max_items_per_message` items as possible.
+/// `make_message` builds the message of a chunk, and `on_sent` is called with each chunk sent successfully.
+///
+/// Stops at the first chunk that could not be sent and returns the error:
+/// the following chunks would fail too if the peer is disconnected or congested.
+pub(crate) fn send_chunked<T>(
+    active_connections: &dyn ActiveConnectionsTrait,
+    peer_id: &PeerId,
+    message_serializer: &MessagesSerializer,
+    items: &[T],
+    max_items_per_message: u64,
+    mut make_message: impl FnMut(&[T]) -> Message,
+    mut on_sent: impl FnMut(&[T]),
+) -> Result<(), ProtocolError> {
+    // a limit of 0 would never let anything through: send the items one by one instead
+    let chunk_size = usize::try_from(max_items_per_message)
+        .unwrap_or(usize::MAX)
+        .max(1);
+    for chunk in items.chunks(chunk_size) {
+        active_connections.send_to_peer(peer_id, message_serializer, make_message(chunk), false)?;
+        on_sent(chunk);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use massa_models::operation::OperationPrefixId;
+    use massa_signature::KeyPair;
+
+    use super::*;
+    use crate::{
+        handlers::operation_handler::OperationMessage,
+        wrap_network::MockActiveConnectionsTraitWrapper,
+    };
+
+    #[test]
+    fn test_send_chunked() {
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let sent_sizes = Arc::new(Mutex::new(Vec::new()));
+        let mut active_connections = MockActiveConnectionsTraitWrapper::new();
+        let sent = sent_sizes.clone();
+        active_connections.set_expectations(move |active_connections| {
+            active_connections.expect_send_to_peer().returning(
+                move |_, _, message, _| match message {
+                    Message::Operation(OperationMessage::AskForOperations(ids)) => {
+                        sent.lock().unwrap().push(ids.len());
+                        Ok(())
+                    }
+                    _ => panic!("Unexpected message type."),
+                },
+            );
+        });
+        let serializer = MessagesSerializer::new();
+        let items: Vec<u8> = (0..7).collect();
+        let make_message = |chunk: &[u8]| -> Message {
+            OperationMessage::AskForOperations(
+                chunk
+                    .iter()
+                    .map(|i| OperationPrefixId::from(&[*i; 17]))
+                    .collect(),
+            )
+            .into()
+        };
+
+        let mut on_sent_items = 0;
+        send_chunked(
+            &active_connections,
+            &peer_id,
+            &serializer,
+            &items,
+            3,
+            make_message,
+            |chunk| on_sent_items += chunk.len(),
+        )
+        .unwrap();
+        assert_eq!(*sent_sizes.lock().unwrap(), vec![3, 3, 1]);
+        assert_eq!(on_sent_items, 7);
+
+        // a limit of 0 sends the items one by one
+        sent_sizes.lock().unwrap().clear();
+        send_chunked(
+            &active_connections,
+            &peer_id,
+            &serializer,
+            &items[..2],
+            0,
+            make_message,
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(*sent_sizes.lock().unwrap(), vec![1, 1]);
+    }
+}
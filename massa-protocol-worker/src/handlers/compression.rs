@@ -0,0 +1,89 @@
+//! Compressed payloads of the messages carrying full operations, which are mostly bytecode
+//! and compress well. A payload is written as `varint(decompressed size) || varint(compressed size) || lz4 block`.
+//!
+//! Compression only saves bandwidth: block sizes are still accounted on the serialized
+//! operations. Accounting them on the compressed form would let more operations fit in a block,
+//! but it cannot be validated identically by every node: lz4 output is not canonical (it depends
+//! on the encoder and its version), and consensus only holds the operation ids of a block, so the
+//! factory, the pool and the block retrieval would each have to agree on an encoding they do not
+//! control. The limits are therefore always checked on the decompressed operations.
+
+use massa_serialization::{
+    DeserializeError, Deserializer, SerializeError, Serializer, U64VarIntDeserializer,
+    U64VarIntSerializer,
+};
+use nom::{
+    error::{ContextError, ParseError},
+    IResult,
+};
+use std::ops::Bound::Included;
+
+/// Compresses the serialized payload `raw` and writes it to `buffer`
+pub(crate) fn serialize_compressed(raw: &[u8], buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+    let length_serializer = U64VarIntSerializer::new();
+    let compressed = lz4_flex::block::compress(raw);
+    length_serializer.serialize(&(raw.len() as u64), buffer)?;
+    length_serializer.serialize(&(compressed.len() as u64), buffer)?;
+    buffer.extend(compressed);
+    Ok(())
+}
+
+/// Reads payloads written by `serialize_compressed`
+#[derive(Clone)]
+pub(crate) struct CompressedDeserializer {
+    decompressed_size_deserializer: U64VarIntDeserializer,
+    compressed_size_deserializer: U64VarIntDeserializer,
+}
+
+impl CompressedDeserializer {
+    /// `max_decompressed_size` bounds the size of the payload once decompressed
+    pub(crate) fn new(max_decompressed_size: u64) -> Self {
+        Self {
+            decompressed_size_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(max_decompressed_size),
+            ),
+            compressed_size_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(
+                    lz4_flex::block::get_maximum_output_size(max_decompressed_size as usize) as u64,
+                ),
+            ),
+        }
+    }
+
+    /// Decompresses a payload and reads it with `content_deserializer`.
+    /// The announced decompressed size is bounded before anything is allocated,
+    /// and the decompressed data must match it exactly and be read entirely.
+    pub(crate) fn deserialize<'a, T, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        content_deserializer: &impl Deserializer<T>,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], T, E> {
+        let (rest, decompressed_size) = self.decompressed_size_deserializer.deserialize(buffer)?;
+        let (rest, compressed_size) = self.compressed_size_deserializer.deserialize(rest)?;
+        let compressed_size = compressed_size as usize;
+        if rest.len() < compressed_size {
+            return Err(nom::Err::Error(ParseError::from_error_kind(
+                rest,
+                nom::error::ErrorKind::Eof,
+            )));
+        }
+        let (compressed, rest) = rest.split_at(compressed_size);
+        let invalid = || {
+            nom::Err::Error(ParseError::from_error_kind(
+                buffer,
+                nom::error::ErrorKind::Verify,
+            ))
+        };
+        let decompressed = lz4_flex::block::decompress(compressed, decompressed_size as usize)
+            .map_err(|_| invalid())?;
+        if decompressed.len() as u64 != decompressed_size {
+            return Err(invalid());
+        }
+        match content_deserializer.deserialize::<DeserializeError>(&decompressed) {
+            Ok((remaining, content)) if remaining.is_empty() => Ok((rest, content)),
+            _ => Err(invalid()),
+        }
+    }
+}
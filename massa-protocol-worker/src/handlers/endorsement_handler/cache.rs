@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use massa_models::endorsement::EndorsementId;
@@ -8,23 +9,35 @@ use massa_protocol_exports::PeerId;
 use parking_lot::RwLock;
 use schnellru::{ByLength, LruMap};
 
+use crate::handlers::peer_knowledge::RetainedPeerKnowledge;
+
 /// Cache of endorsements
 pub struct EndorsementCache {
     /// List of endorsements we checked recently
     pub checked_endorsements: LruMap<EndorsementId, ()>,
+    /// Peer that first sent us each checked endorsement, to credit it once the endorsement is final
+    pub first_senders: LruMap<EndorsementId, PeerId>,
     /// List of endorsements known by peers
     pub endorsements_known_by_peer: HashMap<PeerId, LruMap<EndorsementId, ()>>,
     /// Maximum number of endorsements known by a peer
     pub max_known_endorsements_by_peer: u32,
+    /// Endorsements known by the recently disconnected peers
+    retained_knowledge: RetainedPeerKnowledge<LruMap<EndorsementId, ()>>,
 }
 
 impl EndorsementCache {
     /// Create a new EndorsementCache
-    pub fn new(max_known_endorsements: u32, max_known_endorsements_by_peer: u32) -> Self {
+    pub fn new(
+        max_known_endorsements: u32,
+        max_known_endorsements_by_peer: u32,
+        peer_knowledge_retention: Duration,
+    ) -> Self {
         Self {
             checked_endorsements: LruMap::new(ByLength::new(max_known_endorsements)),
+            first_senders: LruMap::new(ByLength::new(max_known_endorsements)),
             endorsements_known_by_peer: HashMap::new(),
             max_known_endorsements_by_peer,
+            retained_knowledge: RetainedPeerKnowledge::new(peer_knowledge_retention),
         }
     }
 
@@ -50,9 +63,12 @@ impl EndorsementCache {
 
     /// Update caches to remove all data from disconnected peers
     pub fn update_cache(&mut self, peers_connected: &HashSet<PeerId>) {
-        // Remove disconnected peers from cache
-        self.endorsements_known_by_peer
-            .retain(|peer_id, _| peers_connected.contains(peer_id));
+        // Remove disconnected peers from cache, restore the knowledge of the peers that reconnected quickly
+        self.retained_knowledge.update(
+            &mut self.endorsements_known_by_peer,
+            peers_connected,
+            Instant::now(),
+        );
 
         // Add new connected peers to cache
         for peer_id in peers_connected {
@@ -66,6 +82,23 @@ impl EndorsementCache {
             }
         }
     }
+
+    /// Approximate memory used by the cache, in bytes
+    pub fn memory_usage(&self) -> usize {
+        let known_by_peer = self.endorsements_known_by_peer.capacity()
+            * std::mem::size_of::<(PeerId, LruMap<EndorsementId, ()>)>()
+            + self
+                .endorsements_known_by_peer
+                .values()
+                .map(|known_endorsements| known_endorsements.memory_usage())
+                .sum::<usize>();
+        self.checked_endorsements.memory_usage()
+            + self.first_senders.memory_usage()
+            + known_by_peer
+            + self
+                .retained_knowledge
+                .memory_usage(|known_endorsements| known_endorsements.memory_usage())
+    }
 }
 
 pub type SharedEndorsementCache = Arc<RwLock<EndorsementCache>>;
@@ -1,3 +1,7 @@
+use massa_time::MassaTime;
+
 pub enum EndorsementHandlerRetrievalCommand {
+    /// Lag of the node behind the network, notified by consensus
+    SyncLag(MassaTime),
     Stop,
 }
@@ -2,10 +2,12 @@ use massa_models::{
     endorsement::{Endorsement, EndorsementDeserializer, SecureShareEndorsement},
     secure_share::{SecureShareDeserializer, SecureShareSerializer},
 };
-use massa_serialization::{Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
+use massa_serialization::{
+    Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer, VecDeserializer,
+    VecSerializer,
+};
 use nom::{
     error::{context, ContextError, ParseError},
-    multi::length_count,
     IResult, Parser,
 };
 use num_enum::{IntoPrimitive, TryFromPrimitive};
@@ -31,23 +33,27 @@ impl From<&EndorsementMessage> for MessageTypeId {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct EndorsementMessageSerializer {
     id_serializer: U64VarIntSerializer,
-    length_endorsements_serializer: U64VarIntSerializer,
-    secure_share_serializer: SecureShareSerializer,
+    endorsements_serializer: VecSerializer<SecureShareEndorsement, SecureShareSerializer>,
 }
 
 impl EndorsementMessageSerializer {
     pub fn new() -> Self {
         Self {
             id_serializer: U64VarIntSerializer::new(),
-            length_endorsements_serializer: U64VarIntSerializer::new(),
-            secure_share_serializer: SecureShareSerializer::new(),
+            endorsements_serializer: VecSerializer::new(SecureShareSerializer::new()),
         }
     }
 }
 
+impl Default for EndorsementMessageSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Serializer<EndorsementMessage> for EndorsementMessageSerializer {
     fn serialize(
         &self,
@@ -58,12 +64,8 @@ impl Serializer<EndorsementMessage> for EndorsementMessageSerializer {
             .serialize(&MessageTypeId::from(value).into(), buffer)?;
         match value {
             EndorsementMessage::Endorsements(endorsements) => {
-                self.length_endorsements_serializer
-                    .serialize(&(endorsements.len() as u64), buffer)?;
-                for endorsement in endorsements {
-                    self.secure_share_serializer
-                        .serialize(endorsement, buffer)?;
-                }
+                self.endorsements_serializer
+                    .serialize(endorsements, buffer)?;
             }
         }
         Ok(())
@@ -79,22 +81,24 @@ pub struct EndorsementMessageDeserializerArgs {
 
 pub struct EndorsementMessageDeserializer {
     id_deserializer: U64VarIntDeserializer,
-    length_endorsements_deserializer: U64VarIntDeserializer,
-    secure_share_deserializer: SecureShareDeserializer<Endorsement, EndorsementDeserializer>,
+    endorsements_deserializer: VecDeserializer<
+        SecureShareEndorsement,
+        SecureShareDeserializer<Endorsement, EndorsementDeserializer>,
+    >,
 }
 
 impl EndorsementMessageDeserializer {
     pub fn new(args: EndorsementMessageDeserializerArgs) -> Self {
         Self {
             id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
-            length_endorsements_deserializer: U64VarIntDeserializer::new(
+            endorsements_deserializer: VecDeserializer::new(
+                SecureShareDeserializer::new(
+                    EndorsementDeserializer::new(args.thread_count, args.endorsement_count),
+                    args.chain_id,
+                ),
                 Included(0),
                 Included(args.max_length_endorsements),
             ),
-            secure_share_deserializer: SecureShareDeserializer::new(
-                EndorsementDeserializer::new(args.thread_count, args.endorsement_count),
-                args.chain_id,
-            ),
         }
     }
 }
@@ -113,19 +117,13 @@ impl Deserializer<EndorsementMessage> for EndorsementMessageDeserializer {
                 ))
             })?;
             match id {
-                MessageTypeId::Endorsements => context(
-                    "Failed Endorsements deserialization",
-                    length_count(
-                        context("Failed length deserialization", |input| {
-                            self.length_endorsements_deserializer.deserialize(input)
-                        }),
-                        context("Failed endorsement deserialization", |input| {
-                            self.secure_share_deserializer.deserialize(input)
-                        }),
-                    ),
-                )
-                .map(EndorsementMessage::Endorsements)
-                .parse(buffer),
+                MessageTypeId::Endorsements => {
+                    context("Failed Endorsements deserialization", |input| {
+                        self.endorsements_deserializer.deserialize(input)
+                    })
+                    .map(EndorsementMessage::Endorsements)
+                    .parse(buffer)
+                }
             }
         })
         .parse(buffer)
@@ -18,7 +18,7 @@ use self::{
 pub mod cache;
 pub mod commands_propagation;
 pub mod commands_retrieval;
-mod messages;
+pub(crate) mod messages;
 mod propagation;
 mod retrieval;
 
@@ -65,11 +65,16 @@ impl EndorsementHandler {
             pool_controller,
             config.clone(),
             storage.clone_without_refs(),
-            massa_metrics,
+            massa_metrics.clone(),
         );
 
-        let endorsement_propagation_thread =
-            start_propagation_thread(local_receiver, cache, config, active_connections);
+        let endorsement_propagation_thread = start_propagation_thread(
+            local_receiver,
+            cache,
+            config,
+            active_connections,
+            massa_metrics,
+        );
         Self {
             endorsement_retrieval_thread: Some((
                 sender_retrieval_ext,
@@ -2,40 +2,66 @@ use super::{
     cache::SharedEndorsementCache, commands_propagation::EndorsementHandlerPropagationCommand,
     messages::EndorsementMessageSerializer, EndorsementMessage,
 };
-use crate::{messages::MessagesSerializer, wrap_network::ActiveConnectionsTrait};
+use crate::{
+    handlers::chunked::send_chunked, messages::MessagesSerializer,
+    wrap_network::ActiveConnectionsTrait,
+};
+use crossbeam::channel::RecvTimeoutError;
 use massa_channel::receiver::MassaReceiver;
+use massa_metrics::MassaMetrics;
 use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
-use std::thread::JoinHandle;
+use std::{thread::JoinHandle, time::Instant};
 use tracing::{info, log::warn};
 
 // protocol-endorsement-handler-propagation
 const THREAD_NAME: &str = "peh-propagation";
 static_assertions::const_assert!(THREAD_NAME.len() < 16);
 
-/// Endorsements need to propagate fast, so no buffering
+/// Endorsements need to propagate fast, so they are only buffered for a short interval
+/// (`endorsement_propagation_interval`) to be sent in a single batch per peer near slot boundaries
 struct PropagationThread {
     receiver: MassaReceiver<EndorsementHandlerPropagationCommand>,
     config: ProtocolConfig,
     cache: SharedEndorsementCache,
     active_connections: Box<dyn ActiveConnectionsTrait>,
     endorsement_serializer: MessagesSerializer,
+    massa_metrics: MassaMetrics,
 }
 
 impl PropagationThread {
     fn run(&mut self) {
+        let interval = self.config.endorsement_propagation_interval.to_duration();
+        // endorsements waiting for the end of the interval, and the end of the interval
+        let mut buffer: Option<(Storage, Instant)> = None;
         let mut next_message = None;
         loop {
             // get the next message to process
             let msg = match next_message.take() {
                 Some(msg) => msg,
-                None => match self.receiver.recv() {
-                    Ok(msg) => msg,
-                    Err(_) => {
-                        info!("Stop endorsement propagation thread");
-                        return;
+                None => {
+                    let received = match &buffer {
+                        Some((_, deadline)) => self.receiver.recv_deadline(*deadline),
+                        None => self
+                            .receiver
+                            .recv()
+                            .map_err(|_| RecvTimeoutError::Disconnected),
+                    };
+                    match received {
+                        Ok(msg) => msg,
+                        Err(RecvTimeoutError::Timeout) => {
+                            // end of the interval: propagate the buffered endorsements
+                            if let Some((endorsements, _)) = buffer.take() {
+                                self.propagate_endorsements(endorsements);
+                            }
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => {
+                            info!("Stop endorsement propagation thread");
+                            return;
+                        }
                     }
-                },
+                }
             };
 
             match msg {
@@ -57,8 +83,16 @@ impl PropagationThread {
                             }
                         }
                     }
-                    // propagate the endorsements
-                    self.propagate_endorsements(endorsements);
+                    if interval.is_zero() {
+                        // propagate the endorsements
+                        self.propagate_endorsements(endorsements);
+                    } else {
+                        // buffer the endorsements until the end of the interval
+                        match &mut buffer {
+                            Some((buffered, _)) => buffered.extend(endorsements),
+                            None => buffer = Some((endorsements, Instant::now() + interval)),
+                        }
+                    }
                 }
                 // stop the handler
                 EndorsementHandlerPropagationCommand::Stop => {
@@ -115,25 +149,30 @@ impl PropagationThread {
             }
 
             // send by chunks
-            for chunk in to_send.chunks(self.config.max_endorsements_per_message as usize) {
-                if let Err(err) = self.active_connections.send_to_peer(
-                    &peer_id,
-                    &self.endorsement_serializer,
+            if let Err(err) = send_chunked(
+                self.active_connections.as_ref(),
+                &peer_id,
+                &self.endorsement_serializer,
+                &to_send,
+                self.config.max_endorsements_per_message,
+                |chunk| {
                     EndorsementMessage::Endorsements(chunk.iter().map(|&e| e.clone()).collect())
-                        .into(),
-                    false,
-                ) {
-                    warn!(
-                        "could not send endorsements batch to node {}: {}",
-                        peer_id, err
-                    );
-                    // try with next peer, this one is probably congested
-                    continue 'peer_loop;
-                }
-                // sent successfully: mark peer as knowing the endorsements that were sent to it
-                for endorsement in chunk {
-                    peer_knowledge.insert(endorsement.id, ());
-                }
+                        .into()
+                },
+                |chunk| {
+                    // sent successfully: mark peer as knowing the endorsements that were sent to it
+                    self.massa_metrics
+                        .inc_protocol_items_sent("endorsement", chunk.len());
+                    for endorsement in chunk {
+                        peer_knowledge.insert(endorsement.id, ());
+                    }
+                },
+            ) {
+                // try with next peer, this one is probably congested
+                warn!(
+                    "could not send endorsements batch to node {}: {}",
+                    peer_id, err
+                );
             }
         }
     }
@@ -144,6 +183,7 @@ pub fn start_propagation_thread(
     cache: SharedEndorsementCache,
     config: ProtocolConfig,
     active_connections: Box<dyn ActiveConnectionsTrait>,
+    massa_metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
         .name(THREAD_NAME.to_string())
@@ -156,6 +196,7 @@ pub fn start_propagation_thread(
                 active_connections,
                 cache,
                 endorsement_serializer,
+                massa_metrics,
             };
             propagation_thread.run();
         })
@@ -1,4 +1,4 @@
-use std::thread::JoinHandle;
+use std::{thread::JoinHandle, time::Instant};
 
 use crossbeam::{channel::tick, select};
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
@@ -21,7 +21,9 @@ use tracing::{debug, info, warn};
 use crate::{
     handlers::{
         endorsement_handler::messages::EndorsementMessage,
+        message_timer::MessageTimer,
         peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+        rate_limiter::{PeerRateLimiter, RateLimitOutcome},
     },
     sig_verifier::verify_sigs_batch,
 };
@@ -49,19 +51,30 @@ pub struct RetrievalThread {
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     metrics: MassaMetrics,
     endorsement_message_deserializer: EndorsementMessageDeserializer,
+    /// the node lags too far behind the network to process received endorsements
+    syncing: bool,
+    /// limits the number of endorsements received from each peer
+    rate_limiter: PeerRateLimiter,
 }
 
 impl RetrievalThread {
     fn run(&mut self) {
         // regular interval ticks for metrics
         let tick_metrics = tick(self.metrics.tick_delay);
+        // owned by the loop so that the message timers do not borrow `self`
+        let metrics = self.metrics.clone();
+        let slow_message_threshold = self.config.slow_message_threshold.to_duration();
 
         loop {
             select! {
                 recv(self.receiver) -> msg => {
                     self.receiver.update_metrics();
                     match msg {
-                        Ok((peer_id, message)) => self.process_message(peer_id, message),
+                        Ok((peer_id, message)) => {
+                            // endorsement messages have a single type
+                            let _timer = MessageTimer::start(&metrics, slow_message_threshold, peer_id, "Endorsement::Endorsements");
+                            self.process_message(peer_id, message)
+                        }
                         Err(_) => {
                             info!("Stop endorsement retrieval thread");
                             return;
@@ -73,6 +86,17 @@ impl RetrievalThread {
                     match msg {
                         Ok(msg) => {
                             match msg {
+                                EndorsementHandlerRetrievalCommand::SyncLag(lag) => {
+                                    let syncing = lag > self.config.max_sync_lag_for_gossip;
+                                    if syncing != self.syncing {
+                                        if syncing {
+                                            info!("Node is syncing (lag: {}), received endorsements will not be processed", lag);
+                                        } else {
+                                            info!("Node is synced, resuming the processing of received endorsements");
+                                        }
+                                        self.syncing = syncing;
+                                    }
+                                }
                                 EndorsementHandlerRetrievalCommand::Stop => {
                                     info!("Stop endorsement retrieval thread");
                                     return;
@@ -95,6 +119,10 @@ impl RetrievalThread {
                         .sum();
                     self.metrics
                         .set_endorsements_cache_metrics(cache_lock.checked_endorsements.len(), count);
+                    self.metrics
+                        .set_memory_usage("protocol_endorsement_cache", cache_lock.memory_usage());
+                    drop(cache_lock);
+                    self.rate_limiter.prune(Instant::now());
                 }
             }
         }
@@ -122,6 +150,36 @@ impl RetrievalThread {
         match message {
             EndorsementMessage::Endorsements(endorsements) => {
                 debug!("Received endorsement message: Endorsement from {}", peer_id);
+                if self.syncing {
+                    // the endorsements would be outdated before we catch up with the network
+                    return;
+                }
+                match self
+                    .rate_limiter
+                    .consume(&peer_id, endorsements.len(), Instant::now())
+                {
+                    RateLimitOutcome::Allowed => {}
+                    RateLimitOutcome::Throttled => {
+                        debug!(
+                            "dropping {} endorsements from throttled peer {}",
+                            endorsements.len(),
+                            peer_id
+                        );
+                        return;
+                    }
+                    RateLimitOutcome::Ban => {
+                        warn!(
+                            "peer {} keeps flooding us with endorsements above the rate limit",
+                            peer_id
+                        );
+                        if let Err(err) = self.ban_peer(&peer_id) {
+                            warn!("Error while banning peer {} err: {:?}", peer_id, err);
+                        }
+                        return;
+                    }
+                }
+                self.metrics
+                    .inc_protocol_items_received("endorsement", endorsements.len());
                 if let Err(err) = note_endorsements_from_peer(
                     endorsements,
                     &peer_id,
@@ -138,8 +196,19 @@ impl RetrievalThread {
                         loss of sync between us and the remote node. Err = {}",
                         peer_id, err
                     );
-                    if let Err(err) = self.ban_peer(&peer_id) {
-                        warn!("Error while banning peer {} err: {:?}", peer_id, err);
+                    self.metrics
+                        .inc_protocol_invalid_items(&peer_id.to_string(), "endorsement");
+                    if let Err(err) =
+                        self.peer_cmd_sender
+                            .try_send(PeerManagementCmd::NoteInvalidItem {
+                                peer_id,
+                                item_type: "endorsement",
+                            })
+                    {
+                        warn!(
+                            "Error while noting invalid endorsements from peer {} err: {:?}",
+                            peer_id, err
+                        );
                     }
                 }
             }
@@ -237,6 +306,13 @@ pub(crate) fn note_endorsements_from_peer(
             cache_write.insert_checked_endorsement(*endorsement_id);
         }
 
+        // the source node is the first one that sent us the new endorsements
+        for endorsement_id in new_endorsements.keys() {
+            cache_write
+                .first_senders
+                .insert(*endorsement_id, *from_peer_id);
+        }
+
         // add to the cache of endorsements known by the source node
         cache_write.insert_peer_known_endorsements(
             from_peer_id,
@@ -316,10 +392,12 @@ pub fn start_retrieval_thread(
                 internal_sender,
                 selector_controller,
                 pool_controller,
+                rate_limiter: PeerRateLimiter::new(config.endorsements_rate_limit),
                 config,
                 storage,
                 metrics,
                 endorsement_message_deserializer,
+                syncing: false,
             };
             retrieval_thread.run();
         })
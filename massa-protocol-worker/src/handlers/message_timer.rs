@@ -0,0 +1,59 @@
+//! Measurement of the time spent by the handlers on each received message, to find the
+//! messages blocking the processing of the following ones.
+
+use std::time::{Duration, Instant};
+
+use massa_metrics::MassaMetrics;
+use massa_protocol_exports::PeerId;
+use tracing::warn;
+
+/// Measures the processing time of a received message until it is dropped.
+/// The time is exported to the metrics, and a warning is logged if it exceeds the budget.
+pub(crate) struct MessageTimer<'a> {
+    metrics: &'a MassaMetrics,
+    budget: Duration,
+    peer_id: PeerId,
+    message_type: String,
+    start: Instant,
+}
+
+impl<'a> MessageTimer<'a> {
+    /// Starts measuring the processing of a message received from `peer_id`.
+    /// `message_type` is used until the message is deserialized and its exact type is known.
+    pub(crate) fn start(
+        metrics: &'a MassaMetrics,
+        budget: Duration,
+        peer_id: PeerId,
+        message_type: &str,
+    ) -> Self {
+        MessageTimer {
+            metrics,
+            budget,
+            peer_id,
+            message_type: message_type.to_string(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Sets the type of the message once deserialized
+    pub(crate) fn set_message_type(&mut self, message_type: String) {
+        self.message_type = message_type;
+    }
+}
+
+impl Drop for MessageTimer<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        self.metrics
+            .observe_protocol_message_processing_time(&self.message_type, elapsed.as_secs_f64());
+        if elapsed > self.budget {
+            warn!(
+                message_type = %self.message_type,
+                peer_id = %self.peer_id,
+                elapsed_ms = elapsed.as_millis() as u64,
+                budget_ms = self.budget.as_millis() as u64,
+                "slow processing of a received message"
+            );
+        }
+    }
+}
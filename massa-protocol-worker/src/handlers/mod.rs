@@ -1,4 +1,9 @@
 pub mod block_handler;
 pub mod endorsement_handler;
+pub(crate) mod chunked;
+pub(crate) mod compression;
+pub(crate) mod message_timer;
 pub mod operation_handler;
 pub mod peer_handler;
+pub(crate) mod peer_knowledge;
+pub(crate) mod rate_limiter;
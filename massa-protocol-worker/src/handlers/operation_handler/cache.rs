@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use massa_models::operation::{OperationId, OperationPrefixId};
@@ -8,38 +9,75 @@ use massa_protocol_exports::PeerId;
 use parking_lot::RwLock;
 use schnellru::{ByLength, LruMap};
 
+use super::known_ops_filter::KnownOpsFilter;
+use crate::handlers::peer_knowledge::RetainedPeerKnowledge;
+
 /// Cache for operations
 pub struct OperationCache {
     /// List of operations we checked recently
     pub checked_operations: LruMap<OperationId, ()>,
     /// List of operation ID prefixes we checked recently
     pub checked_operations_prefix: LruMap<OperationPrefixId, ()>,
-    /// List of operations known by peers
-    pub ops_known_by_peer: HashMap<PeerId, LruMap<OperationPrefixId, ()>>,
+    /// Operations known by peers
+    pub ops_known_by_peer: HashMap<PeerId, KnownOpsFilter>,
     /// Maximum number of operations known by a peer
-    pub max_known_ops_by_peer: u32,
+    pub max_known_ops_by_peer: usize,
+    /// Target false positive rate of the operations known by a peer
+    pub known_ops_false_positive_rate: f64,
+    /// Number of lookups of operations known by peers that were found
+    pub ops_known_by_peer_hits: u64,
+    /// Number of lookups of operations known by peers that were not found
+    pub ops_known_by_peer_misses: u64,
+    /// Operations known by the recently disconnected peers
+    retained_knowledge: RetainedPeerKnowledge<KnownOpsFilter>,
 }
 
 impl OperationCache {
     /// Create a new OperationCache
-    pub fn new(max_known_ops: u32, max_known_ops_by_peer: u32) -> Self {
+    pub fn new(
+        max_known_ops: u32,
+        max_known_ops_by_peer: usize,
+        known_ops_false_positive_rate: f64,
+        peer_knowledge_retention: Duration,
+    ) -> Self {
         Self {
             checked_operations: LruMap::new(ByLength::new(max_known_ops)),
             checked_operations_prefix: LruMap::new(ByLength::new(max_known_ops)),
             ops_known_by_peer: HashMap::new(),
             max_known_ops_by_peer,
+            known_ops_false_positive_rate,
+            ops_known_by_peer_hits: 0,
+            ops_known_by_peer_misses: 0,
+            retained_knowledge: RetainedPeerKnowledge::new(peer_knowledge_retention),
         }
     }
 
     /// Mark a list of operation ID prefixes as known by a peer
     pub fn insert_peer_known_ops(&mut self, peer_id: &PeerId, ops: &[OperationPrefixId]) {
-        let known_ops = self
-            .ops_known_by_peer
-            .entry(*peer_id)
-            .or_insert_with(|| LruMap::new(ByLength::new(self.max_known_ops_by_peer)));
+        let known_ops = self.ops_known_by_peer.entry(*peer_id).or_insert_with(|| {
+            KnownOpsFilter::new(
+                self.max_known_ops_by_peer,
+                self.known_ops_false_positive_rate,
+            )
+        });
         for op in ops {
-            known_ops.insert(*op, ());
+            known_ops.insert(op);
+        }
+    }
+
+    /// Returns true if the operation ID prefix is known by a peer, or in case of false positive.
+    /// Counts the hits and misses of the lookups.
+    pub fn peer_knows_op(&mut self, peer_id: &PeerId, op: &OperationPrefixId) -> bool {
+        let known = self
+            .ops_known_by_peer
+            .get(peer_id)
+            .map_or(false, |known_ops| known_ops.contains(op));
+        if known {
+            self.ops_known_by_peer_hits += 1;
+        } else {
+            self.ops_known_by_peer_misses += 1;
         }
+        known
     }
 
     /// Mark an operation ID as checked by us
@@ -51,20 +89,43 @@ impl OperationCache {
 
     /// Update caches to remove all data from disconnected peers
     pub fn update_cache(&mut self, peers_connected: &HashSet<PeerId>) {
-        // Remove disconnected peers from cache
-        self.ops_known_by_peer
-            .retain(|peer_id, _| peers_connected.contains(peer_id));
+        // Remove disconnected peers from cache, restore the knowledge of the peers that reconnected quickly
+        self.retained_knowledge.update(
+            &mut self.ops_known_by_peer,
+            peers_connected,
+            Instant::now(),
+        );
 
         // Add new connected peers to cache
         for peer_id in peers_connected {
             match self.ops_known_by_peer.entry(*peer_id) {
                 std::collections::hash_map::Entry::Occupied(_) => {}
                 std::collections::hash_map::Entry::Vacant(entry) => {
-                    entry.insert(LruMap::new(ByLength::new(self.max_known_ops_by_peer)));
+                    entry.insert(KnownOpsFilter::new(
+                        self.max_known_ops_by_peer,
+                        self.known_ops_false_positive_rate,
+                    ));
                 }
             }
         }
     }
+
+    /// Approximate memory used by the cache, in bytes
+    pub fn memory_usage(&self) -> usize {
+        let known_by_peer = self.ops_known_by_peer.capacity()
+            * std::mem::size_of::<(PeerId, KnownOpsFilter)>()
+            + self
+                .ops_known_by_peer
+                .values()
+                .map(|known_ops| known_ops.memory_usage())
+                .sum::<usize>();
+        self.checked_operations.memory_usage()
+            + self.checked_operations_prefix.memory_usage()
+            + known_by_peer
+            + self
+                .retained_knowledge
+                .memory_usage(|known_ops| known_ops.memory_usage())
+    }
 }
 
 pub type SharedOperationCache = Arc<RwLock<OperationCache>>;
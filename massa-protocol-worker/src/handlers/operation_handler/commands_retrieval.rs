@@ -1,4 +1,8 @@
+use massa_time::MassaTime;
+
 #[derive(Clone)]
 pub enum OperationHandlerRetrievalCommand {
+    /// Lag of the node behind the network, notified by consensus
+    SyncLag(MassaTime),
     Stop,
 }
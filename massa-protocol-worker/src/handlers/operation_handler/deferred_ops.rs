@@ -0,0 +1,87 @@
+//! Batches of operations received while block messages are waiting to be processed:
+//! the block messages go first, and the operations are processed once they are handled.
+
+use std::collections::VecDeque;
+
+use massa_channel::receiver::MassaReceiver;
+use massa_models::operation::SecureShareOperation;
+use massa_protocol_exports::PeerId;
+
+use crate::handlers::peer_handler::models::PeerMessageTuple;
+
+/// Batches of operations waiting for the block messages to be processed
+pub(crate) struct DeferredOperations {
+    /// batches in their order of reception
+    batches: VecDeque<(PeerId, Vec<SecureShareOperation>)>,
+    /// max number of batches kept
+    max_batches: usize,
+}
+
+impl DeferredOperations {
+    pub(crate) fn new(max_batches: usize) -> Self {
+        DeferredOperations {
+            batches: VecDeque::new(),
+            max_batches,
+        }
+    }
+
+    /// Keeps a batch to process it later.
+    /// Returns false if it was dropped because too many batches are already waiting.
+    pub(crate) fn defer(&mut self, peer_id: PeerId, ops: Vec<SecureShareOperation>) -> bool {
+        if self.batches.len() >= self.max_batches {
+            return false;
+        }
+        self.batches.push_back((peer_id, ops));
+        true
+    }
+
+    /// Takes the oldest batch, unless block messages are waiting in `block_messages`
+    pub(crate) fn next_ready(
+        &mut self,
+        block_messages: &MassaReceiver<PeerMessageTuple>,
+    ) -> Option<(PeerId, Vec<SecureShareOperation>)> {
+        if !block_messages.is_empty() {
+            return None;
+        }
+        self.batches.pop_front()
+    }
+
+    /// Drops all the waiting batches
+    pub(crate) fn clear(&mut self) {
+        self.batches.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_channel::MassaChannel;
+    use massa_signature::KeyPair;
+
+    use super::*;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_operations_wait_for_block_messages() {
+        let (block_sender, block_receiver) =
+            MassaChannel::new::<PeerMessageTuple>("test_block_messages".to_string(), None);
+        let mut deferred = DeferredOperations::new(2);
+        let (first, second, third) = (peer_id(), peer_id(), peer_id());
+
+        // a block message is queued: the operations are held back
+        block_sender.send((first, vec![0])).unwrap();
+        assert!(deferred.defer(first, Vec::new()));
+        assert!(deferred.defer(second, Vec::new()));
+        // past the bound, the batches are dropped
+        assert!(!deferred.defer(third, Vec::new()));
+        assert!(deferred.next_ready(&block_receiver).is_none());
+
+        // once the block message is consumed, the batches are processed in their order of reception
+        block_receiver.recv().unwrap();
+        assert_eq!(deferred.next_ready(&block_receiver).unwrap().0, first);
+        assert_eq!(deferred.next_ready(&block_receiver).unwrap().0, second);
+        assert!(deferred.next_ready(&block_receiver).is_none());
+    }
+}
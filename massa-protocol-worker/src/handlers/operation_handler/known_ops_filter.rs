@@ -0,0 +1,145 @@
+//! Rotating bloom filter tracking the operations known by a peer.
+//!
+//! The filter is made of two generations of bloom filters, each holding half of the capacity.
+//! Operations are inserted in the current generation, and looked up in both.
+//! When the current generation is full, it becomes the previous one and the oldest is cleared,
+//! so the filter remembers between half and all of the `capacity` last inserted operations
+//! with a bounded memory, at the cost of rare false positives.
+//! A false positive makes us skip the announcement of an operation to a peer,
+//! which will get it from another peer.
+
+use std::{collections::hash_map::RandomState, hash::BuildHasher, mem};
+
+use massa_models::operation::OperationPrefixId;
+
+/// Maximum number of hash functions, bounding the cost of a lookup
+const MAX_HASHES: u32 = 16;
+
+pub struct KnownOpsFilter {
+    /// bits of the current generation
+    current: Vec<u64>,
+    /// bits of the previous generation
+    previous: Vec<u64>,
+    /// number of bits of each generation
+    num_bits: u64,
+    /// number of bits set for each operation
+    num_hashes: u32,
+    /// number of operations inserted in the current generation
+    current_len: usize,
+    /// number of operations inserted in the previous generation
+    previous_len: usize,
+    /// number of operations after which the generations rotate
+    generation_capacity: usize,
+    /// random keys of the hash functions, so that peers cannot craft collisions
+    hasher: RandomState,
+}
+
+impl KnownOpsFilter {
+    /// Creates a filter remembering at least the `capacity / 2` last inserted operations,
+    /// with a false positive probability of about `false_positive_rate`
+    pub fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let generation_capacity = (capacity / 2).max(1);
+        // a lookup checks the two generations, each gets half of the false positive budget
+        let generation_rate = (false_positive_rate / 2.0).clamp(f64::MIN_POSITIVE, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = ((-(generation_capacity as f64) * generation_rate.ln()) / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / generation_capacity as f64) * ln2)
+            .round()
+            .clamp(1.0, MAX_HASHES as f64) as u32;
+        let num_words = num_bits.div_ceil(64) as usize;
+        KnownOpsFilter {
+            current: vec![0; num_words],
+            previous: vec![0; num_words],
+            num_bits,
+            num_hashes,
+            current_len: 0,
+            previous_len: 0,
+            generation_capacity,
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Bit positions of an operation
+    fn positions(&self, op: &OperationPrefixId) -> impl Iterator<Item = u64> {
+        let hash = self.hasher.hash_one(op);
+        // double hashing: the positions are derived from two halves of a single hash
+        let h1 = hash;
+        let h2 = hash.rotate_left(32) | 1;
+        let num_bits = self.num_bits;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+
+    fn generation_contains(bits: &[u64], mut positions: impl Iterator<Item = u64>) -> bool {
+        positions.all(|pos| bits[(pos / 64) as usize] & (1 << (pos % 64)) != 0)
+    }
+
+    /// Returns true if the operation was inserted recently, or in case of false positive
+    pub fn contains(&self, op: &OperationPrefixId) -> bool {
+        Self::generation_contains(&self.current, self.positions(op))
+            || Self::generation_contains(&self.previous, self.positions(op))
+    }
+
+    /// Inserts an operation
+    pub fn insert(&mut self, op: &OperationPrefixId) {
+        if Self::generation_contains(&self.current, self.positions(op)) {
+            return;
+        }
+        if self.current_len >= self.generation_capacity {
+            mem::swap(&mut self.current, &mut self.previous);
+            self.current.fill(0);
+            self.previous_len = mem::take(&mut self.current_len);
+        }
+        for pos in self.positions(op) {
+            self.current[(pos / 64) as usize] |= 1 << (pos % 64);
+        }
+        self.current_len += 1;
+    }
+
+    /// Number of operations remembered by the filter, without the false positives
+    pub fn known_ops_count(&self) -> usize {
+        self.current_len + self.previous_len
+    }
+
+    /// Memory allocated by the bits of the filter, in bytes
+    pub fn memory_usage(&self) -> usize {
+        (self.current.capacity() + self.previous.capacity()) * std::mem::size_of::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_hash::Hash as MassaHash;
+    use massa_models::operation::OperationId;
+
+    use super::*;
+
+    fn op_prefix(i: u64) -> OperationPrefixId {
+        OperationId::new(MassaHash::compute_from(&i.to_be_bytes())).prefix()
+    }
+
+    #[test]
+    fn test_known_ops_filter() {
+        let mut filter = KnownOpsFilter::new(2000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&op_prefix(i));
+        }
+        // no false negatives
+        assert!((0..1000).all(|i| filter.contains(&op_prefix(i))));
+        let false_positives = (1000..11000)
+            .filter(|i| filter.contains(&op_prefix(*i)))
+            .count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+
+        // after two rotations the oldest operations are forgotten
+        for i in 1000..3000 {
+            filter.insert(&op_prefix(i));
+        }
+        assert!((2000..3000).all(|i| filter.contains(&op_prefix(i))));
+        let remembered = (0..1000)
+            .filter(|i| filter.contains(&op_prefix(*i)))
+            .count();
+        assert!(remembered < 30, "{} old operations remembered", remembered);
+    }
+}
@@ -12,6 +12,12 @@ use nom::{
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use std::ops::Bound::Included;
 
+use crate::handlers::compression::{serialize_compressed, CompressedDeserializer};
+
+use super::pending_ops_sketch::{
+    PendingOpsSketch, PendingOpsSketchDeserializer, PendingOpsSketchSerializer,
+};
+
 #[derive(Debug)]
 pub enum OperationMessage {
     /// Batch of operation ids
@@ -20,6 +26,13 @@ pub enum OperationMessage {
     AskForOperations(OperationPrefixIds),
     /// A list of operations
     Operations(Vec<SecureShareOperation>),
+    /// Someone asks for announcements of the operations currently in our pool
+    AskForPendingOperations,
+    /// A list of operations, compressed on the wire
+    CompressedOperations(Vec<SecureShareOperation>),
+    /// Someone sends a sketch of the operations of its pool, and asks for announcements
+    /// of the operations of our pool missing from it
+    PendingOperationsSketch(PendingOpsSketch),
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -28,6 +41,9 @@ pub enum MessageTypeId {
     OperationsAnnouncement = 0,
     AskForOperations = 1,
     Operations = 2,
+    AskForPendingOperations = 3,
+    CompressedOperations = 4,
+    PendingOperationsSketch = 5,
 }
 
 impl From<&OperationMessage> for MessageTypeId {
@@ -36,6 +52,9 @@ impl From<&OperationMessage> for MessageTypeId {
             OperationMessage::OperationsAnnouncement(_) => MessageTypeId::OperationsAnnouncement,
             OperationMessage::AskForOperations(_) => MessageTypeId::AskForOperations,
             OperationMessage::Operations(_) => MessageTypeId::Operations,
+            OperationMessage::AskForPendingOperations => MessageTypeId::AskForPendingOperations,
+            OperationMessage::CompressedOperations(_) => MessageTypeId::CompressedOperations,
+            OperationMessage::PendingOperationsSketch(_) => MessageTypeId::PendingOperationsSketch,
         }
     }
 }
@@ -45,6 +64,7 @@ pub struct OperationMessageSerializer {
     id_serializer: U64VarIntSerializer,
     operation_prefix_ids_serializer: OperationPrefixIdsSerializer,
     operations_serializer: OperationsSerializer,
+    sketch_serializer: PendingOpsSketchSerializer,
 }
 
 impl OperationMessageSerializer {
@@ -53,6 +73,7 @@ impl OperationMessageSerializer {
             id_serializer: U64VarIntSerializer::new(),
             operation_prefix_ids_serializer: OperationPrefixIdsSerializer::new(),
             operations_serializer: OperationsSerializer::new(),
+            sketch_serializer: PendingOpsSketchSerializer::new(),
         }
     }
 }
@@ -77,6 +98,15 @@ impl Serializer<OperationMessage> for OperationMessageSerializer {
             OperationMessage::Operations(operations) => {
                 self.operations_serializer.serialize(operations, buffer)?;
             }
+            OperationMessage::AskForPendingOperations => {}
+            OperationMessage::CompressedOperations(operations) => {
+                let mut raw = Vec::new();
+                self.operations_serializer.serialize(operations, &mut raw)?;
+                serialize_compressed(&raw, buffer)?;
+            }
+            OperationMessage::PendingOperationsSketch(sketch) => {
+                self.sketch_serializer.serialize(sketch, buffer)?;
+            }
         }
         Ok(())
     }
@@ -86,6 +116,8 @@ pub struct OperationMessageDeserializer {
     id_deserializer: U64VarIntDeserializer,
     operation_prefix_ids_deserializer: OperationPrefixIdsDeserializer,
     operations_deserializer: OperationsDeserializer,
+    compressed_deserializer: CompressedDeserializer,
+    sketch_deserializer: PendingOpsSketchDeserializer,
 }
 
 /// Limits used in the deserialization of `OperationMessage`
@@ -109,6 +141,8 @@ pub struct OperationMessageDeserializerArgs {
     pub max_op_datastore_value_length: u64,
     /// Chain id
     pub chain_id: u64,
+    /// Maximum size of the operations of a compressed message, once decompressed
+    pub max_decompressed_operations_size: u64,
 }
 
 impl OperationMessageDeserializer {
@@ -128,6 +162,13 @@ impl OperationMessageDeserializer {
                 args.max_op_datastore_value_length,
                 args.chain_id,
             ),
+            compressed_deserializer: CompressedDeserializer::new(
+                args.max_decompressed_operations_size,
+            ),
+            // a sketch holds at most as many operations as an announcement
+            sketch_deserializer: PendingOpsSketchDeserializer::new(
+                args.max_operations_prefix_ids as usize,
+            ),
         }
     }
 }
@@ -167,8 +208,120 @@ impl Deserializer<OperationMessage> for OperationMessageDeserializer {
                     .map(OperationMessage::Operations)
                     .parse(buffer)
                 }
+                MessageTypeId::AskForPendingOperations => {
+                    Ok((buffer, OperationMessage::AskForPendingOperations))
+                }
+                MessageTypeId::CompressedOperations => {
+                    context("Failed CompressedOperations deserialization", |input| {
+                        self.compressed_deserializer
+                            .deserialize(&self.operations_deserializer, input)
+                    })
+                    .map(OperationMessage::CompressedOperations)
+                    .parse(buffer)
+                }
+                MessageTypeId::PendingOperationsSketch => {
+                    context("Failed PendingOperationsSketch deserialization", |input| {
+                        self.sketch_deserializer.deserialize(input)
+                    })
+                    .map(OperationMessage::PendingOperationsSketch)
+                    .parse(buffer)
+                }
             }
         })
         .parse(buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use massa_models::{
+        address::Address,
+        amount::Amount,
+        config::CHAINID,
+        operation::{Operation, OperationSerializer, OperationType},
+        secure_share::SecureShareContent,
+    };
+    use massa_serialization::{DeserializeError, Deserializer, Serializer};
+    use massa_signature::KeyPair;
+
+    use super::*;
+
+    #[test]
+    fn test_compressed_operations_message() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let operations: Vec<_> = (0..10)
+            .map(|expire_period| {
+                Operation::new_verifiable(
+                    Operation {
+                        fee: Amount::default(),
+                        expire_period,
+                        start_period: None,
+                        fee_payer: None,
+                        op: OperationType::Transaction {
+                            recipient_address: Address::from_public_key(&keypair.get_public_key()),
+                            amount: Amount::default(),
+                        },
+                    },
+                    OperationSerializer::new(),
+                    &keypair,
+                    *CHAINID,
+                )
+                .unwrap()
+            })
+            .collect();
+        let serializer = OperationMessageSerializer::new();
+        let mut raw_buffer = Vec::new();
+        serializer
+            .serialize(
+                &OperationMessage::Operations(operations.clone()),
+                &mut raw_buffer,
+            )
+            .unwrap();
+        let mut buffer = Vec::new();
+        serializer
+            .serialize(
+                &OperationMessage::CompressedOperations(operations.clone()),
+                &mut buffer,
+            )
+            .unwrap();
+        // operations of the same sender share a lot of bytes
+        assert!(buffer.len() < raw_buffer.len());
+
+        let deserializer_args =
+            |max_decompressed_operations_size| OperationMessageDeserializerArgs {
+                max_operations_prefix_ids: 10,
+                max_operations: 10,
+                max_datastore_value_length: 1,
+                max_function_name_length: 1,
+                max_parameters_size: 1,
+                max_op_datastore_entry_count: 1,
+                max_op_datastore_key_length: 1,
+                max_op_datastore_value_length: 1,
+                chain_id: *CHAINID,
+                max_decompressed_operations_size,
+            };
+        let deserializer =
+            OperationMessageDeserializer::new(deserializer_args(raw_buffer.len() as u64));
+        let (rest, message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match message {
+            OperationMessage::CompressedOperations(deserialized_operations) => assert_eq!(
+                deserialized_operations
+                    .iter()
+                    .map(|op| op.id)
+                    .collect::<Vec<_>>(),
+                operations.iter().map(|op| op.id).collect::<Vec<_>>()
+            ),
+            _ => panic!("Wrong message type"),
+        }
+
+        // the size limit applies to the decompressed operations
+        let deserializer =
+            OperationMessageDeserializer::new(deserializer_args(buffer.len() as u64));
+        deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .expect_err("decompressed operations exceed the size limit");
+    }
+}
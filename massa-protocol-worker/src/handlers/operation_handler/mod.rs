@@ -5,6 +5,7 @@ use massa_metrics::MassaMetrics;
 use massa_pool_exports::PoolController;
 use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
+use massa_versioning::versioning::MipStore;
 
 use crate::wrap_network::ActiveConnectionsTrait;
 
@@ -17,14 +18,17 @@ use self::{
 pub mod cache;
 pub mod commands_propagation;
 pub mod commands_retrieval;
-mod messages;
+mod deferred_ops;
+mod known_ops_filter;
+pub(crate) mod messages;
+pub(crate) mod pending_ops_sketch;
 mod propagation;
 mod retrieval;
 
 pub(crate) use messages::{OperationMessage, OperationMessageSerializer};
 pub(crate) use retrieval::note_operations_from_peer;
 
-use super::peer_handler::models::{PeerManagementCmd, PeerMessageTuple};
+use super::peer_handler::models::{PeerManagementCmd, PeerMessageTuple, SharedPeerDB};
 
 pub struct OperationHandler {
     pub operation_retrieval_thread: Option<(
@@ -51,6 +55,9 @@ impl OperationHandler {
         local_sender: MassaSender<OperationHandlerPropagationCommand>,
         local_receiver: MassaReceiver<OperationHandlerPropagationCommand>,
         peer_cmd_sender: MassaSender<PeerManagementCmd>,
+        peer_db: SharedPeerDB,
+        block_messages: MassaReceiver<PeerMessageTuple>,
+        mip_store: MipStore,
         massa_metrics: MassaMetrics,
     ) -> Self {
         let operation_retrieval_thread = start_retrieval_thread(
@@ -63,6 +70,9 @@ impl OperationHandler {
             receiver_retrieval_ext,
             local_sender.clone(),
             peer_cmd_sender,
+            peer_db,
+            block_messages,
+            mip_store,
             massa_metrics.clone(),
         );
 
@@ -0,0 +1,171 @@
+//! Compact sketch of the pending operations of a node, sent to the peers it connects to
+//! so that they only announce the pending operations missing from it.
+//!
+//! The sketch is a bloom filter of the operation prefix ids. Unlike `KnownOpsFilter`, its bit
+//! positions must be the same on both nodes: they are derived from the prefix ids themselves,
+//! which are already hash prefixes. A false positive makes the peer skip the announcement
+//! of an operation we lack, which we will still get from the usual gossip.
+
+use massa_models::operation::OperationPrefixId;
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use nom::{
+    bytes::complete::take,
+    error::{context, ContextError, ParseError},
+    IResult, Parser,
+};
+use std::ops::Bound::Included;
+
+/// Number of bits of the sketch per operation, for a false positive rate of about 1%
+const SKETCH_BITS_PER_OPERATION: usize = 10;
+/// Number of bits set for each operation
+const SKETCH_HASHES: u64 = 7;
+/// Minimal size of a sketch in bytes
+const MIN_SKETCH_SIZE: usize = 8;
+
+/// Maximal size in bytes of the sketch of `max_operations` operations
+pub fn max_sketch_size(max_operations: usize) -> usize {
+    (max_operations * SKETCH_BITS_PER_OPERATION)
+        .div_ceil(8)
+        .max(MIN_SKETCH_SIZE)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingOpsSketch {
+    bits: Vec<u8>,
+}
+
+impl PendingOpsSketch {
+    /// Creates the sketch of a list of operations
+    pub fn new(ops: &[OperationPrefixId]) -> Self {
+        let mut sketch = PendingOpsSketch {
+            bits: vec![0; max_sketch_size(ops.len())],
+        };
+        for op in ops {
+            for pos in sketch.positions(op) {
+                sketch.bits[pos / 8] |= 1 << (pos % 8);
+            }
+        }
+        sketch
+    }
+
+    /// Bit positions of an operation
+    fn positions(&self, op: &OperationPrefixId) -> impl Iterator<Item = usize> {
+        let bytes: Vec<u8> = op.into();
+        // double hashing: the positions are derived from two parts of the prefix
+        let h1 = u64::from_le_bytes(bytes[0..8].try_into().expect("prefix too short"));
+        let h2 = u64::from_le_bytes(bytes[8..16].try_into().expect("prefix too short")) | 1;
+        let num_bits = self.bits.len() as u64 * 8;
+        (0..SKETCH_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+
+    /// Returns true if the operation is in the sketch, or in case of false positive
+    pub fn contains(&self, op: &OperationPrefixId) -> bool {
+        self.positions(op)
+            .all(|pos| self.bits[pos / 8] & (1 << (pos % 8)) != 0)
+    }
+}
+
+/// Serializer for `PendingOpsSketch`
+#[derive(Default, Clone)]
+pub struct PendingOpsSketchSerializer {
+    size_serializer: U64VarIntSerializer,
+}
+
+impl PendingOpsSketchSerializer {
+    pub fn new() -> Self {
+        Self {
+            size_serializer: U64VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Serializer<PendingOpsSketch> for PendingOpsSketchSerializer {
+    fn serialize(
+        &self,
+        value: &PendingOpsSketch,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.size_serializer
+            .serialize(&(value.bits.len() as u64), buffer)?;
+        buffer.extend(&value.bits);
+        Ok(())
+    }
+}
+
+/// Deserializer for `PendingOpsSketch`
+pub struct PendingOpsSketchDeserializer {
+    size_deserializer: U64VarIntDeserializer,
+}
+
+impl PendingOpsSketchDeserializer {
+    /// `max_operations` bounds the number of operations of the sketch
+    pub fn new(max_operations: usize) -> Self {
+        Self {
+            size_deserializer: U64VarIntDeserializer::new(
+                Included(MIN_SKETCH_SIZE as u64),
+                Included(max_sketch_size(max_operations) as u64),
+            ),
+        }
+    }
+}
+
+impl Deserializer<PendingOpsSketch> for PendingOpsSketchDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], PendingOpsSketch, E> {
+        context("Failed PendingOpsSketch deserialization", |input| {
+            let (input, size) = self.size_deserializer.deserialize(input)?;
+            take(size)(input)
+        })
+        .map(|bits: &[u8]| PendingOpsSketch {
+            bits: bits.to_vec(),
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_hash::Hash as MassaHash;
+    use massa_models::operation::OperationId;
+    use massa_serialization::DeserializeError;
+
+    use super::*;
+
+    fn op_prefix(i: u64) -> OperationPrefixId {
+        OperationId::new(MassaHash::compute_from(&i.to_be_bytes())).prefix()
+    }
+
+    #[test]
+    fn test_pending_ops_sketch() {
+        let ops: Vec<_> = (0..1000).map(op_prefix).collect();
+        let sketch = PendingOpsSketch::new(&ops);
+
+        let mut buffer = Vec::new();
+        PendingOpsSketchSerializer::new()
+            .serialize(&sketch, &mut buffer)
+            .unwrap();
+        let (rest, sketch) = PendingOpsSketchDeserializer::new(1000)
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+
+        // no false negatives
+        assert!(ops.iter().all(|op| sketch.contains(op)));
+        let false_positives = (1000..11000)
+            .filter(|i| sketch.contains(&op_prefix(*i)))
+            .count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+
+        // a sketch of more operations than allowed is refused
+        assert!(PendingOpsSketchDeserializer::new(100)
+            .deserialize::<DeserializeError>(&buffer)
+            .is_err());
+
+        // the sketch of an empty pool contains nothing
+        assert!(!PendingOpsSketch::new(&[]).contains(&op_prefix(0)));
+    }
+}
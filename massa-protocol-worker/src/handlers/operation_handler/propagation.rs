@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::{mem, thread::JoinHandle};
 
 use crossbeam::channel::RecvTimeoutError;
@@ -10,12 +10,13 @@ use massa_models::prehash::CapacityAllocator;
 use massa_models::prehash::PreHashSet;
 use massa_protocol_exports::PeerId;
 use massa_protocol_exports::ProtocolConfig;
-use massa_protocol_exports::ProtocolError;
 use massa_storage::Storage;
+use rand::{seq::SliceRandom, thread_rng, Rng};
 use tracing::{debug, info, log::warn};
 
 use crate::{
-    handlers::operation_handler::OperationMessage, messages::MessagesSerializer,
+    handlers::{chunked::send_chunked, operation_handler::OperationMessage},
+    messages::MessagesSerializer,
     wrap_network::ActiveConnectionsTrait,
 };
 
@@ -38,7 +39,7 @@ struct PropagationThread {
     config: ProtocolConfig,
     cache: SharedOperationCache,
     operation_message_serializer: MessagesSerializer,
-    _massa_metrics: MassaMetrics,
+    massa_metrics: MassaMetrics,
 }
 
 impl PropagationThread {
@@ -155,52 +156,73 @@ impl PropagationThread {
             let peers_connected = self.active_connections.get_peer_ids_connected();
             cache_write.update_cache(&peers_connected);
 
-            // Propagate to peers
+            // Select the peers each operation is announced to
             let all_keys: Vec<PeerId> = cache_write.ops_known_by_peer.keys().cloned().collect();
-            for peer_id in all_keys {
-                let ops = cache_write.ops_known_by_peer.get_mut(&peer_id).unwrap();
-                let new_ops: Vec<OperationId> = operation_ids
+            let mut rng = thread_rng();
+            let mut ops_per_peer: HashMap<PeerId, Vec<OperationId>> = HashMap::new();
+            for op_id in &operation_ids {
+                let eligible_peers: Vec<&PeerId> = all_keys
                     .iter()
-                    .filter(|id| ops.peek(&id.prefix()).is_none())
-                    .copied()
+                    .filter(|peer_id| !cache_write.peer_knows_op(peer_id, &op_id.prefix()))
                     .collect();
-                if !new_ops.is_empty() {
-                    for id in &new_ops {
-                        ops.insert(id.prefix(), ());
-                    }
-                    debug!(
-                        "Send operations announcement of len {} to {}",
-                        new_ops.len(),
-                        peer_id
+                for peer_id in select_propagation_peers(
+                    eligible_peers,
+                    self.config.propagation_fanout,
+                    &mut rng,
+                ) {
+                    ops_per_peer.entry(*peer_id).or_default().push(*op_id);
+                }
+            }
+
+            // Propagate to peers
+            for (peer_id, new_ops) in ops_per_peer {
+                cache_write.insert_peer_known_ops(
+                    &peer_id,
+                    &new_ops.iter().map(|id| id.prefix()).collect::<Vec<_>>(),
+                );
+                debug!(
+                    "Send operations announcement of len {} to {}",
+                    new_ops.len(),
+                    peer_id
+                );
+                // if the peer disconnected, its cache is removed in next call of cache_write.update_cache
+                if let Err(err) = send_chunked(
+                    self.active_connections.as_ref(),
+                    &peer_id,
+                    &self.operation_message_serializer,
+                    &new_ops,
+                    self.config.max_operations_per_message,
+                    |sub_list| {
+                        OperationMessage::OperationsAnnouncement(
+                            sub_list.iter().map(|id| id.into_prefix()).collect(),
+                        )
+                        .into()
+                    },
+                    |sub_list| {
+                        self.massa_metrics
+                            .inc_protocol_items_sent("operation", sub_list.len());
+                    },
+                ) {
+                    warn!(
+                        "Failed to send OperationsAnnouncement message to peer: {}",
+                        err
                     );
-                    for sub_list in new_ops.chunks(self.config.max_operations_per_message as usize)
-                    {
-                        if let Err(err) = self.active_connections.send_to_peer(
-                            &peer_id,
-                            &self.operation_message_serializer,
-                            OperationMessage::OperationsAnnouncement(
-                                sub_list.iter().map(|id| id.into_prefix()).collect(),
-                            )
-                            .into(),
-                            false,
-                        ) {
-                            warn!(
-                                "Failed to send OperationsAnnouncement message to peer: {}",
-                                err
-                            );
-
-                            if let ProtocolError::PeerDisconnected(_) = err {
-                                // cache of this peer is removed in next call of cache_write.update_cache
-                                break;
-                            }
-                        }
-                    }
                 }
             }
         }
     }
 }
 
+/// Randomly selects at most `fanout` of the eligible peers, or all of them if `fanout` is 0.
+/// The peers that are not selected will learn about the item from the other peers.
+fn select_propagation_peers<T, R: Rng>(mut eligible: Vec<T>, fanout: usize, rng: &mut R) -> Vec<T> {
+    if fanout > 0 && eligible.len() > fanout {
+        eligible.shuffle(rng);
+        eligible.truncate(fanout);
+    }
+    eligible
+}
+
 pub fn start_propagation_thread(
     internal_receiver: MassaReceiver<OperationHandlerPropagationCommand>,
     active_connections: Box<dyn ActiveConnectionsTrait>,
@@ -226,7 +248,7 @@ pub fn start_propagation_thread(
                 ),
                 config,
                 cache,
-                _massa_metrics: massa_metrics,
+                massa_metrics,
                 operation_message_serializer: MessagesSerializer::new()
                     .with_operation_message_serializer(OperationMessageSerializer::new()),
             };
@@ -234,3 +256,27 @@ pub fn start_propagation_thread(
         })
         .expect("OS failed to start operation propagation thread")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::select_propagation_peers;
+    use rand::thread_rng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_select_propagation_peers() {
+        let mut rng = thread_rng();
+        let peers: Vec<u32> = (0..10).collect();
+
+        // a fanout of 0 or above the number of eligible peers is a full broadcast
+        assert_eq!(select_propagation_peers(peers.clone(), 0, &mut rng), peers);
+        assert_eq!(select_propagation_peers(peers.clone(), 10, &mut rng), peers);
+        assert_eq!(select_propagation_peers(peers.clone(), 20, &mut rng), peers);
+
+        let selected = select_propagation_peers(peers.clone(), 3, &mut rng);
+        assert_eq!(selected.len(), 3);
+        let distinct: HashSet<u32> = selected.iter().copied().collect();
+        assert_eq!(distinct.len(), 3);
+        assert!(distinct.iter().all(|peer| peers.contains(peer)));
+    }
+}
@@ -1,26 +1,38 @@
-use std::{collections::VecDeque, thread::JoinHandle, time::Instant};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    thread::JoinHandle,
+    time::Instant,
+};
 
-use crossbeam::{channel::tick, select};
+use crossbeam::{
+    channel::{never, tick},
+    select,
+};
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::{
-    operation::{OperationPrefixId, OperationPrefixIds, SecureShareOperation},
+    operation::{OperationId, OperationPrefixId, OperationPrefixIds, SecureShareOperation},
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
     secure_share::Id,
-    slot::Slot,
     timeslots::get_block_slot_timestamp,
 };
 use massa_pool_exports::PoolController;
-use massa_protocol_exports::PeerId;
+use massa_protocol_exports::{PeerFeatures, PeerId};
 use massa_protocol_exports::{ProtocolConfig, ProtocolError};
 use massa_serialization::{DeserializeError, Deserializer};
 use massa_storage::Storage;
 use massa_time::{MassaTime, TimeError};
+use massa_versioning::versioning::MipStore;
 use schnellru::{ByLength, LruMap};
 
 use crate::{
-    handlers::peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+    handlers::{
+        chunked::send_chunked,
+        message_timer::MessageTimer,
+        peer_handler::models::{peer_supports, PeerManagementCmd, PeerMessageTuple, SharedPeerDB},
+        rate_limiter::{PeerRateLimiter, RateLimitOutcome},
+    },
     messages::MessagesSerializer,
     sig_verifier::verify_sigs_batch,
     wrap_network::ActiveConnectionsTrait,
@@ -31,7 +43,12 @@ use super::{
     cache::SharedOperationCache,
     commands_propagation::OperationHandlerPropagationCommand,
     commands_retrieval::OperationHandlerRetrievalCommand,
-    messages::{OperationMessage, OperationMessageDeserializer, OperationMessageDeserializerArgs},
+    deferred_ops::DeferredOperations,
+    messages::{
+        MessageTypeId, OperationMessage, OperationMessageDeserializer,
+        OperationMessageDeserializerArgs,
+    },
+    pending_ops_sketch::PendingOpsSketch,
     OperationMessageSerializer,
 };
 
@@ -39,6 +56,9 @@ use super::{
 const THREAD_NAME: &str = "poh-retrieval";
 static_assertions::const_assert!(THREAD_NAME.len() < 16);
 
+/// Max number of batches of operations waiting for the block messages to be processed
+const MAX_DEFERRED_OPERATION_BATCHES: usize = 1_000;
+
 /// Structure containing a Batch of `operation_ids` we would like to ask
 /// to a `peer_id` now or later. Mainly used in protocol and translated into
 /// simple combination of a `peer_id` and `operations_prefix_ids`
@@ -64,7 +84,28 @@ pub struct RetrievalThread {
     receiver_ext: MassaReceiver<OperationHandlerRetrievalCommand>,
     operation_message_serializer: MessagesSerializer,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
-    _massa_metrics: MassaMetrics,
+    /// used to know the wire features announced by the peers
+    peer_db: SharedPeerDB,
+    massa_metrics: MassaMetrics,
+    /// the node lags too far behind the network to process received operations
+    syncing: bool,
+    /// the connected peers were asked for their pending operations since the start of the node
+    pool_warmed_up: bool,
+    /// last time each peer had a request for our pending operations answered
+    pending_operations_answered: HashMap<PeerId, Instant>,
+    /// connected peers to which the sketch of our pending operations was sent
+    pending_operations_synced_peers: HashSet<PeerId>,
+    /// limits the number of operations received from each peer
+    rate_limiter: PeerRateLimiter,
+    /// messages received by the block handler, only used to know if some are waiting:
+    /// they are processed before the batches of operations received from the network
+    block_messages: MassaReceiver<PeerMessageTuple>,
+    /// batches of operations received while block messages were waiting
+    deferred_operations: DeferredOperations,
+    /// operations asked by each peer, sent at the next tick of `operation_send_interval`
+    operations_to_send: HashMap<PeerId, PreHashSet<OperationId>>,
+    /// used to ignore the operations whose format is not active yet
+    mip_store: MipStore,
 }
 
 impl RetrievalThread {
@@ -80,8 +121,17 @@ impl RetrievalThread {
                 max_op_datastore_key_length: self.config.max_op_datastore_key_length,
                 max_op_datastore_value_length: self.config.max_op_datastore_value_length,
                 chain_id: self.config.chain_id,
+                max_decompressed_operations_size: self.config.max_message_size as u64,
             });
         let tick_ask_operations = tick(self.config.operation_batch_proc_period.to_duration());
+        let tick_send_operations = if self.config.operation_send_interval.as_millis() > 0 {
+            tick(self.config.operation_send_interval.to_duration())
+        } else {
+            never()
+        };
+        // owned by the loop so that the message timers do not borrow `self`
+        let massa_metrics = self.massa_metrics.clone();
+        let slow_message_threshold = self.config.slow_message_threshold.to_duration();
 
         loop {
             select! {
@@ -89,6 +139,7 @@ impl RetrievalThread {
                     self.receiver.update_metrics();
                     match msg {
                         Ok((peer_id, message)) => {
+                            let mut timer = MessageTimer::start(&massa_metrics, slow_message_threshold, peer_id, "Operation");
                             let (rest, message) = match operation_message_deserializer
                                 .deserialize::<DeserializeError>(&message) {
                                     Ok((rest, message)) => (rest, message),
@@ -101,24 +152,25 @@ impl RetrievalThread {
                                 println!("Error: message not fully consumed");
                                 return;
                             }
+                            timer.set_message_type(format!("Operation::{:?}", MessageTypeId::from(&message)));
                             match message {
-                                OperationMessage::Operations(ops) => {
+                                OperationMessage::Operations(ops)
+                                | OperationMessage::CompressedOperations(ops) => {
                                     debug!("Received operation message: Operations from {}", peer_id);
-                                    if let Err(err) = note_operations_from_peer(
-                                        &self.storage,
-                                        &mut self.cache,
-                                        &self.config,
-                                        ops,
-                                        &peer_id,
-                                        &mut self.internal_sender,
-                                        &mut self.pool_controller
-                                    ) {
-                                        warn!("peer {} sent us critically incorrect operation, which may be an attack attempt by the remote peer or a loss of sync between us and the remote peer. Err = {}", peer_id, err);
-
-                                        if let Err(e) = self.ban_node(&peer_id) {
-                                            warn!("Error when banning node: {}", e);
-                                        }
+                                    if self.syncing {
+                                        // the operations would expire before we catch up with the network
+                                        continue;
+                                    }
+                                    if !self.check_rate_limit(&peer_id, ops.len()) {
+                                        continue;
                                     }
+                                    if !self.block_messages.is_empty() {
+                                        // block messages go first, the operations are processed once they are handled
+                                        self.defer_operations(peer_id, ops);
+                                        continue;
+                                    }
+                                    self.process_deferred_operations();
+                                    self.process_operations(peer_id, ops);
                                 }
                                 OperationMessage::OperationsAnnouncement(announcement) => {
                                     debug!("Received operation message: OperationsAnnouncement from {}", peer_id);
@@ -134,6 +186,18 @@ impl RetrievalThread {
                                         warn!("error when processing asked operations received from peer {}: Err = {}", peer_id, err);
                                     }
                                 }
+                                OperationMessage::AskForPendingOperations => {
+                                    debug!("Received operation message: AskForPendingOperations from {}", peer_id);
+                                    if let Err(err) = self.on_asked_pending_operations_received(&peer_id, None) {
+                                        warn!("error when processing asked pending operations received from peer {}: Err = {}", peer_id, err);
+                                    }
+                                }
+                                OperationMessage::PendingOperationsSketch(sketch) => {
+                                    debug!("Received operation message: PendingOperationsSketch from {}", peer_id);
+                                    if let Err(err) = self.on_asked_pending_operations_received(&peer_id, Some(sketch)) {
+                                        warn!("error when processing pending operations sketch received from peer {}: Err = {}", peer_id, err);
+                                    }
+                                }
                             }
                         }
                         Err(_) => {
@@ -146,6 +210,24 @@ impl RetrievalThread {
                     self.receiver_ext.update_metrics();
                     match msg {
                         Ok(cmd) => match cmd {
+                            OperationHandlerRetrievalCommand::SyncLag(lag) => {
+                                let syncing = lag > self.config.max_sync_lag_for_gossip;
+                                if syncing != self.syncing {
+                                    if syncing {
+                                        info!("Node is syncing (lag: {}), received operations will not be processed", lag);
+                                    } else {
+                                        info!("Node is synced, resuming the processing of received operations");
+                                    }
+                                    self.syncing = syncing;
+                                    if syncing {
+                                        // the deferred operations would expire before we catch up with the network
+                                        self.deferred_operations.clear();
+                                    }
+                                }
+                                if !self.syncing && !self.pool_warmed_up && self.config.pool_warmup_enabled {
+                                    self.ask_pending_operations();
+                                }
+                            }
                             OperationHandlerRetrievalCommand::Stop => {
                                 info!("Stop operation retrieval thread");
                                 return;
@@ -161,6 +243,14 @@ impl RetrievalThread {
                     if let Err(err) = self.update_ask_operation() {
                         warn!("Error in update_ask_operation: {}", err);
                     };
+                    self.rate_limiter.prune(Instant::now());
+                    self.process_deferred_operations();
+                    if !self.syncing && self.config.pending_operations_sync_enabled {
+                        self.sync_pending_operations();
+                    }
+                }
+                recv(tick_send_operations) -> _ => {
+                    self.send_asked_operations();
                 }
             }
         }
@@ -205,6 +295,11 @@ impl RetrievalThread {
             .write()
             .insert_peer_known_ops(peer_id, &op_batch.iter().copied().collect::<Vec<_>>());
 
+        // do not ask for operations that would expire before we catch up with the network
+        if self.syncing {
+            return Ok(());
+        }
+
         // filter out the operations that we already know about
         {
             let cache_read = self.cache.read();
@@ -269,25 +364,21 @@ impl RetrievalThread {
                 ask_set.len(),
                 peer_id
             );
-            for sub_list in ask_set
-                .into_iter()
-                .collect::<Vec<OperationPrefixId>>()
-                .chunks(self.config.max_operations_per_message as usize)
-            {
-                if let Err(err) = self.active_connections.send_to_peer(
-                    peer_id,
-                    &self.operation_message_serializer,
+            if let Err(err) = send_chunked(
+                self.active_connections.as_ref(),
+                peer_id,
+                &self.operation_message_serializer,
+                &ask_set.into_iter().collect::<Vec<OperationPrefixId>>(),
+                self.config.max_operations_per_message,
+                |sub_list| {
                     OperationMessage::AskForOperations(
                         sub_list.iter().cloned().collect::<OperationPrefixIds>(),
                     )
-                    .into(),
-                    false,
-                ) {
-                    warn!("Failed to send AskForOperations message to peer: {}", err);
-                    if let ProtocolError::PeerDisconnected(_) = err {
-                        break;
-                    }
-                }
+                    .into()
+                },
+                |_| {},
+            ) {
+                warn!("Failed to send AskForOperations message to peer: {}", err);
             }
         }
         Ok(())
@@ -321,38 +412,294 @@ impl RetrievalThread {
             return Ok(());
         }
 
-        let mut ops: Vec<SecureShareOperation> = Vec::with_capacity(op_pre_ids.len());
-        {
-            // Scope the lock because of the async call to `send_operations` below.
+        let op_ids: Vec<OperationId> = {
             let stored_ops = self.storage.read_operations();
-            for prefix in op_pre_ids {
-                let opt_op = match stored_ops
-                    .get_operations_by_prefix(&prefix)
-                    .and_then(|ids| ids.iter().next())
-                {
-                    Some(id) => stored_ops.get(id),
-                    None => continue,
-                };
-                if let Some(op) = opt_op {
-                    ops.push(op.clone());
-                }
+            op_pre_ids
+                .iter()
+                .filter_map(|prefix| {
+                    stored_ops
+                        .get_operations_by_prefix(prefix)
+                        .and_then(|ids| ids.iter().next())
+                        .copied()
+                })
+                .collect()
+        };
+        if self.config.operation_send_interval.as_millis() == 0 {
+            self.send_operations(peer_id, op_ids);
+            return Ok(());
+        }
+
+        // merge with the operations already asked by the peer, they are sent together at the next tick
+        let to_send = self.operations_to_send.entry(*peer_id).or_default();
+        to_send.extend(op_ids);
+        if to_send.len() >= self.config.max_operations_per_message as usize {
+            // a full message is ready, no need to wait
+            if let Some(op_ids) = self.operations_to_send.remove(peer_id) {
+                self.send_operations(peer_id, op_ids);
             }
         }
+        Ok(())
+    }
+
+    /// Send the operations asked by each peer since the last tick
+    fn send_asked_operations(&mut self) {
+        if self.operations_to_send.is_empty() {
+            return;
+        }
+        let peers_connected = self.active_connections.get_peer_ids_connected();
+        for (peer_id, op_ids) in std::mem::take(&mut self.operations_to_send) {
+            // the operations asked by the peers that disconnected are dropped
+            if peers_connected.contains(&peer_id) {
+                self.send_operations(&peer_id, op_ids);
+            }
+        }
+    }
+
+    /// Send full operations to a peer, skipping those that are not in storage anymore
+    fn send_operations(&mut self, peer_id: &PeerId, op_ids: impl IntoIterator<Item = OperationId>) {
+        let ops: Vec<SecureShareOperation> = {
+            let stored_ops = self.storage.read_operations();
+            op_ids
+                .into_iter()
+                .filter_map(|id| stored_ops.get(&id).cloned())
+                .collect()
+        };
+        if ops.is_empty() {
+            return;
+        }
         debug!("Send full operations of len {} to {}", ops.len(), peer_id);
-        for sub_list in ops.chunks(self.config.max_operations_per_message as usize) {
+        // the peer database is only read when compression is enabled
+        let compress = self.config.compress_operation_messages
+            && peer_supports(
+                &self.peer_db,
+                peer_id,
+                PeerFeatures::COMPRESSED_OPERATION_MESSAGES,
+            );
+        if let Err(err) = send_chunked(
+            self.active_connections.as_ref(),
+            peer_id,
+            &self.operation_message_serializer,
+            &ops,
+            self.config.max_operations_per_message,
+            |sub_list| {
+                if compress {
+                    OperationMessage::CompressedOperations(sub_list.to_vec()).into()
+                } else {
+                    OperationMessage::Operations(sub_list.to_vec()).into()
+                }
+            },
+            |_| {},
+        ) {
+            warn!("Failed to send Operations message to peer: {}", err);
+        }
+    }
+
+    /// Ask the connected peers to announce the operations of their pool, to refill ours after a start.
+    /// The warm-up is done once at least one peer was asked, otherwise it is retried at the next sync lag notification.
+    fn ask_pending_operations(&mut self) {
+        let peer_ids = self.active_connections.get_peer_ids_connected();
+        let mut asked_count = 0;
+        for peer_id in &peer_ids {
             if let Err(err) = self.active_connections.send_to_peer(
                 peer_id,
                 &self.operation_message_serializer,
-                OperationMessage::Operations(sub_list.to_vec()).into(),
+                OperationMessage::AskForPendingOperations.into(),
                 false,
             ) {
-                warn!("Failed to send Operations message to peer: {}", err);
-                if let ProtocolError::PeerDisconnected(_) = err {
-                    break;
+                warn!(
+                    "Failed to send AskForPendingOperations message to peer: {}",
+                    err
+                );
+            } else {
+                asked_count += 1;
+            }
+        }
+        if asked_count > 0 {
+            info!(
+                "Asked {} peers for their pending operations to refill the pool",
+                asked_count
+            );
+            self.pool_warmed_up = true;
+        }
+    }
+
+    /// Send the sketch of the operations of our pool to the newly connected peers supporting it,
+    /// so that they announce the operations of their pool that we lack
+    fn sync_pending_operations(&mut self) {
+        let peer_ids = self.active_connections.get_peer_ids_connected();
+        self.pending_operations_synced_peers
+            .retain(|peer_id| peer_ids.contains(peer_id));
+        let new_peer_ids: Vec<PeerId> = peer_ids
+            .into_iter()
+            .filter(|peer_id| {
+                !self.pending_operations_synced_peers.contains(peer_id)
+                    && peer_supports(
+                        &self.peer_db,
+                        peer_id,
+                        PeerFeatures::PENDING_OPERATIONS_SKETCH,
+                    )
+            })
+            .collect();
+        if new_peer_ids.is_empty() {
+            return;
+        }
+        let op_prefix_ids: Vec<OperationPrefixId> = self
+            .pool_controller
+            .get_operation_ids(self.config.max_operations_per_message as usize)
+            .into_iter()
+            .map(|id| id.into_prefix())
+            .collect();
+        let sketch = PendingOpsSketch::new(&op_prefix_ids);
+        for peer_id in new_peer_ids {
+            if let Err(err) = self.active_connections.send_to_peer(
+                &peer_id,
+                &self.operation_message_serializer,
+                OperationMessage::PendingOperationsSketch(sketch.clone()).into(),
+                false,
+            ) {
+                warn!(
+                    "Failed to send PendingOperationsSketch message to peer: {}",
+                    err
+                );
+            } else {
+                self.pending_operations_synced_peers.insert(peer_id);
+            }
+        }
+    }
+
+    /// Process a request of a peer for the operations of our pool,
+    /// by announcing to them the best operations of the pool missing from their `sketch` if any.
+    /// A peer is answered at most once per `pending_operations_request_min_interval`.
+    fn on_asked_pending_operations_received(
+        &mut self,
+        peer_id: &PeerId,
+        sketch: Option<PendingOpsSketch>,
+    ) -> Result<(), ProtocolError> {
+        let now = Instant::now();
+        if let Some(last_answer) = self.pending_operations_answered.get(peer_id) {
+            if now.saturating_duration_since(*last_answer)
+                < self
+                    .config
+                    .pending_operations_request_min_interval
+                    .to_duration()
+            {
+                debug!(
+                    "Ignore too frequent AskForPendingOperations from {}",
+                    peer_id
+                );
+                return Ok(());
+            }
+        }
+        let peer_ids_connected = self.active_connections.get_peer_ids_connected();
+        self.pending_operations_answered
+            .retain(|peer_id, _| peer_ids_connected.contains(peer_id));
+        self.pending_operations_answered.insert(*peer_id, now);
+
+        let op_prefix_ids: Vec<OperationPrefixId> = self
+            .pool_controller
+            .get_operation_ids(self.config.max_operations_per_message as usize)
+            .into_iter()
+            .map(|id| id.into_prefix())
+            .collect();
+        // the peer knows the operations of its sketch, and is announced the others
+        self.cache
+            .write()
+            .insert_peer_known_ops(peer_id, &op_prefix_ids);
+        let op_prefix_ids: Vec<OperationPrefixId> = match sketch {
+            Some(sketch) => op_prefix_ids
+                .into_iter()
+                .filter(|id| !sketch.contains(id))
+                .collect(),
+            None => op_prefix_ids,
+        };
+        if op_prefix_ids.is_empty() {
+            return Ok(());
+        }
+        debug!(
+            "Send pending operations announcement of len {} to {}",
+            op_prefix_ids.len(),
+            peer_id
+        );
+        self.active_connections.send_to_peer(
+            peer_id,
+            &self.operation_message_serializer,
+            OperationMessage::OperationsAnnouncement(op_prefix_ids.into_iter().collect()).into(),
+            false,
+        )
+    }
+
+    /// Checks the operations received from a peer and notes them, striking the peer if they are invalid
+    fn process_operations(&mut self, peer_id: PeerId, ops: Vec<SecureShareOperation>) {
+        self.massa_metrics
+            .inc_protocol_items_received("operation", ops.len());
+        if let Err(err) = note_operations_from_peer(
+            &self.storage,
+            &mut self.cache,
+            &self.config,
+            ops,
+            &peer_id,
+            &mut self.internal_sender,
+            &mut self.pool_controller,
+            &self.mip_store,
+        ) {
+            warn!("peer {} sent us critically incorrect operation, which may be an attack attempt by the remote peer or a loss of sync between us and the remote peer. Err = {}", peer_id, err);
+            self.massa_metrics
+                .inc_protocol_invalid_items(&peer_id.to_string(), "operation");
+
+            if let Err(e) = self
+                .peer_cmd_sender
+                .try_send(PeerManagementCmd::NoteInvalidItem {
+                    peer_id,
+                    item_type: "operation",
+                })
+            {
+                warn!("Error when noting invalid operations from node: {}", e);
+            }
+        }
+    }
+
+    /// Keeps operations received while block messages are waiting, to process them later
+    fn defer_operations(&mut self, peer_id: PeerId, ops: Vec<SecureShareOperation>) {
+        let count = ops.len();
+        if !self.deferred_operations.defer(peer_id, ops) {
+            debug!(
+                "dropping {} operations from peer {}: too many operations waiting for block messages",
+                count, peer_id
+            );
+            self.massa_metrics.inc_protocol_dropped_operation_batches();
+        }
+    }
+
+    /// Processes the deferred operations as long as no block message is waiting
+    fn process_deferred_operations(&mut self) {
+        while let Some((peer_id, ops)) = self.deferred_operations.next_ready(&self.block_messages) {
+            self.process_operations(peer_id, ops);
+        }
+    }
+
+    /// Applies the rate limit of the operations received from a peer.
+    /// Returns false if the operations must be dropped, banning the peer if it keeps flooding us.
+    fn check_rate_limit(&mut self, peer_id: &PeerId, count: usize) -> bool {
+        match self.rate_limiter.consume(peer_id, count, Instant::now()) {
+            RateLimitOutcome::Allowed => true,
+            RateLimitOutcome::Throttled => {
+                debug!(
+                    "dropping {} operations from throttled peer {}",
+                    count, peer_id
+                );
+                false
+            }
+            RateLimitOutcome::Ban => {
+                warn!(
+                    "peer {} keeps flooding us with operations above the rate limit",
+                    peer_id
+                );
+                if let Err(err) = self.ban_node(peer_id) {
+                    warn!("Error when banning node: {}", err);
                 }
+                false
             }
         }
-        Ok(())
     }
 
     /// send a ban peer command to the peer handler
@@ -372,6 +719,7 @@ pub(crate) fn note_operations_from_peer(
     source_peer_id: &PeerId,
     ops_propagation_sender: &mut MassaSender<OperationHandlerPropagationCommand>,
     pool_controller: &mut Box<dyn PoolController>,
+    mip_store: &MipStore,
 ) -> Result<(), ProtocolError> {
     massa_trace!("protocol.protocol_worker.note_operations_from_peer", { "peer": source_peer_id, "operations": operations });
     let now = MassaTime::now();
@@ -383,12 +731,7 @@ pub(crate) fn note_operations_from_peer(
             config.thread_count,
             config.t0,
             config.genesis_timestamp,
-            Slot::new(
-                operation.content.expire_period,
-                operation
-                    .content_creator_address
-                    .get_thread(config.thread_count),
-            ),
+            operation.get_expire_slot(config.thread_count),
         );
         match expire_period_timestamp {
             Ok(slot_timestamp) => {
@@ -399,13 +742,18 @@ pub(crate) fn note_operations_from_peer(
             Err(_) => continue,
         }
 
-        // quit if op is too big
-        if operation.serialized_size() > config.max_serialized_operations_size_per_block {
+        // ignore if the op format is not active yet
+        if !mip_store.is_operation_format_active_at(&operation.content, now) {
+            continue;
+        }
+
+        // quit if op is too big to fit in a block of any chain parameters version
+        let max_block_size = config.chain_parameters.upper_bounds().max_block_size as usize;
+        if operation.serialized_size() > max_block_size {
             return Err(ProtocolError::InvalidOperationError(format!(
                 "Operation {} exceeds max block size,  maximum authorized {} bytes but found {} bytes",
-                operation.id,
-                operation.serialized_size(),
-                config.max_serialized_operations_size_per_block
+                operation.id, max_block_size,
+                operation.serialized_size()
             )));
         };
 
@@ -422,13 +770,18 @@ pub(crate) fn note_operations_from_peer(
         new_operations.retain(|op_id, _| cache_read.checked_operations.peek(op_id).is_none());
     }
 
-    // optimized signature verification
-    verify_sigs_batch(
-        &new_operations
-            .iter()
-            .map(|(op_id, op)| (*op_id.get_hash(), op.signature, op.content_creator_pub_key))
-            .collect::<Vec<_>>(),
-    )?;
+    // optimized signature verification, including the authorizations of the fee payers
+    let mut signatures = Vec::with_capacity(new_operations.len());
+    for (op_id, op) in new_operations.iter() {
+        signatures.push((*op_id.get_hash(), op.signature, op.content_creator_pub_key));
+        if let Some(fee_payer_signature) = op
+            .get_fee_payer_signature_data(config.chain_id, config.thread_count)
+            .map_err(|err| ProtocolError::InvalidOperationError(err.to_string()))?
+        {
+            signatures.push(fee_payer_signature);
+        }
+    }
+    verify_sigs_batch(&signatures)?;
 
     {
         // add to checked operations
@@ -479,6 +832,9 @@ pub fn start_retrieval_thread(
     receiver_ext: MassaReceiver<OperationHandlerRetrievalCommand>,
     internal_sender: MassaSender<OperationHandlerPropagationCommand>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_db: SharedPeerDB,
+    block_messages: MassaReceiver<PeerMessageTuple>,
+    mip_store: MipStore,
     massa_metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
@@ -498,12 +854,22 @@ pub fn start_retrieval_thread(
                         .try_into()
                         .expect("asked_operations_buffer_capacity in config must be > 0"),
                 )),
+                rate_limiter: PeerRateLimiter::new(config.operations_rate_limit),
                 config,
                 operation_message_serializer: MessagesSerializer::new()
                     .with_operation_message_serializer(OperationMessageSerializer::new()),
                 op_batch_buffer: VecDeque::new(),
                 peer_cmd_sender,
-                _massa_metrics: massa_metrics,
+                peer_db,
+                massa_metrics,
+                syncing: false,
+                pool_warmed_up: false,
+                pending_operations_answered: HashMap::new(),
+                pending_operations_synced_peers: HashSet::new(),
+                block_messages,
+                deferred_operations: DeferredOperations::new(MAX_DEFERRED_OPERATION_BATCHES),
+                operations_to_send: HashMap::new(),
+                mip_store,
             };
             retrieval_thread.run();
         })
@@ -170,17 +170,24 @@ impl Deserializer<Announcement> for AnnouncementDeserializer {
 }
 
 impl Announcement {
+    /// Announces our listeners at each of our routable ips, so that dual-stack nodes
+    /// can be reached over both address families.
     pub fn new(
-        mut listeners: HashMap<SocketAddr, TransportType>,
-        routable_ip: Option<IpAddr>,
+        listeners: HashMap<SocketAddr, TransportType>,
+        routable_ips: &[IpAddr],
         keypair: &KeyPair,
     ) -> PeerNetResult<Self> {
         let mut buf: Vec<u8> = vec![];
         let length_serializer = U64VarIntSerializer::new();
-        //TODO: Hacky to fix and adapt to support multiple ip/listeners
-        if routable_ip.is_none() {
-            listeners = HashMap::default()
-        }
+        // without routable ip, we are not reachable and announce no listener
+        let listeners: HashMap<SocketAddr, TransportType> = listeners
+            .iter()
+            .flat_map(|(addr, transport)| {
+                routable_ips
+                    .iter()
+                    .map(move |ip| (SocketAddr::new(*ip, addr.port()), *transport))
+            })
+            .collect();
         length_serializer
             .serialize(&(listeners.len() as u64), &mut buf)
             .map_err(|err| {
@@ -188,8 +195,7 @@ impl Announcement {
                     .error("Announcement serialization", Some(err.to_string()))
             })?;
         for listener in &listeners {
-            let ip = routable_ip.unwrap_or_else(|| listener.0.ip());
-            let ip_bytes = match ip {
+            let ip_bytes = match listener.0.ip() {
                 IpAddr::V4(ip) => {
                     buf.push(4);
                     ip.octets().to_vec()
@@ -227,7 +233,8 @@ mod tests {
     use massa_serialization::{DeserializeError, Deserializer, Serializer};
     use massa_signature::KeyPair;
     use peernet::transports::TransportType;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
+    use std::net::SocketAddr;
 
     use super::AnnouncementSerializer;
 
@@ -237,7 +244,7 @@ mod tests {
         listeners.insert("127.0.0.1:8081".parse().unwrap(), TransportType::Tcp);
         listeners.insert("127.0.0.1:8082".parse().unwrap(), TransportType::Quic);
         let announcement =
-            Announcement::new(listeners, None, &KeyPair::generate(0).unwrap()).unwrap();
+            Announcement::new(listeners, &[], &KeyPair::generate(0).unwrap()).unwrap();
         let announcement_serializer = AnnouncementSerializer::new();
         let announcement_deserializer =
             AnnouncementDeserializer::new(AnnouncementDeserializerArgs { max_listeners: 100 });
@@ -250,4 +257,36 @@ mod tests {
             .unwrap();
         assert_eq!(announcement, announcement_deserialized);
     }
+
+    #[test]
+    fn test_dual_stack_announcement() {
+        let mut listeners = HashMap::new();
+        // a dual-stack bind and an IPv4-only one on the same port
+        listeners.insert("[::]:31244".parse().unwrap(), TransportType::Tcp);
+        listeners.insert("0.0.0.0:31244".parse().unwrap(), TransportType::Tcp);
+        let routable_ips = [
+            "203.0.113.7".parse().unwrap(),
+            "2001:db8::7".parse().unwrap(),
+        ];
+        let announcement =
+            Announcement::new(listeners, &routable_ips, &KeyPair::generate(0).unwrap()).unwrap();
+        let announced: HashSet<SocketAddr> = announcement.listeners.keys().copied().collect();
+        assert_eq!(
+            announced,
+            HashSet::from([
+                "203.0.113.7:31244".parse().unwrap(),
+                "[2001:db8::7]:31244".parse().unwrap(),
+            ])
+        );
+
+        let mut buf: Vec<u8> = vec![];
+        AnnouncementSerializer::new()
+            .serialize(&announcement, &mut buf)
+            .unwrap();
+        let (_, announcement_deserialized) =
+            AnnouncementDeserializer::new(AnnouncementDeserializerArgs { max_listeners: 100 })
+                .deserialize::<DeserializeError>(&buf)
+                .unwrap();
+        assert_eq!(announcement, announcement_deserialized);
+    }
 }
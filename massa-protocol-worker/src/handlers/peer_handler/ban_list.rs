@@ -0,0 +1,270 @@
+//! Bans set by the node operator, kept across restarts.
+//!
+//! Unlike the bans decided by the node on misbehaviour, which are lifted periodically,
+//! these bans last until they expire or are removed, and can target a whole subnet.
+//! They are enforced before the handshake, on the address of the remote peer,
+//! and during the handshake, once the peer id is known.
+//! Addresses of the whitelist are exempted from the subnet bans, to ban a subnet but a few of its hosts.
+//! Both lists are saved as JSON after each change, and can be edited on disk then reloaded without restarting the node.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::ErrorKind,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use massa_protocol_exports::{BanEntry, BanTarget, PeerId, ProtocolError};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+
+use crate::ip::to_canonical;
+
+pub type SharedBanList = Arc<RwLock<BanList>>;
+
+pub struct BanList {
+    /// file in which the list is saved
+    path: PathBuf,
+    /// expiry of each ban, none for a permanent ban
+    bans: HashMap<BanTarget, Option<MassaTime>>,
+    /// file in which the whitelist is saved
+    whitelist_path: PathBuf,
+    /// addresses exempted from the subnet bans
+    whitelist: HashSet<IpAddr>,
+}
+
+impl BanList {
+    /// Loads the list saved at `path` and the whitelist saved at `whitelist_path`, empty if there is no such file
+    pub fn load(path: &Path, whitelist_path: &Path) -> Result<Self, ProtocolError> {
+        let mut ban_list = BanList {
+            path: path.to_path_buf(),
+            bans: HashMap::new(),
+            whitelist_path: whitelist_path.to_path_buf(),
+            whitelist: HashSet::new(),
+        };
+        ban_list.reload()?;
+        Ok(ban_list)
+    }
+
+    /// Reads both lists again from disk, keeping the current ones if a file cannot be read
+    pub fn reload(&mut self) -> Result<(), ProtocolError> {
+        let entries: Vec<BanEntry> = match read_file(&self.path)? {
+            Some(buffer) => serde_json::from_slice(&buffer)?,
+            None => Vec::new(),
+        };
+        let whitelist: Vec<IpAddr> = match read_file(&self.whitelist_path)? {
+            Some(buffer) => serde_json::from_slice(&buffer)?,
+            None => Vec::new(),
+        };
+        self.bans = entries
+            .into_iter()
+            .map(|entry| (entry.target, entry.expires_at))
+            .collect();
+        self.whitelist = whitelist.into_iter().map(to_canonical).collect();
+        Ok(())
+    }
+
+    /// Saves the list, replacing the previous file atomically
+    fn save(&self) -> Result<(), ProtocolError> {
+        write_file(&self.path, &serde_json::to_vec_pretty(&self.entries())?)
+    }
+
+    /// Saves the whitelist, replacing the previous file atomically
+    fn save_whitelist(&self) -> Result<(), ProtocolError> {
+        write_file(
+            &self.whitelist_path,
+            &serde_json::to_vec_pretty(&self.whitelist())?,
+        )
+    }
+
+    /// Adds or replaces bans, and saves the list
+    pub fn insert(&mut self, entries: Vec<BanEntry>) -> Result<(), ProtocolError> {
+        for entry in entries {
+            self.bans.insert(entry.target, entry.expires_at);
+        }
+        self.save()
+    }
+
+    /// Removes bans, and saves the list if it changed
+    pub fn remove(&mut self, targets: &[BanTarget]) -> Result<(), ProtocolError> {
+        let len = self.bans.len();
+        for target in targets {
+            self.bans.remove(target);
+        }
+        if self.bans.len() != len {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Removes the bans expired at `now`, and saves the list if it changed
+    pub fn prune(&mut self, now: MassaTime) -> Result<(), ProtocolError> {
+        let len = self.bans.len();
+        self.bans
+            .retain(|_, expires_at| expires_at.map_or(true, |expires_at| expires_at > now));
+        if self.bans.len() != len {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Bans of the list, including the expired ones not pruned yet
+    pub fn entries(&self) -> Vec<BanEntry> {
+        self.bans
+            .iter()
+            .map(|(target, expires_at)| BanEntry {
+                target: *target,
+                expires_at: *expires_at,
+            })
+            .collect()
+    }
+
+    /// Adds addresses to the whitelist, and saves it
+    pub fn add_to_whitelist(&mut self, ips: Vec<IpAddr>) -> Result<(), ProtocolError> {
+        self.whitelist.extend(ips.into_iter().map(to_canonical));
+        self.save_whitelist()
+    }
+
+    /// Removes addresses from the whitelist, and saves it if it changed
+    pub fn remove_from_whitelist(&mut self, ips: &[IpAddr]) -> Result<(), ProtocolError> {
+        let len = self.whitelist.len();
+        for ip in ips {
+            self.whitelist.remove(&to_canonical(*ip));
+        }
+        if self.whitelist.len() != len {
+            self.save_whitelist()?;
+        }
+        Ok(())
+    }
+
+    /// Addresses of the whitelist, sorted
+    pub fn whitelist(&self) -> Vec<IpAddr> {
+        let mut whitelist: Vec<IpAddr> = self.whitelist.iter().copied().collect();
+        whitelist.sort_unstable();
+        whitelist
+    }
+
+    fn is_active(expires_at: &Option<MassaTime>, now: MassaTime) -> bool {
+        expires_at.map_or(true, |expires_at| expires_at > now)
+    }
+
+    /// Returns true if `peer_id` is banned at `now`
+    pub fn is_peer_banned(&self, peer_id: &PeerId, now: MassaTime) -> bool {
+        self.bans
+            .get(&BanTarget::Peer(*peer_id))
+            .map_or(false, |expires_at| Self::is_active(expires_at, now))
+    }
+
+    /// Returns true if `ip` belongs to a subnet banned at `now` and is not whitelisted
+    pub fn is_ip_banned(&self, ip: &IpAddr, now: MassaTime) -> bool {
+        let ip = to_canonical(*ip);
+        if self.whitelist.contains(&ip) {
+            return false;
+        }
+        self.bans.iter().any(|(target, expires_at)| match target {
+            BanTarget::Subnet(subnet) => subnet.contains(&ip) && Self::is_active(expires_at, now),
+            BanTarget::Peer(_) => false,
+        })
+    }
+}
+
+/// Reads the file at `path`, none if there is no such file or if it is empty
+fn read_file(path: &Path) -> Result<Option<Vec<u8>>, ProtocolError> {
+    match fs::read(path) {
+        Ok(buffer) if buffer.is_empty() => Ok(None),
+        Ok(buffer) => Ok(Some(buffer)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Writes `content` at `path`, replacing the previous file atomically
+fn write_file(path: &Path, content: &[u8]) -> Result<(), ProtocolError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_signature::KeyPair;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_ban_list_persistence_and_expiry() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("protocol/ban_list.json");
+        let whitelist_path = dir.path().join("protocol/peers_whitelist.json");
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let now = MassaTime::from_millis(10_000);
+
+        let mut ban_list = BanList::load(&path, &whitelist_path).unwrap();
+        ban_list
+            .insert(vec![
+                BanEntry {
+                    target: BanTarget::Peer(peer_id),
+                    expires_at: Some(MassaTime::from_millis(20_000)),
+                },
+                BanEntry {
+                    target: BanTarget::Subnet("10.1.0.0/16".parse().unwrap()),
+                    expires_at: None,
+                },
+            ])
+            .unwrap();
+
+        // the bans survive a restart
+        let mut ban_list = BanList::load(&path, &whitelist_path).unwrap();
+        assert!(ban_list.is_peer_banned(&peer_id, now));
+        assert!(ban_list.is_ip_banned(&"10.1.2.3".parse().unwrap(), now));
+        // IPv4-mapped addresses are matched against IPv4 subnets
+        assert!(ban_list.is_ip_banned(&"::ffff:10.1.2.3".parse().unwrap(), now));
+        assert!(!ban_list.is_ip_banned(&"10.2.0.1".parse().unwrap(), now));
+
+        // whitelisted addresses are exempted from the subnet bans
+        ban_list
+            .add_to_whitelist(vec!["::ffff:10.1.2.3".parse().unwrap()])
+            .unwrap();
+        assert!(!ban_list.is_ip_banned(&"10.1.2.3".parse().unwrap(), now));
+        assert!(ban_list.is_ip_banned(&"10.1.2.4".parse().unwrap(), now));
+
+        // the lists edited on disk are reloaded
+        fs::write(&whitelist_path, r#"["10.1.2.4"]"#).unwrap();
+        ban_list.reload().unwrap();
+        assert_eq!(
+            ban_list.whitelist(),
+            vec!["10.1.2.4".parse::<IpAddr>().unwrap()]
+        );
+        assert!(ban_list.is_ip_banned(&"10.1.2.3".parse().unwrap(), now));
+        ban_list
+            .remove_from_whitelist(&["10.1.2.4".parse().unwrap()])
+            .unwrap();
+        assert!(ban_list.whitelist().is_empty());
+
+        // the peer ban expires, the subnet ban does not
+        let later = MassaTime::from_millis(20_000);
+        assert!(!ban_list.is_peer_banned(&peer_id, later));
+        ban_list.prune(later).unwrap();
+        assert_eq!(
+            BanList::load(&path, &whitelist_path)
+                .unwrap()
+                .entries()
+                .len(),
+            1
+        );
+
+        ban_list
+            .remove(&[BanTarget::Subnet("10.1.0.0/16".parse().unwrap())])
+            .unwrap();
+        let ban_list = BanList::load(&path, &whitelist_path).unwrap();
+        assert!(ban_list.entries().is_empty());
+        assert!(!ban_list.is_ip_banned(&"10.1.2.3".parse().unwrap(), later));
+    }
+}
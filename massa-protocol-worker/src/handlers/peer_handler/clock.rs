@@ -0,0 +1,112 @@
+//! Estimation of the offset between our clock and the clocks of the connected peers,
+//! from the timestamps exchanged in `AskTime` / `Time` messages.
+
+use std::collections::{HashMap, HashSet};
+
+use massa_protocol_exports::PeerId;
+use massa_time::MassaTime;
+
+/// Round trip above which a clock sample is considered too imprecise to be kept
+pub(crate) const MAX_CLOCK_SAMPLE_ROUND_TRIP: MassaTime = MassaTime::from_millis(2_000);
+
+/// Minimal number of sampled peers for the network time offset to be computed
+pub(crate) const MIN_CLOCK_SAMPLES: usize = 3;
+
+#[derive(Default)]
+pub(crate) struct ClockSamples {
+    /// latest offset (in milliseconds) of the clock of each peer relative to ours
+    offsets: HashMap<PeerId, i64>,
+}
+
+impl ClockSamples {
+    /// Record the answer of a peer to the time request we sent at `asked_at`.
+    /// The peer is assumed to have read its clock halfway through the round trip.
+    ///
+    /// Returns false if the sample was discarded.
+    pub fn add_sample(
+        &mut self,
+        peer_id: PeerId,
+        asked_at: MassaTime,
+        peer_time: MassaTime,
+        now: MassaTime,
+    ) -> bool {
+        if asked_at > now {
+            return false;
+        }
+        let round_trip = now.saturating_sub(asked_at);
+        if round_trip > MAX_CLOCK_SAMPLE_ROUND_TRIP {
+            return false;
+        }
+        let local_time = asked_at.as_millis() + round_trip.as_millis() / 2;
+        let offset = peer_time.as_millis() as i64 - local_time as i64;
+        self.offsets.insert(peer_id, offset);
+        true
+    }
+
+    /// Forget the samples of the peers that are not connected anymore
+    pub fn retain_connected(&mut self, connected: &HashSet<PeerId>) {
+        self.offsets
+            .retain(|peer_id, _| connected.contains(peer_id));
+    }
+
+    /// Median offset (in milliseconds) of the clocks of the sampled peers relative to ours,
+    /// positive if the network is ahead of us.
+    /// None if too few peers were sampled for a single one not to be able to bias it.
+    pub fn median_offset(&self) -> Option<i64> {
+        if self.offsets.len() < MIN_CLOCK_SAMPLES {
+            return None;
+        }
+        let mut offsets: Vec<i64> = self.offsets.values().copied().collect();
+        offsets.sort_unstable();
+        let middle = offsets.len() / 2;
+        if offsets.len() % 2 == 0 {
+            Some((offsets[middle - 1] + offsets[middle]) / 2)
+        } else {
+            Some(offsets[middle])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_median_offset() {
+        let mut samples = ClockSamples::default();
+        let asked_at = MassaTime::from_millis(10_000);
+        let now = MassaTime::from_millis(10_200);
+        let peers: Vec<PeerId> = (0..4).map(|_| peer_id()).collect();
+
+        // peer clocks read at 10_100 on our clock
+        assert!(samples.add_sample(peers[0], asked_at, MassaTime::from_millis(10_600), now));
+        assert!(samples.add_sample(peers[1], asked_at, MassaTime::from_millis(10_400), now));
+        assert_eq!(samples.median_offset(), None);
+        assert!(samples.add_sample(peers[2], asked_at, MassaTime::from_millis(9_100), now));
+        assert_eq!(samples.median_offset(), Some(300));
+        assert!(samples.add_sample(peers[3], asked_at, MassaTime::from_millis(10_200), now));
+        assert_eq!(samples.median_offset(), Some(200));
+
+        // imprecise or inconsistent samples are discarded
+        assert!(!samples.add_sample(
+            peers[0],
+            asked_at,
+            MassaTime::from_millis(0),
+            asked_at.saturating_add(
+                MAX_CLOCK_SAMPLE_ROUND_TRIP.saturating_add(MassaTime::from_millis(1))
+            )
+        ));
+        assert!(!samples.add_sample(peers[0], now, MassaTime::from_millis(0), asked_at));
+        assert_eq!(samples.median_offset(), Some(200));
+
+        samples.retain_connected(&peers[1..].iter().copied().collect());
+        assert_eq!(samples.median_offset(), Some(100));
+        samples.retain_connected(&peers[2..].iter().copied().collect());
+        assert_eq!(samples.median_offset(), None);
+    }
+}
@@ -0,0 +1,329 @@
+//! Kademlia-style discovery of the peers, by node id.
+//!
+//! Each peer gets a discovery key, the hash of its node id. The peers are sorted in buckets by the
+//! XOR distance between their key and ours: bucket `i` holds the peers whose distance has its highest set bit at `i`.
+//! The buckets that were not looked up for a while are refreshed by asking the closest connected peers for the
+//! peers they know around a random key of the bucket. The peers found are tested and added to the peer database
+//! like the peers exchanged by our connections, and enter the routing table once they are trusted.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_protocol_exports::PeerId;
+use peernet::transports::TransportType;
+use rand::RngCore;
+
+/// Number of bits of a discovery key, and number of buckets of the routing table
+const KEY_BITS: usize = HASH_SIZE_BYTES * 8;
+
+/// Number of connected peers asked for the closest peers to a key at each lookup
+const LOOKUP_PARALLELISM: usize = 3;
+
+/// Discovery key of a peer
+pub(crate) fn discovery_key(peer_id: &PeerId) -> Hash {
+    Hash::compute_from(&peer_id.get_public_key().to_bytes())
+}
+
+/// XOR distance between two discovery keys, comparable as a big-endian number
+fn distance(a: &Hash, b: &Hash) -> [u8; HASH_SIZE_BYTES] {
+    let mut res = *a.to_bytes();
+    for (byte, other) in res.iter_mut().zip(b.to_bytes()) {
+        *byte ^= other;
+    }
+    res
+}
+
+/// Picks the connected peers to ask for the peers closest to `key`:
+/// the `preferred` ones first, then the ones closest to `key`.
+pub(crate) fn lookup_targets(
+    key: &Hash,
+    mut peers: Vec<PeerId>,
+    preferred: &[PeerId],
+) -> Vec<PeerId> {
+    peers.sort_by_cached_key(|peer_id| {
+        (
+            !preferred.contains(peer_id),
+            distance(key, &discovery_key(peer_id)),
+        )
+    });
+    peers.truncate(LOOKUP_PARALLELISM);
+    peers
+}
+
+/// Peer known by the routing table
+type DiscoveredPeer = (PeerId, HashMap<SocketAddr, TransportType>);
+
+#[derive(Default)]
+struct Bucket {
+    /// peers of the bucket, oldest first
+    peers: Vec<DiscoveredPeer>,
+    /// last time the bucket was looked up
+    last_lookup: Option<Instant>,
+}
+
+/// Peers sorted by XOR distance to our own discovery key
+pub(crate) struct RoutingTable {
+    local_key: Hash,
+    /// maximum number of peers per bucket
+    bucket_size: usize,
+    buckets: Vec<Bucket>,
+}
+
+impl RoutingTable {
+    pub(crate) fn new(local_peer_id: &PeerId, bucket_size: usize) -> Self {
+        RoutingTable {
+            local_key: discovery_key(local_peer_id),
+            bucket_size,
+            buckets: (0..KEY_BITS).map(|_| Bucket::default()).collect(),
+        }
+    }
+
+    /// Our own discovery key
+    pub(crate) fn local_key(&self) -> Hash {
+        self.local_key
+    }
+
+    /// Index of the bucket of a key, `None` for our own key
+    fn bucket_index(&self, key: &Hash) -> Option<usize> {
+        let distance = distance(&self.local_key, key);
+        let leading_zeros = distance
+            .iter()
+            .position(|byte| *byte != 0)
+            .map(|index| index * 8 + distance[index].leading_zeros() as usize)?;
+        Some(KEY_BITS - 1 - leading_zeros)
+    }
+
+    /// Adds a peer, or updates its listeners if it is already known.
+    /// A full bucket keeps its oldest peers, which are the most likely to stay online.
+    pub(crate) fn insert(
+        &mut self,
+        peer_id: PeerId,
+        listeners: HashMap<SocketAddr, TransportType>,
+    ) -> bool {
+        let Some(index) = self.bucket_index(&discovery_key(&peer_id)) else {
+            return false;
+        };
+        let bucket_size = self.bucket_size;
+        let bucket = &mut self.buckets[index];
+        if let Some((_, known_listeners)) = bucket.peers.iter_mut().find(|(id, _)| *id == peer_id) {
+            *known_listeners = listeners;
+            return true;
+        }
+        if bucket.peers.len() >= bucket_size {
+            return false;
+        }
+        bucket.peers.push((peer_id, listeners));
+        true
+    }
+
+    /// Removes the peers that do not match `keep`
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&PeerId) -> bool) {
+        for bucket in &mut self.buckets {
+            bucket.peers.retain(|(peer_id, _)| keep(peer_id));
+        }
+    }
+
+    /// Number of peers in the routing table
+    pub(crate) fn len(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.peers.len()).sum()
+    }
+
+    /// Known peers closest to `key`, closest first
+    pub(crate) fn closest(&self, key: &Hash, count: usize) -> Vec<DiscoveredPeer> {
+        let mut peers: Vec<&DiscoveredPeer> = self
+            .buckets
+            .iter()
+            .flat_map(|bucket| bucket.peers.iter())
+            .collect();
+        peers.sort_unstable_by_key(|(peer_id, _)| distance(key, &discovery_key(peer_id)));
+        peers.into_iter().take(count).cloned().collect()
+    }
+
+    /// Picks a random key in the next bucket to refresh, if any, and notes the bucket as looked up.
+    ///
+    /// Only the buckets down to the one below the closest known peer are refreshed:
+    /// the lower ones hold a vanishing share of the key space and are almost always empty.
+    pub(crate) fn next_lookup(&mut self, now: Instant, refresh_interval: Duration) -> Option<Hash> {
+        let lowest = self
+            .buckets
+            .iter()
+            .position(|bucket| !bucket.peers.is_empty())?
+            .saturating_sub(1);
+        let index = (lowest..KEY_BITS).rev().find(|index| {
+            self.buckets[*index].last_lookup.map_or(true, |last| {
+                now.saturating_duration_since(last) >= refresh_interval
+            })
+        })?;
+        self.buckets[index].last_lookup = Some(now);
+        Some(self.random_key_in_bucket(index))
+    }
+
+    /// Random key whose distance to ours has its highest set bit at `index`
+    fn random_key_in_bucket(&self, index: usize) -> Hash {
+        let mut distance = [0u8; HASH_SIZE_BYTES];
+        rand::thread_rng().fill_bytes(&mut distance);
+        // bits are numbered from the least significant one of the big-endian distance
+        let byte_index = HASH_SIZE_BYTES - 1 - index / 8;
+        let bit = 1u8 << (index % 8);
+        for byte in &mut distance[..byte_index] {
+            *byte = 0;
+        }
+        distance[byte_index] = (distance[byte_index] & (bit - 1)) | bit;
+        let mut key = *self.local_key.to_bytes();
+        for (byte, d) in key.iter_mut().zip(distance) {
+            *byte ^= d;
+        }
+        Hash::from_bytes(&key)
+    }
+}
+
+/// Lookups sent to our peers and waiting for their answer
+#[derive(Default)]
+pub(crate) struct PendingLookups {
+    /// key looked up and time of the request, per peer asked
+    lookups: HashMap<PeerId, (Hash, Instant)>,
+}
+
+impl PendingLookups {
+    /// Notes that `peer_id` was asked for the peers closest to `key`
+    pub(crate) fn insert(&mut self, peer_id: PeerId, key: Hash, now: Instant) {
+        self.lookups.insert(peer_id, (key, now));
+    }
+
+    /// Takes the lookup answered by `peer_id`, returning `None` if we did not ask it anything
+    pub(crate) fn take(&mut self, peer_id: &PeerId) -> Option<Hash> {
+        self.lookups.remove(peer_id).map(|(key, _)| key)
+    }
+
+    /// Forgets the lookups that were not answered in time or whose peer is not connected anymore
+    pub(crate) fn prune(&mut self, connected: &HashSet<PeerId>, now: Instant, timeout: Duration) {
+        self.lookups.retain(|peer_id, (_, asked_at)| {
+            connected.contains(peer_id) && now.saturating_duration_since(*asked_at) < timeout
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        time::{Duration, Instant},
+    };
+
+    use massa_protocol_exports::PeerId;
+    use massa_signature::KeyPair;
+
+    use super::{
+        discovery_key, distance, lookup_targets, PendingLookups, RoutingTable, KEY_BITS,
+        LOOKUP_PARALLELISM,
+    };
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    fn contains(table: &RoutingTable, peer_id: &PeerId) -> bool {
+        table
+            .closest(&discovery_key(peer_id), 1)
+            .first()
+            .is_some_and(|(id, _)| id == peer_id)
+    }
+
+    #[test]
+    fn test_routing_table_closest_peers() {
+        let local = peer_id();
+        let mut table = RoutingTable::new(&local, 20);
+        assert!(!table.insert(local, HashMap::new()));
+        let peers: Vec<PeerId> = (0..50).map(|_| peer_id()).collect();
+        for peer in &peers {
+            table.insert(*peer, HashMap::new());
+        }
+        assert_eq!(table.len(), peers.len());
+
+        let target = discovery_key(&peers[0]);
+        let closest = table.closest(&target, 5);
+        assert_eq!(closest.len(), 5);
+        assert_eq!(closest[0].0, peers[0]);
+        let distances: Vec<_> = closest
+            .iter()
+            .map(|(peer_id, _)| distance(&target, &discovery_key(peer_id)))
+            .collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        table.retain(|peer_id| peer_id != &peers[0]);
+        assert!(!contains(&table, &peers[0]));
+        assert_eq!(table.len(), peers.len() - 1);
+    }
+
+    #[test]
+    fn test_routing_table_full_bucket_keeps_oldest_peers() {
+        let local = peer_id();
+        let mut table = RoutingTable::new(&local, 1);
+        // about half of the keys fall in the highest bucket
+        let in_highest_bucket: Vec<PeerId> = (0..)
+            .map(|_| peer_id())
+            .filter(|peer_id| table.bucket_index(&discovery_key(peer_id)) == Some(KEY_BITS - 1))
+            .take(2)
+            .collect();
+        let (oldest, newest) = (in_highest_bucket[0], in_highest_bucket[1]);
+        assert!(table.insert(oldest, HashMap::new()));
+        assert!(!table.insert(newest, HashMap::new()));
+        assert!(contains(&table, &oldest));
+        assert!(!contains(&table, &newest));
+    }
+
+    #[test]
+    fn test_routing_table_refreshes_buckets() {
+        let local = peer_id();
+        let mut table = RoutingTable::new(&local, 20);
+        let now = Instant::now();
+        let refresh = Duration::from_secs(60);
+        // nothing to look up without any peer
+        assert_eq!(table.next_lookup(now, refresh), None);
+
+        table.insert(peer_id(), HashMap::new());
+        let mut looked_up = HashSet::new();
+        while let Some(key) = table.next_lookup(now, refresh) {
+            let index = table.bucket_index(&key).unwrap();
+            assert!(looked_up.insert(index), "bucket {} looked up twice", index);
+        }
+        assert!(looked_up.contains(&(KEY_BITS - 1)));
+        // the buckets are looked up again once the refresh interval elapsed
+        assert!(table.next_lookup(now + refresh, refresh).is_some());
+    }
+
+    #[test]
+    fn test_lookup_targets() {
+        let peers: Vec<PeerId> = (0..10).map(|_| peer_id()).collect();
+        let key = discovery_key(&peers[3]);
+        let targets = lookup_targets(&key, peers.clone(), &[peers[7]]);
+        assert_eq!(targets.len(), LOOKUP_PARALLELISM);
+        // the preferred peer comes first, then the peer whose key is looked up
+        assert_eq!(targets[0], peers[7]);
+        assert_eq!(targets[1], peers[3]);
+    }
+
+    #[test]
+    fn test_pending_lookups() {
+        let peer = peer_id();
+        let key = discovery_key(&peer_id());
+        let now = Instant::now();
+        let mut pending = PendingLookups::default();
+        pending.insert(peer, key, now);
+        assert_eq!(pending.take(&peer), Some(key));
+        // an answer is only accepted once
+        assert_eq!(pending.take(&peer), None);
+
+        pending.insert(peer, key, now);
+        pending.prune(
+            &HashSet::from([peer]),
+            now + Duration::from_secs(60),
+            Duration::from_secs(30),
+        );
+        assert_eq!(pending.take(&peer), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Limit of the handshakes attempted with each address, so that a single host reconnecting in a loop
+//! cannot churn the connection slots nor keep the handshake workers busy.
+//!
+//! The attempts are counted over a sliding window of one minute. An address exceeding the limit is put
+//! in cooldown: its handshakes are refused until the cooldown ends, whatever its attempts meanwhile.
+//! Unlike the bans of the ban list, the cooldowns are only kept in memory.
+//!
+//! The loopback addresses are never limited: the dials relayed through a SOCKS5 proxy all target
+//! a local relay, so limiting them would cut the node from every peer it dials.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::ip::to_canonical;
+
+/// Window over which the handshake attempts of an address are counted
+const WINDOW: Duration = Duration::from_secs(60);
+
+pub(crate) type SharedHandshakeLimiter = Arc<Mutex<HandshakeLimiter>>;
+
+/// Outcome of a handshake attempt with an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HandshakeAdmission {
+    /// the handshake can go on
+    Allowed,
+    /// the address just exceeded the limit and was put in cooldown
+    CooldownStarted,
+    /// the address is in cooldown
+    InCooldown,
+}
+
+/// Handshakes attempted recently with each address
+pub(crate) struct HandshakeLimiter {
+    /// number of attempts within the window after which an address is put in cooldown, 0 for no limit
+    max_attempts: usize,
+    /// duration of the cooldown
+    cooldown: Duration,
+    /// time of the attempts of each address within the window, oldest first
+    attempts: HashMap<IpAddr, VecDeque<Instant>>,
+    /// end of the cooldown of each address
+    cooldowns: HashMap<IpAddr, Instant>,
+    /// last time the expired attempts and cooldowns were forgotten
+    last_prune: Instant,
+}
+
+impl HandshakeLimiter {
+    pub(crate) fn new(max_attempts: usize, cooldown: Duration) -> Self {
+        HandshakeLimiter {
+            max_attempts,
+            cooldown,
+            attempts: HashMap::new(),
+            cooldowns: HashMap::new(),
+            last_prune: Instant::now(),
+        }
+    }
+
+    /// Notes a handshake attempted with `ip` at `now`, and tells whether it can go on
+    pub(crate) fn note_attempt(&mut self, ip: IpAddr, now: Instant) -> HandshakeAdmission {
+        if self.max_attempts == 0 {
+            return HandshakeAdmission::Allowed;
+        }
+        let ip = to_canonical(ip);
+        if ip.is_loopback() {
+            return HandshakeAdmission::Allowed;
+        }
+        if now.saturating_duration_since(self.last_prune) >= WINDOW {
+            self.prune(now);
+        }
+        if self.cooldowns.get(&ip).is_some_and(|end| *end > now) {
+            return HandshakeAdmission::InCooldown;
+        }
+        let attempts = self.attempts.entry(ip).or_default();
+        while let Some(oldest) = attempts.front() {
+            if now.saturating_duration_since(*oldest) < WINDOW {
+                break;
+            }
+            attempts.pop_front();
+        }
+        attempts.push_back(now);
+        if attempts.len() <= self.max_attempts {
+            return HandshakeAdmission::Allowed;
+        }
+        self.attempts.remove(&ip);
+        self.cooldowns.insert(ip, now + self.cooldown);
+        HandshakeAdmission::CooldownStarted
+    }
+
+    /// Forgets the attempts out of the window and the cooldowns that ended
+    fn prune(&mut self, now: Instant) {
+        self.attempts.retain(|_, attempts| {
+            attempts
+                .back()
+                .is_some_and(|last| now.saturating_duration_since(*last) < WINDOW)
+        });
+        self.cooldowns.retain(|_, end| *end > now);
+        self.last_prune = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_limit() {
+        let mut limiter = HandshakeLimiter::new(2, Duration::from_secs(300));
+        let (ip_a, ip_b): (IpAddr, IpAddr) =
+            ("192.0.2.1".parse().unwrap(), "192.0.2.2".parse().unwrap());
+        let start = Instant::now();
+
+        assert_eq!(
+            limiter.note_attempt(ip_a, start),
+            HandshakeAdmission::Allowed
+        );
+        assert_eq!(
+            limiter.note_attempt(ip_a, start + Duration::from_secs(10)),
+            HandshakeAdmission::Allowed
+        );
+        // the other addresses are not limited
+        assert_eq!(
+            limiter.note_attempt(ip_b, start),
+            HandshakeAdmission::Allowed
+        );
+        // the first attempt of A left the window
+        assert_eq!(
+            limiter.note_attempt(ip_a, start + Duration::from_secs(61)),
+            HandshakeAdmission::Allowed
+        );
+        // the IPv4-mapped form of an address shares its attempts
+        assert_eq!(
+            limiter.note_attempt(
+                "::ffff:192.0.2.1".parse().unwrap(),
+                start + Duration::from_secs(62)
+            ),
+            HandshakeAdmission::CooldownStarted
+        );
+        assert_eq!(
+            limiter.note_attempt(ip_a, start + Duration::from_secs(300)),
+            HandshakeAdmission::InCooldown
+        );
+        // allowed again once the cooldown ended
+        assert_eq!(
+            limiter.note_attempt(ip_a, start + Duration::from_secs(362)),
+            HandshakeAdmission::Allowed
+        );
+        assert!(limiter.cooldowns.is_empty());
+        assert!(!limiter.attempts.contains_key(&ip_b));
+
+        // the loopback addresses of the proxied dials are never put in cooldown
+        for loopback in ["127.0.0.1", "::1", "::ffff:127.0.0.1"] {
+            for _ in 0..10 {
+                assert_eq!(
+                    limiter.note_attempt(loopback.parse().unwrap(), start),
+                    HandshakeAdmission::Allowed
+                );
+            }
+        }
+        assert!(!limiter.attempts.keys().any(|ip| ip.is_loopback()));
+        assert!(limiter.cooldowns.is_empty());
+
+        // no limit
+        let mut limiter = HandshakeLimiter::new(0, Duration::from_secs(300));
+        for _ in 0..10 {
+            assert_eq!(
+                limiter.note_attempt(ip_a, start),
+                HandshakeAdmission::Allowed
+            );
+        }
+    }
+}
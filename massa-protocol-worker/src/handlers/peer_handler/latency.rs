@@ -0,0 +1,172 @@
+//! Round trip latency to the connected peers, measured with keepalive `Ping` / `Pong` messages.
+//!
+//! The round trip time and its deviation are smoothed as TCP does for its retransmission timer (RFC 6298).
+//! A peer leaving several pings in a row unanswered is considered dead and disconnected.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+use massa_protocol_exports::{PeerId, PeerLatency};
+use massa_time::MassaTime;
+
+/// Number of pings in a row left unanswered after which a peer is considered dead
+pub(crate) const MAX_LOST_PINGS: u64 = 3;
+
+/// Width of the latency buckets ranking the peers
+const LATENCY_BUCKET_WIDTH: MassaTime = MassaTime::from_millis(50);
+
+/// Latency bucket ranking a peer, the lower the better: its round trip time plus jitter
+/// in steps of `LATENCY_BUCKET_WIDTH`, so that close latencies are ranked by the next criteria.
+/// The peers whose latency was not measured yet come last.
+pub(crate) fn latency_bucket(latency: Option<&PeerLatency>) -> u64 {
+    latency.map_or(u64::MAX, |latency| {
+        latency.rtt.saturating_add(latency.jitter).as_millis() / LATENCY_BUCKET_WIDTH.as_millis()
+    })
+}
+
+#[derive(Default, Clone)]
+pub struct PeerLatencies {
+    /// latency of each connected peer that answered a ping
+    latencies: HashMap<PeerId, PeerLatency>,
+    /// ping waiting for an answer from each peer, with its nonce and sending time
+    pending: HashMap<PeerId, (u64, Instant)>,
+    /// number of pings in a row left unanswered by each peer
+    lost: HashMap<PeerId, u64>,
+}
+
+impl PeerLatencies {
+    /// Starts a ping of `peer_id` at `now`, counting the previous one as lost if it was not answered.
+    ///
+    /// Returns the nonce of the ping to send, or None if the peer left too many pings unanswered:
+    /// it is then forgotten, and should be disconnected.
+    pub fn start_ping(&mut self, peer_id: PeerId, now: Instant) -> Option<u64> {
+        if self.pending.contains_key(&peer_id) {
+            let lost = self.lost.entry(peer_id).or_default();
+            *lost += 1;
+            if *lost >= MAX_LOST_PINGS {
+                self.forget(&peer_id);
+                return None;
+            }
+        }
+        let nonce = rand::random();
+        self.pending.insert(peer_id, (nonce, now));
+        Some(nonce)
+    }
+
+    /// Records the answer of `peer_id` to our pending ping at `now`.
+    ///
+    /// Returns false if no ping with that nonce was waiting for an answer.
+    pub fn note_pong(&mut self, peer_id: PeerId, nonce: u64, now: Instant) -> bool {
+        let sent_at = match self.pending.get(&peer_id) {
+            Some((pending_nonce, sent_at)) if *pending_nonce == nonce => *sent_at,
+            _ => return false,
+        };
+        self.pending.remove(&peer_id);
+        self.lost.remove(&peer_id);
+        let sample = now.saturating_duration_since(sent_at).as_millis() as u64;
+        self.latencies
+            .entry(peer_id)
+            .and_modify(|latency| {
+                // jitter = 3/4 jitter + 1/4 |rtt - sample|, then rtt = 7/8 rtt + 1/8 sample
+                let rtt = latency.rtt.as_millis();
+                let deviation = rtt.abs_diff(sample);
+                latency.jitter =
+                    MassaTime::from_millis((3 * latency.jitter.as_millis() + deviation) / 4);
+                latency.rtt = MassaTime::from_millis((7 * rtt + sample) / 8);
+            })
+            .or_insert(PeerLatency {
+                rtt: MassaTime::from_millis(sample),
+                jitter: MassaTime::from_millis(sample / 2),
+            });
+        true
+    }
+
+    /// Latency of the peers that answered a ping
+    pub fn latencies(&self) -> &HashMap<PeerId, PeerLatency> {
+        &self.latencies
+    }
+
+    /// Forget the peers that are not connected anymore
+    pub fn retain_connected(&mut self, connected: &HashSet<PeerId>) {
+        self.latencies
+            .retain(|peer_id, _| connected.contains(peer_id));
+        self.pending
+            .retain(|peer_id, _| connected.contains(peer_id));
+        self.lost.retain(|peer_id, _| connected.contains(peer_id));
+    }
+
+    fn forget(&mut self, peer_id: &PeerId) {
+        self.latencies.remove(peer_id);
+        self.pending.remove(peer_id);
+        self.lost.remove(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use massa_signature::KeyPair;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_peer_latencies() {
+        let mut latencies = PeerLatencies::default();
+        let (peer, dead_peer) = (peer_id(), peer_id());
+        let start = Instant::now();
+
+        // the first sample sets the round trip time, and half of it as jitter
+        let nonce = latencies.start_ping(peer, start).unwrap();
+        assert!(!latencies.note_pong(peer, nonce.wrapping_add(1), start));
+        assert!(latencies.note_pong(peer, nonce, start + Duration::from_millis(80)));
+        assert_eq!(
+            latencies.latencies()[&peer],
+            PeerLatency {
+                rtt: MassaTime::from_millis(80),
+                jitter: MassaTime::from_millis(40),
+            }
+        );
+        // a ping is answered once
+        assert!(!latencies.note_pong(peer, nonce, start + Duration::from_millis(90)));
+
+        // the next samples are smoothed
+        let sent_at = start + Duration::from_secs(10);
+        let nonce = latencies.start_ping(peer, sent_at).unwrap();
+        assert!(latencies.note_pong(peer, nonce, sent_at + Duration::from_millis(160)));
+        assert_eq!(
+            latencies.latencies()[&peer],
+            PeerLatency {
+                rtt: MassaTime::from_millis(90),
+                jitter: MassaTime::from_millis(50),
+            }
+        );
+
+        // a peer leaving too many pings in a row unanswered is dead
+        for _ in 0..MAX_LOST_PINGS {
+            assert!(latencies.start_ping(dead_peer, start).is_some());
+        }
+        assert_eq!(latencies.start_ping(dead_peer, start), None);
+        assert!(latencies.start_ping(dead_peer, start).is_some());
+
+        latencies.retain_connected(&HashSet::from([dead_peer]));
+        assert!(latencies.latencies().is_empty());
+    }
+
+    #[test]
+    fn test_latency_bucket() {
+        let latency = |rtt, jitter| PeerLatency {
+            rtt: MassaTime::from_millis(rtt),
+            jitter: MassaTime::from_millis(jitter),
+        };
+        assert_eq!(latency_bucket(Some(&latency(30, 10))), 0);
+        assert_eq!(latency_bucket(Some(&latency(40, 10))), 1);
+        assert_eq!(latency_bucket(Some(&latency(200, 120))), 6);
+        assert_eq!(latency_bucket(None), u64::MAX);
+    }
+}
@@ -1,8 +1,15 @@
 use std::{collections::HashMap, net::SocketAddr, ops::Bound::Included};
 
+use massa_hash::{Hash, HashDeserializer, HashSerializer};
+use massa_models::heartbeat::{Heartbeat, HeartbeatDeserializer, SecureShareHeartbeat};
+use massa_models::node_link::{
+    NodeAddressLinkProof, NodeAddressLinkProofDeserializer, NodeAddressLinkProofSerializer,
+};
+use massa_models::secure_share::{SecureShareDeserializer, SecureShareSerializer};
 use massa_models::serialization::{IpAddrDeserializer, IpAddrSerializer};
 use massa_protocol_exports::{PeerId, PeerIdDeserializer, PeerIdSerializer};
 use massa_serialization::{Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
+use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
 use nom::{
     error::{context, ContextError, ParseError},
     multi::length_count,
@@ -20,6 +27,29 @@ pub enum PeerManagementMessage {
     NewPeerConnected((PeerId, HashMap<SocketAddr, TransportType>)),
     // Receive the ip addresses sent by a peer that is already connected.
     ListPeers(Vec<(PeerId, HashMap<SocketAddr, TransportType>)>),
+    // A peer asks us to dial back its announced routable address.
+    AskReachabilityTest,
+    // Result of the dial back we asked to a peer (true if it could reach us).
+    ReachabilityTestResult(bool),
+    // Signed liveness heartbeats of staking addresses.
+    Heartbeats(Vec<SecureShareHeartbeat>),
+    // A peer asks for our clock, sending the time of its own clock when asking.
+    AskTime(MassaTime),
+    // Our clock when answering a time request, along with the time of the request.
+    Time {
+        asked_at: MassaTime,
+        time: MassaTime,
+    },
+    // Links between node ids and staking addresses, signed by both keys.
+    NodeAddressLinks(Vec<NodeAddressLinkProof>),
+    // A peer asks for the peers we know that are the closest to a discovery key.
+    FindPeers(Hash),
+    // The peers closest to the discovery key of a lookup we asked.
+    ClosestPeers(Vec<(PeerId, HashMap<SocketAddr, TransportType>)>),
+    // Keepalive ping measuring the round trip time of the connection, carrying a random nonce.
+    Ping(u64),
+    // Answer to a ping, carrying its nonce.
+    Pong(u64),
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -27,6 +57,16 @@ pub enum PeerManagementMessage {
 pub enum MessageTypeId {
     NewPeerConnected = 0,
     ListPeers = 1,
+    AskReachabilityTest = 2,
+    ReachabilityTestResult = 3,
+    Heartbeats = 4,
+    AskTime = 5,
+    Time = 6,
+    NodeAddressLinks = 7,
+    FindPeers = 8,
+    ClosestPeers = 9,
+    Ping = 10,
+    Pong = 11,
 }
 
 impl From<&PeerManagementMessage> for MessageTypeId {
@@ -34,6 +74,18 @@ impl From<&PeerManagementMessage> for MessageTypeId {
         match message {
             PeerManagementMessage::NewPeerConnected(_) => MessageTypeId::NewPeerConnected,
             PeerManagementMessage::ListPeers(_) => MessageTypeId::ListPeers,
+            PeerManagementMessage::AskReachabilityTest => MessageTypeId::AskReachabilityTest,
+            PeerManagementMessage::ReachabilityTestResult(_) => {
+                MessageTypeId::ReachabilityTestResult
+            }
+            PeerManagementMessage::Heartbeats(_) => MessageTypeId::Heartbeats,
+            PeerManagementMessage::AskTime(_) => MessageTypeId::AskTime,
+            PeerManagementMessage::Time { .. } => MessageTypeId::Time,
+            PeerManagementMessage::NodeAddressLinks(_) => MessageTypeId::NodeAddressLinks,
+            PeerManagementMessage::FindPeers(_) => MessageTypeId::FindPeers,
+            PeerManagementMessage::ClosestPeers(_) => MessageTypeId::ClosestPeers,
+            PeerManagementMessage::Ping(_) => MessageTypeId::Ping,
+            PeerManagementMessage::Pong(_) => MessageTypeId::Pong,
         }
     }
 }
@@ -44,6 +96,10 @@ pub struct PeerManagementMessageSerializer {
     length_serializer: U64VarIntSerializer,
     ip_addr_serializer: IpAddrSerializer,
     peer_id_serializer: PeerIdSerializer,
+    secure_share_serializer: SecureShareSerializer,
+    time_serializer: MassaTimeSerializer,
+    node_link_serializer: NodeAddressLinkProofSerializer,
+    hash_serializer: HashSerializer,
 }
 
 impl PeerManagementMessageSerializer {
@@ -53,6 +109,10 @@ impl PeerManagementMessageSerializer {
             length_serializer: U64VarIntSerializer::new(),
             ip_addr_serializer: IpAddrSerializer::new(),
             peer_id_serializer: PeerIdSerializer::new(),
+            secure_share_serializer: SecureShareSerializer::new(),
+            time_serializer: MassaTimeSerializer::new(),
+            node_link_serializer: NodeAddressLinkProofSerializer::new(),
+            hash_serializer: HashSerializer::new(),
         }
     }
 }
@@ -77,7 +137,8 @@ impl Serializer<PeerManagementMessage> for PeerManagementMessageSerializer {
                     buffer.push(*transport_type as u8);
                 }
             }
-            PeerManagementMessage::ListPeers(peers) => {
+            PeerManagementMessage::ListPeers(peers)
+            | PeerManagementMessage::ClosestPeers(peers) => {
                 self.length_serializer
                     .serialize(&(peers.len() as u64), buffer)?;
                 for (peer_id, listeners) in peers {
@@ -92,6 +153,37 @@ impl Serializer<PeerManagementMessage> for PeerManagementMessageSerializer {
                     }
                 }
             }
+            PeerManagementMessage::AskReachabilityTest => {}
+            PeerManagementMessage::ReachabilityTestResult(reachable) => {
+                buffer.push(u8::from(*reachable));
+            }
+            PeerManagementMessage::Heartbeats(heartbeats) => {
+                self.length_serializer
+                    .serialize(&(heartbeats.len() as u64), buffer)?;
+                for heartbeat in heartbeats {
+                    self.secure_share_serializer.serialize(heartbeat, buffer)?;
+                }
+            }
+            PeerManagementMessage::AskTime(asked_at) => {
+                self.time_serializer.serialize(asked_at, buffer)?;
+            }
+            PeerManagementMessage::Time { asked_at, time } => {
+                self.time_serializer.serialize(asked_at, buffer)?;
+                self.time_serializer.serialize(time, buffer)?;
+            }
+            PeerManagementMessage::NodeAddressLinks(links) => {
+                self.length_serializer
+                    .serialize(&(links.len() as u64), buffer)?;
+                for link in links {
+                    self.node_link_serializer.serialize(link, buffer)?;
+                }
+            }
+            PeerManagementMessage::FindPeers(key) => {
+                self.hash_serializer.serialize(key, buffer)?;
+            }
+            PeerManagementMessage::Ping(nonce) | PeerManagementMessage::Pong(nonce) => {
+                buffer.extend_from_slice(&nonce.to_be_bytes());
+            }
         }
         Ok(())
     }
@@ -103,6 +195,12 @@ pub struct PeerManagementMessageDeserializer {
     peers_length_deserializer: U64VarIntDeserializer,
     ip_addr_deserializer: IpAddrDeserializer,
     peer_id_deserializer: PeerIdDeserializer,
+    heartbeats_length_deserializer: U64VarIntDeserializer,
+    heartbeat_deserializer: SecureShareDeserializer<Heartbeat, HeartbeatDeserializer>,
+    time_deserializer: MassaTimeDeserializer,
+    node_links_length_deserializer: U64VarIntDeserializer,
+    node_link_deserializer: NodeAddressLinkProofDeserializer,
+    hash_deserializer: HashDeserializer,
 }
 
 /// Limits used in the deserialization of `OperationMessage`
@@ -111,6 +209,14 @@ pub struct PeerManagementMessageDeserializerArgs {
     pub max_listeners_per_peer: u64,
     /// Maximum number of peers per announcement
     pub max_peers_per_announcement: u64,
+    /// Maximum number of heartbeats per message
+    pub max_heartbeats_per_message: u64,
+    /// Maximum number of node address links per message
+    pub max_node_links_per_message: u64,
+    /// Number of threads
+    pub thread_count: u8,
+    /// Chain id
+    pub chain_id: u64,
 }
 
 impl PeerManagementMessageDeserializer {
@@ -127,8 +233,61 @@ impl PeerManagementMessageDeserializer {
             ),
             ip_addr_deserializer: IpAddrDeserializer::new(),
             peer_id_deserializer: PeerIdDeserializer::new(),
+            heartbeats_length_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(limits.max_heartbeats_per_message),
+            ),
+            heartbeat_deserializer: SecureShareDeserializer::new(
+                HeartbeatDeserializer::new(limits.thread_count),
+                limits.chain_id,
+            ),
+            time_deserializer: MassaTimeDeserializer::new((
+                Included(MassaTime::from_millis(0)),
+                Included(MassaTime::max()),
+            )),
+            node_links_length_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(limits.max_node_links_per_message),
+            ),
+            node_link_deserializer: NodeAddressLinkProofDeserializer::new(limits.chain_id),
+            hash_deserializer: HashDeserializer::new(),
         }
     }
+
+    /// Deserializes a list of peers along with their listeners
+    fn deserialize_peers<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Vec<(PeerId, HashMap<SocketAddr, TransportType>)>, E> {
+        length_count(
+            context(
+                "Failed length peers deserialization",
+                |buffer: &'a [u8]| self.peers_length_deserializer.deserialize(buffer),
+            ),
+            context(
+                "Failed peer deserialization",
+                tuple((
+                    context("Failed PeerId deserialization", |buffer: &'a [u8]| {
+                        self.peer_id_deserializer.deserialize(buffer)
+                    }),
+                    length_count(
+                        context("Failed length listeners deserialization", |buffer| {
+                            self.listeners_length_deserializer.deserialize(buffer)
+                        }),
+                        context("Failed listener deserialization", |buffer| {
+                            listener_deserializer(buffer, &self.ip_addr_deserializer)
+                        }),
+                    )
+                    .map::<_, HashMap<SocketAddr, TransportType>>(
+                        |listeners: Vec<(SocketAddr, TransportType)>| {
+                            listeners.into_iter().collect()
+                        },
+                    ),
+                )),
+            ),
+        )
+        .parse(buffer)
+    }
 }
 
 impl Deserializer<PeerManagementMessage> for PeerManagementMessageDeserializer {
@@ -168,40 +327,102 @@ impl Deserializer<PeerManagementMessage> for PeerManagementMessageDeserializer {
                     },
                 )
                 .parse(buffer),
-                MessageTypeId::ListPeers => context(
-                    "Failed ListPeers deserialization",
+                MessageTypeId::ListPeers => context("Failed ListPeers deserialization", |buffer| {
+                    self.deserialize_peers(buffer)
+                })
+                .map(PeerManagementMessage::ListPeers)
+                .parse(buffer),
+                MessageTypeId::AskReachabilityTest => {
+                    Ok((buffer, PeerManagementMessage::AskReachabilityTest))
+                }
+                MessageTypeId::ReachabilityTestResult => context(
+                    "Failed ReachabilityTestResult deserialization",
+                    |buffer: &'a [u8]| {
+                        let (buffer, reachable) = nom::number::complete::u8(buffer)?;
+                        match reachable {
+                            0 => Ok((buffer, false)),
+                            1 => Ok((buffer, true)),
+                            _ => Err(nom::Err::Error(ParseError::from_error_kind(
+                                buffer,
+                                nom::error::ErrorKind::Digit,
+                            ))),
+                        }
+                    },
+                )
+                .map(PeerManagementMessage::ReachabilityTestResult)
+                .parse(buffer),
+                MessageTypeId::Heartbeats => context(
+                    "Failed Heartbeats deserialization",
                     length_count(
                         context(
-                            "Failed length peers deserialization",
-                            |buffer: &'a [u8]| self.peers_length_deserializer.deserialize(buffer),
+                            "Failed length heartbeats deserialization",
+                            |buffer: &'a [u8]| {
+                                self.heartbeats_length_deserializer.deserialize(buffer)
+                            },
                         ),
+                        context("Failed heartbeat deserialization", |buffer| {
+                            self.heartbeat_deserializer.deserialize(buffer)
+                        }),
+                    ),
+                )
+                .map(PeerManagementMessage::Heartbeats)
+                .parse(buffer),
+                MessageTypeId::AskTime => context("Failed AskTime deserialization", |buffer| {
+                    self.time_deserializer.deserialize(buffer)
+                })
+                .map(PeerManagementMessage::AskTime)
+                .parse(buffer),
+                MessageTypeId::Time => context(
+                    "Failed Time deserialization",
+                    tuple((
+                        context("Failed asked_at deserialization", |buffer| {
+                            self.time_deserializer.deserialize(buffer)
+                        }),
+                        context("Failed time deserialization", |buffer| {
+                            self.time_deserializer.deserialize(buffer)
+                        }),
+                    )),
+                )
+                .map(|(asked_at, time)| PeerManagementMessage::Time { asked_at, time })
+                .parse(buffer),
+                MessageTypeId::NodeAddressLinks => context(
+                    "Failed NodeAddressLinks deserialization",
+                    length_count(
                         context(
-                            "Failed peer deserialization",
-                            tuple((
-                                context("Failed PeerId deserialization", |buffer: &'a [u8]| {
-                                    self.peer_id_deserializer.deserialize(buffer)
-                                }),
-                                length_count(
-                                    context("Failed length listeners deserialization", |buffer| {
-                                        self.listeners_length_deserializer.deserialize(buffer)
-                                    }),
-                                    context("Failed listener deserialization", |buffer| {
-                                        listener_deserializer(buffer, &self.ip_addr_deserializer)
-                                    }),
-                                )
-                                .map::<_, HashMap<SocketAddr, TransportType>>(
-                                    |listeners: Vec<(SocketAddr, TransportType)>| {
-                                        listeners.into_iter().collect()
-                                    },
-                                ),
-                            )),
+                            "Failed length node links deserialization",
+                            |buffer: &'a [u8]| {
+                                self.node_links_length_deserializer.deserialize(buffer)
+                            },
                         ),
+                        context("Failed node link deserialization", |buffer| {
+                            self.node_link_deserializer.deserialize(buffer)
+                        }),
                     ),
                 )
-                .map(|data: Vec<(PeerId, HashMap<SocketAddr, TransportType>)>| {
-                    PeerManagementMessage::ListPeers(data)
+                .map(PeerManagementMessage::NodeAddressLinks)
+                .parse(buffer),
+                MessageTypeId::FindPeers => context("Failed FindPeers deserialization", |buffer| {
+                    self.hash_deserializer.deserialize(buffer)
                 })
+                .map(PeerManagementMessage::FindPeers)
                 .parse(buffer),
+                MessageTypeId::ClosestPeers => {
+                    context("Failed ClosestPeers deserialization", |buffer| {
+                        self.deserialize_peers(buffer)
+                    })
+                    .map(PeerManagementMessage::ClosestPeers)
+                    .parse(buffer)
+                }
+                MessageTypeId::Ping => {
+                    context("Failed Ping deserialization", nom::number::complete::be_u64)
+                        .map(PeerManagementMessage::Ping)
+                        .parse(buffer)
+                }
+                MessageTypeId::Pong => {
+                    context("Failed Pong deserialization", nom::number::complete::be_u64)
+                        .map(PeerManagementMessage::Pong)
+                        .parse(buffer)
+                }
             }
         })
         .parse(buffer)
@@ -271,9 +492,17 @@ mod tests {
         PeerManagementMessage, PeerManagementMessageDeserializer,
         PeerManagementMessageDeserializerArgs, PeerManagementMessageSerializer,
     };
+    use massa_hash::Hash;
+    use massa_models::config::{CHAINID, THREAD_COUNT};
+    use massa_models::heartbeat::{Heartbeat, HeartbeatSerializer, SecureShareHeartbeat};
+    use massa_models::node::NodeId;
+    use massa_models::node_link::{NodeAddressLink, NodeAddressLinkProof};
+    use massa_models::secure_share::SecureShareContent;
+    use massa_models::slot::Slot;
     use massa_protocol_exports::PeerId;
     use massa_serialization::{DeserializeError, Deserializer, Serializer};
     use massa_signature::KeyPair;
+    use massa_time::MassaTime;
     use peernet::transports::TransportType;
 
     #[test]
@@ -296,6 +525,10 @@ mod tests {
             PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
                 max_listeners_per_peer: 1000,
                 max_peers_per_announcement: 1000,
+                max_heartbeats_per_message: 1000,
+                max_node_links_per_message: 1000,
+                thread_count: THREAD_COUNT,
+                chain_id: *CHAINID,
             });
         let (rest, message) = deserializer
             .deserialize::<DeserializeError>(&buffer)
@@ -339,6 +572,10 @@ mod tests {
             PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
                 max_listeners_per_peer: 1000,
                 max_peers_per_announcement: 1000,
+                max_heartbeats_per_message: 1000,
+                max_node_links_per_message: 1000,
+                thread_count: THREAD_COUNT,
+                chain_id: *CHAINID,
             });
         let (rest, message) = deserializer
             .deserialize::<DeserializeError>(&buffer)
@@ -361,4 +598,243 @@ mod tests {
             _ => panic!("Bad message deserialized"),
         }
     }
+
+    #[test]
+    fn test_reachability_messages() {
+        let serializer = PeerManagementMessageSerializer::new();
+        let deserializer =
+            PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
+                max_listeners_per_peer: 1000,
+                max_peers_per_announcement: 1000,
+                max_heartbeats_per_message: 1000,
+                max_node_links_per_message: 1000,
+                thread_count: THREAD_COUNT,
+                chain_id: *CHAINID,
+            });
+
+        let mut buffer = vec![];
+        serializer
+            .serialize(&PeerManagementMessage::AskReachabilityTest, &mut buffer)
+            .unwrap();
+        let (rest, message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(
+            message,
+            PeerManagementMessage::AskReachabilityTest
+        ));
+
+        for reachable in [true, false] {
+            let mut buffer = vec![];
+            serializer
+                .serialize(
+                    &PeerManagementMessage::ReachabilityTestResult(reachable),
+                    &mut buffer,
+                )
+                .unwrap();
+            let (rest, message) = deserializer
+                .deserialize::<DeserializeError>(&buffer)
+                .unwrap();
+            assert!(rest.is_empty());
+            match message {
+                PeerManagementMessage::ReachabilityTestResult(result) => {
+                    assert_eq!(result, reachable)
+                }
+                _ => panic!("Bad message deserialized"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_discovery_messages() {
+        let serializer = PeerManagementMessageSerializer::new();
+        let deserializer =
+            PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
+                max_listeners_per_peer: 1000,
+                max_peers_per_announcement: 1000,
+                max_heartbeats_per_message: 1000,
+                max_node_links_per_message: 1000,
+                thread_count: THREAD_COUNT,
+                chain_id: *CHAINID,
+            });
+
+        let key = Hash::compute_from(b"target");
+        let mut buffer = vec![];
+        serializer
+            .serialize(&PeerManagementMessage::FindPeers(key), &mut buffer)
+            .unwrap();
+        let (rest, message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match message {
+            PeerManagementMessage::FindPeers(message_key) => assert_eq!(message_key, key),
+            _ => panic!("Bad message deserialized"),
+        }
+
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let mut listeners = HashMap::new();
+        listeners.insert("127.0.0.1:33036".parse().unwrap(), TransportType::Tcp);
+        let mut buffer = vec![];
+        serializer
+            .serialize(
+                &PeerManagementMessage::ClosestPeers(vec![(peer_id, listeners.clone())]),
+                &mut buffer,
+            )
+            .unwrap();
+        let (rest, message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match message {
+            PeerManagementMessage::ClosestPeers(peers) => {
+                assert_eq!(peers, vec![(peer_id, listeners)]);
+            }
+            _ => panic!("Bad message deserialized"),
+        }
+    }
+
+    #[test]
+    fn test_heartbeats() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let heartbeat: SecureShareHeartbeat = Heartbeat {
+            timestamp: MassaTime::from_millis(42),
+            last_produced_slot: Some(Slot::new(1, 2)),
+        }
+        .new_verifiable(HeartbeatSerializer::new(), &keypair, *CHAINID)
+        .unwrap();
+
+        let serializer = PeerManagementMessageSerializer::new();
+        let mut buffer = vec![];
+        serializer
+            .serialize(
+                &PeerManagementMessage::Heartbeats(vec![heartbeat.clone()]),
+                &mut buffer,
+            )
+            .unwrap();
+        let deserializer =
+            PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
+                max_listeners_per_peer: 1000,
+                max_peers_per_announcement: 1000,
+                max_heartbeats_per_message: 1000,
+                max_node_links_per_message: 1000,
+                thread_count: THREAD_COUNT,
+                chain_id: *CHAINID,
+            });
+        let (rest, message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match message {
+            PeerManagementMessage::Heartbeats(heartbeats) => {
+                assert_eq!(heartbeats.len(), 1);
+                assert_eq!(heartbeats[0].id, heartbeat.id);
+                assert_eq!(heartbeats[0].content, heartbeat.content);
+                heartbeats[0].verify_signature().unwrap();
+            }
+            _ => panic!("Bad message deserialized"),
+        }
+    }
+
+    #[test]
+    fn test_time_messages() {
+        let serializer = PeerManagementMessageSerializer::new();
+        let deserializer =
+            PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
+                max_listeners_per_peer: 1000,
+                max_peers_per_announcement: 1000,
+                max_heartbeats_per_message: 1000,
+                max_node_links_per_message: 1000,
+                thread_count: THREAD_COUNT,
+                chain_id: *CHAINID,
+            });
+
+        let mut buffer = vec![];
+        serializer
+            .serialize(
+                &PeerManagementMessage::AskTime(MassaTime::from_millis(42)),
+                &mut buffer,
+            )
+            .unwrap();
+        let (rest, message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match message {
+            PeerManagementMessage::AskTime(asked_at) => {
+                assert_eq!(asked_at, MassaTime::from_millis(42))
+            }
+            _ => panic!("Bad message deserialized"),
+        }
+
+        let mut buffer = vec![];
+        serializer
+            .serialize(
+                &PeerManagementMessage::Time {
+                    asked_at: MassaTime::from_millis(42),
+                    time: MassaTime::from_millis(1_700_000_000_000),
+                },
+                &mut buffer,
+            )
+            .unwrap();
+        let (rest, message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match message {
+            PeerManagementMessage::Time { asked_at, time } => {
+                assert_eq!(asked_at, MassaTime::from_millis(42));
+                assert_eq!(time, MassaTime::from_millis(1_700_000_000_000));
+            }
+            _ => panic!("Bad message deserialized"),
+        }
+    }
+
+    #[test]
+    fn test_node_address_links() {
+        let staking_keypair = KeyPair::generate(0).unwrap();
+        let node_keypair = KeyPair::generate(0).unwrap();
+        let proof = NodeAddressLinkProof::new(
+            NodeAddressLink {
+                node_id: NodeId::new(node_keypair.get_public_key()),
+                timestamp: MassaTime::from_millis(42),
+            },
+            &staking_keypair,
+            &node_keypair,
+            *CHAINID,
+        )
+        .unwrap();
+
+        let serializer = PeerManagementMessageSerializer::new();
+        let mut buffer = vec![];
+        serializer
+            .serialize(
+                &PeerManagementMessage::NodeAddressLinks(vec![proof.clone()]),
+                &mut buffer,
+            )
+            .unwrap();
+        let deserializer =
+            PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
+                max_listeners_per_peer: 1000,
+                max_peers_per_announcement: 1000,
+                max_heartbeats_per_message: 1000,
+                max_node_links_per_message: 1000,
+                thread_count: THREAD_COUNT,
+                chain_id: *CHAINID,
+            });
+        let (rest, message) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match message {
+            PeerManagementMessage::NodeAddressLinks(links) => {
+                assert_eq!(links.len(), 1);
+                assert_eq!(links[0].link.id, proof.link.id);
+                assert_eq!(links[0].link.content, proof.link.content);
+                links[0].verify().unwrap();
+            }
+            _ => panic!("Bad message deserialized"),
+        }
+    }
 }
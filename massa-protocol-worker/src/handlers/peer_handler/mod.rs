@@ -1,20 +1,37 @@
 use std::net::IpAddr;
+use std::ops::Bound::Included;
+use std::sync::Arc;
+use std::time::Instant;
 use std::{collections::HashMap, net::SocketAddr, thread::JoinHandle, time::Duration};
 
-use crossbeam::channel::tick;
+use crossbeam::channel::{never, tick};
 use crossbeam::select;
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
-use massa_hash::Hash;
+use massa_hash::{Hash, HashDeserializer, HashSerializer};
 use massa_metrics::MassaMetrics;
+use massa_models::address::Address;
 use massa_models::config::SIGNATURE_DESER_SIZE;
+use massa_models::heartbeat::SecureShareHeartbeat;
+use massa_models::node_link::NodeAddressLinkProof;
+use massa_models::serialization::{StringDeserializer, StringSerializer};
+use massa_models::slot::Slot;
+use massa_models::timeslots::get_latest_block_slot_at_timestamp;
 use massa_models::version::{VersionDeserializer, VersionSerializer};
+use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{
-    BootstrapPeers, PeerId, PeerIdDeserializer, PeerIdSerializer, ProtocolConfig,
+    BanTarget, BootstrapPeers, PeerAgent, PeerFeatures, PeerId, PeerIdDeserializer,
+    PeerIdSerializer, ProtocolConfig, ProtocolEvent,
+};
+use massa_serialization::{
+    DeserializeError, Deserializer, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
+    U64VarIntDeserializer, U64VarIntSerializer,
 };
-use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::Signature;
+use massa_time::MassaTime;
+use parking_lot::Mutex;
 use peernet::context::Context as _;
 use peernet::messages::MessagesSerializer as _;
+use rand::seq::SliceRandom;
 use rand::{rngs::StdRng, RngCore, SeedableRng};
 
 use peernet::{
@@ -26,14 +43,25 @@ use peernet::{
 use tracing::{debug, error, info, warn};
 
 use crate::context::Context;
+use crate::handlers::message_timer::MessageTimer;
 use crate::handlers::peer_handler::models::PeerState;
+use crate::ip::{advertised_listeners, routable_ips, to_canonical};
 use crate::messages::{Message, MessagesHandler, MessagesSerializer};
+use crate::transport::announced_features;
 use crate::wrap_network::ActiveConnectionsTrait;
 
+use self::ban_list::SharedBanList;
+use self::clock::ClockSamples;
+use self::discovery::{lookup_targets, PendingLookups, RoutingTable};
+use self::handshake_limit::{HandshakeAdmission, HandshakeLimiter, SharedHandshakeLimiter};
+use self::latency::MAX_LOST_PINGS;
 use self::models::PeerInfo;
+use self::reachability::PendingReachabilityTest;
+use self::strikes::InvalidItemStrikes;
 use self::{
     models::{
-        InitialPeers, PeerManagementChannel, PeerManagementCmd, PeerMessageTuple, SharedPeerDB,
+        peers_supporting, InitialPeers, PeerManagementChannel, PeerManagementCmd, PeerMessageTuple,
+        SharedEnvelopePeers, SharedPeerDB,
     },
     tester::Tester,
 };
@@ -43,15 +71,25 @@ use self::{
         Announcement, AnnouncementDeserializer, AnnouncementDeserializerArgs,
         AnnouncementSerializer,
     },
-    messages::{PeerManagementMessageDeserializer, PeerManagementMessageDeserializerArgs},
+    messages::{
+        MessageTypeId, PeerManagementMessageDeserializer, PeerManagementMessageDeserializerArgs,
+    },
 };
 
 /// This file contains the definition of the peer management handler
 /// This handler is here to check that announcements we receive are valid and
 /// that all the endpoints we received are active.
 mod announcement;
-mod messages;
+pub(crate) mod ban_list;
+mod clock;
+mod discovery;
+mod handshake_limit;
+pub(crate) mod latency;
+pub(crate) mod messages;
 pub mod models;
+pub(crate) mod peer_store;
+mod reachability;
+mod strikes;
 mod tester;
 
 pub(crate) use messages::{PeerManagementMessage, PeerManagementMessageSerializer};
@@ -68,12 +106,31 @@ pub struct PeerManagementHandler {
 const THREAD_NAME: &str = "pph";
 static_assertions::const_assert!(THREAD_NAME.len() < 16);
 
+const DIAL_BACK_THREAD_NAME: &str = "pph-dial-back";
+static_assertions::const_assert!(DIAL_BACK_THREAD_NAME.len() < 16);
+
+/// Minimum delay between two dial backs requested by the same peer
+const DIAL_BACK_MIN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Minimum delay between two heartbeats of the same address for the newest one to be relayed
+const HEARTBEAT_MIN_INTERVAL: MassaTime = MassaTime::from_millis(10_000);
+
+/// Minimum delay between two node links of the same address for the newest one to be relayed
+const NODE_LINK_MIN_INTERVAL: MassaTime = MassaTime::from_millis(60_000);
+
+/// Delay after which a peer that did not answer a discovery lookup is not expected to anymore
+const DISCOVERY_LOOKUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Minimum delay between two discovery lookups answered to the same peer
+const FIND_PEERS_MIN_INTERVAL: Duration = Duration::from_secs(1);
+
 impl PeerManagementHandler {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         initial_peers: InitialPeers,
         peer_id: PeerId,
         peer_db: SharedPeerDB,
+        ban_list: SharedBanList,
         (sender_msg, receiver_msg): (
             MassaSender<PeerMessageTuple>,
             MassaReceiver<PeerMessageTuple>,
@@ -88,6 +145,8 @@ impl PeerManagementHandler {
         default_target_out_connections: usize,
         config: &ProtocolConfig,
         massa_metrics: MassaMetrics,
+        protocol_event_sender: MassaSender<ProtocolEvent>,
+        selector_controller: Box<dyn SelectorController>,
     ) -> Self {
         let message_serializer = PeerManagementMessageSerializer::new();
 
@@ -98,7 +157,7 @@ impl PeerManagementHandler {
             messages_handler,
             target_out_connections,
             default_target_out_connections,
-            massa_metrics,
+            massa_metrics.clone(),
         );
 
         let thread_join = std::thread::Builder::new()
@@ -106,19 +165,169 @@ impl PeerManagementHandler {
         .spawn({
             let peer_db = peer_db.clone();
             let ticker = tick(Duration::from_secs(10));
+            // keepalive pings measuring the latency to the connected peers, disabled by a zero interval
+            let ping_ticker = if config.ping_interval.as_millis() == 0 {
+                never()
+            } else {
+                tick(config.ping_interval.to_duration())
+            };
             let config = config.clone();
+            let slow_message_threshold = config.slow_message_threshold.to_duration();
             let message_serializer = MessagesSerializer::new()
                 .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
             let message_deserializer =
                 PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
                     max_peers_per_announcement: config.max_size_peers_announcement,
                     max_listeners_per_peer: config.max_size_listeners_per_peer,
+                    max_heartbeats_per_message: config.max_heartbeats_per_message,
+                    max_node_links_per_message: config.max_node_links_per_message,
+                    thread_count: config.thread_count,
+                    chain_id: config.chain_id,
                 });
+            let local_peer_id = peer_id;
+            let mut routing_table = RoutingTable::new(&peer_id, config.peer_discovery_bucket_size);
+            for bootstrap_peer_id in &config.peer_discovery_bootstrap_nodes {
+                match initial_peers.get(bootstrap_peer_id) {
+                    Some(listeners) => {
+                        routing_table.insert(*bootstrap_peer_id, listeners.clone());
+                    }
+                    None => warn!(
+                        "discovery bootstrap node {} is not an initial peer, ignoring it",
+                        bootstrap_peer_id
+                    ),
+                }
+            }
 
             move || {
+                // peer we asked to dial back our routable address
+                let mut reachability_test =
+                    PendingReachabilityTest::new(config.tester_timeout.to_duration());
+                // last time we dialed back each peer that asked for it
+                let mut last_dial_backs: HashMap<PeerId, Instant> = HashMap::new();
+                // latest heartbeat received for each staking address
+                let mut known_heartbeats: HashMap<Address, SecureShareHeartbeat> = HashMap::new();
+                // latest node link received for each staking address
+                let mut known_node_links: HashMap<Address, NodeAddressLinkProof> = HashMap::new();
+                // latest clock offsets of the connected peers
+                let mut clock_samples = ClockSamples::default();
+                // invalid items recently received from each peer
+                let mut invalid_item_strikes = InvalidItemStrikes::new(
+                    config.invalid_items_ban_threshold,
+                    config.invalid_items_window.to_duration(),
+                );
+                // discovery lookups we sent and that were not answered yet
+                let mut pending_lookups = PendingLookups::default();
+                // last time we answered a discovery lookup of each peer
+                let mut last_find_peers: HashMap<PeerId, Instant> = HashMap::new();
+                // whether we looked up our own key to find our closest peers
+                let mut self_lookup_done = false;
                 loop {
                     select! {
                         recv(ticker) -> _ => {
+                            if let Err(err) = ban_list.write().prune(MassaTime::now()) {
+                                warn!("error saving the ban list: {}", err);
+                            }
+                            if let Err(err) = peer_db.write().get_peer_store_mut().save_periodically() {
+                                warn!("error saving the peer store: {}", err);
+                            }
+
+                            // ask a connected peer to dial back our routable address until we get an answer,
+                            // giving each tester the time to answer
+                            if !routable_ips(&config).is_empty()
+                                && peer_db.read().get_reachability().is_none()
+                                && reachability_test.can_ask(Instant::now())
+                            {
+                                let connected_peers = peers_supporting(&peer_db, active_connections.get_peer_ids_connected(), PeerFeatures::REACHABILITY_TESTS);
+                                if let Some(tester_peer_id) = connected_peers.choose(&mut rand::thread_rng()) {
+                                    match active_connections.send_to_peer(
+                                        tester_peer_id,
+                                        &message_serializer,
+                                        PeerManagementMessage::AskReachabilityTest.into(),
+                                        false,
+                                    ) {
+                                        Ok(()) => reachability_test.asked(*tester_peer_id, Instant::now()),
+                                        Err(e) => warn!("error sending AskReachabilityTest message to peer: {:?}", e),
+                                    }
+                                }
+                            }
+
+                            // estimate the network time from the latest clock samples, then sample the peers again
+                            let connected_peers = active_connections.get_peer_ids_connected();
+                            clock_samples.retain_connected(&connected_peers);
+                            invalid_item_strikes.prune(&connected_peers, Instant::now());
+                            let network_time_offset = clock_samples.median_offset();
+                            peer_db.write().set_network_time_offset(network_time_offset);
+                            if let Some(offset) = network_time_offset {
+                                let tolerance = config.max_header_future_time.as_millis();
+                                if tolerance > 0 && offset.unsigned_abs() > tolerance {
+                                    warn!(
+                                        "The local clock is {} ms {} the network median time, beyond the {} ms block timing tolerance: \
+                                        the blocks produced by this node will be dropped or arrive too late. Synchronize your clock (e.g. with NTP).",
+                                        offset.unsigned_abs(),
+                                        if offset > 0 { "behind" } else { "ahead of" },
+                                        tolerance
+                                    );
+                                }
+                            }
+                            let clock_peers: Vec<PeerId> = {
+                                let peer_db_read = peer_db.read();
+                                connected_peers
+                                    .into_iter()
+                                    .filter(|peer_id| {
+                                        peer_db_read
+                                            .get_peers()
+                                            .get(peer_id)
+                                            .and_then(|info| info.agent.as_ref())
+                                            .map(|agent| agent.features.contains(PeerFeatures::CLOCK_SAMPLING))
+                                            .unwrap_or(false)
+                                    })
+                                    .collect()
+                            };
+                            for clock_peer_id in &clock_peers {
+                                if let Err(e) = active_connections.send_to_peer(
+                                    clock_peer_id,
+                                    &message_serializer,
+                                    PeerManagementMessage::AskTime(MassaTime::now()).into(),
+                                    false,
+                                ) {
+                                    debug!("error sending AskTime message to peer: {:?}", e);
+                                }
+                            }
+
+                            // keep the discovery routing table in sync with the peer database, then look up the next key
+                            sync_routing_table(&mut routing_table, &peer_db);
+                            debug!("discovery routing table: {} peers", routing_table.len());
+                            if config.peer_discovery_enabled {
+                                let now = Instant::now();
+                                let connected_peers = active_connections.get_peer_ids_connected();
+                                pending_lookups.prune(&connected_peers, now, DISCOVERY_LOOKUP_TIMEOUT);
+                                let discovery_peers = peers_supporting(&peer_db, connected_peers, PeerFeatures::PEER_DISCOVERY);
+                                // first look up our own key to find our closest peers, asking the bootstrap nodes first
+                                let lookup = if discovery_peers.is_empty() {
+                                    None
+                                } else if !self_lookup_done {
+                                    self_lookup_done = true;
+                                    Some((routing_table.local_key(), config.peer_discovery_bootstrap_nodes.as_slice()))
+                                } else {
+                                    routing_table
+                                        .next_lookup(now, config.peer_discovery_refresh_interval.to_duration())
+                                        .map(|key| (key, &[] as &[PeerId]))
+                                };
+                                if let Some((key, preferred)) = lookup {
+                                    for target_peer_id in lookup_targets(&key, discovery_peers, preferred) {
+                                        match active_connections.send_to_peer(
+                                            &target_peer_id,
+                                            &message_serializer,
+                                            PeerManagementMessage::FindPeers(key).into(),
+                                            false,
+                                        ) {
+                                            Ok(()) => pending_lookups.insert(target_peer_id, key, now),
+                                            Err(e) => debug!("error sending FindPeers message to peer: {:?}", e),
+                                        }
+                                    }
+                                }
+                            }
+
                             let peers_to_send = peer_db.read().get_rand_peers_to_send(100);
                             if peers_to_send.is_empty() {
                                 continue;
@@ -133,6 +342,35 @@ impl PeerManagementHandler {
                                }
                             }
                         }
+                        recv(ping_ticker) -> _ => {
+                            let connected_peers = active_connections.get_peer_ids_connected();
+                            let ping_peers = peers_supporting(&peer_db, connected_peers.iter().copied(), PeerFeatures::PING);
+                            let now = Instant::now();
+                            let pings: Vec<(PeerId, Option<u64>)> = {
+                                let mut peer_db_write = peer_db.write();
+                                let latencies = peer_db_write.get_latencies_mut();
+                                latencies.retain_connected(&connected_peers);
+                                ping_peers
+                                    .into_iter()
+                                    .map(|ping_peer_id| (ping_peer_id, latencies.start_ping(ping_peer_id, now)))
+                                    .collect()
+                            };
+                            for (ping_peer_id, nonce) in pings {
+                                let Some(nonce) = nonce else {
+                                    warn!("closing the connection to peer {} that left the last {} pings unanswered", ping_peer_id, MAX_LOST_PINGS);
+                                    active_connections.shutdown_connection(&ping_peer_id);
+                                    continue;
+                                };
+                                if let Err(e) = active_connections.send_to_peer(
+                                    &ping_peer_id,
+                                    &message_serializer,
+                                    PeerManagementMessage::Ping(nonce).into(),
+                                    false,
+                                ) {
+                                    debug!("error sending Ping message to peer: {:?}", e);
+                                }
+                            }
+                        }
                         recv(receiver_cmd) -> cmd => {
                             receiver_cmd.update_metrics();
                             // internal command
@@ -145,6 +383,24 @@ impl PeerManagementHandler {
                                     // update peer_db
                                     peer_db.write().ban_peer(&peer_id);
                                 }
+                            },
+                             Ok(PeerManagementCmd::NoteInvalidItem { peer_id: invalid_peer_id, item_type }) => {
+                                let Some(invalid_items) = invalid_item_strikes.add_strike(invalid_peer_id, Instant::now()) else {
+                                    continue;
+                                };
+                                warn!(
+                                    "banning peer {} after {} invalid items (last one: {}) within {} ms",
+                                    invalid_peer_id, invalid_items, item_type, config.invalid_items_window.as_millis()
+                                );
+                                active_connections.shutdown_connection(&invalid_peer_id);
+                                peer_db.write().ban_peer(&invalid_peer_id);
+                                if let Err(err) = protocol_event_sender.try_send(ProtocolEvent::PeerBannedForInvalidItems {
+                                    peer_id: invalid_peer_id,
+                                    invalid_items,
+                                    item_type: item_type.to_string(),
+                                }) {
+                                    debug!("error sending protocol event: {:?}", err);
+                                }
                             },
                              Ok(PeerManagementCmd::Unban(peer_ids)) => {
                                 for peer_id in peer_ids {
@@ -153,10 +409,11 @@ impl PeerManagementHandler {
                             },
                              Ok(PeerManagementCmd::GetBootstrapPeers { responder }) => {
                                 let mut peers = peer_db.read().get_rand_peers_to_send(100);
-                                // Add myself
-                                if let Some(routable_ip) = config.routable_ip {
-                                    let listeners = config.listeners.iter().map(|(addr, ty)| {
-                                        (SocketAddr::new(routable_ip, addr.port()), *ty)
+                                // Add myself, at each of our routable ips
+                                let routable_ips = routable_ips(&config);
+                                if !routable_ips.is_empty() {
+                                    let listeners = advertised_listeners(&config.listeners, &config).into_iter().flat_map(|(addr, ty)| {
+                                        routable_ips.iter().map(move |ip| (SocketAddr::new(*ip, addr.port()), ty))
                                     }).collect();
                                     peers.push((peer_id, listeners));
                                 }
@@ -164,7 +421,132 @@ impl PeerManagementHandler {
                                     warn!("error sending bootstrap peers: {:?}", err);
                                 }
                              },
+                             Ok(PeerManagementCmd::PropagateHeartbeats(heartbeats)) => {
+                                let new_heartbeats = integrate_heartbeats(&mut known_heartbeats, heartbeats, selector_controller.as_ref(), &config);
+                                if !new_heartbeats.is_empty() {
+                                    let msg = PeerManagementMessage::Heartbeats(new_heartbeats);
+                                    for peer_id in &peers_supporting(&peer_db, active_connections.get_peer_ids_connected(), PeerFeatures::HEARTBEATS) {
+                                        if let Err(e) = active_connections
+                                            .send_to_peer(peer_id, &message_serializer, msg.clone().into(), false) {
+                                            debug!("error sending Heartbeats message to peer: {:?}", e);
+                                        }
+                                    }
+                                }
+                             },
+                             Ok(PeerManagementCmd::GetHeartbeats { responder }) => {
+                                prune_heartbeats(&mut known_heartbeats, &config);
+                                if let Err(err) = responder.try_send(known_heartbeats.values().cloned().collect()) {
+                                    warn!("error sending staker heartbeats: {:?}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::PropagateNodeAddressLinks(links)) => {
+                                let new_links = integrate_node_links(&mut known_node_links, links, &config);
+                                if !new_links.is_empty() {
+                                    let msg = PeerManagementMessage::NodeAddressLinks(new_links);
+                                    for peer_id in &peers_supporting(&peer_db, active_connections.get_peer_ids_connected(), PeerFeatures::NODE_ADDRESS_LINKS) {
+                                        if let Err(e) = active_connections
+                                            .send_to_peer(peer_id, &message_serializer, msg.clone().into(), false) {
+                                            debug!("error sending NodeAddressLinks message to peer: {:?}", e);
+                                        }
+                                    }
+                                }
+                             },
+                             Ok(PeerManagementCmd::GetNodeAddressLinks { responder }) => {
+                                prune_node_links(&mut known_node_links, &config);
+                                if let Err(err) = responder.try_send(known_node_links.values().cloned().collect()) {
+                                    warn!("error sending node address links: {:?}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::AddBans(bans)) => {
+                                for ban in &bans {
+                                    info!("Banned {} until {:?}", ban.target, ban.expires_at);
+                                }
+                                {
+                                    let now = MassaTime::now();
+                                    let mut peer_db_write = peer_db.write();
+                                    for ban in &bans {
+                                        if let BanTarget::Peer(banned_peer_id) = ban.target {
+                                            peer_db_write.get_peer_store_mut().note_ban(&banned_peer_id, now);
+                                        }
+                                    }
+                                }
+                                if let Err(err) = ban_list.write().insert(bans) {
+                                    warn!("error saving the ban list: {}", err);
+                                }
+                                shutdown_banned_connections(&ban_list, active_connections.as_mut());
+                             },
+                             Ok(PeerManagementCmd::RemoveBans(targets)) => {
+                                for target in &targets {
+                                    info!("Unbanned {}", target);
+                                    // also lift the ban decided by the node itself
+                                    if let BanTarget::Peer(peer_id) = target {
+                                        peer_db.write().unban_peer(peer_id);
+                                    }
+                                }
+                                if let Err(err) = ban_list.write().remove(&targets) {
+                                    warn!("error saving the ban list: {}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::GetBans { responder }) => {
+                                if let Err(err) = responder.try_send(ban_list.read().entries()) {
+                                    warn!("error sending bans: {:?}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::AddToWhitelist(ips)) => {
+                                for ip in &ips {
+                                    info!("Whitelisted {}", ip);
+                                }
+                                if let Err(err) = ban_list.write().add_to_whitelist(ips) {
+                                    warn!("error saving the peers whitelist: {}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::RemoveFromWhitelist(ips)) => {
+                                for ip in &ips {
+                                    info!("Removed {} from the whitelist", ip);
+                                }
+                                if let Err(err) = ban_list.write().remove_from_whitelist(&ips) {
+                                    warn!("error saving the peers whitelist: {}", err);
+                                }
+                                shutdown_banned_connections(&ban_list, active_connections.as_mut());
+                             },
+                             Ok(PeerManagementCmd::GetWhitelist { responder }) => {
+                                if let Err(err) = responder.try_send(ban_list.read().whitelist()) {
+                                    warn!("error sending the peers whitelist: {:?}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::ReloadIpLists) => {
+                                if let Err(err) = ban_list.write().reload() {
+                                    warn!("error reloading the ban list and the peers whitelist: {}", err);
+                                } else {
+                                    info!("Reloaded the ban list and the peers whitelist");
+                                    shutdown_banned_connections(&ban_list, active_connections.as_mut());
+                                }
+                             },
+                             Ok(PeerManagementCmd::GetPeerRecords { peer_ids, responder }) => {
+                                let records = peer_db.read().get_peer_store().entries(&peer_ids);
+                                if let Err(err) = responder.try_send(records) {
+                                    warn!("error sending peer records: {:?}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::EditPeerRecords(edits)) => {
+                                peer_db.write().get_peer_store_mut().edit(&edits, MassaTime::now());
+                                // test the listeners set by the node operator right away
+                                for edit in edits {
+                                    if let Some(listeners) = edit.listeners.filter(|listeners| !listeners.is_empty()) {
+                                        let listeners = listeners.into_iter().map(|addr| (addr, TransportType::Tcp)).collect();
+                                        if let Err(e) = test_sender.try_send((edit.peer_id, listeners)) {
+                                            debug!("error when sending msg to peer tester : {}", e);
+                                        }
+                                    }
+                                }
+                             },
+                             Ok(PeerManagementCmd::RemovePeerRecords(peer_ids)) => {
+                                peer_db.write().get_peer_store_mut().remove(&peer_ids);
+                             },
                              Ok(PeerManagementCmd::Stop) => {
+                                if let Err(err) = peer_db.write().get_peer_store_mut().save() {
+                                    warn!("error saving the peer store: {}", err);
+                                }
                                 while let Ok(_msg) = test_receiver.try_recv() {
                                     // nothing to do just clean the channel
                                 }
@@ -183,6 +565,7 @@ impl PeerManagementHandler {
                                     return;
                                 }
                             };
+                            let mut timer = MessageTimer::start(&massa_metrics, slow_message_threshold, peer_id, "PeerManagement");
                             // check if peer is banned
                             if let Some(peer) = peer_db.read().get_peers().get(&peer_id) {
                                 if peer.state == PeerState::Banned {
@@ -202,6 +585,7 @@ impl PeerManagementHandler {
                                 warn!("message not fully deserialized");
                                 continue;
                             }
+                            timer.set_message_type(format!("PeerManagement::{:?}", MessageTypeId::from(&message)));
                             match message {
                                 PeerManagementMessage::NewPeerConnected((peer_id, listeners)) => {
                                     debug!("Received peer message: NewPeerConnected from {}", peer_id);
@@ -217,6 +601,167 @@ impl PeerManagementHandler {
                                         }
                                     }
                                 }
+                                PeerManagementMessage::AskReachabilityTest => {
+                                    debug!("Received peer message: AskReachabilityTest from {}", peer_id);
+                                    last_dial_backs.retain(|_, asked_at| asked_at.elapsed() < DIAL_BACK_MIN_INTERVAL);
+                                    if last_dial_backs.contains_key(&peer_id) {
+                                        continue;
+                                    }
+                                    last_dial_backs.insert(peer_id, Instant::now());
+                                    // only dial back the ip the peer is connected from, so that we cannot be used to reach third parties
+                                    let Some((connection_addr, _, _)) = active_connections.get_peers_connected().get(&peer_id).cloned() else {
+                                        continue;
+                                    };
+                                    let connection_ip = to_canonical(connection_addr.ip());
+                                    let target = peer_db
+                                        .read()
+                                        .get_peers()
+                                        .get(&peer_id)
+                                        .and_then(|info| info.last_announce.as_ref())
+                                        .and_then(|announcement| {
+                                            announcement
+                                                .listeners
+                                                .iter()
+                                                .find(|(addr, ty)| {
+                                                    matches!(ty, TransportType::Tcp)
+                                                        && to_canonical(addr.ip()) == connection_ip
+                                                })
+                                                .map(|(addr, _)| *addr)
+                                        });
+                                    let active_connections = active_connections.clone();
+                                    let message_serializer = message_serializer.clone();
+                                    let config = config.clone();
+                                    if let Err(e) = std::thread::Builder::new()
+                                        .name(DIAL_BACK_THREAD_NAME.to_string())
+                                        .spawn(move || {
+                                            let reachable = target
+                                                .map(|addr| Tester::dial_back(addr, &peer_id, &config))
+                                                .unwrap_or(false);
+                                            if let Err(e) = active_connections.send_to_peer(
+                                                &peer_id,
+                                                &message_serializer,
+                                                PeerManagementMessage::ReachabilityTestResult(reachable).into(),
+                                                false,
+                                            ) {
+                                                warn!("error sending ReachabilityTestResult message to peer: {:?}", e);
+                                            }
+                                        })
+                                    {
+                                        warn!("OS failed to start dial back thread: {:?}", e);
+                                    }
+                                }
+                                PeerManagementMessage::ReachabilityTestResult(reachable) => {
+                                    debug!("Received peer message: ReachabilityTestResult from {}", peer_id);
+                                    if !reachability_test.answered(&peer_id) {
+                                        debug!("Ignoring unsolicited reachability test result from {}", peer_id);
+                                        continue;
+                                    }
+                                    if reachable {
+                                        info!("Reachability self-test succeeded: peer {} could dial back our routable address", peer_id);
+                                    } else {
+                                        warn!(
+                                            "Reachability self-test failed: peer {} could not dial back our routable ips {:?}. Check your NAT, port forwarding and firewall configuration.",
+                                            peer_id, routable_ips(&config)
+                                        );
+                                    }
+                                    peer_db.write().set_reachability(reachable);
+                                }
+                                PeerManagementMessage::AskTime(asked_at) => {
+                                    debug!("Received peer message: AskTime from {}", peer_id);
+                                    let msg = PeerManagementMessage::Time { asked_at, time: MassaTime::now() };
+                                    if let Err(e) = active_connections.send_to_peer(&peer_id, &message_serializer, msg.into(), false) {
+                                        debug!("error sending Time message to peer: {:?}", e);
+                                    }
+                                }
+                                PeerManagementMessage::Time { asked_at, time } => {
+                                    debug!("Received peer message: Time from {}", peer_id);
+                                    if !clock_samples.add_sample(peer_id, asked_at, time, MassaTime::now()) {
+                                        debug!("Ignoring imprecise or inconsistent clock sample from {}", peer_id);
+                                    }
+                                }
+                                PeerManagementMessage::Ping(nonce) => {
+                                    debug!("Received peer message: Ping from {}", peer_id);
+                                    if let Err(e) = active_connections.send_to_peer(&peer_id, &message_serializer, PeerManagementMessage::Pong(nonce).into(), false) {
+                                        debug!("error sending Pong message to peer: {:?}", e);
+                                    }
+                                }
+                                PeerManagementMessage::Pong(nonce) => {
+                                    debug!("Received peer message: Pong from {}", peer_id);
+                                    if !peer_db.write().get_latencies_mut().note_pong(peer_id, nonce, Instant::now()) {
+                                        debug!("Ignoring unsolicited pong from {}", peer_id);
+                                    }
+                                }
+                                PeerManagementMessage::Heartbeats(heartbeats) => {
+                                    debug!("Received peer message: Heartbeats from {}", peer_id);
+                                    let new_heartbeats = integrate_heartbeats(&mut known_heartbeats, heartbeats, selector_controller.as_ref(), &config);
+                                    if new_heartbeats.is_empty() {
+                                        continue;
+                                    }
+                                    // relay the heartbeats we did not know to the other peers
+                                    let msg = PeerManagementMessage::Heartbeats(new_heartbeats);
+                                    for other_peer_id in &peers_supporting(&peer_db, active_connections.get_peer_ids_connected(), PeerFeatures::HEARTBEATS) {
+                                        if other_peer_id == &peer_id {
+                                            continue;
+                                        }
+                                        if let Err(e) = active_connections
+                                            .send_to_peer(other_peer_id, &message_serializer, msg.clone().into(), false) {
+                                            debug!("error sending Heartbeats message to peer: {:?}", e);
+                                        }
+                                    }
+                                }
+                                PeerManagementMessage::NodeAddressLinks(links) => {
+                                    debug!("Received peer message: NodeAddressLinks from {}", peer_id);
+                                    let new_links = integrate_node_links(&mut known_node_links, links, &config);
+                                    if new_links.is_empty() {
+                                        continue;
+                                    }
+                                    // relay the links we did not know to the other peers
+                                    let msg = PeerManagementMessage::NodeAddressLinks(new_links);
+                                    for other_peer_id in &peers_supporting(&peer_db, active_connections.get_peer_ids_connected(), PeerFeatures::NODE_ADDRESS_LINKS) {
+                                        if other_peer_id == &peer_id {
+                                            continue;
+                                        }
+                                        if let Err(e) = active_connections
+                                            .send_to_peer(other_peer_id, &message_serializer, msg.clone().into(), false) {
+                                            debug!("error sending NodeAddressLinks message to peer: {:?}", e);
+                                        }
+                                    }
+                                }
+                                PeerManagementMessage::FindPeers(key) => {
+                                    debug!("Received peer message: FindPeers from {}", peer_id);
+                                    last_find_peers.retain(|_, asked_at| asked_at.elapsed() < FIND_PEERS_MIN_INTERVAL);
+                                    if last_find_peers.contains_key(&peer_id) {
+                                        continue;
+                                    }
+                                    last_find_peers.insert(peer_id, Instant::now());
+                                    let closest_peers = routing_table
+                                        .closest(&key, config.peer_discovery_bucket_size.saturating_add(1))
+                                        .into_iter()
+                                        .filter(|(closest_peer_id, _)| closest_peer_id != &peer_id)
+                                        .take(config.peer_discovery_bucket_size)
+                                        .collect();
+                                    let msg = PeerManagementMessage::ClosestPeers(closest_peers);
+                                    if let Err(e) = active_connections.send_to_peer(&peer_id, &message_serializer, msg.into(), false) {
+                                        debug!("error sending ClosestPeers message to peer: {:?}", e);
+                                    }
+                                }
+                                PeerManagementMessage::ClosestPeers(peers) => {
+                                    debug!("Received peer message: ClosestPeers from {}", peer_id);
+                                    let Some(key) = pending_lookups.take(&peer_id) else {
+                                        debug!("Ignoring unsolicited closest peers from {}", peer_id);
+                                        continue;
+                                    };
+                                    debug!("peer {} answered {} peers close to {}", peer_id, peers.len(), key);
+                                    // the peers found are tested before entering the peer database, like the listed ones
+                                    for (found_peer_id, listeners) in peers.into_iter().take(config.peer_discovery_bucket_size) {
+                                        if found_peer_id == local_peer_id {
+                                            continue;
+                                        }
+                                        if let Err(e) = test_sender.try_send((found_peer_id, listeners)) {
+                                            debug!("error when sending msg to peer tester : {}", e);
+                                        }
+                                    }
+                                }
                             }
                         }
                     }
@@ -235,6 +780,33 @@ impl PeerManagementHandler {
             sender_msg.try_send((*peer_id, message)).unwrap();
         }
 
+        // dial again the peers remembered from the previous runs
+        let recorded_peers = peer_db.read().get_peer_store().dial_candidates();
+        for (peer_id, listeners) in recorded_peers {
+            if initial_peers.contains_key(&peer_id) {
+                continue;
+            }
+            let mut message = Vec::new();
+            message_serializer
+                .serialize(
+                    &PeerManagementMessage::NewPeerConnected((
+                        peer_id,
+                        listeners
+                            .into_iter()
+                            .map(|addr| (addr, TransportType::Tcp))
+                            .collect(),
+                    )),
+                    &mut message,
+                )
+                .unwrap();
+            if let Err(err) = sender_msg.try_send((peer_id, message)) {
+                debug!(
+                    "error when sending recorded peer {} to the peer handler: {}",
+                    peer_id, err
+                );
+            }
+        }
+
         Self {
             peer_db,
             thread_join: Some(thread_join),
@@ -261,23 +833,224 @@ impl PeerManagementHandler {
     }
 }
 
+/// Close the connections of the peers banned by the node operator, or connected from a banned subnet
+fn shutdown_banned_connections(
+    ban_list: &SharedBanList,
+    active_connections: &mut dyn ActiveConnectionsTrait,
+) {
+    let now = MassaTime::now();
+    let ban_list_read = ban_list.read();
+    for (peer_id, (addr, _, _)) in active_connections.get_peers_connected() {
+        if ban_list_read.is_peer_banned(&peer_id, now)
+            || ban_list_read.is_ip_banned(&addr.ip(), now)
+        {
+            active_connections.shutdown_connection(&peer_id);
+        }
+    }
+}
+
+/// Add the trusted peers to the discovery routing table, and remove the ones that failed or were banned
+fn sync_routing_table(routing_table: &mut RoutingTable, peer_db: &SharedPeerDB) {
+    let peer_db_read = peer_db.read();
+    let peers = peer_db_read.get_peers();
+    routing_table.retain(|peer_id| {
+        !matches!(
+            peers.get(peer_id).map(|info| &info.state),
+            Some(PeerState::Banned | PeerState::HandshakeFailed)
+        )
+    });
+    for (peer_id, info) in peers {
+        if info.state != PeerState::Trusted {
+            continue;
+        }
+        if let Some(announcement) = &info.last_announce {
+            if !announcement.listeners.is_empty() {
+                routing_table.insert(*peer_id, announcement.listeners.clone());
+            }
+        }
+    }
+}
+
+/// Remove the heartbeats that are no longer fresh
+fn prune_heartbeats(
+    known_heartbeats: &mut HashMap<Address, SecureShareHeartbeat>,
+    config: &ProtocolConfig,
+) {
+    let now = MassaTime::now();
+    known_heartbeats.retain(|_, heartbeat| {
+        now.saturating_sub(heartbeat.content.timestamp) <= config.heartbeat_validity
+    });
+}
+
+/// Check and store received heartbeats, returning the ones that are new to us and should be relayed.
+/// Only the heartbeats of addresses that can be drawn in the current cycle are kept,
+/// so that throwaway keypairs cannot fill `max_known_heartbeats`.
+fn integrate_heartbeats(
+    known_heartbeats: &mut HashMap<Address, SecureShareHeartbeat>,
+    heartbeats: Vec<SecureShareHeartbeat>,
+    selector_controller: &dyn SelectorController,
+    config: &ProtocolConfig,
+) -> Vec<SecureShareHeartbeat> {
+    prune_heartbeats(known_heartbeats, config);
+    let now = MassaTime::now();
+    let current_slot = get_latest_block_slot_at_timestamp(
+        config.thread_count,
+        config.t0,
+        config.genesis_timestamp,
+        now,
+    )
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| Slot::new(0, 0));
+    let mut new_heartbeats = Vec::new();
+    for heartbeat in heartbeats {
+        let timestamp = heartbeat.content.timestamp;
+        // reject stale heartbeats and heartbeats from the future
+        if now.saturating_sub(timestamp) > config.heartbeat_validity
+            || timestamp.saturating_sub(now) > config.heartbeat_validity
+        {
+            continue;
+        }
+        match known_heartbeats.get(&heartbeat.content_creator_address) {
+            Some(known)
+                if timestamp
+                    < known
+                        .content
+                        .timestamp
+                        .saturating_add(HEARTBEAT_MIN_INTERVAL) =>
+            {
+                continue
+            }
+            None if known_heartbeats.len() >= config.max_known_heartbeats => continue,
+            _ => {}
+        }
+        match selector_controller.has_rolls_at(current_slot, &heartbeat.content_creator_address) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                debug!(
+                    "could not check the rolls of heartbeat creator {}: {}",
+                    heartbeat.content_creator_address, err
+                );
+                continue;
+            }
+        }
+        if let Err(err) = heartbeat.verify_signature() {
+            debug!("invalid heartbeat signature: {}", err);
+            continue;
+        }
+        known_heartbeats.insert(heartbeat.content_creator_address, heartbeat.clone());
+        new_heartbeats.push(heartbeat);
+    }
+    new_heartbeats
+}
+
+/// Remove the node links that are no longer fresh
+fn prune_node_links(
+    known_node_links: &mut HashMap<Address, NodeAddressLinkProof>,
+    config: &ProtocolConfig,
+) {
+    let now = MassaTime::now();
+    known_node_links.retain(|_, proof| {
+        now.saturating_sub(proof.link.content.timestamp) <= config.node_link_validity
+    });
+}
+
+/// Check and store received node links, returning the ones that are new to us and should be relayed
+fn integrate_node_links(
+    known_node_links: &mut HashMap<Address, NodeAddressLinkProof>,
+    links: Vec<NodeAddressLinkProof>,
+    config: &ProtocolConfig,
+) -> Vec<NodeAddressLinkProof> {
+    prune_node_links(known_node_links, config);
+    let now = MassaTime::now();
+    let mut new_links = Vec::new();
+    for proof in links {
+        let timestamp = proof.link.content.timestamp;
+        // reject stale links and links from the future
+        if now.saturating_sub(timestamp) > config.node_link_validity
+            || timestamp.saturating_sub(now) > config.node_link_validity
+        {
+            continue;
+        }
+        match known_node_links.get(&proof.link.content_creator_address) {
+            // a newer link to another node replaces the known one right away
+            Some(known)
+                if known.link.content.node_id == proof.link.content.node_id
+                    && timestamp
+                        < known
+                            .link
+                            .content
+                            .timestamp
+                            .saturating_add(NODE_LINK_MIN_INTERVAL) =>
+            {
+                continue
+            }
+            Some(known) if timestamp <= known.link.content.timestamp => continue,
+            None if known_node_links.len() >= config.max_known_node_links => continue,
+            _ => {}
+        }
+        if let Err(err) = proof.verify() {
+            debug!("invalid node link signatures: {}", err);
+            continue;
+        }
+        known_node_links.insert(proof.link.content_creator_address, proof.clone());
+        new_links.push(proof);
+    }
+    new_links
+}
+
+/// Identifier of the network of the node, exchanged during the handshake to refuse the peers of other networks.
+/// It derives from the chain id and the genesis timestamp, which also tell apart the networks reset with the same chain id,
+/// while the node versions are checked for compatibility on their own.
+pub(crate) fn chain_identifier(config: &ProtocolConfig) -> Hash {
+    let mut bytes = config.chain_id.to_be_bytes().to_vec();
+    bytes.extend(config.genesis_timestamp.as_millis().to_be_bytes());
+    Hash::compute_from(&bytes)
+}
+
 #[derive(Clone)]
 pub struct MassaHandshake {
     pub announcement_serializer: AnnouncementSerializer,
     pub announcement_deserializer: AnnouncementDeserializer,
     pub version_serializer: VersionSerializer,
     pub version_deserializer: VersionDeserializer,
+    user_agent_serializer: StringSerializer<U32VarIntSerializer, u32>,
+    user_agent_deserializer: StringDeserializer<U32VarIntDeserializer, u32>,
+    features_serializer: U64VarIntSerializer,
+    features_deserializer: U64VarIntDeserializer,
+    features_version_serializer: U32VarIntSerializer,
+    features_version_deserializer: U32VarIntDeserializer,
+    /// identifier of the network of the node
+    chain_identifier: Hash,
+    chain_identifier_serializer: HashSerializer,
+    chain_identifier_deserializer: HashDeserializer,
     pub config: ProtocolConfig,
     pub peer_db: SharedPeerDB,
+    ban_list: SharedBanList,
+    envelope_peers: SharedEnvelopePeers,
+    /// handshakes attempted recently with each address, shared by the handshake workers
+    handshake_limiter: SharedHandshakeLimiter,
     peer_mngt_msg_serializer: MessagesSerializer,
     peer_id_serializer: PeerIdSerializer,
     peer_id_deserializer: PeerIdDeserializer,
 }
 
 impl MassaHandshake {
-    pub fn new(peer_db: SharedPeerDB, config: ProtocolConfig) -> Self {
+    pub fn new(
+        peer_db: SharedPeerDB,
+        ban_list: SharedBanList,
+        envelope_peers: SharedEnvelopePeers,
+        config: ProtocolConfig,
+    ) -> Self {
         Self {
             peer_db,
+            ban_list,
+            envelope_peers,
+            handshake_limiter: Arc::new(Mutex::new(HandshakeLimiter::new(
+                config.max_handshakes_per_ip_per_minute,
+                config.handshake_cooldown.to_duration(),
+            ))),
             announcement_serializer: AnnouncementSerializer::new(),
             announcement_deserializer: AnnouncementDeserializer::new(
                 AnnouncementDeserializerArgs {
@@ -286,6 +1059,21 @@ impl MassaHandshake {
             ),
             version_serializer: VersionSerializer::new(),
             version_deserializer: VersionDeserializer::new(),
+            user_agent_serializer: StringSerializer::new(U32VarIntSerializer::new()),
+            user_agent_deserializer: StringDeserializer::new(U32VarIntDeserializer::new(
+                Included(0),
+                Included(config.max_user_agent_length),
+            )),
+            features_serializer: U64VarIntSerializer::new(),
+            features_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            features_version_serializer: U32VarIntSerializer::new(),
+            features_version_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(u32::MAX),
+            ),
+            chain_identifier: chain_identifier(&config),
+            chain_identifier_serializer: HashSerializer::new(),
+            chain_identifier_deserializer: HashDeserializer::new(),
             config,
             peer_id_serializer: PeerIdSerializer::new(),
             peer_id_deserializer: PeerIdDeserializer::new(),
@@ -298,6 +1086,27 @@ impl MassaHandshake {
         let mut peer_db_write = self.peer_db.write();
         peer_db_write.set_try_connect_failure_or_insert(addr);
     }
+
+    /// Refuses the handshake if too many were attempted recently with the address
+    fn check_handshake_limit(&self, addr: &SocketAddr) -> PeerNetResult<()> {
+        let admission = self
+            .handshake_limiter
+            .lock()
+            .note_attempt(addr.ip(), Instant::now());
+        match admission {
+            HandshakeAdmission::Allowed => return Ok(()),
+            HandshakeAdmission::CooldownStarted => warn!(
+                "Too many handshakes attempted with {}, refusing them for {}ms",
+                addr.ip(),
+                self.config.handshake_cooldown.as_millis()
+            ),
+            HandshakeAdmission::InCooldown => {}
+        }
+        Err(PeerNetError::HandshakeError.error(
+            "Massa Handshake",
+            Some(format!("Too many handshakes attempted with {}", addr.ip())),
+        ))
+    }
 }
 
 impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake {
@@ -309,6 +1118,18 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
         messages_handler: MessagesHandler,
     ) -> PeerNetResult<PeerId> {
         let addr = *endpoint.get_target_addr();
+        // refuse the banned addresses before sending anything
+        if self
+            .ban_list
+            .read()
+            .is_ip_banned(&addr.ip(), MassaTime::now())
+        {
+            return Err(PeerNetError::HandshakeError.error(
+                "Massa Handshake",
+                Some(format!("Banned address: {}", addr.ip())),
+            ));
+        }
+        self.check_handshake_limit(&addr)?;
         let mut bytes = vec![];
         self.peer_id_serializer
             .serialize(&context.get_peer_id(), &mut bytes)
@@ -330,8 +1151,8 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
             })?;
         bytes.push(0);
         let listeners_announcement = Announcement::new(
-            listeners.clone(),
-            self.config.routable_ip,
+            advertised_listeners(listeners, &self.config),
+            &routable_ips(&self.config),
             &context.our_keypair,
         )
         .unwrap();
@@ -344,6 +1165,45 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     Some(format!("Failed to serialize announcement: {}", err)),
                 )
             })?;
+        // optional trailing fields, in the order they were added: user agent, features,
+        // features version, chain identifier. A node reads the ones it knows and ignores the rest.
+        self.user_agent_serializer
+            .serialize(&self.config.user_agent, &mut bytes)
+            .map_err(|err| {
+                self.handshake_fail(&addr);
+                PeerNetError::HandshakeError.error(
+                    "Massa Handshake",
+                    Some(format!("Failed to serialize user agent: {}", err)),
+                )
+            })?;
+        self.features_serializer
+            .serialize(&announced_features(&self.config).0, &mut bytes)
+            .map_err(|err| {
+                self.handshake_fail(&addr);
+                PeerNetError::HandshakeError.error(
+                    "Massa Handshake",
+                    Some(format!("Failed to serialize features: {}", err)),
+                )
+            })?;
+        self.features_version_serializer
+            .serialize(&PeerFeatures::VERSION, &mut bytes)
+            .map_err(|err| {
+                self.handshake_fail(&addr);
+                PeerNetError::HandshakeError.error(
+                    "Massa Handshake",
+                    Some(format!("Failed to serialize features version: {}", err)),
+                )
+            })?;
+        // the chain identifier comes last
+        self.chain_identifier_serializer
+            .serialize(&self.chain_identifier, &mut bytes)
+            .map_err(|err| {
+                self.handshake_fail(&addr);
+                PeerNetError::HandshakeError.error(
+                    "Massa Handshake",
+                    Some(format!("Failed to serialize chain identifier: {}", err)),
+                )
+            })?;
         endpoint.send::<PeerId>(&bytes)?;
         let received = endpoint.receive::<PeerId>()?;
         if received.len() < 32 {
@@ -371,7 +1231,16 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                 }
             }
         }
+        if self
+            .ban_list
+            .read()
+            .is_peer_banned(&peer_id, MassaTime::now())
+        {
+            return Err(PeerNetError::HandshakeError
+                .error("Massa Handshake", Some(format!("Banned peer: {}", peer_id))));
+        }
 
+        let mut peer_agent = None;
         let res = {
             {
                 let mut peer_db_write = self.peer_db.write();
@@ -398,13 +1267,19 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     Some(format!("Received version incompatible: {}", version)),
                 ));
             }
+            peer_agent = Some(PeerAgent {
+                version,
+                user_agent: None,
+                features: PeerFeatures::default(),
+                features_version: 0,
+            });
             let id = received.first().ok_or(
                 PeerNetError::HandshakeError
                     .error("Massa Handshake", Some("Failed to get id".to_string())),
             )?;
             match id {
                 0 => {
-                    let (_, announcement) = self
+                    let (rest, announcement) = self
                         .announcement_deserializer
                         .deserialize::<DeserializeError>(
                             received.get(1..).ok_or(PeerNetError::HandshakeError.error(
@@ -425,6 +1300,47 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                         return Err(PeerNetError::HandshakeError
                             .error("Massa Handshake", Some("Invalid signature".to_string())));
                     }
+                    // optional and informative only: an invalid user agent is dropped,
+                    // and the features that follow it are then considered absent
+                    let mut peer_chain_identifier = None;
+                    if let Some(agent) = peer_agent.as_mut() {
+                        if let Ok((rest, user_agent)) = self
+                            .user_agent_deserializer
+                            .deserialize::<DeserializeError>(rest)
+                        {
+                            agent.user_agent = Some(user_agent);
+                            if let Ok((rest, features)) = self
+                                .features_deserializer
+                                .deserialize::<DeserializeError>(rest)
+                            {
+                                if let Ok((rest, version)) = self
+                                    .features_version_deserializer
+                                    .deserialize::<DeserializeError>(rest)
+                                {
+                                    agent.features_version = version;
+                                    peer_chain_identifier = self
+                                        .chain_identifier_deserializer
+                                        .deserialize::<DeserializeError>(rest)
+                                        .map(|(_, chain_identifier)| chain_identifier)
+                                        .ok();
+                                }
+                                agent.features = PeerFeatures::from_announcement(
+                                    features,
+                                    agent.features_version,
+                                );
+                            }
+                        }
+                    }
+                    // the older nodes do not announce their network, only their version is checked
+                    if peer_chain_identifier
+                        .is_some_and(|chain_identifier| chain_identifier != self.chain_identifier)
+                    {
+                        self.handshake_fail(&addr);
+                        return Err(PeerNetError::HandshakeError.error(
+                            "Massa Handshake",
+                            Some(format!("Peer {} is on another network", peer_id)),
+                        ));
+                    }
                     let message = PeerManagementMessage::NewPeerConnected((
                         peer_id,
                         announcement.clone().listeners,
@@ -512,17 +1428,33 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
             match &res {
                 Ok((peer_id, Some(announcement))) => {
                     info!("Peer connected: {:?}", peer_id);
+                    // the messages are wrapped in an envelope only for the peers that decode it
+                    if peer_agent.as_ref().is_some_and(|agent| {
+                        agent.features.contains(PeerFeatures::MESSAGE_ENVELOPE)
+                    }) {
+                        self.envelope_peers.write().insert(*peer_id);
+                    } else {
+                        self.envelope_peers.write().remove(peer_id);
+                    }
                     peer_db_write.set_try_connect_success_or_insert(&addr);
+                    peer_db_write.get_peer_store_mut().note_handshake(
+                        *peer_id,
+                        announcement.listeners.keys().copied().collect(),
+                        peer_agent.clone(),
+                        MassaTime::now(),
+                    );
                     peer_db_write
                         .get_peers_mut()
                         .entry(*peer_id)
                         .and_modify(|info| {
                             info.last_announce = Some(announcement.clone());
                             info.state = PeerState::Trusted;
+                            info.agent = peer_agent.clone();
                         })
                         .or_insert(PeerInfo {
                             last_announce: Some(announcement.clone()),
                             state: PeerState::Trusted,
+                            agent: peer_agent,
                         });
                 }
                 Ok((_peer_id, None)) => {
@@ -541,6 +1473,9 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                 }
                 Err(_) => {
                     peer_db_write.set_try_connect_failure_or_insert(&addr);
+                    peer_db_write
+                        .get_peer_store_mut()
+                        .note_handshake_failure(&peer_id);
                     peer_db_write
                         .get_peers_mut()
                         .entry(peer_id)
@@ -572,6 +1507,7 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
         endpoint: &mut Endpoint,
         _listeners: &HashMap<SocketAddr, TransportType>,
     ) -> PeerNetResult<()> {
+        self.check_handshake_limit(endpoint.get_target_addr())?;
         //TODO: Fix this clone
         let context = context.clone();
         let mut endpoint = endpoint.try_clone()?;
@@ -625,16 +1561,30 @@ mod tests {
     use std::{collections::HashMap, ops::Deref, sync::Arc};
 
     use massa_channel::MassaChannel;
-    use massa_protocol_exports::ProtocolConfig;
+    use massa_models::address::Address;
+    use massa_models::heartbeat::{Heartbeat, HeartbeatSerializer};
+    use massa_models::prehash::PreHashSet;
+    use massa_models::secure_share::SecureShareContent;
+    use massa_pos_exports::{MockSelectorController, PosError};
+    use massa_protocol_exports::{BanEntry, BanTarget, ProtocolConfig};
     use massa_serialization::U64VarIntDeserializer;
     use massa_signature::KeyPair;
+    use massa_time::MassaTime;
     use parking_lot::RwLock;
     use peernet::{peer::InitConnectionHandler, transports::endpoint::Endpoint};
 
     use crate::{context::Context, messages::MessagesHandler};
 
+    use super::ban_list::{BanList, SharedBanList};
     use super::models::PeerDB;
 
+    fn shared_ban_list() -> SharedBanList {
+        let config = ProtocolConfig::default();
+        Arc::new(RwLock::new(
+            BanList::load(&config.ban_list_file, &config.peers_whitelist_file).unwrap(),
+        ))
+    }
+
     #[test]
     fn test_handshake_working_behaviour() {
         let (sender_blocks, _) = MassaChannel::new(String::from("test_blocks"), None);
@@ -642,7 +1592,12 @@ mod tests {
         let (sender_operations, _) = MassaChannel::new(String::from("test_operations"), None);
         let (sender_peers, _) = MassaChannel::new(String::from("test_peers"), None);
         let shared_peer_db = Arc::new(RwLock::new(PeerDB::default()));
-        let mut handshake = super::MassaHandshake::new(shared_peer_db, ProtocolConfig::default());
+        let mut handshake = super::MassaHandshake::new(
+            shared_peer_db,
+            shared_ban_list(),
+            Default::default(),
+            ProtocolConfig::default(),
+        );
         let our_keypair = KeyPair::generate(0).unwrap();
         let messages_handlers = MessagesHandler {
             id_deserializer: U64VarIntDeserializer::new(
@@ -653,6 +1608,7 @@ mod tests {
             sender_endorsements,
             sender_operations,
             sender_peers,
+            traffic: Default::default(),
         };
         let (local_sender, remote_receiver) =
             MassaChannel::new(String::from("Test_transport_local_to_remote"), None);
@@ -703,7 +1659,12 @@ mod tests {
         let (sender_operations, _) = MassaChannel::new(String::from("test_operations"), None);
         let (sender_peers, _) = MassaChannel::new(String::from("test_peers"), None);
         let shared_peer_db = Arc::new(RwLock::new(PeerDB::default()));
-        let mut handshake = super::MassaHandshake::new(shared_peer_db, ProtocolConfig::default());
+        let mut handshake = super::MassaHandshake::new(
+            shared_peer_db,
+            shared_ban_list(),
+            Default::default(),
+            ProtocolConfig::default(),
+        );
         let our_keypair = KeyPair::generate(0).unwrap();
         let messages_handlers = MessagesHandler {
             id_deserializer: U64VarIntDeserializer::new(
@@ -714,6 +1675,7 @@ mod tests {
             sender_endorsements,
             sender_operations,
             sender_peers,
+            traffic: Default::default(),
         };
         let (local_sender, _) =
             MassaChannel::new(String::from("Test_transport_local_to_remote"), None);
@@ -748,7 +1710,12 @@ mod tests {
         let (sender_operations, _) = MassaChannel::new(String::from("test_operations"), None);
         let (sender_peers, _) = MassaChannel::new(String::from("test_peers"), None);
         let shared_peer_db = Arc::new(RwLock::new(PeerDB::default()));
-        let mut handshake = super::MassaHandshake::new(shared_peer_db, ProtocolConfig::default());
+        let mut handshake = super::MassaHandshake::new(
+            shared_peer_db,
+            shared_ban_list(),
+            Default::default(),
+            ProtocolConfig::default(),
+        );
         let our_keypair = KeyPair::generate(0).unwrap();
         let messages_handlers = MessagesHandler {
             id_deserializer: U64VarIntDeserializer::new(
@@ -759,6 +1726,7 @@ mod tests {
             sender_endorsements,
             sender_operations,
             sender_peers,
+            traffic: Default::default(),
         };
         let (local_sender, _) =
             MassaChannel::new(String::from("Test_transport_local_to_remote"), None);
@@ -779,4 +1747,202 @@ mod tests {
         );
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_handshake_refused_for_banned_subnet() {
+        let (sender_blocks, _) = MassaChannel::new(String::from("test_blocks"), None);
+        let (sender_endorsements, _) = MassaChannel::new(String::from("test_endorsements"), None);
+        let (sender_operations, _) = MassaChannel::new(String::from("test_operations"), None);
+        let (sender_peers, _) = MassaChannel::new(String::from("test_peers"), None);
+        let shared_peer_db = Arc::new(RwLock::new(PeerDB::default()));
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut ban_list = BanList::load(
+            &dir.path().join("ban_list.json"),
+            &dir.path().join("peers_whitelist.json"),
+        )
+        .unwrap();
+        ban_list
+            .insert(vec![BanEntry {
+                target: BanTarget::Subnet("127.0.0.0/8".parse().unwrap()),
+                expires_at: None,
+            }])
+            .unwrap();
+        let mut handshake = super::MassaHandshake::new(
+            shared_peer_db,
+            Arc::new(RwLock::new(ban_list)),
+            Default::default(),
+            ProtocolConfig::default(),
+        );
+        let messages_handlers = MessagesHandler {
+            id_deserializer: U64VarIntDeserializer::new(
+                std::ops::Bound::Included(0),
+                std::ops::Bound::Included(u64::MAX),
+            ),
+            sender_blocks,
+            sender_endorsements,
+            sender_operations,
+            sender_peers,
+            traffic: Default::default(),
+        };
+        let (local_sender, remote_receiver) =
+            MassaChannel::new(String::from("Test_transport_local_to_remote"), None);
+        let (_remote_sender, local_receiver) =
+            MassaChannel::new(String::from("Test_transport_remote_to_local"), None);
+        let mut endpoint = Endpoint::MockEndpoint((
+            (*local_sender.deref()).clone(),
+            (*local_receiver.deref()).clone(),
+            "127.0.0.1:0".parse().unwrap(),
+        ));
+        let context = Context {
+            our_keypair: KeyPair::generate(0).unwrap(),
+        };
+        let res = handshake.perform_handshake(
+            &context,
+            &mut endpoint,
+            &HashMap::default(),
+            messages_handlers,
+        );
+        assert!(res.is_err());
+        // nothing was sent to the banned address
+        assert!(remote_receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_heartbeats_flood_from_non_stakers() {
+        let config = ProtocolConfig {
+            max_known_heartbeats: 4,
+            ..ProtocolConfig::default()
+        };
+        let stakers: Vec<KeyPair> = (0..2).map(|_| KeyPair::generate(0).unwrap()).collect();
+        let staker_addresses: PreHashSet<Address> = stakers
+            .iter()
+            .map(|keypair| Address::from_public_key(&keypair.get_public_key()))
+            .collect();
+        let mut selector_controller = MockSelectorController::new();
+        selector_controller
+            .expect_has_rolls_at()
+            .returning(move |_, address| Ok(staker_addresses.contains(address)));
+        let heartbeat = |keypair: &KeyPair| {
+            Heartbeat {
+                timestamp: MassaTime::now(),
+                last_produced_slot: None,
+            }
+            .new_verifiable(HeartbeatSerializer::new(), keypair, config.chain_id)
+            .unwrap()
+        };
+
+        // a flood of heartbeats from addresses without rolls does not fill the known heartbeats
+        let mut known_heartbeats = HashMap::new();
+        let flood = (0..20)
+            .map(|_| heartbeat(&KeyPair::generate(0).unwrap()))
+            .collect();
+        assert!(super::integrate_heartbeats(
+            &mut known_heartbeats,
+            flood,
+            &selector_controller,
+            &config
+        )
+        .is_empty());
+        assert!(known_heartbeats.is_empty());
+
+        // the heartbeats of the stakers are still accepted and relayed
+        let new_heartbeats = super::integrate_heartbeats(
+            &mut known_heartbeats,
+            stakers.iter().map(heartbeat).collect(),
+            &selector_controller,
+            &config,
+        );
+        assert_eq!(new_heartbeats.len(), 2);
+        assert_eq!(known_heartbeats.len(), 2);
+
+        // heartbeats are dropped while the rolls of their creators cannot be checked
+        let mut unavailable_selector = MockSelectorController::new();
+        unavailable_selector
+            .expect_has_rolls_at()
+            .returning(|slot, _| Err(PosError::CycleUnavailable(slot.period)));
+        let mut known_heartbeats = HashMap::new();
+        assert!(super::integrate_heartbeats(
+            &mut known_heartbeats,
+            stakers.iter().map(heartbeat).collect(),
+            &unavailable_selector,
+            &config,
+        )
+        .is_empty());
+        assert!(known_heartbeats.is_empty());
+    }
+
+    #[test]
+    fn test_handshake_refused_for_other_network() {
+        let messages_handlers = |name: &str| {
+            let channel = |kind: &str| MassaChannel::new(format!("{}_{}", name, kind), None).0;
+            MessagesHandler {
+                id_deserializer: U64VarIntDeserializer::new(
+                    std::ops::Bound::Included(0),
+                    std::ops::Bound::Included(u64::MAX),
+                ),
+                sender_blocks: channel("blocks"),
+                sender_endorsements: channel("endorsements"),
+                sender_operations: channel("operations"),
+                sender_peers: channel("peers"),
+                traffic: Default::default(),
+            }
+        };
+        let config = ProtocolConfig::default();
+        let mut handshake = super::MassaHandshake::new(
+            Arc::new(RwLock::new(PeerDB::default())),
+            shared_ban_list(),
+            Default::default(),
+            config.clone(),
+        );
+        // same version, other chain
+        let mut other_handshake = super::MassaHandshake::new(
+            Arc::new(RwLock::new(PeerDB::default())),
+            shared_ban_list(),
+            Default::default(),
+            ProtocolConfig {
+                chain_id: config.chain_id + 1,
+                ..config
+            },
+        );
+        let (local_sender, remote_receiver) =
+            MassaChannel::new(String::from("Test_transport_local_to_remote"), None);
+        let (remote_sender, local_receiver) =
+            MassaChannel::new(String::from("Test_transport_remote_to_local"), None);
+        let mut endpoint = Endpoint::MockEndpoint((
+            (*local_sender.deref()).clone(),
+            (*local_receiver.deref()).clone(),
+            "127.0.0.1:0".parse().unwrap(),
+        ));
+        let thread = std::thread::spawn({
+            let mut endpoint = Endpoint::MockEndpoint((
+                (*remote_sender.deref()).clone(),
+                (*remote_receiver.deref()).clone(),
+                "127.0.0.1:0".parse().unwrap(),
+            ));
+            let context = Context {
+                our_keypair: KeyPair::generate(0).unwrap(),
+            };
+            let messages_handlers = messages_handlers("remote");
+            move || {
+                let res = other_handshake.perform_handshake(
+                    &context,
+                    &mut endpoint,
+                    &HashMap::default(),
+                    messages_handlers,
+                );
+                assert!(res.is_err());
+            }
+        });
+        let context = Context {
+            our_keypair: KeyPair::generate(0).unwrap(),
+        };
+        let res = handshake.perform_handshake(
+            &context,
+            &mut endpoint,
+            &HashMap::default(),
+            messages_handlers("local"),
+        );
+        assert!(res.is_err());
+        thread.join().unwrap();
+    }
 }
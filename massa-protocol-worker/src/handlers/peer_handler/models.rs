@@ -1,5 +1,10 @@
 use massa_channel::sender::MassaSender;
-use massa_protocol_exports::{BootstrapPeers, PeerId};
+use massa_models::heartbeat::SecureShareHeartbeat;
+use massa_models::node_link::NodeAddressLinkProof;
+use massa_protocol_exports::{
+    BanEntry, BanTarget, BootstrapPeers, PeerAgent, PeerFeatures, PeerId, PeerRecord,
+    PeerRecordEdit,
+};
 use massa_time::MassaTime;
 use parking_lot::RwLock;
 use peernet::transports::TransportType;
@@ -8,12 +13,18 @@ use rand::{thread_rng, Rng};
 use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::time::Duration;
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
 use tracing::info;
 
 use crate::wrap_peer_db::PeerDBTrait;
 
 use super::announcement::Announcement;
+use super::latency::PeerLatencies;
+use super::peer_store::PeerStore;
 
 const THREE_DAYS_MS: u64 = 3 * 24 * 60 * 60 * 1_000;
 
@@ -155,16 +166,58 @@ pub struct PeerDB {
     pub try_connect_history: HashMap<SocketAddr, ConnectionMetadata>,
     /// peers currently tested
     pub peers_in_test: HashSet<SocketAddr>,
+    /// result of the dial back test of our routable address (None if not concluded yet)
+    pub reachability: Option<bool>,
+    /// median offset in milliseconds of the clocks of the connected peers relative to ours (None if too few samples)
+    pub network_time_offset: Option<i64>,
+    /// peers remembered across restarts
+    pub peer_store: PeerStore,
+    /// round trip latency to the connected peers
+    pub latencies: PeerLatencies,
 }
 
 pub type SharedPeerDB = Arc<RwLock<dyn PeerDBTrait>>;
 
+/// Peers that announced they decode the messages wrapped in an envelope, updated on each handshake
+pub type SharedEnvelopePeers = Arc<RwLock<HashSet<PeerId>>>;
+
+/// Returns true if `peer_id` announced all the `features` during its last handshake
+pub fn peer_supports(peer_db: &SharedPeerDB, peer_id: &PeerId, features: PeerFeatures) -> bool {
+    peer_db
+        .read()
+        .get_peers()
+        .get(peer_id)
+        .and_then(|info| info.agent.as_ref())
+        .is_some_and(|agent| agent.features.contains(features))
+}
+
+/// Returns the peers among `peer_ids` that announced all the `features` during their last handshake
+pub fn peers_supporting(
+    peer_db: &SharedPeerDB,
+    peer_ids: impl IntoIterator<Item = PeerId>,
+    features: PeerFeatures,
+) -> Vec<PeerId> {
+    let peer_db_read = peer_db.read();
+    let peers = peer_db_read.get_peers();
+    peer_ids
+        .into_iter()
+        .filter(|peer_id| {
+            peers
+                .get(peer_id)
+                .and_then(|info| info.agent.as_ref())
+                .is_some_and(|agent| agent.features.contains(features))
+        })
+        .collect()
+}
+
 pub type PeerMessageTuple = (PeerId, Vec<u8>);
 
 #[derive(Clone, Debug)]
 pub struct PeerInfo {
     pub last_announce: Option<Announcement>,
     pub state: PeerState,
+    /// version and user agent received during the last handshake
+    pub agent: Option<PeerAgent>,
 }
 
 #[warn(dead_code)]
@@ -179,10 +232,44 @@ pub enum PeerState {
 #[derive(Clone)]
 pub enum PeerManagementCmd {
     Ban(Vec<PeerId>),
+    /// the peer sent us an invalid item: it is banned if it sends too many of them
+    NoteInvalidItem {
+        peer_id: PeerId,
+        item_type: &'static str,
+    },
     Unban(Vec<PeerId>),
     GetBootstrapPeers {
         responder: MassaSender<BootstrapPeers>,
     },
+    PropagateHeartbeats(Vec<SecureShareHeartbeat>),
+    GetHeartbeats {
+        responder: MassaSender<Vec<SecureShareHeartbeat>>,
+    },
+    PropagateNodeAddressLinks(Vec<NodeAddressLinkProof>),
+    GetNodeAddressLinks {
+        responder: MassaSender<Vec<NodeAddressLinkProof>>,
+    },
+    /// bans kept across restarts, set by the node operator
+    AddBans(Vec<BanEntry>),
+    RemoveBans(Vec<BanTarget>),
+    GetBans {
+        responder: MassaSender<Vec<BanEntry>>,
+    },
+    /// addresses exempted from the subnet bans, kept across restarts
+    AddToWhitelist(Vec<IpAddr>),
+    RemoveFromWhitelist(Vec<IpAddr>),
+    GetWhitelist {
+        responder: MassaSender<Vec<IpAddr>>,
+    },
+    /// read the ban list and the whitelist again from disk
+    ReloadIpLists,
+    /// records of the given peers, or of all the peers if none is given
+    GetPeerRecords {
+        peer_ids: Vec<PeerId>,
+        responder: MassaSender<Vec<PeerRecord>>,
+    },
+    EditPeerRecords(Vec<PeerRecordEdit>),
+    RemovePeerRecords(Vec<PeerId>),
     Stop,
 }
 
@@ -196,6 +283,7 @@ impl PeerDBTrait for PeerDB {
     fn ban_peer(&mut self, peer_id: &PeerId) {
         if let Some(peer) = self.peers.get_mut(peer_id) {
             peer.state = PeerState::Banned;
+            self.peer_store.note_ban(peer_id, MassaTime::now());
             info!("Banned peer: {:?}", peer_id);
         } else {
             info!("Tried to ban unknown peer: {:?}", peer_id);
@@ -352,4 +440,36 @@ impl PeerDBTrait for PeerDB {
     fn get_tested_addresses(&self) -> &HashMap<SocketAddr, MassaTime> {
         &self.tested_addresses
     }
+
+    fn get_reachability(&self) -> Option<bool> {
+        self.reachability
+    }
+
+    fn set_reachability(&mut self, reachable: bool) {
+        self.reachability = Some(reachable);
+    }
+
+    fn get_network_time_offset(&self) -> Option<i64> {
+        self.network_time_offset
+    }
+
+    fn set_network_time_offset(&mut self, offset: Option<i64>) {
+        self.network_time_offset = offset;
+    }
+
+    fn get_peer_store(&self) -> &PeerStore {
+        &self.peer_store
+    }
+
+    fn get_peer_store_mut(&mut self) -> &mut PeerStore {
+        &mut self.peer_store
+    }
+
+    fn get_latencies(&self) -> &PeerLatencies {
+        &self.latencies
+    }
+
+    fn get_latencies_mut(&mut self) -> &mut PeerLatencies {
+        &mut self.latencies
+    }
 }
@@ -0,0 +1,333 @@
+//! Peers remembered across restarts, with what the node observed of them.
+//!
+//! A peer is recorded once a handshake with it succeeds, or when the node operator edits its record.
+//! Its uptime, handshake failures and bans are then tracked to compute its reputation,
+//! which ranks the candidates for the outbound connections.
+//! The records with listeners are dialed again after a restart, before the announcements of the peers are received.
+//! When the store is full, the peers neither pinned nor connected are forgotten first, the lowest reputation first.
+//! The store is saved as JSON at most every minute while it changes, and on shutdown.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::{
+    PeerAgent, PeerId, PeerLatency, PeerRecord, PeerRecordEdit, ProtocolError,
+};
+use massa_time::MassaTime;
+
+/// Minimum delay between two saves of the store
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct PeerStore {
+    /// file in which the store is saved, none to keep it in memory only
+    path: Option<PathBuf>,
+    /// maximum number of records kept
+    max_records: usize,
+    records: HashMap<PeerId, PeerRecord>,
+    /// connected peers, with the last time their uptime was updated
+    connected: HashMap<PeerId, MassaTime>,
+    /// whether the records changed since the last save
+    dirty: bool,
+    last_save: Option<Instant>,
+}
+
+impl Default for PeerStore {
+    fn default() -> Self {
+        PeerStore {
+            path: None,
+            max_records: usize::MAX,
+            records: HashMap::new(),
+            connected: HashMap::new(),
+            dirty: false,
+            last_save: None,
+        }
+    }
+}
+
+impl PeerStore {
+    /// Loads the store saved at `path`, empty if there is no such file
+    pub fn load(path: &Path, max_records: usize) -> Result<Self, ProtocolError> {
+        let records: Vec<PeerRecord> = match fs::read(path) {
+            Ok(buffer) if buffer.is_empty() => Vec::new(),
+            Ok(buffer) => serde_json::from_slice(&buffer)?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let mut store = PeerStore {
+            path: Some(path.to_path_buf()),
+            max_records,
+            records: records
+                .into_iter()
+                .map(|record| (record.peer_id, record))
+                .collect(),
+            ..Default::default()
+        };
+        store.prune(MassaTime::now());
+        Ok(store)
+    }
+
+    /// Saves the store if it changed, replacing the previous file atomically
+    pub fn save(&mut self) -> Result<(), ProtocolError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&self.entries(&[]))?)?;
+        fs::rename(&tmp_path, path)?;
+        self.dirty = false;
+        self.last_save = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Saves the store if it changed and was not saved within the last minute
+    pub fn save_periodically(&mut self) -> Result<(), ProtocolError> {
+        if self
+            .last_save
+            .is_some_and(|last_save| last_save.elapsed() < SAVE_INTERVAL)
+        {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    /// Records of the given peers, or of all the peers if none is given, the best ranked first
+    pub fn entries(&self, peer_ids: &[PeerId]) -> Vec<PeerRecord> {
+        let mut records: Vec<PeerRecord> = if peer_ids.is_empty() {
+            self.records.values().cloned().collect()
+        } else {
+            peer_ids
+                .iter()
+                .filter_map(|peer_id| self.records.get(peer_id).cloned())
+                .collect()
+        };
+        let now = MassaTime::now();
+        records.sort_by_cached_key(|record| {
+            (
+                std::cmp::Reverse((record.pinned, record.reputation(now))),
+                record.peer_id,
+            )
+        });
+        records
+    }
+
+    /// Rank of `peer_id` among the candidates for the outbound connections at `now`, the greater the better:
+    /// the pinned peers come first, then the peers are ranked by reputation
+    pub fn rank(&self, peer_id: &PeerId, now: MassaTime) -> (bool, i64) {
+        self.records
+            .get(peer_id)
+            .map_or((false, 0), |record| (record.pinned, record.reputation(now)))
+    }
+
+    /// Last latency measured with `peer_id`, if it is recorded and ever answered a ping
+    pub fn latency(&self, peer_id: &PeerId) -> Option<PeerLatency> {
+        self.records.get(peer_id).and_then(|record| record.latency)
+    }
+
+    /// Recorded peers with listeners, to be dialed again after a restart
+    pub fn dial_candidates(&self) -> Vec<(PeerId, Vec<SocketAddr>)> {
+        self.records
+            .values()
+            .filter(|record| !record.listeners.is_empty())
+            .map(|record| (record.peer_id, record.listeners.clone()))
+            .collect()
+    }
+
+    /// Notes a successful handshake with `peer_id` at `now`, recording the peer if needed
+    pub fn note_handshake(
+        &mut self,
+        peer_id: PeerId,
+        listeners: Vec<SocketAddr>,
+        agent: Option<PeerAgent>,
+        now: MassaTime,
+    ) {
+        let record = self
+            .records
+            .entry(peer_id)
+            .or_insert_with(|| PeerRecord::new(peer_id, now));
+        record.connections += 1;
+        record.last_seen = Some(now);
+        if !listeners.is_empty() {
+            record.listeners = listeners;
+        }
+        if agent.is_some() {
+            record.agent = agent;
+        }
+        self.connected.entry(peer_id).or_insert(now);
+        self.dirty = true;
+        self.prune(now);
+    }
+
+    /// Notes a failed handshake with a recorded peer
+    pub fn note_handshake_failure(&mut self, peer_id: &PeerId) {
+        if let Some(record) = self.records.get_mut(peer_id) {
+            record.handshake_failures += 1;
+            self.dirty = true;
+        }
+    }
+
+    /// Notes a ban of a recorded peer at `now`
+    pub fn note_ban(&mut self, peer_id: &PeerId, now: MassaTime) {
+        if let Some(record) = self.records.get_mut(peer_id) {
+            record.note_ban(now);
+            self.dirty = true;
+        }
+    }
+
+    /// Adds the time elapsed since the last update to the uptime of the recorded peers still connected at `now`
+    pub fn update_connected(
+        &mut self,
+        connected: impl IntoIterator<Item = PeerId>,
+        now: MassaTime,
+    ) {
+        let mut still_connected = HashMap::new();
+        for peer_id in connected {
+            let Some(record) = self.records.get_mut(&peer_id) else {
+                continue;
+            };
+            if let Some(since) = self.connected.get(&peer_id) {
+                record.uptime = record.uptime.saturating_add(now.saturating_sub(*since));
+            }
+            record.last_seen = Some(now);
+            still_connected.insert(peer_id, now);
+            self.dirty = true;
+        }
+        self.connected = still_connected;
+    }
+
+    /// Notes the latest latencies measured with the recorded peers
+    pub fn note_latencies(&mut self, latencies: &HashMap<PeerId, PeerLatency>) {
+        for (peer_id, latency) in latencies {
+            if let Some(record) = self.records.get_mut(peer_id) {
+                record.latency = Some(*latency);
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Applies changes asked by the node operator at `now`, recording the unknown peers
+    pub fn edit(&mut self, edits: &[PeerRecordEdit], now: MassaTime) {
+        for edit in edits {
+            let record = self
+                .records
+                .entry(edit.peer_id)
+                .or_insert_with(|| PeerRecord::new(edit.peer_id, now));
+            edit.apply(record);
+        }
+        self.dirty = true;
+        self.prune(now);
+    }
+
+    /// Forgets the given peers
+    pub fn remove(&mut self, peer_ids: &[PeerId]) {
+        for peer_id in peer_ids {
+            if self.records.remove(peer_id).is_some() {
+                self.connected.remove(peer_id);
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Forgets the unpinned peers with the lowest reputation until the store fits its maximum size
+    fn prune(&mut self, now: MassaTime) {
+        if self.records.len() <= self.max_records {
+            return;
+        }
+        let mut candidates: Vec<(i64, Option<MassaTime>, PeerId)> = self
+            .records
+            .values()
+            .filter(|record| !record.pinned && !self.connected.contains_key(&record.peer_id))
+            .map(|record| (record.reputation(now), record.last_seen, record.peer_id))
+            .collect();
+        candidates.sort_unstable();
+        let excess = self.records.len() - self.max_records;
+        for (_, _, peer_id) in candidates.into_iter().take(excess) {
+            self.records.remove(&peer_id);
+        }
+        self.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_signature::KeyPair;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_peer_store_persistence_and_ranking() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("protocol/peer_store.json");
+        let (good_peer, bad_peer, new_peer) = (peer_id(), peer_id(), peer_id());
+        let listener: SocketAddr = "198.51.100.1:31245".parse().unwrap();
+        let now = MassaTime::from_millis(1_000_000);
+
+        let mut store = PeerStore::load(&path, 2).unwrap();
+        store.note_handshake(good_peer, vec![listener], None, now);
+        store.note_handshake(bad_peer, Vec::new(), None, now);
+        let later = now.saturating_add(MassaTime::from_millis(10 * 60 * 1000));
+        store.update_connected([good_peer], later);
+        let latency = PeerLatency {
+            rtt: MassaTime::from_millis(80),
+            jitter: MassaTime::from_millis(10),
+        };
+        store.note_latencies(&HashMap::from([(good_peer, latency), (new_peer, latency)]));
+        store.note_handshake_failure(&bad_peer);
+        store.note_ban(&bad_peer, later);
+        // peers without record are not tracked
+        store.note_handshake_failure(&new_peer);
+        store.save().unwrap();
+
+        // the records survive a restart
+        let mut store = PeerStore::load(&path, 2).unwrap();
+        assert_eq!(store.rank(&good_peer, later), (false, 20));
+        assert_eq!(store.rank(&bad_peer, later), (false, 10 - 50 - 1000));
+        assert_eq!(store.rank(&new_peer, later), (false, 0));
+        assert_eq!(store.dial_candidates(), vec![(good_peer, vec![listener])]);
+        assert_eq!(store.latency(&good_peer), Some(latency));
+        assert_eq!(store.latency(&new_peer), None);
+
+        // when the store is full, the peers neither pinned nor connected are forgotten first
+        store.edit(
+            &[PeerRecordEdit {
+                peer_id: bad_peer,
+                listeners: None,
+                pinned: Some(true),
+                reset_reputation: false,
+            }],
+            later,
+        );
+        store.note_handshake(new_peer, Vec::new(), None, later);
+        let entries = store.entries(&[]);
+        assert_eq!(
+            entries
+                .iter()
+                .map(|record| record.peer_id)
+                .collect::<Vec<_>>(),
+            vec![bad_peer, new_peer]
+        );
+
+        store.remove(&[bad_peer]);
+        store.save().unwrap();
+        let store = PeerStore::load(&path, 2).unwrap();
+        assert_eq!(store.entries(&[]).len(), 1);
+        assert!(store.entries(&[bad_peer]).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+//! Reachability self-test: a connected peer is asked to dial back our routable address, and we
+//! wait for its answer before asking another one.
+
+use std::time::{Duration, Instant};
+
+use massa_protocol_exports::PeerId;
+
+/// Reachability test we asked a peer to run and that was not answered yet
+pub(crate) struct PendingReachabilityTest {
+    /// time given to a tester to answer: a failing dial back can wait for the connection, the
+    /// write and the read timeouts in turn
+    answer_timeout: Duration,
+    /// peer we asked to dial back our routable address, and the time after which we ask another one
+    pending: Option<(PeerId, Instant)>,
+}
+
+impl PendingReachabilityTest {
+    pub(crate) fn new(tester_timeout: Duration) -> Self {
+        PendingReachabilityTest {
+            answer_timeout: tester_timeout.saturating_mul(3),
+            pending: None,
+        }
+    }
+
+    /// Whether a new peer can be asked at `now`: no test is pending or its tester did not answer in time
+    pub(crate) fn can_ask(&self, now: Instant) -> bool {
+        self.pending.map_or(true, |(_, deadline)| now >= deadline)
+    }
+
+    /// Notes that `peer_id` was asked to run the test at `now`
+    pub(crate) fn asked(&mut self, peer_id: PeerId, now: Instant) {
+        self.pending = Some((peer_id, now + self.answer_timeout));
+    }
+
+    /// Takes the answer of `peer_id`: returns false if we were not waiting for it
+    pub(crate) fn answered(&mut self, peer_id: &PeerId) -> bool {
+        match self.pending {
+            Some((tester, _)) if tester == *peer_id => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_signature::KeyPair;
+
+    use super::*;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_late_reachability_result() {
+        let tester_timeout = Duration::from_secs(10);
+        let tick = Duration::from_secs(10);
+        let mut test = PendingReachabilityTest::new(tester_timeout);
+        let (tester, other) = (peer_id(), peer_id());
+        let start = Instant::now();
+
+        assert!(test.can_ask(start));
+        test.asked(tester, start);
+        // the dial back of the tester times out on connect and then on read:
+        // no other peer is asked on the next ticks
+        assert!(!test.can_ask(start + tick));
+        assert!(!test.can_ask(start + 2 * tick));
+        // the late negative result is still expected
+        assert!(!test.answered(&other));
+        assert!(test.answered(&tester));
+        assert!(test.can_ask(start + 2 * tick));
+        // it is only taken once
+        assert!(!test.answered(&tester));
+    }
+
+    #[test]
+    fn test_unanswered_reachability_test() {
+        let tester_timeout = Duration::from_secs(10);
+        let mut test = PendingReachabilityTest::new(tester_timeout);
+        let (tester, other) = (peer_id(), peer_id());
+        let start = Instant::now();
+
+        test.asked(tester, start);
+        // the tester did not answer in time: another peer is asked
+        assert!(test.can_ask(start + 3 * tester_timeout));
+        test.asked(other, start + 3 * tester_timeout);
+        assert!(!test.answered(&tester));
+        assert!(test.answered(&other));
+    }
+}
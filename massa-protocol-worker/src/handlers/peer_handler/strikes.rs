@@ -0,0 +1,117 @@
+//! Strikes given to the peers sending us invalid items (blocks, headers, operations, endorsements),
+//! so that a single invalid item does not get a peer banned but a stream of them does.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::PeerId;
+
+/// Invalid items received recently from each peer
+pub(crate) struct InvalidItemStrikes {
+    /// number of strikes within `window` after which a peer is banned
+    threshold: usize,
+    /// sliding window over which the strikes are counted
+    window: Duration,
+    /// time of the strikes of each peer within the window, oldest first
+    strikes: HashMap<PeerId, VecDeque<Instant>>,
+}
+
+impl InvalidItemStrikes {
+    pub(crate) fn new(threshold: usize, window: Duration) -> Self {
+        InvalidItemStrikes {
+            threshold,
+            window,
+            strikes: HashMap::new(),
+        }
+    }
+
+    /// Notes an invalid item received from `peer_id` at `now`.
+    ///
+    /// Returns the number of strikes of the peer within the window if it reached the threshold,
+    /// in which case the peer must be banned and its strikes are forgotten.
+    pub(crate) fn add_strike(&mut self, peer_id: PeerId, now: Instant) -> Option<usize> {
+        let strikes = self.strikes.entry(peer_id).or_default();
+        while let Some(oldest) = strikes.front() {
+            if now.saturating_duration_since(*oldest) < self.window {
+                break;
+            }
+            strikes.pop_front();
+        }
+        strikes.push_back(now);
+        let count = strikes.len();
+        if count < self.threshold.max(1) {
+            return None;
+        }
+        self.strikes.remove(&peer_id);
+        Some(count)
+    }
+
+    /// Forgets the strikes of the peers that are not connected anymore, and the expired strikes
+    pub(crate) fn prune(&mut self, connected: &HashSet<PeerId>, now: Instant) {
+        let window = self.window;
+        self.strikes.retain(|peer_id, strikes| {
+            connected.contains(peer_id)
+                && strikes
+                    .back()
+                    .is_some_and(|last| now.saturating_duration_since(*last) < window)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_signature::KeyPair;
+
+    use super::*;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_strikes_within_window() {
+        let mut strikes = InvalidItemStrikes::new(3, Duration::from_secs(10));
+        let (peer_a, peer_b) = (peer_id(), peer_id());
+        let start = Instant::now();
+
+        assert_eq!(strikes.add_strike(peer_a, start), None);
+        assert_eq!(strikes.add_strike(peer_b, start), None);
+        assert_eq!(
+            strikes.add_strike(peer_a, start + Duration::from_secs(4)),
+            None
+        );
+        // the first strike of A expired: it is still below the threshold
+        assert_eq!(
+            strikes.add_strike(peer_a, start + Duration::from_secs(11)),
+            None
+        );
+        assert_eq!(
+            strikes.add_strike(peer_a, start + Duration::from_secs(12)),
+            Some(3)
+        );
+        // the strikes of a banned peer are forgotten
+        assert_eq!(
+            strikes.add_strike(peer_a, start + Duration::from_secs(12)),
+            None
+        );
+
+        // the strikes of the disconnected peers are forgotten
+        strikes.prune(
+            &[peer_a].into_iter().collect(),
+            start + Duration::from_secs(12),
+        );
+        assert!(strikes.strikes.contains_key(&peer_a));
+        assert!(!strikes.strikes.contains_key(&peer_b));
+    }
+
+    #[test]
+    fn test_strikes_immediate_ban() {
+        let mut strikes = InvalidItemStrikes::new(1, Duration::from_secs(10));
+        assert_eq!(strikes.add_strike(peer_id(), Instant::now()), Some(1));
+        // a threshold of 0 is handled as 1
+        let mut strikes = InvalidItemStrikes::new(0, Duration::from_secs(10));
+        assert_eq!(strikes.add_strike(peer_id(), Instant::now()), Some(1));
+    }
+}
@@ -2,16 +2,25 @@ use std::{
     collections::HashMap,
     io::Read,
     net::{IpAddr, SocketAddr},
+    ops::Bound::Included,
     thread::JoinHandle,
     time::Duration,
 };
 
-use crate::{ip::to_canonical, messages::MessagesHandler};
+use crate::{
+    dialer::Dialer,
+    ip::{routable_ips, to_canonical},
+    messages::MessagesHandler,
+    transport::listeners_of,
+};
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
+use massa_hash::{Hash, HashDeserializer};
 use massa_metrics::MassaMetrics;
-use massa_models::version::VersionDeserializer;
+use massa_models::{serialization::StringDeserializer, version::VersionDeserializer};
 use massa_protocol_exports::{PeerConnectionType, PeerId, PeerIdDeserializer, ProtocolConfig};
-use massa_serialization::{DeserializeError, Deserializer};
+use massa_serialization::{
+    DeserializeError, Deserializer, U32VarIntDeserializer, U64VarIntDeserializer,
+};
 use massa_time::MassaTime;
 use peernet::{
     error::{PeerNetError, PeerNetResult},
@@ -27,6 +36,26 @@ use super::{
 };
 use crate::wrap_network::ActiveConnectionsTrait;
 
+/// Chain identifier announced after the user agent and the features in a handshake, if any
+fn announced_chain_identifier(data: &[u8], config: &ProtocolConfig) -> Option<Hash> {
+    let (data, _user_agent) = StringDeserializer::new(U32VarIntDeserializer::new(
+        Included(0),
+        Included(config.max_user_agent_length),
+    ))
+    .deserialize::<DeserializeError>(data)
+    .ok()?;
+    let (data, _features) = U64VarIntDeserializer::new(Included(0), Included(u64::MAX))
+        .deserialize::<DeserializeError>(data)
+        .ok()?;
+    let (data, _features_version) = U32VarIntDeserializer::new(Included(0), Included(u32::MAX))
+        .deserialize::<DeserializeError>(data)
+        .ok()?;
+    HashDeserializer::new()
+        .deserialize::<DeserializeError>(data)
+        .map(|(_, chain_identifier)| chain_identifier)
+        .ok()
+}
+
 const THREAD_NAME: &str = "pph-tester";
 static_assertions::const_assert!(THREAD_NAME.len() < 16);
 
@@ -89,9 +118,9 @@ impl Tester {
         let our_version = config.version;
 
         let exec_handshake = || {
-            let mut socket =
-                std::net::TcpStream::connect_timeout(&addr, config.tester_timeout.into())
-                    .map_err(|e| PeerNetError::PeerConnectionError.new("connect", e, None))?;
+            let mut socket = Dialer::new(config)
+                .connect(addr, config.tester_timeout.into())
+                .map_err(|e| PeerNetError::PeerConnectionError.new("connect", e, None))?;
             socket
                 .set_read_timeout(Some(config.tester_timeout.into()))
                 .map_err(|err| PeerNetError::PeerConnectionError.new("read timeout", err, None))?;
@@ -163,7 +192,7 @@ impl Tester {
                 )?;
                 match id {
                     0 => {
-                        let (_, announcement) = announcement_deserializer
+                        let (rest, announcement) = announcement_deserializer
                             .deserialize::<DeserializeError>(data.get(1..).ok_or(
                                 PeerNetError::HandshakeError.error(
                                     "Massa Handshake",
@@ -186,6 +215,14 @@ impl Tester {
                                 Some(String::from("Invalid signature")),
                             ));
                         }
+                        if announced_chain_identifier(rest, config).is_some_and(
+                            |chain_identifier| chain_identifier != super::chain_identifier(config),
+                        ) {
+                            return Err(PeerNetError::HandshakeError.error(
+                                "Tester Handshake",
+                                Some(String::from("Peer is on another network")),
+                            ));
+                        }
                         //TODO: Check ip we are connected match one of the announced ips
                         {
                             let mut peer_db_write = peer_db.write();
@@ -205,6 +242,7 @@ impl Tester {
                                 .or_insert(PeerInfo {
                                     last_announce: Some(announcement),
                                     state: super::PeerState::Trusted,
+                                    agent: None,
                                 });
                         }
                         Ok(peer_id)
@@ -242,6 +280,7 @@ impl Tester {
                         .or_insert(PeerInfo {
                             last_announce: None,
                             state: super::PeerState::HandshakeFailed,
+                            agent: None,
                         });
                     peer_db_write.set_try_connect_test_failure_or_insert(&addr);
                 } else {
@@ -266,6 +305,56 @@ impl Tester {
         result
     }
 
+    /// Dial back a peer that asked us to test its reachability.
+    ///
+    /// Returns true if we could open a connection to `addr` and the node behind it
+    /// introduced itself with `expected_peer_id`.
+    pub fn dial_back(addr: SocketAddr, expected_peer_id: &PeerId, config: &ProtocolConfig) -> bool {
+        let exec_dial_back = || -> PeerNetResult<PeerId> {
+            let mut socket = Dialer::new(config)
+                .connect(addr, config.tester_timeout.into())
+                .map_err(|e| PeerNetError::PeerConnectionError.new("connect", e, None))?;
+            socket
+                .set_read_timeout(Some(config.tester_timeout.into()))
+                .map_err(|err| PeerNetError::PeerConnectionError.new("read timeout", err, None))?;
+            let mut len_bytes = vec![0u8; 4];
+            socket
+                .read_exact(&mut len_bytes)
+                .map_err(|err| PeerNetError::PeerConnectionError.new("recv len", err, None))?;
+            let res_size = u32::from_be_bytes(len_bytes.try_into().map_err(|err| {
+                PeerNetError::PeerConnectionError.error("recv len", Some(format!("{:?}", err)))
+            })?);
+            if res_size > 1048576000 {
+                return Err(PeerNetError::InvalidMessage
+                    .error("len too long", Some(format!("{:?}", res_size))));
+            }
+            let mut data = vec![0u8; res_size as usize];
+            socket
+                .read_exact(&mut data)
+                .map_err(|err| PeerNetError::PeerConnectionError.new("recv data", err, None))?;
+            let (_, peer_id) = PeerIdDeserializer::new()
+                .deserialize::<DeserializeError>(&data)
+                .map_err(|_| {
+                    PeerNetError::HandshakeError.error(
+                        "Dial back",
+                        Some("Failed to deserialize PeerId".to_string()),
+                    )
+                })?;
+            if let Err(e) = socket.shutdown(std::net::Shutdown::Both) {
+                tracing::error!("Failed to shutdown socket for {} : {}", addr, e);
+            }
+            Ok(peer_id)
+        };
+
+        match exec_dial_back() {
+            Ok(peer_id) => &peer_id == expected_peer_id,
+            Err(err) => {
+                debug!("dial back to {} failed: {:?}", addr, err);
+                false
+            }
+        }
+    }
+
     /// Create a new tester (spawn a thread)
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -293,6 +382,8 @@ impl Tester {
 
             //let mut network_manager = PeerNetManager::new(config);
             let protocol_config = protocol_config.clone();
+            let dialer = Dialer::new(&protocol_config);
+            let our_ips = routable_ips(&protocol_config);
             'main_loop: loop {
                 crossbeam::select! {
                     recv(receiver) -> res => {
@@ -334,7 +425,10 @@ impl Tester {
                                     let now = MassaTime::now();
                                     let db = db.clone();
                                     // receive new listener to test
-                                    for (addr, _) in listener.1.iter() {
+                                    // the listeners of a dual-stack peer are tried in dialing order until one of them answers,
+                                    // only the TCP ones being tested by the handshake below
+                                    let addrs: Vec<SocketAddr> = listeners_of(&listener.1, TransportType::Tcp).collect();
+                                    for addr in &dialer.order_addresses(&addrs) {
                                         if !db.write().insert_peer_in_test(addr) {
                                             // if the peer is already in test, we skip it
                                             continue;
@@ -379,12 +473,10 @@ impl Tester {
                                                 continue 'main_loop;
                                             }
 
-                                            //Don't test our proper ip
-                                            if let Some(ip) = protocol_config.routable_ip {
-                                                if to_canonical(ip) == ip_canonical {
-                                                    db.write().remove_peer_in_test(addr);
-                                                    continue 'main_loop;
-                                                }
+                                            //Don't test our proper ips
+                                            if our_ips.contains(&ip_canonical) {
+                                                db.write().remove_peer_in_test(addr);
+                                                continue 'main_loop;
                                             }
                                             debug!("testing peer {} listener addr: {}", &listener.0, &addr);
 
@@ -408,6 +500,10 @@ impl Tester {
                                             // );
 
                                             tracing::debug!("{:?}", res);
+                                            if res.is_ok() {
+                                                // the peer is reachable, there is no need to test its other addresses
+                                                break;
+                                            }
                                         }
                                     };
                                 }
@@ -445,12 +541,10 @@ impl Tester {
                                 continue;
                             }
                         }
-                        //Don't test our proper ip
-                        if let Some(ip) = protocol_config.routable_ip {
-                            if to_canonical(ip) == ip_canonical {
-                                db.write().remove_peer_in_test(&listener);
-                                continue;
-                            }
+                        //Don't test our proper ips
+                        if our_ips.contains(&ip_canonical) {
+                            db.write().remove_peer_in_test(&listener);
+                            continue;
                         }
                         debug!("testing listener addr: {}", &listener);
 
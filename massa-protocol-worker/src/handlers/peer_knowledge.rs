@@ -0,0 +1,134 @@
+//! Knowledge of the peers that disconnected recently, kept for a short time so that a peer
+//! reconnecting quickly does not get sent again everything it already knows.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::PeerId;
+
+/// Knowledge of the recently disconnected peers, for a given item type
+pub(crate) struct RetainedPeerKnowledge<T> {
+    /// how long the knowledge of a disconnected peer is kept
+    retention: Duration,
+    /// knowledge of each disconnected peer, with the time it was seen disconnected
+    peers: HashMap<PeerId, (Instant, T)>,
+}
+
+impl<T> RetainedPeerKnowledge<T> {
+    pub(crate) fn new(retention: Duration) -> Self {
+        RetainedPeerKnowledge {
+            retention,
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Moves the knowledge of the peers that are not connected anymore out of `known_by_peer`,
+    /// and moves back the knowledge of the peers that reconnected before the end of the retention.
+    pub(crate) fn update(
+        &mut self,
+        known_by_peer: &mut HashMap<PeerId, T>,
+        peers_connected: &HashSet<PeerId>,
+        now: Instant,
+    ) {
+        // forget the peers that have been disconnected for too long
+        let retention = self.retention;
+        self.peers.retain(|_, (disconnected_at, _)| {
+            now.saturating_duration_since(*disconnected_at) < retention
+        });
+
+        // keep the knowledge of the peers that just disconnected
+        let disconnected: Vec<PeerId> = known_by_peer
+            .keys()
+            .filter(|peer_id| !peers_connected.contains(peer_id))
+            .copied()
+            .collect();
+        for peer_id in disconnected {
+            if let Some(knowledge) = known_by_peer.remove(&peer_id) {
+                if !retention.is_zero() {
+                    self.peers.insert(peer_id, (now, knowledge));
+                }
+            }
+        }
+
+        // restore the knowledge of the peers that reconnected
+        for peer_id in peers_connected {
+            if known_by_peer.contains_key(peer_id) {
+                continue;
+            }
+            if let Some((_, knowledge)) = self.peers.remove(peer_id) {
+                known_by_peer.insert(*peer_id, knowledge);
+            }
+        }
+    }
+
+    /// Approximate memory used by the retained knowledge in bytes,
+    /// `heap_usage` giving the memory allocated by the knowledge of a peer
+    pub(crate) fn memory_usage(&self, heap_usage: impl Fn(&T) -> usize) -> usize {
+        self.peers.capacity() * std::mem::size_of::<(PeerId, (Instant, T))>()
+            + self
+                .peers
+                .values()
+                .map(|(_, knowledge)| heap_usage(knowledge))
+                .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_signature::KeyPair;
+
+    use super::*;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_reconnect_within_retention() {
+        let mut retained = RetainedPeerKnowledge::new(Duration::from_secs(10));
+        let (peer_a, peer_b) = (peer_id(), peer_id());
+        let mut known_by_peer: HashMap<PeerId, Vec<u64>> =
+            [(peer_a, vec![1, 2]), (peer_b, vec![3])]
+                .into_iter()
+                .collect();
+        let start = Instant::now();
+
+        // both peers disconnect
+        retained.update(&mut known_by_peer, &HashSet::new(), start);
+        assert!(known_by_peer.is_empty());
+
+        // peer A reconnects within the retention and gets its knowledge back
+        let connected: HashSet<PeerId> = [peer_a].into_iter().collect();
+        retained.update(
+            &mut known_by_peer,
+            &connected,
+            start + Duration::from_secs(5),
+        );
+        assert_eq!(known_by_peer.get(&peer_a), Some(&vec![1, 2]));
+
+        // peer B reconnects too late and starts from scratch
+        let connected: HashSet<PeerId> = [peer_a, peer_b].into_iter().collect();
+        retained.update(
+            &mut known_by_peer,
+            &connected,
+            start + Duration::from_secs(11),
+        );
+        assert_eq!(known_by_peer.get(&peer_a), Some(&vec![1, 2]));
+        assert_eq!(known_by_peer.get(&peer_b), None);
+    }
+
+    #[test]
+    fn test_no_retention() {
+        let mut retained = RetainedPeerKnowledge::new(Duration::ZERO);
+        let peer = peer_id();
+        let mut known_by_peer: HashMap<PeerId, Vec<u64>> = [(peer, vec![1])].into_iter().collect();
+        let now = Instant::now();
+
+        retained.update(&mut known_by_peer, &HashSet::new(), now);
+        let connected: HashSet<PeerId> = [peer].into_iter().collect();
+        retained.update(&mut known_by_peer, &connected, now);
+        assert!(known_by_peer.is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+//! Per-peer token buckets limiting the number of items of a given type received from each peer,
+//! so that a single peer flooding us cannot saturate the processing of the following messages.
+
+use std::{collections::HashMap, time::Instant};
+
+use massa_protocol_exports::{PeerId, PeerRateLimit};
+
+/// Outcome of the rate limiting of a received message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RateLimitOutcome {
+    /// the message can be processed
+    Allowed,
+    /// the message must be dropped
+    Throttled,
+    /// the message must be dropped and the peer banned
+    Ban,
+}
+
+/// Token bucket of a peer
+struct PeerBucket {
+    /// available tokens, one per item
+    tokens: f64,
+    /// last time the bucket was refilled
+    last_refill: Instant,
+    /// number of messages dropped since the bucket was last full
+    throttled_messages: u64,
+}
+
+/// Token buckets of the connected peers for a given item type
+pub(crate) struct PeerRateLimiter {
+    limit: PeerRateLimit,
+    buckets: HashMap<PeerId, PeerBucket>,
+}
+
+impl PeerRateLimiter {
+    pub(crate) fn new(limit: PeerRateLimit) -> Self {
+        PeerRateLimiter {
+            limit,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Consumes the tokens of `count` items received from `peer_id` at `now`.
+    /// A message with more items than the burst capacity is allowed when the bucket is full.
+    pub(crate) fn consume(
+        &mut self,
+        peer_id: &PeerId,
+        count: usize,
+        now: Instant,
+    ) -> RateLimitOutcome {
+        if self.limit.items_per_second == 0 {
+            return RateLimitOutcome::Allowed;
+        }
+        let burst = self.limit.burst as f64;
+        let bucket = self.buckets.entry(*peer_id).or_insert(PeerBucket {
+            tokens: burst,
+            last_refill: now,
+            throttled_messages: 0,
+        });
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.limit.items_per_second as f64).min(burst);
+        bucket.last_refill = now;
+        if bucket.tokens >= burst {
+            bucket.throttled_messages = 0;
+        }
+        let cost = (count as f64).min(burst);
+        if bucket.tokens >= cost {
+            bucket.tokens -= cost;
+            return RateLimitOutcome::Allowed;
+        }
+        bucket.throttled_messages += 1;
+        if bucket.throttled_messages > self.limit.max_throttled_messages {
+            self.buckets.remove(peer_id);
+            return RateLimitOutcome::Ban;
+        }
+        RateLimitOutcome::Throttled
+    }
+
+    /// Forgets the buckets that are full again at `now`: they are in the same state as new ones.
+    /// This also forgets the peers that are not connected anymore.
+    pub(crate) fn prune(&mut self, now: Instant) {
+        let burst = self.limit.burst as f64;
+        let items_per_second = self.limit.items_per_second as f64;
+        self.buckets.retain(|_, bucket| {
+            let elapsed = now.saturating_duration_since(bucket.last_refill);
+            bucket.tokens + elapsed.as_secs_f64() * items_per_second < burst
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use massa_signature::KeyPair;
+
+    use super::*;
+
+    fn limiter() -> PeerRateLimiter {
+        PeerRateLimiter::new(PeerRateLimit {
+            items_per_second: 10,
+            burst: 20,
+            max_throttled_messages: 2,
+        })
+    }
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_rate_limiter_refills_over_time() {
+        let mut limiter = limiter();
+        let peer_id = peer_id();
+        let start = Instant::now();
+        assert_eq!(
+            limiter.consume(&peer_id, 20, start),
+            RateLimitOutcome::Allowed
+        );
+        assert_eq!(
+            limiter.consume(&peer_id, 5, start),
+            RateLimitOutcome::Throttled
+        );
+        // half a second refills 5 items
+        let later = start + Duration::from_millis(500);
+        assert_eq!(
+            limiter.consume(&peer_id, 5, later),
+            RateLimitOutcome::Allowed
+        );
+        assert_eq!(
+            limiter.consume(&peer_id, 1, later),
+            RateLimitOutcome::Throttled
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter_bans_flooding_peer() {
+        let mut limiter = limiter();
+        let peer_id = peer_id();
+        let other_peer_id = self::peer_id();
+        let now = Instant::now();
+        assert_eq!(
+            limiter.consume(&peer_id, 20, now),
+            RateLimitOutcome::Allowed
+        );
+        assert_eq!(
+            limiter.consume(&peer_id, 1, now),
+            RateLimitOutcome::Throttled
+        );
+        assert_eq!(
+            limiter.consume(&peer_id, 1, now),
+            RateLimitOutcome::Throttled
+        );
+        assert_eq!(limiter.consume(&peer_id, 1, now), RateLimitOutcome::Ban);
+        // the other peers have their own bucket
+        assert_eq!(
+            limiter.consume(&other_peer_id, 20, now),
+            RateLimitOutcome::Allowed
+        );
+        // the buckets are forgotten once full again
+        limiter.prune(now + Duration::from_secs(2));
+        assert!(limiter.buckets.is_empty());
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled() {
+        let mut limiter = PeerRateLimiter::new(PeerRateLimit {
+            items_per_second: 0,
+            burst: 0,
+            max_throttled_messages: 0,
+        });
+        let peer_id = peer_id();
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert_eq!(
+                limiter.consume(&peer_id, 1000, now),
+                RateLimitOutcome::Allowed
+            );
+        }
+    }
+}
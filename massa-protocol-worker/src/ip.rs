@@ -1,4 +1,10 @@
-use std::net::IpAddr;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+};
+
+use massa_protocol_exports::ProtocolConfig;
+use peernet::transports::TransportType;
 
 // TODO: Use std one when stable
 pub(crate) fn to_canonical(ip: IpAddr) -> IpAddr {
@@ -12,3 +18,81 @@ pub(crate) fn to_canonical(ip: IpAddr) -> IpAddr {
         }
     }
 }
+
+/// Addresses at which the other nodes can reach us, in canonical form and without duplicates.
+/// Dual-stack nodes have one of each family.
+pub(crate) fn routable_ips(config: &ProtocolConfig) -> Vec<IpAddr> {
+    let mut ips: Vec<IpAddr> = Vec::with_capacity(2);
+    let candidates = config
+        .routable_ip
+        .into_iter()
+        .chain(config.routable_ipv6.map(IpAddr::V6));
+    for ip in candidates.map(to_canonical) {
+        if !ips.contains(&ip) {
+            ips.push(ip);
+        }
+    }
+    ips
+}
+
+/// Listeners among `listeners` whose port is announced to the other nodes at our routable ips.
+/// Those listening on a private network only can be left out in the configuration.
+pub(crate) fn advertised_listeners(
+    listeners: &HashMap<SocketAddr, TransportType>,
+    config: &ProtocolConfig,
+) -> HashMap<SocketAddr, TransportType> {
+    listeners
+        .iter()
+        .filter(|(addr, _)| {
+            config
+                .advertised_listeners
+                .as_ref()
+                .map_or(true, |advertised| advertised.contains(addr))
+        })
+        .map(|(addr, transport)| (*addr, *transport))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        net::{IpAddr, Ipv6Addr, SocketAddr},
+    };
+
+    use massa_protocol_exports::ProtocolConfig;
+    use peernet::transports::TransportType;
+
+    use super::{advertised_listeners, routable_ips};
+
+    #[test]
+    fn test_routable_ips() {
+        let mut config = ProtocolConfig::default();
+        assert!(routable_ips(&config).is_empty());
+
+        let v4: IpAddr = "203.0.113.7".parse().unwrap();
+        let v6: Ipv6Addr = "2001:db8::7".parse().unwrap();
+        config.routable_ip = Some(v4);
+        config.routable_ipv6 = Some(v6);
+        assert_eq!(routable_ips(&config), vec![v4, IpAddr::V6(v6)]);
+
+        // an IPv4-mapped address is the same as the IPv4 one
+        config.routable_ipv6 = Some("::ffff:203.0.113.7".parse().unwrap());
+        assert_eq!(routable_ips(&config), vec![v4]);
+    }
+
+    #[test]
+    fn test_advertised_listeners() {
+        let public: SocketAddr = "0.0.0.0:31244".parse().unwrap();
+        let lan: SocketAddr = "192.168.1.10:31254".parse().unwrap();
+        let listeners = HashMap::from([(public, TransportType::Tcp), (lan, TransportType::Tcp)]);
+        let mut config = ProtocolConfig::default();
+        assert_eq!(advertised_listeners(&listeners, &config), listeners);
+
+        config.advertised_listeners = Some(HashSet::from([public]));
+        assert_eq!(
+            advertised_listeners(&listeners, &config),
+            HashMap::from([(public, TransportType::Tcp)])
+        );
+    }
+}
@@ -1,11 +1,19 @@
 mod connectivity;
 mod context;
+mod contributions;
 mod controller;
+mod dialer;
 mod handlers;
 mod ip;
 mod manager;
 mod messages;
+mod nat;
+mod net_split;
+mod outbound;
+mod relay;
 mod sig_verifier;
+mod traffic;
+mod transport;
 mod worker;
 mod wrap_network;
 mod wrap_peer_db;
@@ -4,17 +4,22 @@ use massa_channel::sender::MassaSender;
 use massa_protocol_exports::ProtocolManager;
 use tracing::info;
 
-use crate::connectivity::ConnectivityCommand;
+use crate::{connectivity::ConnectivityCommand, nat::NatTraversal};
 
 /// protocol manager used to stop the protocol
 pub struct ProtocolManagerImpl {
     connectivity_thread: Option<(MassaSender<ConnectivityCommand>, JoinHandle<()>)>,
+    nat_traversal: Option<NatTraversal>,
 }
 
 impl ProtocolManagerImpl {
-    pub fn new(connectivity_thread: (MassaSender<ConnectivityCommand>, JoinHandle<()>)) -> Self {
+    pub(crate) fn new(
+        connectivity_thread: (MassaSender<ConnectivityCommand>, JoinHandle<()>),
+        nat_traversal: Option<NatTraversal>,
+    ) -> Self {
         Self {
             connectivity_thread: Some(connectivity_thread),
+            nat_traversal,
         }
     }
 }
@@ -31,5 +36,8 @@ impl ProtocolManager for ProtocolManagerImpl {
                 .join()
                 .expect("connectivity thread panicked on try to join");
         }
+        if let Some(mut nat_traversal) = self.nat_traversal.take() {
+            nat_traversal.stop();
+        }
     }
 }
@@ -1,5 +1,5 @@
 use massa_channel::sender::MassaSender;
-use massa_protocol_exports::PeerId;
+use massa_protocol_exports::{PeerFeatures, PeerId};
 use massa_serialization::{
     DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
@@ -12,15 +12,36 @@ use peernet::{
 };
 use tracing::debug;
 
-use crate::handlers::{
-    block_handler::{BlockMessage, BlockMessageSerializer},
-    endorsement_handler::{EndorsementMessage, EndorsementMessageSerializer},
-    operation_handler::{OperationMessage, OperationMessageSerializer},
-    peer_handler::{
-        models::PeerMessageTuple, PeerManagementMessage, PeerManagementMessageSerializer,
+use crate::{
+    handlers::{
+        block_handler::{
+            messages::MessageTypeId as BlockMessageTypeId, BlockMessage, BlockMessageSerializer,
+        },
+        endorsement_handler::{
+            messages::MessageTypeId as EndorsementMessageTypeId, EndorsementMessage,
+            EndorsementMessageSerializer,
+        },
+        operation_handler::{
+            messages::MessageTypeId as OperationMessageTypeId, OperationMessage,
+            OperationMessageSerializer,
+        },
+        peer_handler::{
+            messages::MessageTypeId as PeerManagementMessageTypeId, models::PeerMessageTuple,
+            PeerManagementMessage, PeerManagementMessageSerializer,
+        },
     },
+    traffic::SharedTrafficStats,
 };
 
+/// Id announcing a message wrapped in an envelope, never used as a message type id.
+/// The envelope is `varint(ENVELOPE_ID) || varint(message version) || message`:
+/// a message of a type unknown to this node is skipped if it was sent with a newer message version.
+pub(crate) const ENVELOPE_ID: u64 = 127;
+
+/// Version of the message set carried in the envelope.
+/// It follows the version of the feature bit set, increased each time new messages are defined.
+pub(crate) const MESSAGE_VERSION: u64 = PeerFeatures::VERSION as u64;
+
 #[derive(Debug)]
 pub enum Message {
     Block(Box<BlockMessage>),
@@ -49,6 +70,46 @@ impl From<&Message> for MessageTypeId {
     }
 }
 
+/// Message type id and sub-type id of a message
+pub(crate) fn message_ids(message: &Message) -> (u64, Option<u64>) {
+    let sub_id = match message {
+        Message::Block(message) => BlockMessageTypeId::from(message.as_ref()).into(),
+        Message::Endorsement(message) => EndorsementMessageTypeId::from(message).into(),
+        Message::Operation(message) => OperationMessageTypeId::from(message).into(),
+        Message::PeerManagement(message) => {
+            PeerManagementMessageTypeId::from(message.as_ref()).into()
+        }
+    };
+    (MessageTypeId::from(message).into(), Some(sub_id))
+}
+
+/// Name of a message type and sub-type, such as `Block/Header`
+pub(crate) fn message_type_name(id: u64, sub_id: Option<u64>) -> String {
+    let Ok(message_type) = MessageTypeId::try_from(id) else {
+        return format!("Unknown({})", id);
+    };
+    let Some(sub_id) = sub_id else {
+        return format!("{:?}/Unknown", message_type);
+    };
+    let sub_type = match message_type {
+        MessageTypeId::Block => BlockMessageTypeId::try_from(sub_id).map(|t| format!("{:?}", t)),
+        MessageTypeId::Endorsement => {
+            EndorsementMessageTypeId::try_from(sub_id).map(|t| format!("{:?}", t))
+        }
+        MessageTypeId::Operation => {
+            OperationMessageTypeId::try_from(sub_id).map(|t| format!("{:?}", t))
+        }
+        MessageTypeId::PeerManagement => {
+            PeerManagementMessageTypeId::try_from(sub_id).map(|t| format!("{:?}", t))
+        }
+    };
+    format!(
+        "{:?}/{}",
+        message_type,
+        sub_type.unwrap_or_else(|_| format!("Unknown({})", sub_id))
+    )
+}
+
 //TODO: Macroize this
 impl From<BlockMessage> for Message {
     fn from(message: BlockMessage) -> Self {
@@ -209,6 +270,35 @@ impl PeerNetMessagesSerializer<Message> for MessagesSerializer {
     }
 }
 
+/// Serializes the messages wrapped in an envelope, for the peers that announced they decode it
+pub(crate) struct EnvelopeSerializer<'a> {
+    message_serializer: &'a MessagesSerializer,
+}
+
+impl<'a> EnvelopeSerializer<'a> {
+    pub(crate) fn new(message_serializer: &'a MessagesSerializer) -> Self {
+        Self { message_serializer }
+    }
+}
+
+impl PeerNetMessagesSerializer<Message> for EnvelopeSerializer<'_> {
+    /// Serialize the message in an envelope
+    fn serialize(&self, message: &Message, buffer: &mut Vec<u8>) -> PeerNetResult<()> {
+        for value in [ENVELOPE_ID, MESSAGE_VERSION] {
+            self.message_serializer
+                .id_serializer
+                .serialize(&value, buffer)
+                .map_err(|err| {
+                    PeerNetError::HandlerError.error(
+                        "EnvelopeSerializer",
+                        Some(format!("Failed to serialize envelope {}", err)),
+                    )
+                })?;
+        }
+        self.message_serializer.serialize(message, buffer)
+    }
+}
+
 #[derive(Clone)]
 pub struct MessagesHandler {
     pub id_deserializer: U64VarIntDeserializer,
@@ -216,10 +306,28 @@ pub struct MessagesHandler {
     pub sender_endorsements: MassaSender<PeerMessageTuple>,
     pub sender_operations: MassaSender<PeerMessageTuple>,
     pub sender_peers: MassaSender<PeerMessageTuple>,
+    /// traffic received, counted per message type and per peer
+    pub traffic: SharedTrafficStats,
+}
+
+impl MessagesHandler {
+    /// Returns true if the sub-message type id at the start of `data` is known for the messages of type `id`
+    fn is_known_sub_message(&self, id: &MessageTypeId, data: &[u8]) -> bool {
+        let Ok((_, sub_id)) = self.id_deserializer.deserialize::<DeserializeError>(data) else {
+            return false;
+        };
+        match id {
+            MessageTypeId::Block => BlockMessageTypeId::try_from(sub_id).is_ok(),
+            MessageTypeId::Endorsement => EndorsementMessageTypeId::try_from(sub_id).is_ok(),
+            MessageTypeId::Operation => OperationMessageTypeId::try_from(sub_id).is_ok(),
+            MessageTypeId::PeerManagement => PeerManagementMessageTypeId::try_from(sub_id).is_ok(),
+        }
+    }
 }
 
 impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
     fn handle(&self, data: &[u8], peer_id: &PeerId) -> PeerNetResult<()> {
+        let size = data.len();
         let (data, raw_id) = self
             .id_deserializer
             .deserialize::<DeserializeError>(data)
@@ -229,7 +337,49 @@ impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
                     Some(format!("Failed to deserialize message type id: {}", err)),
                 )
             })?;
-        let id = MessageTypeId::try_from(raw_id).map_err(|_| {
+        // a message in an envelope comes with the message version of its sender
+        let (data, raw_id, version) = if raw_id == ENVELOPE_ID {
+            let envelope_error = |err: nom::Err<DeserializeError>| {
+                PeerNetError::HandlerError.error(
+                    "MessagesHandler",
+                    Some(format!("Failed to deserialize message envelope: {}", err)),
+                )
+            };
+            let (data, version) = self
+                .id_deserializer
+                .deserialize::<DeserializeError>(data)
+                .map_err(envelope_error)?;
+            let (data, raw_id) = self
+                .id_deserializer
+                .deserialize::<DeserializeError>(data)
+                .map_err(envelope_error)?;
+            (data, raw_id, Some(version))
+        } else {
+            (data, raw_id, None)
+        };
+        let sub_id = self
+            .id_deserializer
+            .deserialize::<DeserializeError>(data)
+            .ok()
+            .map(|(_, sub_id)| sub_id);
+        self.traffic
+            .lock()
+            .note_received(peer_id, (raw_id, sub_id), size);
+        let id = MessageTypeId::try_from(raw_id).ok();
+        // the newer message types are skipped rather than failing the connection
+        if let Some(version) = version.filter(|version| *version > MESSAGE_VERSION) {
+            if !id
+                .as_ref()
+                .is_some_and(|id| self.is_known_sub_message(id, data))
+            {
+                debug!(
+                    "Skipping message of type {} from {}, unknown to this node but sent with the newer message version {}",
+                    raw_id, peer_id, version
+                );
+                return Ok(());
+            }
+        }
+        let id = id.ok_or_else(|| {
             PeerNetError::HandlerError.error(
                 "MessagesHandler",
                 Some(String::from("Invalid message type id")),
@@ -272,3 +422,75 @@ impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound::Included;
+
+    use massa_channel::MassaChannel;
+    use massa_signature::KeyPair;
+
+    use super::*;
+
+    fn varints(values: &[u64]) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        for value in values {
+            U64VarIntSerializer::new()
+                .serialize(value, &mut buffer)
+                .unwrap();
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_envelope_skips_newer_message_types() {
+        let (sender_blocks, _receiver_blocks) = MassaChannel::new("test_blocks".to_string(), None);
+        let (sender_endorsements, _receiver_endorsements) =
+            MassaChannel::new("test_endorsements".to_string(), None);
+        let (sender_operations, _receiver_operations) =
+            MassaChannel::new("test_operations".to_string(), None);
+        let (sender_peers, receiver_peers) = MassaChannel::new("test_peers".to_string(), None);
+        let handler = MessagesHandler {
+            id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            sender_blocks,
+            sender_endorsements,
+            sender_operations,
+            sender_peers,
+            traffic: Default::default(),
+        };
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let serializer = MessagesSerializer::new()
+            .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
+
+        // a known message is forwarded to its handler without its envelope
+        let mut buffer = Vec::new();
+        EnvelopeSerializer::new(&serializer)
+            .serialize(&PeerManagementMessage::Pong(42).into(), &mut buffer)
+            .unwrap();
+        handler.handle(&buffer, &peer_id).unwrap();
+        let mut legacy = Vec::new();
+        serializer
+            .serialize(&PeerManagementMessage::Pong(42).into(), &mut legacy)
+            .unwrap();
+        assert_eq!(
+            receiver_peers.try_recv().unwrap(),
+            (peer_id, legacy[1..].to_vec())
+        );
+
+        // the unknown message types and sub-message types of a newer version are skipped
+        let newer = MESSAGE_VERSION + 1;
+        handler
+            .handle(&varints(&[ENVELOPE_ID, newer, 42, 0]), &peer_id)
+            .unwrap();
+        handler
+            .handle(&varints(&[ENVELOPE_ID, newer, 3, 1000]), &peer_id)
+            .unwrap();
+        assert!(receiver_peers.try_recv().is_err());
+
+        // but not from a version that should know them, nor without envelope
+        assert!(handler
+            .handle(&varints(&[ENVELOPE_ID, MESSAGE_VERSION, 42, 0]), &peer_id)
+            .is_err());
+        assert!(handler.handle(&varints(&[42, 0]), &peer_id).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! NAT traversal with NAT-PMP (RFC 6886).
+//!
+//! Nodes behind a home router cannot accept inbound connections unless the router forwards the listened ports.
+//! At startup, the router is asked to map the TCP ports of our advertised listeners and for its external IP,
+//! which is announced when no routable IP is configured. The mappings are renewed at half their lifetime,
+//! and removed when the protocol stops.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use crossbeam::channel::{bounded, RecvTimeoutError, Sender};
+use massa_protocol_exports::{ProtocolConfig, ProtocolError};
+use peernet::transports::TransportType;
+use tracing::{debug, info, warn};
+
+use crate::ip::advertised_listeners;
+
+/// Port on which the router listens for NAT-PMP requests
+const NAT_PMP_PORT: u16 = 5351;
+
+/// NAT-PMP version
+const VERSION: u8 = 0;
+
+/// Opcode of the external address request
+const OPCODE_EXTERNAL_ADDRESS: u8 = 0;
+
+/// Opcode of the TCP port mapping request
+const OPCODE_MAP_TCP: u8 = 2;
+
+/// Added to the request opcode in the response
+const RESPONSE_OPCODE_OFFSET: u8 = 128;
+
+/// Timeout of the first request, doubled at each retry
+const INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Number of attempts before giving up on a request
+const MAX_ATTEMPTS: u32 = 4;
+
+const THREAD_NAME: &str = "protocol-nat";
+
+fn nat_error(message: impl Into<String>) -> ProtocolError {
+    ProtocolError::NatTraversalError(message.into())
+}
+
+/// Decodes the result code of a response to the request of opcode `opcode`
+fn check_response(response: &[u8], opcode: u8, len: usize) -> Result<(), ProtocolError> {
+    if response.len() < len {
+        return Err(nat_error(format!(
+            "response too short: {} bytes",
+            response.len()
+        )));
+    }
+    if response[0] != VERSION || response[1] != opcode + RESPONSE_OPCODE_OFFSET {
+        return Err(nat_error(format!(
+            "unexpected response version {} opcode {}",
+            response[0], response[1]
+        )));
+    }
+    let result_code = u16::from_be_bytes([response[2], response[3]]);
+    match result_code {
+        0 => Ok(()),
+        1 => Err(nat_error("unsupported version")),
+        2 => Err(nat_error("not authorized or refused by the router")),
+        3 => Err(nat_error("network failure on the router")),
+        4 => Err(nat_error("out of resources on the router")),
+        5 => Err(nat_error("unsupported opcode")),
+        code => Err(nat_error(format!("unknown result code {}", code))),
+    }
+}
+
+fn external_address_request() -> [u8; 2] {
+    [VERSION, OPCODE_EXTERNAL_ADDRESS]
+}
+
+fn parse_external_address_response(response: &[u8]) -> Result<Ipv4Addr, ProtocolError> {
+    check_response(response, OPCODE_EXTERNAL_ADDRESS, 12)?;
+    Ok(Ipv4Addr::new(
+        response[8],
+        response[9],
+        response[10],
+        response[11],
+    ))
+}
+
+/// Request of a TCP mapping of `port` to the same external port, a lifetime of 0 removing the mapping
+fn map_tcp_request(port: u16, lifetime: u32) -> [u8; 12] {
+    let mut request = [0u8; 12];
+    request[0] = VERSION;
+    request[1] = OPCODE_MAP_TCP;
+    request[4..6].copy_from_slice(&port.to_be_bytes());
+    let external_port = if lifetime == 0 { 0 } else { port };
+    request[6..8].copy_from_slice(&external_port.to_be_bytes());
+    request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+    request
+}
+
+/// Returns the external port and the lifetime granted by the router
+fn parse_map_tcp_response(response: &[u8], port: u16) -> Result<(u16, u32), ProtocolError> {
+    check_response(response, OPCODE_MAP_TCP, 16)?;
+    let internal_port = u16::from_be_bytes([response[8], response[9]]);
+    if internal_port != port {
+        return Err(nat_error(format!(
+            "mapping of port {} answered for port {}",
+            port, internal_port
+        )));
+    }
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    let lifetime = u32::from_be_bytes([response[12], response[13], response[14], response[15]]);
+    Ok((external_port, lifetime))
+}
+
+/// Default IPv4 gateway, read from the kernel routing table
+fn default_gateway() -> Result<Ipv4Addr, ProtocolError> {
+    let routes = std::fs::read_to_string("/proc/net/route")
+        .map_err(|err| nat_error(format!("could not read the routing table: {}", err)))?;
+    parse_default_gateway(&routes).ok_or_else(|| nat_error("no default gateway found"))
+}
+
+/// Gateway of the default route of a `/proc/net/route` table
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 3 || fields[1] != "00000000" {
+            return None;
+        }
+        // addresses are written as the hexadecimal value of their bytes read in host byte order
+        let gateway = u32::from_str_radix(fields[2], 16).ok()?;
+        (gateway != 0).then(|| Ipv4Addr::from(gateway.to_ne_bytes()))
+    })
+}
+
+/// Ports of the TCP listeners that accept IPv4 connections, the only ones NAT-PMP can map
+fn mappable_ports(listeners: &HashMap<SocketAddr, TransportType>) -> BTreeSet<u16> {
+    listeners
+        .iter()
+        .filter(|(addr, transport)| {
+            **transport == TransportType::Tcp && (addr.is_ipv4() || addr.ip().is_unspecified())
+        })
+        .map(|(addr, _)| addr.port())
+        .collect()
+}
+
+/// NAT-PMP client of a router
+struct NatPmpClient {
+    socket: UdpSocket,
+}
+
+impl NatPmpClient {
+    fn new(gateway: Ipv4Addr) -> Result<Self, ProtocolError> {
+        let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.connect(SocketAddrV4::new(gateway, NAT_PMP_PORT))?;
+        Ok(NatPmpClient { socket })
+    }
+
+    /// Sends a request until a response is received, doubling the timeout at each attempt
+    fn request(&self, request: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        let mut buffer = [0u8; 16];
+        let mut timeout = INITIAL_TIMEOUT;
+        for _ in 0..MAX_ATTEMPTS {
+            self.socket.send(request)?;
+            self.socket.set_read_timeout(Some(timeout))?;
+            match self.socket.recv(&mut buffer) {
+                Ok(len) => return Ok(buffer[..len].to_vec()),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    timeout *= 2;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Err(nat_error("no response from the router"))
+    }
+
+    fn external_address(&self) -> Result<Ipv4Addr, ProtocolError> {
+        parse_external_address_response(&self.request(&external_address_request())?)
+    }
+
+    /// Maps `port` to the same external port, returning the lifetime granted by the router
+    fn map_tcp(&self, port: u16, lifetime: u32) -> Result<u32, ProtocolError> {
+        let (external_port, granted) =
+            parse_map_tcp_response(&self.request(&map_tcp_request(port, lifetime))?, port)?;
+        if lifetime != 0 && external_port != port {
+            // the router chose another port: it could not be announced, release it
+            let _ = self.request(&map_tcp_request(port, 0));
+            return Err(nat_error(format!(
+                "port {} is mapped to external port {} by the router",
+                port, external_port
+            )));
+        }
+        Ok(granted)
+    }
+}
+
+/// Port mappings kept by the router for our listeners
+pub(crate) struct NatTraversal {
+    external_ip: Ipv4Addr,
+    renewal_thread: Option<(Sender<()>, JoinHandle<()>)>,
+}
+
+impl NatTraversal {
+    /// Maps the TCP ports of our listeners on the router and gets its external IP.
+    /// Fails if no port could be mapped.
+    pub(crate) fn start(config: &ProtocolConfig) -> Result<Self, ProtocolError> {
+        let gateway = match config.nat_gateway {
+            Some(gateway) => gateway,
+            None => default_gateway()?,
+        };
+        let client = NatPmpClient::new(gateway)?;
+        let external_ip = client.external_address()?;
+        let lifetime = u32::try_from(config.nat_mapping_lifetime.as_millis() / 1000)
+            .unwrap_or(u32::MAX)
+            .max(1);
+        let ports = mappable_ports(&advertised_listeners(&config.listeners, config));
+        let mut mapped = Vec::with_capacity(ports.len());
+        let mut renewal = Duration::from_secs(u64::from(lifetime));
+        for port in ports {
+            match client.map_tcp(port, lifetime) {
+                Ok(granted) => {
+                    info!(
+                        "NAT-PMP: port {} mapped on router {} for {}s",
+                        port, gateway, granted
+                    );
+                    renewal = renewal.min(Duration::from_secs(u64::from(granted)));
+                    mapped.push(port);
+                }
+                Err(err) => warn!("NAT-PMP: could not map port {}: {}", port, err),
+            }
+        }
+        if mapped.is_empty() {
+            return Err(nat_error("no port could be mapped"));
+        }
+        info!("NAT-PMP: external IP of the router is {}", external_ip);
+
+        let (stop_tx, stop_rx) = bounded::<()>(1);
+        let renewal_interval = (renewal / 2).max(Duration::from_secs(1));
+        let join_handle = std::thread::Builder::new()
+            .name(THREAD_NAME.to_string())
+            .spawn(move || {
+                loop {
+                    match stop_rx.recv_timeout(renewal_interval) {
+                        Err(RecvTimeoutError::Timeout) => {
+                            for port in &mapped {
+                                if let Err(err) = client.map_tcp(*port, lifetime) {
+                                    warn!(
+                                        "NAT-PMP: could not renew the mapping of port {}: {}",
+                                        port, err
+                                    );
+                                }
+                            }
+                        }
+                        Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                for port in &mapped {
+                    match client.map_tcp(*port, 0) {
+                        Ok(_) => debug!("NAT-PMP: mapping of port {} removed", port),
+                        Err(err) => {
+                            debug!(
+                                "NAT-PMP: could not remove the mapping of port {}: {}",
+                                port, err
+                            )
+                        }
+                    }
+                }
+            })
+            .expect("OS failed to start NAT traversal thread");
+
+        Ok(NatTraversal {
+            external_ip,
+            renewal_thread: Some((stop_tx, join_handle)),
+        })
+    }
+
+    /// External IP of the router
+    pub(crate) fn external_ip(&self) -> Ipv4Addr {
+        self.external_ip
+    }
+
+    /// Stops renewing the mappings and removes them
+    pub(crate) fn stop(&mut self) {
+        if let Some((stop_tx, join_handle)) = self.renewal_thread.take() {
+            let _ = stop_tx.send(());
+            join_handle
+                .join()
+                .expect("NAT traversal thread panicked on try to join");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::{
+        external_address_request, map_tcp_request, parse_default_gateway,
+        parse_external_address_response, parse_map_tcp_response,
+    };
+
+    #[test]
+    fn test_nat_pmp_messages() {
+        assert_eq!(external_address_request(), [0, 0]);
+        let response = [0, 128, 0, 0, 0, 0, 0, 42, 203, 0, 113, 7];
+        assert_eq!(
+            parse_external_address_response(&response).unwrap(),
+            Ipv4Addr::new(203, 0, 113, 7)
+        );
+        // a refused request is an error
+        let refused = [0, 128, 0, 2, 0, 0, 0, 42, 0, 0, 0, 0];
+        assert!(parse_external_address_response(&refused).is_err());
+
+        assert_eq!(
+            map_tcp_request(31244, 7200),
+            [0, 2, 0, 0, 0x7a, 0x0c, 0x7a, 0x0c, 0, 0, 0x1c, 0x20]
+        );
+        // removing a mapping asks for the external port 0 and a lifetime of 0
+        assert_eq!(
+            map_tcp_request(31244, 0),
+            [0, 2, 0, 0, 0x7a, 0x0c, 0, 0, 0, 0, 0, 0]
+        );
+        let response = [
+            0, 130, 0, 0, 0, 0, 0, 42, 0x7a, 0x0c, 0x7a, 0x0d, 0, 0, 0x0e, 0x10,
+        ];
+        assert_eq!(
+            parse_map_tcp_response(&response, 31244).unwrap(),
+            (31245, 3600)
+        );
+        assert!(parse_map_tcp_response(&response, 31245).is_err());
+    }
+
+    #[test]
+    fn test_parse_default_gateway() {
+        let routes =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+            eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+            eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+        assert_eq!(
+            parse_default_gateway(routes),
+            Some(Ipv4Addr::new(192, 168, 1, 1))
+        );
+        assert_eq!(parse_default_gateway("Iface\tDestination\tGateway\n"), None);
+    }
+}
@@ -0,0 +1,206 @@
+//! Detection of an eclipse or of a network split.
+//!
+//! A node cut from most of the network keeps finalizing the blocks of the fork it sees.
+//! When the blocks finalized recently were first sent by a handful of peers, or by peers from a handful of subnets,
+//! the node is likely eclipsed or on the minority side of a split, and its final state should not be trusted.
+//! The blocks produced by the node itself have no origin peer, so an isolated staking node is detected as well.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::{IpSubnet, NetSplitAlert, PeerId, ProtocolConfig};
+
+use crate::ip::to_canonical;
+
+/// Length of the IPv4 prefix shared by the peers counted in the same subnet
+const IPV4_SUBNET_PREFIX: u8 = 16;
+/// Length of the IPv6 prefix shared by the peers counted in the same subnet
+const IPV6_SUBNET_PREFIX: u8 = 32;
+
+/// Subnet counted for the diversity of the peers
+pub(crate) fn subnet(ip: IpAddr) -> IpSubnet {
+    let ip = to_canonical(ip);
+    let prefix_length = match ip {
+        IpAddr::V4(_) => IPV4_SUBNET_PREFIX,
+        IpAddr::V6(_) => IPV6_SUBNET_PREFIX,
+    };
+    IpSubnet::new(ip, prefix_length).unwrap_or_else(|_| IpSubnet::from_address(ip))
+}
+
+/// Block finalized within the detection window
+struct FinalBlockOrigin {
+    finalized_at: Instant,
+    /// peer that first sent us the block, with its address at finalization, None if we produced it
+    origin: Option<(PeerId, Option<IpAddr>)>,
+}
+
+pub(crate) struct NetSplitDetector {
+    window: Duration,
+    min_final_blocks: usize,
+    min_origin_peers: usize,
+    min_subnets: usize,
+    /// blocks finalized within the window, the oldest first
+    final_blocks: VecDeque<FinalBlockOrigin>,
+    /// alert raised at the last update, if any
+    alert: Option<NetSplitAlert>,
+}
+
+impl NetSplitDetector {
+    pub(crate) fn new(config: &ProtocolConfig) -> Self {
+        NetSplitDetector {
+            window: config.net_split_window.to_duration(),
+            min_final_blocks: config.net_split_min_final_blocks,
+            min_origin_peers: config.net_split_min_origin_peers,
+            min_subnets: config.net_split_min_subnets,
+            final_blocks: VecDeque::new(),
+            alert: None,
+        }
+    }
+
+    /// Notes a block that became final, first sent by `origin` if we did not produce it
+    pub(crate) fn note_final_block(
+        &mut self,
+        origin: Option<(PeerId, Option<IpAddr>)>,
+        now: Instant,
+    ) {
+        if self.min_final_blocks == 0 {
+            return;
+        }
+        self.final_blocks.push_back(FinalBlockOrigin {
+            finalized_at: now,
+            origin,
+        });
+    }
+
+    /// Checks the origin of the blocks finalized within the window against the addresses of the connected peers.
+    /// Returns the alert when it is newly raised.
+    pub(crate) fn update(
+        &mut self,
+        connected: impl IntoIterator<Item = IpAddr>,
+        now: Instant,
+    ) -> Option<NetSplitAlert> {
+        while let Some(block) = self.final_blocks.front() {
+            if now.saturating_duration_since(block.finalized_at) <= self.window {
+                break;
+            }
+            self.final_blocks.pop_front();
+        }
+        if self.min_final_blocks == 0 || self.final_blocks.len() < self.min_final_blocks {
+            self.alert = None;
+            return None;
+        }
+
+        let mut connected_peers = 0;
+        let mut connected_subnets = HashSet::new();
+        for ip in connected {
+            connected_peers += 1;
+            connected_subnets.insert(subnet(ip));
+        }
+        let mut origins = HashSet::new();
+        let mut origin_subnets = HashSet::new();
+        for (peer_id, ip) in self.final_blocks.iter().filter_map(|block| block.origin) {
+            origins.insert(peer_id);
+            if let Some(ip) = ip {
+                origin_subnets.insert(subnet(ip));
+            }
+        }
+        let collapsed = origins.len() < self.min_origin_peers
+            || origin_subnets.len() < self.min_subnets
+            || connected_subnets.len() < self.min_subnets;
+        if !collapsed {
+            self.alert = None;
+            return None;
+        }
+
+        let raised = self.alert.is_none();
+        let alert = NetSplitAlert {
+            connected_peers,
+            connected_subnets: connected_subnets.len(),
+            final_blocks: self.final_blocks.len(),
+            final_block_origins: origins.len(),
+            final_block_origin_subnets: origin_subnets.len(),
+        };
+        self.alert = Some(alert.clone());
+        raised.then_some(alert)
+    }
+
+    /// Alert raised at the last update, if any
+    pub(crate) fn alert(&self) -> Option<&NetSplitAlert> {
+        self.alert.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::IpAddr,
+        time::{Duration, Instant},
+    };
+
+    use massa_protocol_exports::{PeerId, ProtocolConfig};
+    use massa_signature::KeyPair;
+    use massa_time::MassaTime;
+
+    use super::NetSplitDetector;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn test_net_split_detection() {
+        let mut detector = NetSplitDetector::new(&ProtocolConfig {
+            net_split_window: MassaTime::from_millis(60_000),
+            net_split_min_final_blocks: 4,
+            net_split_min_origin_peers: 2,
+            net_split_min_subnets: 2,
+            ..Default::default()
+        });
+        let connected = [ip("198.51.100.1"), ip("203.0.113.1"), ip("2001:db8::1")];
+        let (peer_1, peer_2) = (peer_id(), peer_id());
+        let now = Instant::now();
+
+        // all the blocks come from the same peer, but not enough were finalized yet
+        for _ in 0..3 {
+            detector.note_final_block(Some((peer_1, Some(ip("198.51.100.1")))), now);
+        }
+        assert_eq!(detector.update(connected, now), None);
+        detector.note_final_block(Some((peer_1, Some(ip("198.51.100.1")))), now);
+        let alert = detector.update(connected, now).unwrap();
+        assert_eq!(alert.final_blocks, 4);
+        assert_eq!(alert.final_block_origins, 1);
+        assert_eq!(alert.connected_peers, 3);
+        assert_eq!(alert.connected_subnets, 3);
+        // the alert is raised once, and kept while the diversity is collapsed
+        assert_eq!(detector.update(connected, now), None);
+        assert!(detector.alert().is_some());
+
+        // two peers of the same /16 subnet do not bring enough diversity
+        detector.note_final_block(Some((peer_2, Some(ip("198.51.7.7")))), now);
+        assert_eq!(detector.update(connected, now), None);
+        assert_eq!(detector.alert().unwrap().final_block_origins, 2);
+        assert_eq!(detector.alert().unwrap().final_block_origin_subnets, 1);
+
+        // a peer from another subnet clears the alert
+        let later = now + Duration::from_secs(30);
+        detector.note_final_block(Some((peer_2, Some(ip("203.0.113.1")))), later);
+        assert_eq!(detector.update(connected, later), None);
+        assert!(detector.alert().is_none());
+
+        // the blocks leave the window: the node now only finalizes its own blocks
+        let much_later = now + Duration::from_secs(120);
+        for _ in 0..4 {
+            detector.note_final_block(None, much_later);
+        }
+        let alert = detector.update(connected, much_later).unwrap();
+        assert_eq!(alert.final_blocks, 4);
+        assert_eq!(alert.final_block_origins, 0);
+    }
+}
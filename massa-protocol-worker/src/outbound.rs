@@ -0,0 +1,163 @@
+//! Order in which the candidates for the outbound connections fill the slots.
+//!
+//! The pinned peers come first. The other candidates are then taken one at a time from the subnet
+//! the least represented among the connected peers and the candidates already taken, so that the node
+//! builds a peer set spread over many networks rather than over a single hosting provider.
+//! Within a subnet, the candidates with the best reputation come first, then those with the lowest
+//! latency measured during their previous connections, then those whose connections went best recently.
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    net::{IpAddr, SocketAddr},
+};
+
+use massa_protocol_exports::{IpSubnet, PeerLatency, TransportType};
+
+use crate::{
+    handlers::peer_handler::{latency::latency_bucket, models::ConnectionMetadata},
+    net_split::subnet,
+};
+
+/// Peer that can be dialed to fill an outbound connection slot
+pub(crate) struct OutboundCandidate<'a> {
+    /// first listener of the peer, identifying it in the connection attempts
+    pub(crate) addr: SocketAddr,
+    /// best connection history among the addresses of the peer
+    pub(crate) metadata: ConnectionMetadata,
+    /// category of the peer, the default one if None
+    pub(crate) category: Option<&'a String>,
+    /// addresses to dial for each transport, the preferred transport first
+    pub(crate) dial_targets: Vec<(TransportType, Vec<SocketAddr>)>,
+    /// whether the node operator asked to connect to the peer first
+    pub(crate) pinned: bool,
+    /// reputation of the peer in the peer store
+    pub(crate) reputation: i64,
+    /// last latency measured with the peer, if any
+    pub(crate) latency: Option<PeerLatency>,
+}
+
+impl OutboundCandidate<'_> {
+    /// Compares the quality of two candidates, the best first
+    fn cmp_quality(&self, other: &Self) -> Ordering {
+        other
+            .reputation
+            .cmp(&self.reputation)
+            .then_with(|| {
+                latency_bucket(self.latency.as_ref()).cmp(&latency_bucket(other.latency.as_ref()))
+            })
+            .then_with(|| self.metadata.cmp(&other.metadata))
+    }
+}
+
+/// Orders the `candidates` in which the outbound slots are filled,
+/// given the addresses of the peers already `connected`
+pub(crate) fn allocation_order<'a>(
+    candidates: Vec<OutboundCandidate<'a>>,
+    connected: impl IntoIterator<Item = IpAddr>,
+) -> Vec<OutboundCandidate<'a>> {
+    let mut subnet_peers = HashMap::new();
+    for ip in connected {
+        *subnet_peers.entry(subnet(ip)).or_insert(0usize) += 1;
+    }
+
+    let (mut order, mut others): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|candidate| candidate.pinned);
+    order.sort_by(OutboundCandidate::cmp_quality);
+    for candidate in &order {
+        *subnet_peers.entry(subnet(candidate.addr.ip())).or_insert(0) += 1;
+    }
+
+    // the candidates of each subnet, the best first
+    others.sort_by(OutboundCandidate::cmp_quality);
+    let mut by_subnet: HashMap<_, VecDeque<OutboundCandidate>> = HashMap::new();
+    for candidate in others {
+        by_subnet
+            .entry(subnet(candidate.addr.ip()))
+            .or_default()
+            .push_back(candidate);
+    }
+    // take the best candidate of the least represented subnet, until there is none left
+    while let Some(next_subnet) = by_subnet
+        .iter()
+        .min_by(|(subnet_a, queue_a), (subnet_b, queue_b)| {
+            let peers = |key: &IpSubnet| subnet_peers.get(key).copied().unwrap_or(0);
+            peers(*subnet_a)
+                .cmp(&peers(*subnet_b))
+                .then_with(|| queue_a[0].cmp_quality(&queue_b[0]))
+        })
+        .map(|(subnet, _)| *subnet)
+    {
+        let queue = by_subnet
+            .get_mut(&next_subnet)
+            .expect("subnet of the candidates not found");
+        if let Some(candidate) = queue.pop_front() {
+            order.push(candidate);
+        }
+        if queue.is_empty() {
+            by_subnet.remove(&next_subnet);
+        }
+        *subnet_peers.entry(next_subnet).or_insert(0) += 1;
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_time::MassaTime;
+
+    use super::*;
+
+    fn candidate(addr: &str, reputation: i64, rtt: Option<u64>) -> OutboundCandidate<'static> {
+        OutboundCandidate {
+            addr: addr.parse().unwrap(),
+            metadata: ConnectionMetadata::default(),
+            category: None,
+            dial_targets: Vec::new(),
+            pinned: false,
+            reputation,
+            latency: rtt.map(|rtt| PeerLatency {
+                rtt: MassaTime::from_millis(rtt),
+                jitter: MassaTime::from_millis(0),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_allocation_order() {
+        let mut pinned = candidate("192.0.2.1:31244", -1000, None);
+        pinned.pinned = true;
+        let candidates = vec![
+            // three peers of the same hosting provider, the best of them being the best overall
+            candidate("198.51.100.1:31244", 100, Some(20)),
+            candidate("198.51.100.2:31244", 90, Some(20)),
+            candidate("198.51.7.3:31244", 80, Some(20)),
+            // peers of two other networks, one of them already connected
+            candidate("203.0.113.1:31244", 10, Some(300)),
+            candidate("203.0.113.2:31244", 10, Some(20)),
+            candidate("[2001:db8::1]:31244", 5, None),
+            pinned,
+        ];
+        let order: Vec<String> = allocation_order(candidates, ["2001:db8::2".parse().unwrap()])
+            .into_iter()
+            .map(|candidate| candidate.addr.to_string())
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                // pinned first, whatever its reputation
+                "192.0.2.1:31244",
+                // then from the subnets without peer, the best candidate first
+                "198.51.100.1:31244",
+                // the lower latency breaks the tie of reputation
+                "203.0.113.2:31244",
+                // then alternating between the subnets with as many peers
+                "198.51.100.2:31244",
+                "203.0.113.1:31244",
+                "[2001:db8::1]:31244",
+                "198.51.7.3:31244",
+            ]
+        );
+    }
+}
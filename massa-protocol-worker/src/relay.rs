@@ -0,0 +1,74 @@
+//! Relay of the outbound peer connections through the SOCKS5 proxy.
+//!
+//! The network layer opens its connections itself: to route one of them through the proxy,
+//! the connection to the peer is first opened through the proxy, then relayed from a local port
+//! to which the network layer connects. The network layer thus sees the peer at a loopback address.
+
+use std::io;
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use massa_protocol_exports::{ProtocolError, Socks5Proxy};
+use tracing::debug;
+
+// protocol-relay
+const RELAY_THREAD_NAME: &str = "p-relay";
+static_assertions::const_assert!(RELAY_THREAD_NAME.len() < 16);
+
+/// Interval at which the relay checks whether the network layer connected to it
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Opens a connection to `target` through the proxy and relays it from a local port, returned.
+/// The relay is dropped if nothing connects to the local port within `timeout`.
+pub(crate) fn open_relay(
+    proxy: &Socks5Proxy,
+    target: SocketAddr,
+    timeout: Duration,
+) -> Result<SocketAddr, ProtocolError> {
+    let upstream = proxy.connect(target, timeout)?;
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    listener.set_nonblocking(true)?;
+    let local_addr = listener.local_addr()?;
+    std::thread::Builder::new()
+        .name(RELAY_THREAD_NAME.to_string())
+        .spawn(move || {
+            let deadline = Instant::now() + timeout;
+            let downstream = loop {
+                match listener.accept() {
+                    Ok((stream, _)) => break stream,
+                    Err(err)
+                        if err.kind() == io::ErrorKind::WouldBlock && Instant::now() < deadline =>
+                    {
+                        std::thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                    Err(err) => {
+                        debug!("relay to {} dropped: {}", target, err);
+                        return;
+                    }
+                }
+            };
+            drop(listener);
+            if let Err(err) = pipe(downstream, upstream) {
+                debug!("relay to {} failed: {}", target, err);
+            }
+        })?;
+    Ok(local_addr)
+}
+
+/// Copies the data between two streams in both directions until both are closed
+fn pipe(downstream: TcpStream, upstream: TcpStream) -> io::Result<()> {
+    downstream.set_nonblocking(false)?;
+    let mut downstream_read = downstream.try_clone()?;
+    let mut upstream_write = upstream.try_clone()?;
+    let forward = std::thread::Builder::new()
+        .name(RELAY_THREAD_NAME.to_string())
+        .spawn(move || {
+            let _ = io::copy(&mut downstream_read, &mut upstream_write);
+            let _ = upstream_write.shutdown(Shutdown::Write);
+        })?;
+    let (mut upstream_read, mut downstream_write) = (upstream, downstream);
+    let _ = io::copy(&mut upstream_read, &mut downstream_write);
+    let _ = downstream_write.shutdown(Shutdown::Write);
+    let _ = forward.join();
+    Ok(())
+}
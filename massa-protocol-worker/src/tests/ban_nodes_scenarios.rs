@@ -73,6 +73,7 @@ fn test_protocol_bans_node_sending_block_header_with_invalid_signature() {
                 PeerInfo {
                     last_announce: None,
                     state: PeerState::Trusted,
+                    agent: None,
                 },
             );
             peers
@@ -100,6 +101,7 @@ fn test_protocol_bans_node_sending_block_header_with_invalid_signature() {
         PeerInfo {
             last_announce: None,
             state: PeerState::Banned,
+            agent: None,
         },
     );
     foreign_controllers
@@ -178,6 +180,7 @@ fn test_protocol_bans_node_sending_operation_with_invalid_signature() {
                 PeerInfo {
                     last_announce: None,
                     state: PeerState::Trusted,
+                    agent: None,
                 },
             );
             peers
@@ -260,6 +263,7 @@ fn test_protocol_bans_node_sending_header_with_invalid_signature() {
                 PeerInfo {
                     last_announce: None,
                     state: PeerState::Trusted,
+                    agent: None,
                 },
             );
             peers
@@ -279,6 +283,7 @@ fn test_protocol_bans_node_sending_header_with_invalid_signature() {
         PeerInfo {
             last_announce: None,
             state: PeerState::Banned,
+            agent: None,
         },
     );
     foreign_controllers
@@ -347,6 +352,7 @@ fn test_protocol_bans_node_sending_header_with_invalid_signature() {
         &node_a_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::OperationIds(vec![operation_2.id]),
         })),
     );
@@ -387,6 +393,7 @@ fn test_protocol_does_not_asks_for_block_from_banned_node_who_propagated_header(
                 PeerInfo {
                     last_announce: None,
                     state: PeerState::Trusted,
+                    agent: None,
                 },
             );
             peers
@@ -407,6 +414,7 @@ fn test_protocol_does_not_asks_for_block_from_banned_node_who_propagated_header(
         PeerInfo {
             last_announce: None,
             state: PeerState::Banned,
+            agent: None,
         },
     );
     foreign_controllers
@@ -501,6 +509,7 @@ fn test_protocol_bans_all_nodes_propagating_an_attack_attempt() {
                 PeerInfo {
                     last_announce: None,
                     state: PeerState::Trusted,
+                    agent: None,
                 },
             );
             peers.insert(
@@ -508,6 +517,7 @@ fn test_protocol_bans_all_nodes_propagating_an_attack_attempt() {
                 PeerInfo {
                     last_announce: None,
                     state: PeerState::Trusted,
+                    agent: None,
                 },
             );
             peers
@@ -518,6 +528,7 @@ fn test_protocol_bans_all_nodes_propagating_an_attack_attempt() {
         PeerInfo {
             last_announce: None,
             state: PeerState::Banned,
+            agent: None,
         },
     );
     peers.insert(
@@ -525,6 +536,7 @@ fn test_protocol_bans_all_nodes_propagating_an_attack_attempt() {
         PeerInfo {
             last_announce: None,
             state: PeerState::Banned,
+            agent: None,
         },
     );
     let counter = Arc::new(RwLock::new(0));
@@ -618,3 +630,110 @@ fn test_protocol_bans_all_nodes_propagating_an_attack_attempt() {
 
     ban_waitpoint.wait();
 }
+
+/// Sends unrequested headers at the given slots from a single peer,
+/// and checks that none of them reach consensus and that the peer is banned after the last one
+fn assert_bans_node_sending_out_of_bounds_headers(
+    protocol_config: ProtocolConfig,
+    slots: Vec<Slot>,
+) {
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+
+    let block_creator = KeyPair::generate(0).unwrap();
+    let headers: Vec<_> = slots
+        .into_iter()
+        .map(|slot| {
+            ProtocolTestUniverse::create_block(&block_creator, slot, vec![], vec![], vec![])
+                .content
+                .header
+        })
+        .collect();
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+
+    let ban_waitpoint = WaitPoint::new();
+    let ban_waitpoint_trigger_handle = ban_waitpoint.get_trigger_handle();
+
+    foreign_controllers
+        .peer_db
+        .write()
+        .expect_ban_peer()
+        .times(1)
+        .returning(move |peer_id| {
+            assert_eq!(peer_id, &node_a_peer_id);
+            ban_waitpoint_trigger_handle.trigger();
+        });
+    peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    foreign_controllers
+        .peer_db
+        .write()
+        .expect_get_peers()
+        .return_const(HashMap::new());
+    // the headers are dropped before reaching consensus
+    foreign_controllers
+        .consensus_controller
+        .expect_register_block_header()
+        .times(0);
+    let mut shared_active_connections = MockActiveConnectionsTraitWrapper::new();
+    shared_active_connections.set_expectations(|active_connections| {
+        active_connections
+            .expect_get_peer_ids_connected()
+            .returning(move || {
+                let mut peers = HashSet::new();
+                peers.insert(node_a_peer_id);
+                peers
+            });
+        active_connections
+            .expect_shutdown_connection()
+            .times(1)
+            .with(predicate::eq(node_a_peer_id))
+            .returning(move |_| {});
+    });
+    foreign_controllers
+        .network_controller
+        .expect_get_active_connections()
+        .returning(move || Box::new(shared_active_connections.clone()));
+
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    for header in headers {
+        universe.mock_message_receive(
+            &node_a_peer_id,
+            Message::Block(Box::new(BlockMessage::Header(header))),
+        );
+    }
+    ban_waitpoint.wait();
+}
+
+#[test]
+fn test_protocol_bans_node_sending_headers_too_far_in_the_future() {
+    // genesis is now: with a 16s t0, the period 10 is more than two minutes in the future
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        max_header_future_time: MassaTime::from_millis(1000),
+        max_out_of_bounds_headers_per_peer: 1,
+        ..Default::default()
+    };
+    assert_bans_node_sending_out_of_bounds_headers(
+        protocol_config,
+        vec![Slot::new(10, 0), Slot::new(10, 1)],
+    );
+}
+
+#[test]
+fn test_protocol_bans_node_sending_long_since_final_headers() {
+    // genesis is 100 periods ago: the periods 1 and 2 are older than the 10 accepted periods
+    let t0 = MassaTime::from_millis(1000);
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        t0,
+        genesis_timestamp: MassaTime::now().saturating_sub(t0.saturating_mul(100)),
+        max_header_past_periods: 10,
+        max_out_of_bounds_headers_per_peer: 1,
+        ..Default::default()
+    };
+    assert_bans_node_sending_out_of_bounds_headers(
+        protocol_config,
+        vec![Slot::new(1, 0), Slot::new(2, 0)],
+    );
+}
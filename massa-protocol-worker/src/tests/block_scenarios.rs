@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::handlers::block_handler::{AskForBlockInfo, BlockInfoReply, BlockMessage};
 use crate::handlers::operation_handler::OperationMessage;
@@ -13,8 +13,8 @@ use massa_models::config::CHAINID;
 use massa_models::operation::{OperationId, OperationPrefixId};
 use massa_models::prehash::PreHashSet;
 use massa_models::{block_id::BlockId, slot::Slot};
-use massa_protocol_exports::PeerId;
 use massa_protocol_exports::ProtocolConfig;
+use massa_protocol_exports::{PeerFeatures, PeerId};
 use massa_signature::KeyPair;
 use massa_test_framework::{TestUniverse, WaitPoint};
 use massa_time::MassaTime;
@@ -88,6 +88,7 @@ fn block_retrieval_mock(
                                         BlockMessage::DataRequest {
                                             block_id,
                                             block_info,
+                                            ..
                                         } => {
                                             assert_eq!(block_id, asked_block_id);
                                             assert_eq!(block_info, asked_infos);
@@ -303,6 +304,7 @@ fn test_full_ask_block_workflow() {
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
         })),
     );
@@ -312,12 +314,254 @@ fn test_full_ask_block_workflow() {
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::Operations(vec![op_1]),
         })),
     );
     waitpoint.wait();
 }
 
+#[test]
+fn test_stale_block_info_responses_are_discarded() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        ask_block_timeout: MassaTime::from_millis(100),
+        ..Default::default()
+    };
+
+    let block_creator = KeyPair::generate(0).unwrap();
+    let op_1 = ProtocolTestUniverse::create_operation(&block_creator, 5, *CHAINID);
+    let op_thread = op_1
+        .content_creator_address
+        .get_thread(protocol_config.thread_count);
+    let block = ProtocolTestUniverse::create_block(
+        &block_creator,
+        Slot::new(1, op_thread),
+        vec![op_1.clone()],
+        vec![],
+        vec![],
+    );
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+
+    let waitpoint = WaitPoint::new();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    // only the requests to the peers announcing it are numbered
+    ProtocolTestUniverse::peer_db_boilerplate_with_features(
+        &mut foreign_controllers.peer_db.write(),
+        HashMap::from([(node_a_peer_id, PeerFeatures::SUPPORTED)]),
+    );
+    foreign_controllers
+        .consensus_controller
+        .expect_register_block_header()
+        .return_once(move |block_id, header| {
+            assert_eq!(block_id, block.id);
+            assert_eq!(header.id, block.content.header.id);
+        });
+    // an accepted response to any of the operation ids requests would lead to asking the operations
+    block_retrieval_mock(
+        vec![
+            TestsStepMatch::AskData((
+                PeerIdMatchers::PeerId(node_a_peer_id),
+                block.id,
+                AskForBlockInfo::OperationIds,
+            )),
+            TestsStepMatch::AskData((
+                PeerIdMatchers::PeerId(node_a_peer_id),
+                block.id,
+                AskForBlockInfo::OperationIds,
+            )),
+            TestsStepMatch::AskData((
+                PeerIdMatchers::PeerId(node_a_peer_id),
+                block.id,
+                AskForBlockInfo::OperationIds,
+            )),
+            TestsStepMatch::AskData((
+                PeerIdMatchers::PeerId(node_a_peer_id),
+                block.id,
+                AskForBlockInfo::Operations(vec![op_1.id]),
+            )),
+            TestsStepMatch::BlockManaged((block.id, true)),
+        ],
+        &mut foreign_controllers,
+        waitpoint.get_trigger_handle(),
+    );
+
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::Header(block.content.header.clone()))),
+    );
+
+    universe
+        .module_controller
+        .send_wishlist_delta(
+            vec![(block.id, Some(block.content.header.clone()))]
+                .into_iter()
+                .collect(),
+            PreHashSet::<BlockId>::default(),
+        )
+        .unwrap();
+    waitpoint.wait();
+
+    // response to a request that was not sent yet
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: Some(1),
+            block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
+        })),
+    );
+    // the first request times out and is sent again
+    waitpoint.wait();
+
+    // response to the timed out request
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: Some(0),
+            block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
+        })),
+    );
+    waitpoint.wait();
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: Some(2),
+            block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
+        })),
+    );
+    waitpoint.wait();
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: Some(3),
+            block_info: BlockInfoReply::Operations(vec![op_1]),
+        })),
+    );
+    waitpoint.wait();
+}
+
+#[test]
+fn test_parallel_block_asks_process_a_single_response() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        ask_block_timeout: MassaTime::from_millis(10000),
+        max_parallel_block_asks: 2,
+        ..Default::default()
+    };
+
+    let block_creator = KeyPair::generate(0).unwrap();
+    let op_1 = ProtocolTestUniverse::create_operation(&block_creator, 5, *CHAINID);
+    let op_thread = op_1
+        .content_creator_address
+        .get_thread(protocol_config.thread_count);
+    let block = ProtocolTestUniverse::create_block(
+        &block_creator,
+        Slot::new(1, op_thread),
+        vec![op_1.clone()],
+        vec![],
+        vec![],
+    );
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+    let node_b_keypair = KeyPair::generate(0).unwrap();
+    let node_b_peer_id = PeerId::from_public_key(node_b_keypair.get_public_key());
+    let both_peers =
+        PeerIdMatchers::AmongPeerIds([node_a_peer_id, node_b_peer_id].into_iter().collect());
+
+    let waitpoint = WaitPoint::new();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    foreign_controllers
+        .consensus_controller
+        .expect_register_block_header()
+        .returning(move |_, _| {});
+    // each step is asked to both peers, and the block is registered once
+    block_retrieval_mock(
+        vec![
+            TestsStepMatch::AskData((both_peers.clone(), block.id, AskForBlockInfo::OperationIds)),
+            TestsStepMatch::AskData((both_peers.clone(), block.id, AskForBlockInfo::OperationIds)),
+            TestsStepMatch::AskData((
+                both_peers.clone(),
+                block.id,
+                AskForBlockInfo::Operations(vec![op_1.id]),
+            )),
+            TestsStepMatch::AskData((
+                both_peers,
+                block.id,
+                AskForBlockInfo::Operations(vec![op_1.id]),
+            )),
+            TestsStepMatch::BlockManaged((block.id, true)),
+        ],
+        &mut foreign_controllers,
+        waitpoint.get_trigger_handle(),
+    );
+
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe
+        .module_controller
+        .send_wishlist_delta(
+            vec![(block.id, Some(block.content.header.clone()))]
+                .into_iter()
+                .collect(),
+            PreHashSet::<BlockId>::default(),
+        )
+        .unwrap();
+    waitpoint.wait();
+    waitpoint.wait();
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: None,
+            block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
+        })),
+    );
+    waitpoint.wait();
+    waitpoint.wait();
+
+    // the answer of the other peer to the cancelled ask is dropped
+    universe.mock_message_receive(
+        &node_b_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: None,
+            block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
+        })),
+    );
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: None,
+            block_info: BlockInfoReply::Operations(vec![op_1.clone()]),
+        })),
+    );
+    waitpoint.wait();
+
+    // the block is not processed a second time
+    universe.mock_message_receive(
+        &node_b_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: None,
+            block_info: BlockInfoReply::Operations(vec![op_1]),
+        })),
+    );
+    std::thread::sleep(std::time::Duration::from_millis(200));
+}
+
 #[test]
 fn test_empty_block() {
     let protocol_config = ProtocolConfig {
@@ -385,6 +629,7 @@ fn test_empty_block() {
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::OperationIds(vec![]),
         })),
     );
@@ -466,6 +711,7 @@ fn test_dont_want_it_anymore() {
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::OperationIds(vec![op_1.id, op_2.id]),
         })),
     );
@@ -544,6 +790,7 @@ fn test_no_one_has_it() {
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::NotFound,
         })),
     );
@@ -698,6 +945,7 @@ fn test_protocol_sends_blocks_when_asked_for() {
         &node_a_peer_id,
         Message::Block(Box::new(BlockMessage::DataRequest {
             block_id: block.id,
+            sequence: None,
             block_info: AskForBlockInfo::OperationIds,
         })),
     );
@@ -707,6 +955,7 @@ fn test_protocol_sends_blocks_when_asked_for() {
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataRequest {
             block_id: block.id,
+            sequence: None,
             block_info: AskForBlockInfo::OperationIds,
         })),
     );
@@ -787,6 +1036,7 @@ fn test_protocol_propagates_block_to_node_who_asked_for_operations_and_only_head
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataRequest {
             block_id: block.id,
+            sequence: None,
             block_info: AskForBlockInfo::OperationIds,
         })),
     );
@@ -881,6 +1131,7 @@ fn test_noting_block_does_not_panic_with_one_max_node_known_blocks_size() {
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
         })),
     );
@@ -890,6 +1141,7 @@ fn test_noting_block_does_not_panic_with_one_max_node_known_blocks_size() {
         &node_b_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::Operations(vec![op_1]),
         })),
     );
@@ -984,6 +1236,7 @@ fn test_protocol_does_propagate_operations_received_in_blocks() {
         &node_a_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
         })),
     );
@@ -993,9 +1246,219 @@ fn test_protocol_does_propagate_operations_received_in_blocks() {
         &node_a_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::Operations(vec![op_1]),
         })),
     );
     waitpoint.wait();
     waitpoint.wait();
 }
+
+#[test]
+fn test_block_with_operation_of_another_thread_is_rejected() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        ask_block_timeout: MassaTime::from_millis(100),
+        ..Default::default()
+    };
+
+    let block_creator = KeyPair::generate(0).unwrap();
+    let op_1 = ProtocolTestUniverse::create_operation(&block_creator, 5, *CHAINID);
+    let op_thread = op_1
+        .content_creator_address
+        .get_thread(protocol_config.thread_count);
+    // the block is in the other thread
+    let block = ProtocolTestUniverse::create_block(
+        &block_creator,
+        Slot::new(1, 1 - op_thread),
+        vec![op_1.clone()],
+        vec![],
+        vec![],
+    );
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+
+    let waitpoint = WaitPoint::new();
+    let invalid_waitpoint = WaitPoint::new();
+    let invalid_trigger_handle = invalid_waitpoint.get_trigger_handle();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    foreign_controllers
+        .peer_db
+        .write()
+        .expect_ban_peer()
+        .returning(move |peer_id| assert_eq!(peer_id, &node_a_peer_id));
+    foreign_controllers
+        .consensus_controller
+        .expect_register_block_header()
+        .return_once(move |block_id, header| {
+            assert_eq!(block_id, block.id);
+            assert_eq!(header.id, block.content.header.id);
+        });
+    // the block never reaches consensus as a full block
+    foreign_controllers
+        .consensus_controller
+        .expect_register_block()
+        .never();
+    foreign_controllers
+        .consensus_controller
+        .expect_mark_invalid_block()
+        .times(1)
+        .return_once(move |block_id, _| {
+            assert_eq!(block_id, block.id);
+            invalid_trigger_handle.trigger();
+        });
+    block_retrieval_mock(
+        vec![
+            TestsStepMatch::AskData((
+                PeerIdMatchers::PeerId(node_a_peer_id),
+                block.id,
+                AskForBlockInfo::OperationIds,
+            )),
+            TestsStepMatch::AskData((
+                PeerIdMatchers::PeerId(node_a_peer_id),
+                block.id,
+                AskForBlockInfo::Operations(vec![op_1.id]),
+            )),
+        ],
+        &mut foreign_controllers,
+        waitpoint.get_trigger_handle(),
+    );
+
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::Header(block.content.header.clone()))),
+    );
+    universe
+        .module_controller
+        .send_wishlist_delta(
+            vec![(block.id, Some(block.content.header.clone()))]
+                .into_iter()
+                .collect(),
+            PreHashSet::<BlockId>::default(),
+        )
+        .unwrap();
+    waitpoint.wait();
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: None,
+            block_info: BlockInfoReply::OperationIds(vec![op_1.id]),
+        })),
+    );
+    waitpoint.wait();
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::DataResponse {
+            block_id: block.id,
+            sequence: None,
+            block_info: BlockInfoReply::Operations(vec![op_1]),
+        })),
+    );
+    invalid_waitpoint.wait();
+}
+
+#[test]
+fn test_locally_held_block_is_not_downloaded_again() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        ask_block_timeout: MassaTime::from_millis(100),
+        ..Default::default()
+    };
+
+    let block_creator = KeyPair::generate(0).unwrap();
+    let block =
+        ProtocolTestUniverse::create_block(&block_creator, Slot::new(1, 1), vec![], vec![], vec![]);
+
+    let waitpoint = WaitPoint::new();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    // no ask is expected: the block is registered straight from the local storage
+    block_retrieval_mock(
+        vec![TestsStepMatch::BlockManaged((block.id, false))],
+        &mut foreign_controllers,
+        waitpoint.get_trigger_handle(),
+    );
+
+    let mut universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe.storage.store_block(block.clone());
+    universe
+        .module_controller
+        .send_wishlist_delta(
+            vec![(block.id, Some(block.content.header.clone()))]
+                .into_iter()
+                .collect(),
+            PreHashSet::<BlockId>::default(),
+        )
+        .unwrap();
+    waitpoint.wait();
+}
+
+#[test]
+fn test_equivocated_block_is_still_downloaded() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        ask_block_timeout: MassaTime::from_millis(100),
+        ..Default::default()
+    };
+
+    let block_creator = KeyPair::generate(0).unwrap();
+    let local_block =
+        ProtocolTestUniverse::create_block(&block_creator, Slot::new(1, 1), vec![], vec![], vec![]);
+    let op = ProtocolTestUniverse::create_operation(&block_creator, 5, *CHAINID);
+    // same creator and slot, different content
+    let block = ProtocolTestUniverse::create_block(
+        &block_creator,
+        Slot::new(1, 1),
+        vec![op],
+        vec![],
+        vec![],
+    );
+    assert_ne!(local_block.id, block.id);
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+
+    let waitpoint = WaitPoint::new();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    foreign_controllers
+        .consensus_controller
+        .expect_register_block_header()
+        .return_once(move |block_id, header| {
+            assert_eq!(block_id, block.id);
+            assert_eq!(header.id, block.content.header.id);
+        });
+    block_retrieval_mock(
+        vec![TestsStepMatch::AskData((
+            PeerIdMatchers::PeerId(node_a_peer_id),
+            block.id,
+            AskForBlockInfo::OperationIds,
+        ))],
+        &mut foreign_controllers,
+        waitpoint.get_trigger_handle(),
+    );
+
+    let mut universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe.storage.store_block(local_block);
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Block(Box::new(BlockMessage::Header(block.content.header.clone()))),
+    );
+    universe
+        .module_controller
+        .send_wishlist_delta(
+            vec![(block.id, Some(block.content.header.clone()))]
+                .into_iter()
+                .collect(),
+            PreHashSet::<BlockId>::default(),
+        )
+        .unwrap();
+    waitpoint.wait();
+}
@@ -0,0 +1,202 @@
+//! Checks that the network messages of the previous releases are still understood.
+//!
+//! The `compat/protocol-<version>.json` fixtures hold the test vectors of the wire spec of a
+//! released node (see `wire_spec.rs`). Each vector must be fully decoded by the current
+//! deserializers and re-encoded to the exact same bytes, so that both old and new nodes
+//! understand each other's messages.
+//!
+//! When a version is released, generate its spec (see `wire_spec.rs`) and copy
+//! `target/wire-spec/protocol-<version>.json` to `src/tests/compat/`. Intended breaking changes
+//! are listed in `ACCEPTED_BREAKING_CHANGES`.
+
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+use massa_models::config::{
+    CHAINID, ENDORSEMENT_COUNT, MAX_DATASTORE_VALUE_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+    MAX_ENDORSEMENTS_PER_MESSAGE, MAX_FUNCTION_NAME_LENGTH, MAX_HEARTBEATS_PER_MESSAGE,
+    MAX_LISTENERS_PER_PEER, MAX_MESSAGE_SIZE, MAX_NODE_LINKS_PER_MESSAGE, MAX_OPERATIONS_PER_BLOCK,
+    MAX_OPERATIONS_PER_MESSAGE, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
+    MAX_PEERS_IN_ANNOUNCEMENT_LIST, THREAD_COUNT,
+};
+use massa_models::test_exports::from_hex;
+use massa_serialization::{DeserializeError, Deserializer, U64VarIntDeserializer};
+use peernet::messages::MessagesSerializer as PeerNetMessagesSerializer;
+use serde_json::Value;
+use std::ops::Bound::Included;
+
+use crate::handlers::{
+    block_handler::{
+        messages::{BlockMessageDeserializer, BlockMessageDeserializerArgs},
+        BlockMessageSerializer,
+    },
+    endorsement_handler::{
+        messages::{EndorsementMessageDeserializer, EndorsementMessageDeserializerArgs},
+        EndorsementMessageSerializer,
+    },
+    operation_handler::{
+        messages::{OperationMessageDeserializer, OperationMessageDeserializerArgs},
+        OperationMessageSerializer,
+    },
+    peer_handler::{
+        messages::{PeerManagementMessageDeserializer, PeerManagementMessageDeserializerArgs},
+        PeerManagementMessageSerializer,
+    },
+};
+use crate::messages::{Message, MessageTypeId, MessagesSerializer};
+
+/// Vectors of previous releases that are known not to be understood anymore:
+/// `(node version, vector name, reason)`
+const ACCEPTED_BREAKING_CHANGES: &[(&str, &str, &str)] = &[];
+
+/// Deserializes messages with the same limits as the node
+pub(super) struct MessagesDeserializer {
+    id_deserializer: U64VarIntDeserializer,
+    block: BlockMessageDeserializer,
+    endorsement: EndorsementMessageDeserializer,
+    operation: OperationMessageDeserializer,
+    peer_management: PeerManagementMessageDeserializer,
+}
+
+impl MessagesDeserializer {
+    pub(super) fn new() -> Self {
+        MessagesDeserializer {
+            id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            block: BlockMessageDeserializer::new(BlockMessageDeserializerArgs {
+                thread_count: THREAD_COUNT,
+                endorsement_count: ENDORSEMENT_COUNT,
+                max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+                max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+                max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+                max_parameters_size: MAX_PARAMETERS_SIZE,
+                max_op_datastore_entry_count: MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+                max_op_datastore_key_length: MAX_OPERATION_DATASTORE_KEY_LENGTH,
+                max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+                max_denunciations_in_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+                last_start_period: Some(0),
+                chain_id: *CHAINID,
+                max_decompressed_operations_size: MAX_MESSAGE_SIZE as u64,
+            }),
+            endorsement: EndorsementMessageDeserializer::new(EndorsementMessageDeserializerArgs {
+                thread_count: THREAD_COUNT,
+                max_length_endorsements: MAX_ENDORSEMENTS_PER_MESSAGE as u64,
+                endorsement_count: ENDORSEMENT_COUNT,
+                chain_id: *CHAINID,
+            }),
+            operation: OperationMessageDeserializer::new(OperationMessageDeserializerArgs {
+                max_operations_prefix_ids: MAX_OPERATIONS_PER_MESSAGE,
+                max_operations: MAX_OPERATIONS_PER_MESSAGE,
+                max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+                max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+                max_parameters_size: MAX_PARAMETERS_SIZE,
+                max_op_datastore_entry_count: MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+                max_op_datastore_key_length: MAX_OPERATION_DATASTORE_KEY_LENGTH,
+                max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+                chain_id: *CHAINID,
+                max_decompressed_operations_size: MAX_MESSAGE_SIZE as u64,
+            }),
+            peer_management: PeerManagementMessageDeserializer::new(
+                PeerManagementMessageDeserializerArgs {
+                    max_listeners_per_peer: MAX_LISTENERS_PER_PEER,
+                    max_peers_per_announcement: MAX_PEERS_IN_ANNOUNCEMENT_LIST,
+                    max_heartbeats_per_message: MAX_HEARTBEATS_PER_MESSAGE,
+                    max_node_links_per_message: MAX_NODE_LINKS_PER_MESSAGE,
+                    thread_count: THREAD_COUNT,
+                    chain_id: *CHAINID,
+                },
+            ),
+        }
+    }
+
+    /// Deserializes a whole message, failing if some bytes are left
+    pub(super) fn deserialize(&self, buffer: &[u8]) -> Result<Message, String> {
+        let (rest, id) = self
+            .id_deserializer
+            .deserialize::<DeserializeError>(buffer)
+            .map_err(|e| format!("invalid message type id: {}", e))?;
+        let id = MessageTypeId::try_from(id).map_err(|e| e.to_string())?;
+        let (rest, message) = match id {
+            MessageTypeId::Block => self
+                .block
+                .deserialize::<DeserializeError>(rest)
+                .map(|(rest, message)| (rest, Message::Block(Box::new(message)))),
+            MessageTypeId::Endorsement => self
+                .endorsement
+                .deserialize::<DeserializeError>(rest)
+                .map(|(rest, message)| (rest, Message::Endorsement(message))),
+            MessageTypeId::Operation => self
+                .operation
+                .deserialize::<DeserializeError>(rest)
+                .map(|(rest, message)| (rest, Message::Operation(message))),
+            MessageTypeId::PeerManagement => self
+                .peer_management
+                .deserialize::<DeserializeError>(rest)
+                .map(|(rest, message)| (rest, Message::PeerManagement(Box::new(message)))),
+        }
+        .map_err(|e| e.to_string())?;
+        if !rest.is_empty() {
+            return Err(format!("{} bytes left after the message", rest.len()));
+        }
+        Ok(message)
+    }
+}
+
+#[test]
+fn previous_releases_wire_compatibility() {
+    let deserializer = MessagesDeserializer::new();
+    let serializer = MessagesSerializer::new()
+        .with_block_message_serializer(BlockMessageSerializer::new())
+        .with_endorsement_message_serializer(EndorsementMessageSerializer::new())
+        .with_operation_message_serializer(OperationMessageSerializer::new())
+        .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
+
+    let compat_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/compat");
+    let mut fixture_count = 0;
+    let mut errors = Vec::new();
+    for entry in read_dir(&compat_dir).expect("could not read the compat fixtures directory") {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let fixture: Value = serde_json::from_str(&read_to_string(&path).unwrap()).unwrap();
+        let node_version = fixture["node_version"]
+            .as_str()
+            .expect("missing node version");
+        fixture_count += 1;
+        for vector in fixture["test_vectors"]
+            .as_array()
+            .expect("missing test vectors")
+        {
+            let name = vector["name"].as_str().expect("missing vector name");
+            let bytes = from_hex(vector["hex"].as_str().expect("missing vector hex"));
+            let result = deserializer.deserialize(&bytes).and_then(|message| {
+                let mut buffer = Vec::new();
+                serializer
+                    .serialize(&message, &mut buffer)
+                    .map_err(|e| e.to_string())?;
+                if buffer != bytes {
+                    return Err("re-serialized to different bytes".to_string());
+                }
+                Ok(())
+            });
+            let accepted = ACCEPTED_BREAKING_CHANGES
+                .iter()
+                .any(|(version, listed_name, _)| *version == node_version && *listed_name == name);
+            match (result, accepted) {
+                (Ok(()), false) | (Err(_), true) => {}
+                (Err(e), false) => errors.push(format!("{} {}: {}", node_version, name, e)),
+                (Ok(()), true) => errors.push(format!(
+                    "{} {}: listed as a breaking change but still compatible",
+                    node_version, name
+                )),
+            }
+        }
+    }
+    assert!(fixture_count > 0, "no fixture in {}", compat_dir.display());
+    assert!(
+        errors.is_empty(),
+        "wire incompatibilities with previous releases:\n{}",
+        errors.join("\n")
+    );
+}
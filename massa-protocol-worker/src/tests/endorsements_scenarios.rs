@@ -4,6 +4,7 @@ use massa_protocol_exports::PeerId;
 use massa_protocol_exports::ProtocolConfig;
 use massa_signature::KeyPair;
 use massa_test_framework::{TestUniverse, WaitPoint};
+use massa_time::MassaTime;
 
 use crate::{
     handlers::{block_handler::BlockMessage, endorsement_handler::EndorsementMessage},
@@ -307,3 +308,64 @@ fn test_protocol_propagates_endorsements_only_to_nodes_that_dont_know_about_it_b
     );
     waitpoint.wait();
 }
+
+#[test]
+fn test_protocol_batches_endorsements_propagated_during_interval() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        endorsement_propagation_interval: MassaTime::from_millis(300),
+        ..Default::default()
+    };
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+    let endorsement_1 =
+        ProtocolTestUniverse::create_endorsement(&KeyPair::generate(0).unwrap(), Slot::new(1, 0));
+    let endorsement_2 =
+        ProtocolTestUniverse::create_endorsement(&KeyPair::generate(0).unwrap(), Slot::new(1, 1));
+    let expected_ids = vec![endorsement_1.id, endorsement_2.id];
+
+    let waitpoint = WaitPoint::new();
+    let waitpoint_trigger_handle = waitpoint.get_trigger_handle();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    let mut shared_active_connections = MockActiveConnectionsTraitWrapper::new();
+    ProtocolTestUniverse::active_connections_boilerplate(
+        &mut shared_active_connections,
+        std::iter::once(node_a_peer_id).collect(),
+    );
+    shared_active_connections.set_expectations(|active_connections| {
+        active_connections.expect_send_to_peer().returning(
+            move |peer_id, _message_serializer, message, _high_priority| {
+                assert_eq!(peer_id, &node_a_peer_id);
+                match message {
+                    // both endorsements are sent in a single message
+                    Message::Endorsement(EndorsementMessage::Endorsements(endorsements)) => {
+                        let mut ids: Vec<_> = endorsements.iter().map(|e| e.id).collect();
+                        ids.sort();
+                        let mut expected_ids = expected_ids.clone();
+                        expected_ids.sort();
+                        assert_eq!(ids, expected_ids);
+                        waitpoint_trigger_handle.trigger();
+                    }
+                    _ => panic!("Unexpected message type"),
+                }
+                Ok(())
+            },
+        );
+    });
+    foreign_controllers
+        .network_controller
+        .expect_get_active_connections()
+        .returning(move || Box::new(shared_active_connections.clone()));
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    for endorsement in [endorsement_1, endorsement_2] {
+        let mut storage = universe.storage.clone_without_refs();
+        storage.store_endorsements(vec![endorsement]);
+        universe
+            .module_controller
+            .propagate_endorsements(storage)
+            .unwrap();
+    }
+    waitpoint.wait();
+}
@@ -17,10 +17,12 @@ use crate::{create_protocol_controller, start_protocol_controller};
 
 mod ban_nodes_scenarios;
 mod block_scenarios;
+mod compat;
 mod endorsements_scenarios;
 mod operations_scenarios;
 mod peer_priorization;
 mod universe;
+mod wire_spec;
 
 #[test]
 fn basic() {
@@ -90,6 +92,7 @@ fn basic() {
         PeerData {
             listeners: peers_1,
             category: "Bootstrap".to_string(),
+            hostnames: Vec::new(),
         },
     );
     serde_json::to_writer_pretty(initial_peers_file.as_file(), &initial_peers1)
@@ -103,6 +106,7 @@ fn basic() {
         PeerData {
             listeners: peers_2,
             category: "Bootstrap".to_string(),
+            hostnames: Vec::new(),
         },
     );
     serde_json::to_writer_pretty(initial_peers_file_2.as_file(), &initial_peers2)
@@ -253,6 +257,7 @@ fn stop_with_controller_still_exists() {
         PeerData {
             listeners: peers_1,
             category: "Bootstrap".to_string(),
+            hostnames: Vec::new(),
         },
     );
     serde_json::to_writer_pretty(initial_peers_file.as_file(), &initial_peers1)
@@ -266,6 +271,7 @@ fn stop_with_controller_still_exists() {
         PeerData {
             listeners: peers_2,
             category: "Bootstrap".to_string(),
+            hostnames: Vec::new(),
         },
     );
     serde_json::to_writer_pretty(initial_peers_file_2.as_file(), &initial_peers2)
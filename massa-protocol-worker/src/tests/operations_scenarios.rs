@@ -18,7 +18,7 @@ use crate::wrap_network::MockActiveConnectionsTraitWrapper;
 use crate::{
     handlers::{
         block_handler::{BlockInfoReply, BlockMessage},
-        operation_handler::OperationMessage,
+        operation_handler::{pending_ops_sketch::PendingOpsSketch, OperationMessage},
     },
     messages::Message,
 };
@@ -30,6 +30,7 @@ enum TestsStepMatch {
     OperationsPropagated((PeerId, Vec<OperationPrefixId>, bool)),
     AskBlockInfos((PeerId, BlockId, AskForBlockInfo)),
     AskForOperations((PeerId, Vec<OperationPrefixId>)),
+    AskForPendingOperations(PeerId),
     OperationsInPool(Vec<SecureShareOperation>),
     OperationsSent((PeerId, Vec<SecureShareOperation>)),
 }
@@ -94,6 +95,7 @@ fn operation_workflow_mock(
                                     BlockMessage::DataRequest {
                                         block_id,
                                         block_info,
+                                        ..
                                     } => {
                                         assert_eq!(block_id, asked_block_id);
                                         assert_eq!(block_info, asked_block_info);
@@ -133,6 +135,26 @@ fn operation_workflow_mock(
                         });
                 });
             }
+            TestsStepMatch::AskForPendingOperations(node_peer_id) => {
+                peer_ids.insert(node_peer_id);
+                shared_active_connections.set_expectations(|active_connections| {
+                    active_connections
+                        .expect_send_to_peer()
+                        .times(1)
+                        .in_sequence(&mut sequence)
+                        .returning(move |peer_id, _, message, high_priority| {
+                            assert_eq!(*peer_id, node_peer_id);
+                            assert!(!high_priority);
+                            match message {
+                                Message::Operation(OperationMessage::AskForPendingOperations) => {
+                                    waitpoint_trigger_handle.trigger();
+                                }
+                                _ => panic!("Unexpected message type."),
+                            }
+                            Ok(())
+                        });
+                });
+            }
             TestsStepMatch::OperationsSent((node_peer_id, sent_operations)) => {
                 peer_ids.insert(node_peer_id);
                 shared_active_connections.set_expectations(|active_connections| {
@@ -258,6 +280,188 @@ fn test_protocol_does_not_send_invalid_operations_it_receives_to_pool() {
     waitpoint.wait();
 }
 
+#[test]
+fn test_protocol_ignores_operations_while_syncing() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        max_sync_lag_for_gossip: MassaTime::from_millis(32000),
+        ..Default::default()
+    };
+    let block_creator = KeyPair::generate(0).unwrap();
+    let operation_1 = ProtocolTestUniverse::create_operation(&block_creator, 1, *CHAINID);
+    let operation_2 = ProtocolTestUniverse::create_operation(&block_creator, 2, *CHAINID);
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+
+    let waitpoint = WaitPoint::new();
+    let waitpoint_trigger_handle = waitpoint.get_trigger_handle();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    // only the operation received once synced reaches the pool
+    operation_workflow_mock(
+        vec![TestsStepMatch::OperationsInPool(vec![operation_2.clone()])],
+        &mut foreign_controllers,
+        waitpoint_trigger_handle,
+    );
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe
+        .module_controller
+        .notify_sync_lag(MassaTime::from_millis(320000))
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Operation(OperationMessage::Operations(vec![operation_1])),
+    );
+    std::thread::sleep(Duration::from_millis(100));
+    universe
+        .module_controller
+        .notify_sync_lag(MassaTime::from_millis(500))
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Operation(OperationMessage::Operations(vec![operation_2])),
+    );
+    waitpoint.wait();
+}
+
+#[test]
+fn test_protocol_asks_pending_operations_once_synced() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        max_sync_lag_for_gossip: MassaTime::from_millis(32000),
+        pool_warmup_enabled: true,
+        ..Default::default()
+    };
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+
+    let waitpoint = WaitPoint::new();
+    let waitpoint_trigger_handle = waitpoint.get_trigger_handle();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    // the pool is warmed up only once, when the node becomes synced
+    operation_workflow_mock(
+        vec![TestsStepMatch::AskForPendingOperations(node_a_peer_id)],
+        &mut foreign_controllers,
+        waitpoint_trigger_handle,
+    );
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe
+        .module_controller
+        .notify_sync_lag(MassaTime::from_millis(320000))
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    universe
+        .module_controller
+        .notify_sync_lag(MassaTime::from_millis(500))
+        .unwrap();
+    waitpoint.wait();
+    universe
+        .module_controller
+        .notify_sync_lag(MassaTime::from_millis(500))
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+}
+
+#[test]
+fn test_protocol_announces_pending_operations_when_asked() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        ..Default::default()
+    };
+    let block_creator = KeyPair::generate(0).unwrap();
+    let operation_1 = ProtocolTestUniverse::create_operation(&block_creator, 1, *CHAINID);
+    let operation_2 = ProtocolTestUniverse::create_operation(&block_creator, 2, *CHAINID);
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+
+    let waitpoint = WaitPoint::new();
+    let waitpoint_trigger_handle = waitpoint.get_trigger_handle();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    let pool_op_ids = vec![operation_1.id, operation_2.id];
+    foreign_controllers
+        .pool_controller
+        .set_expectations(|pool_controller| {
+            pool_controller
+                .expect_get_operation_ids()
+                .times(1)
+                .returning(move |_| pool_op_ids.clone());
+        });
+    // a second request from the same peer is too frequent to be answered
+    operation_workflow_mock(
+        vec![TestsStepMatch::OperationsPropagated((
+            node_a_peer_id,
+            vec![operation_1.id.into_prefix(), operation_2.id.into_prefix()],
+            true,
+        ))],
+        &mut foreign_controllers,
+        waitpoint_trigger_handle,
+    );
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Operation(OperationMessage::AskForPendingOperations),
+    );
+    waitpoint.wait();
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Operation(OperationMessage::AskForPendingOperations),
+    );
+    std::thread::sleep(Duration::from_millis(100));
+}
+
+#[test]
+fn test_protocol_announces_pending_operations_missing_from_sketch() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        ..Default::default()
+    };
+    let block_creator = KeyPair::generate(0).unwrap();
+    let operation_1 = ProtocolTestUniverse::create_operation(&block_creator, 1, *CHAINID);
+    let operation_2 = ProtocolTestUniverse::create_operation(&block_creator, 2, *CHAINID);
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+
+    let waitpoint = WaitPoint::new();
+    let waitpoint_trigger_handle = waitpoint.get_trigger_handle();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    let pool_op_ids = vec![operation_1.id, operation_2.id];
+    foreign_controllers
+        .pool_controller
+        .set_expectations(|pool_controller| {
+            pool_controller
+                .expect_get_operation_ids()
+                .times(1)
+                .returning(move |_| pool_op_ids.clone());
+        });
+    // the peer already has the first operation
+    operation_workflow_mock(
+        vec![TestsStepMatch::OperationsPropagated((
+            node_a_peer_id,
+            vec![operation_2.id.into_prefix()],
+            true,
+        ))],
+        &mut foreign_controllers,
+        waitpoint_trigger_handle,
+    );
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Operation(OperationMessage::PendingOperationsSketch(
+            PendingOpsSketch::new(&[operation_1.id.into_prefix()]),
+        )),
+    );
+    waitpoint.wait();
+}
+
 #[test]
 fn test_protocol_propagates_operations_to_active_nodes() {
     let protocol_config = ProtocolConfig {
@@ -449,6 +653,7 @@ fn test_protocol_propagates_operations_only_to_nodes_that_dont_know_about_it_ind
         &node_a_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::OperationIds(vec![operation_1.id].into_iter().collect()),
         })),
     );
@@ -458,6 +663,7 @@ fn test_protocol_propagates_operations_only_to_nodes_that_dont_know_about_it_ind
         &node_a_peer_id,
         Message::Block(Box::new(BlockMessage::DataResponse {
             block_id: block.id,
+            sequence: None,
             block_info: BlockInfoReply::Operations(vec![operation_1].into_iter().collect()),
         })),
     );
@@ -661,3 +867,60 @@ fn test_protocol_on_ask_operations() {
     );
     waitpoint.wait();
 }
+
+#[test]
+fn test_protocol_merges_operations_asked_by_a_peer() {
+    let protocol_config = ProtocolConfig {
+        thread_count: 2,
+        operation_send_interval: MassaTime::from_millis(100),
+        ..Default::default()
+    };
+    let block_creator = KeyPair::generate(0).unwrap();
+    let operation_1 = ProtocolTestUniverse::create_operation(&block_creator, 1, *CHAINID);
+    let operation_2 = ProtocolTestUniverse::create_operation(&block_creator, 1, *CHAINID);
+    let node_a_keypair = KeyPair::generate(0).unwrap();
+    let node_a_peer_id = PeerId::from_public_key(node_a_keypair.get_public_key());
+    let node_b_keypair = KeyPair::generate(0).unwrap();
+    let node_b_peer_id = PeerId::from_public_key(node_b_keypair.get_public_key());
+
+    let waitpoint = WaitPoint::new();
+    let waitpoint_trigger_handle = waitpoint.get_trigger_handle();
+    let mut foreign_controllers = ProtocolForeignControllers::new_with_mocks();
+    ProtocolTestUniverse::peer_db_boilerplate(&mut foreign_controllers.peer_db.write());
+    // both asks of node B are answered by a single message
+    operation_workflow_mock(
+        vec![
+            TestsStepMatch::OperationsInPool(vec![operation_1.clone(), operation_2.clone()]),
+            TestsStepMatch::OperationsSent((
+                node_b_peer_id,
+                vec![operation_1.clone(), operation_2.clone()],
+            )),
+        ],
+        &mut foreign_controllers,
+        waitpoint_trigger_handle,
+    );
+    let universe = ProtocolTestUniverse::new(foreign_controllers, protocol_config);
+
+    universe.mock_message_receive(
+        &node_a_peer_id,
+        Message::Operation(OperationMessage::Operations(vec![
+            operation_1.clone(),
+            operation_2.clone(),
+        ])),
+    );
+    waitpoint.wait();
+
+    universe.mock_message_receive(
+        &node_b_peer_id,
+        Message::Operation(OperationMessage::AskForOperations(
+            vec![operation_1.id.into_prefix()].into_iter().collect(),
+        )),
+    );
+    universe.mock_message_receive(
+        &node_b_peer_id,
+        Message::Operation(OperationMessage::AskForOperations(
+            vec![operation_2.id.into_prefix()].into_iter().collect(),
+        )),
+    );
+    waitpoint.wait();
+}
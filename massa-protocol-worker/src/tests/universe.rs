@@ -1,11 +1,11 @@
 use massa_channel::MassaChannel;
 use massa_consensus_exports::{ConsensusController, MockConsensusController};
-use massa_models::config::MIP_STORE_STATS_BLOCK_CONSIDERED;
+use massa_models::config::{MIP_STORE_STATS_BLOCK_CONSIDERED, VERSION};
 use massa_pool_exports::{MockPoolControllerWrapper, PoolController};
 use massa_pos_exports::{MockSelectorControllerWrapper, SelectorController};
 use massa_protocol_exports::{
-    PeerCategoryInfo, PeerConnectionType, PeerId, ProtocolConfig, ProtocolController,
-    ProtocolError, ProtocolManager,
+    PeerAgent, PeerCategoryInfo, PeerConnectionType, PeerFeatures, PeerId, ProtocolConfig,
+    ProtocolController, ProtocolError, ProtocolManager,
 };
 use massa_serialization::U64VarIntDeserializer;
 use massa_signature::KeyPair;
@@ -26,7 +26,13 @@ use crate::{
         block_handler::BlockMessageSerializer,
         endorsement_handler::EndorsementMessageSerializer,
         operation_handler::OperationMessageSerializer,
-        peer_handler::{models::SharedPeerDB, PeerManagementMessageSerializer},
+        peer_handler::{
+            ban_list::BanList,
+            latency::PeerLatencies,
+            models::{PeerInfo, PeerState, SharedPeerDB},
+            peer_store::PeerStore,
+            PeerManagementMessageSerializer,
+        },
     },
     manager::ProtocolManagerImpl,
     messages::{Message, MessagesHandler, MessagesSerializer},
@@ -59,12 +65,26 @@ pub struct ProtocolForeignControllers {
 
 impl ProtocolForeignControllers {
     pub fn new_with_mocks() -> Self {
+        // the peer store is read on startup and saved on shutdown, the latencies are read to ask for blocks
+        let mut peer_db = MockPeerDBTrait::new();
+        peer_db
+            .expect_get_peer_store()
+            .return_const(PeerStore::default());
+        peer_db
+            .expect_get_peer_store_mut()
+            .return_var(PeerStore::default());
+        peer_db
+            .expect_get_latencies()
+            .return_const(PeerLatencies::default());
+        peer_db
+            .expect_get_latencies_mut()
+            .return_var(PeerLatencies::default());
         Self {
             consensus_controller: Box::new(MockConsensusController::new()),
             pool_controller: Box::new(MockPoolControllerWrapper::new()),
             selector_controller: Box::new(MockSelectorControllerWrapper::new()),
             network_controller: Box::new(MockNetworkController::new()),
-            peer_db: Arc::new(RwLock::new(MockPeerDBTrait::new())),
+            peer_db: Arc::new(RwLock::new(peer_db)),
         }
     }
 }
@@ -123,6 +143,32 @@ impl ProtocolTestUniverse {
     }
 
     pub fn peer_db_boilerplate(mock_peer_db: &mut RwLockWriteGuard<MockPeerDBTrait>) {
+        Self::peer_db_boilerplate_with_features(mock_peer_db, HashMap::default());
+    }
+
+    /// Same as `peer_db_boilerplate`, with peers that announced some wire features
+    pub fn peer_db_boilerplate_with_features(
+        mock_peer_db: &mut RwLockWriteGuard<MockPeerDBTrait>,
+        features: HashMap<PeerId, PeerFeatures>,
+    ) {
+        let peers: HashMap<PeerId, PeerInfo> = features
+            .into_iter()
+            .map(|(peer_id, features)| {
+                let agent = PeerAgent {
+                    version: *VERSION,
+                    user_agent: None,
+                    features,
+                    features_version: PeerFeatures::VERSION,
+                };
+                let info = PeerInfo {
+                    last_announce: None,
+                    state: PeerState::Trusted,
+                    agent: Some(agent),
+                };
+                (peer_id, info)
+            })
+            .collect();
+        mock_peer_db.expect_get_peers().return_const(peers);
         mock_peer_db
             .expect_get_peers_in_test()
             .return_const(HashSet::default());
@@ -223,6 +269,7 @@ pub fn start_protocol_controller_with_mock_network(
         sender_operations: sender_operations.clone(),
         sender_peers: sender_peers.clone(),
         id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        traffic: Default::default(),
     };
 
     let (controller, channels) = create_protocol_controller(config.clone());
@@ -245,6 +292,10 @@ pub fn start_protocol_controller_with_mock_network(
         (sender_peers, receiver_peers),
         HashMap::default(),
         peer_db,
+        Arc::new(RwLock::new(BanList::load(
+            &config.ban_list_file,
+            &config.peers_whitelist_file,
+        )?)),
         storage,
         channels,
         message_handlers.clone(),
@@ -266,7 +317,7 @@ pub fn start_protocol_controller_with_mock_network(
         .0,
     )?;
 
-    let manager = ProtocolManagerImpl::new(connectivity_thread_handle);
+    let manager = ProtocolManagerImpl::new(connectivity_thread_handle, None);
 
     Ok((message_handlers, controller, Box::new(manager)))
 }
@@ -0,0 +1,372 @@
+//! Describes the network messages from their actual definitions: type identifiers are
+//! enumerated from the message id enums, bounds come from the network constants and test
+//! vectors are produced by the node's serializers. Alternative implementations can check
+//! their encoding against the generated `wire-spec/protocol-<version>.json` artifact.
+//!
+//! Each test vector is checked against its expected byte layout, built by hand from the
+//! framing described in the spec, and must round trip through the node's deserializers.
+//!
+//! The artifact is written by an ignored test:
+//! `cargo test -p massa_protocol_worker generate_protocol_wire_spec -- --ignored`
+
+use std::fs::read_to_string;
+
+use massa_hash::Hash;
+use massa_models::{
+    block_header::{BlockHeader, BlockHeaderSerializer},
+    block_id::BlockId,
+    config::{
+        CHAINID, ENDORSEMENT_COUNT, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        MAX_ENDORSEMENTS_PER_MESSAGE, MAX_HEARTBEATS_PER_MESSAGE, MAX_LISTENERS_PER_PEER,
+        MAX_MESSAGE_SIZE, MAX_NODE_LINKS_PER_MESSAGE, MAX_OPERATIONS_PER_BLOCK,
+        MAX_OPERATIONS_PER_MESSAGE, MAX_PEERS_IN_ANNOUNCEMENT_LIST, THREAD_COUNT, VERSION,
+    },
+    secure_share::SecureShareContent,
+    slot::Slot,
+    test_exports::{enum_ids, to_hex, varint, write_wire_spec},
+};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use peernet::messages::MessagesSerializer as PeerNetMessagesSerializer;
+use serde_json::{json, Value};
+
+use super::compat::MessagesDeserializer;
+use crate::handlers::{
+    block_handler::{
+        messages::{BlockInfoType, MessageTypeId as BlockMessageTypeId},
+        AskForBlockInfo, BlockInfoReply, BlockMessage, BlockMessageSerializer,
+    },
+    endorsement_handler::{
+        messages::MessageTypeId as EndorsementMessageTypeId, EndorsementMessage,
+        EndorsementMessageSerializer,
+    },
+    operation_handler::{
+        messages::MessageTypeId as OperationMessageTypeId, OperationMessage,
+        OperationMessageSerializer,
+    },
+    peer_handler::{
+        messages::MessageTypeId as PeerManagementMessageTypeId, PeerManagementMessage,
+        PeerManagementMessageSerializer,
+    },
+};
+use crate::messages::{Message, MessageTypeId, MessagesSerializer, ENVELOPE_ID, MESSAGE_VERSION};
+
+const NONCE: u64 = 0x0123_4567_89ab_cdef;
+const ASKED_AT_MILLIS: u64 = 1_700_000_000_000;
+const SEQUENCE: u64 = 7;
+
+/// `varint(message type id) || varint(sub-message type id) || payload`
+fn frame(type_id: u64, sub_id: u64, payload: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = varint(type_id);
+    bytes.extend(varint(sub_id));
+    for part in payload {
+        bytes.extend_from_slice(part);
+    }
+    bytes
+}
+
+fn messages_serializer() -> MessagesSerializer {
+    MessagesSerializer::new()
+        .with_block_message_serializer(BlockMessageSerializer::new())
+        .with_endorsement_message_serializer(EndorsementMessageSerializer::new())
+        .with_operation_message_serializer(OperationMessageSerializer::new())
+        .with_peer_management_message_serializer(PeerManagementMessageSerializer::new())
+}
+
+/// Test vectors of the spec: `(name, message, expected encoding)`
+fn test_vectors() -> Vec<(&'static str, Message, Vec<u8>)> {
+    let keypair = serde_json::from_str::<KeyPair>(
+        &read_to_string("./src/tests/test_keypair1.json").expect("could not read test keypair"),
+    )
+    .unwrap();
+    let parent_hashes: Vec<Hash> = (0..THREAD_COUNT)
+        .map(|i| Hash::compute_from(&[i]))
+        .collect();
+    let parents: Vec<BlockId> = parent_hashes
+        .iter()
+        .map(|hash| BlockId::generate_from_hash(*hash))
+        .collect();
+    let header = BlockHeader::new_verifiable(
+        BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot: Slot::new(1, 0),
+            parents: parents.clone(),
+            operation_merkle_root: Hash::compute_from(b""),
+            endorsements: Vec::new(),
+            denunciations: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        &keypair,
+        *CHAINID,
+    )
+    .unwrap();
+    // block ids are `varint(version) || hash`
+    let mut block_id_bytes = varint(0);
+    block_id_bytes.extend(parent_hashes[0].to_bytes());
+    // secure shares are `signature || creator public key || content`
+    let header_bytes = [
+        header.signature.to_bytes(),
+        header.content_creator_pub_key.to_bytes(),
+        header.serialized_data.clone(),
+    ]
+    .concat();
+    let key = Hash::compute_from(b"");
+
+    vec![
+        (
+            "Block::Header",
+            BlockMessage::Header(header).into(),
+            frame(0, 0, &[&header_bytes]),
+        ),
+        (
+            "Block::DataRequest(OperationIds)",
+            BlockMessage::DataRequest {
+                block_id: parents[0],
+                sequence: None,
+                block_info: AskForBlockInfo::OperationIds,
+            }
+            .into(),
+            frame(0, 1, &[&block_id_bytes, &varint(1)]),
+        ),
+        (
+            "Block::DataResponse(NotFound)",
+            BlockMessage::DataResponse {
+                block_id: parents[0],
+                sequence: None,
+                block_info: BlockInfoReply::NotFound,
+            }
+            .into(),
+            frame(0, 2, &[&block_id_bytes, &varint(3)]),
+        ),
+        (
+            "Block::SequencedDataRequest(OperationIds)",
+            BlockMessage::DataRequest {
+                block_id: parents[0],
+                sequence: Some(SEQUENCE),
+                block_info: AskForBlockInfo::OperationIds,
+            }
+            .into(),
+            frame(0, 3, &[&block_id_bytes, &varint(SEQUENCE), &varint(1)]),
+        ),
+        (
+            "Block::SequencedDataResponse(NotFound)",
+            BlockMessage::DataResponse {
+                block_id: parents[0],
+                sequence: Some(SEQUENCE),
+                block_info: BlockInfoReply::NotFound,
+            }
+            .into(),
+            frame(0, 4, &[&block_id_bytes, &varint(SEQUENCE), &varint(3)]),
+        ),
+        (
+            "Endorsement::Endorsements(empty)",
+            EndorsementMessage::Endorsements(Vec::new()).into(),
+            frame(1, 0, &[&varint(0)]),
+        ),
+        (
+            "Operation::OperationsAnnouncement(empty)",
+            OperationMessage::OperationsAnnouncement(Default::default()).into(),
+            frame(2, 0, &[&varint(0)]),
+        ),
+        (
+            "Operation::Operations(empty)",
+            OperationMessage::Operations(Vec::new()).into(),
+            frame(2, 2, &[&varint(0)]),
+        ),
+        (
+            "PeerManagement::ListPeers(empty)",
+            PeerManagementMessage::ListPeers(Vec::new()).into(),
+            frame(3, 1, &[&varint(0)]),
+        ),
+        (
+            "PeerManagement::AskReachabilityTest",
+            PeerManagementMessage::AskReachabilityTest.into(),
+            frame(3, 2, &[]),
+        ),
+        (
+            "PeerManagement::ReachabilityTestResult(true)",
+            PeerManagementMessage::ReachabilityTestResult(true).into(),
+            frame(3, 3, &[&[1]]),
+        ),
+        (
+            "PeerManagement::AskTime",
+            PeerManagementMessage::AskTime(MassaTime::from_millis(ASKED_AT_MILLIS)).into(),
+            frame(3, 5, &[&varint(ASKED_AT_MILLIS)]),
+        ),
+        (
+            "PeerManagement::FindPeers",
+            PeerManagementMessage::FindPeers(key).into(),
+            frame(3, 8, &[key.to_bytes()]),
+        ),
+        (
+            "PeerManagement::Ping",
+            PeerManagementMessage::Ping(NONCE).into(),
+            frame(3, 10, &[&NONCE.to_be_bytes()]),
+        ),
+        (
+            "PeerManagement::Pong",
+            PeerManagementMessage::Pong(NONCE).into(),
+            frame(3, 11, &[&NONCE.to_be_bytes()]),
+        ),
+    ]
+}
+
+#[test]
+fn test_protocol_wire_layout() {
+    let serializer = messages_serializer();
+    for (name, message, expected) in test_vectors() {
+        let mut buffer = Vec::new();
+        serializer.serialize(&message, &mut buffer).unwrap();
+        assert_eq!(
+            to_hex(&buffer),
+            to_hex(&expected),
+            "unexpected layout for {}",
+            name
+        );
+        assert!(buffer.len() <= MAX_MESSAGE_SIZE as usize);
+    }
+}
+
+#[test]
+fn test_protocol_wire_round_trip() {
+    let serializer = messages_serializer();
+    let deserializer = MessagesDeserializer::new();
+    for (name, message, _) in test_vectors() {
+        let mut buffer = Vec::new();
+        serializer.serialize(&message, &mut buffer).unwrap();
+        let decoded = deserializer
+            .deserialize(&buffer)
+            .unwrap_or_else(|e| panic!("could not decode {}: {}", name, e));
+        let mut reencoded = Vec::new();
+        serializer.serialize(&decoded, &mut reencoded).unwrap();
+        assert_eq!(reencoded, buffer, "{} re-encoded to different bytes", name);
+    }
+}
+
+#[test]
+fn test_protocol_wire_type_ids() {
+    let ids = |values: Vec<Value>| -> Vec<(String, u64)> {
+        values
+            .iter()
+            .map(|v| {
+                (
+                    v["name"].as_str().unwrap().to_string(),
+                    v["id"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    };
+    let expected = |names: &[&str]| -> Vec<(String, u64)> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.to_string(), id as u64))
+            .collect()
+    };
+    assert_eq!(
+        ids(enum_ids::<u64, MessageTypeId>()),
+        expected(&["Block", "Endorsement", "Operation", "PeerManagement"])
+    );
+    assert_eq!(
+        ids(enum_ids::<u64, BlockMessageTypeId>()),
+        expected(&[
+            "Header",
+            "DataRequest",
+            "DataResponse",
+            "SequencedDataRequest",
+            "SequencedDataResponse",
+        ])
+    );
+    assert_eq!(
+        ids(enum_ids::<u64, BlockInfoType>()),
+        expected(&[
+            "Header",
+            "OperationIds",
+            "Operations",
+            "NotFound",
+            "CompressedOperations",
+        ])
+    );
+    assert_eq!(
+        ids(enum_ids::<u64, EndorsementMessageTypeId>()),
+        expected(&["Endorsements"])
+    );
+    assert_eq!(
+        ids(enum_ids::<u64, OperationMessageTypeId>()),
+        expected(&[
+            "OperationsAnnouncement",
+            "AskForOperations",
+            "Operations",
+            "AskForPendingOperations",
+            "CompressedOperations",
+            "PendingOperationsSketch",
+        ])
+    );
+    assert_eq!(
+        ids(enum_ids::<u64, PeerManagementMessageTypeId>()),
+        expected(&[
+            "NewPeerConnected",
+            "ListPeers",
+            "AskReachabilityTest",
+            "ReachabilityTestResult",
+            "Heartbeats",
+            "AskTime",
+            "Time",
+            "NodeAddressLinks",
+            "FindPeers",
+            "ClosestPeers",
+            "Ping",
+            "Pong",
+        ])
+    );
+}
+
+#[test]
+#[ignore]
+fn generate_protocol_wire_spec() {
+    let serializer = messages_serializer();
+    let test_vectors: Vec<Value> = test_vectors()
+        .into_iter()
+        .map(|(name, message, _)| {
+            let mut buffer = Vec::new();
+            serializer.serialize(&message, &mut buffer).unwrap();
+            json!({ "name": name, "hex": to_hex(&buffer) })
+        })
+        .collect();
+
+    let spec = json!({
+        "spec": "massa-protocol-wire",
+        "node_version": VERSION.to_string(),
+        "framing": "each message is `varint(message type id) || varint(sub-message type id) || payload`, varints are unsigned LEB128",
+        "envelope": {
+            "id": ENVELOPE_ID,
+            "message_version": MESSAGE_VERSION,
+            "framing": "to the peers announcing the MESSAGE_ENVELOPE feature, each message is sent as `varint(envelope id) || varint(message version) || message`: a message of unknown type sent with a newer message version is skipped",
+        },
+        "message_types": enum_ids::<u64, MessageTypeId>(),
+        "sub_message_types": {
+            "Block": enum_ids::<u64, BlockMessageTypeId>(),
+            "BlockInfo": enum_ids::<u64, BlockInfoType>(),
+            "Endorsement": enum_ids::<u64, EndorsementMessageTypeId>(),
+            "Operation": enum_ids::<u64, OperationMessageTypeId>(),
+            "PeerManagement": enum_ids::<u64, PeerManagementMessageTypeId>(),
+        },
+        "bounds": {
+            "thread_count": THREAD_COUNT,
+            "endorsement_count": ENDORSEMENT_COUNT,
+            "max_message_size": MAX_MESSAGE_SIZE,
+            "max_operations_per_block": MAX_OPERATIONS_PER_BLOCK,
+            "max_operations_per_message": MAX_OPERATIONS_PER_MESSAGE,
+            "max_endorsements_per_message": MAX_ENDORSEMENTS_PER_MESSAGE,
+            "max_denunciations_per_block_header": MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            "max_peers_in_announcement_list": MAX_PEERS_IN_ANNOUNCEMENT_LIST,
+            "max_listeners_per_peer": MAX_LISTENERS_PER_PEER,
+            "max_heartbeats_per_message": MAX_HEARTBEATS_PER_MESSAGE,
+            "max_node_links_per_message": MAX_NODE_LINKS_PER_MESSAGE,
+        },
+        "test_vectors": test_vectors,
+    });
+
+    write_wire_spec("protocol", &spec);
+}
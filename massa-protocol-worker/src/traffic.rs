@@ -0,0 +1,175 @@
+//! Traffic exchanged with the peers, counted per message type and per peer.
+//!
+//! The messages are counted with their size on the wire, envelope included: the messages sent when
+//! they are serialized for a peer, and the messages received as they come, even if their type is unknown.
+//! The traffic of a peer is forgotten when it disconnects, while the traffic per message type
+//! is kept since the node started.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+
+use massa_protocol_exports::{NetworkTrafficStats, PeerId, TrafficCounters};
+use parking_lot::Mutex;
+use peernet::{error::PeerNetResult, messages::MessagesSerializer as PeerNetMessagesSerializer};
+
+use crate::messages::{message_ids, message_type_name, Message};
+
+pub(crate) type SharedTrafficStats = Arc<Mutex<TrafficStats>>;
+
+#[derive(Default)]
+pub(crate) struct TrafficStats {
+    /// traffic of each message type, by message type id and sub-type id if it could be read
+    by_message_type: HashMap<(u64, Option<u64>), TrafficCounters>,
+    /// traffic with each connected peer
+    by_peer: HashMap<PeerId, TrafficCounters>,
+}
+
+impl TrafficStats {
+    /// Notes a message of type `ids` and of `bytes` sent to `peer_id`
+    pub(crate) fn note_sent(&mut self, peer_id: &PeerId, ids: (u64, Option<u64>), bytes: usize) {
+        self.by_message_type
+            .entry(ids)
+            .or_default()
+            .note_sent(bytes);
+        self.by_peer.entry(*peer_id).or_default().note_sent(bytes);
+    }
+
+    /// Notes a message of type `ids` and of `bytes` received from `peer_id`
+    pub(crate) fn note_received(
+        &mut self,
+        peer_id: &PeerId,
+        ids: (u64, Option<u64>),
+        bytes: usize,
+    ) {
+        self.by_message_type
+            .entry(ids)
+            .or_default()
+            .note_received(bytes);
+        self.by_peer
+            .entry(*peer_id)
+            .or_default()
+            .note_received(bytes);
+    }
+
+    /// Forgets the traffic of the peers that are not connected anymore
+    pub(crate) fn retain_connected(&mut self, connected: &HashSet<PeerId>) {
+        self.by_peer
+            .retain(|peer_id, _| connected.contains(peer_id));
+    }
+
+    /// Traffic per named message type and per peer
+    pub(crate) fn stats(&self) -> NetworkTrafficStats {
+        let mut by_message_type = BTreeMap::new();
+        for ((id, sub_id), counters) in &self.by_message_type {
+            let total: &mut TrafficCounters = by_message_type
+                .entry(message_type_name(*id, *sub_id))
+                .or_default();
+            total.messages_sent += counters.messages_sent;
+            total.bytes_sent += counters.bytes_sent;
+            total.messages_received += counters.messages_received;
+            total.bytes_received += counters.bytes_received;
+        }
+        NetworkTrafficStats {
+            by_message_type,
+            by_peer: self.by_peer.clone(),
+        }
+    }
+}
+
+/// Serializes the messages with another serializer, noting their size in the traffic sent to a peer
+pub(crate) struct CountingSerializer<'a, S> {
+    serializer: &'a S,
+    peer_id: &'a PeerId,
+    traffic: &'a SharedTrafficStats,
+}
+
+impl<'a, S> CountingSerializer<'a, S> {
+    pub(crate) fn new(
+        serializer: &'a S,
+        peer_id: &'a PeerId,
+        traffic: &'a SharedTrafficStats,
+    ) -> Self {
+        Self {
+            serializer,
+            peer_id,
+            traffic,
+        }
+    }
+}
+
+impl<S: PeerNetMessagesSerializer<Message>> PeerNetMessagesSerializer<Message>
+    for CountingSerializer<'_, S>
+{
+    /// Serialize the message and note its size
+    fn serialize(&self, message: &Message, buffer: &mut Vec<u8>) -> PeerNetResult<()> {
+        let start = buffer.len();
+        self.serializer.serialize(message, buffer)?;
+        self.traffic.lock().note_sent(
+            self.peer_id,
+            message_ids(message),
+            buffer.len().saturating_sub(start),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_signature::KeyPair;
+
+    use super::*;
+    use crate::{
+        handlers::peer_handler::{PeerManagementMessage, PeerManagementMessageSerializer},
+        messages::MessagesSerializer,
+    };
+
+    #[test]
+    fn test_traffic_stats() {
+        let traffic = SharedTrafficStats::default();
+        let (peer, other_peer) = (
+            PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key()),
+            PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key()),
+        );
+        let serializer = MessagesSerializer::new()
+            .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
+
+        // the messages sent are counted with their serialized size
+        let mut buffer = vec![0; 3];
+        CountingSerializer::new(&serializer, &peer, &traffic)
+            .serialize(&PeerManagementMessage::Ping(7).into(), &mut buffer)
+            .unwrap();
+        let ping_size = buffer.len() as u64 - 3;
+        let ping_ids = message_ids(&PeerManagementMessage::Ping(7).into());
+        traffic.lock().note_received(&other_peer, ping_ids, 20);
+        traffic
+            .lock()
+            .note_received(&other_peer, (3, Some(1000)), 5);
+        traffic.lock().note_received(&other_peer, (42, None), 5);
+
+        let stats = traffic.lock().stats();
+        assert_eq!(
+            stats.by_message_type["PeerManagement/Ping"],
+            TrafficCounters {
+                messages_sent: 1,
+                bytes_sent: ping_size,
+                messages_received: 1,
+                bytes_received: 20,
+            }
+        );
+        assert_eq!(
+            stats.by_message_type["PeerManagement/Unknown(1000)"].bytes_received,
+            5
+        );
+        assert_eq!(stats.by_message_type["Unknown(42)"].messages_received, 1);
+        assert_eq!(stats.by_peer[&peer].messages_sent, 1);
+        assert_eq!(stats.by_peer[&other_peer].bytes_received, 30);
+
+        // the traffic of the disconnected peers is forgotten, not the traffic per message type
+        traffic.lock().retain_connected(&HashSet::from([peer]));
+        let stats = traffic.lock().stats();
+        assert_eq!(stats.by_peer.len(), 1);
+        assert_eq!(stats.by_message_type.len(), 3);
+    }
+}
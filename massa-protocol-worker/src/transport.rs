@@ -0,0 +1,105 @@
+//! Choice of the transport of the connections.
+//!
+//! Built with the `quic` feature, the node can listen on a QUIC endpoint besides its TCP listeners.
+//! QUIC multiplexes its streams over a single connection, so that a large bootstrap or block transfer
+//! does not hold back the gossip of the operations behind it as it does on a TCP connection.
+//! A node listening on QUIC announces it during the handshake, and the peers announcing it are dialed
+//! over QUIC first, falling back to their TCP listeners.
+//! QUIC runs over UDP, so it is not used when the connections are relayed through a SOCKS5 proxy.
+
+use std::{collections::HashMap, net::SocketAddr};
+
+use massa_protocol_exports::{PeerFeatures, ProtocolConfig};
+use peernet::transports::TransportType;
+
+/// Returns true if the node can open and accept connections over QUIC
+pub(crate) fn quic_enabled(config: &ProtocolConfig) -> bool {
+    cfg!(feature = "quic") && config.socks5_proxy.is_none()
+}
+
+/// Features announced to the peers during the handshake:
+/// QUIC is only announced when the node listens on it
+pub(crate) fn announced_features(config: &ProtocolConfig) -> PeerFeatures {
+    let listens_on_quic = quic_enabled(config)
+        && config
+            .listeners
+            .values()
+            .any(|transport| *transport == TransportType::Quic);
+    if listens_on_quic {
+        PeerFeatures::SUPPORTED
+    } else {
+        PeerFeatures(PeerFeatures::SUPPORTED.0 & !PeerFeatures::QUIC.0)
+    }
+}
+
+/// Transports to dial a peer with, in order of preference.
+/// The QUIC listeners are only dialed if both sides support QUIC.
+pub(crate) fn dial_transports(
+    config: &ProtocolConfig,
+    peer_features: PeerFeatures,
+) -> Vec<TransportType> {
+    if quic_enabled(config) && peer_features.contains(PeerFeatures::QUIC) {
+        vec![TransportType::Quic, TransportType::Tcp]
+    } else {
+        vec![TransportType::Tcp]
+    }
+}
+
+/// Addresses of the `listeners` of the given transport
+pub(crate) fn listeners_of(
+    listeners: &HashMap<SocketAddr, TransportType>,
+    transport: TransportType,
+) -> impl Iterator<Item = SocketAddr> + '_ {
+    listeners
+        .iter()
+        .filter(move |(_, listener_transport)| **listener_transport == transport)
+        .map(|(addr, _)| *addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_choice() {
+        let mut listeners = HashMap::new();
+        listeners.insert("[::]:31244".parse().unwrap(), TransportType::Tcp);
+        listeners.insert("[::]:31250".parse().unwrap(), TransportType::Quic);
+        let config = ProtocolConfig {
+            listeners: listeners.clone(),
+            ..Default::default()
+        };
+        assert_eq!(
+            listeners_of(&listeners, TransportType::Quic).collect::<Vec<_>>(),
+            vec!["[::]:31250".parse().unwrap()]
+        );
+
+        // peers that did not announce QUIC are only dialed over TCP
+        let legacy_features = PeerFeatures(PeerFeatures::SUPPORTED.0 & !PeerFeatures::QUIC.0);
+        assert_eq!(
+            dial_transports(&config, legacy_features),
+            vec![TransportType::Tcp]
+        );
+        let expected = if cfg!(feature = "quic") {
+            vec![TransportType::Quic, TransportType::Tcp]
+        } else {
+            vec![TransportType::Tcp]
+        };
+        assert_eq!(dial_transports(&config, PeerFeatures::SUPPORTED), expected);
+        assert_eq!(
+            announced_features(&config).contains(PeerFeatures::QUIC),
+            cfg!(feature = "quic")
+        );
+
+        // QUIC is not relayed through a SOCKS5 proxy
+        let proxied = ProtocolConfig {
+            socks5_proxy: Some("127.0.0.1:9050".parse().unwrap()),
+            ..config
+        };
+        assert_eq!(
+            dial_transports(&proxied, PeerFeatures::SUPPORTED),
+            vec![TransportType::Tcp]
+        );
+        assert!(!announced_features(&proxied).contains(PeerFeatures::QUIC));
+    }
+}
@@ -6,7 +6,7 @@ use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{
     BootstrapPeers, PeerData, PeerId, ProtocolConfig, ProtocolController, ProtocolError,
-    ProtocolManager,
+    ProtocolEvent, ProtocolManager, Socks5Proxy,
 };
 use massa_serialization::U64VarIntDeserializer;
 use massa_signature::KeyPair;
@@ -21,14 +21,16 @@ use parking_lot::RwLock;
 use peernet::{
     config::{PeerNetCategoryInfo, PeerNetConfiguration},
     network_manager::PeerNetManager,
+    transports::TransportType,
 };
-use std::{collections::HashMap, fs::read_to_string, ops::Bound::Included, sync::Arc};
+use std::{collections::HashMap, fs::read_to_string, net::IpAddr, ops::Bound::Included, sync::Arc};
 use tracing::{debug, log::warn};
 
 use crate::{
     connectivity::{start_connectivity_thread, ConnectivityCommand},
     context::Context,
     controller::ProtocolControllerImpl,
+    dialer::Dialer,
     handlers::{
         block_handler::{
             commands_propagation::BlockHandlerPropagationCommand,
@@ -43,13 +45,16 @@ use crate::{
             commands_retrieval::OperationHandlerRetrievalCommand,
         },
         peer_handler::{
-            models::{PeerDB, PeerManagementCmd},
+            ban_list::BanList,
+            models::{PeerDB, PeerManagementCmd, SharedEnvelopePeers},
+            peer_store::PeerStore,
             MassaHandshake,
         },
     },
     ip::to_canonical,
     manager::ProtocolManagerImpl,
     messages::MessagesHandler,
+    nat::NatTraversal,
     wrap_network::NetworkControllerImpl,
 };
 
@@ -86,6 +91,8 @@ pub struct ProtocolChannels {
         MassaSender<PeerManagementCmd>,
         MassaReceiver<PeerManagementCmd>,
     ),
+    /// events emitted for the node operator
+    pub protocol_events: (MassaSender<ProtocolEvent>, MassaReceiver<ProtocolEvent>),
 }
 
 /// This function exists because consensus need the protocol controller and we need consensus controller.
@@ -131,12 +138,18 @@ pub fn create_protocol_controller(
         "peer_management_ext".to_string(),
         Some(config.max_size_channel_commands_peers),
     );
+    let (sender_protocol_events, receiver_protocol_events) = MassaChannel::new(
+        "protocol_events".to_string(),
+        Some(config.max_size_channel_commands_peers),
+    );
     (
         Box::new(ProtocolControllerImpl::new(
             sender_blocks_retrieval_ext.clone(),
             sender_blocks_propagation_ext.clone(),
             sender_operations_propagation_ext.clone(),
+            sender_operations_retrieval_ext.clone(),
             sender_endorsements_propagation_ext.clone(),
+            sender_endorsements_retrieval_ext.clone(),
             sender_connectivity_ext.clone(),
             sender_peer_management_ext.clone(),
         )),
@@ -164,6 +177,7 @@ pub fn create_protocol_controller(
             ),
             connectivity_thread: (sender_connectivity_ext, receiver_connectivity_ext),
             peer_management_handler: (sender_peer_management_ext, receiver_peer_management_ext),
+            protocol_events: (sender_protocol_events, receiver_protocol_events),
         },
     )
 }
@@ -177,7 +191,7 @@ pub fn create_protocol_controller(
 /// * `storage`: Shared storage to fetch data that are fetch across all modules
 #[allow(clippy::too_many_arguments)]
 pub fn start_protocol_controller(
-    config: ProtocolConfig,
+    mut config: ProtocolConfig,
     selector_controller: Box<dyn SelectorController>,
     consensus_controller: Box<dyn ConsensusController>,
     bootstrap_peers: Option<BootstrapPeers>,
@@ -188,7 +202,15 @@ pub fn start_protocol_controller(
     massa_metrics: MassaMetrics,
 ) -> Result<(Box<dyn ProtocolManager>, KeyPair, NodeId), ProtocolError> {
     debug!("starting protocol controller");
-    let peer_db = Arc::new(RwLock::new(PeerDB::default()));
+    let peer_db = Arc::new(RwLock::new(PeerDB {
+        peer_store: PeerStore::load(&config.peer_store_file, config.peer_store_max_records)?,
+        ..Default::default()
+    }));
+    let ban_list = Arc::new(RwLock::new(BanList::load(
+        &config.ban_list_file,
+        &config.peers_whitelist_file,
+    )?));
+    let envelope_peers = SharedEnvelopePeers::default();
 
     let (sender_operations, receiver_operations) = MassaChannel::new(
         "sender_operations".to_string(),
@@ -208,12 +230,14 @@ pub fn start_protocol_controller(
     );
 
     // Register channels for handlers
+    let traffic = SharedTrafficStats::default();
     let message_handlers: MessagesHandler = MessagesHandler {
         sender_blocks: sender_blocks.clone(),
         sender_endorsements: sender_endorsements.clone(),
         sender_operations: sender_operations.clone(),
         sender_peers: sender_peers.clone(),
         id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        traffic: traffic.clone(),
     };
 
     // try to read node keypair from file, otherwise generate it & write to file. Then derive nodeId
@@ -237,8 +261,34 @@ pub fn start_protocol_controller(
         keypair
     };
 
+    // ask the router to forward our listeners, before the routable ip is used in the announcements
+    let nat_traversal = if config.nat_traversal {
+        match NatTraversal::start(&config) {
+            Ok(nat_traversal) => {
+                if config.routable_ip.is_none() {
+                    config.routable_ip = Some(IpAddr::V4(nat_traversal.external_ip()));
+                }
+                Some(nat_traversal)
+            }
+            Err(err) => {
+                warn!(
+                    "NAT traversal failed, inbound connections may not be reachable: {}",
+                    err
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let mut peernet_config = PeerNetConfiguration::default(
-        MassaHandshake::new(peer_db.clone(), config.clone()),
+        MassaHandshake::new(
+            peer_db.clone(),
+            ban_list.clone(),
+            envelope_peers.clone(),
+            config.clone(),
+        ),
         message_handlers.clone(),
         Context {
             our_keypair: keypair.clone(),
@@ -250,9 +300,24 @@ pub fn start_protocol_controller(
     peernet_config.rate_limit = config.rate_limit;
     peernet_config.rate_bucket_size = config.rate_limit.saturating_mul(2);
 
-    let initial_peers_infos = serde_json::from_str::<HashMap<PeerId, PeerData>>(
+    let mut initial_peers_infos = serde_json::from_str::<HashMap<PeerId, PeerData>>(
         &std::fs::read_to_string(&config.initial_peers)?,
     )?;
+    let dialer = Dialer::new(&config);
+    initial_peers_infos.retain(|peer_id, data| {
+        for hostname in &data.hostnames {
+            match dialer.resolve(hostname) {
+                Ok(addrs) => data
+                    .listeners
+                    .extend(addrs.into_iter().map(|addr| (addr, TransportType::Tcp))),
+                Err(err) => warn!("could not resolve initial peer {}: {}", peer_id, err),
+            }
+        }
+        if data.listeners.is_empty() {
+            warn!("initial peer {} has no listener, ignoring it", peer_id);
+        }
+        !data.listeners.is_empty()
+    });
 
     let initial_peers = if let Some(bootstrap_peers) = bootstrap_peers {
         //TODO: Remove when we will be able to test the bootstrap peer even if someone else found them full
@@ -314,9 +379,12 @@ pub fn start_protocol_controller(
     };
     peernet_config.max_in_connections = config.max_in_connections;
 
-    let network_controller = Box::new(NetworkControllerImpl::new(PeerNetManager::new(
-        peernet_config,
-    )));
+    let network_controller = Box::new(NetworkControllerImpl::new(
+        PeerNetManager::new(peernet_config),
+        config.socks5_proxy.map(Socks5Proxy::new),
+        envelope_peers,
+        traffic.clone(),
+    ));
 
     let connectivity_thread_handle = start_connectivity_thread(
         PeerId::from_public_key(keypair.get_public_key()),
@@ -330,6 +398,7 @@ pub fn start_protocol_controller(
         (sender_peers, receiver_peers),
         initial_peers,
         peer_db,
+        ban_list,
         storage,
         protocol_channels,
         message_handlers,
@@ -369,7 +438,7 @@ pub fn start_protocol_controller(
         massa_metrics,
     )?;
 
-    let manager = ProtocolManagerImpl::new(connectivity_thread_handle);
+    let manager = ProtocolManagerImpl::new(connectivity_thread_handle, nat_traversal);
 
     Ok((
         Box::new(manager),
@@ -3,7 +3,7 @@ use std::{
     net::SocketAddr,
 };
 
-use massa_protocol_exports::{PeerId, ProtocolError};
+use massa_protocol_exports::{PeerId, ProtocolError, Socks5Proxy};
 use peernet::{
     network_manager::{PeerNetManager, SharedActiveConnections},
     peer::PeerConnectionType,
@@ -12,8 +12,10 @@ use peernet::{
 
 use crate::{
     context::Context,
-    handlers::peer_handler::MassaHandshake,
-    messages::{Message, MessagesHandler, MessagesSerializer},
+    handlers::peer_handler::{models::SharedEnvelopePeers, MassaHandshake},
+    messages::{EnvelopeSerializer, Message, MessagesHandler, MessagesSerializer},
+    relay::open_relay,
+    traffic::{CountingSerializer, SharedTrafficStats},
 };
 
 #[cfg(test)]
@@ -118,6 +120,84 @@ impl ActiveConnectionsTrait for SharedActiveConnections<PeerId> {
     }
 }
 
+/// Connections to the peers, wrapping the messages in an envelope for the peers that decode it
+#[derive(Clone)]
+pub struct PeerConnections {
+    connections: SharedActiveConnections<PeerId>,
+    envelope_peers: SharedEnvelopePeers,
+    /// traffic sent, counted per message type and per peer
+    traffic: SharedTrafficStats,
+}
+
+impl ActiveConnectionsTrait for PeerConnections {
+    fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        high_priority: bool,
+    ) -> Result<(), ProtocolError> {
+        let connections = self.connections.read();
+        let Some(connection) = connections.connections.get(peer_id) else {
+            self.envelope_peers.write().remove(peer_id);
+            return Err(ProtocolError::PeerDisconnected(peer_id.to_string()));
+        };
+        let sent = if self.envelope_peers.read().contains(peer_id) {
+            connection.send_channels.try_send(
+                &CountingSerializer::new(
+                    &EnvelopeSerializer::new(message_serializer),
+                    peer_id,
+                    &self.traffic,
+                ),
+                message,
+                high_priority,
+            )
+        } else {
+            connection.send_channels.try_send(
+                &CountingSerializer::new(message_serializer, peer_id, &self.traffic),
+                message,
+                high_priority,
+            )
+        };
+        sent.map_err(|err| ProtocolError::SendError(err.to_string()))
+    }
+
+    fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(self.clone())
+    }
+
+    fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+        self.connections.get_peer_ids_connected()
+    }
+
+    fn get_peers_connected(
+        &self,
+    ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+        self.connections.get_peers_connected()
+    }
+
+    fn get_peer_ids_out_connection_queue(&self) -> HashSet<SocketAddr> {
+        self.connections.get_peer_ids_out_connection_queue()
+    }
+
+    fn get_nb_out_connections(&self) -> usize {
+        self.connections.get_nb_out_connections()
+    }
+
+    fn get_nb_in_connections(&self) -> usize {
+        self.connections.get_nb_in_connections()
+    }
+
+    fn shutdown_connection(&mut self, peer_id: &PeerId) {
+        self.envelope_peers.write().remove(peer_id);
+        self.connections.shutdown_connection(peer_id);
+    }
+
+    fn get_peers_connections_bandwidth(&self) -> HashMap<String, (u64, u64)> {
+        self.connections.get_peers_connections_bandwidth()
+    }
+}
+
 #[allow(dead_code)]
 #[cfg_attr(test, mockall::automock)]
 pub trait NetworkController: Send + Sync {
@@ -134,6 +214,7 @@ pub trait NetworkController: Send + Sync {
     ) -> Result<(), ProtocolError>;
     fn try_connect(
         &mut self,
+        transport_type: TransportType,
         addr: SocketAddr,
         timeout: std::time::Duration,
     ) -> Result<(), ProtocolError>;
@@ -143,19 +224,37 @@ pub trait NetworkController: Send + Sync {
 
 pub struct NetworkControllerImpl {
     peernet_manager: PeerNetManager<PeerId, Context, MassaHandshake, MessagesHandler>,
+    /// proxy through which the out connections are relayed
+    socks5_proxy: Option<Socks5Proxy>,
+    /// peers to which the messages are sent in an envelope
+    envelope_peers: SharedEnvelopePeers,
+    /// traffic with the peers
+    traffic: SharedTrafficStats,
 }
 
 impl NetworkControllerImpl {
     pub fn new(
         peernet_manager: PeerNetManager<PeerId, Context, MassaHandshake, MessagesHandler>,
+        socks5_proxy: Option<Socks5Proxy>,
+        envelope_peers: SharedEnvelopePeers,
+        traffic: SharedTrafficStats,
     ) -> Self {
-        Self { peernet_manager }
+        Self {
+            peernet_manager,
+            socks5_proxy,
+            envelope_peers,
+            traffic,
+        }
     }
 }
 
 impl NetworkController for NetworkControllerImpl {
     fn get_active_connections(&self) -> Box<dyn ActiveConnectionsTrait> {
-        Box::new(self.peernet_manager.active_connections.clone())
+        Box::new(PeerConnections {
+            connections: self.peernet_manager.active_connections.clone(),
+            envelope_peers: self.envelope_peers.clone(),
+            traffic: self.traffic.clone(),
+        })
     }
 
     fn start_listener(
@@ -180,12 +279,21 @@ impl NetworkController for NetworkControllerImpl {
 
     fn try_connect(
         &mut self,
+        transport_type: TransportType,
         addr: SocketAddr,
         timeout: std::time::Duration,
     ) -> Result<(), ProtocolError> {
-        //TODO: Change when we support multiple transports
+        let addr = match (&self.socks5_proxy, transport_type) {
+            (Some(_), TransportType::Quic) => {
+                return Err(ProtocolError::GeneralProtocolError(
+                    "QUIC connections cannot be relayed through a SOCKS5 proxy".to_string(),
+                ))
+            }
+            (Some(proxy), _) => open_relay(proxy, addr, timeout)?,
+            (None, _) => addr,
+        };
         self.peernet_manager
-            .try_connect(TransportType::Tcp, addr, timeout)
+            .try_connect(transport_type, addr, timeout)
             .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
         Ok(())
     }
@@ -1,4 +1,6 @@
+use crate::handlers::peer_handler::latency::PeerLatencies;
 use crate::handlers::peer_handler::models::{ConnectionMetadata, PeerInfo};
+use crate::handlers::peer_handler::peer_store::PeerStore;
 use std::{
     collections::{HashMap, HashSet},
     net::SocketAddr,
@@ -35,6 +37,14 @@ pub trait PeerDBTrait: Send + Sync {
     fn get_peers_in_test(&self) -> &HashSet<SocketAddr>;
     fn insert_tested_address(&mut self, addr: &SocketAddr, time: massa_time::MassaTime);
     fn get_tested_addresses(&self) -> &HashMap<SocketAddr, massa_time::MassaTime>;
+    fn get_reachability(&self) -> Option<bool>;
+    fn set_reachability(&mut self, reachable: bool);
+    fn get_network_time_offset(&self) -> Option<i64>;
+    fn set_network_time_offset(&mut self, offset: Option<i64>);
+    fn get_peer_store(&self) -> &PeerStore;
+    fn get_peer_store_mut(&mut self) -> &mut PeerStore;
+    fn get_latencies(&self) -> &PeerLatencies;
+    fn get_latencies_mut(&mut self) -> &mut PeerLatencies;
 }
 
 impl Clone for Box<dyn PeerDBTrait> {
@@ -22,8 +22,10 @@ use massa_api_exports::{
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall, Transfer},
+    filter::QueryFilter,
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    operation::{OperationCancellationInfo, OperationInfo, OperationInput, OperationReplacement},
+    rolls::StakingAddressActivation,
     TimeInterval,
 };
 use massa_models::secure_share::SecureShare;
@@ -51,9 +53,11 @@ use thiserror::Error;
 
 pub mod cert_manager;
 mod config;
+mod operation_template;
 pub use config::ClientConfig;
 pub use config::HttpConfig;
 pub use config::WsConfig;
+pub use operation_template::OperationTemplate;
 
 /// Error when creating a new client
 #[derive(Error, Debug)]
@@ -190,6 +194,16 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Returns the activation state of each staking address.
+    pub async fn get_staking_addresses_activation(
+        &self,
+    ) -> RpcResult<Vec<StakingAddressActivation>> {
+        self.http_client
+            .request("get_staking_addresses_activation", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Bans given ip address(es)
     /// No confirmation to expect.
     pub async fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
@@ -226,6 +240,14 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Enable or disable the maintenance mode of the node
+    pub async fn node_set_maintenance_mode(&self, enabled: bool) -> RpcResult<()> {
+        self.http_client
+            .request("node_set_maintenance_mode", rpc_params![enabled])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Returns node peers whitelist IP address(es).
     pub async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         self.http_client
@@ -357,6 +379,30 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Returns whether operations their sender regrets can still be included.
+    pub async fn get_operations_cancellation_status(
+        &self,
+        operation_ids: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationCancellationInfo>> {
+        self.http_client
+            .request(
+                "get_operations_cancellation_status",
+                rpc_params![operation_ids],
+            )
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns true if the operation appears in at least one block.
+    /// An operation unknown to the node is reported as not included.
+    pub async fn is_operation_included(&self, operation_id: OperationId) -> RpcResult<bool> {
+        Ok(self
+            .get_operations(vec![operation_id])
+            .await?
+            .iter()
+            .any(|info| !info.in_blocks.is_empty()))
+    }
+
     /// Returns endorsement(s) information associated to a given list of endorsement(s) ID(s)
     pub async fn get_endorsements(
         &self,
@@ -387,6 +433,25 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Get the operations matching a filter
+    pub async fn get_filtered_operations(
+        &self,
+        filter: QueryFilter,
+    ) -> RpcResult<Vec<OperationInfo>> {
+        self.http_client
+            .request("get_filtered_operations", rpc_params![filter])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Get the events matching a filter
+    pub async fn get_filtered_events(&self, filter: QueryFilter) -> RpcResult<Vec<SCOutputEvent>> {
+        self.http_client
+            .request("get_filtered_events", rpc_params![filter])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
     pub(crate) async fn _get_graph_interval(
@@ -431,6 +496,21 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Broadcasts a pre-built replacement of an operation that is not final yet, from the same sender.
+    pub async fn send_replacement_operation(
+        &self,
+        replaced: OperationId,
+        replacement: OperationInput,
+    ) -> RpcResult<OperationReplacement> {
+        self.http_client
+            .request(
+                "send_replacement_operation",
+                rpc_params![replaced, replacement],
+            )
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// execute read only bytecode
     pub async fn execute_read_only_bytecode(
         &self,
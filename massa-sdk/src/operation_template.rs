@@ -0,0 +1,119 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Unsigned operation templates that can be re-signed with a bumped fee
+
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    operation::{Operation, OperationType},
+};
+
+/// Unsigned operation held by a client until it gets included in a block.
+///
+/// Each signed attempt is a distinct operation with its own id: an attempt must only
+/// be re-signed with a higher fee once the previous one has expired without being
+/// included, otherwise both attempts could end up executed.
+#[derive(Clone, Debug)]
+pub struct OperationTemplate {
+    /// address whose key signs the operation
+    pub sender_address: Address,
+    /// operation payload
+    pub op: OperationType,
+    /// fee of the next signed attempt
+    pub fee: Amount,
+}
+
+impl OperationTemplate {
+    /// Creates a new template
+    pub fn new(sender_address: Address, op: OperationType, fee: Amount) -> Self {
+        OperationTemplate {
+            sender_address,
+            op,
+            fee,
+        }
+    }
+
+    /// Builds the unsigned operation content of an attempt expiring at `expire_period`
+    pub fn to_operation(&self, expire_period: u64) -> Operation {
+        Operation {
+            fee: self.fee,
+            expire_period,
+            start_period: None,
+            fee_payer: None,
+            op: self.op.clone(),
+        }
+    }
+
+    /// Increases the fee by `increment`, capped to `max_fee`.
+    /// Returns false if the fee could not be increased any further.
+    pub fn bump_fee(&mut self, increment: Amount, max_fee: Amount) -> bool {
+        let bumped = self.fee.saturating_add(increment).min(max_fee);
+        if bumped <= self.fee {
+            return false;
+        }
+        self.fee = bumped;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn new_template(fee: &str) -> OperationTemplate {
+        let address =
+            Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+        OperationTemplate::new(
+            address,
+            OperationType::Transaction {
+                recipient_address: address,
+                amount: Amount::from_str("1").unwrap(),
+            },
+            Amount::from_str(fee).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_bump_fee() {
+        let mut template = new_template("0.01");
+        let increment = Amount::from_str("0.01").unwrap();
+        let max_fee = Amount::from_str("0.025").unwrap();
+
+        assert!(template.bump_fee(increment, max_fee));
+        assert_eq!(template.fee, Amount::from_str("0.02").unwrap());
+
+        // the last bump is capped to the maximal fee
+        assert!(template.bump_fee(increment, max_fee));
+        assert_eq!(template.fee, max_fee);
+
+        // the maximal fee is reached: the fee is left unchanged
+        assert!(!template.bump_fee(increment, max_fee));
+        assert_eq!(template.fee, max_fee);
+
+        // attempts are built with the bumped fee
+        let op = template.to_operation(42);
+        assert_eq!(op.fee, max_fee);
+        assert_eq!(op.expire_period, 42);
+    }
+
+    #[test]
+    fn test_bump_fee_never_decreases() {
+        // a maximal fee below the current one must not lower the fee
+        let mut template = new_template("1");
+        assert!(!template.bump_fee(
+            Amount::from_str("0.5").unwrap(),
+            Amount::from_str("0.1").unwrap()
+        ));
+        assert_eq!(template.fee, Amount::from_str("1").unwrap());
+
+        // a zero increment cannot bump the fee
+        assert!(!template.bump_fee(Amount::zero(), Amount::from_str("10").unwrap()));
+        assert_eq!(template.fee, Amount::from_str("1").unwrap());
+
+        // the fee saturates instead of overflowing
+        let mut template = new_template("1");
+        assert!(template.bump_fee(Amount::MAX, Amount::MAX));
+        assert_eq!(template.fee, Amount::MAX);
+    }
+}
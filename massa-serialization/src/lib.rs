@@ -17,6 +17,12 @@ use num::rational::Ratio;
 use num::Integer;
 use thiserror::Error;
 
+mod min_be_int;
+mod vec;
+
+pub use min_be_int::*;
+pub use vec::*;
+
 #[non_exhaustive]
 #[derive(Display, Error, Debug, Clone)]
 pub enum SerializeError {
@@ -26,6 +32,8 @@ pub enum SerializeError {
     GeneralError(String),
     /// String too big {0},
     StringTooBig(String),
+    /// Buffer error {0}
+    BufferError(String),
 }
 
 #[derive(Clone, Error)]
@@ -0,0 +1,150 @@
+//! Integers serialized with the minimal amount of big endian bytes needed to represent a maximal value,
+//! and fixed size reads from byte slices.
+
+use crate::SerializeError;
+use std::convert::TryInto;
+use std::mem::size_of;
+
+/// Serialize min big endian integer
+pub trait SerializeMinBEInt {
+    /// serializes with the minimal amount of big endian bytes
+    fn to_be_bytes_min(self, max_value: Self) -> Result<Vec<u8>, SerializeError>;
+}
+
+impl SerializeMinBEInt for u32 {
+    fn to_be_bytes_min(self, max_value: Self) -> Result<Vec<u8>, SerializeError> {
+        if self > max_value {
+            return Err(SerializeError::NumberTooBig("integer out of bounds".into()));
+        }
+        let skip_bytes = (max_value.leading_zeros() as usize) / 8;
+        Ok(self.to_be_bytes()[skip_bytes..].to_vec())
+    }
+}
+
+impl SerializeMinBEInt for u64 {
+    fn to_be_bytes_min(self, max_value: Self) -> Result<Vec<u8>, SerializeError> {
+        if self > max_value {
+            return Err(SerializeError::NumberTooBig("integer out of bounds".into()));
+        }
+        let skip_bytes = (max_value.leading_zeros() as usize) / 8;
+        Ok(self.to_be_bytes()[skip_bytes..].to_vec())
+    }
+}
+
+/// Deserialize min big endian
+pub trait DeserializeMinBEInt: Sized {
+    /// Deserializes a minimally sized big endian integer to Self from the provided buffer and checks that its value is within given bounds.
+    /// In case of success, return the deserialized data and the number of bytes read
+    fn from_be_bytes_min(buffer: &[u8], max_value: Self) -> Result<(Self, usize), SerializeError>;
+}
+
+/// Compute the minimal big endian deserialization size
+pub const fn u32_be_bytes_min_length(max_value: u32) -> usize {
+    size_of::<u32>() - (max_value.leading_zeros() as usize) / 8
+}
+
+/// Compute the minimal big endian deserialization size
+pub const fn u64_be_bytes_min_length(max_value: u64) -> usize {
+    size_of::<u64>() - (max_value.leading_zeros() as usize) / 8
+}
+impl DeserializeMinBEInt for u32 {
+    fn from_be_bytes_min(buffer: &[u8], max_value: Self) -> Result<(Self, usize), SerializeError> {
+        let read_bytes = u32_be_bytes_min_length(max_value);
+        let skip_bytes = size_of::<Self>() - read_bytes;
+        if buffer.len() < read_bytes {
+            return Err(SerializeError::BufferError("unexpected buffer END".into()));
+        }
+        let mut buf = [0u8; size_of::<Self>()];
+        buf[skip_bytes..].clone_from_slice(&buffer[..read_bytes]);
+        let res = u32::from_be_bytes(buf);
+        if res > max_value {
+            return Err(SerializeError::NumberTooBig(
+                "integer outside of bounds".into(),
+            ));
+        }
+        Ok((res, read_bytes))
+    }
+}
+
+impl DeserializeMinBEInt for u64 {
+    fn from_be_bytes_min(buffer: &[u8], max_value: Self) -> Result<(Self, usize), SerializeError> {
+        let read_bytes = u64_be_bytes_min_length(max_value);
+        let skip_bytes = size_of::<Self>() - read_bytes;
+        if buffer.len() < read_bytes {
+            return Err(SerializeError::BufferError("unexpected buffer END".into()));
+        }
+        let mut buf = [0u8; size_of::<Self>()];
+        buf[skip_bytes..].clone_from_slice(&buffer[..read_bytes]);
+        let res = u64::from_be_bytes(buf);
+        if res > max_value {
+            return Err(SerializeError::NumberTooBig(
+                "integer outside of bounds".into(),
+            ));
+        }
+        Ok((res, read_bytes))
+    }
+}
+
+/// array from slice
+pub fn array_from_slice<const ARRAY_SIZE: usize>(
+    buffer: &[u8],
+) -> Result<[u8; ARRAY_SIZE], SerializeError> {
+    if buffer.len() < ARRAY_SIZE {
+        return Err(SerializeError::BufferError(
+            "slice too small to extract array".into(),
+        ));
+    }
+    buffer[..ARRAY_SIZE].try_into().map_err(|err| {
+        SerializeError::BufferError(format!("could not extract array from slice: {}", err))
+    })
+}
+
+/// `u8` from slice
+pub fn u8_from_slice(buffer: &[u8]) -> Result<u8, SerializeError> {
+    if buffer.is_empty() {
+        return Err(SerializeError::BufferError(
+            "could not read u8 from empty buffer".into(),
+        ));
+    }
+    Ok(buffer[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_be_min() {
+        let x32 = 70_000u32;
+        let x64 = 10_000_000_000u64;
+
+        // serialize
+        let mut res: Vec<u8> = Vec::new();
+        res.extend(x32.to_be_bytes_min(70_001).unwrap());
+        assert_eq!(res.len(), 3);
+        res.extend(x64.to_be_bytes_min(10_000_000_001).unwrap());
+        assert_eq!(res.len(), 3 + 5);
+
+        // serialize fail bounds
+        assert!(x32.to_be_bytes_min(69_999).is_err());
+        assert!(x64.to_be_bytes_min(9_999_999_999).is_err());
+
+        // deserialize
+        let buf = res.as_slice();
+        let mut cursor = 0;
+        let (out_x32, delta) = u32::from_be_bytes_min(&buf[cursor..], 70_001).unwrap();
+        assert_eq!(out_x32, x32);
+        cursor += delta;
+        let (out_x64, delta) = u64::from_be_bytes_min(&buf[cursor..], 10_000_000_001).unwrap();
+        assert_eq!(out_x64, x64);
+        cursor += delta;
+        assert_eq!(cursor, buf.len());
+    }
+
+    #[test]
+    fn test_array_from_slice_with_zero_u64() {
+        let zero: u64 = 0;
+        let res = array_from_slice(&zero.to_be_bytes()).unwrap();
+        assert_eq!(zero, u64::from_be_bytes(res));
+    }
+}
@@ -0,0 +1,362 @@
+//! Length-prefixed collections, the length being bounded at deserialization.
+
+use crate::{Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
+use nom::{
+    error::{context, ContextError, ParseError},
+    multi::{length_count, length_data},
+    IResult, Parser,
+};
+use std::marker::PhantomData;
+use std::ops::Bound;
+
+/// Basic `Vec<u8>` serializer
+#[derive(Clone)]
+pub struct VecU8Serializer {
+    len_serializer: U64VarIntSerializer,
+}
+
+impl VecU8Serializer {
+    /// Creates a new `VecU8Serializer`
+    pub fn new() -> Self {
+        Self {
+            len_serializer: U64VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Default for VecU8Serializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<Vec<u8>> for VecU8Serializer {
+    /// ```
+    /// use std::ops::Bound::Included;
+    /// use massa_serialization::Serializer;
+    /// use massa_serialization::VecU8Serializer;
+    ///
+    /// let vec = vec![1, 2, 3];
+    /// let mut buffer = Vec::new();
+    /// let serializer = VecU8Serializer::new();
+    /// serializer.serialize(&vec, &mut buffer).unwrap();
+    /// ```
+    fn serialize(&self, value: &Vec<u8>, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        let len: u64 = value.len().try_into().map_err(|err| {
+            SerializeError::NumberTooBig(format!("too many entries data in VecU8: {}", err))
+        })?;
+        self.len_serializer.serialize(&len, buffer)?;
+        buffer.extend(value);
+        Ok(())
+    }
+}
+
+/// Basic `Vec<u8>` deserializer
+#[derive(Clone)]
+pub struct VecU8Deserializer {
+    varint_u64_deserializer: U64VarIntDeserializer,
+}
+
+impl VecU8Deserializer {
+    /// Creates a new `VecU8Deserializer`
+    pub const fn new(min_length: Bound<u64>, max_length: Bound<u64>) -> Self {
+        Self {
+            varint_u64_deserializer: U64VarIntDeserializer::new(min_length, max_length),
+        }
+    }
+}
+
+impl Deserializer<Vec<u8>> for VecU8Deserializer {
+    /// ```
+    /// use std::ops::Bound::Included;
+    /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
+    /// use massa_serialization::{VecU8Serializer, VecU8Deserializer};
+    ///
+    /// let vec = vec![1, 2, 3];
+    /// let mut serialized = Vec::new();
+    /// let serializer = VecU8Serializer::new();
+    /// let deserializer = VecU8Deserializer::new(Included(0), Included(1000000));
+    /// serializer.serialize(&vec, &mut serialized).unwrap();
+    /// let (rest, vec_deser) = deserializer.deserialize::<DeserializeError>(&serialized).unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(vec, vec_deser);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Vec<u8>, E> {
+        context("Failed Vec<u8> deserialization", |input| {
+            length_data(|input| self.varint_u64_deserializer.deserialize(input))(input)
+        })
+        .map(|res| res.to_vec())
+        .parse(buffer)
+    }
+}
+
+/// Basic `Vec<_>` serializer
+#[derive(Clone)]
+pub struct VecSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    len_serializer: U64VarIntSerializer,
+    data_serializer: ST,
+    phantom_t: PhantomData<T>,
+}
+
+impl<T, ST> VecSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    /// Creates a new `VecSerializer`
+    pub fn new(data_serializer: ST) -> Self {
+        Self {
+            len_serializer: U64VarIntSerializer::new(),
+            data_serializer,
+            phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<T, ST> Serializer<Vec<T>> for VecSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    fn serialize(&self, value: &Vec<T>, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        self.len_serializer
+            .serialize(&(value.len() as u64), buffer)?;
+        for elem in value {
+            self.data_serializer.serialize(elem, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Basic `Vec<_>` deserializer
+#[derive(Clone)]
+pub struct VecDeserializer<T, ST>
+where
+    ST: Deserializer<T>,
+{
+    varint_u64_deserializer: U64VarIntDeserializer,
+    data_deserializer: ST,
+    phantom_t: PhantomData<T>,
+}
+
+impl<T, ST> VecDeserializer<T, ST>
+where
+    ST: Deserializer<T>,
+{
+    /// Creates a new `VecDeserializer`
+    pub const fn new(
+        data_deserializer: ST,
+        min_length: Bound<u64>,
+        max_length: Bound<u64>,
+    ) -> Self {
+        Self {
+            varint_u64_deserializer: U64VarIntDeserializer::new(min_length, max_length),
+            data_deserializer,
+            phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<T, ST> Deserializer<Vec<T>> for VecDeserializer<T, ST>
+where
+    ST: Deserializer<T>,
+{
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Vec<T>, E> {
+        context("Failed Vec<_> deserialization", |input| {
+            length_count(
+                context("length", |input| {
+                    self.varint_u64_deserializer.deserialize(input)
+                }),
+                context("data", |input| self.data_deserializer.deserialize(input)),
+            )(input)
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeserializeError, OptionDeserializer, OptionSerializer};
+    use std::ops::Bound::Included;
+
+    #[test]
+    fn vec_u8() {
+        let vec: Vec<u8> = vec![9, 8, 7];
+        let vec_u8_serializer = VecU8Serializer::new();
+        let vec_u8_deserializer = VecU8Deserializer::new(Included(u64::MIN), Included(u64::MAX));
+        let mut serialized = Vec::new();
+        vec_u8_serializer.serialize(&vec, &mut serialized).unwrap();
+        let (rest, new_vec) = vec_u8_deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(vec, new_vec);
+    }
+
+    #[test]
+    fn vec_u8_big_length() {
+        let vec: Vec<u8> = vec![9, 8, 7];
+        let len: u64 = 10;
+        let mut serialized = Vec::new();
+        U64VarIntSerializer::new()
+            .serialize(&len, &mut serialized)
+            .unwrap();
+        serialized.extend(vec);
+        let vec_u8_deserializer = VecU8Deserializer::new(Included(u64::MIN), Included(u64::MAX));
+        let _ = vec_u8_deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .expect_err("Should fail too long size");
+    }
+
+    #[test]
+    fn vec_u8_min_length() {
+        let vec: Vec<u8> = vec![9, 8, 7];
+        let len: u64 = 1;
+        let mut serialized = Vec::new();
+        U64VarIntSerializer::new()
+            .serialize(&len, &mut serialized)
+            .unwrap();
+        serialized.extend(vec);
+        let vec_u8_deserializer = VecU8Deserializer::new(Included(u64::MIN), Included(u64::MAX));
+        let (rest, res) = vec_u8_deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert_eq!(rest, &[8, 7]);
+        assert_eq!(res, &[9])
+    }
+
+    #[test]
+    fn vec_max_length() {
+        let vec: Vec<u64> = vec![1, 2, 3];
+        let serializer = VecSerializer::new(U64VarIntSerializer::new());
+        let mut serialized = Vec::new();
+        serializer.serialize(&vec, &mut serialized).unwrap();
+
+        let deserializer = VecDeserializer::new(
+            U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            Included(0),
+            Included(3),
+        );
+        let (rest, new_vec) = deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(vec, new_vec);
+
+        let deserializer = VecDeserializer::new(
+            U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            Included(0),
+            Included(2),
+        );
+        deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .expect_err("Should fail with more elements than the max length");
+    }
+
+    #[test]
+    fn option_vec_u8() {
+        let serializer = OptionSerializer::new(VecU8Serializer::new());
+        let deserializer =
+            OptionDeserializer::new(VecU8Deserializer::new(Included(0), Included(10)));
+        for value in [None, Some(vec![]), Some(vec![1, 2, 3])] {
+            let mut serialized = Vec::new();
+            serializer.serialize(&value, &mut serialized).unwrap();
+            let (rest, new_value) = deserializer
+                .deserialize::<DeserializeError>(&serialized)
+                .unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(value, new_value);
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Entry {
+        id: u64,
+        data: Option<Vec<u8>>,
+    }
+
+    struct EntrySerializer {
+        id_serializer: U64VarIntSerializer,
+        data_serializer: OptionSerializer<Vec<u8>, VecU8Serializer>,
+    }
+
+    impl Serializer<Entry> for EntrySerializer {
+        fn serialize(&self, value: &Entry, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+            self.id_serializer.serialize(&value.id, buffer)?;
+            self.data_serializer.serialize(&value.data, buffer)
+        }
+    }
+
+    #[derive(Clone)]
+    struct EntryDeserializer {
+        id_deserializer: U64VarIntDeserializer,
+        data_deserializer: OptionDeserializer<Vec<u8>, VecU8Deserializer>,
+    }
+
+    impl Deserializer<Entry> for EntryDeserializer {
+        fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+            &self,
+            buffer: &'a [u8],
+        ) -> IResult<&'a [u8], Entry, E> {
+            let (rest, id) = self.id_deserializer.deserialize(buffer)?;
+            let (rest, data) = self.data_deserializer.deserialize(rest)?;
+            Ok((rest, Entry { id, data }))
+        }
+    }
+
+    #[test]
+    fn vec_nested_struct() {
+        let entries = vec![
+            Entry {
+                id: 1,
+                data: Some(vec![9, 8, 7]),
+            },
+            Entry { id: 2, data: None },
+        ];
+        let serializer = VecSerializer::new(EntrySerializer {
+            id_serializer: U64VarIntSerializer::new(),
+            data_serializer: OptionSerializer::new(VecU8Serializer::new()),
+        });
+        let deserializer = VecDeserializer::new(
+            EntryDeserializer {
+                id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+                data_deserializer: OptionDeserializer::new(VecU8Deserializer::new(
+                    Included(0),
+                    Included(2),
+                )),
+            },
+            Included(0),
+            Included(10),
+        );
+
+        let mut serialized = Vec::new();
+        serializer.serialize(&entries, &mut serialized).unwrap();
+        // the data of the first entry is longer than allowed
+        deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .expect_err("Should fail with a nested vec above its max length");
+
+        let entries = vec![
+            Entry {
+                id: 1,
+                data: Some(vec![9, 8]),
+            },
+            Entry { id: 2, data: None },
+        ];
+        let mut serialized = Vec::new();
+        serializer.serialize(&entries, &mut serialized).unwrap();
+        let (rest, new_entries) = deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(entries, new_entries);
+    }
+}
@@ -1,5 +1,9 @@
-use std::{collections::btree_map, collections::hash_map, collections::BTreeMap, ops::RangeBounds};
+use std::{
+    collections::btree_map, collections::hash_map, collections::BTreeMap, collections::HashMap,
+    ops::RangeBounds, sync::Arc,
+};
 
+use massa_hash::Hash;
 use massa_models::{
     address::Address,
     block::SecureShareBlock,
@@ -10,6 +14,14 @@ use massa_models::{
     slot::Slot,
 };
 
+/// Operation list shared by the stored blocks having the same operation merkle root
+struct StoredBody {
+    /// ids of the operations of the body
+    operations: Arc<Vec<OperationId>>,
+    /// ids of the stored blocks referencing the body: the body is dropped with the last of them
+    block_ids: PreHashSet<BlockId>,
+}
+
 /// Container for all blocks and different indexes.
 /// Note: The structure can evolve and store more indexes.
 #[derive(Default)]
@@ -20,6 +32,8 @@ pub struct BlockIndexes {
     index_by_creator: PreHashMap<Address, PreHashSet<BlockId>>,
     /// Structure mapping slot with their block id
     index_by_slot: BTreeMap<Slot, PreHashSet<BlockId>>,
+    /// Operation lists of the stored blocks, keyed by their merkle root
+    bodies: HashMap<Hash, StoredBody>,
     /// Structure mapping operation id with ids of blocks they are contained in
     index_by_op: PreHashMap<OperationId, PreHashSet<BlockId>>,
     /// Structure mapping endorsement id with ids of blocks they are contained in
@@ -46,6 +60,18 @@ impl BlockIndexes {
                 .or_default()
                 .insert(block.id);
 
+            // share the operation list with the blocks having the same body
+            let body = self
+                .bodies
+                .entry(block.content.header.content.operation_merkle_root)
+                .or_insert_with(|| StoredBody {
+                    operations: Arc::new(block.content.operations.clone()),
+                    block_ids: PreHashSet::default(),
+                });
+            if *body.operations == block.content.operations {
+                body.block_ids.insert(block.id);
+            }
+
             // update index_by_op
             for op in &block.content.operations {
                 self.index_by_op.entry(*op).or_default().insert(block.id);
@@ -88,6 +114,17 @@ impl BlockIndexes {
                 }
             }
 
+            // release the shared operation list
+            if let hash_map::Entry::Occupied(mut occ) = self
+                .bodies
+                .entry(b.content.header.content.operation_merkle_root)
+            {
+                occ.get_mut().block_ids.remove(&b.id);
+                if occ.get().block_ids.is_empty() {
+                    occ.remove();
+                }
+            }
+
             // update index_by_op
             for op in &b.content.operations {
                 if let hash_map::Entry::Occupied(mut occ) = self.index_by_op.entry(*op) {
@@ -177,6 +214,39 @@ impl BlockIndexes {
         self.index_by_op.get(id)
     }
 
+    /// Get the operation list of a block, shared with the stored blocks having the same body.
+    /// Arguments:
+    /// - id: the ID of the block
+    ///
+    /// Returns:
+    /// - the ids of the operations of the block if it is stored, None otherwise
+    pub fn get_block_operations(&self, id: &BlockId) -> Option<Arc<Vec<OperationId>>> {
+        let block = self.blocks.get(id)?;
+        match self
+            .bodies
+            .get(&block.content.header.content.operation_merkle_root)
+        {
+            Some(body) if body.block_ids.contains(id) => Some(body.operations.clone()),
+            // the block lists other operations than the body stored under its merkle root
+            _ => Some(Arc::new(block.content.operations.clone())),
+        }
+    }
+
+    /// Get the block ids of the blocks sharing a body.
+    /// Arguments:
+    /// - operation_merkle_root: the merkle root of the operations of the body
+    ///
+    /// Returns:
+    /// - the block ids referencing the body if it is stored, None otherwise
+    pub fn get_blocks_by_operation_merkle_root(
+        &self,
+        operation_merkle_root: &Hash,
+    ) -> Option<&PreHashSet<BlockId>> {
+        self.bodies
+            .get(operation_merkle_root)
+            .map(|body| &body.block_ids)
+    }
+
     /// Get the block ids of the blocks containing a given endorsement.
     /// Arguments:
     /// - id: the ID of the endorsement
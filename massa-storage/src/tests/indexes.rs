@@ -46,3 +46,46 @@ fn test_block_fail_find() {
         .get_blocks_created_by(&Address::from_public_key(&keypair2.get_public_key()))
         .is_none());
 }
+
+#[test]
+fn test_block_body_shared_by_merkle_root() {
+    let mut storage = Storage::create_root();
+    let slot = Slot::new(1, 0);
+    let keypair = KeyPair::generate(0).unwrap();
+    // two equivocating headers with the same (empty) operation list
+    let block = create_empty_block(&keypair, &slot);
+    let equivocation = create_empty_block(&keypair, &Slot::new(2, 0));
+    let merkle_root = block.content.header.content.operation_merkle_root;
+
+    storage.store_block(block.clone());
+    storage.store_block(equivocation.clone());
+    {
+        let blocks = storage.read_blocks();
+        let sharing = blocks
+            .get_blocks_by_operation_merkle_root(&merkle_root)
+            .unwrap();
+        assert_eq!(sharing.len(), 2);
+        assert!(std::sync::Arc::ptr_eq(
+            &blocks.get_block_operations(&block.id).unwrap(),
+            &blocks.get_block_operations(&equivocation.id).unwrap()
+        ));
+    }
+
+    // pruning one header keeps the body of the other
+    storage.drop_block_refs(&[block.id].into_iter().collect());
+    {
+        let blocks = storage.read_blocks();
+        let sharing = blocks
+            .get_blocks_by_operation_merkle_root(&merkle_root)
+            .unwrap();
+        assert_eq!(sharing.len(), 1);
+        assert!(sharing.contains(&equivocation.id));
+        assert!(blocks.get_block_operations(&equivocation.id).is_some());
+    }
+
+    storage.drop_block_refs(&[equivocation.id].into_iter().collect());
+    assert!(storage
+        .read_blocks()
+        .get_blocks_by_operation_merkle_root(&merkle_root)
+        .is_none());
+}
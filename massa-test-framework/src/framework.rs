@@ -95,6 +95,8 @@ pub trait TestUniverse {
             fee: Amount::default(),
             op,
             expire_period,
+            start_period: None,
+            fee_payer: None,
         };
         Operation::new_verifiable(content, OperationSerializer::new(), keypair, chain_id).unwrap()
     }
@@ -5,11 +5,13 @@
 
 mod error;
 mod mapping_grpc;
+mod slot_ticker;
 pub use error::TimeError;
 use massa_serialization::{Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
 use nom::error::{context, ContextError, ParseError};
 use nom::IResult;
 use serde::{Deserialize, Serialize};
+pub use slot_ticker::SlotTicker;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::ops::Bound;
@@ -0,0 +1,217 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Computation of the successive slot boundaries, shared by the modules acting at each slot.
+//!
+//! Slots are identified by their index since genesis: slot `(period, thread)` has index
+//! `period * thread_count + thread`, and happens at `genesis_timestamp + index * t0 / thread_count`.
+
+use std::time::Instant;
+
+use crate::{MassaTime, TimeError};
+
+/// Yields the index and time of the next slot each time it is asked for one.
+///
+/// Every tick is recomputed from the current absolute time: waiting does not accumulate drift,
+/// and the slots that passed while the node was suspended or too slow are skipped.
+/// A tick is never yielded twice, even if the system clock goes backwards.
+#[derive(Debug, Clone)]
+pub struct SlotTicker {
+    genesis_timestamp: MassaTime,
+    slot_duration: MassaTime,
+    offset: MassaTime,
+    initial_delay: MassaTime,
+    min_index: u64,
+    previous_index: Option<u64>,
+}
+
+impl SlotTicker {
+    /// Creates a ticker yielding each slot at its timestamp.
+    ///
+    /// # Arguments
+    /// * `genesis_timestamp`: time of the genesis slot
+    /// * `t0`: time between two periods in the same thread
+    /// * `thread_count`: number of threads
+    ///
+    /// ```
+    /// # use massa_time::*;
+    /// let mut ticker = SlotTicker::new(MassaTime::from_millis(0), MassaTime::from_millis(16000), 32).unwrap();
+    /// // 10.2s after genesis, the closest slot is the 20th one (period 0, thread 20)
+    /// let (index, timestamp) = ticker.next_tick_at(MassaTime::from_millis(10200)).unwrap();
+    /// assert_eq!(index, 20);
+    /// assert_eq!(timestamp, MassaTime::from_millis(10000));
+    /// ```
+    pub fn new(
+        genesis_timestamp: MassaTime,
+        t0: MassaTime,
+        thread_count: u8,
+    ) -> Result<Self, TimeError> {
+        let slot_duration = t0.checked_div_u64(thread_count as u64)?;
+        if slot_duration == MassaTime::from_millis(0) {
+            return Err(TimeError::CheckedOperationError(
+                "slot duration must be strictly positive".to_string(),
+            ));
+        }
+        Ok(SlotTicker {
+            genesis_timestamp,
+            slot_duration,
+            offset: MassaTime::from_millis(0),
+            initial_delay: MassaTime::from_millis(0),
+            min_index: 0,
+            previous_index: None,
+        })
+    }
+
+    /// Yields the slots `offset` before their timestamp
+    #[must_use]
+    pub fn with_offset(mut self, offset: MassaTime) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Shifts the first computed slot by `initial_delay`,
+    /// to prevent acting twice at the same slot on restart with clock skew
+    #[must_use]
+    pub fn with_initial_delay(mut self, initial_delay: MassaTime) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Never yields slots with an index lower than `min_index`
+    #[must_use]
+    pub fn with_min_index(mut self, min_index: u64) -> Self {
+        self.min_index = min_index;
+        self
+    }
+
+    /// Gets the index of the next slot and the instant at which it should be processed.
+    pub fn next_tick(&mut self) -> Result<(u64, Instant), TimeError> {
+        let (index, timestamp) = self.next_tick_at(MassaTime::now())?;
+        Ok((index, timestamp.estimate_instant()?))
+    }
+
+    /// Gets the index of the next slot and the time at which it should be processed,
+    /// as seen at absolute time `now`.
+    ///
+    /// ```
+    /// # use massa_time::*;
+    /// let mut ticker = SlotTicker::new(MassaTime::from_millis(0), MassaTime::from_millis(1000), 2).unwrap();
+    /// assert_eq!(ticker.next_tick_at(MassaTime::from_millis(1000)).unwrap().0, 2);
+    /// // the clock went backwards: the next slot is yielded anyway
+    /// assert_eq!(ticker.next_tick_at(MassaTime::from_millis(600)).unwrap().0, 3);
+    /// // the node was suspended: the slots in-between are skipped
+    /// assert_eq!(ticker.next_tick_at(MassaTime::from_millis(5000)).unwrap().0, 10);
+    /// ```
+    pub fn next_tick_at(&mut self, now: MassaTime) -> Result<(u64, MassaTime), TimeError> {
+        // if it's the first computed slot, add a time shift to prevent double-processing on restart with clock skew
+        let base_time = if self.previous_index.is_none() {
+            now.saturating_add(self.initial_delay)
+        } else {
+            now
+        };
+
+        // get closest slot according to the current absolute time
+        let mut index = self.closest_index(base_time)?;
+
+        // ignore the slots before the minimal one
+        index = index.max(self.min_index);
+
+        // protection against double-processing on unexpected system clock adjustment
+        if let Some(previous_index) = self.previous_index {
+            if index <= previous_index {
+                index = previous_index
+                    .checked_add(1)
+                    .ok_or(TimeError::TimeOverflowError)?;
+            }
+        }
+        self.previous_index = Some(index);
+
+        let timestamp = self
+            .genesis_timestamp
+            .checked_add(self.slot_duration.checked_mul(index)?)?
+            .saturating_sub(self.offset);
+        Ok((index, timestamp))
+    }
+
+    /// Index of the slot closest to `timestamp`
+    fn closest_index(&self, timestamp: MassaTime) -> Result<u64, TimeError> {
+        // we are before genesis
+        if timestamp < self.genesis_timestamp {
+            return Ok(0);
+        }
+        let elapsed = timestamp.saturating_sub(self.genesis_timestamp);
+        let latest_past_index = elapsed.as_millis() / self.slot_duration.as_millis();
+        let delta_t = elapsed.checked_rem_time(self.slot_duration)?;
+
+        // round up if more than half of the slot duration has passed since the latest past slot
+        if delta_t.checked_mul(2)? <= self.slot_duration {
+            Ok(latest_past_index)
+        } else {
+            latest_past_index
+                .checked_add(1)
+                .ok_or(TimeError::TimeOverflowError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_ticker_matches_slot_boundaries() {
+        let mut ticker =
+            SlotTicker::new(MassaTime::from_millis(1000), MassaTime::from_millis(300), 3).unwrap();
+        // before genesis
+        assert_eq!(
+            ticker.next_tick_at(MassaTime::from_millis(0)).unwrap(),
+            (0, MassaTime::from_millis(1000))
+        );
+        // exactly half a slot after slot 1 rounds down
+        assert_eq!(
+            ticker.next_tick_at(MassaTime::from_millis(1150)).unwrap(),
+            (1, MassaTime::from_millis(1100))
+        );
+        // more than half a slot after slot 1 rounds up
+        let mut ticker =
+            SlotTicker::new(MassaTime::from_millis(1000), MassaTime::from_millis(300), 3).unwrap();
+        assert_eq!(
+            ticker.next_tick_at(MassaTime::from_millis(1151)).unwrap(),
+            (2, MassaTime::from_millis(1200))
+        );
+    }
+
+    #[test]
+    fn test_slot_ticker_never_repeats_a_slot() {
+        let mut ticker =
+            SlotTicker::new(MassaTime::from_millis(0), MassaTime::from_millis(1000), 4).unwrap();
+        let mut previous = None;
+        for now in [1000, 1000, 900, 1260, 100, 7000] {
+            let (index, _) = ticker.next_tick_at(MassaTime::from_millis(now)).unwrap();
+            assert!(previous < Some(index));
+            previous = Some(index);
+        }
+        // the suspend skipped the slots in-between
+        assert_eq!(previous, Some(28));
+    }
+
+    #[test]
+    fn test_slot_ticker_options() {
+        let mut ticker =
+            SlotTicker::new(MassaTime::from_millis(0), MassaTime::from_millis(1000), 2)
+                .unwrap()
+                .with_offset(MassaTime::from_millis(500))
+                .with_initial_delay(MassaTime::from_millis(2000))
+                .with_min_index(10);
+        // the initial delay applies to the first slot only
+        assert_eq!(
+            ticker.next_tick_at(MassaTime::from_millis(1000)).unwrap(),
+            (10, MassaTime::from_millis(4500))
+        );
+        assert_eq!(
+            ticker.next_tick_at(MassaTime::from_millis(6000)).unwrap(),
+            (12, MassaTime::from_millis(5500))
+        );
+        // a null slot duration is rejected
+        assert!(SlotTicker::new(MassaTime::from_millis(0), MassaTime::from_millis(1), 2).is_err());
+    }
+}
@@ -1,7 +1,14 @@
-use crate::versioning::{Advance, ComponentState, MipInfo, MipState};
+use std::collections::BTreeMap;
 
-use massa_models::config::VERSIONING_THRESHOLD_TRANSITION_ACCEPTED;
+use crate::versioning::{
+    Advance, ComponentState, MipComponent, MipInfo, MipState, MipStatsConfig, MipStore,
+};
+
+use massa_models::config::{
+    MIP_STORE_STATS_BLOCK_CONSIDERED, VERSIONING_THRESHOLD_TRANSITION_ACCEPTED,
+};
 use massa_time::MassaTime;
+use num::rational::Ratio;
 
 // TODO: rename versioning_info
 pub fn advance_state_until(at_state: ComponentState, versioning_info: &MipInfo) -> MipState {
@@ -61,3 +68,23 @@ pub fn advance_state_until(at_state: ComponentState, versioning_info: &MipInfo)
     // Active
     state
 }
+
+/// MIP store in which the given component versions are active from the first milliseconds of the epoch
+pub fn mip_store_with_active_components(components: BTreeMap<MipComponent, u32>) -> MipStore {
+    let mip_info = MipInfo {
+        name: "MIP-TEST".to_string(),
+        version: 1,
+        components,
+        start: MassaTime::from_millis(2),
+        timeout: MassaTime::from_millis(5),
+        activation_delay: MassaTime::from_millis(2),
+    };
+    let mip_state =
+        advance_state_until(ComponentState::active(MassaTime::from_millis(0)), &mip_info);
+    let mip_stats_config = MipStatsConfig {
+        block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+        warn_announced_version_ratio: Ratio::new_raw(30, 100),
+    };
+    MipStore::try_from(([(mip_info, mip_state)], mip_stats_config))
+        .expect("could not create the MIP store")
+}
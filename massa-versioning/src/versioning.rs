@@ -21,6 +21,7 @@ use massa_models::config::MIP_STORE_STATS_BLOCK_CONSIDERED;
 use massa_models::config::VERSIONING_ACTIVATION_DELAY_MIN;
 use massa_models::config::VERSIONING_THRESHOLD_TRANSITION_ACCEPTED;
 use massa_models::error::ModelsError;
+use massa_models::operation::Operation;
 use massa_models::slot::Slot;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_serialization::{DeserializeError, Deserializer, SerializeError, Serializer};
@@ -45,6 +46,7 @@ pub enum MipComponent {
     Block,
     VM,
     FinalStateHashKind,
+    Operation,
     #[doc(hidden)]
     #[num_enum(default)]
     __Nonexhaustive,
@@ -641,6 +643,13 @@ impl MipStore {
         guard.get_latest_component_version_at(component, ts)
     }
 
+    /// Check if the format of an operation is accepted at given timestamp (e.g. slot):
+    /// the version of the Operation component it requires must be active
+    pub fn is_operation_format_active_at(&self, operation: &Operation, ts: MassaTime) -> bool {
+        operation.get_format_version()
+            <= self.get_latest_component_version_at(&MipComponent::Operation, ts)
+    }
+
     /// Get all versions in 'Active state' for the given MipComponent
     pub(crate) fn get_all_active_component_versions(&self, component: &MipComponent) -> Vec<u32> {
         let guard = self.0.read();
@@ -1595,9 +1604,13 @@ mod test {
     use std::sync::Arc;
     use tempfile::tempdir;
 
-    use crate::test_helpers::versioning_helpers::advance_state_until;
+    use crate::test_helpers::versioning_helpers::{
+        advance_state_until, mip_store_with_active_components,
+    };
 
+    use massa_models::amount::Amount;
     use massa_models::config::{MIP_STORE_STATS_BLOCK_CONSIDERED, T0, THREAD_COUNT};
+    use massa_models::operation::{OperationType, EXTENDED_OPERATION_FORMAT_VERSION};
     use massa_models::timeslots::get_closest_slot_to_timestamp;
 
     // Only for unit tests
@@ -2266,6 +2279,41 @@ mod test {
         assert!(mip_store.is_ok());
     }
 
+    #[test]
+    fn test_operation_format_activation() {
+        // Test that the extended operation formats are only accepted once the Operation component is active
+
+        let mip_store = mip_store_with_active_components(BTreeMap::from([(
+            MipComponent::Operation,
+            EXTENDED_OPERATION_FORMAT_VERSION,
+        )]));
+
+        let original = Operation {
+            fee: Amount::zero(),
+            expire_period: 10,
+            start_period: None,
+            fee_payer: None,
+            op: OperationType::RollBuy { roll_count: 1 },
+        };
+        let deferred = Operation {
+            start_period: Some(5),
+            ..original.clone()
+        };
+        let name = Operation {
+            op: OperationType::RegisterName {
+                name: "massa".to_string(),
+            },
+            ..original.clone()
+        };
+        let (before, after) = (MassaTime::from_millis(0), MassaTime::from_millis(1000));
+        assert!(mip_store.is_operation_format_active_at(&original, before));
+        assert!(mip_store.is_operation_format_active_at(&original, after));
+        for extended in [&deferred, &name] {
+            assert!(!mip_store.is_operation_format_active_at(extended, before));
+            assert!(mip_store.is_operation_format_active_at(extended, after));
+        }
+    }
+
     #[test]
     fn test_update_with_unknown() {
         // Test update_with with unknown MipComponent (can happen if a node software is outdated)
@@ -2546,6 +2594,8 @@ mod test {
             max_versioning_elements_size: 100_000,
             thread_count: THREAD_COUNT,
             max_ledger_backups: 10,
+            disk_soft_quota: None,
+            disk_hard_quota: None,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -26,4 +26,6 @@ pub enum WalletError {
     MassaCipherError(#[from] massa_cipher::CipherError),
     /// Version error: {0}
     VersionError(String),
+    /// Key share error: {0}
+    KeyShareError(String),
 }
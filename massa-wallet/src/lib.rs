@@ -22,6 +22,7 @@ use std::str::FromStr;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 mod error;
+pub mod shamir;
 
 const WALLET_VERSION: u64 = 1;
 
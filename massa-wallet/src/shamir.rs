@@ -0,0 +1,265 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Shamir secret sharing of keypairs, used to back up staking keys.
+//!
+//! A keypair is split into `share_count` shares so that any `threshold` of them
+//! rebuild it, while fewer shares reveal nothing about it.
+//! Sharing is done byte by byte over GF(256).
+
+use crate::WalletError;
+use massa_signature::KeyPair;
+use rand::{rngs::OsRng, RngCore};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Prefix of the string representation of a key share
+pub const KEY_SHARE_PREFIX: &str = "KS";
+
+/// Share of a keypair
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct KeyShare {
+    /// number of shares needed to rebuild the keypair
+    threshold: u8,
+    /// index of the share, in `1..=255`
+    index: u8,
+    /// shared bytes of the keypair
+    data: Vec<u8>,
+}
+
+impl KeyShare {
+    /// Number of shares needed to rebuild the keypair
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Index of the share
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+}
+
+impl Display for KeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = Vec::with_capacity(self.data.len() + 2);
+        bytes.push(self.threshold);
+        bytes.push(self.index);
+        bytes.extend_from_slice(&self.data);
+        let encoded = bs58::encode(&bytes).with_check().into_string();
+        bytes.zeroize();
+        write!(f, "{}{}", KEY_SHARE_PREFIX, encoded)
+    }
+}
+
+impl fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KeyShare {}/{}", self.index, self.threshold)
+    }
+}
+
+impl FromStr for KeyShare {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s
+            .strip_prefix(KEY_SHARE_PREFIX)
+            .ok_or_else(|| WalletError::KeyShareError(format!("missing prefix in {}", s)))?;
+        let mut bytes = bs58::decode(encoded)
+            .with_check(None)
+            .into_vec()
+            .map_err(|err| WalletError::KeyShareError(err.to_string()))?;
+        if bytes.len() < 3 {
+            bytes.zeroize();
+            return Err(WalletError::KeyShareError("share is too short".to_string()));
+        }
+        let share = KeyShare {
+            threshold: bytes[0],
+            index: bytes[1],
+            data: bytes[2..].to_vec(),
+        };
+        bytes.zeroize();
+        if share.threshold == 0 || share.index == 0 {
+            return Err(WalletError::KeyShareError(
+                "share has a null index or threshold".to_string(),
+            ));
+        }
+        Ok(share)
+    }
+}
+
+/// Split `keypair` into `share_count` shares, any `threshold` of which rebuild it
+pub fn split_keypair(
+    keypair: &KeyPair,
+    threshold: u8,
+    share_count: u8,
+) -> Result<Vec<KeyShare>, WalletError> {
+    if threshold == 0 || threshold > share_count {
+        return Err(WalletError::KeyShareError(format!(
+            "threshold must be between 1 and the number of shares ({}), got {}",
+            share_count, threshold
+        )));
+    }
+    let mut secret = keypair.to_bytes();
+    let mut shares: Vec<KeyShare> = (1..=share_count)
+        .map(|index| KeyShare {
+            threshold,
+            index,
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+    // random polynomial of degree `threshold - 1` for each byte, whose constant term is the byte
+    let mut coefficients = vec![0u8; threshold as usize];
+    for byte in secret.iter() {
+        coefficients[0] = *byte;
+        OsRng.fill_bytes(&mut coefficients[1..]);
+        for share in shares.iter_mut() {
+            let value = coefficients
+                .iter()
+                .rev()
+                .fold(0, |acc, coef| gf_mul(acc, share.index) ^ coef);
+            share.data.push(value);
+        }
+    }
+    coefficients.zeroize();
+    secret.zeroize();
+    Ok(shares)
+}
+
+/// Rebuild a keypair from at least `threshold` of its shares
+pub fn combine_key_shares(shares: &[KeyShare]) -> Result<KeyPair, WalletError> {
+    let first = shares
+        .first()
+        .ok_or_else(|| WalletError::KeyShareError("no share provided".to_string()))?;
+    let threshold = first.threshold as usize;
+    for (i, share) in shares.iter().enumerate() {
+        if share.threshold != first.threshold || share.data.len() != first.data.len() {
+            return Err(WalletError::KeyShareError(
+                "shares do not belong to the same key".to_string(),
+            ));
+        }
+        if shares[..i].iter().any(|other| other.index == share.index) {
+            return Err(WalletError::KeyShareError(format!(
+                "share {} is provided twice",
+                share.index
+            )));
+        }
+    }
+    if shares.len() < threshold {
+        return Err(WalletError::KeyShareError(format!(
+            "{} shares are needed, got {}",
+            threshold,
+            shares.len()
+        )));
+    }
+    let shares = &shares[..threshold];
+
+    // Lagrange interpolation at 0
+    let weights: Vec<u8> = shares
+        .iter()
+        .map(|share| {
+            shares
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |acc, other| {
+                    gf_mul(acc, gf_mul(other.index, gf_inv(other.index ^ share.index)))
+                })
+        })
+        .collect();
+    let mut secret: Vec<u8> = (0..first.data.len())
+        .map(|pos| {
+            shares
+                .iter()
+                .zip(weights.iter())
+                .fold(0, |acc, (share, weight)| {
+                    acc ^ gf_mul(share.data[pos], *weight)
+                })
+        })
+        .collect();
+    let keypair = KeyPair::from_bytes(&secret);
+    secret.zeroize();
+    Ok(keypair?)
+}
+
+/// Multiplication in GF(256), modulo x^8 + x^4 + x^3 + x + 1
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Inverse in GF(256) of a non-null element: `a^254`
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut power = a;
+    let mut exponent = 254u8;
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            result = gf_mul(result, power);
+        }
+        power = gf_mul(power, power);
+        exponent >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_split_and_combine_key_shares() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let shares = split_keypair(&keypair, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        // any 3 shares rebuild the keypair, whatever their order
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset: Vec<KeyShare> = subset.iter().map(|i| shares[*i].clone()).collect();
+            let rebuilt = combine_key_shares(&subset).unwrap();
+            assert_eq!(rebuilt.to_string(), keypair.to_string());
+        }
+
+        // too few shares or duplicated shares are rejected
+        assert!(combine_key_shares(&shares[..2]).is_err());
+        assert!(
+            combine_key_shares(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err()
+        );
+        assert!(split_keypair(&keypair, 6, 5).is_err());
+        assert!(split_keypair(&keypair, 0, 5).is_err());
+    }
+
+    #[test]
+    fn test_key_share_string_roundtrip() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let shares: Vec<KeyShare> = split_keypair(&keypair, 2, 3)
+            .unwrap()
+            .iter()
+            .map(|share| share.to_string().parse().unwrap())
+            .collect();
+        assert_eq!(shares[2].index(), 3);
+        assert_eq!(shares[2].threshold(), 2);
+        let rebuilt = combine_key_shares(&shares[1..]).unwrap();
+        assert_eq!(rebuilt.to_string(), keypair.to_string());
+
+        let mut corrupted = shares[0].to_string();
+        corrupted.pop();
+        assert!(corrupted.parse::<KeyShare>().is_err());
+    }
+}